@@ -0,0 +1,88 @@
+//! Numeric Accessor Benchmark
+//!
+//! 約定報告 (35=8) を大量に読む処理を想定し、数値フィールドの読み取りを
+//! 比較する。値を `&str` で取り出して `str::parse` する方法と、バイト列から
+//! 直接パースする `FixMessage` のアクセサ (`get_u64` / `get_decimal`)。
+//!
+//! `cargo bench --bench accessors`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use alice_fix::{parser, tag, BeginString, FixBuilder, FixMessage};
+
+/// 計測するメッセージ数。
+const MESSAGES: usize = 1_000;
+/// 全メッセージを読む回数。
+const ROUNDS: usize = 200;
+
+fn exec_report(i: usize) -> FixMessage {
+    let raw = FixBuilder::new(BeginString::Fix44, "8")
+        .field(tag::SENDER_COMP_ID, "BROKER")
+        .field(tag::TARGET_COMP_ID, "ALICE")
+        .field_u64(tag::MSG_SEQ_NUM, i as u64 + 1)
+        .field(tag::SENDING_TIME, "20260315-13:30:00.130")
+        .field(tag::ORDER_ID, "EX-778")
+        .field(tag::EXEC_ID, "EXEC-1")
+        .field(tag::EXEC_TYPE, "F")
+        .field(tag::ORD_STATUS, "1")
+        .field(tag::SYMBOL, "AAPL")
+        .field(tag::SIDE, "1")
+        .field_u64(tag::ORDER_QTY, 100)
+        .field_u64(tag::LAST_QTY, 40)
+        .field(tag::LAST_PX, "187.25")
+        .field_u64(tag::LEAVES_QTY, 60)
+        .field_u64(tag::CUM_QTY, 40)
+        .field(tag::AVG_PX, "187.25")
+        .field(tag::TRANSACT_TIME, "20260315-13:30:00.129")
+        .build();
+    parser::parse(&raw).expect("valid exec report")
+}
+
+/// `f` を `ROUNDS` 回実行し、1 メッセージあたりの ns を表示。
+fn bench(name: &str, msgs: &[FixMessage], f: impl Fn(&FixMessage) -> u64) {
+    let start = Instant::now();
+    let mut acc = 0u64;
+    for _ in 0..ROUNDS {
+        for msg in msgs {
+            acc = acc.wrapping_add(f(black_box(msg)));
+        }
+    }
+    let count = u32::try_from(ROUNDS * msgs.len()).expect("message count fits in u32");
+    let per_msg = start.elapsed().as_secs_f64() * 1e9 / f64::from(count);
+    println!("{name:<24} {per_msg:>8.1} ns/msg  (checksum {acc})");
+}
+
+// 価格は比較用のチェックサムに混ぜるだけなので切り捨てでよい。
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn main() {
+    let msgs: Vec<_> = (0..MESSAGES).map(exec_report).collect();
+
+    bench("str::parse (baseline)", &msgs, |m| {
+        let qty: u64 = ([tag::ORDER_QTY, tag::LAST_QTY, tag::LEAVES_QTY, tag::CUM_QTY])
+            .iter()
+            .filter_map(|&t| m.get(t)?.parse::<u64>().ok())
+            .sum();
+        let seq: u64 = m
+            .get(tag::MSG_SEQ_NUM)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let px = m
+            .get(tag::LAST_PX)
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        qty + seq + px as u64
+    });
+
+    bench("byte accessors", &msgs, |m| {
+        let qty: u64 = ([tag::ORDER_QTY, tag::LAST_QTY, tag::LEAVES_QTY, tag::CUM_QTY])
+            .iter()
+            .filter_map(|&t| m.get_u64(t))
+            .sum();
+        let seq = m.get_u64(tag::MSG_SEQ_NUM).unwrap_or(0);
+        let px = m
+            .get_decimal(tag::LAST_PX)
+            .map_or(0, |d| d.mantissa() as u64 / 100);
+        qty + seq + px
+    });
+}
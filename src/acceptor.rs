@@ -0,0 +1,476 @@
+//! Multi-Tenant Acceptor
+//!
+//! 1 つの待ち受けエンドポイントで複数の相手方を受け付ける。相手方の
+//! `SenderCompID` ごとに [`CounterpartyConfig`] (ハートビート間隔・
+//! `BeginString`・受信ポリシー・送信スロットル・ストア配置) を登録し、
+//! Logon 受信時に選択して [`FixSession`] を組み立てる。
+//!
+//! [`Acceptor::on_logon`] は Logon (35=A) を検証し、設定済みのセッションに
+//! [`FixSession::on_message`] で処理させた結果 (返信 Logon・欠番の
+//! `ResendRequest`) と開いたストアを [`AcceptedSession`] として返す。
+//! `HeartBtInt` は相手方の値を返し、`ResetSeqNumFlag` と欠番は通常の
+//! セッション処理どおりに扱う。ストア配置があればシーケンス番号を
+//! [`FileSeqNumStore`] から再開する。取引所側 FIX ゲートウェイの構成要素。
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::clock::{Clock, SystemClock};
+use crate::firewall::InboundPolicy;
+use crate::message::{FieldError, FixMessage};
+use crate::parser;
+use crate::session::{FixSession, SessionAction, SessionRole, SessionState};
+use crate::store::{FileSeqNumStore, FileStore, NoCompression};
+use crate::tag;
+use crate::throttle::Throttle;
+use crate::version::{BeginString, VersionError};
+
+/// 相手方ごとの設定。
+#[derive(Debug, Clone)]
+pub struct CounterpartyConfig {
+    heartbeat_secs: u32,
+    begin_string: Option<String>,
+    inbound_policy: InboundPolicy,
+    throttle: Throttle,
+    store_dir: Option<PathBuf>,
+}
+
+impl CounterpartyConfig {
+    /// ハートビート間隔 (秒) を指定して作成。その他は無制限・ストアなし。
+    /// Logon 後は相手方の `HeartBtInt` に従う。
+    #[must_use]
+    pub fn new(heartbeat_secs: u32) -> Self {
+        Self {
+            heartbeat_secs,
+            begin_string: None,
+            inbound_policy: InboundPolicy::default(),
+            throttle: Throttle::default(),
+            store_dir: None,
+        }
+    }
+
+    /// 受け付ける `BeginString` を限定。
+    #[must_use]
+    pub fn begin_string(mut self, begin_string: &str) -> Self {
+        self.begin_string = Some(begin_string.to_string());
+        self
+    }
+
+    /// セッションに設定する受信ポリシー (許可 `MsgType` 等)。
+    #[must_use]
+    pub fn inbound_policy(mut self, policy: InboundPolicy) -> Self {
+        self.inbound_policy = policy;
+        self
+    }
+
+    /// セッションに設定する送信スロットル。
+    #[must_use]
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// メッセージストアとシーケンス番号を置くディレクトリ
+    /// ([`FileStore::for_session`]・[`FileSeqNumStore::for_session`])。
+    #[must_use]
+    pub fn store_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.store_dir = Some(dir.into());
+        self
+    }
+
+    /// ハートビート間隔 (秒)。
+    #[must_use]
+    pub const fn heartbeat_secs(&self) -> u32 {
+        self.heartbeat_secs
+    }
+}
+
+/// Logon を受け付けられなかった理由。
+#[derive(Debug)]
+pub enum AcceptError {
+    /// Logon (35=A) ではない。
+    NotLogon(String),
+    /// 必須フィールドの欠落・不正。
+    Field(FieldError),
+    /// `TargetCompID` が自エンドポイントの `CompID` と異なる。
+    WrongTarget(String),
+    /// `SenderCompID` が登録されていない。
+    UnknownCompId(String),
+    /// `BeginString` が設定と異なる。
+    BeginStringMismatch {
+        /// 設定値。
+        expected: String,
+        /// 受信値。
+        actual: String,
+    },
+    /// `BeginString` が未対応、または `FIXT.1.1` で `DefaultApplVerID`
+    /// (1137) が無い・未対応。
+    Version(VersionError),
+    /// ストアを開けない・読み書きできない。
+    Store(io::Error),
+    /// セッションが Logon を拒否した (`MsgSeqNum` が小さすぎる、
+    /// `HeartBtInt` の欠落など)。
+    LogonRefused {
+        /// 拒否の理由 (返信の `Text`)。
+        text: String,
+        /// 返信するフレーム (Logout、`SendingTime` が不正なら Reject)。
+        response: Vec<u8>,
+    },
+}
+
+impl core::fmt::Display for AcceptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotLogon(msg_type) => write!(f, "expected Logon, got MsgType {msg_type}"),
+            Self::Field(err) => write!(f, "{err}"),
+            Self::WrongTarget(target) => write!(f, "TargetCompID {target} is not served here"),
+            Self::UnknownCompId(sender) => write!(f, "unknown SenderCompID {sender}"),
+            Self::BeginStringMismatch { expected, actual } => {
+                write!(f, "BeginString {actual} does not match {expected}")
+            }
+            Self::Version(err) => write!(f, "{err}"),
+            Self::Store(err) => write!(f, "message store failed: {err}"),
+            Self::LogonRefused { text, .. } => write!(f, "Logon refused: {text}"),
+        }
+    }
+}
+
+impl std::error::Error for AcceptError {}
+
+impl From<FieldError> for AcceptError {
+    fn from(err: FieldError) -> Self {
+        Self::Field(err)
+    }
+}
+
+impl From<VersionError> for AcceptError {
+    fn from(err: VersionError) -> Self {
+        Self::Version(err)
+    }
+}
+
+/// 受け付けたセッション。
+pub struct AcceptedSession {
+    /// Logon を処理済みの [`SessionState::Active`] のセッション。
+    pub session: FixSession,
+    /// `store_dir` が設定されていれば開いたストア。
+    pub store: Option<FileStore>,
+    /// `store_dir` が設定されていればシーケンス番号の保存先 (Logon 処理後の
+    /// 値を保存済み)。
+    pub seq_nums: Option<FileSeqNumStore>,
+    /// Logon に対するセッションのアクション: 返信 Logon と、欠番があれば
+    /// ResendRequest。送信フレームはまだストアに記録していない。
+    pub actions: Vec<SessionAction>,
+}
+
+/// 複数相手方の Logon を受け付けるアクセプタ。
+pub struct Acceptor {
+    comp_id: String,
+    counterparties: HashMap<String, CounterpartyConfig>,
+    clock: Arc<dyn Clock>,
+}
+
+impl core::fmt::Debug for Acceptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Acceptor")
+            .field("comp_id", &self.comp_id)
+            .field("counterparties", &self.counterparties)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Acceptor {
+    /// 自エンドポイントの `CompID` を指定して作成。
+    #[must_use]
+    pub fn new(comp_id: &str) -> Self {
+        Self {
+            comp_id: comp_id.to_string(),
+            counterparties: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 相手方 `comp_id` の設定を登録 (既存は置き換え)。
+    #[must_use]
+    pub fn counterparty(mut self, comp_id: &str, config: CounterpartyConfig) -> Self {
+        self.counterparties.insert(comp_id.to_string(), config);
+        self
+    }
+
+    /// `SendingTime` の時計を差し替え。
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 相手方 `comp_id` の設定。
+    #[must_use]
+    pub fn config(&self, comp_id: &str) -> Option<&CounterpartyConfig> {
+        self.counterparties.get(comp_id)
+    }
+
+    /// 受信した Logon を検証し、相手方の設定でセッションを組み立てる。
+    ///
+    /// # Errors
+    ///
+    /// Logon でない、`TargetCompID` 不一致、未登録の `SenderCompID`、
+    /// `BeginString` 不一致、`MsgSeqNum` 欠落、未対応の `BeginString` /
+    /// `DefaultApplVerID`、ストアを開けない場合、
+    /// セッションが Logon を拒否した場合 ([`AcceptError::LogonRefused`])。
+    pub fn on_logon(&self, logon: &FixMessage) -> Result<AcceptedSession, AcceptError> {
+        if logon.msg_type != "A" {
+            return Err(AcceptError::NotLogon(logon.msg_type.clone()));
+        }
+        let target = logon.require(tag::TARGET_COMP_ID)?;
+        if target != self.comp_id {
+            return Err(AcceptError::WrongTarget(target.to_string()));
+        }
+        let sender = logon.require(tag::SENDER_COMP_ID)?;
+        let config = self
+            .counterparties
+            .get(sender)
+            .ok_or_else(|| AcceptError::UnknownCompId(sender.to_string()))?;
+        if let Some(expected) = &config.begin_string {
+            if *expected != logon.begin_string {
+                return Err(AcceptError::BeginStringMismatch {
+                    expected: expected.clone(),
+                    actual: logon.begin_string.clone(),
+                });
+            }
+        }
+        logon.require_u64(tag::MSG_SEQ_NUM)?;
+        let begin_string =
+            BeginString::from_parts(&logon.begin_string, logon.get(tag::DEFAULT_APPL_VER_ID))?;
+
+        let mut session = FixSession::new(&self.comp_id, sender, begin_string);
+        session.set_role(SessionRole::Acceptor);
+        session.set_heart_bt_int(config.heartbeat_secs);
+        session.set_inbound_policy(config.inbound_policy.clone());
+        session.set_throttle(config.throttle.clone());
+        session.set_clock(Arc::clone(&self.clock));
+
+        let id = session.session_id();
+        let (mut store, mut seq_nums) = match &config.store_dir {
+            Some(dir) => (
+                Some(
+                    FileStore::for_session(dir, &id, Box::new(NoCompression))
+                        .map_err(AcceptError::Store)?,
+                ),
+                Some(FileSeqNumStore::for_session(dir, &id)),
+            ),
+            None => (None, None),
+        };
+        if let Some(seq_nums) = &mut seq_nums {
+            session.restore(seq_nums).map_err(AcceptError::Store)?;
+        }
+        let actions = session.on_message(logon);
+        if let Some(seq_nums) = &mut seq_nums {
+            session
+                .save_seq_nums(seq_nums)
+                .map_err(AcceptError::Store)?;
+        }
+
+        if *session.state() != SessionState::Active {
+            let response = actions
+                .into_iter()
+                .find_map(|action| match action {
+                    SessionAction::Send(frame) => Some(frame),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            if let Some(store) = store.as_mut().filter(|_| !response.is_empty()) {
+                session
+                    .store_sent(store, &response)
+                    .map_err(AcceptError::Store)?;
+            }
+            let text = parser::parse(&response)
+                .ok()
+                .and_then(|msg| msg.get(tag::TEXT).map(str::to_string))
+                .unwrap_or_default();
+            return Err(AcceptError::LogonRefused { text, response });
+        }
+        Ok(AcceptedSession {
+            session,
+            store,
+            seq_nums,
+            actions,
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::throttle::RateLimit;
+
+    fn logon(sender: &str, target: &str, begin: BeginString) -> FixMessage {
+        logon_seq(sender, target, begin, 1)
+    }
+
+    fn logon_seq(sender: &str, target: &str, begin: BeginString, seq: u64) -> FixMessage {
+        let raw = FixBuilder::new(begin, "A")
+            .field(tag::SENDER_COMP_ID, sender)
+            .field(tag::TARGET_COMP_ID, target)
+            .field_u64(tag::MSG_SEQ_NUM, seq)
+            .field(tag::ENCRYPT_METHOD, "0")
+            .field(tag::HEART_BT_INT, "30")
+            .build();
+        parser::parse(&raw).unwrap()
+    }
+
+    fn sent(actions: &[SessionAction]) -> Vec<FixMessage> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                SessionAction::Send(frame) => Some(parser::parse(frame).unwrap()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn gateway() -> Acceptor {
+        Acceptor::new("EXCH")
+            .counterparty("FIRM_A", CounterpartyConfig::new(30))
+            .counterparty(
+                "FIRM_B",
+                CounterpartyConfig::new(10)
+                    .begin_string("FIX.4.4")
+                    .throttle(Throttle::new().global_limit(RateLimit::new(1, 1), 0)),
+            )
+    }
+
+    #[test]
+    fn selects_config_per_comp_id() {
+        let gw = gateway();
+        let a = gw
+            .on_logon(&logon("FIRM_A", "EXCH", BeginString::Fix44))
+            .unwrap();
+        let b = gw
+            .on_logon(&logon("FIRM_B", "EXCH", BeginString::Fix44))
+            .unwrap();
+
+        let resp = &sent(&a.actions)[0];
+        assert_eq!(resp.msg_type, "A");
+        assert_eq!(resp.get(tag::TARGET_COMP_ID), Some("FIRM_A"));
+        assert_eq!(resp.get(tag::HEART_BT_INT), Some("30"));
+        assert!(resp.contains(tag::SENDING_TIME));
+        // The counterparty's HeartBtInt is echoed, not the configured one.
+        assert_eq!(sent(&b.actions)[0].get(tag::HEART_BT_INT), Some("30"));
+        assert_eq!(b.session.heart_bt_int(), 30);
+
+        assert_eq!(*a.session.state(), SessionState::Active);
+        assert_eq!(a.session.role(), SessionRole::Acceptor);
+        assert_eq!(a.session.snapshot().incoming_seq, 2);
+        assert_eq!(a.session.snapshot().outgoing_seq, 2);
+        let mut b_session = b.session;
+        assert!(b_session.throttle_mut().check("D", 0).is_send());
+        assert!(!b_session.throttle_mut().check("D", 0).is_send());
+        assert!(a.store.is_none());
+        assert!(a.seq_nums.is_none());
+        // The session runs its heartbeat timer from the Logon.
+        assert!(a.session.time_until_next_heartbeat().is_some());
+    }
+
+    #[test]
+    fn logon_goes_through_session_sequence_handling() {
+        let gw = gateway();
+        let gap = gw
+            .on_logon(&logon_seq("FIRM_A", "EXCH", BeginString::Fix44, 4))
+            .unwrap();
+        let frames = sent(&gap.actions);
+        assert_eq!(frames[1].msg_type, "2");
+        assert_eq!(frames[1].get_u64(tag::BEGIN_SEQ_NO), Some(1));
+        assert_eq!(frames[1].get_u64(tag::END_SEQ_NO), Some(3));
+
+        let mut reset = logon("FIRM_A", "EXCH", BeginString::Fix44);
+        reset.set(tag::RESET_SEQ_NUM_FLAG, "Y");
+        let reset = gw.on_logon(&reset).unwrap();
+        assert_eq!(
+            sent(&reset.actions)[0].get(tag::RESET_SEQ_NUM_FLAG),
+            Some("Y")
+        );
+
+        let mut bad = logon("FIRM_A", "EXCH", BeginString::Fix44);
+        bad.set(tag::HEART_BT_INT, "0");
+        match gw.on_logon(&bad) {
+            Err(AcceptError::LogonRefused { text, response }) => {
+                assert_eq!(text, "HeartBtInt missing or invalid");
+                assert_eq!(parser::parse(&response).unwrap().msg_type, "5");
+            }
+            other => panic!("expected LogonRefused, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_or_mismatched_logons() {
+        let gw = gateway();
+        assert!(matches!(
+            gw.on_logon(&logon("FIRM_X", "EXCH", BeginString::Fix44)),
+            Err(AcceptError::UnknownCompId(s)) if s == "FIRM_X"
+        ));
+        assert!(matches!(
+            gw.on_logon(&logon("FIRM_A", "OTHER", BeginString::Fix44)),
+            Err(AcceptError::WrongTarget(_))
+        ));
+        assert!(matches!(
+            gw.on_logon(&logon("FIRM_B", "EXCH", BeginString::Fix42)),
+            Err(AcceptError::BeginStringMismatch { .. })
+        ));
+        let mut hb = logon("FIRM_A", "EXCH", BeginString::Fix44);
+        hb.msg_type = "0".into();
+        assert!(matches!(gw.on_logon(&hb), Err(AcceptError::NotLogon(_))));
+        let fixt = BeginString::Fixt11 {
+            appl_ver: crate::version::FixVersion::Fix44,
+        };
+        assert!(matches!(
+            gw.on_logon(&logon("FIRM_A", "EXCH", fixt)),
+            Err(AcceptError::Version(VersionError::MissingField(
+                tag::DEFAULT_APPL_VER_ID
+            )))
+        ));
+        let mut fixt_logon = logon("FIRM_A", "EXCH", fixt);
+        fixt_logon.set(tag::DEFAULT_APPL_VER_ID, "6");
+        let accepted = gw.on_logon(&fixt_logon).unwrap();
+        let reply = sent(&accepted.actions);
+        assert_eq!(reply[0].get(tag::DEFAULT_APPL_VER_ID), Some("6"));
+    }
+
+    #[test]
+    fn opens_store_per_session() {
+        let dir = std::env::temp_dir().join(format!("alice-fix-acceptor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let gw = Acceptor::new("EXCH")
+            .counterparty("FIRM_A", CounterpartyConfig::new(30).store_dir(&dir));
+        let accepted = gw
+            .on_logon(&logon("FIRM_A", "EXCH", BeginString::Fix44))
+            .unwrap();
+        let store = accepted.store.unwrap();
+        assert_eq!(
+            store.path(),
+            FileStore::session_path(&dir, &accepted.session.session_id())
+        );
+        drop(store);
+
+        // The next connection resumes both sequences from the store.
+        let again = gw
+            .on_logon(&logon_seq("FIRM_A", "EXCH", BeginString::Fix44, 2))
+            .unwrap();
+        assert_eq!(sent(&again.actions)[0].get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(again.session.snapshot().incoming_seq, 3);
+        drop(again);
+        assert!(matches!(
+            gw.on_logon(&logon_seq("FIRM_A", "EXCH", BeginString::Fix44, 1)),
+            Err(AcceptError::LogonRefused { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
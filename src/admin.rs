@@ -1,9 +1,17 @@
 //! Administrative message builders
 //!
-//! Logon, Heartbeat, Logout, `TestRequest`, `ResendRequest`。
+//! Logon, Heartbeat, Logout, `TestRequest`, `ResendRequest`, Reject。
+//!
+//! [`Reject::from_validation`] は [`ValidationError`] と拒否対象メッセージ
+//! から `RefSeqNum` / `RefTagID` / `RefMsgType` / `SessionRejectReason` /
+//! `Text` を埋める。
 
 use crate::builder::FixBuilder;
+use crate::message::FixMessage;
 use crate::tag;
+use crate::tag::{BEGIN_SEQ_NO, ENCRYPT_METHOD, END_SEQ_NO, HEART_BT_INT, TEST_REQ_ID};
+use crate::validation::ValidationError;
+use crate::version::BeginString;
 
 /// Administrative メッセージ種別。
 pub mod msg_type {
@@ -23,27 +31,25 @@ pub mod msg_type {
     pub const LOGON: &str = "A";
 }
 
-/// Logon タグ。
-const ENCRYPT_METHOD: u32 = 98;
-/// Heartbeat interval タグ。
-const HEART_BT_INT: u32 = 108;
-/// Test request ID タグ。
-const TEST_REQ_ID: u32 = 112;
-/// Begin sequence number タグ。
-const BEGIN_SEQ_NO: u32 = 7;
-/// End sequence number タグ。
-const END_SEQ_NO: u32 = 16;
+/// 管理メッセージの `MsgType` (35=0/1/2/3/4/5/A) か。
+#[must_use]
+pub fn is_admin_msg_type(msg_type: &str) -> bool {
+    matches!(msg_type, "0" | "1" | "2" | "3" | "4" | "5" | "A")
+}
 
 /// Logon メッセージを構築。
+///
+/// `FIXT.1.1` では `DefaultApplVerID` (1137) も付ける。
 #[must_use]
 pub fn build_logon(
-    begin_string: &str,
+    begin_string: impl Into<BeginString>,
     sender: &str,
     target: &str,
     seq_num: u64,
     sending_time: &str,
     heartbeat_interval: u32,
 ) -> Vec<u8> {
+    let begin_string = begin_string.into();
     let mut b = FixBuilder::new(begin_string, msg_type::LOGON);
     b.field(tag::SENDER_COMP_ID, sender);
     b.field(tag::TARGET_COMP_ID, target);
@@ -51,13 +57,16 @@ pub fn build_logon(
     b.field(tag::SENDING_TIME, sending_time);
     b.field(ENCRYPT_METHOD, "0");
     b.field(HEART_BT_INT, &heartbeat_interval.to_string());
+    if let Some(id) = begin_string.appl_ver_id() {
+        b.field(tag::DEFAULT_APPL_VER_ID, id);
+    }
     b.build()
 }
 
 /// Heartbeat メッセージを構築。
 #[must_use]
 pub fn build_heartbeat(
-    begin_string: &str,
+    begin_string: impl Into<BeginString>,
     sender: &str,
     target: &str,
     seq_num: u64,
@@ -78,7 +87,7 @@ pub fn build_heartbeat(
 /// Logout メッセージを構築。
 #[must_use]
 pub fn build_logout(
-    begin_string: &str,
+    begin_string: impl Into<BeginString>,
     sender: &str,
     target: &str,
     seq_num: u64,
@@ -99,7 +108,7 @@ pub fn build_logout(
 /// Test Request メッセージを構築。
 #[must_use]
 pub fn build_test_request(
-    begin_string: &str,
+    begin_string: impl Into<BeginString>,
     sender: &str,
     target: &str,
     seq_num: u64,
@@ -118,7 +127,7 @@ pub fn build_test_request(
 /// Resend Request メッセージを構築。
 #[must_use]
 pub fn build_resend_request(
-    begin_string: &str,
+    begin_string: impl Into<BeginString>,
     sender: &str,
     target: &str,
     seq_num: u64,
@@ -136,6 +145,132 @@ pub fn build_resend_request(
     b.build()
 }
 
+/// `SessionRejectReason` (tag 373)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionRejectReason {
+    /// 0: 不正なタグ番号。
+    InvalidTagNumber,
+    /// 1: 必須タグ欠落。
+    RequiredTagMissing,
+    /// 2: この `MsgType` で未定義のタグ。
+    TagNotDefinedForMsgType,
+    /// 3: 未定義のタグ。
+    UndefinedTag,
+    /// 4: 値の無いタグ。
+    TagWithoutValue,
+    /// 5: 値が範囲外。
+    ValueOutOfRange,
+    /// 6: 値の形式が不正。
+    IncorrectDataFormat,
+    /// 7: 復号エラー。
+    DecryptionProblem,
+    /// 8: 署名エラー。
+    SignatureProblem,
+    /// 9: `CompID` の不一致。
+    CompIdProblem,
+    /// 10: `SendingTime` の精度エラー。
+    SendingTimeAccuracy,
+    /// 11: 不正な `MsgType`。
+    InvalidMsgType,
+    /// 13: タグの重複。
+    TagAppearsMoreThanOnce,
+    /// 14: タグの順序が不正。
+    TagOutOfOrder,
+    /// 15: 繰り返しグループのフィールド順序が不正。
+    GroupFieldsOutOfOrder,
+    /// 16: `NumInGroup` と件数の不一致。
+    IncorrectNumInGroupCount,
+    /// 99: その他。
+    Other,
+}
+
+impl SessionRejectReason {
+    /// タグ 373 の値。
+    #[must_use]
+    pub const fn code(self) -> u32 {
+        match self {
+            Self::InvalidTagNumber => 0,
+            Self::RequiredTagMissing => 1,
+            Self::TagNotDefinedForMsgType => 2,
+            Self::UndefinedTag => 3,
+            Self::TagWithoutValue => 4,
+            Self::ValueOutOfRange => 5,
+            Self::IncorrectDataFormat => 6,
+            Self::DecryptionProblem => 7,
+            Self::SignatureProblem => 8,
+            Self::CompIdProblem => 9,
+            Self::SendingTimeAccuracy => 10,
+            Self::InvalidMsgType => 11,
+            Self::TagAppearsMoreThanOnce => 13,
+            Self::TagOutOfOrder => 14,
+            Self::GroupFieldsOutOfOrder => 15,
+            Self::IncorrectNumInGroupCount => 16,
+            Self::Other => 99,
+        }
+    }
+}
+
+/// セッションレベル Reject (35=3) の内容。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reject {
+    /// 拒否対象の `MsgSeqNum` (`RefSeqNum`)。
+    pub ref_seq_num: u64,
+    /// 問題のタグ (`RefTagID`)。
+    pub ref_tag_id: Option<u32>,
+    /// 拒否対象の `MsgType` (`RefMsgType`)。
+    pub ref_msg_type: Option<String>,
+    /// 拒否理由 (`SessionRejectReason`)。
+    pub reason: SessionRejectReason,
+    /// 説明文 (`Text`)。
+    pub text: Option<String>,
+}
+
+impl Reject {
+    /// 検証エラーと拒否対象メッセージから Reject を作成。
+    ///
+    /// `RefSeqNum` は `ref_msg` の `MsgSeqNum` (読めなければ 0)、`Text` は
+    /// エラーの表示文字列。
+    #[must_use]
+    pub fn from_validation(err: &ValidationError, ref_msg: &FixMessage) -> Self {
+        Self {
+            ref_seq_num: ref_msg.get_u64(tag::MSG_SEQ_NUM).unwrap_or(0),
+            ref_tag_id: err.tag(),
+            ref_msg_type: (!ref_msg.msg_type.is_empty()).then(|| ref_msg.msg_type.clone()),
+            reason: err.reason(),
+            text: Some(err.to_string()),
+        }
+    }
+
+    /// Reject メッセージを構築。
+    #[must_use]
+    pub fn build(
+        &self,
+        begin_string: impl Into<BeginString>,
+        sender: &str,
+        target: &str,
+        seq_num: u64,
+        sending_time: &str,
+    ) -> Vec<u8> {
+        let mut b = FixBuilder::new(begin_string, msg_type::REJECT);
+        b.field(tag::SENDER_COMP_ID, sender);
+        b.field(tag::TARGET_COMP_ID, target);
+        b.field(tag::MSG_SEQ_NUM, &seq_num.to_string());
+        b.field(tag::SENDING_TIME, sending_time);
+        b.field_u64(tag::REF_SEQ_NUM, self.ref_seq_num);
+        if let Some(t) = self.ref_tag_id {
+            b.field_u64(tag::REF_TAG_ID, u64::from(t));
+        }
+        if let Some(mt) = &self.ref_msg_type {
+            b.field(tag::REF_MSG_TYPE, mt);
+        }
+        b.field_u64(tag::SESSION_REJECT_REASON, u64::from(self.reason.code()));
+        if let Some(t) = &self.text {
+            b.field(tag::TEXT, t);
+        }
+        b.build()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -144,8 +279,9 @@ pub fn build_resend_request(
 mod tests {
     use super::*;
     use crate::parser;
+    use crate::version::FixVersion;
 
-    const FIX44: &str = "FIX.4.4";
+    const FIX44: BeginString = BeginString::Fix44;
     const TIME: &str = "20260101-00:00:00";
 
     #[test]
@@ -230,9 +366,12 @@ mod tests {
 
     #[test]
     fn fix50_logon() {
-        let bytes = build_logon("FIXT.1.1", "ALICE", "BROKER", 1, TIME, 30);
+        let bytes = build_logon(FixVersion::Fix50, "ALICE", "BROKER", 1, TIME, 30);
         let msg = parser::parse(&bytes).unwrap();
         assert_eq!(msg.begin_string, "FIXT.1.1");
+        assert_eq!(msg.get(tag::DEFAULT_APPL_VER_ID), Some("7"));
+        let msg = parser::parse(&build_logon(FIX44, "A", "B", 1, TIME, 30)).unwrap();
+        assert!(!msg.contains(tag::DEFAULT_APPL_VER_ID));
     }
 
     #[test]
@@ -256,4 +395,29 @@ mod tests {
             assert!(parser::parse(bytes).is_ok());
         }
     }
+
+    #[test]
+    fn reject_from_validation_fills_references() {
+        let order = FixBuilder::new(FIX44, "D")
+            .field(tag::SENDER_COMP_ID, "BROKER")
+            .field(tag::TARGET_COMP_ID, "ALICE")
+            .field_u64(tag::MSG_SEQ_NUM, 42)
+            .build();
+        let order = parser::parse(&order).unwrap();
+        let err = crate::validation::validate(&order).unwrap_err();
+
+        let reject = Reject::from_validation(&err, &order);
+        assert_eq!(reject.reason, SessionRejectReason::RequiredTagMissing);
+        let bytes = reject.build(FIX44, "ALICE", "BROKER", 9, TIME);
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.msg_type, "3");
+        assert_eq!(msg.get_u64(tag::REF_SEQ_NUM), Some(42));
+        assert_eq!(
+            msg.get_u64(tag::REF_TAG_ID),
+            Some(u64::from(tag::SENDING_TIME))
+        );
+        assert_eq!(msg.get(tag::REF_MSG_TYPE), Some("D"));
+        assert_eq!(msg.get(tag::SESSION_REJECT_REASON), Some("1"));
+        assert_eq!(msg.get(tag::TEXT), Some("required tag missing: 52"));
+    }
 }
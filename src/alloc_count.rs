@@ -0,0 +1,188 @@
+//! Allocation Accounting
+//!
+//! ゼロコピー経路 (パース、`build_into`) の性能劣化を本番ではなくテストで
+//! 検出するための計測 (feature `alloc-count`)。[`CountingAllocator`] を
+//! テスト・ベンチのバイナリで `#[global_allocator]` に設定すると、
+//! ヒープ操作をスレッドごとに数える。[`measure`] は 1 回の呼び出しで
+//! 発生した回数を [`AllocStats`] で返す。
+//!
+//! ```rust
+//! use alice_fix::alloc_count::{measure, CountingAllocator};
+//! use alice_fix::{builder::FixBuilder, tag, BeginString};
+//!
+//! #[global_allocator]
+//! static ALLOC: CountingAllocator = CountingAllocator;
+//!
+//! fn main() {
+//!     let mut b = FixBuilder::new(BeginString::Fix44, "0");
+//!     b.field(tag::SENDER_COMP_ID, "ALICE");
+//!     let mut buf = Vec::with_capacity(128);
+//!     let ((), stats) = measure(|| b.build_into(&mut buf));
+//!     assert_eq!(stats.total(), 0);
+//! }
+//! ```
+//!
+//! カウンタはスレッドローカルなので、並列に走る他のテストの割り当ては
+//! 混ざらない。アロケータを設定していなければ常に 0。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// ヒープ操作の回数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocStats {
+    /// `alloc` / `alloc_zeroed` の回数。
+    pub allocations: u64,
+    /// `realloc` の回数。
+    pub reallocations: u64,
+    /// `dealloc` の回数。
+    pub deallocations: u64,
+    /// 確保したバイト数 (`realloc` は増加分)。
+    pub bytes: u64,
+}
+
+impl AllocStats {
+    /// `self` から `earlier` を引いた差分。
+    #[must_use]
+    pub const fn since(&self, earlier: &Self) -> Self {
+        Self {
+            allocations: self.allocations - earlier.allocations,
+            reallocations: self.reallocations - earlier.reallocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            bytes: self.bytes - earlier.bytes,
+        }
+    }
+
+    /// 新規確保と再確保の合計。
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.allocations + self.reallocations
+    }
+}
+
+thread_local! {
+    static STATS: Cell<AllocStats> = const {
+        Cell::new(AllocStats {
+            allocations: 0,
+            reallocations: 0,
+            deallocations: 0,
+            bytes: 0,
+        })
+    };
+}
+
+/// 現スレッドのカウンタを更新 (スレッド終了処理中は無視)。
+fn bump(f: impl FnOnce(&mut AllocStats)) {
+    let _ = STATS.try_with(|cell| {
+        let mut stats = cell.get();
+        f(&mut stats);
+        cell.set(stats);
+    });
+}
+
+/// 現スレッドの累計。
+#[must_use]
+pub fn snapshot() -> AllocStats {
+    STATS.try_with(Cell::get).unwrap_or_default()
+}
+
+/// `f` を実行し、その間に現スレッドで発生したヒープ操作を返す。
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    let before = snapshot();
+    let out = f();
+    (out, snapshot().since(&before))
+}
+
+/// ヒープ操作を数える [`System`] のラッパー。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+// SAFETY: 実際の確保・解放はすべて `System` に委譲し、カウンタの更新は
+// ヒープを使わない (const 初期化のスレッドローカル `Cell`)。
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        bump(|s| {
+            s.allocations += 1;
+            s.bytes += layout.size() as u64;
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        bump(|s| {
+            s.allocations += 1;
+            s.bytes += layout.size() as u64;
+        });
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        bump(|s| s.deallocations += 1);
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        bump(|s| {
+            s.reallocations += 1;
+            s.bytes += new_size.saturating_sub(layout.size()) as u64;
+        });
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::parser;
+    use crate::tag;
+    use crate::version::BeginString;
+
+    #[global_allocator]
+    static ALLOC: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn counts_allocations_on_current_thread() {
+        let (v, stats) = measure(|| vec![0u8; 64]);
+        assert_eq!(v.len(), 64);
+        assert_eq!(stats.allocations, 1);
+        assert_eq!(stats.bytes, 64);
+        let ((), stats) = measure(move || drop(v));
+        assert_eq!(stats.deallocations, 1);
+        assert_eq!(measure(|| 1 + 1).1, AllocStats::default());
+    }
+
+    #[test]
+    fn reused_buffers_do_not_allocate() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, 7)
+            .field(tag::SENDING_TIME, "20260101-00:00:00.000")
+            .field(tag::CL_ORD_ID, "A1")
+            .field_i64(tag::ORDER_QTY, 100)
+            .field_decimal(tag::PRICE, crate::value::Decimal::new(1015, 1), 2);
+        let mut buf = Vec::with_capacity(256);
+        b.build_into(&mut buf);
+        buf.clear();
+        let ((), stats) = measure(|| b.build_into(&mut buf));
+        assert_eq!(stats.total(), 0);
+
+        let (frame, stats) = measure(|| b.build());
+        assert_eq!(frame, buf);
+        assert_eq!(stats.total(), 1);
+
+        let mut sink = Vec::with_capacity(1024);
+        b.build_to_writer(&mut sink).unwrap();
+        let (_, stats) = measure(|| b.build_to_writer(&mut sink).unwrap());
+        assert_eq!(stats.total(), 0);
+
+        let (msg, stats) = measure(|| parser::parse(&buf).unwrap());
+        assert_eq!(msg.get(tag::CL_ORD_ID), Some("A1"));
+        assert!(stats.allocations > 0);
+    }
+}
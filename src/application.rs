@@ -0,0 +1,500 @@
+//! Application Callbacks
+//!
+//! `QuickFIX` の `Application` に相当するフック。[`Application`] を実装した
+//! 注文管理ロジックを [`ApplicationSession`] で [`FixSession`] に結び付けると、
+//! 管理メッセージ (35=0/1/2/3/4/5/A) とアプリケーションメッセージの振り分け、
+//! Logon / Logout の検出をセッション側で行い、対応するフックを呼ぶ。
+//!
+//! - 受信: 管理メッセージは受信時に [`Application::from_admin`]、
+//!   アプリケーションメッセージは配送時 (ギャップ解消後の順序) に
+//!   [`Application::from_app`]。
+//! - 送信: フレームを解析して [`Application::to_admin`] /
+//!   [`Application::to_app`] に渡し、書き換えられた場合だけ再エンコードする
+//!   (`BodyLength` / `CheckSum` は再計算)。`to_app` は [`DoNotSend`] で
+//!   送信を取り消せる。`*_with_store` はフックを通した後のフレームを
+//!   ストアに記録する。
+//! - 状態: [`SessionState::Active`] に入ると [`Application::on_logon`]、
+//!   Active / `LogoutSent` から切断されると [`Application::on_logout`]。
+//!
+//! - 取消拒否: `OrderCancelReject` (35=9) は [`Application::from_app`] の後、
+//!   解析できれば [`Application::on_cancel_reject`] にも型付きで渡す。
+//!
+//! 再送 ([`FixSession::resolve_resends`]) のフレームはフックを通さない。
+
+use std::io;
+
+pub use crate::admin::is_admin_msg_type;
+use crate::message::FixMessage;
+use crate::order::{msg_type, OrderCancelReject};
+use crate::parser::{self, ParseError};
+use crate::session::{FixSession, SessionAction, SessionId, SessionState};
+use crate::store::MessageStore;
+
+/// [`Application::to_app`] が送信を取り消すときに返す (`QuickFIX` の
+/// `DoNotSend`)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoNotSend;
+
+/// セッションから呼ばれるアプリケーションのフック。
+///
+/// [`Self::from_app`] 以外は既定で何もしない。
+// フック名は QuickFIX に合わせる。
+#[allow(clippy::wrong_self_convention)]
+pub trait Application {
+    /// [`ApplicationSession`] の作成時。
+    fn on_create(&mut self, _session_id: &SessionId) {}
+
+    /// Logon が成立した。
+    fn on_logon(&mut self, _session_id: &SessionId) {}
+
+    /// Logout の完了またはプロトコル違反で切断された。
+    fn on_logout(&mut self, _session_id: &SessionId) {}
+
+    /// 管理メッセージの送信直前。認証情報の追加などに使う。
+    fn to_admin(&mut self, _message: &mut FixMessage, _session_id: &SessionId) {}
+
+    /// アプリケーションメッセージの送信直前。
+    ///
+    /// # Errors
+    ///
+    /// [`DoNotSend`] を返すとメッセージは送られない。`MsgSeqNum` は消費済みの
+    /// ため、相手の `ResendRequest` にはその番号を `GapFill` で埋めて応える。
+    /// 管理メッセージはセッションの進行に必要なため取り消せない。
+    fn to_app(
+        &mut self,
+        _message: &mut FixMessage,
+        _session_id: &SessionId,
+    ) -> Result<(), DoNotSend> {
+        Ok(())
+    }
+
+    /// 管理メッセージを受信した (セッション処理の前)。
+    fn from_admin(&mut self, _message: &FixMessage, _session_id: &SessionId) {}
+
+    /// アプリケーションメッセージが配送された。
+    fn from_app(&mut self, message: &FixMessage, session_id: &SessionId);
+
+    /// 取消 / 訂正要求が拒否された ([`Self::from_app`] の後に呼ばれる)。
+    fn on_cancel_reject(&mut self, _reject: &OrderCancelReject, _session_id: &SessionId) {}
+}
+
+/// [`Application`] を結び付けた [`FixSession`]。
+pub struct ApplicationSession<A> {
+    session: FixSession,
+    app: A,
+}
+
+impl<A: Application> ApplicationSession<A> {
+    /// `session` と `app` を結び付け、[`Application::on_create`] を呼ぶ。
+    pub fn new(session: FixSession, mut app: A) -> Self {
+        app.on_create(&session.session_id());
+        Self { session, app }
+    }
+
+    /// セッション。
+    #[must_use]
+    pub const fn session(&self) -> &FixSession {
+        &self.session
+    }
+
+    /// セッション (フレームの組み立てや設定変更用)。
+    pub const fn session_mut(&mut self) -> &mut FixSession {
+        &mut self.session
+    }
+
+    /// アプリケーション。
+    #[must_use]
+    pub const fn app(&self) -> &A {
+        &self.app
+    }
+
+    /// アプリケーション。
+    pub const fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    /// セッションとアプリケーションに分解。
+    pub fn into_parts(self) -> (FixSession, A) {
+        (self.session, self.app)
+    }
+
+    /// 受信メッセージを [`FixSession::on_message`] で処理し、フックを呼ぶ。
+    ///
+    /// 配送 ([`SessionAction::Deliver`] / [`SessionAction::DeliverAdmin`]) は
+    /// フックで済ませるため返り値に含まない。送信フレームは送信側フックを通した後のもの。
+    pub fn on_message(&mut self, msg: &FixMessage) -> Vec<SessionAction> {
+        let id = self.session.session_id();
+        if is_admin_msg_type(&msg.msg_type) {
+            self.app.from_admin(msg, &id);
+        }
+        let before = *self.session.state();
+        let actions = self.session.on_message(msg);
+        self.dispatch(before, actions)
+    }
+
+    /// [`Self::on_message`] の送信フレーム (フックを通した後のもの) を
+    /// [`FixSession::store_sent`] で `store` に記録し、
+    /// [`SessionAction::Resend`] を [`FixSession::resolve_resends`] で解決する。
+    ///
+    /// # Errors
+    ///
+    /// ストアの読み書きに失敗した場合。
+    pub fn on_message_with_store(
+        &mut self,
+        msg: &FixMessage,
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Vec<SessionAction>> {
+        let actions = self.on_message(msg);
+        self.record(actions, store)
+    }
+
+    /// [`FixSession::on_timer`] を呼び、送信フレームにフックを通す。
+    pub fn on_timer(&mut self, now_ns: u64) -> Vec<SessionAction> {
+        let before = *self.session.state();
+        let actions = self.session.on_timer(now_ns);
+        self.dispatch(before, actions)
+    }
+
+    /// [`Self::on_timer`] の送信フレームを [`Self::on_message_with_store`]
+    /// と同じく `store` に記録する。
+    ///
+    /// # Errors
+    ///
+    /// ストアの書き込みに失敗した場合。
+    pub fn on_timer_with_store(
+        &mut self,
+        now_ns: u64,
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Vec<SessionAction>> {
+        let actions = self.on_timer(now_ns);
+        self.record(actions, store)
+    }
+
+    /// 呼び出し側が組み立てたフレーム (`build_*` の結果) に送信側フックを
+    /// 通す。
+    ///
+    /// フックがメッセージを変えなければ `frame` をそのまま返す。変えた場合は
+    /// 再エンコードしたもの (`SignatureLength` / `Signature` は外れる)。
+    /// [`Application::to_app`] が [`DoNotSend`] を返した場合は `None`。
+    ///
+    /// # Errors
+    ///
+    /// `frame` を解析できない場合。
+    pub fn send(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, ParseError> {
+        let original = parser::parse(frame)?;
+        let mut msg = original.clone();
+        let id = self.session.session_id();
+        if is_admin_msg_type(&msg.msg_type) {
+            self.app.to_admin(&mut msg, &id);
+        } else if self.app.to_app(&mut msg, &id).is_err() {
+            return Ok(None);
+        }
+        Ok(Some(if msg == original {
+            frame.to_vec()
+        } else {
+            msg.to_bytes()
+        }))
+    }
+
+    /// [`Self::send`] の結果を [`FixSession::store_sent`] で `store` に
+    /// 記録する。取り消されたフレームは記録しない。
+    ///
+    /// # Errors
+    ///
+    /// `frame` を解析できない場合 ([`io::ErrorKind::InvalidData`])、ストアの
+    /// 書き込みに失敗した場合。
+    pub fn send_with_store(
+        &mut self,
+        frame: &[u8],
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Option<Vec<u8>>> {
+        let sent = self
+            .send(frame)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        if let Some(frame) = &sent {
+            self.session.store_sent(store, frame)?;
+        }
+        Ok(sent)
+    }
+
+    /// `actions` の送信フレームを記録し、再送要求をストアから解決する。
+    fn record(
+        &self,
+        actions: Vec<SessionAction>,
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Vec<SessionAction>> {
+        for action in &actions {
+            if let SessionAction::Send(frame) = action {
+                self.session.store_sent(store, frame)?;
+            }
+        }
+        self.session.resolve_resends(actions, store)
+    }
+
+    fn dispatch(
+        &mut self,
+        before: SessionState,
+        actions: Vec<SessionAction>,
+    ) -> Vec<SessionAction> {
+        let id = self.session.session_id();
+        let mut out = Vec::with_capacity(actions.len());
+        for action in actions {
+            match action {
+                SessionAction::Deliver(msg) => {
+                    self.app.from_app(&msg, &id);
+                    if msg.msg_type == msg_type::ORDER_CANCEL_REJECT {
+                        if let Ok(reject) = OrderCancelReject::try_from(&msg) {
+                            self.app.on_cancel_reject(&reject, &id);
+                        }
+                    }
+                }
+                // 管理メッセージは受信時に from_admin 済み。
+                SessionAction::DeliverAdmin(_) => {}
+                SessionAction::Send(frame) => match self.send(&frame) {
+                    Ok(Some(sent)) => out.push(SessionAction::Send(sent)),
+                    Ok(None) => {}
+                    // セッションが組み立てたフレームは常に解析できる。
+                    Err(_) => out.push(SessionAction::Send(frame)),
+                },
+                other => out.push(other),
+            }
+        }
+        let after = *self.session.state();
+        if before != SessionState::Active && after == SessionState::Active {
+            self.app.on_logon(&id);
+        }
+        if matches!(before, SessionState::Active | SessionState::LogoutSent)
+            && after == SessionState::Disconnected
+        {
+            self.app.on_logout(&id);
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::session::SessionRole;
+    use crate::tag;
+    use crate::version::BeginString;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Application for Recorder {
+        fn on_create(&mut self, session_id: &SessionId) {
+            self.events.push(format!("create {session_id}"));
+        }
+
+        fn on_logon(&mut self, _session_id: &SessionId) {
+            self.events.push("logon".into());
+        }
+
+        fn on_logout(&mut self, _session_id: &SessionId) {
+            self.events.push("logout".into());
+        }
+
+        fn to_admin(&mut self, message: &mut FixMessage, _session_id: &SessionId) {
+            if message.msg_type == "A" {
+                message.set(tag::USERNAME, "alice");
+            }
+            self.events.push(format!("to_admin {}", message.msg_type));
+        }
+
+        fn to_app(
+            &mut self,
+            message: &mut FixMessage,
+            _session_id: &SessionId,
+        ) -> Result<(), DoNotSend> {
+            self.events.push(format!("to_app {}", message.msg_type));
+            if message.get(tag::CL_ORD_ID) == Some("BLOCKED") {
+                return Err(DoNotSend);
+            }
+            if !message.contains(tag::ACCOUNT) {
+                message.set(tag::ACCOUNT, "ACC1");
+            }
+            Ok(())
+        }
+
+        fn from_admin(&mut self, message: &FixMessage, _session_id: &SessionId) {
+            self.events.push(format!("from_admin {}", message.msg_type));
+        }
+
+        fn from_app(&mut self, message: &FixMessage, _session_id: &SessionId) {
+            self.events.push(format!("from_app {}", message.msg_type));
+        }
+
+        fn on_cancel_reject(&mut self, reject: &OrderCancelReject, _session_id: &SessionId) {
+            self.events.push(format!(
+                "cancel_reject {} {:?}",
+                reject.orig_cl_ord_id, reject.reason
+            ));
+        }
+    }
+
+    fn inbound(msg_type: &str, seq: u64) -> FixMessage {
+        let mut msg = FixMessage::new("FIX.4.4", msg_type);
+        msg.set(tag::SENDER_COMP_ID, "BROKER")
+            .set(tag::TARGET_COMP_ID, "ALICE")
+            .set_value(tag::MSG_SEQ_NUM, seq)
+            .set(tag::HEART_BT_INT, "30");
+        msg
+    }
+
+    #[test]
+    fn dispatches_hooks_through_session_lifecycle() {
+        let mut session = FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+        session.set_role(SessionRole::Acceptor);
+        let mut app = ApplicationSession::new(session, Recorder::default());
+
+        let actions = app.on_message(&inbound("A", 1));
+        let SessionAction::Send(reply) = &actions[0] else {
+            panic!("expected Logon reply");
+        };
+        assert_eq!(
+            parser::parse(reply).unwrap().get(tag::USERNAME),
+            Some("alice")
+        );
+
+        // Application messages are consumed by from_app.
+        assert!(app.on_message(&inbound("8", 2)).is_empty());
+
+        let seq = app.session_mut().next_outgoing_seq();
+        let order = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, seq)
+            .build();
+        let order = parser::parse(&app.send(&order).unwrap().unwrap()).unwrap();
+        assert_eq!(order.get(tag::ACCOUNT), Some("ACC1"));
+
+        let actions = app.on_message(&inbound("5", 3));
+        assert_eq!(actions.last(), Some(&SessionAction::Disconnect));
+
+        assert_eq!(
+            app.app().events,
+            [
+                "create FIX.4.4:ALICE->BROKER",
+                "from_admin A",
+                "to_admin A",
+                "logon",
+                "from_app 8",
+                "to_app D",
+                "from_admin 5",
+                "to_admin 5",
+                "logout",
+            ]
+        );
+    }
+
+    #[test]
+    fn cancel_reject_surfaces_typed() {
+        use crate::order::CxlRejReason;
+
+        let mut session = FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+        session.set_role(SessionRole::Acceptor);
+        let mut app = ApplicationSession::new(session, Recorder::default());
+        app.on_message(&inbound("A", 1));
+
+        let mut reject = inbound("9", 2);
+        reject
+            .set(tag::ORDER_ID, "O1")
+            .set(tag::CL_ORD_ID, "C2")
+            .set(tag::ORIG_CL_ORD_ID, "C1")
+            .set(tag::ORD_STATUS, "2")
+            .set(tag::CXL_REJ_RESPONSE_TO, "1")
+            .set_value(
+                tag::CXL_REJ_REASON,
+                u64::from(CxlRejReason::TooLateToCancel.code()),
+            );
+        assert!(app.on_message(&reject).is_empty());
+
+        // 解析できない 35=9 は from_app のみ。
+        let mut malformed = inbound("9", 3);
+        malformed.set(tag::CL_ORD_ID, "C3");
+        app.on_message(&malformed);
+
+        assert_eq!(
+            app.app().events[4..],
+            [
+                "from_app 9",
+                "cancel_reject C1 Some(TooLateToCancel)",
+                "from_app 9",
+            ]
+        );
+    }
+
+    #[test]
+    fn send_keeps_unchanged_frames_and_honours_do_not_send() {
+        use crate::store::{MemoryStore, MessageStore};
+        use std::sync::Arc;
+
+        let mut session = FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+        session.set_role(SessionRole::Acceptor);
+        let mut app = ApplicationSession::new(session, Recorder::default());
+        let mut store = MemoryStore::new();
+        let actions = app
+            .on_message_with_store(&inbound("A", 1), &mut store)
+            .unwrap();
+        let SessionAction::Send(reply) = &actions[0] else {
+            panic!("expected Logon reply");
+        };
+        assert_eq!(store.get(1).unwrap().as_deref(), Some(reply.as_slice()));
+
+        let order = |app: &mut ApplicationSession<Recorder>, cl_ord_id: &str| {
+            let seq = app.session_mut().next_outgoing_seq();
+            FixBuilder::new(BeginString::Fix44, "D")
+                .field(tag::SENDER_COMP_ID, "ALICE")
+                .field(tag::TARGET_COMP_ID, "BROKER")
+                .field_u64(tag::MSG_SEQ_NUM, seq)
+                .field(tag::CL_ORD_ID, cl_ord_id)
+                .field(tag::ACCOUNT, "ACC1")
+                .sign_with(Arc::new(|_: &[u8]| b"SIG".to_vec()))
+                .build()
+        };
+        // Untouched by the hook: sent and stored byte for byte, signature included.
+        let signed = order(&mut app, "C1");
+        let sent = app.send_with_store(&signed, &mut store).unwrap();
+        assert_eq!(sent.as_ref(), Some(&signed));
+        assert_eq!(store.get(2).unwrap(), Some(signed));
+
+        let blocked = order(&mut app, "BLOCKED");
+        assert_eq!(app.send_with_store(&blocked, &mut store).unwrap(), None);
+        assert_eq!(store.get(3).unwrap(), None);
+
+        let mut resend = inbound("2", 2);
+        resend
+            .set_value(tag::BEGIN_SEQ_NO, 2u64)
+            .set_value(tag::END_SEQ_NO, 0u64);
+        let frames: Vec<FixMessage> = app
+            .on_message_with_store(&resend, &mut store)
+            .unwrap()
+            .iter()
+            .map(|action| match action {
+                SessionAction::Send(frame) => parser::parse(frame).unwrap(),
+                other => panic!("expected Send, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].get(tag::CL_ORD_ID), Some("C1"));
+        assert_eq!(frames[1].msg_type, "4");
+        assert_eq!(frames[1].get(tag::NEW_SEQ_NO), Some("4"));
+    }
+
+    #[test]
+    fn classifies_admin_msg_types() {
+        for msg_type in ["0", "1", "2", "3", "4", "5", "A"] {
+            assert!(is_admin_msg_type(msg_type));
+        }
+        assert!(!is_admin_msg_type("D"));
+        assert!(!is_admin_msg_type("AE"));
+    }
+}
@@ -11,15 +11,67 @@
 //!
 //! ## Build Flow
 //!
-//! 1. Collect all user-supplied fields as `"tag=value\x01"` segments.
+//! 1. Collect all user-supplied fields as `"tag=value\x01"` segments:
+//!    standard header fields first, in [`HEADER_TAG_ORDER`], then the
+//!    remaining fields in call order.
 //! 2. Prepend `"35=<msg_type>\x01"` so it appears first in the body.
 //! 3. Compute the body length (bytes of the body, including tag 35).
 //! 4. Prepend `"8=<begin_string>\x01"` and `"9=<body_length>\x01"`.
 //! 5. Compute the checksum over all preceding bytes, modulo 256.
 //! 6. Append `"10=<checksum_3digits>\x01"`.
+//!
+//! With a [`Signer`] installed ([`FixBuilder::sign_with`]), the body from
+//! `35=` through the last user field is signed and `SignatureLength` (93) /
+//! `Signature` (89) are appended in trailer order, before the checksum.
+//! Both fields count towards `BodyLength` and the checksum.
+//!
+//! `SendingTime` (52) comes from a pluggable [`Clock`]:
+//! [`FixBuilder::sending_time_now`] stamps it immediately, and
+//! [`FixBuilder::auto_sending_time`] stamps it at build time when absent.
+//! The fractional-second precision is set with
+//! [`FixBuilder::timestamp_precision`] (milliseconds by default).
+//!
+//! [`FixBuilder::from_message`] seeds a builder with the contents of a
+//! received [`FixMessage`], so it can be amended and sent without retyping
+//! every field.
+//!
+//! [`FixBuilder::raw_field_order`] disables the header reordering and emits
+//! every field strictly in call order, for counterparties that expect a
+//! non-standard layout or tests that need malformed frames.
+//! `FixBuilder::build_with` (feature `testing`) goes further and writes a
+//! wrong `BodyLength`, a wrong `CheckSum` or no trailer at all.
+//!
+//! Values are held as [`FixValue`]s and rendered to their wire form only
+//! in [`FixBuilder::build`]. [`FixBuilder::build_into`] appends the same
+//! bytes to a caller-provided buffer so one allocation can be reused across
+//! messages (and `build_into_bytes` does so for `bytes::BytesMut` with the
+//! `bytes` feature). [`FixBuilder::build_to_writer`] streams the frame into
+//! any [`std::io::Write`] (`build_to_async_writer` into a tokio `AsyncWrite`
+//! through a caller-provided staging buffer, with the `tokio` feature). [`FixBuilder::encoded_len`] computes the
+//! exact frame size up front, so [`FixBuilder::build`] allocates once.
+//!
+//! ## Required-field checks
+//!
+//! [`FixBuilder::try_build`] refuses to serialize a message that lacks a
+//! standard header field or a body field required for its `MsgType`
+//! (see [`required_body_tags`]). [`FixBuilder::assert_required`] turns the
+//! same check into a `debug_assert!` inside [`FixBuilder::build`], so
+//! malformed messages are caught in development builds at no release cost.
+
+use std::cell::Cell;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::sync::Arc;
 
+use crate::clock::{Clock, SystemClock};
+use crate::dictionary::Dictionary;
+use crate::message::FixMessage;
 use crate::parser::SOH;
+use crate::repeating_group::{find_group_def, RepeatingGroup};
 use crate::tag;
+use crate::validation::ValidationError;
+use crate::value::{format_utc_timestamp_with, Decimal, FixValue, TimestampPrecision};
+use crate::version::BeginString;
 
 /// FIX message serializer.
 ///
@@ -27,92 +79,1119 @@ use crate::tag;
 /// tag 9 (`BodyLength`), tag 35 (`MsgType`), and tag 10 (Checksum) are managed
 /// automatically.
 pub struct FixBuilder {
-    begin_string: String,
-    msg_type: String,
+    pub(crate) begin_string: String,
+    pub(crate) msg_type: String,
     /// User-supplied body fields, in insertion order.
-    fields: Vec<(u32, String)>,
+    fields: Vec<(u32, FixValue)>,
+    /// Debug-assert that required fields are present in [`Self::build`].
+    assert_required: bool,
+    /// Trailer signer; `None` emits no `Signature` fields.
+    signer: Option<Arc<dyn Signer>>,
+    /// Time source for `SendingTime`; `None` uses [`SystemClock`].
+    clock: Option<Arc<dyn Clock>>,
+    /// Fractional-second precision of generated timestamps.
+    precision: TimestampPrecision,
+    /// Stamp `SendingTime` in [`Self::build`] when it was not set.
+    auto_sending_time: bool,
+    /// Emit fields strictly in call order (no header reordering).
+    raw_order: bool,
+}
+
+thread_local! {
+    /// Staging buffer for [`FixBuilder::build_to_writer`], reused per thread.
+    static SCRATCH: Cell<Vec<u8>> = const { Cell::new(Vec::new()) };
+}
+
+/// Produces the trailer `Signature` (tag 89) for a serialized body.
+///
+/// `body` spans `35=<msg_type>` through the SOH of the last user field;
+/// `BeginString`, `BodyLength`, and the trailer are not signed. Closures
+/// `Fn(&[u8]) -> Vec<u8>` implement this trait.
+///
+/// The parser does not treat tag 89 as length-prefixed data, so signatures
+/// meant to round-trip through [`crate::parser::parse`] must not contain SOH
+/// (use a text encoding such as hex or base64).
+pub trait Signer: Send + Sync {
+    /// Return the signature bytes for `body`.
+    fn sign(&self, body: &[u8]) -> Vec<u8>;
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8> + Send + Sync> Signer for F {
+    fn sign(&self, body: &[u8]) -> Vec<u8> {
+        self(body)
+    }
 }
 
-impl FixBuilder {
-    /// Create a new builder for a message of the given FIX version and type.
-    #[inline(always)]
-    #[must_use]
-    pub fn new(begin_string: &str, msg_type: &str) -> Self {
-        Self {
-            begin_string: begin_string.to_string(),
-            msg_type: msg_type.to_string(),
-            fields: Vec::new(),
+/// Standard header fields required on every message (besides 8, 9, 35).
+pub const REQUIRED_HEADER_TAGS: &[u32] = &[
+    tag::SENDER_COMP_ID,
+    tag::TARGET_COMP_ID,
+    tag::MSG_SEQ_NUM,
+    tag::SENDING_TIME,
+];
+
+/// Header fields that identify the session route and are kept by
+/// [`FixBuilder::reset`].
+pub const ROUTING_HEADER_TAGS: &[u32] = &[
+    tag::APPL_VER_ID,
+    tag::SENDER_COMP_ID,
+    tag::TARGET_COMP_ID,
+    tag::ON_BEHALF_OF_COMP_ID,
+    tag::DELIVER_TO_COMP_ID,
+    tag::SENDER_SUB_ID,
+    tag::TARGET_SUB_ID,
+    tag::ON_BEHALF_OF_SUB_ID,
+    tag::DELIVER_TO_SUB_ID,
+];
+
+/// Standard header fields in the order they are emitted after `35=`.
+///
+/// Unless [`FixBuilder::raw_field_order`] is set, these tags are written
+/// before all other fields regardless of the order they were added in.
+pub const HEADER_TAG_ORDER: &[u32] = &[
+    tag::APPL_VER_ID,
+    tag::SENDER_COMP_ID,
+    tag::TARGET_COMP_ID,
+    tag::ON_BEHALF_OF_COMP_ID,
+    tag::DELIVER_TO_COMP_ID,
+    tag::MSG_SEQ_NUM,
+    tag::SENDER_SUB_ID,
+    tag::TARGET_SUB_ID,
+    tag::ON_BEHALF_OF_SUB_ID,
+    tag::DELIVER_TO_SUB_ID,
+    tag::POSS_DUP_FLAG,
+    tag::POSS_RESEND,
+    tag::SENDING_TIME,
+    tag::ORIG_SENDING_TIME,
+];
+
+/// Body fields required for the given `MsgType`.
+///
+/// Unknown message types have no required body fields.
+#[must_use]
+pub fn required_body_tags(msg_type: &str) -> &'static [u32] {
+    match msg_type {
+        "1" => &[tag::TEST_REQ_ID],
+        "2" => &[tag::BEGIN_SEQ_NO, tag::END_SEQ_NO],
+        "3" => &[tag::REF_SEQ_NUM],
+        "4" => &[tag::NEW_SEQ_NO],
+        "A" => &[tag::ENCRYPT_METHOD, tag::HEART_BT_INT],
+        "D" => &[
+            tag::CL_ORD_ID,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::TRANSACT_TIME,
+            tag::ORD_TYPE,
+        ],
+        "8" => &[
+            tag::ORDER_ID,
+            tag::EXEC_ID,
+            tag::EXEC_TYPE,
+            tag::ORD_STATUS,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::LEAVES_QTY,
+            tag::CUM_QTY,
+        ],
+        "F" => &[
+            tag::ORIG_CL_ORD_ID,
+            tag::CL_ORD_ID,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::TRANSACT_TIME,
+        ],
+        "9" => &[
+            tag::ORDER_ID,
+            tag::CL_ORD_ID,
+            tag::ORIG_CL_ORD_ID,
+            tag::ORD_STATUS,
+            tag::CXL_REJ_RESPONSE_TO,
+        ],
+        "H" => &[tag::CL_ORD_ID, tag::SYMBOL, tag::SIDE],
+        "AF" => &[tag::MASS_STATUS_REQ_ID, tag::MASS_STATUS_REQ_TYPE],
+        "E" => &[
+            tag::LIST_ID,
+            tag::BID_TYPE,
+            tag::TOT_NO_ORDERS,
+            tag::NO_ORDERS,
+        ],
+        "N" => &[
+            tag::LIST_ID,
+            tag::LIST_STATUS_TYPE,
+            tag::NO_RPTS,
+            tag::LIST_ORDER_STATUS,
+            tag::RPT_SEQ,
+            tag::TOT_NO_ORDERS,
+            tag::NO_ORDERS,
+        ],
+        _ => &[],
+    }
+}
+
+/// Header and trailer overrides for [`FixBuilder::build_with`].
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameOverrides {
+    /// `CheckSum` (10) to write instead of the computed value.
+    pub checksum: Option<u8>,
+    /// `BodyLength` (9) to write instead of the actual body length.
+    pub body_length: Option<usize>,
+    /// Leave out the `CheckSum` trailer entirely.
+    pub omit_checksum: bool,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl FrameOverrides {
+    /// No overrides: [`FixBuilder::build_with`] produces a valid frame.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            checksum: None,
+            body_length: None,
+            omit_checksum: false,
+        }
+    }
+
+    /// Write `checksum` as `CheckSum` (10).
+    #[must_use]
+    pub const fn checksum(mut self, checksum: u8) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Write `body_length` as `BodyLength` (9).
+    #[must_use]
+    pub const fn body_length(mut self, body_length: usize) -> Self {
+        self.body_length = Some(body_length);
+        self
+    }
+
+    /// Omit the `CheckSum` trailer.
+    #[must_use]
+    pub const fn omit_checksum(mut self) -> Self {
+        self.omit_checksum = true;
+        self
+    }
+}
+
+/// Errors returned by [`FixBuilder::try_build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A field required for the message type is absent.
+    MissingRequiredField {
+        /// `MsgType` of the message being built.
+        msg_type: String,
+        /// Tag number of the first missing field.
+        tag: u32,
+    },
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingRequiredField { msg_type, tag } => {
+                write!(f, "MsgType {msg_type}: missing required tag {tag}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl FixBuilder {
+    /// Create a new builder for a message of the given FIX version and type.
+    ///
+    /// `begin_string` is a [`BeginString`] or a [`crate::version::FixVersion`].
+    /// Only `BeginString` (8) is written; `ApplVerID` (1128) for `FIXT.1.1`
+    /// is up to the caller ([`crate::session::FixSession`] and
+    /// [`crate::header::HeaderTemplate`] stamp it).
+    #[inline(always)]
+    #[must_use]
+    pub fn new(begin_string: impl Into<BeginString>, msg_type: &str) -> Self {
+        Self::with_begin_string(begin_string.into().as_str(), msg_type)
+    }
+
+    /// [`Self::new`] with `BeginString` (8) taken verbatim, for messages of
+    /// any version.
+    fn with_begin_string(begin_string: &str, msg_type: &str) -> Self {
+        Self {
+            begin_string: begin_string.to_string(),
+            msg_type: msg_type.to_string(),
+            fields: Vec::new(),
+            assert_required: false,
+            signer: None,
+            clock: None,
+            precision: TimestampPrecision::Millis,
+            auto_sending_time: false,
+            raw_order: false,
+        }
+    }
+
+    /// Use `clock` for [`Self::sending_time_now`] and
+    /// [`Self::auto_sending_time`] instead of the system clock.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn clock(&mut self, clock: Arc<dyn Clock>) -> &mut Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Set the fractional-second precision of generated `SendingTime`
+    /// values (milliseconds by default).
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub const fn timestamp_precision(&mut self, precision: TimestampPrecision) -> &mut Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Append `SendingTime` (52) with the clock's current time.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn sending_time_now(&mut self) -> &mut Self {
+        let stamp = self.now_stamp();
+        self.fields.push((tag::SENDING_TIME, FixValue::Str(stamp)));
+        self
+    }
+
+    /// Enable or disable stamping `SendingTime` at build time.
+    ///
+    /// When enabled and tag 52 was not set, [`Self::build`] inserts it at its
+    /// header position (after the last standard header field with
+    /// [`Self::raw_field_order`]) with the clock's time at that moment, and
+    /// [`Self::missing_required`] no longer reports it.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub const fn auto_sending_time(&mut self, enabled: bool) -> &mut Self {
+        self.auto_sending_time = enabled;
+        self
+    }
+
+    /// Create a builder holding every field and repeating group of `msg`.
+    ///
+    /// If `msg` recorded spans ([`crate::parser::parse_with_spans`]), fields
+    /// keep their wire order ([`Self::raw_field_order`] is enabled); fields
+    /// added after parsing follow in tag order.
+    /// Otherwise the standard header fields ([`REQUIRED_HEADER_TAGS`]) come
+    /// first, followed by the rest in tag order. Each repeating group's
+    /// entries follow its count tag, whose value is taken from the number of
+    /// entries; every entry starts with the group's delimiter tag.
+    ///
+    /// `SignatureLength` (93) and `Signature` (89) are dropped, since an
+    /// amended message no longer matches them; use [`Self::sign_with`] to
+    /// re-sign.
+    #[must_use]
+    pub fn from_message(msg: &FixMessage) -> Self {
+        let mut builder = Self::with_begin_string(&msg.begin_string, &msg.msg_type);
+        builder.raw_order = !msg.spans.is_empty();
+        let mut order: Vec<u32> = Vec::with_capacity(msg.len());
+        let mut push = |t: u32| {
+            if msg.contains(t) && !order.contains(&t) {
+                order.push(t);
+            }
+        };
+        if msg.spans.is_empty() {
+            REQUIRED_HEADER_TAGS.iter().copied().for_each(&mut push);
+        } else {
+            msg.spans.iter().map(|&(t, _)| t).for_each(&mut push);
+        }
+        msg.iter().map(|(t, _)| t).for_each(&mut push);
+
+        for t in order {
+            if t == tag::SIGNATURE_LENGTH || t == tag::SIGNATURE {
+                continue;
+            }
+            let entries = msg.groups(t);
+            if entries.is_empty() {
+                builder.field(t, msg.get(t).unwrap_or_default());
+                continue;
+            }
+            builder.field_u64(t, entries.len() as u64);
+            let delimiter = find_group_def(t).map(|d| d.delimiter_tag);
+            for entry in entries {
+                if let Some(d) = delimiter {
+                    if let Some(dv) = entry.get(d) {
+                        builder.field(d, dv);
+                    }
+                }
+                for (gt, gv) in entry.iter().filter(|&(gt, _)| Some(gt) != delimiter) {
+                    builder.field(gt, gv);
+                }
+            }
+        }
+        builder
+    }
+
+    /// Emit fields strictly in the order they were added, instead of moving
+    /// the standard header fields ([`HEADER_TAG_ORDER`]) in front.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub const fn raw_field_order(&mut self, enabled: bool) -> &mut Self {
+        self.raw_order = enabled;
+        self
+    }
+
+    /// Remove every field, keeping the message type, configuration (clock,
+    /// signer, flags) and the allocated capacity of the field vector.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn clear(&mut self) -> &mut Self {
+        self.fields.clear();
+        self
+    }
+
+    /// Prepare the builder for the next message of type `msg_type`.
+    ///
+    /// The routing header fields ([`ROUTING_HEADER_TAGS`], e.g. set by
+    /// [`crate::header::HeaderTemplate::apply`]) are kept; per-message
+    /// header fields (`MsgSeqNum`, `SendingTime`, `PossDupFlag`, ...) and the
+    /// body are removed. Capacity of the field vector and the `MsgType`
+    /// string is retained, so one builder can serve a whole session without
+    /// reallocating.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn reset(&mut self, msg_type: &str) -> &mut Self {
+        self.msg_type.clear();
+        self.msg_type.push_str(msg_type);
+        self.fields.retain(|(t, _)| ROUTING_HEADER_TAGS.contains(t));
+        self
+    }
+
+    /// Sign the message with `signer`, emitting `SignatureLength` (93) and
+    /// `Signature` (89) in the trailer.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn sign_with(&mut self, signer: Arc<dyn Signer>) -> &mut Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Enable or disable the required-field assertion in [`Self::build`].
+    ///
+    /// When enabled, [`Self::build`] panics in builds with debug assertions
+    /// if any field listed by [`Self::missing_required`] is absent.
+    /// Release builds are unaffected.
+    #[inline(always)]
+    pub const fn assert_required(&mut self, enabled: bool) -> &mut Self {
+        self.assert_required = enabled;
+        self
+    }
+
+    /// Return the header and body tags required for this message type that
+    /// have not been set, in specification order.
+    #[must_use]
+    pub fn missing_required(&self) -> Vec<u32> {
+        REQUIRED_HEADER_TAGS
+            .iter()
+            .chain(required_body_tags(&self.msg_type))
+            .copied()
+            .filter(|&t| !(self.auto_sending_time && t == tag::SENDING_TIME))
+            .filter(|&t| !self.fields.iter().any(|(f, _)| *f == t))
+            .collect()
+    }
+
+    /// Serialize the message after checking that every required field is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::MissingRequiredField`] naming the first missing tag.
+    pub fn try_build(&self) -> Result<Vec<u8>, BuildError> {
+        if let Some(&tag) = self.missing_required().first() {
+            return Err(BuildError::MissingRequiredField {
+                msg_type: self.msg_type.clone(),
+                tag,
+            });
+        }
+        Ok(self.serialize())
+    }
+
+    /// Check the fields set so far against `dictionary` before sending:
+    /// required tags for the message type, value types and domains, and
+    /// repeating group counts and ordering.
+    ///
+    /// `SendingTime` (52) is not required when [`Self::auto_sending_time`]
+    /// is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`ValidationError`] found, mapping to the
+    /// `SessionRejectReason` the counterparty would have sent.
+    pub fn validate(&self, dictionary: &Dictionary) -> Result<(), ValidationError> {
+        let values: Vec<(u32, String)> = self
+            .fields
+            .iter()
+            .map(|(t, v)| (*t, v.to_string()))
+            .collect();
+        let fields: Vec<(u32, &str)> = values.iter().map(|(t, v)| (*t, v.as_str())).collect();
+        let stamped: &[u32] = if self.auto_sending_time {
+            &[tag::SENDING_TIME]
+        } else {
+            &[]
+        };
+        dictionary.check_fields(&self.msg_type, &fields, stamped)
+    }
+
+    /// Append a string tag/value pair to the message body.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field(&mut self, tag: u32, value: &str) -> &mut Self {
+        self.fields.push((tag, FixValue::from(value)));
+        self
+    }
+
+    /// Append an `i64` value for the given tag.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_i64(&mut self, tag: u32, value: i64) -> &mut Self {
+        self.fields.push((tag, FixValue::Int(value)));
+        self
+    }
+
+    /// Append a `u64` value for the given tag.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_u64(&mut self, tag: u32, value: u64) -> &mut Self {
+        self.fields.push((tag, FixValue::UInt(value)));
+        self
+    }
+
+    /// Append an `f64` as the shortest decimal string that parses back to
+    /// the same value: no exponent and no trailing zeros (`0.1 + 0.2` is
+    /// `0.30000000000000004`, `1e21` is `1000000000000000000000`). `-0.0`
+    /// is written as `0`. Use [`Self::field_decimal`] for a fixed scale.
+    ///
+    /// Returns `&mut self` for method chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is NaN or infinite.
+    #[inline(always)]
+    pub fn field_f64(&mut self, tag: u32, value: f64) -> &mut Self {
+        assert!(value.is_finite(), "tag {tag}: non-finite f64 {value}");
+        let text = if value == 0.0 {
+            "0".to_string()
+        } else {
+            value.to_string()
+        };
+        self.fields.push((tag, FixValue::Str(text)));
+        self
+    }
+
+    /// Append a [`Decimal`] with exactly `scale` fractional digits, rounding
+    /// half away from zero when digits are dropped (`12.345` at scale 2 is
+    /// `12.35`). Convert an `f64` with [`Decimal::from_f64`].
+    ///
+    /// Returns `&mut self` for method chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if widening to `scale` overflows the `i64` mantissa.
+    #[inline(always)]
+    pub fn field_decimal(&mut self, tag: u32, value: Decimal, scale: u32) -> &mut Self {
+        let scaled = value
+            .rescale(scale)
+            .unwrap_or_else(|| panic!("tag {tag}: {value} overflows at scale {scale}"));
+        self.fields.push((tag, FixValue::Decimal(scaled)));
+        self
+    }
+
+    /// Set `tag` to `value`, overwriting its first occurrence in place, or
+    /// append it if the tag is absent.
+    ///
+    /// Lets a template be adjusted (e.g. swap `TimeInForce`) without
+    /// rebuilding the field list. Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn set_field(&mut self, tag: u32, value: &str) -> &mut Self {
+        self.set_field_value(tag, value)
+    }
+
+    /// [`Self::set_field`] for a typed [`FixValue`].
+    #[inline(always)]
+    pub fn set_field_value(&mut self, tag: u32, value: impl Into<FixValue>) -> &mut Self {
+        let value = value.into();
+        match self.fields.iter_mut().find(|(t, _)| *t == tag) {
+            Some((_, v)) => *v = value,
+            None => self.fields.push((tag, value)),
+        }
+        self
+    }
+
+    /// Remove every occurrence of `tag` (e.g. `Price` on a market order).
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn remove_field(&mut self, tag: u32) -> &mut Self {
+        self.fields.retain(|(t, _)| *t != tag);
+        self
+    }
+
+    /// Whether `tag` has been set.
+    #[inline(always)]
+    #[must_use]
+    pub fn has_field(&self, tag: u32) -> bool {
+        self.fields.iter().any(|(t, _)| *t == tag)
+    }
+
+    /// Append a FIX Boolean as `Y` / `N`.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_bool(&mut self, tag: u32, value: bool) -> &mut Self {
+        self.fields.push((tag, FixValue::Bool(value)));
+        self
+    }
+
+    /// Append a single-character code (`Side`, `OrdType`, `ExecType`, ...).
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_char(&mut self, tag: u32, value: char) -> &mut Self {
+        self.fields.push((tag, FixValue::Str(value.to_string())));
+        self
+    }
+
+    /// Append a `UTCTimestamp` from nanoseconds since the Unix epoch,
+    /// truncated to the precision set by [`Self::timestamp_precision`]
+    /// (call that first to change it).
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_timestamp(&mut self, tag: u32, nanos: u64) -> &mut Self {
+        let text = format_utc_timestamp_with(nanos, self.precision);
+        self.fields.push((tag, FixValue::Str(text)));
+        self
+    }
+
+    /// Append a typed [`FixValue`] for the given tag.
+    ///
+    /// The value is rendered to its wire form only when the message is built.
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_value(&mut self, tag: u32, value: impl Into<FixValue>) -> &mut Self {
+        self.fields.push((tag, value.into()));
+        self
+    }
+
+    /// Append a string field if `value` is `Some`; `None` appends nothing.
+    ///
+    /// Keeps optional fields (`Price` on market orders, `Text`) inside one
+    /// builder chain. Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_opt(&mut self, tag: u32, value: Option<&str>) -> &mut Self {
+        if let Some(v) = value {
+            self.field(tag, v);
+        }
+        self
+    }
+
+    /// [`Self::field_i64`] if `value` is `Some`.
+    #[inline(always)]
+    pub fn field_opt_i64(&mut self, tag: u32, value: Option<i64>) -> &mut Self {
+        if let Some(v) = value {
+            self.field_i64(tag, v);
+        }
+        self
+    }
+
+    /// [`Self::field_u64`] if `value` is `Some`.
+    #[inline(always)]
+    pub fn field_opt_u64(&mut self, tag: u32, value: Option<u64>) -> &mut Self {
+        if let Some(v) = value {
+            self.field_u64(tag, v);
+        }
+        self
+    }
+
+    /// [`Self::field_f64`] if `value` is `Some`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is `Some` of NaN or infinity.
+    #[inline(always)]
+    pub fn field_opt_f64(&mut self, tag: u32, value: Option<f64>) -> &mut Self {
+        if let Some(v) = value {
+            self.field_f64(tag, v);
+        }
+        self
+    }
+
+    /// [`Self::field_decimal`] if `value` is `Some`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if widening to `scale` overflows the `i64` mantissa.
+    #[inline(always)]
+    pub fn field_opt_decimal(&mut self, tag: u32, value: Option<Decimal>, scale: u32) -> &mut Self {
+        if let Some(v) = value {
+            self.field_decimal(tag, v, scale);
+        }
+        self
+    }
+
+    /// [`Self::field_value`] if `value` is `Some`.
+    #[inline(always)]
+    pub fn field_opt_value<V: Into<FixValue>>(&mut self, tag: u32, value: Option<V>) -> &mut Self {
+        if let Some(v) = value {
+            self.field_value(tag, v);
+        }
+        self
+    }
+
+    /// Append a length-prefixed data field pair: `len_tag` with the byte
+    /// length of `data`, followed by `data_tag` with the raw bytes.
+    ///
+    /// `data` is written verbatim and may contain SOH; the length is what
+    /// lets a receiver skip over it (e.g., `RawDataLength` 95 / `RawData`
+    /// 96, `XmlDataLen` 212 / `XmlData` 213, `SignatureLength` 93 /
+    /// `Signature` 89). Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_data(&mut self, len_tag: u32, data_tag: u32, data: &[u8]) -> &mut Self {
+        self.fields
+            .push((len_tag, FixValue::UInt(data.len() as u64)));
+        self.fields.push((data_tag, FixValue::Data(data.to_vec())));
+        self
+    }
+
+    /// Append a `MultipleStringValue` / `MultipleCharValue` field, joining
+    /// `values` with single spaces (e.g., `ExecInst` = `"6 G"`).
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn field_multiple(&mut self, tag: u32, values: &[&str]) -> &mut Self {
+        self.fields.push((tag, FixValue::from(values)));
+        self
+    }
+
+    /// Append a repeating group: the count tag followed by every entry,
+    /// each starting with the group's delimiter tag.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn group(&mut self, group: &RepeatingGroup) -> &mut Self {
+        self.fields.extend(
+            group
+                .serialize()
+                .into_iter()
+                .map(|(t, v)| (t, FixValue::Str(v))),
+        );
+        self
+    }
+
+    /// Serialize the message to FIX wire format.
+    ///
+    /// The returned bytes include the leading "8=..." and trailing "10=..."
+    /// fields with correctly computed `BodyLength` and Checksum.
+    ///
+    /// # Panics
+    ///
+    /// With [`Self::assert_required`] enabled and debug assertions on, panics
+    /// if a required field is missing.
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        self.debug_assert_required();
+        self.serialize()
+    }
+
+    /// Serialize the message by appending it to `out`, reusing its capacity.
+    ///
+    /// Produces the same bytes as [`Self::build`] without allocating a new
+    /// frame; bytes already in `out` are kept, so call `out.clear()` first
+    /// to reuse a single buffer per message.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::build`].
+    pub fn build_into(&self, out: &mut Vec<u8>) {
+        self.debug_assert_required();
+        self.serialize_into(out);
+    }
+
+    /// [`Self::build_into`] for a [`bytes::BytesMut`] buffer.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::build`].
+    #[cfg(feature = "bytes")]
+    pub fn build_into_bytes(&self, out: &mut bytes::BytesMut) {
+        self.debug_assert_required();
+        self.serialize_into(out);
+    }
+
+    /// Serialize a deliberately malformed frame for exercising parser and
+    /// counterparty error paths (feature `testing`).
+    ///
+    /// `BodyLength` and `CheckSum` are replaced as requested by `overrides`;
+    /// anything not overridden is computed normally, so a wrong body length
+    /// still carries a checksum that matches the bytes actually sent.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn build_with(&self, overrides: &FrameOverrides) -> Vec<u8> {
+        let frame = self.serialize();
+        let body_start = frame
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == SOH)
+            .nth(1)
+            .map_or(0, |(i, _)| i + 1);
+        let body = &frame[body_start..frame.len() - 7];
+
+        let mut out = Vec::with_capacity(frame.len());
+        append_field(&mut out, tag::BEGIN_STRING, self.begin_string.as_bytes());
+        let body_length = overrides.body_length.unwrap_or(body.len());
+        append_uint_field(&mut out, tag::BODY_LENGTH, body_length as u64);
+        out.extend_from_slice(body);
+        if !overrides.omit_checksum {
+            let chk = overrides.checksum.unwrap_or_else(|| compute_checksum(&out));
+            append_field(&mut out, tag::CHECKSUM, format!("{chk:03}").as_bytes());
+        }
+        out
+    }
+
+    /// Serialize the message straight into `writer` (a socket, file or
+    /// `BufWriter`), returning the number of bytes written.
+    ///
+    /// The frame is staged in a per-thread scratch buffer that is reused
+    /// across calls, so streaming a batch allocates no per-message `Vec`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `writer`.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::build`].
+    pub fn build_to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<usize> {
+        self.debug_assert_required();
+        let mut buf = SCRATCH.take();
+        buf.clear();
+        self.serialize_into(&mut buf);
+        let result = writer.write_all(&buf).map(|()| buf.len());
+        SCRATCH.set(buf);
+        result
+    }
+
+    /// [`Self::build_to_writer`] for a `tokio::io::AsyncWrite`.
+    ///
+    /// The frame is staged in `buf`, which is cleared first; pass the same
+    /// buffer across calls to avoid a per-message allocation. Unlike the
+    /// sync version this keeps no per-thread state, since the task may
+    /// resume on another thread or be dropped mid-write.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `writer`.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::build`].
+    #[cfg(feature = "tokio")]
+    pub async fn build_to_async_writer<W>(
+        &self,
+        writer: &mut W,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<usize>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        self.debug_assert_required();
+        buf.clear();
+        self.serialize_into(buf);
+        writer.write_all(buf).await?;
+        Ok(buf.len())
+    }
+
+    /// Panic (debug builds only) if [`Self::assert_required`] is enabled and
+    /// a required field is missing.
+    fn debug_assert_required(&self) {
+        if self.assert_required {
+            let missing = self.missing_required();
+            debug_assert!(
+                missing.is_empty(),
+                "FIX MsgType {} is missing required tags {missing:?}",
+                self.msg_type
+            );
+        }
+    }
+
+    /// Exact size in bytes of the frame [`Self::build`] would produce,
+    /// computed from the field tags and values without serializing.
+    ///
+    /// [`Self::build`] uses it to allocate the output once. Returns `None`
+    /// when a [`Signer`] is installed, since the signature length is only
+    /// known after signing.
+    #[must_use]
+    pub fn encoded_len(&self) -> Option<usize> {
+        if self.signer.is_some() {
+            return None;
+        }
+        // "35=<msg_type>\x01" + user fields.
+        let mut body = 3 + self.msg_type.len() + 1;
+        body += self
+            .fields
+            .iter()
+            .map(|(t, v)| value_field_len(*t, v))
+            .sum::<usize>();
+        if self.auto_sending_time && !self.fields.iter().any(|(t, _)| *t == tag::SENDING_TIME) {
+            // "52=YYYYMMDD-HH:MM:SS[.fff...]\x01"
+            let digits = self.precision.digits() as usize;
+            body += 3 + 17 + if digits > 0 { digits + 1 } else { 0 } + 1;
+        }
+        let prefix = 2 + self.begin_string.len() + 1 + 2 + uint_len(body as u64) + 1;
+        Some(prefix + body + 7)
+    }
+
+    /// Serialize without any required-field checks.
+    fn serialize(&self) -> Vec<u8> {
+        let capacity = self.encoded_len().unwrap_or(64 + self.fields.len() * 16);
+        let mut out = Vec::with_capacity(capacity);
+        self.serialize_into(&mut out);
+        out
+    }
+
+    /// Append the serialized frame to `out`.
+    ///
+    /// The body is written first, then `8=`/`9=` are appended and rotated to
+    /// the front of the frame, so no intermediate buffer is needed.
+    fn serialize_into<B: FrameBuffer + ?Sized>(&self, out: &mut B) {
+        let start = out.len();
+
+        // Body: "35=<msg_type>\x01" + user fields.
+        append_field(out, tag::MSG_TYPE, self.msg_type.as_bytes());
+        let stamp =
+            self.auto_sending_time && !self.fields.iter().any(|(t, _)| *t == tag::SENDING_TIME);
+        if self.raw_order {
+            self.append_fields_raw(out, stamp);
+        } else {
+            // Header fields in standard order, then the rest in call order.
+            for &h in HEADER_TAG_ORDER {
+                if stamp && h == tag::SENDING_TIME {
+                    append_field(out, tag::SENDING_TIME, self.now_stamp().as_bytes());
+                }
+                for (t, v) in self.fields.iter().filter(|(t, _)| *t == h) {
+                    append_value(out, *t, v);
+                }
+            }
+            for (t, v) in self
+                .fields
+                .iter()
+                .filter(|(t, _)| !HEADER_TAG_ORDER.contains(t))
+            {
+                append_value(out, *t, v);
+            }
+        }
+
+        // Trailer signature: "93=<len>\x01" + "89=<signature>\x01".
+        if let Some(signer) = &self.signer {
+            let signature = signer.sign(&out.bytes()[start..]);
+            append_uint_field(out, tag::SIGNATURE_LENGTH, signature.len() as u64);
+            append_field(out, tag::SIGNATURE, &signature);
+        }
+
+        // Prefix: "8=<begin_string>\x01" + "9=<body_length>\x01", moved
+        // in front of the body.
+        let body_len = out.len() - start;
+        append_field(out, tag::BEGIN_STRING, self.begin_string.as_bytes());
+        append_uint_field(out, tag::BODY_LENGTH, body_len as u64);
+        let prefix_len = out.len() - start - body_len;
+        out.bytes_mut()[start..].rotate_right(prefix_len);
+
+        // Append "10=<chk>\x01" (checksum is always 3 digits, zero-padded).
+        let chk = compute_checksum(&out.bytes()[start..]);
+        out.extend_from_slice(&[
+            b'1',
+            b'0',
+            b'=',
+            b'0' + chk / 100,
+            b'0' + chk / 10 % 10,
+            b'0' + chk % 10,
+            SOH,
+        ]);
+    }
+
+    /// Append the fields in call order, stamping `SendingTime` after the
+    /// last standard header field when `stamp` is set.
+    fn append_fields_raw<B: FrameBuffer + ?Sized>(&self, out: &mut B, stamp: bool) {
+        let stamp_at = stamp.then(|| {
+            self.fields
+                .iter()
+                .rposition(|(t, _)| REQUIRED_HEADER_TAGS.contains(t))
+                .map_or(0, |i| i + 1)
+        });
+        for (i, (t, v)) in self.fields.iter().enumerate() {
+            if stamp_at == Some(i) {
+                append_field(out, tag::SENDING_TIME, self.now_stamp().as_bytes());
+            }
+            append_value(out, *t, v);
+        }
+        if stamp_at == Some(self.fields.len()) {
+            append_field(out, tag::SENDING_TIME, self.now_stamp().as_bytes());
         }
     }
 
-    /// Append a string tag/value pair to the message body.
-    ///
-    /// Returns `&mut self` for method chaining.
-    #[inline(always)]
-    pub fn field(&mut self, tag: u32, value: &str) -> &mut Self {
-        self.fields.push((tag, value.to_string()));
-        self
+    /// Current clock time as a `UTCTimestamp` at the configured precision.
+    fn now_stamp(&self) -> String {
+        let now = self
+            .clock
+            .as_ref()
+            .map_or_else(|| SystemClock.now_ns(), |c| c.now_ns());
+        format_utc_timestamp_with(now, self.precision)
     }
+}
 
-    /// Append an `i64` value for the given tag.
-    ///
-    /// Returns `&mut self` for method chaining.
-    #[inline(always)]
-    pub fn field_i64(&mut self, tag: u32, value: i64) -> &mut Self {
-        self.fields.push((tag, value.to_string()));
+/// Growable byte buffer a frame can be serialized into.
+trait FrameBuffer {
+    fn len(&self) -> usize;
+    fn extend_from_slice(&mut self, bytes: &[u8]);
+    fn bytes(&self) -> &[u8];
+    fn bytes_mut(&mut self) -> &mut [u8];
+}
+
+impl FrameBuffer for Vec<u8> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+    fn bytes(&self) -> &[u8] {
         self
     }
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
 
-    /// Append a `u64` value for the given tag.
-    ///
-    /// Returns `&mut self` for method chaining.
-    #[inline(always)]
-    pub fn field_u64(&mut self, tag: u32, value: u64) -> &mut Self {
-        self.fields.push((tag, value.to_string()));
+#[cfg(feature = "bytes")]
+impl FrameBuffer for bytes::BytesMut {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+    fn bytes_mut(&mut self) -> &mut [u8] {
         self
     }
+}
 
-    /// Serialize the message to FIX wire format.
-    ///
-    /// The returned bytes include the leading "8=..." and trailing "10=..."
-    /// fields with correctly computed `BodyLength` and Checksum.
-    #[must_use]
-    pub fn build(&self) -> Vec<u8> {
-        // Build the body: "35=<msg_type>\x01" + user fields.
-        let mut body: Vec<u8> = Vec::new();
-        append_field(&mut body, tag::MSG_TYPE, &self.msg_type);
-        for (t, v) in &self.fields {
-            append_field(&mut body, *t, v);
+/// Append the decimal digits of `n` without allocating.
+#[inline(always)]
+fn append_uint<B: FrameBuffer + ?Sized>(buf: &mut B, mut n: u64) {
+    let mut digits = [0u8; 20];
+    let mut i = digits.len();
+    loop {
+        i -= 1;
+        digits[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
         }
+    }
+    buf.extend_from_slice(&digits[i..]);
+}
 
-        // Prefix: "8=<begin_string>\x01" + "9=<body_length>\x01"
-        let mut prefix: Vec<u8> = Vec::new();
-        append_field(&mut prefix, tag::BEGIN_STRING, &self.begin_string);
-        append_field(&mut prefix, tag::BODY_LENGTH, &body.len().to_string());
+/// Append `"<tag>=<value>\x01"` to `buf`.
+#[inline(always)]
+fn append_field<B: FrameBuffer + ?Sized>(buf: &mut B, tag: u32, value: &[u8]) {
+    append_uint(buf, u64::from(tag));
+    buf.extend_from_slice(b"=");
+    buf.extend_from_slice(value);
+    buf.extend_from_slice(&[SOH]);
+}
 
-        // Assemble everything before the checksum.
-        let mut out: Vec<u8> = Vec::with_capacity(prefix.len() + body.len() + 7);
-        out.extend_from_slice(&prefix);
-        out.extend_from_slice(&body);
+/// Append `"<tag>=<n>\x01"` to `buf`.
+#[inline(always)]
+fn append_uint_field<B: FrameBuffer + ?Sized>(buf: &mut B, tag: u32, n: u64) {
+    append_uint(buf, u64::from(tag));
+    buf.extend_from_slice(b"=");
+    append_uint(buf, n);
+    buf.extend_from_slice(&[SOH]);
+}
 
-        // Compute checksum over all bytes so far.
-        let chk = compute_checksum(&out);
+/// Append `"<tag>=<rendered value>\x01"` to `buf`.
+#[inline(always)]
+fn append_value<B: FrameBuffer + ?Sized>(buf: &mut B, tag: u32, value: &FixValue) {
+    match value {
+        FixValue::Str(s) => append_field(buf, tag, s.as_bytes()),
+        FixValue::Data(d) => append_field(buf, tag, d),
+        FixValue::Bool(b) => append_field(buf, tag, if *b { b"Y" } else { b"N" }),
+        FixValue::UInt(n) => append_uint_field(buf, tag, *n),
+        FixValue::Int(n) => {
+            append_uint(buf, u64::from(tag));
+            buf.extend_from_slice(if *n < 0 { b"=-" } else { b"=" });
+            append_uint(buf, n.unsigned_abs());
+            buf.extend_from_slice(&[SOH]);
+        }
+        other => {
+            append_uint(buf, u64::from(tag));
+            buf.extend_from_slice(b"=");
+            let _ = write!(FmtSink(buf), "{other}");
+            buf.extend_from_slice(&[SOH]);
+        }
+    }
+}
 
-        // Append "10=<chk>\x01" (checksum is always 3 digits, zero-padded).
-        out.extend_from_slice(format!("10={chk:03}").as_bytes());
-        out.push(SOH);
+/// Number of decimal digits in `n`.
+#[inline(always)]
+const fn uint_len(n: u64) -> usize {
+    if n == 0 {
+        1
+    } else {
+        n.ilog10() as usize + 1
+    }
+}
 
-        out
+/// Length of `"<tag>=<rendered value>\x01"`, as [`append_value`] writes it.
+fn value_field_len(tag: u32, value: &FixValue) -> usize {
+    let value_len = match value {
+        FixValue::Str(s) => s.len(),
+        FixValue::Data(d) => d.len(),
+        FixValue::Bool(_) => 1,
+        FixValue::UInt(n) => uint_len(*n),
+        FixValue::Int(n) => uint_len(n.unsigned_abs()) + usize::from(*n < 0),
+        other => {
+            let mut counter = FmtCounter(0);
+            let _ = write!(counter, "{other}");
+            counter.0
+        }
+    };
+    uint_len(u64::from(tag)) + 1 + value_len + 1
+}
+
+/// [`fmt::Write`] adapter appending to a [`FrameBuffer`].
+struct FmtSink<'a, B: ?Sized>(&'a mut B);
+
+impl<B: FrameBuffer + ?Sized> fmt::Write for FmtSink<'_, B> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
     }
 }
 
-/// Append `"<tag>=<value>\x01"` to `buf`.
-#[inline(always)]
-fn append_field(buf: &mut Vec<u8>, tag: u32, value: &str) {
-    buf.extend_from_slice(tag.to_string().as_bytes());
-    buf.push(b'=');
-    buf.extend_from_slice(value.as_bytes());
-    buf.push(SOH);
+/// [`fmt::Write`] that only counts bytes.
+struct FmtCounter(usize);
+
+impl fmt::Write for FmtCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
 }
 
 /// Compute the FIX checksum: sum of all byte values, modulo 256.
@@ -134,10 +1213,11 @@ mod tests {
     use super::*;
     use crate::parser;
     use crate::tag;
+    use crate::version::FixVersion;
 
     #[test]
     fn test_build_simple_message() {
-        let bytes = FixBuilder::new("FIX.4.4", "0")
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
             .field(tag::SENDER_COMP_ID, "ALICE")
             .field(tag::TARGET_COMP_ID, "BROKER")
             .field(tag::MSG_SEQ_NUM, "1")
@@ -153,7 +1233,7 @@ mod tests {
 
     #[test]
     fn test_build_includes_msg_type() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "X")
             .build();
         let msg = parser::parse(&bytes).unwrap();
@@ -162,7 +1242,7 @@ mod tests {
 
     #[test]
     fn test_roundtrip_build_parse() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "ALICE")
             .field(tag::TARGET_COMP_ID, "BROKER")
             .field(tag::MSG_SEQ_NUM, "5")
@@ -191,7 +1271,7 @@ mod tests {
 
     #[test]
     fn test_field_i64() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "X")
             .field_i64(tag::PRICE, -100)
             .build();
@@ -201,7 +1281,7 @@ mod tests {
 
     #[test]
     fn test_checksum_is_three_digits() {
-        let bytes = FixBuilder::new("FIX.4.4", "0")
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
             .field(tag::SENDER_COMP_ID, "A")
             .build();
         // The last 7 bytes are "10=XXX\x01"
@@ -218,7 +1298,7 @@ mod tests {
 
     #[test]
     fn test_build_field_u64() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "X")
             .field_u64(tag::ORDER_QTY, 500)
             .build();
@@ -228,7 +1308,7 @@ mod tests {
 
     #[test]
     fn test_build_field_i64_positive() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "X")
             .field_i64(tag::PRICE, 99999)
             .build();
@@ -239,7 +1319,7 @@ mod tests {
     #[test]
     fn test_build_no_user_fields() {
         // Build a message with only the mandatory type and version.
-        let bytes = FixBuilder::new("FIX.4.4", "0").build();
+        let bytes = FixBuilder::new(BeginString::Fix44, "0").build();
         let msg = parser::parse(&bytes).unwrap();
         assert_eq!(msg.msg_type, "0");
         assert_eq!(msg.begin_string, "FIX.4.4");
@@ -247,7 +1327,7 @@ mod tests {
 
     #[test]
     fn test_build_body_length_is_correct() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "ALICE")
             .build();
         // Extract body length from the raw bytes.
@@ -264,7 +1344,7 @@ mod tests {
 
     #[test]
     fn test_build_multiple_fields_order_preserved() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "A")
             .field(tag::TARGET_COMP_ID, "B")
             .field(tag::CL_ORD_ID, "ORD-1")
@@ -279,7 +1359,7 @@ mod tests {
 
     #[test]
     fn test_build_fixt11() {
-        let bytes = FixBuilder::new("FIXT.1.1", "A").build();
+        let bytes = FixBuilder::new(FixVersion::Fix50, "A").build();
         let msg = parser::parse(&bytes).unwrap();
         assert_eq!(msg.begin_string, "FIXT.1.1");
         assert_eq!(msg.msg_type, "A");
@@ -287,17 +1367,632 @@ mod tests {
 
     #[test]
     fn test_build_empty_value_field() {
-        let bytes = FixBuilder::new("FIX.4.4", "0").field(tag::TEXT, "").build();
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::TEXT, "")
+            .build();
         let msg = parser::parse(&bytes).unwrap();
         assert_eq!(msg.get(tag::TEXT), Some(""));
     }
 
+    #[test]
+    fn test_build_group_roundtrip() {
+        use crate::repeating_group::GroupEntry;
+
+        let mut group = RepeatingGroup::new(tag::NO_PARTY_IDS, tag::PARTY_ID);
+        for (id, role) in [("FIRM", "1"), ("DESK", "3")] {
+            let mut e = GroupEntry::new();
+            e.set(tag::PARTY_ROLE, role).set(tag::PARTY_ID, id);
+            group.add_entry(e);
+        }
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .group(&group)
+            .field(tag::SYMBOL, "BTCUSD")
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        let parties = msg.groups(tag::NO_PARTY_IDS);
+        assert_eq!(parties.len(), 2);
+        assert_eq!(parties[1].get(tag::PARTY_ID), Some("DESK"));
+        assert_eq!(parties[1].get(tag::PARTY_ROLE), Some("3"));
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+    }
+
+    #[test]
+    fn test_build_field_value_typed() {
+        use crate::value::Decimal;
+
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field_value(tag::PRICE, Decimal::new(5_000_025, 2))
+            .field_value(tag::ORDER_QTY, 10u64)
+            .field_value(tag::TEXT, "typed")
+            .field_value(43, true)
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::PRICE), Some("50000.25"));
+        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(10));
+        assert_eq!(msg.get(tag::TEXT), Some("typed"));
+        assert_eq!(msg.get_bool(43), Some(true));
+    }
+
+    #[test]
+    fn test_build_field_multiple() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field_multiple(tag::EXEC_INST, &["6", "G", "E"])
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::EXEC_INST), Some("6 G E"));
+        assert_eq!(msg.get_multiple(tag::EXEC_INST).last(), Some("E"));
+    }
+
+    fn header(b: &mut FixBuilder) -> &mut FixBuilder {
+        b.field(tag::SENDER_COMP_ID, "A")
+            .field(tag::TARGET_COMP_ID, "B")
+            .field_u64(tag::MSG_SEQ_NUM, 1)
+            .field(tag::SENDING_TIME, "20260101-00:00:00")
+    }
+
+    #[test]
+    fn test_missing_required_lists_header_and_body() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::SENDER_COMP_ID, "A")
+            .field(tag::SYMBOL, "BTCUSD");
+        assert_eq!(
+            b.missing_required(),
+            vec![
+                tag::TARGET_COMP_ID,
+                tag::MSG_SEQ_NUM,
+                tag::SENDING_TIME,
+                tag::CL_ORD_ID,
+                tag::SIDE,
+                tag::TRANSACT_TIME,
+                tag::ORD_TYPE,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_build_reports_first_missing_tag() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "1");
+        header(&mut b);
+        assert_eq!(
+            b.try_build(),
+            Err(BuildError::MissingRequiredField {
+                msg_type: "1".into(),
+                tag: tag::TEST_REQ_ID,
+            })
+        );
+        b.field(tag::TEST_REQ_ID, "T1");
+        let bytes = b.try_build().unwrap();
+        assert_eq!(bytes, b.build());
+    }
+
+    #[test]
+    fn test_try_build_unknown_msg_type_needs_header_only() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "ZZ");
+        assert!(b.try_build().is_err());
+        header(&mut b);
+        assert!(b.try_build().is_ok());
+    }
+
+    #[test]
+    fn test_build_error_display() {
+        let err = BuildError::MissingRequiredField {
+            msg_type: "D".into(),
+            tag: 11,
+        };
+        assert_eq!(err.to_string(), "MsgType D: missing required tag 11");
+    }
+
+    #[test]
+    fn test_assert_required_passes_when_complete() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        header(&mut b).assert_required(true);
+        assert!(parser::parse(&b.build()).is_ok());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "missing required tags")]
+    fn test_assert_required_panics_in_debug() {
+        let _ = FixBuilder::new(BeginString::Fix44, "D")
+            .assert_required(true)
+            .field(tag::SYMBOL, "BTCUSD")
+            .build();
+    }
+
+    #[test]
+    fn test_build_without_assertion_ignores_missing_fields() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D").build();
+        assert!(parser::parse(&bytes).is_ok());
+    }
+
     #[test]
     fn test_build_large_seq_number() {
-        let bytes = FixBuilder::new("FIX.4.4", "0")
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
             .field_u64(tag::MSG_SEQ_NUM, 999_999_999)
             .build();
         let msg = parser::parse(&bytes).unwrap();
         assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(999_999_999));
     }
+
+    #[test]
+    fn test_signature_in_trailer_order() {
+        // Toy signer: hex-encoded byte sum of the signed body.
+        let signer = |body: &[u8]| {
+            let sum: u32 = body.iter().map(|&b| u32::from(b)).sum();
+            format!("{sum:08x}").into_bytes()
+        };
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::SYMBOL, "BTCUSD")
+            .sign_with(Arc::new(signer))
+            .build();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        let sig_at = text.find("\x0193=8\x0189=").unwrap() + 1;
+        assert!(text[sig_at..].starts_with("93=8\x0189="));
+        assert!(text[sig_at + 16..].starts_with("\x0110="));
+
+        let body_at = text.find("35=").unwrap();
+        let expected = signer(&bytes[body_at..sig_at]);
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(
+            msg.get(tag::SIGNATURE).map(str::as_bytes),
+            Some(&expected[..])
+        );
+        assert_eq!(msg.get_u64(tag::SIGNATURE_LENGTH), Some(8));
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+    }
+
+    #[test]
+    fn test_from_message_keeps_wire_order() {
+        let mut original = FixBuilder::new(BeginString::Fix44, "D");
+        original
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, 7)
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(tag::CL_ORD_ID, "ORD-1")
+            .field(tag::SIDE, "1");
+        let unsigned = original.build();
+        let signed = original
+            .sign_with(Arc::new(|_: &[u8]| b"SIG".to_vec()))
+            .build();
+
+        let mut msg = parser::parse_with_spans(&signed).unwrap();
+        assert_eq!(FixBuilder::from_message(&msg).build(), unsigned);
+
+        // Amend and resend: new fields follow the original ones.
+        msg.set(tag::TEXT, "ack");
+        let amended = FixBuilder::from_message(&msg).build();
+        let text = String::from_utf8(amended).unwrap();
+        assert!(text.contains("55=BTCUSD\x0111=ORD-1\x0154=1\x0158=ack\x01"));
+    }
+
+    #[test]
+    fn test_from_message_without_spans_uses_header_then_tag_order() {
+        let raw = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(tag::CL_ORD_ID, "ORD-1")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .build();
+        let msg = parser::parse(&raw).unwrap();
+        let text = String::from_utf8(FixBuilder::from_message(&msg).build()).unwrap();
+        assert!(text.contains("35=D\x0149=ALICE\x0111=ORD-1\x0155=BTCUSD\x01"));
+    }
+
+    #[test]
+    fn test_sending_time_now_uses_clock_and_precision() {
+        use crate::clock::FixedClock;
+
+        let ns = 1_767_225_600 * 1_000_000_000 + 123_456_789;
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
+            .clock(Arc::new(FixedClock(ns)))
+            .timestamp_precision(TimestampPrecision::Micros)
+            .sending_time_now()
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::SENDING_TIME), Some("20260101-00:00:00.123456"));
+    }
+
+    #[test]
+    fn test_auto_sending_time_fills_header_slot() {
+        use crate::clock::FixedClock;
+
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.clock(Arc::new(FixedClock(1_767_225_600 * 1_000_000_000)))
+            .auto_sending_time(true)
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, 1)
+            .field(tag::TEST_REQ_ID, "T1");
+        assert!(b.missing_required().is_empty());
+        let text = String::from_utf8(b.try_build().unwrap()).unwrap();
+        assert!(text.contains("34=1\x0152=20260101-00:00:00.000\x01112=T1\x01"));
+
+        // An explicit SendingTime is left alone.
+        b.field(tag::SENDING_TIME, "20250101-00:00:00");
+        let msg = parser::parse(&b.build()).unwrap();
+        assert_eq!(msg.get(tag::SENDING_TIME), Some("20250101-00:00:00"));
+    }
+
+    #[test]
+    fn test_field_f64_has_no_exponent() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field_f64(tag::PRICE, 1e21)
+            .field_f64(tag::AVG_PX, 1e-7)
+            .field_f64(tag::ORDER_QTY, -0.0)
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::PRICE), Some("1000000000000000000000"));
+        assert_eq!(msg.get(tag::AVG_PX), Some("0.0000001"));
+        assert_eq!(msg.get(tag::ORDER_QTY), Some("0"));
+    }
+
+    #[test]
+    fn test_field_decimal_fixed_scale() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field_decimal(tag::PRICE, Decimal::new(12_345, 3), 2)
+            .field_decimal(tag::ORDER_QTY, Decimal::new(5, 0), 4)
+            .field_decimal(tag::AVG_PX, Decimal::from_f64(0.1 + 0.2, 8).unwrap(), 2)
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::PRICE), Some("12.35"));
+        assert_eq!(msg.get(tag::ORDER_QTY), Some("5.0000"));
+        assert_eq!(msg.get(tag::AVG_PX), Some("0.30"));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-finite")]
+    fn test_field_f64_rejects_nan() {
+        let _ = FixBuilder::new(BeginString::Fix44, "D").field_f64(tag::PRICE, f64::NAN);
+    }
+
+    #[test]
+    fn test_build_into_matches_build_and_appends() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::SENDER_COMP_ID, "ALICE")
+            .field_u64(tag::MSG_SEQ_NUM, 42)
+            .field_i64(tag::AVG_PX, -1)
+            .field_f64(tag::PRICE, 50_000.5)
+            .field(tag::TEXT, "hi");
+        let mut out = b"prev".to_vec();
+        b.build_into(&mut out);
+        assert_eq!(&out[..4], b"prev");
+        assert_eq!(&out[4..], b.build().as_slice());
+        assert!(parser::parse(&out[4..]).is_ok());
+    }
+
+    #[test]
+    fn test_build_into_reuses_capacity() {
+        let mut buf = Vec::with_capacity(256);
+        let ptr = buf.as_ptr();
+        for seq in 1..=3u64 {
+            buf.clear();
+            FixBuilder::new(BeginString::Fix44, "0")
+                .field_u64(tag::MSG_SEQ_NUM, seq)
+                .build_into(&mut buf);
+            assert_eq!(
+                parser::parse(&buf).unwrap().get(tag::MSG_SEQ_NUM),
+                Some(seq.to_string().as_str())
+            );
+        }
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_build_into_with_signature() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.field_u64(tag::MSG_SEQ_NUM, 1)
+            .sign_with(Arc::new(|body: &[u8]| body.len().to_string().into_bytes()));
+        let mut out = Vec::new();
+        b.build_into(&mut out);
+        assert_eq!(out, b.build());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_build_into_bytes() {
+        let b = FixBuilder::new(BeginString::Fix44, "0");
+        let mut out = bytes::BytesMut::with_capacity(128);
+        b.build_into_bytes(&mut out);
+        assert_eq!(&out[..], b.build().as_slice());
+    }
+
+    #[test]
+    fn test_field_opt_skips_none() {
+        let price: Option<f64> = None;
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field_opt(tag::TEXT, None)
+            .field_opt(tag::SYMBOL, Some("BTCUSD"))
+            .field_opt_f64(tag::PRICE, price)
+            .field_opt_u64(tag::ORDER_QTY, Some(5))
+            .field_opt_i64(tag::AVG_PX, None)
+            .field_opt_decimal(tag::AVG_PX, Some(Decimal::new(15, 1)), 2)
+            .field_opt_value(tag::CL_ORD_ID, Some("A1"))
+            .field_opt_value::<bool>(tag::POSS_DUP_FLAG, None)
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert!(!msg.contains(tag::TEXT));
+        assert!(!msg.contains(tag::PRICE));
+        assert!(!msg.contains(tag::POSS_DUP_FLAG));
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+        assert_eq!(msg.get(tag::ORDER_QTY), Some("5"));
+        assert_eq!(msg.get(tag::AVG_PX), Some("1.50"));
+        assert_eq!(msg.get(tag::CL_ORD_ID), Some("A1"));
+    }
+
+    #[test]
+    fn test_header_fields_precede_body_regardless_of_call_order() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::CL_ORD_ID, "ORD-1")
+            .field(tag::SENDING_TIME, "20260101-00:00:00")
+            .field(tag::SYMBOL, "BTCUSD")
+            .field_u64(tag::MSG_SEQ_NUM, 3)
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field(tag::SENDER_COMP_ID, "ALICE");
+        let text = String::from_utf8(b.build()).unwrap();
+        assert!(text.contains(
+            "35=D\x0149=ALICE\x0156=BROKER\x0134=3\x0152=20260101-00:00:00\x01\
+             11=ORD-1\x0155=BTCUSD\x0110="
+        ));
+
+        // Escape hatch: strict call order.
+        let text = String::from_utf8(b.raw_field_order(true).build()).unwrap();
+        assert!(text.contains("35=D\x0111=ORD-1\x0152=20260101-00:00:00\x0155=BTCUSD\x01"));
+        assert!(parser::parse(text.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_auto_sending_time_takes_header_position() {
+        use crate::clock::FixedClock;
+
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.clock(Arc::new(FixedClock(1_767_225_600 * 1_000_000_000)))
+            .auto_sending_time(true)
+            .field(tag::TEST_REQ_ID, "T1")
+            .field(tag::ORIG_SENDING_TIME, "20251231-23:59:59.000")
+            .field(tag::POSS_DUP_FLAG, "Y")
+            .field_u64(tag::MSG_SEQ_NUM, 1);
+        let text = String::from_utf8(b.build()).unwrap();
+        assert!(text.contains(
+            "35=0\x0134=1\x0143=Y\x0152=20260101-00:00:00.000\x01\
+             122=20251231-23:59:59.000\x01112=T1\x01"
+        ));
+    }
+
+    #[test]
+    fn test_from_message_with_spans_keeps_nonstandard_order() {
+        let raw = FixBuilder::new(BeginString::Fix44, "0")
+            .raw_field_order(true)
+            .field(tag::TEST_REQ_ID, "T1")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .build();
+        let msg = parser::parse_with_spans(&raw).unwrap();
+        assert_eq!(FixBuilder::from_message(&msg).build(), raw);
+    }
+
+    #[test]
+    fn test_field_data_writes_length_and_raw_bytes() {
+        let data = b"a\x01b=c";
+        let bytes = FixBuilder::new(BeginString::Fix44, "B")
+            .field(tag::TEXT, "x")
+            .field_data(tag::RAW_DATA_LENGTH, tag::RAW_DATA, data)
+            .field_data(tag::XML_DATA_LEN, tag::XML_DATA, b"<x/>")
+            .build();
+        let body = b"35=B\x0158=x\x0195=5\x0196=a\x01b=c\x01212=4\x01213=<x/>\x01";
+        let prefix = format!("8=FIX.4.4\x019={}\x01", body.len());
+        assert!(bytes.starts_with(prefix.as_bytes()));
+        assert_eq!(&bytes[prefix.len()..prefix.len() + body.len()], body);
+        let chk = compute_checksum(&bytes[..bytes.len() - 7]);
+        assert!(bytes.ends_with(format!("10={chk:03}\x01").as_bytes()));
+    }
+
+    #[test]
+    fn test_validate_against_dictionary() {
+        use crate::repeating_group::GroupEntry;
+
+        let dict = Dictionary::fix44();
+        let mut parties = RepeatingGroup::new(tag::NO_PARTY_IDS, tag::PARTY_ID);
+        let mut entry = GroupEntry::new();
+        entry.set(tag::PARTY_ID, "DESK1").set(tag::PARTY_ROLE, "11");
+        parties.add_entry(entry);
+
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, 2)
+            .field(tag::CL_ORD_ID, "A1")
+            .field(tag::SYMBOL, "7203")
+            .field(tag::SIDE, "1")
+            .field(tag::TRANSACT_TIME, "20260101-00:00:00.000")
+            .field(tag::ORD_TYPE, "2")
+            .field_f64(tag::PRICE, 101.5)
+            .group(&parties);
+        assert_eq!(
+            b.validate(&dict),
+            Err(ValidationError::RequiredTagMissing {
+                tag: tag::SENDING_TIME
+            })
+        );
+        b.auto_sending_time(true);
+        assert_eq!(b.validate(&dict), Ok(()));
+
+        b.field(tag::SIDE, "Z");
+        assert_eq!(
+            b.validate(&dict),
+            Err(ValidationError::ValueOutOfRange {
+                tag: tag::SIDE,
+                value: "Z".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_typed_setters_round_trip_through_getters() {
+        let ts = 1_767_225_600_123_456_789;
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field_bool(tag::POSS_DUP_FLAG, true)
+            .field_bool(tag::POSS_RESEND, false)
+            .field_char(tag::SIDE, '1')
+            .field_timestamp(tag::TRANSACT_TIME, ts)
+            .timestamp_precision(TimestampPrecision::Micros)
+            .field_timestamp(tag::ORIG_SENDING_TIME, ts);
+        let text = String::from_utf8(b.build()).unwrap();
+        assert!(text.contains("\x0154=1\x0160=20260101-00:00:00.123\x01"));
+        assert!(text.contains("\x01122=20260101-00:00:00.123456\x01"));
+        let msg = parser::parse(text.as_bytes()).unwrap();
+        assert_eq!(msg.get_bool(tag::POSS_DUP_FLAG), Some(true));
+        assert_eq!(msg.get_bool(tag::POSS_RESEND), Some(false));
+        assert_eq!(msg.get_char(tag::SIDE), Some('1'));
+        assert_eq!(
+            msg.get_timestamp(tag::TRANSACT_TIME),
+            Some(1_767_225_600_123_000_000)
+        );
+    }
+
+    #[test]
+    fn test_build_with_overrides_produces_malformed_frames() {
+        use crate::parser::ParseError;
+
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER");
+        assert_eq!(b.build_with(&FrameOverrides::new()), b.build());
+
+        let valid = b.build();
+        let chk = compute_checksum(&valid[..valid.len() - 7]);
+        let bad_chk = b.build_with(&FrameOverrides::new().checksum(chk.wrapping_add(1)));
+        assert_eq!(bad_chk.len(), valid.len());
+        assert!(matches!(
+            parser::parse(&bad_chk),
+            Err(ParseError::InvalidChecksum { .. })
+        ));
+
+        let bad_len = b.build_with(&FrameOverrides::new().body_length(3));
+        assert!(bad_len.starts_with(b"8=FIX.4.4\x019=3\x0135=0\x01"));
+        assert!(parser::parse(&bad_len).is_err());
+
+        let no_trailer = b.build_with(&FrameOverrides::new().omit_checksum());
+        assert_eq!(no_trailer, valid[..valid.len() - 7]);
+        assert!(parser::parse(&no_trailer).is_err());
+    }
+
+    #[test]
+    fn test_reset_keeps_routing_header_and_capacity() {
+        let header = crate::header::HeaderTemplate::new(BeginString::Fix44, "ALICE", "BROKER")
+            .on_behalf_of("DESK1");
+        let mut b = header.builder("D");
+        b.field_u64(tag::MSG_SEQ_NUM, 1)
+            .field(tag::SENDING_TIME, "20260101-00:00:00.000")
+            .field(tag::CL_ORD_ID, "A1")
+            .field(tag::SYMBOL, "7203");
+        let capacity = b.fields.capacity();
+
+        b.reset("F")
+            .field_u64(tag::MSG_SEQ_NUM, 2)
+            .field(tag::SENDING_TIME, "20260101-00:00:01.000")
+            .field(tag::ORIG_CL_ORD_ID, "A1");
+        assert_eq!(b.fields.capacity(), capacity);
+        let expected = header
+            .builder("F")
+            .field_u64(tag::MSG_SEQ_NUM, 2)
+            .field(tag::SENDING_TIME, "20260101-00:00:01.000")
+            .field(tag::ORIG_CL_ORD_ID, "A1")
+            .build();
+        assert_eq!(b.build(), expected);
+
+        b.clear();
+        assert!(b.fields.is_empty());
+        assert_eq!(b.fields.capacity(), capacity);
+        assert!(b.build().starts_with(b"8=FIX.4.4\x019=5\x0135=F\x01"));
+    }
+
+    #[test]
+    fn test_build_to_writer_streams_frames() {
+        let mut out = Vec::new();
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.field(tag::SENDER_COMP_ID, "ALICE");
+        let mut expected = Vec::new();
+        for seq in 1..=3 {
+            b.reset("0").field_u64(tag::MSG_SEQ_NUM, seq);
+            let n = b.build_to_writer(&mut out).unwrap();
+            expected.extend_from_slice(&b.build());
+            assert_eq!(out.len(), expected.len());
+            assert!(n > 0);
+        }
+        assert_eq!(out, expected);
+
+        let mut full = [0u8; 8];
+        let err = b.build_to_writer(&mut &mut full[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_build_to_async_writer() {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        let mut b = FixBuilder::new(BeginString::Fix44, "0");
+        b.field(tag::SENDER_COMP_ID, "ALICE");
+        let mut out = Vec::new();
+        let mut buf = b"stale".to_vec();
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..2 {
+            let poll = std::pin::pin!(b.build_to_async_writer(&mut out, &mut buf)).poll(&mut cx);
+            let Poll::Ready(n) = poll else {
+                panic!("Vec<u8> writer is always ready");
+            };
+            assert_eq!(n.unwrap(), b.build().len());
+            assert_eq!(buf, b.build());
+        }
+        assert_eq!(out, [b.build(), b.build()].concat());
+    }
+
+    #[test]
+    fn test_encoded_len_matches_build() {
+        let clock: Arc<dyn Clock> = Arc::new(crate::clock::FixedClock(1_767_225_600_000_000_000));
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::SENDER_COMP_ID, "ALICE")
+            .field_u64(tag::MSG_SEQ_NUM, 1_234)
+            .field_i64(tag::ORDER_QTY, -5)
+            .field_i64(tag::PRICE, 0)
+            .field_decimal(tag::LAST_PX, Decimal::new(-12_345, 2), 4)
+            .field_value(tag::TRANSACT_TIME, FixValue::Timestamp(1))
+            .field_bool(tag::POSS_DUP_FLAG, true)
+            .field_multiple(tag::EXEC_INST, &["6", "G"])
+            .field_data(tag::RAW_DATA_LENGTH, tag::RAW_DATA, b"a\x01b");
+        assert_eq!(b.encoded_len(), Some(b.build().len()));
+
+        for precision in [
+            TimestampPrecision::Seconds,
+            TimestampPrecision::Millis,
+            TimestampPrecision::Nanos,
+        ] {
+            b.clock(Arc::clone(&clock))
+                .auto_sending_time(true)
+                .timestamp_precision(precision);
+            assert_eq!(b.encoded_len(), Some(b.build().len()));
+        }
+
+        b.sign_with(Arc::new(|_: &[u8]| b"sig".to_vec()));
+        assert_eq!(b.encoded_len(), None);
+    }
+
+    #[test]
+    fn test_set_remove_has_field() {
+        let mut b = FixBuilder::new(BeginString::Fix44, "D");
+        b.field(tag::CL_ORD_ID, "A1")
+            .field(tag::ORD_TYPE, "2")
+            .field(tag::PRICE, "101.5")
+            .field(tag::TIME_IN_FORCE, "0")
+            .field(tag::SYMBOL, "7203");
+        b.set_field(tag::TIME_IN_FORCE, "3")
+            .set_field(tag::ORD_TYPE, "1")
+            .remove_field(tag::PRICE)
+            .set_field_value(tag::ORDER_QTY, 100u64);
+        assert!(!b.has_field(tag::PRICE));
+        assert!(b.has_field(tag::ORDER_QTY));
+        let text = String::from_utf8(b.build()).unwrap();
+        assert!(text.contains("35=D\x0111=A1\x0140=1\x0159=3\x0155=7203\x0138=100\x0110="));
+    }
 }
@@ -0,0 +1,107 @@
+//! Wall Clock
+//!
+//! [`Clock`] は現在時刻 (Unix エポックからのナノ秒) を返す。
+//! [`crate::builder::FixBuilder::sending_time_now`] と自動 `SendingTime`
+//! 付与が使う。既定は [`SystemClock`]。テストでは [`FixedClock`] や
+//! クロージャ (`Fn() -> u64`) に差し替える。
+//!
+//! [`crate::session::FixSession`] の時刻依存処理 (`SendingTime`、
+//! ハートビート、`SendingTime` 遅延検査) もセッションの時計に従う。
+//! [`ManualClock`] を `Arc` で共有して進め、
+//! [`crate::session::FixSession::tick`] を呼べばタイマーを決定的に試験できる。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 現在時刻の供給元。
+pub trait Clock: Send + Sync {
+    /// Unix エポックからのナノ秒。
+    fn now_ns(&self) -> u64;
+}
+
+impl<F: Fn() -> u64 + Send + Sync> Clock for F {
+    fn now_ns(&self) -> u64 {
+        self()
+    }
+}
+
+/// OS の時計 ([`SystemTime`])。エポック以前は 0。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ns(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+    }
+}
+
+/// 常に同じ時刻を返す時計。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now_ns(&self) -> u64 {
+        self.0
+    }
+}
+
+/// 呼び出し側が進める時計。`Arc` で共有し、テスト中に時刻を動かす。
+#[derive(Debug, Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    /// `now_ns` を指す時計を作成。
+    #[must_use]
+    pub const fn new(now_ns: u64) -> Self {
+        Self(AtomicU64::new(now_ns))
+    }
+
+    /// 時刻を `now_ns` に設定。
+    pub fn set(&self, now_ns: u64) {
+        self.0.store(now_ns, Ordering::SeqCst);
+    }
+
+    /// 時刻を `delta_ns` 進め、進めた後の時刻を返す。
+    pub fn advance(&self, delta_ns: u64) -> u64 {
+        self.0.fetch_add(delta_ns, Ordering::SeqCst) + delta_ns
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ns(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_clock_is_after_2026() {
+        assert!(SystemClock.now_ns() > 1_767_225_600 * 1_000_000_000);
+    }
+
+    #[test]
+    fn fixed_and_closure_clocks() {
+        assert_eq!(FixedClock(42).now_ns(), 42);
+        let clock = || 7;
+        assert_eq!(clock.now_ns(), 7);
+    }
+
+    #[test]
+    fn manual_clock_moves_when_told() {
+        let clock = ManualClock::new(10);
+        assert_eq!(clock.now_ns(), 10);
+        assert_eq!(clock.advance(5), 15);
+        assert_eq!(clock.now_ns(), 15);
+        clock.set(3);
+        assert_eq!(clock.now_ns(), 3);
+    }
+}
@@ -0,0 +1,231 @@
+//! `tokio_util::codec` support (feature `codec`).
+//!
+//! [`FixCodec`] splits a byte stream into frames with
+//! [`parser::declared_frame_len`] and parses each one, so a
+//! `Framed<TcpStream, FixCodec>` yields whole [`FixMessage`]s and accepts
+//! [`FixMessage`]s (serialized with [`FixBuilder::from_message`]) or
+//! pre-built frames such as [`crate::session::SessionAction::Send`] payloads.
+//!
+//! ```
+//! use alice_fix::{builder::FixBuilder, codec::FixCodec, tag, BeginString};
+//! use bytes::BytesMut;
+//! use tokio_util::codec::Decoder;
+//!
+//! let frame = FixBuilder::new(BeginString::Fix44, "0")
+//!     .field(tag::SENDER_COMP_ID, "BROKER")
+//!     .field(tag::TARGET_COMP_ID, "ALICE")
+//!     .field(tag::MSG_SEQ_NUM, "1")
+//!     .build();
+//! let mut codec = FixCodec::new();
+//! let mut buf = BytesMut::from(&frame[..10]);
+//! assert!(codec.decode(&mut buf).unwrap().is_none());
+//! buf.extend_from_slice(&frame[10..]);
+//! assert_eq!(codec.decode(&mut buf).unwrap().unwrap().msg_type, "0");
+//! ```
+//!
+//! A frame that is delimited but does not parse (bad checksum, malformed
+//! field) is consumed and reported as [`CodecError::Parse`]; bytes that do
+//! not start a frame at all cannot be resynchronized and are reported the
+//! same way without being consumed.
+
+use std::io;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::builder::FixBuilder;
+use crate::message::FixMessage;
+use crate::parser::{self, ParseError};
+
+pub use crate::parser::DEFAULT_MAX_FRAME_LEN;
+
+/// Error produced by [`FixCodec`].
+#[derive(Debug)]
+pub enum CodecError {
+    /// Reading from or writing to the underlying transport failed.
+    Io(io::Error),
+    /// The next frame is malformed.
+    Parse(ParseError),
+    /// The next frame declares more than the codec's maximum frame length.
+    FrameTooLarge {
+        /// Length of the frame, from its `BodyLength`.
+        len: usize,
+        /// Configured maximum.
+        max: usize,
+    },
+}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::FrameTooLarge { len, max } => {
+                write!(f, "frame of {len} bytes exceeds the {max} byte limit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<io::Error> for CodecError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ParseError> for CodecError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+/// FIX frame codec for `tokio_util::codec::Framed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixCodec {
+    max_frame_len: usize,
+}
+
+impl Default for FixCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixCodec {
+    /// Codec accepting frames up to [`DEFAULT_MAX_FRAME_LEN`] bytes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Codec accepting inbound frames up to `max` bytes.
+    #[must_use]
+    pub const fn with_max_frame_len(max: usize) -> Self {
+        Self { max_frame_len: max }
+    }
+
+    /// Maximum inbound frame length, in bytes.
+    #[must_use]
+    pub const fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Length of the frame at the start of `src`, once it is complete.
+    fn next_frame_len(self, src: &[u8]) -> Result<Option<usize>, CodecError> {
+        // The declared length is known before the frame arrives, so an
+        // oversized frame is refused without buffering it.
+        let declared = parser::declared_frame_len(src)?;
+        if let Some(len) = declared.filter(|&len| len > self.max_frame_len) {
+            return Err(CodecError::FrameTooLarge {
+                len,
+                max: self.max_frame_len,
+            });
+        }
+        Ok(declared.filter(|&len| src.len() >= len))
+    }
+}
+
+impl Decoder for FixCodec {
+    type Item = FixMessage;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<FixMessage>, CodecError> {
+        let Some(len) = self.next_frame_len(src)? else {
+            return Ok(None);
+        };
+        let result = parser::parse(&src[..len]);
+        src.advance(len);
+        result.map(Some).map_err(CodecError::Parse)
+    }
+}
+
+impl Encoder<FixMessage> for FixCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, msg: FixMessage, dst: &mut BytesMut) -> Result<(), CodecError> {
+        FixBuilder::from_message(&msg).build_into_bytes(dst);
+        Ok(())
+    }
+}
+
+impl Encoder<Vec<u8>> for FixCodec {
+    type Error = CodecError;
+
+    /// Write a frame that is already serialized, e.g. from
+    /// [`crate::session::FixSession`], unchanged.
+    fn encode(&mut self, frame: Vec<u8>, dst: &mut BytesMut) -> Result<(), CodecError> {
+        dst.extend_from_slice(&frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag;
+    use crate::version::BeginString;
+
+    fn heartbeat(seq: u64) -> Vec<u8> {
+        FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, seq)
+            .field(tag::SENDING_TIME, "20260101-00:00:00")
+            .build()
+    }
+
+    #[test]
+    fn decodes_frames_as_they_complete() {
+        let mut stream = heartbeat(1);
+        stream.extend_from_slice(&heartbeat(2));
+        let mut codec = FixCodec::new();
+        let mut src = BytesMut::new();
+        let mut seqs = Vec::new();
+        for chunk in stream.chunks(7) {
+            src.extend_from_slice(chunk);
+            while let Some(msg) = codec.decode(&mut src).unwrap() {
+                seqs.push(msg.get_u64(tag::MSG_SEQ_NUM).unwrap());
+            }
+        }
+        assert_eq!(seqs, [1, 2]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn reports_bad_frames_and_oversized_lengths() {
+        let mut frame = heartbeat(1);
+        let n = frame.len();
+        frame[n - 2] = if frame[n - 2] == b'0' { b'1' } else { b'0' };
+        frame.extend_from_slice(&heartbeat(2));
+        let mut codec = FixCodec::new();
+        let mut src = BytesMut::from(&frame[..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(CodecError::Parse(ParseError::InvalidChecksum { .. }))
+        ));
+        // The bad frame was consumed; the next one decodes.
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap().msg_type, "0");
+
+        let mut small = FixCodec::with_max_frame_len(32);
+        let mut src = BytesMut::from(&heartbeat(3)[..20]);
+        assert!(matches!(
+            small.decode(&mut src),
+            Err(CodecError::FrameTooLarge { max: 32, .. })
+        ));
+    }
+
+    #[test]
+    fn encodes_messages_and_raw_frames() {
+        let raw = heartbeat(1);
+        let msg = parser::parse(&raw).unwrap();
+        let mut codec = FixCodec::new();
+        let mut dst = BytesMut::new();
+        codec.encode(msg, &mut dst).unwrap();
+        codec.encode(raw.clone(), &mut dst).unwrap();
+        let mut expected = raw.clone();
+        expected.extend_from_slice(&raw);
+        assert_eq!(&dst[..], &expected[..]);
+    }
+}
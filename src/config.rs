@@ -0,0 +1,748 @@
+//! Session Configuration Files
+//!
+//! 運用チームが管理している設定ファイルからセッション設定を読み込む。
+//! [`SessionConfig::from_toml`] は TOML、[`SessionConfig::from_quickfix_cfg`]
+//! は `QuickFIX` 形式の `.cfg` を受け付け、どちらもセッションブロックごとに
+//! 1 件の [`SessionConfig`] を返す (マルチセッションエンジン用)。
+//!
+//! 既定値ブロック (TOML の `[default]`、cfg の `[DEFAULT]`) の値は各
+//! セッションに引き継がれ、セッション側の値が優先される。
+//!
+//! ```toml
+//! [default]
+//! begin_string = "FIX.4.4"
+//! heart_bt_int = 30
+//!
+//! [[session]]
+//! sender_comp_id = "ALICE"
+//! target_comp_id = "BROKER"
+//! reset_on_logon = true
+//! ```
+//!
+//! TOML は設定に必要な部分集合のみ: `[default]` / `[[session]]` テーブル、
+//! `key = value` (文字列・整数・真偽値)、`#` コメント。未知のキーは
+//! [`SessionConfig::other`] に文字列で残す。
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::header::Routing;
+use crate::session::{
+    FixSession, LogonConfig, SessionId, SessionMode, SessionRole, DEFAULT_HEART_BT_INT,
+};
+use crate::throttle::{RateLimit, Throttle, ThrottlePolicy};
+use crate::value::TimestampPrecision;
+use crate::version::{BeginString, VersionError};
+
+/// `QuickFIX` のキー名と [`SessionConfig`] のキー名の対応。
+const QUICKFIX_KEYS: &[(&str, &str)] = &[
+    ("BeginString", "begin_string"),
+    ("DefaultApplVerID", "default_appl_ver_id"),
+    ("SenderCompID", "sender_comp_id"),
+    ("TargetCompID", "target_comp_id"),
+    ("HeartBtInt", "heart_bt_int"),
+    ("Username", "username"),
+    ("Password", "password"),
+    ("ResetOnLogon", "reset_on_logon"),
+    ("FileStorePath", "store_dir"),
+    ("ConnectionType", "role"),
+    ("SessionMode", "mode"),
+    ("SessionQualifier", "qualifier"),
+    ("MaxLatency", "max_latency"),
+    ("OrderAckTimeoutMs", "order_ack_timeout_ms"),
+    ("ThrottleBurst", "throttle_burst"),
+    ("ThrottlePerSec", "throttle_per_sec"),
+    ("ThrottlePolicy", "throttle_policy"),
+    ("TimeStampPrecision", "timestamp_precision"),
+    ("OnBehalfOfCompID", "on_behalf_of_comp_id"),
+    ("OnBehalfOfSubID", "on_behalf_of_sub_id"),
+    ("DeliverToCompID", "deliver_to_comp_id"),
+    ("DeliverToSubID", "deliver_to_sub_id"),
+];
+
+/// 設定ファイルの読み込みエラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 構文エラー。
+    Syntax {
+        /// 行番号 (1 始まり)。
+        line: usize,
+        /// 内容。
+        message: String,
+    },
+    /// セッションブロックに必須キーが無い。
+    MissingKey {
+        /// セッションブロックの番号 (0 始まり)。
+        session: usize,
+        /// キー名。
+        key: &'static str,
+    },
+    /// 値が不正。
+    InvalidValue {
+        /// セッションブロックの番号 (0 始まり)。
+        session: usize,
+        /// キー名。
+        key: &'static str,
+        /// 値。
+        value: String,
+    },
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            Self::MissingKey { session, key } => {
+                write!(f, "session {session}: missing required key {key}")
+            }
+            Self::InvalidValue {
+                session,
+                key,
+                value,
+            } => write!(f, "session {session}: invalid value for {key}: {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 1 セッション分の設定。
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// `BeginString` (tag 8)。`FIXT.1.1` のアプリケーション層はキー
+    /// `default_appl_ver_id` (`QuickFIX` は `DefaultApplVerID`) に
+    /// `ApplVerID` の値 (`4` / `6` / `7`) で指定する。
+    pub begin_string: BeginString,
+    /// `SenderCompID` (tag 49)。
+    pub sender_comp_id: String,
+    /// `TargetCompID` (tag 56)。
+    pub target_comp_id: String,
+    /// [`SessionId::qualifier`] (`QuickFIX` は `SessionQualifier`)。
+    pub qualifier: Option<String>,
+    /// イニシエータかアクセプタか。キー `role` (`QuickFIX` は
+    /// `ConnectionType`) の `initiator` / `acceptor`、既定はイニシエータ。
+    pub role: SessionRole,
+    /// 用途。キー `mode` (`QuickFIX` 形式では `SessionMode`) の `trading` /
+    /// `drop_copy`、既定は `trading`。
+    pub mode: SessionMode,
+    /// `HeartBtInt` (tag 108, 秒)。既定 [`DEFAULT_HEART_BT_INT`]。
+    pub heart_bt_int: u32,
+    /// 受信 `SendingTime` と時計の許容差 (秒)。`None` なら検査しない。
+    pub max_latency: Option<u32>,
+    /// `NewOrderSingle` が最初の `ExecutionReport` を待つ時間 (ミリ秒)。
+    /// `None` なら追跡しない。
+    pub order_ack_timeout_ms: Option<u32>,
+    /// アプリケーションメッセージの送信レート上限。キー `throttle_per_sec`
+    /// (1 秒あたりの件数) と `throttle_burst` (既定は `throttle_per_sec`)。
+    pub throttle: Option<RateLimit>,
+    /// 上限超過時の扱い。キー `throttle_policy` の `queue` / `reject`。
+    pub throttle_policy: ThrottlePolicy,
+    /// 送信する `SendingTime` の精度。キー `timestamp_precision` (`QuickFIX`
+    /// は `TimeStampPrecision`) の `seconds` / `millis` / `micros` / `nanos`、
+    /// 既定はミリ秒。
+    pub timestamp_precision: TimestampPrecision,
+    /// アプリケーションメッセージに付ける `OnBehalfOf*` / `DeliverTo*`。
+    /// キー `on_behalf_of_comp_id` / `on_behalf_of_sub_id` /
+    /// `deliver_to_comp_id` / `deliver_to_sub_id` (`QuickFIX` 形式では
+    /// `OnBehalfOfCompID` など)。
+    pub routing: Routing,
+    /// `Username` (tag 553)。
+    pub username: Option<String>,
+    /// `Password` (tag 554)。
+    pub password: Option<String>,
+    /// Logon で `ResetSeqNumFlag` (141) Y を送るか。
+    pub reset_on_logon: bool,
+    /// メッセージストアのディレクトリ。
+    pub store_dir: Option<PathBuf>,
+    /// 上記以外のキー (接続先ホストなど、アプリケーション側で解釈)。
+    pub other: BTreeMap<String, String>,
+}
+
+impl core::fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("begin_string", &self.begin_string)
+            .field("sender_comp_id", &self.sender_comp_id)
+            .field("target_comp_id", &self.target_comp_id)
+            .field("qualifier", &self.qualifier)
+            .field("role", &self.role)
+            .field("mode", &self.mode)
+            .field("heart_bt_int", &self.heart_bt_int)
+            .field("max_latency", &self.max_latency)
+            .field("order_ack_timeout_ms", &self.order_ack_timeout_ms)
+            .field("throttle", &self.throttle)
+            .field("throttle_policy", &self.throttle_policy)
+            .field("timestamp_precision", &self.timestamp_precision)
+            .field("routing", &self.routing)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("reset_on_logon", &self.reset_on_logon)
+            .field("store_dir", &self.store_dir)
+            .field("other", &self.other)
+            .finish()
+    }
+}
+
+impl SessionConfig {
+    /// 識別子だけを指定して作成。その他は既定値。
+    #[must_use]
+    pub fn new(begin_string: impl Into<BeginString>, sender: &str, target: &str) -> Self {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender.to_string(),
+            target_comp_id: target.to_string(),
+            qualifier: None,
+            role: SessionRole::Initiator,
+            mode: SessionMode::Trading,
+            heart_bt_int: DEFAULT_HEART_BT_INT,
+            max_latency: None,
+            order_ack_timeout_ms: None,
+            throttle: None,
+            throttle_policy: ThrottlePolicy::Queue,
+            timestamp_precision: TimestampPrecision::Millis,
+            routing: Routing::new(),
+            username: None,
+            password: None,
+            reset_on_logon: false,
+            store_dir: None,
+            other: BTreeMap::new(),
+        }
+    }
+
+    /// TOML の `[[session]]` ブロックをすべて読み込む。
+    ///
+    /// # Errors
+    ///
+    /// 構文エラー、必須キー (`begin_string` / `sender_comp_id` /
+    /// `target_comp_id`) の欠落、値の型が合わない場合。
+    pub fn from_toml(text: &str) -> Result<Vec<Self>, ConfigError> {
+        let mut defaults = BTreeMap::new();
+        let mut sessions: Vec<BTreeMap<String, String>> = Vec::new();
+        let mut in_session = false;
+        for (i, raw) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = strip_toml_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                match line {
+                    "[[session]]" => {
+                        sessions.push(BTreeMap::new());
+                        in_session = true;
+                    }
+                    "[default]" if sessions.is_empty() => in_session = false,
+                    _ => return Err(syntax(line_no, format!("unsupported table {line}"))),
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax(line_no, "expected key = value".to_string()))?;
+            let key = key.trim();
+            if key.is_empty()
+                || !key
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+            {
+                return Err(syntax(line_no, format!("invalid key {key:?}")));
+            }
+            let value = parse_toml_value(value.trim()).map_err(|m| syntax(line_no, m))?;
+            let table = match sessions.last_mut() {
+                Some(table) if in_session => table,
+                _ => &mut defaults,
+            };
+            if table.insert(key.to_string(), value).is_some() {
+                return Err(syntax(line_no, format!("duplicate key {key}")));
+            }
+        }
+        Self::from_tables(&defaults, sessions)
+    }
+
+    /// `QuickFIX` 形式 (`[DEFAULT]` / `[SESSION]`、`Key=Value`) の
+    /// `[SESSION]` ブロックをすべて読み込む。
+    ///
+    /// `BeginString` / `SenderCompID` / `TargetCompID` / `HeartBtInt` /
+    /// `Username` / `Password` / `ResetOnLogon` / `FileStorePath` /
+    /// `ConnectionType` / `SessionMode` / `SessionQualifier` / `MaxLatency` /
+    /// `OrderAckTimeoutMs` / `ThrottlePerSec` / `ThrottleBurst` / `ThrottlePolicy` /
+    /// `TimeStampPrecision` / `OnBehalfOfCompID` / `OnBehalfOfSubID` /
+    /// `DeliverToCompID` / `DeliverToSubID` を対応する
+    /// フィールドに、その他のキーは元の名前のまま [`Self::other`] に入れる。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::from_toml`] と同じ。
+    pub fn from_quickfix_cfg(text: &str) -> Result<Vec<Self>, ConfigError> {
+        let mut defaults = BTreeMap::new();
+        let mut sessions: Vec<BTreeMap<String, String>> = Vec::new();
+        let mut in_session = false;
+        for (i, raw) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if name.eq_ignore_ascii_case("SESSION") {
+                    sessions.push(BTreeMap::new());
+                    in_session = true;
+                } else if name.eq_ignore_ascii_case("DEFAULT") && sessions.is_empty() {
+                    in_session = false;
+                } else {
+                    return Err(syntax(line_no, format!("unsupported section {line}")));
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax(line_no, "expected Key=Value".to_string()))?;
+            let key = key.trim();
+            let key = QUICKFIX_KEYS
+                .iter()
+                .find(|(qf, _)| *qf == key)
+                .map_or(key, |&(_, name)| name);
+            let table = match sessions.last_mut() {
+                Some(table) if in_session => table,
+                _ => &mut defaults,
+            };
+            table.insert(key.to_string(), value.trim().to_string());
+        }
+        Self::from_tables(&defaults, sessions)
+    }
+
+    /// この設定の [`SessionId`]。
+    #[must_use]
+    pub fn session_id(&self) -> SessionId {
+        SessionId {
+            qualifier: self.qualifier.clone(),
+            ..SessionId::new(
+                self.begin_string.as_str(),
+                &self.sender_comp_id,
+                &self.target_comp_id,
+            )
+        }
+    }
+
+    /// 設定を反映した [`FixSession`] を作成。
+    #[must_use]
+    pub fn session(&self) -> FixSession {
+        let mut session = FixSession::new(
+            &self.sender_comp_id,
+            &self.target_comp_id,
+            self.begin_string,
+        );
+        session.set_role(self.role);
+        session.set_mode(self.mode);
+        session.set_qualifier(self.qualifier.as_deref());
+        session.set_heart_bt_int(self.heart_bt_int);
+        session.set_max_latency(self.max_latency);
+        session.set_order_ack_timeout_ms(self.order_ack_timeout_ms);
+        session.set_timestamp_precision(self.timestamp_precision);
+        session.set_routing(self.routing.clone());
+        if let Some(limit) = self.throttle {
+            session.set_throttle(
+                Throttle::new()
+                    .global_limit(limit, 0)
+                    .on_limit(self.throttle_policy),
+            );
+        }
+        let mut logon = LogonConfig::new().reset_seq_num(self.reset_on_logon);
+        if let Some(username) = &self.username {
+            logon = logon.username(username);
+        }
+        if let Some(password) = &self.password {
+            logon = logon.password(password);
+        }
+        session.set_logon_config(logon);
+        session
+    }
+
+    fn from_tables(
+        defaults: &BTreeMap<String, String>,
+        sessions: Vec<BTreeMap<String, String>>,
+    ) -> Result<Vec<Self>, ConfigError> {
+        sessions
+            .into_iter()
+            .enumerate()
+            .map(|(index, table)| {
+                let mut merged = defaults.clone();
+                merged.extend(table);
+                Self::from_table(index, merged)
+            })
+            .collect()
+    }
+
+    fn from_table(index: usize, mut table: BTreeMap<String, String>) -> Result<Self, ConfigError> {
+        let mut required = |key: &'static str| {
+            table.remove(key).ok_or(ConfigError::MissingKey {
+                session: index,
+                key,
+            })
+        };
+        let begin_string = required("begin_string")?;
+        let sender = required("sender_comp_id")?;
+        let target = required("target_comp_id")?;
+        let invalid = |key: &'static str, value: String| ConfigError::InvalidValue {
+            session: index,
+            key,
+            value,
+        };
+        let appl_ver_id = table.remove("default_appl_ver_id");
+        let begin_string = match BeginString::from_parts(&begin_string, appl_ver_id.as_deref()) {
+            Ok(begin_string) => begin_string,
+            Err(VersionError::MissingField(_)) => {
+                return Err(ConfigError::MissingKey {
+                    session: index,
+                    key: "default_appl_ver_id",
+                })
+            }
+            Err(_) if begin_string == "FIXT.1.1" => {
+                return Err(invalid(
+                    "default_appl_ver_id",
+                    appl_ver_id.unwrap_or_default(),
+                ))
+            }
+            Err(_) => return Err(invalid("begin_string", begin_string)),
+        };
+        let mut config = Self::new(begin_string, &sender, &target);
+        if let Some(v) = table.remove("role") {
+            config.role = match v.to_ascii_lowercase().as_str() {
+                "initiator" => SessionRole::Initiator,
+                "acceptor" => SessionRole::Acceptor,
+                _ => return Err(invalid("role", v)),
+            };
+        }
+        if let Some(v) = table.remove("mode") {
+            config.mode = match v.to_ascii_lowercase().as_str() {
+                "trading" => SessionMode::Trading,
+                "drop_copy" => SessionMode::DropCopy,
+                _ => return Err(invalid("mode", v)),
+            };
+        }
+        if let Some(v) = table.remove("heart_bt_int") {
+            config.heart_bt_int = v.parse().map_err(|_| invalid("heart_bt_int", v))?;
+        }
+        if let Some(v) = table.remove("max_latency") {
+            config.max_latency = Some(v.parse().map_err(|_| invalid("max_latency", v))?);
+        }
+        if let Some(v) = table.remove("order_ack_timeout_ms") {
+            config.order_ack_timeout_ms =
+                Some(v.parse().map_err(|_| invalid("order_ack_timeout_ms", v))?);
+        }
+        if let Some(v) = table.remove("throttle_per_sec") {
+            let per_sec = v.parse().map_err(|_| invalid("throttle_per_sec", v))?;
+            config.throttle = Some(RateLimit::new(per_sec, per_sec));
+        }
+        if let Some(v) = table.remove("throttle_burst") {
+            let limit = config
+                .throttle
+                .as_mut()
+                .ok_or_else(|| invalid("throttle_burst", v.clone()))?;
+            limit.burst = v.parse().map_err(|_| invalid("throttle_burst", v))?;
+        }
+        if let Some(v) = table.remove("throttle_policy") {
+            config.throttle_policy = match v.to_ascii_lowercase().as_str() {
+                "queue" => ThrottlePolicy::Queue,
+                "reject" => ThrottlePolicy::Reject,
+                _ => return Err(invalid("throttle_policy", v)),
+            };
+        }
+        if let Some(v) = table.remove("timestamp_precision") {
+            config.timestamp_precision = match v.to_ascii_lowercase().as_str() {
+                "seconds" => TimestampPrecision::Seconds,
+                "millis" => TimestampPrecision::Millis,
+                "micros" => TimestampPrecision::Micros,
+                "nanos" => TimestampPrecision::Nanos,
+                _ => return Err(invalid("timestamp_precision", v)),
+            };
+        }
+        if let Some(v) = table.remove("reset_on_logon") {
+            config.reset_on_logon = match v.as_str() {
+                "Y" | "true" => true,
+                "N" | "false" => false,
+                _ => return Err(invalid("reset_on_logon", v)),
+            };
+        }
+        config.qualifier = table.remove("qualifier");
+        config.routing = Routing {
+            on_behalf_of_comp_id: table.remove("on_behalf_of_comp_id"),
+            on_behalf_of_sub_id: table.remove("on_behalf_of_sub_id"),
+            deliver_to_comp_id: table.remove("deliver_to_comp_id"),
+            deliver_to_sub_id: table.remove("deliver_to_sub_id"),
+        };
+        config.username = table.remove("username");
+        config.password = table.remove("password");
+        config.store_dir = table.remove("store_dir").map(PathBuf::from);
+        config.other = table;
+        Ok(config)
+    }
+}
+
+const fn syntax(line: usize, message: String) -> ConfigError {
+    ConfigError::Syntax { line, message }
+}
+
+/// 文字列外の `#` 以降を取り除く。
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+/// TOML の値 (文字列・整数・真偽値) を文字列表現に変換。
+fn parse_toml_value(value: &str) -> Result<String, String> {
+    if let Some(inner) = value.strip_prefix('\'') {
+        return inner
+            .strip_suffix('\'')
+            .filter(|s| !s.contains('\''))
+            .map(str::to_string)
+            .ok_or_else(|| format!("unterminated string {value}"));
+    }
+    if let Some(inner) = value.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if chars.as_str().is_empty() => return Ok(out),
+                '"' => return Err(format!("unexpected text after string {value}")),
+                '\\' => out.push(match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    other => return Err(format!("unsupported escape \\{}", other.unwrap_or(' '))),
+                }),
+                c => out.push(c),
+            }
+        }
+        return Err(format!("unterminated string {value}"));
+    }
+    match value {
+        "true" | "false" => Ok(value.to_string()),
+        _ => value
+            .replace('_', "")
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| format!("unsupported value {value}")),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::tag;
+
+    #[test]
+    fn toml_sessions_inherit_defaults() {
+        let text = r#"
+            # 共通設定
+            [default]
+            begin_string = "FIX.4.4"
+            heart_bt_int = 30
+
+            [[session]]
+            sender_comp_id = "ALICE"
+            target_comp_id = "BROKER"  # 本番
+            username = "alice"
+            password = "p#ss\"word"
+            reset_on_logon = true
+            store_dir = '/var/lib/fix'
+
+            [[session]]
+            sender_comp_id = "ALICE"
+            target_comp_id = "DROPCOPY"
+            mode = "drop_copy"
+            heart_bt_int = 60
+            socket_connect_host = "10.0.0.1"
+        "#;
+        let configs = SessionConfig::from_toml(text).unwrap();
+        assert_eq!(configs.len(), 2);
+        let trading = &configs[0];
+        assert_eq!(
+            trading.session_id(),
+            SessionId::new("FIX.4.4", "ALICE", "BROKER")
+        );
+        assert_eq!(trading.heart_bt_int, 30);
+        assert_eq!(trading.password.as_deref(), Some("p#ss\"word"));
+        assert!(trading.reset_on_logon);
+        assert_eq!(trading.store_dir, Some(PathBuf::from("/var/lib/fix")));
+        assert!(!format!("{trading:?}").contains("p#ss"));
+
+        let drop_copy = &configs[1];
+        assert_eq!(drop_copy.heart_bt_int, 60);
+        assert_eq!(drop_copy.session().mode(), SessionMode::DropCopy);
+        assert_eq!(trading.mode, SessionMode::Trading);
+        assert_eq!(drop_copy.username, None);
+        assert_eq!(
+            drop_copy
+                .other
+                .get("socket_connect_host")
+                .map(String::as_str),
+            Some("10.0.0.1")
+        );
+
+        let logon = parser::parse(&trading.session().build_logon()).unwrap();
+        assert_eq!(logon.get(tag::USERNAME), Some("alice"));
+        assert_eq!(logon.get_bool(tag::RESET_SEQ_NUM_FLAG), Some(true));
+        assert_eq!(logon.get(tag::HEART_BT_INT), Some("30"));
+    }
+
+    #[test]
+    fn quickfix_cfg_maps_keys() {
+        let text = "
+            [DEFAULT]
+            BeginString=FIX.4.4
+            SocketConnectPort=9876
+            # comment
+            [SESSION]
+            SenderCompID=ALICE
+            TargetCompID=BROKER
+            HeartBtInt=20
+            MaxLatency=120
+            OrderAckTimeoutMs=1500
+            ThrottlePerSec=50
+            ThrottleBurst=10
+            ThrottlePolicy=reject
+            TimeStampPrecision=MICROS
+            ResetOnLogon=Y
+            FileStorePath=store
+            ConnectionType=acceptor
+            SessionQualifier=primary
+            OnBehalfOfCompID=FUND
+            DeliverToCompID=BROKER_B
+            DeliverToSubID=DESK9
+
+            [SESSION]
+            BeginString=FIX.4.2
+            SenderCompID=ALICE
+            TargetCompID=OLDBROKER
+        ";
+        let configs = SessionConfig::from_quickfix_cfg(text).unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].heart_bt_int, 20);
+        assert_eq!(configs[0].session().max_latency(), Some(120));
+        assert_eq!(configs[1].max_latency, None);
+        assert_eq!(configs[0].session().order_ack_timeout_ms(), Some(1500));
+        assert_eq!(configs[1].order_ack_timeout_ms, None);
+        assert_eq!(configs[0].throttle, Some(RateLimit::new(10, 50)));
+        assert_eq!(configs[0].throttle_policy, ThrottlePolicy::Reject);
+        assert_eq!(
+            configs[0].session().throttle().limit_policy(),
+            ThrottlePolicy::Reject
+        );
+        assert_eq!(configs[1].throttle, None);
+        assert_eq!(
+            configs[0].session().timestamp_precision(),
+            TimestampPrecision::Micros
+        );
+        assert_eq!(configs[1].timestamp_precision, TimestampPrecision::Millis);
+        assert_eq!(
+            *configs[0].session().routing(),
+            Routing::new()
+                .on_behalf_of("FUND")
+                .deliver_to("BROKER_B")
+                .deliver_to_sub("DESK9")
+        );
+        assert!(configs[1].routing.is_empty());
+        assert!(configs[0].reset_on_logon);
+        assert_eq!(configs[0].store_dir, Some(PathBuf::from("store")));
+        assert_eq!(configs[0].role, SessionRole::Acceptor);
+        assert_eq!(configs[0].session().role(), SessionRole::Acceptor);
+        assert_eq!(
+            configs[0].session().session_id().to_string(),
+            "FIX.4.4:ALICE->BROKER:primary"
+        );
+        assert_eq!(configs[1].role, SessionRole::Initiator);
+        assert_eq!(
+            configs[0]
+                .other
+                .get("SocketConnectPort")
+                .map(String::as_str),
+            Some("9876")
+        );
+        assert_eq!(configs[1].begin_string, BeginString::Fix42);
+        assert_eq!(configs[1].heart_bt_int, DEFAULT_HEART_BT_INT);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(
+            SessionConfig::from_toml("[[session]]\nsender_comp_id = ALICE"),
+            Err(ConfigError::Syntax {
+                line: 2,
+                message: "unsupported value ALICE".to_string()
+            })
+        );
+        assert_eq!(
+            SessionConfig::from_toml("[[session]]\nbegin_string = \"FIX.4.4\""),
+            Err(ConfigError::MissingKey {
+                session: 0,
+                key: "sender_comp_id"
+            })
+        );
+        let err = SessionConfig::from_quickfix_cfg(
+            "[SESSION]\nBeginString=FIX.4.4\nSenderCompID=A\nTargetCompID=B\nResetOnLogon=maybe",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "session 0: invalid value for reset_on_logon: \"maybe\""
+        );
+        assert!(matches!(
+            SessionConfig::from_toml("[[session]]\na = 1\na = 2"),
+            Err(ConfigError::Syntax { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn fixt_session_needs_default_appl_ver_id() {
+        let configs = SessionConfig::from_quickfix_cfg(
+            "[SESSION]\nBeginString=FIXT.1.1\nDefaultApplVerID=7\nSenderCompID=A\nTargetCompID=B",
+        )
+        .unwrap();
+        assert_eq!(
+            configs[0].begin_string,
+            BeginString::Fixt11 {
+                appl_ver: crate::version::FixVersion::Fix50
+            }
+        );
+        assert_eq!(
+            SessionConfig::from_toml(
+                "[[session]]\nbegin_string = \"FIXT.1.1\"\nsender_comp_id = \"A\"\ntarget_comp_id = \"B\""
+            ),
+            Err(ConfigError::MissingKey {
+                session: 0,
+                key: "default_appl_ver_id"
+            })
+        );
+        assert_eq!(
+            SessionConfig::from_toml(
+                "[[session]]\nbegin_string = \"FIX4.4\"\nsender_comp_id = \"A\"\ntarget_comp_id = \"B\""
+            ),
+            Err(ConfigError::InvalidValue {
+                session: 0,
+                key: "begin_string",
+                value: "FIX4.4".to_string()
+            })
+        );
+    }
+}
@@ -8,9 +8,10 @@
 //! All FIX tag values are plain string slices following the FIX 4.4
 //! specification. ALICE-Ledger types are defined in the `alice_ledger` crate.
 
-use crate::message::FixMessage;
+use crate::execution_report::{ExecType, ExecutionReport, OrdStatus};
+use crate::message::{FieldError, FixMessage};
 use crate::tag;
-use alice_ledger::{Fill, OrderId, OrderType, Side, TimeInForce};
+use alice_ledger::{Fill, Order, OrderId, OrderType, Side, TimeInForce};
 
 // ---------------------------------------------------------------------------
 // Side
@@ -126,25 +127,27 @@ pub const fn alice_tif_to_fix(tif: TimeInForce) -> &'static str {
 /// [`Fill`].
 ///
 /// Required tags: 17 (`ExecID`), 37 (`OrderID`), 11 (`ClOrdID`), 31 (`LastPx`),
-/// 32 (`LastQty`), 60 (`TransactTime`).
+/// 32 (`LastQty`). 60 (`TransactTime`) is optional.
 ///
-/// Returns `None` if any required tag is absent or cannot be parsed.
-#[must_use]
-pub fn parse_execution_report(msg: &FixMessage) -> Option<Fill> {
+/// # Errors
+///
+/// [`FieldError::MissingTag`] naming the first required tag that is absent,
+/// or [`FieldError::InvalidValue`] for one that cannot be parsed.
+pub fn parse_execution_report(msg: &FixMessage) -> Result<Fill, FieldError> {
     // Tag 17 (ExecID) — used as taker_id for the fill record.
-    let exec_id: u64 = msg.get_u64(tag::EXEC_ID)?;
+    let exec_id: u64 = msg.require_u64(tag::EXEC_ID)?;
 
     // Tag 37 (OrderID) — broker-assigned maker order ID.
-    let order_id: u64 = msg.get_u64(tag::ORDER_ID)?;
+    let order_id: u64 = msg.require_u64(tag::ORDER_ID)?;
 
     // Tag 11 (ClOrdID) — client-assigned order ID used as taker reference.
-    let cl_ord_id: u64 = msg.get_u64(tag::CL_ORD_ID)?;
+    let cl_ord_id: u64 = msg.require_u64(tag::CL_ORD_ID)?;
 
     // Tag 31 (LastPx) — fill price in ticks.
-    let last_px: i64 = msg.get_i64(tag::LAST_PX)?;
+    let last_px: i64 = msg.require_i64(tag::LAST_PX)?;
 
     // Tag 32 (LastQty) — fill quantity.
-    let last_qty: u64 = msg.get_u64(tag::LAST_QTY)?;
+    let last_qty: u64 = msg.require_u64(tag::LAST_QTY)?;
 
     // Tag 60 (TransactTime) — timestamp; store as 0 if not parseable as u64
     // (FIX timestamps are strings like "20260101-12:00:00.000").
@@ -153,7 +156,7 @@ pub fn parse_execution_report(msg: &FixMessage) -> Option<Fill> {
     // Suppress unused variable warning for exec_id: embed it in taker_id.
     let _ = exec_id;
 
-    Some(Fill {
+    Ok(Fill {
         maker_id: OrderId(order_id),
         taker_id: OrderId(cl_ord_id),
         price: last_px,
@@ -162,6 +165,64 @@ pub fn parse_execution_report(msg: &FixMessage) -> Option<Fill> {
     })
 }
 
+// ---------------------------------------------------------------------------
+// Fill → ExecutionReport
+// ---------------------------------------------------------------------------
+
+/// Order state an `ExecutionReport` for a [`Fill`] reports against, on the
+/// acceptor (exchange) side.
+#[derive(Debug, Clone, Copy)]
+pub struct FillContext<'a> {
+    /// The order after the fill was applied: `filled_quantity` includes it.
+    pub order: &'a Order,
+    /// The client's `ClOrdID` (tag 11) for the order.
+    pub cl_ord_id: &'a str,
+    /// Instrument (tag 55).
+    pub symbol: &'a str,
+    /// `ExecID` (tag 17), unique per report.
+    pub exec_id: &'a str,
+    /// Average price (tag 6) of all fills on the order, this one included.
+    pub avg_px: f64,
+}
+
+/// Build the FIX 4.4 `ExecutionReport` (`ExecType` F) announcing `fill`.
+///
+/// `OrderID` (37) is the ledger order id; `OrdStatus` is Filled once
+/// `filled_quantity` reaches `quantity`, otherwise `PartiallyFilled`.
+/// `LastPx` / `LastQty` / `TransactTime` come from `fill`, `CumQty` /
+/// `LeavesQty` from the order. Frame it with
+/// [`crate::session::FixSession::build_execution_report`]; a FIX 4.2
+/// counterparty needs [`crate::version::convert`] for `ExecType` 1 / 2.
+#[must_use]
+pub fn fill_execution_report(fill: &Fill, ctx: &FillContext<'_>) -> ExecutionReport {
+    let order = ctx.order;
+    let leaves = order.quantity.saturating_sub(order.filled_quantity);
+    ExecutionReport {
+        order_id: order.id.0.to_string(),
+        cl_ord_id: ctx.cl_ord_id.to_string(),
+        exec_id: ctx.exec_id.to_string(),
+        exec_type: ExecType::Trade,
+        ord_status: if leaves == 0 {
+            OrdStatus::Filled
+        } else {
+            OrdStatus::PartiallyFilled
+        },
+        symbol: ctx.symbol.to_string(),
+        side: alice_side_to_fix(order.side).to_string(),
+        last_px: Some(fill.price as f64),
+        last_qty: Some(fill.quantity as f64),
+        leaves_qty: Some(leaves as f64),
+        cum_qty: Some(order.filled_quantity as f64),
+        avg_px: Some(ctx.avg_px),
+        text: None,
+        transact_time: Some(fill.timestamp_ns),
+        ord_status_req_id: None,
+        mass_status_req_id: None,
+        tot_num_reports: None,
+        last_rpt_requested: None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -172,6 +233,64 @@ mod tests {
     use crate::builder::FixBuilder;
     use crate::message::FixMessage;
     use crate::tag;
+    use crate::version::BeginString;
+
+    // --- Fill → ExecutionReport ---
+
+    #[test]
+    fn test_fill_execution_report() {
+        let order = Order {
+            id: OrderId(20),
+            side: Side::Ask,
+            order_type: OrderType::Limit,
+            price: 48_000,
+            quantity: 10,
+            filled_quantity: 3,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::GTC,
+        };
+        let fill = Fill {
+            maker_id: OrderId(20),
+            taker_id: OrderId(55),
+            price: 48_000,
+            quantity: 3,
+            timestamp_ns: 1_767_225_600_000_000_000,
+        };
+        let ctx = FillContext {
+            order: &order,
+            cl_ord_id: "C20",
+            symbol: "BTCUSD",
+            exec_id: "E1",
+            avg_px: 48_000.0,
+        };
+        let report = fill_execution_report(&fill, &ctx);
+        assert_eq!(report.exec_type, ExecType::Trade);
+        assert_eq!(report.ord_status, OrdStatus::PartiallyFilled);
+        assert_eq!(report.leaves_qty, Some(7.0));
+        assert_eq!(report.cum_qty, Some(3.0));
+
+        let msg = crate::parser::parse(&FixBuilder::from(&report).build()).unwrap();
+        assert_eq!(msg.get(tag::EXEC_TYPE), Some("F"));
+        assert_eq!(msg.get(tag::ORDER_ID), Some("20"));
+        assert_eq!(msg.get(tag::SIDE), Some("2"));
+        assert_eq!(msg.get(tag::TRANSACT_TIME), Some("20260101-00:00:00.000"));
+        assert!(crate::builder::required_body_tags("8")
+            .iter()
+            .all(|&t| msg.contains(t)));
+
+        let filled = Order {
+            filled_quantity: 10,
+            ..order
+        };
+        let ctx = FillContext {
+            order: &filled,
+            exec_id: "E2",
+            ..ctx
+        };
+        let last = fill_execution_report(&fill, &ctx);
+        assert_eq!(last.ord_status, OrdStatus::Filled);
+        assert_eq!(last.leaves_qty, Some(0.0));
+    }
 
     // --- Side ---
 
@@ -254,13 +373,19 @@ mod tests {
             .set(tag::ORDER_ID, "2")
             .set(tag::CL_ORD_ID, "3")
             .set(tag::LAST_QTY, "5");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::LAST_PX,
+                msg_type: "8".into()
+            })
+        );
     }
 
     #[test]
     fn test_parse_execution_report_via_builder() {
         // Build a proper wire message and re-parse it to confirm end-to-end.
-        let bytes = FixBuilder::new("FIX.4.4", "8")
+        let bytes = FixBuilder::new(BeginString::Fix44, "8")
             .field(tag::SENDER_COMP_ID, "BROKER")
             .field(tag::TARGET_COMP_ID, "ALICE")
             .field(tag::MSG_SEQ_NUM, "10")
@@ -374,8 +499,14 @@ mod tests {
             .set(tag::CL_ORD_ID, "42")
             .set(tag::LAST_PX, "50000")
             .set(tag::LAST_QTY, "5");
-        // Missing EXEC_ID -> should return None.
-        assert!(parse_execution_report(&msg).is_none());
+        // Missing EXEC_ID -> reported by tag.
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::EXEC_ID,
+                msg_type: "8".into()
+            })
+        );
     }
 
     #[test]
@@ -385,7 +516,13 @@ mod tests {
             .set(tag::CL_ORD_ID, "42")
             .set(tag::LAST_PX, "50000")
             .set(tag::LAST_QTY, "5");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::ORDER_ID,
+                msg_type: "8".into()
+            })
+        );
     }
 
     #[test]
@@ -395,7 +532,13 @@ mod tests {
             .set(tag::ORDER_ID, "10")
             .set(tag::LAST_PX, "50000")
             .set(tag::LAST_QTY, "5");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::CL_ORD_ID,
+                msg_type: "8".into()
+            })
+        );
     }
 
     #[test]
@@ -405,7 +548,13 @@ mod tests {
             .set(tag::ORDER_ID, "10")
             .set(tag::CL_ORD_ID, "42")
             .set(tag::LAST_PX, "50000");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::LAST_QTY,
+                msg_type: "8".into()
+            })
+        );
     }
 
     #[test]
@@ -416,7 +565,14 @@ mod tests {
             .set(tag::CL_ORD_ID, "42")
             .set(tag::LAST_PX, "not_a_number")
             .set(tag::LAST_QTY, "5");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::InvalidValue {
+                tag: tag::LAST_PX,
+                msg_type: "8".into(),
+                value: "not_a_number".into()
+            })
+        );
     }
 
     #[test]
@@ -463,6 +619,12 @@ mod tests {
     #[test]
     fn test_parse_execution_report_empty_message() {
         let msg = FixMessage::new("FIX.4.4", "8");
-        assert!(parse_execution_report(&msg).is_none());
+        assert_eq!(
+            parse_execution_report(&msg).err(),
+            Some(FieldError::MissingTag {
+                tag: tag::EXEC_ID,
+                msg_type: "8".into()
+            })
+        );
     }
 }
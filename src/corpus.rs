@@ -0,0 +1,412 @@
+//! Golden Wire-Format Corpus
+//!
+//! 実運用に近い形のフレーム集 (feature `testing`)。注文、約定報告、
+//! グループ付きのマーケットデータ、`=` を含むデータフィールド、FIXT.1.1 の
+//! Logon などを [`CORPUS`] に期待するパース結果とともに収める。パーサ内部を
+//! 変更したときに [`crate::testing::assert_parses_like`] で全件を検査し、
+//! 取引所方言の回帰を検出する。
+//!
+//! 各フレームの `BodyLength` / `CheckSum` は正しい値。データフィールドは
+//! SOH を含まないもののみ (パーサは長さ付きデータを特別扱いしない)。
+
+use crate::testing::Expected;
+
+/// 名前付きの実例フレームと期待値。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoldenFrame {
+    /// 識別名 (失敗時の表示用)。
+    pub name: &'static str,
+    /// 受信フレーム。
+    pub frame: &'static [u8],
+    /// 期待するパース結果。
+    pub expected: Expected<'static>,
+}
+
+/// 指値の `NewOrderSingle` (FIX.4.4、ミリ秒 `SendingTime`)。
+pub const NEW_ORDER_SINGLE: &[u8] = b"\
+    8=FIX.4.4\x019=139\x0135=D\x0149=ALICE\x0156=BROKER\x0134=12\x01\
+    52=20260315-13:30:00.125\x0111=ORD-0001\x011=ACC1\x0155=AAPL\x0154=1\x01\
+    60=20260315-13:30:00.120\x0138=100\x0140=2\x0144=187.25\x0159=0\x0110=097\x01";
+
+/// `Price` なしの成行注文 (FIX.4.2、秒精度、`HandlInst`)。
+pub const MARKET_ORDER_FIX42: &[u8] = b"\
+    8=FIX.4.2\x019=113\x0135=D\x0149=ALICE\x0156=BROKER\x0134=13\x01\
+    52=20260315-13:30:01\x0111=ORD-0002\x0121=1\x0155=MSFT\x0154=2\x01\
+    60=20260315-13:30:01\x0138=50\x0140=1\x0110=120\x01";
+
+/// `NoPartyIDs` (453) グループと `SecurityID` を持つ注文。
+pub const ORDER_WITH_PARTIES: &[u8] = b"\
+    8=FIX.4.4\x019=215\x0135=D\x0149=ALICE\x0156=BROKER\x0134=14\x01\
+    52=20260315-13:30:02.000\x0111=ORD-0003\x01453=2\x01448=TRADER1\x01447=D\x01\
+    452=11\x01448=DESK-A\x01447=D\x01452=24\x0155=7203\x0148=JP3633400001\x01\
+    22=4\x01207=XTKS\x0154=1\x0160=20260315-13:30:02.000\x0138=300\x0140=2\x01\
+    44=2850\x0159=0\x0110=119\x01";
+
+/// 一部約定の `ExecutionReport`。
+pub const EXEC_REPORT_PARTIAL_FILL: &[u8] = b"\
+    8=FIX.4.4\x019=182\x0135=8\x0149=BROKER\x0156=ALICE\x0134=220\x01\
+    52=20260315-13:30:00.130\x0137=EX-778\x0111=ORD-0001\x0117=EXEC-1\x01150=F\x01\
+    39=1\x0155=AAPL\x0154=1\x0138=100\x0132=40\x0131=187.25\x01151=60\x0114=40\x01\
+    6=187.25\x0160=20260315-13:30:00.129\x0110=150\x01";
+
+/// `OrderCancelReject` (`Text` に空白を含む)。
+pub const ORDER_CANCEL_REJECT: &[u8] = b"\
+    8=FIX.4.4\x019=129\x0135=9\x0149=BROKER\x0156=ALICE\x0134=221\x01\
+    52=20260315-13:30:05.000\x0137=EX-778\x0111=ORD-0004\x0141=ORD-0001\x01\
+    39=2\x01434=1\x01102=0\x0158=Too late to cancel\x0110=158\x01";
+
+/// `NoMDEntries` (268) 3 件の板スナップショット (マイクロ秒 `SendingTime`)。
+pub const MD_SNAPSHOT: &[u8] = b"\
+    8=FIX.4.4\x019=162\x0135=W\x0149=VENUE\x0156=ALICE\x0134=5001\x01\
+    52=20260315-13:30:00.000001\x01262=MD-1\x0155=BTCUSD\x01268=3\x01269=0\x01\
+    270=65000.5\x01271=1.25\x01269=1\x01270=65001\x01271=0.8\x01269=2\x01\
+    270=65000.75\x01271=0.1\x0110=121\x01";
+
+/// `MDUpdateAction` で始まるエントリを持つ差分配信。
+pub const MD_INCREMENTAL: &[u8] = b"\
+    8=FIX.4.4\x019=138\x0135=X\x0149=VENUE\x0156=ALICE\x0134=5002\x01\
+    52=20260315-13:30:00.000002\x01268=2\x01279=0\x01269=0\x01278=B1\x01\
+    55=ETHUSD\x01270=3200\x01271=5\x01279=2\x01269=1\x01278=A7\x0155=ETHUSD\x01\
+    10=176\x01";
+
+/// `NoRelatedSym` (146) 2 件の購読要求。
+pub const MD_REQUEST: &[u8] = b"\
+    8=FIX.4.4\x019=120\x0135=V\x0149=ALICE\x0156=VENUE\x0134=3\x01\
+    52=20260315-13:29:59.000\x01262=MD-1\x01263=1\x01264=0\x01146=2\x01\
+    55=BTCUSD\x01207=VENUE\x0155=ETHUSD\x01207=VENUE\x0110=132\x01";
+
+/// FIXT.1.1 の Logon (`Username` / `Password` / `DefaultApplVerID`)。
+pub const LOGON_FIXT: &[u8] = b"\
+    8=FIXT.1.1\x019=99\x0135=A\x0149=ALICE\x0156=VENUE\x0134=1\x01\
+    52=20260315-13:29:58.000\x0198=0\x01108=30\x01141=Y\x01553=alice\x01\
+    554=s3cr3t\x011137=9\x0110=125\x01";
+
+/// `=` を含むデータフィールド (`RawData` / `XmlData`) とユーザー定義タグを持つ `News`。
+pub const NEWS_WITH_DATA: &[u8] = b"\
+    8=FIX.4.4\x019=149\x0135=B\x0149=VENUE\x0156=ALICE\x0134=42\x01\
+    52=20260315-13:31:00.000\x01148=Halt\x0133=1\x0158=Trading halted\x0195=11\x01\
+    96=k=v;a==b;c=\x01212=15\x01213=<h sym=\"AAPL\"/>\x015001=venue-ext\x01\
+    10=099\x01";
+
+/// セッション層の Reject (`RefTagID` / `SessionRejectReason`)。
+pub const SESSION_REJECT: &[u8] = b"\
+    8=FIX.4.4\x019=131\x0135=3\x0149=BROKER\x0156=ALICE\x0134=222\x01\
+    52=20260315-13:30:06.000\x0145=15\x01371=44\x01372=D\x01373=5\x01\
+    58=Value is incorrect (out of range) for this tag\x0110=011\x01";
+
+/// 全フレームと期待値。
+pub const CORPUS: &[GoldenFrame] = &[
+    GoldenFrame {
+        name: "new_order_single",
+        frame: NEW_ORDER_SINGLE,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "D",
+            fields: &[
+                (49, "ALICE"),
+                (56, "BROKER"),
+                (34, "12"),
+                (52, "20260315-13:30:00.125"),
+                (11, "ORD-0001"),
+                (1, "ACC1"),
+                (55, "AAPL"),
+                (54, "1"),
+                (60, "20260315-13:30:00.120"),
+                (38, "100"),
+                (40, "2"),
+                (44, "187.25"),
+                (59, "0"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "market_order_fix42",
+        frame: MARKET_ORDER_FIX42,
+        expected: Expected {
+            begin_string: "FIX.4.2",
+            msg_type: "D",
+            fields: &[
+                (49, "ALICE"),
+                (56, "BROKER"),
+                (34, "13"),
+                (52, "20260315-13:30:01"),
+                (11, "ORD-0002"),
+                (21, "1"),
+                (55, "MSFT"),
+                (54, "2"),
+                (60, "20260315-13:30:01"),
+                (38, "50"),
+                (40, "1"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "order_with_parties",
+        frame: ORDER_WITH_PARTIES,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "D",
+            fields: &[
+                (49, "ALICE"),
+                (56, "BROKER"),
+                (34, "14"),
+                (52, "20260315-13:30:02.000"),
+                (11, "ORD-0003"),
+                (453, "2"),
+                (55, "7203"),
+                (48, "JP3633400001"),
+                (22, "4"),
+                (207, "XTKS"),
+                (54, "1"),
+                (60, "20260315-13:30:02.000"),
+                (38, "300"),
+                (40, "2"),
+                (44, "2850"),
+                (59, "0"),
+            ],
+            groups: &[(
+                453,
+                &[
+                    &[(448, "TRADER1"), (447, "D"), (452, "11")],
+                    &[(448, "DESK-A"), (447, "D"), (452, "24")],
+                ],
+            )],
+        },
+    },
+    GoldenFrame {
+        name: "exec_report_partial_fill",
+        frame: EXEC_REPORT_PARTIAL_FILL,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "8",
+            fields: &[
+                (49, "BROKER"),
+                (56, "ALICE"),
+                (34, "220"),
+                (52, "20260315-13:30:00.130"),
+                (37, "EX-778"),
+                (11, "ORD-0001"),
+                (17, "EXEC-1"),
+                (150, "F"),
+                (39, "1"),
+                (55, "AAPL"),
+                (54, "1"),
+                (38, "100"),
+                (32, "40"),
+                (31, "187.25"),
+                (151, "60"),
+                (14, "40"),
+                (6, "187.25"),
+                (60, "20260315-13:30:00.129"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "order_cancel_reject",
+        frame: ORDER_CANCEL_REJECT,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "9",
+            fields: &[
+                (49, "BROKER"),
+                (56, "ALICE"),
+                (34, "221"),
+                (52, "20260315-13:30:05.000"),
+                (37, "EX-778"),
+                (11, "ORD-0004"),
+                (41, "ORD-0001"),
+                (39, "2"),
+                (434, "1"),
+                (102, "0"),
+                (58, "Too late to cancel"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "md_snapshot",
+        frame: MD_SNAPSHOT,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "W",
+            fields: &[
+                (49, "VENUE"),
+                (56, "ALICE"),
+                (34, "5001"),
+                (52, "20260315-13:30:00.000001"),
+                (262, "MD-1"),
+                (55, "BTCUSD"),
+                (268, "3"),
+            ],
+            groups: &[(
+                268,
+                &[
+                    &[(269, "0"), (270, "65000.5"), (271, "1.25")],
+                    &[(269, "1"), (270, "65001"), (271, "0.8")],
+                    &[(269, "2"), (270, "65000.75"), (271, "0.1")],
+                ],
+            )],
+        },
+    },
+    GoldenFrame {
+        name: "md_incremental",
+        frame: MD_INCREMENTAL,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "X",
+            fields: &[
+                (49, "VENUE"),
+                (56, "ALICE"),
+                (34, "5002"),
+                (52, "20260315-13:30:00.000002"),
+                (268, "2"),
+            ],
+            groups: &[(
+                268,
+                &[
+                    &[
+                        (279, "0"),
+                        (269, "0"),
+                        (278, "B1"),
+                        (55, "ETHUSD"),
+                        (270, "3200"),
+                        (271, "5"),
+                    ],
+                    &[(279, "2"), (269, "1"), (278, "A7"), (55, "ETHUSD")],
+                ],
+            )],
+        },
+    },
+    GoldenFrame {
+        name: "md_request",
+        frame: MD_REQUEST,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "V",
+            fields: &[
+                (49, "ALICE"),
+                (56, "VENUE"),
+                (34, "3"),
+                (52, "20260315-13:29:59.000"),
+                (262, "MD-1"),
+                (263, "1"),
+                (264, "0"),
+                (146, "2"),
+            ],
+            groups: &[(
+                146,
+                &[
+                    &[(55, "BTCUSD"), (207, "VENUE")],
+                    &[(55, "ETHUSD"), (207, "VENUE")],
+                ],
+            )],
+        },
+    },
+    GoldenFrame {
+        name: "logon_fixt",
+        frame: LOGON_FIXT,
+        expected: Expected {
+            begin_string: "FIXT.1.1",
+            msg_type: "A",
+            fields: &[
+                (49, "ALICE"),
+                (56, "VENUE"),
+                (34, "1"),
+                (52, "20260315-13:29:58.000"),
+                (98, "0"),
+                (108, "30"),
+                (141, "Y"),
+                (553, "alice"),
+                (554, "s3cr3t"),
+                (1137, "9"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "news_with_data",
+        frame: NEWS_WITH_DATA,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "B",
+            fields: &[
+                (49, "VENUE"),
+                (56, "ALICE"),
+                (34, "42"),
+                (52, "20260315-13:31:00.000"),
+                (148, "Halt"),
+                (33, "1"),
+                (58, "Trading halted"),
+                (95, "11"),
+                (96, "k=v;a==b;c="),
+                (212, "15"),
+                (213, "<h sym=\"AAPL\"/>"),
+                (5001, "venue-ext"),
+            ],
+            groups: &[],
+        },
+    },
+    GoldenFrame {
+        name: "session_reject",
+        frame: SESSION_REJECT,
+        expected: Expected {
+            begin_string: "FIX.4.4",
+            msg_type: "3",
+            fields: &[
+                (49, "BROKER"),
+                (56, "ALICE"),
+                (34, "222"),
+                (52, "20260315-13:30:06.000"),
+                (45, "15"),
+                (371, "44"),
+                (372, "D"),
+                (373, "5"),
+                (58, "Value is incorrect (out of range) for this tag"),
+            ],
+            groups: &[],
+        },
+    },
+];
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::testing::{assert_frame_valid, assert_parses_like};
+
+    #[test]
+    fn every_frame_parses_as_expected() {
+        for golden in CORPUS {
+            let msg = assert_parses_like(golden.frame, &golden.expected);
+            let _ = assert_frame_valid(golden.frame);
+            assert_eq!(msg.msg_type, golden.expected.msg_type, "{}", golden.name);
+        }
+    }
+
+    #[test]
+    fn spans_cover_every_field() {
+        for golden in CORPUS {
+            let msg = parser::parse_with_spans(golden.frame).unwrap();
+            let fields = golden.frame.split(|&b| b == parser::SOH).count() - 1;
+            assert_eq!(msg.spans.len(), fields, "{}", golden.name);
+        }
+    }
+
+    #[test]
+    fn names_are_unique() {
+        for (i, a) in CORPUS.iter().enumerate() {
+            assert!(
+                CORPUS[i + 1..].iter().all(|b| b.name != a.name),
+                "{}",
+                a.name
+            );
+        }
+    }
+}
@@ -0,0 +1,810 @@
+//! Message Definition Dictionary
+//!
+//! `MsgType` ごとの必須・任意タグ、フィールドの型と値ドメイン、繰り返し
+//! グループの構造をまとめた定義。[`crate::builder::FixBuilder::validate`]
+//! で送信前に検査すれば、不正な注文を相手の Reject (35=3) を待たずに
+//! 手元で検出できる。
+//!
+//! [`Dictionary::fix44`] は本クレートが扱う主要メッセージ (セッション層、
+//! 注文・訂正・状態照会・一括照会・リスト注文、約定、マーケットデータ) の
+//! FIX 4.4 定義。取引所固有の制約は
+//! [`Dictionary::field`] / [`Dictionary::message`] で上書き・追加する。
+//!
+//! 検査の失敗は [`ValidationError`] で返すため、受信側の検証と同じ
+//! `SessionRejectReason` に対応する。
+
+use std::collections::HashMap;
+
+use crate::builder::{HEADER_TAG_ORDER, REQUIRED_HEADER_TAGS};
+use crate::repeating_group::{find_group_def, GroupDef};
+use crate::tag;
+use crate::validation::ValidationError;
+use crate::value::{
+    parse_bool, parse_i64_bytes, parse_u64_bytes, parse_utc_timestamp_bytes, Decimal,
+};
+
+/// フィールドの値の型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// 任意の文字列。
+    String,
+    /// 1 文字。
+    Char,
+    /// 符号付き整数。
+    Int,
+    /// 符号なし整数 (`SeqNum`、`Length`、`NumInGroup`)。
+    UInt,
+    /// 10 進数 (`Price`、`Qty`、`Amt`)。
+    Decimal,
+    /// `Y` / `N`。
+    Boolean,
+    /// `UTCTimestamp`。
+    UtcTimestamp,
+    /// 生バイト列 (形式を問わない)。
+    Data,
+}
+
+impl FieldType {
+    /// `value` がこの型の形式に合うか。
+    #[must_use]
+    pub fn accepts(self, value: &str) -> bool {
+        let b = value.as_bytes();
+        match self {
+            Self::String | Self::Data => true,
+            Self::Char => value.chars().count() == 1,
+            Self::Int => parse_i64_bytes(b).is_some(),
+            Self::UInt => parse_u64_bytes(b).is_some(),
+            Self::Decimal => Decimal::parse_bytes(b).is_some(),
+            Self::Boolean => parse_bool(value).is_some(),
+            Self::UtcTimestamp => parse_utc_timestamp_bytes(b).is_some(),
+        }
+    }
+}
+
+/// フィールド定義。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDef {
+    /// タグ番号。
+    pub tag: u32,
+    /// 値の型。
+    pub field_type: FieldType,
+    /// 許可する値 (空なら型に合う任意の値)。
+    pub values: Vec<String>,
+}
+
+impl FieldDef {
+    /// 値ドメインなしで作成。
+    #[must_use]
+    pub const fn new(tag: u32, field_type: FieldType) -> Self {
+        Self {
+            tag,
+            field_type,
+            values: Vec::new(),
+        }
+    }
+
+    /// 許可する値を設定。
+    #[must_use]
+    pub fn values(mut self, values: &[&str]) -> Self {
+        self.values = values.iter().map(|v| (*v).to_string()).collect();
+        self
+    }
+
+    /// `value` を検査。
+    fn check(&self, value: &str) -> Result<(), ValidationError> {
+        if !self.field_type.accepts(value) {
+            return Err(ValidationError::IncorrectDataFormat {
+                tag: self.tag,
+                value: value.to_string(),
+            });
+        }
+        if !self.values.is_empty() && !self.values.iter().any(|v| v == value) {
+            return Err(ValidationError::ValueOutOfRange {
+                tag: self.tag,
+                value: value.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// `MsgType` ごとのメッセージ定義。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageDef {
+    /// `MsgType` (35)。
+    pub msg_type: String,
+    /// 必須の本文タグ。
+    pub required: Vec<u32>,
+    /// 任意の本文タグ。
+    pub optional: Vec<u32>,
+    /// 本文に置ける繰り返しグループ。
+    pub groups: Vec<GroupDef>,
+}
+
+impl MessageDef {
+    /// 本文タグなしで作成。
+    #[must_use]
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            required: Vec::new(),
+            optional: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// 必須タグを追加。
+    #[must_use]
+    pub fn required(mut self, tags: &[u32]) -> Self {
+        self.required.extend_from_slice(tags);
+        self
+    }
+
+    /// 任意タグを追加。
+    #[must_use]
+    pub fn optional(mut self, tags: &[u32]) -> Self {
+        self.optional.extend_from_slice(tags);
+        self
+    }
+
+    /// 繰り返しグループを追加。
+    #[must_use]
+    pub fn group(mut self, group: GroupDef) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// [`crate::repeating_group::STANDARD_GROUPS`] の `count_tag` の
+    /// グループを追加 (標準定義が無ければ何もしない)。
+    #[must_use]
+    pub fn standard_group(mut self, count_tag: u32) -> Self {
+        self.groups.extend(find_group_def(count_tag).copied());
+        self
+    }
+
+    /// `tag` が本文タグ (グループのカウントタグを含む) として定義されているか。
+    #[must_use]
+    pub fn defines(&self, tag: u32) -> bool {
+        self.required.contains(&tag)
+            || self.optional.contains(&tag)
+            || self.groups.iter().any(|g| g.count_tag == tag)
+    }
+}
+
+/// `MarketDataRequest` の `NoMDEntryTypes` グループ。
+const MD_ENTRY_TYPES_GROUP: GroupDef = GroupDef {
+    count_tag: tag::NO_MD_ENTRY_TYPES,
+    delimiter_tag: tag::MD_ENTRY_TYPE,
+    member_tags: &[],
+};
+
+/// メッセージ定義の集合。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    header_required: Vec<u32>,
+    header_optional: Vec<u32>,
+    fields: HashMap<u32, FieldDef>,
+    messages: HashMap<String, MessageDef>,
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dictionary {
+    /// 標準ヘッダのみを持つ空の辞書を作成。
+    ///
+    /// 必須ヘッダは [`REQUIRED_HEADER_TAGS`]、任意ヘッダは
+    /// [`HEADER_TAG_ORDER`] と署名 (93 / 89)。
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            header_required: REQUIRED_HEADER_TAGS.to_vec(),
+            header_optional: HEADER_TAG_ORDER
+                .iter()
+                .copied()
+                .chain([tag::SIGNATURE_LENGTH, tag::SIGNATURE])
+                .filter(|t| !REQUIRED_HEADER_TAGS.contains(t))
+                .collect(),
+            fields: HashMap::new(),
+            messages: HashMap::new(),
+        }
+    }
+
+    /// 主要メッセージの FIX 4.4 定義。
+    #[must_use]
+    pub fn fix44() -> Self {
+        use FieldType::{Boolean, Char, Data, Int, String as Str, UInt, UtcTimestamp};
+
+        let mut dict = Self::new();
+        for (t, ty) in [
+            (tag::SENDER_COMP_ID, Str),
+            (tag::TARGET_COMP_ID, Str),
+            (tag::ON_BEHALF_OF_COMP_ID, Str),
+            (tag::DELIVER_TO_COMP_ID, Str),
+            (tag::SENDER_SUB_ID, Str),
+            (tag::TARGET_SUB_ID, Str),
+            (tag::ON_BEHALF_OF_SUB_ID, Str),
+            (tag::DELIVER_TO_SUB_ID, Str),
+            (tag::MSG_SEQ_NUM, UInt),
+            (tag::SENDING_TIME, UtcTimestamp),
+            (tag::ORIG_SENDING_TIME, UtcTimestamp),
+            (tag::POSS_DUP_FLAG, Boolean),
+            (tag::POSS_RESEND, Boolean),
+            (tag::SIGNATURE_LENGTH, UInt),
+            (tag::SIGNATURE, Data),
+            (tag::RAW_DATA_LENGTH, UInt),
+            (tag::RAW_DATA, Data),
+            (tag::HEART_BT_INT, UInt),
+            (tag::TEST_REQ_ID, Str),
+            (tag::USERNAME, Str),
+            (tag::PASSWORD, Str),
+            (tag::BEGIN_SEQ_NO, UInt),
+            (tag::END_SEQ_NO, UInt),
+            (tag::NEW_SEQ_NO, UInt),
+            (tag::GAP_FILL_FLAG, Boolean),
+            (tag::REF_SEQ_NUM, UInt),
+            (tag::REF_TAG_ID, UInt),
+            (tag::REF_MSG_TYPE, Str),
+            (tag::SESSION_REJECT_REASON, Int),
+            (tag::TEXT, Str),
+            (tag::CL_ORD_ID, Str),
+            (tag::ORIG_CL_ORD_ID, Str),
+            (tag::ORDER_ID, Str),
+            (tag::EXEC_ID, Str),
+            (tag::ACCOUNT, Str),
+            (tag::SYMBOL, Str),
+            (tag::SECURITY_ID, Str),
+            (tag::SECURITY_EXCHANGE, Str),
+            (tag::SECURITY_TYPE, Str),
+            (tag::CURRENCY, Str),
+            (tag::TRANSACT_TIME, UtcTimestamp),
+            (tag::PRICE, FieldType::Decimal),
+            (tag::ORDER_QTY, FieldType::Decimal),
+            (tag::LAST_PX, FieldType::Decimal),
+            (tag::LAST_QTY, FieldType::Decimal),
+            (tag::LEAVES_QTY, FieldType::Decimal),
+            (tag::CUM_QTY, FieldType::Decimal),
+            (tag::AVG_PX, FieldType::Decimal),
+            (tag::CXL_REJ_REASON, Int),
+            (tag::NO_PARTY_IDS, UInt),
+            (tag::PARTY_ID, Str),
+            (tag::PARTY_ID_SOURCE, Char),
+            (tag::PARTY_ROLE, Int),
+            (tag::MD_REQ_ID, Str),
+            (tag::MARKET_DEPTH, UInt),
+            (tag::NO_MD_ENTRY_TYPES, UInt),
+            (tag::NO_MD_ENTRIES, UInt),
+            (tag::NO_RELATED_SYM, UInt),
+            (tag::MD_ENTRY_PX, FieldType::Decimal),
+            (tag::MD_ENTRY_SIZE, FieldType::Decimal),
+            (tag::MD_ENTRY_ID, Str),
+            (tag::MD_ENTRY_POSITION_NO, UInt),
+            (tag::MD_ENTRY_DATE, Str),
+            (tag::MD_ENTRY_TIME, Str),
+            (tag::STOP_PX, FieldType::Decimal),
+            (tag::EXPIRE_TIME, UtcTimestamp),
+            (tag::EXEC_INST, Str),
+            (tag::ORD_STATUS_REQ_ID, Str),
+            (tag::MASS_STATUS_REQ_ID, Str),
+            (tag::TOT_NUM_REPORTS, UInt),
+            (tag::LAST_RPT_REQUESTED, Boolean),
+            (tag::LIST_ID, Str),
+            (tag::TOT_NO_ORDERS, UInt),
+            (tag::NO_ORDERS, UInt),
+            (tag::LIST_SEQ_NO, UInt),
+            (tag::NO_RPTS, UInt),
+            (tag::RPT_SEQ, UInt),
+            (tag::LIST_STATUS_TEXT, Str),
+            (tag::CXL_QTY, FieldType::Decimal),
+        ] {
+            dict = dict.field(FieldDef::new(t, ty));
+        }
+        dict = dict
+            .field(
+                FieldDef::new(tag::ENCRYPT_METHOD, Int)
+                    .values(&["0", "1", "2", "3", "4", "5", "6"]),
+            )
+            .field(FieldDef::new(tag::RESET_SEQ_NUM_FLAG, Boolean))
+            .field(FieldDef::new(tag::SIDE, Char).values(&[
+                "1", "2", "3", "4", "5", "6", "7", "8", "9", "A", "B", "C", "D", "E", "F", "G",
+            ]))
+            .field(FieldDef::new(tag::ORD_TYPE, Char).values(&[
+                "1", "2", "3", "4", "6", "7", "8", "9", "D", "E", "G", "I", "J", "K", "L", "M",
+                "P", "Q",
+            ]))
+            .field(
+                FieldDef::new(tag::TIME_IN_FORCE, Char)
+                    .values(&["0", "1", "2", "3", "4", "5", "6", "7"]),
+            )
+            .field(FieldDef::new(tag::HANDL_INST, Char).values(&["1", "2", "3"]))
+            .field(FieldDef::new(tag::EXEC_TYPE, Char).values(&[
+                "0", "3", "4", "5", "6", "7", "8", "9", "A", "B", "C", "D", "E", "F", "G", "H", "I",
+            ]))
+            .field(FieldDef::new(tag::ORD_STATUS, Char).values(&[
+                "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "A", "B", "C", "D", "E",
+            ]))
+            .field(FieldDef::new(tag::CXL_REJ_RESPONSE_TO, Char).values(&["1", "2"]))
+            .field(FieldDef::new(tag::SUBSCRIPTION_REQUEST_TYPE, Char).values(&["0", "1", "2"]))
+            .field(FieldDef::new(tag::MD_UPDATE_TYPE, Int).values(&["0", "1"]))
+            .field(
+                FieldDef::new(tag::MD_UPDATE_ACTION, Char).values(&["0", "1", "2", "3", "4", "5"]),
+            )
+            .field(FieldDef::new(tag::MD_ENTRY_TYPE, Char))
+            .field(FieldDef::new(tag::ORDER_CAPACITY, Char).values(&["A", "G", "I", "P", "R", "W"]))
+            .field(
+                FieldDef::new(tag::MASS_STATUS_REQ_TYPE, Int)
+                    .values(&["1", "2", "3", "4", "5", "6", "7", "8"]),
+            )
+            .field(FieldDef::new(tag::BID_TYPE, Int).values(&["1", "2", "3"]))
+            .field(
+                FieldDef::new(tag::LIST_STATUS_TYPE, Int).values(&["1", "2", "3", "4", "5", "6"]),
+            )
+            .field(
+                FieldDef::new(tag::LIST_ORDER_STATUS, Int)
+                    .values(&["1", "2", "3", "4", "5", "6", "7"]),
+            );
+
+        let order_common = [
+            tag::ACCOUNT,
+            tag::ORDER_QTY,
+            tag::PRICE,
+            tag::STOP_PX,
+            tag::TIME_IN_FORCE,
+            tag::EXPIRE_TIME,
+            tag::ORDER_CAPACITY,
+            tag::EXEC_INST,
+            tag::HANDL_INST,
+            tag::SECURITY_ID,
+            tag::SECURITY_EXCHANGE,
+            tag::CURRENCY,
+            tag::TEXT,
+        ];
+        dict.message(MessageDef::new("0").optional(&[tag::TEST_REQ_ID]))
+            .message(MessageDef::new("1").required(&[tag::TEST_REQ_ID]))
+            .message(MessageDef::new("2").required(&[tag::BEGIN_SEQ_NO, tag::END_SEQ_NO]))
+            .message(
+                MessageDef::new("3")
+                    .required(&[tag::REF_SEQ_NUM])
+                    .optional(&[
+                        tag::REF_TAG_ID,
+                        tag::REF_MSG_TYPE,
+                        tag::SESSION_REJECT_REASON,
+                        tag::TEXT,
+                    ]),
+            )
+            .message(
+                MessageDef::new("4")
+                    .required(&[tag::NEW_SEQ_NO])
+                    .optional(&[tag::GAP_FILL_FLAG]),
+            )
+            .message(MessageDef::new("5").optional(&[tag::TEXT]))
+            .message(
+                MessageDef::new("A")
+                    .required(&[tag::ENCRYPT_METHOD, tag::HEART_BT_INT])
+                    .optional(&[
+                        tag::RESET_SEQ_NUM_FLAG,
+                        tag::USERNAME,
+                        tag::PASSWORD,
+                        tag::RAW_DATA_LENGTH,
+                        tag::RAW_DATA,
+                        tag::DEFAULT_APPL_VER_ID,
+                    ]),
+            )
+            .message(
+                MessageDef::new("D")
+                    .required(&[
+                        tag::CL_ORD_ID,
+                        tag::SYMBOL,
+                        tag::SIDE,
+                        tag::TRANSACT_TIME,
+                        tag::ORD_TYPE,
+                    ])
+                    .optional(&order_common)
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("F")
+                    .required(&[
+                        tag::ORIG_CL_ORD_ID,
+                        tag::CL_ORD_ID,
+                        tag::SYMBOL,
+                        tag::SIDE,
+                        tag::TRANSACT_TIME,
+                    ])
+                    .optional(&[tag::ORDER_ID, tag::ACCOUNT, tag::ORDER_QTY, tag::TEXT])
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("G")
+                    .required(&[
+                        tag::ORIG_CL_ORD_ID,
+                        tag::CL_ORD_ID,
+                        tag::SYMBOL,
+                        tag::SIDE,
+                        tag::TRANSACT_TIME,
+                        tag::ORD_TYPE,
+                    ])
+                    .optional(&[tag::ORDER_ID])
+                    .optional(&order_common)
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("8")
+                    .required(&[
+                        tag::ORDER_ID,
+                        tag::EXEC_ID,
+                        tag::EXEC_TYPE,
+                        tag::ORD_STATUS,
+                        tag::SYMBOL,
+                        tag::SIDE,
+                        tag::LEAVES_QTY,
+                        tag::CUM_QTY,
+                        tag::AVG_PX,
+                    ])
+                    .optional(&[
+                        tag::CL_ORD_ID,
+                        tag::ORIG_CL_ORD_ID,
+                        tag::ORD_TYPE,
+                        tag::LAST_PX,
+                        tag::LAST_QTY,
+                        tag::TRANSACT_TIME,
+                        tag::ORD_STATUS_REQ_ID,
+                        tag::MASS_STATUS_REQ_ID,
+                        tag::TOT_NUM_REPORTS,
+                        tag::LAST_RPT_REQUESTED,
+                    ])
+                    .optional(&order_common)
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("9")
+                    .required(&[
+                        tag::ORDER_ID,
+                        tag::CL_ORD_ID,
+                        tag::ORIG_CL_ORD_ID,
+                        tag::ORD_STATUS,
+                        tag::CXL_REJ_RESPONSE_TO,
+                    ])
+                    .optional(&[tag::CXL_REJ_REASON, tag::ACCOUNT, tag::TEXT]),
+            )
+            .message(
+                MessageDef::new("H")
+                    .required(&[tag::CL_ORD_ID, tag::SYMBOL, tag::SIDE])
+                    .optional(&[tag::ORDER_ID, tag::ORD_STATUS_REQ_ID, tag::ACCOUNT])
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("AF")
+                    .required(&[tag::MASS_STATUS_REQ_ID, tag::MASS_STATUS_REQ_TYPE])
+                    .optional(&[tag::ACCOUNT, tag::SYMBOL, tag::SIDE])
+                    .standard_group(tag::NO_PARTY_IDS),
+            )
+            .message(
+                MessageDef::new("E")
+                    .required(&[tag::LIST_ID, tag::BID_TYPE, tag::TOT_NO_ORDERS])
+                    .standard_group(tag::NO_ORDERS),
+            )
+            .message(
+                MessageDef::new("N")
+                    .required(&[
+                        tag::LIST_ID,
+                        tag::LIST_STATUS_TYPE,
+                        tag::NO_RPTS,
+                        tag::LIST_ORDER_STATUS,
+                        tag::RPT_SEQ,
+                        tag::TOT_NO_ORDERS,
+                    ])
+                    .optional(&[tag::LIST_STATUS_TEXT, tag::TRANSACT_TIME])
+                    .standard_group(tag::NO_ORDERS),
+            )
+            .message(
+                MessageDef::new("V")
+                    .required(&[
+                        tag::MD_REQ_ID,
+                        tag::SUBSCRIPTION_REQUEST_TYPE,
+                        tag::MARKET_DEPTH,
+                    ])
+                    .optional(&[tag::MD_UPDATE_TYPE])
+                    .group(MD_ENTRY_TYPES_GROUP)
+                    .standard_group(tag::NO_RELATED_SYM),
+            )
+            .message(
+                MessageDef::new("W")
+                    .optional(&[tag::MD_REQ_ID, tag::SYMBOL, tag::SECURITY_ID])
+                    .standard_group(tag::NO_MD_ENTRIES),
+            )
+            .message(
+                MessageDef::new("X")
+                    .optional(&[tag::MD_REQ_ID])
+                    .standard_group(tag::NO_MD_ENTRIES),
+            )
+    }
+
+    /// フィールド定義を追加 (同じタグの定義は置き換える)。
+    #[must_use]
+    pub fn field(mut self, def: FieldDef) -> Self {
+        self.fields.insert(def.tag, def);
+        self
+    }
+
+    /// メッセージ定義を追加 (同じ `MsgType` の定義は置き換える)。
+    #[must_use]
+    pub fn message(mut self, def: MessageDef) -> Self {
+        self.messages.insert(def.msg_type.clone(), def);
+        self
+    }
+
+    /// 全メッセージで許可する任意ヘッダタグを追加。
+    #[must_use]
+    pub fn header_field(mut self, tag: u32) -> Self {
+        self.header_optional.push(tag);
+        self
+    }
+
+    /// `tag` のフィールド定義。
+    #[must_use]
+    pub fn field_def(&self, tag: u32) -> Option<&FieldDef> {
+        self.fields.get(&tag)
+    }
+
+    /// `msg_type` のメッセージ定義。
+    #[must_use]
+    pub fn message_def(&self, msg_type: &str) -> Option<&MessageDef> {
+        self.messages.get(msg_type)
+    }
+
+    /// ワイヤ順の tag-value 列を `msg_type` の定義で検査。
+    ///
+    /// `BeginString` / `BodyLength` / `MsgType` / `CheckSum` は含めない。
+    ///
+    /// # Errors
+    ///
+    /// 最初に見つかった問題。未定義の `MsgType`
+    /// ([`ValidationError::InvalidMsgType`])、必須タグ欠落、未定義タグ、
+    /// 空値、型・値ドメイン違反、グループの件数不一致・順序違反。
+    pub fn validate_fields(
+        &self,
+        msg_type: &str,
+        fields: &[(u32, &str)],
+    ) -> Result<(), ValidationError> {
+        self.check_fields(msg_type, fields, &[])
+    }
+
+    /// [`Self::validate_fields`] の本体。`stamped` のタグは送信時に
+    /// 付与されるものとして必須検査から除く。
+    pub(crate) fn check_fields(
+        &self,
+        msg_type: &str,
+        fields: &[(u32, &str)],
+        stamped: &[u32],
+    ) -> Result<(), ValidationError> {
+        let def = self
+            .messages
+            .get(msg_type)
+            .ok_or(ValidationError::InvalidMsgType)?;
+        for &t in self.header_required.iter().chain(&def.required) {
+            if !stamped.contains(&t) && !fields.iter().any(|&(f, _)| f == t) {
+                return Err(ValidationError::RequiredTagMissing { tag: t });
+            }
+        }
+        let mut i = 0;
+        while i < fields.len() {
+            let (t, value) = fields[i];
+            self.check_value(t, value)?;
+            if let Some(group) = def.groups.iter().find(|g| g.count_tag == t) {
+                i = self.check_group(group, value, fields, i + 1)?;
+                continue;
+            }
+            if !self.header_required.contains(&t)
+                && !self.header_optional.contains(&t)
+                && !def.defines(t)
+            {
+                return Err(ValidationError::TagNotDefinedForMsgType { tag: t });
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// 空値・型・値ドメインを検査。
+    fn check_value(&self, tag: u32, value: &str) -> Result<(), ValidationError> {
+        if value.is_empty() {
+            return Err(ValidationError::TagWithoutValue { tag });
+        }
+        self.fields.get(&tag).map_or(Ok(()), |def| def.check(value))
+    }
+
+    /// `fields[start..]` のグループ本体を検査し、グループ直後の位置を返す。
+    fn check_group(
+        &self,
+        group: &GroupDef,
+        count: &str,
+        fields: &[(u32, &str)],
+        start: usize,
+    ) -> Result<usize, ValidationError> {
+        let expected = parse_u64_bytes(count.as_bytes()).ok_or_else(|| {
+            ValidationError::IncorrectDataFormat {
+                tag: group.count_tag,
+                value: count.to_string(),
+            }
+        })?;
+        let mut entries = 0u64;
+        let mut i = start;
+        while let Some(&(t, value)) = fields.get(i).filter(|(t, _)| group.contains(*t)) {
+            if t == group.delimiter_tag {
+                entries += 1;
+            } else if entries == 0 {
+                return Err(ValidationError::GroupFieldsOutOfOrder {
+                    tag: group.count_tag,
+                });
+            }
+            self.check_value(t, value)?;
+            i += 1;
+        }
+        if entries != expected {
+            return Err(ValidationError::IncorrectNumInGroupCount {
+                tag: group.count_tag,
+            });
+        }
+        Ok(i)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: [(u32, &str); 4] = [
+        (tag::SENDER_COMP_ID, "ALICE"),
+        (tag::TARGET_COMP_ID, "BROKER"),
+        (tag::MSG_SEQ_NUM, "2"),
+        (tag::SENDING_TIME, "20260101-00:00:00.000"),
+    ];
+
+    fn order(body: &[(u32, &'static str)]) -> Vec<(u32, &'static str)> {
+        let mut fields = HEADER.to_vec();
+        fields.extend_from_slice(&[
+            (tag::CL_ORD_ID, "A1"),
+            (tag::SYMBOL, "7203"),
+            (tag::SIDE, "1"),
+            (tag::TRANSACT_TIME, "20260101-00:00:00.000"),
+            (tag::ORD_TYPE, "2"),
+        ]);
+        fields.extend_from_slice(body);
+        fields
+    }
+
+    #[test]
+    fn valid_order_with_parties_passes() {
+        let dict = Dictionary::fix44();
+        let fields = order(&[
+            (tag::PRICE, "101.5"),
+            (tag::ORDER_QTY, "100"),
+            (tag::NO_PARTY_IDS, "2"),
+            (tag::PARTY_ID, "DESK1"),
+            (tag::PARTY_ID_SOURCE, "D"),
+            (tag::PARTY_ROLE, "11"),
+            (tag::PARTY_ID, "TRADER"),
+            (tag::PARTY_ROLE, "12"),
+            (tag::TEXT, "ok"),
+        ]);
+        assert_eq!(dict.validate_fields("D", &fields), Ok(()));
+    }
+
+    #[test]
+    fn required_type_and_domain() {
+        let dict = Dictionary::fix44();
+        let mut fields = order(&[]);
+        fields.retain(|&(t, _)| t != tag::CL_ORD_ID);
+        assert_eq!(
+            dict.validate_fields("D", &fields),
+            Err(ValidationError::RequiredTagMissing {
+                tag: tag::CL_ORD_ID
+            })
+        );
+        assert_eq!(
+            dict.validate_fields("D", &order(&[(tag::PRICE, "abc")])),
+            Err(ValidationError::IncorrectDataFormat {
+                tag: tag::PRICE,
+                value: "abc".into()
+            })
+        );
+        assert_eq!(
+            dict.validate_fields("D", &order(&[(tag::TIME_IN_FORCE, "9")])),
+            Err(ValidationError::ValueOutOfRange {
+                tag: tag::TIME_IN_FORCE,
+                value: "9".into()
+            })
+        );
+        assert_eq!(
+            dict.validate_fields("D", &order(&[(tag::EXEC_TYPE, "0")])),
+            Err(ValidationError::TagNotDefinedForMsgType {
+                tag: tag::EXEC_TYPE
+            })
+        );
+        assert_eq!(
+            dict.validate_fields("D", &order(&[(tag::TEXT, "")])),
+            Err(ValidationError::TagWithoutValue { tag: tag::TEXT })
+        );
+        assert_eq!(
+            dict.validate_fields("ZZ", &order(&[])),
+            Err(ValidationError::InvalidMsgType)
+        );
+    }
+
+    #[test]
+    fn group_structure() {
+        let dict = Dictionary::fix44();
+        assert_eq!(
+            dict.validate_fields(
+                "D",
+                &order(&[
+                    (tag::NO_PARTY_IDS, "2"),
+                    (tag::PARTY_ID, "DESK1"),
+                    (tag::PARTY_ROLE, "11"),
+                ])
+            ),
+            Err(ValidationError::IncorrectNumInGroupCount {
+                tag: tag::NO_PARTY_IDS
+            })
+        );
+        assert_eq!(
+            dict.validate_fields(
+                "D",
+                &order(&[
+                    (tag::NO_PARTY_IDS, "1"),
+                    (tag::PARTY_ROLE, "11"),
+                    (tag::PARTY_ID, "DESK1"),
+                ])
+            ),
+            Err(ValidationError::GroupFieldsOutOfOrder {
+                tag: tag::NO_PARTY_IDS
+            })
+        );
+        // グループ外に置かれたメンバタグは未定義タグ
+        assert_eq!(
+            dict.validate_fields("D", &order(&[(tag::PARTY_ID, "DESK1")])),
+            Err(ValidationError::TagNotDefinedForMsgType { tag: tag::PARTY_ID })
+        );
+    }
+
+    #[test]
+    fn custom_definitions_override_builtin() {
+        let dict = Dictionary::fix44()
+            .field(FieldDef::new(tag::ORD_TYPE, FieldType::Char).values(&["1", "2"]))
+            .message(
+                MessageDef::new("U1")
+                    .required(&[tag::TEXT])
+                    .optional(&[9001]),
+            )
+            .header_field(tag::APPL_VER_ID);
+        assert_eq!(
+            dict.validate_fields(
+                "D",
+                &order(&[])
+                    .into_iter()
+                    .map(|(t, v)| { (t, if t == tag::ORD_TYPE { "P" } else { v }) })
+                    .collect::<Vec<_>>()
+            ),
+            Err(ValidationError::ValueOutOfRange {
+                tag: tag::ORD_TYPE,
+                value: "P".into()
+            })
+        );
+        let mut fields = HEADER.to_vec();
+        fields.extend_from_slice(&[(tag::APPL_VER_ID, "9"), (tag::TEXT, "x"), (9001, "y")]);
+        assert_eq!(dict.validate_fields("U1", &fields), Ok(()));
+        assert!(dict.message_def("U1").unwrap().defines(9001));
+        assert_eq!(
+            dict.field_def(tag::SIDE).unwrap().field_type,
+            FieldType::Char
+        );
+    }
+}
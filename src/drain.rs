@@ -0,0 +1,342 @@
+//! Outbound Drain Metrics & Slow-Consumer Detection
+//!
+//! セッション単位の送信キュー (深さ・滞留時間・flush 所要時間) を計測し、
+//! 閾値超過時に [`SlowConsumer`] イベントを発火する。
+//! 時刻は呼び出し側が供給するナノ秒 (`now_ns`)。
+
+use std::collections::VecDeque;
+
+/// スロー判定の閾値。未設定の項目は判定しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DrainThresholds {
+    /// キュー深さの上限 (メッセージ数)。
+    pub max_depth: Option<usize>,
+    /// 最古メッセージの滞留時間上限 (ns)。
+    pub max_age_ns: Option<u64>,
+    /// 1 メッセージあたりの enqueue → flush 所要時間上限 (ns)。
+    pub max_flush_ns: Option<u64>,
+}
+
+impl DrainThresholds {
+    /// 判定なしの閾値を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_depth: None,
+            max_age_ns: None,
+            max_flush_ns: None,
+        }
+    }
+
+    /// キュー深さの上限を設定。
+    #[must_use]
+    pub const fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// 滞留時間の上限を設定。
+    #[must_use]
+    pub const fn max_age_ns(mut self, max: u64) -> Self {
+        self.max_age_ns = Some(max);
+        self
+    }
+
+    /// flush 所要時間の上限を設定。
+    #[must_use]
+    pub const fn max_flush_ns(mut self, max: u64) -> Self {
+        self.max_flush_ns = Some(max);
+        self
+    }
+}
+
+/// スロー判定理由。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowConsumer {
+    /// キュー深さ超過。
+    QueueDepth {
+        /// 現在の深さ。
+        depth: usize,
+        /// 上限。
+        max: usize,
+    },
+    /// 最古メッセージの滞留時間超過。
+    QueueAge {
+        /// 滞留時間 (ns)。
+        age_ns: u64,
+        /// 上限 (ns)。
+        max_ns: u64,
+    },
+    /// flush 所要時間超過。
+    FlushTime {
+        /// 所要時間 (ns)。
+        flush_ns: u64,
+        /// 上限 (ns)。
+        max_ns: u64,
+    },
+}
+
+impl core::fmt::Display for SlowConsumer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::QueueDepth { depth, max } => {
+                write!(f, "outbound queue depth {depth} exceeds {max}")
+            }
+            Self::QueueAge { age_ns, max_ns } => {
+                write!(
+                    f,
+                    "oldest outbound message aged {age_ns}ns exceeds {max_ns}ns"
+                )
+            }
+            Self::FlushTime { flush_ns, max_ns } => {
+                write!(f, "time-to-flush {flush_ns}ns exceeds {max_ns}ns")
+            }
+        }
+    }
+}
+
+/// 送信キューのメトリクス。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrainMetrics {
+    /// 未 flush のメッセージ数。
+    pub depth: usize,
+    /// 未 flush のバイト数。
+    pub queued_bytes: usize,
+    /// 最古メッセージの滞留時間 (ns)。
+    pub oldest_age_ns: u64,
+    /// 直近に flush したメッセージの所要時間 (ns)。
+    pub last_flush_ns: u64,
+    /// flush 所要時間の最大値 (ns)。
+    pub max_flush_ns: u64,
+    /// flush 済みメッセージ総数。
+    pub flushed_total: u64,
+}
+
+/// 送信キュー監視。
+///
+/// スロー状態への遷移時にのみ [`SlowConsumer`] を返す (エッジトリガ)。
+/// 全閾値を下回ると状態は解除され、再度超過すると再び発火する。
+#[derive(Debug, Clone, Default)]
+pub struct DrainMonitor {
+    thresholds: DrainThresholds,
+    /// (enqueue 時刻, バイト長)。
+    queue: VecDeque<(u64, usize)>,
+    queued_bytes: usize,
+    last_flush_ns: u64,
+    max_flush_ns: u64,
+    flushed_total: u64,
+    slow: bool,
+}
+
+impl DrainMonitor {
+    /// 新しいモニタを作成。
+    #[must_use]
+    pub fn new(thresholds: DrainThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Self::default()
+        }
+    }
+
+    /// 閾値を取得。
+    #[must_use]
+    pub const fn thresholds(&self) -> &DrainThresholds {
+        &self.thresholds
+    }
+
+    /// 閾値を変更。
+    pub const fn set_thresholds(&mut self, thresholds: DrainThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// メッセージのキュー投入を記録。
+    pub fn on_enqueue(&mut self, now_ns: u64, len: usize) -> Option<SlowConsumer> {
+        self.queue.push_back((now_ns, len));
+        self.queued_bytes += len;
+        self.evaluate(now_ns, None)
+    }
+
+    /// 古い順に `count` 件の送出完了を記録。
+    pub fn on_flush(&mut self, now_ns: u64, count: usize) -> Option<SlowConsumer> {
+        let mut worst = None;
+        for _ in 0..count {
+            let Some((enqueued_ns, len)) = self.queue.pop_front() else {
+                break;
+            };
+            let flush_ns = now_ns.saturating_sub(enqueued_ns);
+            self.queued_bytes -= len;
+            self.last_flush_ns = flush_ns;
+            self.max_flush_ns = self.max_flush_ns.max(flush_ns);
+            self.flushed_total += 1;
+            worst = worst.max(Some(flush_ns));
+        }
+        self.evaluate(now_ns, worst)
+    }
+
+    /// 時間経過のみで閾値判定 (タイマー駆動用)。
+    pub fn check(&mut self, now_ns: u64) -> Option<SlowConsumer> {
+        self.evaluate(now_ns, None)
+    }
+
+    /// スロー状態か。
+    #[must_use]
+    pub const fn is_slow(&self) -> bool {
+        self.slow
+    }
+
+    /// 現時点のメトリクス。
+    #[must_use]
+    pub fn metrics(&self, now_ns: u64) -> DrainMetrics {
+        DrainMetrics {
+            depth: self.queue.len(),
+            queued_bytes: self.queued_bytes,
+            oldest_age_ns: self.oldest_age_ns(now_ns),
+            last_flush_ns: self.last_flush_ns,
+            max_flush_ns: self.max_flush_ns,
+            flushed_total: self.flushed_total,
+        }
+    }
+
+    fn oldest_age_ns(&self, now_ns: u64) -> u64 {
+        self.queue
+            .front()
+            .map_or(0, |&(t, _)| now_ns.saturating_sub(t))
+    }
+
+    fn evaluate(&mut self, now_ns: u64, flush_ns: Option<u64>) -> Option<SlowConsumer> {
+        let breach = self.breach(now_ns, flush_ns);
+        let was_slow = self.slow;
+        self.slow = breach.is_some();
+        breach.filter(|_| !was_slow)
+    }
+
+    fn breach(&self, now_ns: u64, flush_ns: Option<u64>) -> Option<SlowConsumer> {
+        let t = &self.thresholds;
+        if let Some(max) = t.max_depth {
+            let depth = self.queue.len();
+            if depth > max {
+                return Some(SlowConsumer::QueueDepth { depth, max });
+            }
+        }
+        if let Some(max_ns) = t.max_age_ns {
+            let age_ns = self.oldest_age_ns(now_ns);
+            if age_ns > max_ns {
+                return Some(SlowConsumer::QueueAge { age_ns, max_ns });
+            }
+        }
+        if let (Some(max_ns), Some(flush_ns)) = (t.max_flush_ns, flush_ns) {
+            if flush_ns > max_ns {
+                return Some(SlowConsumer::FlushTime { flush_ns, max_ns });
+            }
+        }
+        None
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_track_depth_bytes_and_age() {
+        let mut m = DrainMonitor::new(DrainThresholds::new());
+        m.on_enqueue(100, 40);
+        m.on_enqueue(150, 60);
+        let metrics = m.metrics(400);
+        assert_eq!(metrics.depth, 2);
+        assert_eq!(metrics.queued_bytes, 100);
+        assert_eq!(metrics.oldest_age_ns, 300);
+
+        m.on_flush(500, 1);
+        let metrics = m.metrics(500);
+        assert_eq!(metrics.depth, 1);
+        assert_eq!(metrics.queued_bytes, 60);
+        assert_eq!(metrics.last_flush_ns, 400);
+        assert_eq!(metrics.flushed_total, 1);
+        assert_eq!(metrics.oldest_age_ns, 350);
+    }
+
+    #[test]
+    fn depth_threshold_fires_once() {
+        let mut m = DrainMonitor::new(DrainThresholds::new().max_depth(2));
+        assert_eq!(m.on_enqueue(0, 1), None);
+        assert_eq!(m.on_enqueue(0, 1), None);
+        assert_eq!(
+            m.on_enqueue(0, 1),
+            Some(SlowConsumer::QueueDepth { depth: 3, max: 2 })
+        );
+        assert!(m.is_slow());
+        // 既にスロー状態なので再発火しない
+        assert_eq!(m.on_enqueue(0, 1), None);
+    }
+
+    #[test]
+    fn recovers_and_refires() {
+        let mut m = DrainMonitor::new(DrainThresholds::new().max_depth(1));
+        m.on_enqueue(0, 1);
+        assert!(m.on_enqueue(0, 1).is_some());
+        m.on_flush(10, 2);
+        assert!(!m.is_slow());
+        m.on_enqueue(20, 1);
+        assert!(m.on_enqueue(20, 1).is_some());
+    }
+
+    #[test]
+    fn age_threshold_via_timer() {
+        let mut m = DrainMonitor::new(DrainThresholds::new().max_age_ns(1_000));
+        m.on_enqueue(0, 10);
+        assert_eq!(m.check(1_000), None);
+        assert_eq!(
+            m.check(1_500),
+            Some(SlowConsumer::QueueAge {
+                age_ns: 1_500,
+                max_ns: 1_000
+            })
+        );
+    }
+
+    #[test]
+    fn flush_time_threshold() {
+        let mut m = DrainMonitor::new(DrainThresholds::new().max_flush_ns(100));
+        m.on_enqueue(0, 1);
+        m.on_enqueue(50, 1);
+        assert_eq!(
+            m.on_flush(250, 2),
+            Some(SlowConsumer::FlushTime {
+                flush_ns: 250,
+                max_ns: 100
+            })
+        );
+        assert_eq!(m.metrics(250).max_flush_ns, 250);
+        assert_eq!(m.metrics(250).last_flush_ns, 200);
+    }
+
+    #[test]
+    fn flush_more_than_queued_is_clamped() {
+        let mut m = DrainMonitor::new(DrainThresholds::new());
+        m.on_enqueue(0, 5);
+        m.on_flush(10, 5);
+        assert_eq!(
+            m.metrics(10),
+            DrainMetrics {
+                last_flush_ns: 10,
+                max_flush_ns: 10,
+                flushed_total: 1,
+                ..DrainMetrics::default()
+            }
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            SlowConsumer::QueueDepth { depth: 5, max: 4 }.to_string(),
+            "outbound queue depth 5 exceeds 4"
+        );
+    }
+}
@@ -0,0 +1,1554 @@
+//! Async Engine (tokio)
+//!
+//! TCP 接続を所有して [`FixSession`] を駆動する非同期エンジン。1 つの
+//! タスクが以下を行う。
+//!
+//! - 接続直後の Logon 送信
+//! - 受信バイト列のフレーム分割 ([`parser::declared_frame_len`]、宣言長が
+//!   上限を超えるフレームは受け取らずに切断)・受信ポリシーでの
+//!   選別 ([`FixSession::screen_inbound`])・解析と [`FixSession::on_message_with_store`]
+//! - 一定間隔の [`FixSession::tick`] (ハートビート・TestRequest・各種タイムアウト)
+//! - 送信フレームのストア記録 ([`FixSession::store_sent`]) と `ResendRequest`
+//!   への再送
+//! - (Initiator) 切断後の再接続 ([`ReconnectPolicy`])。シーケンス番号は
+//!   引き継ぎ、[`Initiator::seq_num_store`] があればプロセスの再起動も
+//!   またいで再開する
+//!
+//! アプリケーションは [`EngineHandle`] (複製可) でアプリケーションメッセージを
+//! 送り、[`EngineEvents`] で受信アプリケーションメッセージ・セッション
+//! レベルの Reject 等とセッションの状態変化 ([`EngineEvent`]) を受け取る。
+//! `engine` フィーチャで有効。
+//!
+//! 接続を始める側は [`Initiator`]、待ち受ける側は [`Listener`] で組み立てる。
+//! [`Listener`] は [`Acceptor`] の設定で接続ごとの Logon を検証し、受け付けた
+//! セッションを [`Incoming`] 経由で [`AcceptedEngine`] として渡す。
+//!
+//! ```no_run
+//! # async fn run() -> std::io::Result<()> {
+//! use std::time::Duration;
+//!
+//! use alice_fix::engine::{EngineEvent, Initiator, ReconnectPolicy};
+//! use alice_fix::session::FixSession;
+//! use alice_fix::BeginString;
+//!
+//! let session = FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+//! let policy = ReconnectPolicy::new(Duration::from_secs(1))
+//!     .max_attempts(10)
+//!     .jitter(Duration::from_millis(500));
+//! let (handle, mut events) = Initiator::new(session)
+//!     .reconnect(policy)
+//!     .connect("127.0.0.1:9878")
+//!     .await?;
+//! while let Some(event) = events.recv().await {
+//!     match event {
+//!         EngineEvent::LoggedOn => println!("logged on"),
+//!         EngineEvent::Message(msg) => println!("received {}", msg.msg_type),
+//!         EngineEvent::Admin(msg) => println!("session message {}", msg.msg_type),
+//!         EngineEvent::Disconnected(_) => println!("disconnected"),
+//!         EngineEvent::Reconnecting { attempt, delay } => {
+//!             println!("reconnect #{attempt} in {delay:?}");
+//!         }
+//!         EngineEvent::ConnectFailed(err) => println!("connect failed: {err}"),
+//!     }
+//! }
+//! # drop(handle);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{self, TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant, MissedTickBehavior};
+#[cfg(feature = "tls")]
+use tokio_rustls::{
+    rustls::{pki_types::ServerName, ClientConfig, ServerConfig},
+    TlsAcceptor, TlsConnector,
+};
+
+use crate::acceptor::{AcceptError, Acceptor};
+use crate::heartbeat::{fnv1a, splitmix64};
+use crate::message::FixMessage;
+use crate::parser::{self, ParseError, DEFAULT_MAX_FRAME_LEN};
+use crate::session::{FixSession, SessionAction, SessionId, SessionState, SubmitError};
+use crate::store::{MemoryStore, MessageStore, SeqNumStore};
+use crate::tag;
+
+/// 既定のタイマー駆動間隔。
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 既定のチャネル容量 (送信コマンド・イベントそれぞれ)。
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 既定の Logon 待ち時間 ([`Listener::logon_timeout`])。
+pub const DEFAULT_LOGON_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 既定の再接続の待ち時間の上限 ([`ReconnectPolicy::max_delay`])。
+pub const DEFAULT_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// 待ち受けソケットの受け付けに失敗した後、再試行までの待ち時間。
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 1 回の読み込みで受け取る最大バイト数。
+const READ_CHUNK: usize = 8 * 1024;
+
+/// エンジンからアプリケーションへの通知。
+#[derive(Debug)]
+pub enum EngineEvent {
+    /// Logon が成立した ([`SessionState::Active`] へ遷移)。
+    LoggedOn,
+    /// 相手方からのアプリケーションメッセージ。
+    Message(FixMessage),
+    /// 相手方からのセッションレベルのメッセージのうち、セッションが自身では
+    /// 処理せずアプリケーションに渡すもの (Reject 35=3 など、
+    /// [`SessionAction::DeliverAdmin`])。
+    Admin(FixMessage),
+    /// 接続が閉じた。I/O エラーや解析できないバイト列による切断では `Some`。
+    /// 再接続しない場合、エンジンはこの後停止する。
+    Disconnected(Option<io::Error>),
+    /// `delay` 待ってから `attempt` 回目 (1 始まり) の再接続を試みる
+    /// ([`Initiator::reconnect`])。
+    Reconnecting {
+        /// 連続した再接続の試行回数。
+        attempt: u32,
+        /// 試行までの待ち時間。
+        delay: Duration,
+    },
+    /// 再接続の試行が失敗した。
+    ConnectFailed(io::Error),
+}
+
+/// [`EngineHandle`] の操作が失敗した理由。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    /// エンジンは停止している。
+    Closed,
+    /// セッションが送信を拒否した ([`FixSession::submit`])。
+    Submit(SubmitError),
+}
+
+impl core::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Closed => write!(f, "engine closed"),
+            Self::Submit(e) => write!(f, "submit refused: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// エンジンタスクへの指示。
+enum Command {
+    Send(FixMessage, oneshot::Sender<Result<(), SubmitError>>),
+    Logout(String),
+}
+
+impl core::fmt::Debug for Command {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Send(msg, _) => f.debug_tuple("Send").field(&msg.msg_type).finish(),
+            Self::Logout(text) => f.debug_tuple("Logout").field(text).finish(),
+        }
+    }
+}
+
+/// 実行中のエンジンへの送信ハンドル。
+#[derive(Debug, Clone)]
+pub struct EngineHandle {
+    commands: mpsc::Sender<Command>,
+}
+
+impl EngineHandle {
+    /// アプリケーションメッセージを [`FixSession::submit`] で送信。Logon
+    /// 前やスロットル超過で保留されたメッセージも `Ok` (後で送られる)。
+    ///
+    /// # Errors
+    ///
+    /// [`EngineError::Submit`] はセッションの拒否、[`EngineError::Closed`]
+    /// はエンジン停止後。
+    pub async fn send(&self, msg: FixMessage) -> Result<(), EngineError> {
+        let (reply, result) = oneshot::channel();
+        self.commands
+            .send(Command::Send(msg, reply))
+            .await
+            .map_err(|_| EngineError::Closed)?;
+        result
+            .await
+            .map_err(|_| EngineError::Closed)?
+            .map_err(EngineError::Submit)
+    }
+
+    /// `Text` (58) 付きの Logout を送信。相手方の Logout 応答で接続が閉じる。
+    ///
+    /// # Errors
+    ///
+    /// [`EngineError::Closed`] はエンジン停止後。
+    pub async fn logout(&self, text: &str) -> Result<(), EngineError> {
+        self.commands
+            .send(Command::Logout(text.to_string()))
+            .await
+            .map_err(|_| EngineError::Closed)
+    }
+
+    /// エンジンが停止したか。
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+}
+
+/// エンジンからの通知の受信側。
+#[derive(Debug)]
+pub struct EngineEvents {
+    events: mpsc::Receiver<EngineEvent>,
+}
+
+impl EngineEvents {
+    /// 次の通知を待つ。エンジンが停止した後は `None`。
+    pub async fn recv(&mut self) -> Option<EngineEvent> {
+        self.events.recv().await
+    }
+}
+
+/// Initiator の再接続の方針 ([`Initiator::reconnect`])。
+///
+/// `attempt` 回目 (1 始まり) の待ち時間は `initial_delay × 2^(attempt - 1)`
+/// を `max_delay` で頭打ちにし、`0..=jitter` の揺らぎを加えたもの。揺らぎは
+/// セッションの `CompID` と回数から決定的に計算するので、多数の
+/// セッションが同時に切れても再接続の時刻は分散する。Logon まで成立した
+/// 接続が切れると、回数は 1 から数え直す。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+    jitter: Duration,
+}
+
+impl ReconnectPolicy {
+    /// 初回の待ち時間 `initial_delay` で作成。上限
+    /// [`DEFAULT_MAX_RECONNECT_DELAY`]、回数無制限、揺らぎなし。
+    #[must_use]
+    pub const fn new(initial_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay: DEFAULT_MAX_RECONNECT_DELAY,
+            max_attempts: None,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// 待ち時間の上限 (揺らぎを除く)。
+    #[must_use]
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// 連続した試行回数の上限。超えるとエンジンは停止する。
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// 待ち時間に加える揺らぎの最大値。
+    #[must_use]
+    pub const fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// `attempt` 回目の試行までの待ち時間。`seed` は揺らぎの種。回数の上限を
+    /// 超えていれば `None`。
+    #[must_use]
+    pub fn delay(&self, attempt: u32, seed: u64) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt > max) {
+            return None;
+        }
+        let factor = 1_u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let base = self
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay);
+        let jitter_ns = self.jitter.as_nanos() as u64;
+        if jitter_ns == 0 {
+            return Some(base);
+        }
+        let jitter = splitmix64(seed ^ u64::from(attempt)) % (jitter_ns + 1);
+        Some(base.saturating_add(Duration::from_nanos(jitter)))
+    }
+}
+
+/// 再接続のたびに新しい接続を開く。
+type Connector<S> = Box<dyn FnMut() -> Pin<Box<dyn Future<Output = io::Result<S>> + Send>> + Send>;
+
+/// 再接続の方針と接続手段。
+struct Reconnect<S> {
+    policy: ReconnectPolicy,
+    seed: u64,
+    connect: Connector<S>,
+}
+
+/// Initiator 側エンジンの組み立て。
+pub struct Initiator {
+    session: FixSession,
+    store: Box<dyn MessageStore + Send>,
+    seq_nums: Option<Box<dyn SeqNumStore + Send>>,
+    tick_interval: Duration,
+    channel_capacity: usize,
+    max_frame_len: usize,
+    reconnect: Option<ReconnectPolicy>,
+}
+
+impl core::fmt::Debug for Initiator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Initiator")
+            .field("state", self.session.state())
+            .field("tick_interval", &self.tick_interval)
+            .field("channel_capacity", &self.channel_capacity)
+            .field("max_frame_len", &self.max_frame_len)
+            .field("reconnect", &self.reconnect)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Initiator {
+    /// `session` を駆動するエンジンを作成。ストアは [`MemoryStore`]。
+    #[must_use]
+    pub fn new(session: FixSession) -> Self {
+        Self {
+            session,
+            store: Box::new(MemoryStore::new()),
+            seq_nums: None,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            reconnect: None,
+        }
+    }
+
+    /// 送信フレームを記録し再送に使うストア。
+    #[must_use]
+    pub fn store(mut self, store: Box<dyn MessageStore + Send>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// シーケンス番号の保存先。開始時に [`FixSession::restore`] で保存済みの
+    /// 番号から再開し、以後は処理のたびに保存する。
+    #[must_use]
+    pub fn seq_num_store(mut self, store: Box<dyn SeqNumStore + Send>) -> Self {
+        self.seq_nums = Some(store);
+        self
+    }
+
+    /// [`FixSession::tick`] の駆動間隔 ([`DEFAULT_TICK_INTERVAL`])。
+    #[must_use]
+    pub const fn tick_interval(mut self, interval: Duration) -> Self {
+        self.tick_interval = interval;
+        self
+    }
+
+    /// 送信コマンド・イベントのチャネル容量 ([`DEFAULT_CHANNEL_CAPACITY`])。
+    #[must_use]
+    pub const fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// 受信フレームの最大長 (バイト、[`DEFAULT_MAX_FRAME_LEN`])。宣言長が
+    /// これを超えるフレームを受けると接続を閉じる。
+    #[must_use]
+    pub const fn max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    /// 切断後に `policy` で再接続する。自分から Logout した場合と
+    /// [`Self::run_on`] で開始した場合は再接続しない。
+    ///
+    /// セッションは切断時に [`FixSession::on_disconnect`] で
+    /// [`SessionState::Disconnected`] へ戻り、シーケンス番号を保ったまま
+    /// 次の Logon を送る。欠番は通常どおり `ResendRequest` とストアからの
+    /// 再送で回復する。
+    #[must_use]
+    pub const fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// `addr` へ TCP 接続し (`TCP_NODELAY`)、エンジンを開始する。
+    ///
+    /// # Errors
+    ///
+    /// アドレスの解決、シーケンス番号の読み込み、最初の接続の失敗。
+    pub async fn connect(
+        self,
+        addr: impl ToSocketAddrs,
+    ) -> io::Result<(EngineHandle, EngineEvents)> {
+        let addrs: Vec<SocketAddr> = net::lookup_host(addr).await?.collect();
+        self.connect_with(move || connect_tcp(addrs.clone())).await
+    }
+
+    /// `addr` へ TCP 接続して `config` で TLS ハンドシェイクを行い
+    /// ([`crate::tls::client_config`])、エンジンを開始する。`server_name` は
+    /// SNI として送り、サーバ証明書と照合する。再接続でも同じ設定を使う。
+    ///
+    /// # Errors
+    ///
+    /// `server_name` が DNS 名・IP アドレスとして不正、接続やハンドシェイク
+    /// (証明書の検証を含む) の失敗。
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls(
+        self,
+        addr: impl ToSocketAddrs,
+        server_name: &str,
+        config: Arc<ClientConfig>,
+    ) -> io::Result<(EngineHandle, EngineEvents)> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let addrs: Vec<SocketAddr> = net::lookup_host(addr).await?.collect();
+        let connector = TlsConnector::from(config);
+        self.connect_with(move || {
+            let (addrs, server_name, connector) =
+                (addrs.clone(), server_name.clone(), connector.clone());
+            async move {
+                let stream = connect_tcp(addrs).await?;
+                connector.connect(server_name, stream).await
+            }
+        })
+        .await
+    }
+
+    /// `connect` で開いた接続でエンジンを開始する。`connect` は再接続
+    /// ([`Self::reconnect`]) のたびにも呼ばれる。
+    ///
+    /// # Errors
+    ///
+    /// シーケンス番号の読み込み、最初の接続の失敗。
+    pub async fn connect_with<S, F, Fut>(
+        mut self,
+        mut connect: F,
+    ) -> io::Result<(EngineHandle, EngineEvents)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = io::Result<S>> + Send + 'static,
+    {
+        self.restore()?;
+        let stream = connect().await?;
+        let reconnect = self.reconnect.map(|policy| {
+            let id = self.session.session_id();
+            let key = format!("{}:{}", id.sender_comp_id, id.target_comp_id);
+            Reconnect {
+                policy,
+                seed: fnv1a(key.as_bytes()),
+                connect: Box::new(move || {
+                    Box::pin(connect()) as Pin<Box<dyn Future<Output = io::Result<S>> + Send>>
+                }),
+            }
+        });
+        Ok(self.spawn(stream, reconnect))
+    }
+
+    /// 接続済みの `stream` 上で Logon を送ってエンジンを開始する。tokio
+    /// ランタイム上で呼ぶこと。切断後は再接続しない。
+    ///
+    /// # Errors
+    ///
+    /// シーケンス番号の読み込みの失敗。
+    pub fn run_on<S>(mut self, stream: S) -> io::Result<(EngineHandle, EngineEvents)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        self.restore()?;
+        Ok(self.spawn(stream, None))
+    }
+
+    /// 保存済みのシーケンス番号があれば再開する。
+    fn restore(&mut self) -> io::Result<()> {
+        if let Some(store) = &mut self.seq_nums {
+            self.session.restore(store.as_mut())?;
+        }
+        Ok(())
+    }
+
+    fn spawn<S>(self, stream: S, reconnect: Option<Reconnect<S>>) -> (EngineHandle, EngineEvents)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (engine, handle, events) = Engine::new(
+            self.session,
+            self.store,
+            self.seq_nums,
+            self.tick_interval,
+            self.channel_capacity,
+            self.max_frame_len,
+        );
+        tokio::spawn(engine.run_initiator(stream, reconnect));
+        (handle, events)
+    }
+}
+
+async fn connect_tcp(addrs: Vec<SocketAddr>) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect(&addrs[..]).await?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// 受け付けた接続でセッションを開始できなかった理由。
+#[derive(Debug)]
+pub enum ConnectionError {
+    /// 読み込みの失敗、または Logon の前に相手方が切断した。
+    Io(io::Error),
+    /// 最初のフレームを解析できない。
+    Parse(ParseError),
+    /// 最初のフレームの宣言長が [`Listener::max_frame_len`] を超える。
+    FrameTooLarge {
+        /// `BodyLength` から求めたフレーム長。
+        len: usize,
+        /// 設定された上限。
+        max: usize,
+    },
+    /// Logon が時間内に揃わない ([`Listener::logon_timeout`])。
+    LogonTimeout,
+    /// [`Acceptor::on_logon`] が拒否した。
+    Rejected(AcceptError),
+    /// 同じ相手方 (`SenderCompID`) のセッションが接続中。
+    AlreadyConnected(String),
+    /// TLS ハンドシェイクの失敗 ([`Listener::tls`])。
+    #[cfg(feature = "tls")]
+    Handshake(io::Error),
+}
+
+impl core::fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "cannot parse Logon: {err}"),
+            Self::FrameTooLarge { len, max } => {
+                write!(f, "frame of {len} bytes exceeds the {max} byte limit")
+            }
+            Self::LogonTimeout => write!(f, "no Logon before the timeout"),
+            Self::Rejected(err) => write!(f, "{err}"),
+            Self::AlreadyConnected(comp_id) => write!(f, "{comp_id} is already connected"),
+            #[cfg(feature = "tls")]
+            Self::Handshake(err) => write!(f, "TLS handshake failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {}
+
+/// [`Incoming::recv`] で受け取る、接続ごとの結果。
+#[derive(Debug)]
+pub enum ListenerEvent {
+    /// Logon を受け付け、セッションのエンジンを開始した。
+    Accepted(AcceptedEngine),
+    /// セッションを開始せずに接続を閉じた。
+    Refused {
+        /// 接続元アドレス。
+        peer: SocketAddr,
+        /// 理由。
+        error: ConnectionError,
+    },
+}
+
+/// 受け付けたセッションのエンジン。
+#[derive(Debug)]
+pub struct AcceptedEngine {
+    /// 接続元アドレス。
+    pub peer: SocketAddr,
+    /// セッションの識別子 (`TargetCompID` が相手方)。
+    pub session_id: SessionId,
+    /// 送信ハンドル。
+    pub handle: EngineHandle,
+    /// 通知の受信側。Logon 応答の送信後に [`EngineEvent::LoggedOn`] が届く。
+    pub events: EngineEvents,
+}
+
+/// Acceptor 側エンジンの組み立て。
+///
+/// 待ち受けポートへの接続ごとに最初のフレームを Logon として読み、
+/// [`Acceptor::on_logon`] で相手方の設定を選んでセッションを組み立てる。
+/// 受け付けたセッションはそれぞれ独立したタスクで並行に駆動する。
+/// 相手方ごとに同時に接続できるのは 1 セッションまで。
+#[derive(Debug)]
+pub struct Listener {
+    acceptor: Acceptor,
+    tick_interval: Duration,
+    channel_capacity: usize,
+    logon_timeout: Duration,
+    max_frame_len: usize,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<ServerConfig>>,
+}
+
+impl Listener {
+    /// `acceptor` の設定で接続を受け付けるリスナを作成。
+    #[must_use]
+    pub const fn new(acceptor: Acceptor) -> Self {
+        Self {
+            acceptor,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            logon_timeout: DEFAULT_LOGON_TIMEOUT,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// 各セッションの [`FixSession::tick`] の駆動間隔 ([`DEFAULT_TICK_INTERVAL`])。
+    #[must_use]
+    pub const fn tick_interval(mut self, interval: Duration) -> Self {
+        self.tick_interval = interval;
+        self
+    }
+
+    /// 各セッションと [`Incoming`] のチャネル容量 ([`DEFAULT_CHANNEL_CAPACITY`])。
+    #[must_use]
+    pub const fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// 接続から Logon が揃うまでの待ち時間 ([`DEFAULT_LOGON_TIMEOUT`])。
+    /// TLS ではハンドシェイクを含む。
+    #[must_use]
+    pub const fn logon_timeout(mut self, timeout: Duration) -> Self {
+        self.logon_timeout = timeout;
+        self
+    }
+
+    /// 受信フレームの最大長 (バイト、[`DEFAULT_MAX_FRAME_LEN`])。Logon の
+    /// 前から適用し、宣言長が超えた時点で残りを待たずに接続を閉じる。
+    #[must_use]
+    pub const fn max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    /// 接続を `config` で TLS 化する ([`crate::tls::server_config`])。
+    /// ハンドシェイクに失敗した接続は [`ConnectionError::Handshake`] で閉じる。
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn tls(mut self, config: Arc<ServerConfig>) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// `addr` で待ち受けを開始する。
+    ///
+    /// # Errors
+    ///
+    /// バインドの失敗。
+    pub async fn bind(self, addr: impl ToSocketAddrs) -> io::Result<Incoming> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener)
+    }
+
+    /// バインド済みの `listener` で待ち受けを開始する。tokio ランタイム上で
+    /// 呼ぶこと。
+    ///
+    /// # Errors
+    ///
+    /// `listener` のアドレスを取得できない場合。
+    pub fn serve(self, listener: TcpListener) -> io::Result<Incoming> {
+        let local_addr = listener.local_addr()?;
+        let (tx, events) = mpsc::channel(self.channel_capacity);
+        let listener_config = Arc::new(self);
+        let connected = Arc::new(Mutex::new(HashSet::new()));
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        let _ = stream.set_nodelay(true);
+                        tokio::spawn(Arc::clone(&listener_config).on_connection(
+                            stream,
+                            peer,
+                            tx.clone(),
+                            Arc::clone(&connected),
+                        ));
+                    }
+                    // ファイル記述子の枯渇などは一時的なものとして待ち受けを続ける。
+                    Err(_) => time::sleep(ACCEPT_RETRY_DELAY).await,
+                }
+            }
+        });
+        Ok(Incoming {
+            local_addr,
+            events,
+            task,
+        })
+    }
+
+    /// 1 接続分: (TLS なら) ハンドシェイクの後 [`Self::start`]。
+    async fn on_connection(
+        self: Arc<Self>,
+        stream: TcpStream,
+        peer: SocketAddr,
+        tx: mpsc::Sender<ListenerEvent>,
+        connected: Arc<Mutex<HashSet<String>>>,
+    ) {
+        let deadline = Instant::now() + self.logon_timeout;
+        #[cfg(feature = "tls")]
+        if let Some(config) = &self.tls {
+            let handshake = TlsAcceptor::from(Arc::clone(config)).accept(stream);
+            let error = match time::timeout_at(deadline, handshake).await {
+                Ok(Ok(stream)) => return self.start(stream, peer, deadline, &tx, &connected).await,
+                Ok(Err(err)) => ConnectionError::Handshake(err),
+                Err(_) => ConnectionError::LogonTimeout,
+            };
+            let _ = tx.send(ListenerEvent::Refused { peer, error }).await;
+            return;
+        }
+        self.start(stream, peer, deadline, &tx, &connected).await;
+    }
+
+    /// Logon を読んで検証し、受け付けたらエンジンを終了まで駆動する。
+    async fn start<S>(
+        &self,
+        mut stream: S,
+        peer: SocketAddr,
+        deadline: Instant,
+        tx: &mpsc::Sender<ListenerEvent>,
+        connected: &Mutex<HashSet<String>>,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut buf = Vec::new();
+        let logon = time::timeout_at(
+            deadline,
+            read_frame(&mut stream, &mut buf, self.max_frame_len),
+        )
+        .await
+        .unwrap_or(Err(ConnectionError::LogonTimeout));
+        let logon = match logon {
+            Ok(logon) => logon,
+            Err(error) => return refuse(stream, peer, error, tx).await,
+        };
+        // 接続中の CompID の Logon は、ストアや保存済みシーケンス番号に
+        // 触れる on_logon より前に断る。
+        let comp_id = logon
+            .get(tag::SENDER_COMP_ID)
+            .unwrap_or_default()
+            .to_owned();
+        if !lock(connected).insert(comp_id.clone()) {
+            let error = ConnectionError::AlreadyConnected(comp_id);
+            return refuse(stream, peer, error, tx).await;
+        }
+        let accepted = match self.acceptor.on_logon(&logon) {
+            Ok(accepted) => accepted,
+            Err(error) => {
+                lock(connected).remove(&comp_id);
+                return refuse(stream, peer, ConnectionError::Rejected(error), tx).await;
+            }
+        };
+        let session_id = accepted.session.session_id();
+
+        let store: Box<dyn MessageStore + Send> = match accepted.store {
+            Some(store) => Box::new(store),
+            None => Box::new(MemoryStore::new()),
+        };
+        let seq_nums = accepted
+            .seq_nums
+            .map(|store| Box::new(store) as Box<dyn SeqNumStore + Send>);
+        let (engine, handle, events) = Engine::new(
+            accepted.session,
+            store,
+            seq_nums,
+            self.tick_interval,
+            self.channel_capacity,
+            self.max_frame_len,
+        );
+        let event = ListenerEvent::Accepted(AcceptedEngine {
+            peer,
+            session_id,
+            handle,
+            events,
+        });
+        if tx.send(event).await.is_ok() {
+            engine.run(stream, accepted.actions, buf).await;
+        }
+        lock(connected).remove(&comp_id);
+    }
+}
+
+/// 待ち受け中の [`Listener`]。破棄すると新しい接続の受け付けを止める
+/// (開始済みのセッションは続く)。
+#[derive(Debug)]
+pub struct Incoming {
+    local_addr: SocketAddr,
+    events: mpsc::Receiver<ListenerEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Incoming {
+    /// 待ち受けアドレス。
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 次の接続の結果を待つ。
+    pub async fn recv(&mut self) -> Option<ListenerEvent> {
+        self.events.recv().await
+    }
+}
+
+impl Drop for Incoming {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn refuse<S>(
+    mut stream: S,
+    peer: SocketAddr,
+    error: ConnectionError,
+    tx: &mpsc::Sender<ListenerEvent>,
+) where
+    S: AsyncWrite + Unpin,
+{
+    if let ConnectionError::Rejected(AcceptError::LogonRefused { response, .. }) = &error {
+        let _ = stream.write_all(response).await;
+    }
+    let _ = stream.shutdown().await;
+    let _ = tx.send(ListenerEvent::Refused { peer, error }).await;
+}
+
+fn lock(connected: &Mutex<HashSet<String>>) -> MutexGuard<'_, HashSet<String>> {
+    connected.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `stream` から 1 フレームを読んで解析する。続きのバイト列は `buf` に残る。
+async fn read_frame<S>(
+    stream: &mut S,
+    buf: &mut Vec<u8>,
+    max_frame_len: usize,
+) -> Result<FixMessage, ConnectionError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut chunk = vec![0u8; READ_CHUNK];
+    loop {
+        if let Some(len) = frame_len(buf, max_frame_len)? {
+            let msg = parser::parse(&buf[..len]).map_err(ConnectionError::Parse);
+            buf.drain(..len);
+            return msg;
+        }
+        let n = stream.read(&mut chunk).await.map_err(ConnectionError::Io)?;
+        if n == 0 {
+            return Err(ConnectionError::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// `buf` 先頭のフレームが揃っていればその長さ。宣言長が `max` を超える
+/// フレームは受け取る前に拒否する。
+fn frame_len(buf: &[u8], max: usize) -> Result<Option<usize>, ConnectionError> {
+    let declared = parser::declared_frame_len(buf).map_err(ConnectionError::Parse)?;
+    if let Some(len) = declared.filter(|&len| len > max) {
+        return Err(ConnectionError::FrameTooLarge { len, max });
+    }
+    Ok(declared.filter(|&len| buf.len() >= len))
+}
+
+/// エンジンタスクの状態。
+struct Engine {
+    session: FixSession,
+    store: Box<dyn MessageStore + Send>,
+    seq_nums: Option<Box<dyn SeqNumStore + Send>>,
+    tick_interval: Duration,
+    max_frame_len: usize,
+    commands: mpsc::Receiver<Command>,
+    events: mpsc::Sender<EngineEvent>,
+    /// 現在の接続で Logon が成立したか。
+    logged_on: bool,
+    /// アプリケーションが Logout を指示したか。
+    logout_requested: bool,
+}
+
+impl Engine {
+    fn new(
+        session: FixSession,
+        store: Box<dyn MessageStore + Send>,
+        seq_nums: Option<Box<dyn SeqNumStore + Send>>,
+        tick_interval: Duration,
+        channel_capacity: usize,
+        max_frame_len: usize,
+    ) -> (Self, EngineHandle, EngineEvents) {
+        let (commands_tx, commands) = mpsc::channel(channel_capacity);
+        let (events_tx, events) = mpsc::channel(channel_capacity);
+        let engine = Self {
+            session,
+            store,
+            seq_nums,
+            tick_interval,
+            max_frame_len,
+            commands,
+            events: events_tx,
+            logged_on: false,
+            logout_requested: false,
+        };
+        (
+            engine,
+            EngineHandle {
+                commands: commands_tx,
+            },
+            EngineEvents { events },
+        )
+    }
+
+    /// [`Self::drive`] の後に接続を閉じ、[`EngineEvent::Disconnected`] を通知する。
+    async fn run<S>(mut self, mut stream: S, opening: Vec<SessionAction>, buf: Vec<u8>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let error = self.drive(&mut stream, opening, buf).await.err();
+        let _ = stream.shutdown().await;
+        self.session.on_disconnect();
+        let _ = self.events.send(EngineEvent::Disconnected(error)).await;
+    }
+
+    /// Initiator 側: Logon を送って [`Self::drive`] し、切断後は
+    /// `reconnect` があれば方針に従って接続し直して繰り返す。
+    async fn run_initiator<S>(mut self, mut stream: S, mut reconnect: Option<Reconnect<S>>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut attempt = 0;
+        loop {
+            let logon = vec![SessionAction::Send(self.session.build_logon())];
+            let error = self.drive(&mut stream, logon, Vec::new()).await.err();
+            let _ = stream.shutdown().await;
+            self.session.on_disconnect();
+            let _ = self.events.send(EngineEvent::Disconnected(error)).await;
+            let Some(reconnect) = reconnect.as_mut() else {
+                return;
+            };
+            if self.logout_requested || self.events.is_closed() {
+                return;
+            }
+            if self.logged_on {
+                attempt = 0;
+            }
+            stream = loop {
+                attempt += 1;
+                let Some(delay) = reconnect.policy.delay(attempt, reconnect.seed) else {
+                    return;
+                };
+                let _ = self
+                    .events
+                    .send(EngineEvent::Reconnecting { attempt, delay })
+                    .await;
+                time::sleep(delay).await;
+                match (reconnect.connect)().await {
+                    Ok(stream) => break stream,
+                    Err(e) => {
+                        let _ = self.events.send(EngineEvent::ConnectFailed(e)).await;
+                    }
+                }
+            };
+        }
+    }
+
+    /// 最初に `opening` (Logon またはその応答) を実行し、接続が閉じるまで
+    /// `stream` を駆動する。`buf` は受信済みでまだ処理していないバイト列。
+    /// 相手方の切断・Logout 完了で `Ok`。
+    async fn drive<S>(
+        &mut self,
+        stream: &mut S,
+        opening: Vec<SessionAction>,
+        mut buf: Vec<u8>,
+    ) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        self.logged_on = false;
+        let opening = self.record(opening)?;
+        if self.apply(stream, opening).await? {
+            return Ok(());
+        }
+        let actions = self.on_bytes(&mut buf)?;
+        if self.apply(stream, actions).await? {
+            return Ok(());
+        }
+        let mut ticker = time::interval(self.tick_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut chunk = vec![0u8; READ_CHUNK];
+        let mut commands_open = true;
+        loop {
+            if !self.logged_on && *self.session.state() == SessionState::Active {
+                self.logged_on = true;
+                let _ = self.events.send(EngineEvent::LoggedOn).await;
+            }
+            let actions = tokio::select! {
+                read = stream.read(&mut chunk) => {
+                    let n = read?;
+                    if n == 0 {
+                        return Ok(());
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    self.on_bytes(&mut buf)?
+                }
+                command = self.commands.recv(), if commands_open => match command {
+                    Some(Command::Send(msg, reply)) => {
+                        let actions = match self.session.submit(msg) {
+                            Ok(frame) => {
+                                let _ = reply.send(Ok(()));
+                                frame.map(SessionAction::Send).into_iter().collect()
+                            }
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                                Vec::new()
+                            }
+                        };
+                        self.record(actions)?
+                    }
+                    Some(Command::Logout(text)) => {
+                        self.logout_requested = true;
+                        let logout = self.session.build_logout_with_text(&text);
+                        self.record(vec![SessionAction::Send(logout)])?
+                    }
+                    None => {
+                        commands_open = false;
+                        Vec::new()
+                    }
+                },
+                _ = ticker.tick() => {
+                    let actions = self.session.tick();
+                    self.record(actions)?
+                }
+            };
+            if self.apply(stream, actions).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 受信バッファから揃ったフレームを取り出して処理し、生じた
+    /// アクションを返す。受信ポリシーを通らないフレームは捨てる。
+    fn on_bytes(&mut self, buf: &mut Vec<u8>) -> io::Result<Vec<SessionAction>> {
+        let mut actions = Vec::new();
+        let mut start = 0;
+        while let Some(len) = frame_len(&buf[start..], self.max_frame_len).map_err(invalid_data)? {
+            let frame = &buf[start..start + len];
+            start += len;
+            if !self.session.screen_inbound(frame).is_accept() {
+                continue;
+            }
+            // 壊れたフレームは FIX の規定どおり無視し、後続の欠番検出に任せる。
+            let Ok(msg) = parser::parse(frame) else {
+                continue;
+            };
+            actions.extend(
+                self.session
+                    .on_message_with_store(&msg, self.store.as_mut())?,
+            );
+        }
+        buf.drain(..start);
+        Ok(actions)
+    }
+
+    /// `actions` の送信フレームをストアに記録し、再送要求をストアから解決する。
+    fn record(&mut self, actions: Vec<SessionAction>) -> io::Result<Vec<SessionAction>> {
+        for action in &actions {
+            if let SessionAction::Send(frame) = action {
+                self.session.store_sent(self.store.as_mut(), frame)?;
+            }
+        }
+        self.session.resolve_resends(actions, self.store.as_mut())
+    }
+
+    /// 解決済みの `actions` を実行する。接続を閉じるべきなら `true`。
+    async fn apply<S>(&mut self, stream: &mut S, actions: Vec<SessionAction>) -> io::Result<bool>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        let mut wrote = false;
+        let mut close = false;
+        for action in actions {
+            match action {
+                SessionAction::Send(frame) => {
+                    stream.write_all(&frame).await?;
+                    wrote = true;
+                }
+                SessionAction::Deliver(msg) => {
+                    let _ = self.events.send(EngineEvent::Message(msg)).await;
+                }
+                SessionAction::DeliverAdmin(msg) => {
+                    let _ = self.events.send(EngineEvent::Admin(msg)).await;
+                }
+                SessionAction::Disconnect | SessionAction::ForceDisconnect => close = true,
+                SessionAction::Resend { .. } => {}
+            }
+        }
+        if wrote {
+            stream.flush().await?;
+        }
+        if let Some(seq_nums) = &mut self.seq_nums {
+            self.session.save_seq_nums(seq_nums.as_mut())?;
+        }
+        Ok(close)
+    }
+}
+
+fn invalid_data(e: ConnectionError) -> io::Error {
+    let text = match e {
+        ConnectionError::Parse(e) => e.to_string(),
+        e => e.to_string(),
+    };
+    io::Error::new(io::ErrorKind::InvalidData, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acceptor::CounterpartyConfig;
+    use crate::session::SessionRole;
+    use crate::version::BeginString;
+    use std::collections::HashMap;
+    use tokio::io::DuplexStream;
+
+    /// 相手方 (Acceptor) 側の 1 フレームを読む。
+    async fn read_frame(stream: &mut DuplexStream, buf: &mut Vec<u8>) -> FixMessage {
+        loop {
+            if let Some(len) = parser::frame_len(buf).unwrap() {
+                let msg = parser::parse(&buf[..len]).unwrap();
+                buf.drain(..len);
+                return msg;
+            }
+            let mut chunk = [0u8; 1024];
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "engine closed the stream");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// 相手方セッションのアクションのうち送信フレームを書き出す。
+    async fn write_sends(stream: &mut DuplexStream, actions: Vec<SessionAction>) {
+        for action in actions {
+            if let SessionAction::Send(frame) = action {
+                stream.write_all(&frame).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn logon_exchange_send_receive_and_logout() {
+        let (client, mut server) = tokio::io::duplex(64 * 1024);
+        let mut broker = FixSession::new("BROKER", "ALICE", BeginString::Fix44);
+        broker.set_role(SessionRole::Acceptor);
+        let (handle, mut events) =
+            Initiator::new(FixSession::new("ALICE", "BROKER", BeginString::Fix44))
+                .run_on(client)
+                .unwrap();
+
+        let mut buf = Vec::new();
+        let logon = read_frame(&mut server, &mut buf).await;
+        assert_eq!(logon.msg_type, "A");
+        let actions = broker.on_message(&logon);
+        write_sends(&mut server, actions).await;
+        assert!(matches!(events.recv().await, Some(EngineEvent::LoggedOn)));
+
+        let mut order = FixMessage::new("FIX.4.4", "D");
+        order.set(tag::CL_ORD_ID, "ORD-1");
+        handle.send(order).await.unwrap();
+        let received = read_frame(&mut server, &mut buf).await;
+        assert_eq!(received.msg_type, "D");
+        assert_eq!(received.get(tag::CL_ORD_ID), Some("ORD-1"));
+        let _ = broker.on_message(&received);
+
+        let mut reject = FixMessage::new("FIX.4.4", "3");
+        reject
+            .set(tag::REF_SEQ_NUM, "2")
+            .set(tag::TEXT, "unsupported");
+        let frame = broker.submit(reject).unwrap().unwrap();
+        server.write_all(&frame).await.unwrap();
+        match events.recv().await {
+            Some(EngineEvent::Admin(msg)) => {
+                assert_eq!(msg.msg_type, "3");
+                assert_eq!(msg.get_u64(tag::REF_SEQ_NUM), Some(2));
+            }
+            other => panic!("expected Admin, got {other:?}"),
+        }
+
+        let mut report = FixMessage::new("FIX.4.4", "8");
+        report.set(tag::CL_ORD_ID, "ORD-1");
+        let frame = broker.submit(report).unwrap().unwrap();
+        server.write_all(&frame).await.unwrap();
+        match events.recv().await {
+            Some(EngineEvent::Message(msg)) => {
+                assert_eq!(msg.msg_type, "8");
+                assert_eq!(msg.get(tag::CL_ORD_ID), Some("ORD-1"));
+            }
+            other => panic!("expected Message, got {other:?}"),
+        }
+
+        handle.logout("done").await.unwrap();
+        let logout = read_frame(&mut server, &mut buf).await;
+        assert_eq!(logout.msg_type, "5");
+        let actions = broker.on_message(&logout);
+        write_sends(&mut server, actions).await;
+        assert!(matches!(
+            events.recv().await,
+            Some(EngineEvent::Disconnected(None))
+        ));
+        assert!(events.recv().await.is_none());
+        assert_eq!(
+            handle.send(FixMessage::new("FIX.4.4", "D")).await,
+            Err(EngineError::Closed)
+        );
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_with_bounded_jitter() {
+        let policy = ReconnectPolicy::new(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .max_attempts(5);
+        let delays: Vec<_> = (1..=6).map(|n| policy.delay(n, 7)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1000]
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .into_iter()
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+
+        let jittered = policy.jitter(Duration::from_millis(50));
+        for n in 1..=5 {
+            let delay = jittered.delay(n, 7).unwrap();
+            let base = policy.delay(n, 7).unwrap();
+            assert!(delay >= base && delay <= base + Duration::from_millis(50));
+            assert_eq!(jittered.delay(n, 7), Some(delay));
+        }
+        assert_ne!(
+            (1..=5).map(|n| jittered.delay(n, 7)).collect::<Vec<_>>(),
+            (1..=5).map(|n| jittered.delay(n, 8)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ReconnectPolicy::new(Duration::from_secs(1)).delay(64, 0),
+            Some(DEFAULT_MAX_RECONNECT_DELAY)
+        );
+    }
+
+    #[tokio::test]
+    async fn reconnect_resumes_sequence_numbers_and_recovers_gap() {
+        let (server_tx, mut servers) = mpsc::unbounded_channel();
+        let connect = move || {
+            let (client, server) = tokio::io::duplex(64 * 1024);
+            let _ = server_tx.send(server);
+            async move { Ok(client) }
+        };
+        let mut broker = FixSession::new("BROKER", "ALICE", BeginString::Fix44);
+        broker.set_role(SessionRole::Acceptor);
+        let (handle, mut events) =
+            Initiator::new(FixSession::new("ALICE", "BROKER", BeginString::Fix44))
+                .reconnect(ReconnectPolicy::new(Duration::from_millis(10)))
+                .connect_with(connect)
+                .await
+                .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        let mut buf = Vec::new();
+        let logon = read_frame(&mut server, &mut buf).await;
+        write_sends(&mut server, broker.on_message(&logon)).await;
+        assert!(matches!(events.recv().await, Some(EngineEvent::LoggedOn)));
+        let mut order = FixMessage::new("FIX.4.4", "D");
+        order.set(tag::CL_ORD_ID, "ORD-1");
+        handle.send(order).await.unwrap();
+        let received = read_frame(&mut server, &mut buf).await;
+        let _ = broker.on_message(&received);
+
+        // 約定通知 (seq 2) を送る前に接続が切れる。
+        let mut report = FixMessage::new("FIX.4.4", "8");
+        report.set(tag::CL_ORD_ID, "ORD-1");
+        let _lost = broker.submit(report).unwrap().unwrap();
+        drop(server);
+        assert!(matches!(
+            events.recv().await,
+            Some(EngineEvent::Disconnected(_))
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(EngineEvent::Reconnecting { attempt: 1, .. })
+        ));
+
+        // 再接続の Logon はシーケンス番号を引き継ぎ、リセットしない。
+        let mut server = servers.recv().await.unwrap();
+        let mut buf = Vec::new();
+        let logon = read_frame(&mut server, &mut buf).await;
+        assert_eq!(logon.msg_type, "A");
+        assert_eq!(logon.get_u64(tag::MSG_SEQ_NUM), Some(3));
+        assert_eq!(logon.get(tag::RESET_SEQ_NUM_FLAG), None);
+        broker.on_disconnect();
+        write_sends(&mut server, broker.on_message(&logon)).await;
+        assert!(matches!(events.recv().await, Some(EngineEvent::LoggedOn)));
+
+        // 相手方の Logon (seq 3) で欠番 2 を検出して再送を求める。
+        let resend = read_frame(&mut server, &mut buf).await;
+        assert_eq!(resend.msg_type, "2");
+        assert_eq!(resend.get_u64(tag::BEGIN_SEQ_NO), Some(2));
+        assert_eq!(resend.get_u64(tag::END_SEQ_NO), Some(2));
+    }
+
+    async fn connect(addr: SocketAddr, comp_id: &str) -> (EngineHandle, EngineEvents) {
+        Initiator::new(FixSession::new(comp_id, "EXCH", BeginString::Fix44))
+            .connect(addr)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn listener_refuses_oversized_frame_before_logon() {
+        let acceptor = Acceptor::new("EXCH").counterparty("FIRM_A", CounterpartyConfig::new(30));
+        let mut incoming = Listener::new(acceptor)
+            .max_frame_len(4096)
+            .bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let mut stream = TcpStream::connect(incoming.local_addr()).await.unwrap();
+        stream
+            .write_all(b"8=FIX.4.4\x019=1000000\x01")
+            .await
+            .unwrap();
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::FrameTooLarge { max: 4096, .. },
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn listener_runs_one_session_per_counterparty() {
+        let acceptor = Acceptor::new("EXCH")
+            .counterparty("FIRM_A", CounterpartyConfig::new(30))
+            .counterparty("FIRM_B", CounterpartyConfig::new(30));
+        let mut incoming = Listener::new(acceptor).bind("127.0.0.1:0").await.unwrap();
+        let addr = incoming.local_addr();
+
+        let (firm_a, mut firm_a_events) = connect(addr, "FIRM_A").await;
+        let (_firm_b, mut firm_b_events) = connect(addr, "FIRM_B").await;
+        let mut accepted = HashMap::new();
+        for _ in 0..2 {
+            match incoming.recv().await {
+                Some(ListenerEvent::Accepted(engine)) => {
+                    accepted.insert(engine.session_id.target_comp_id.clone(), engine);
+                }
+                other => panic!("expected Accepted, got {other:?}"),
+            }
+        }
+        assert!(matches!(
+            firm_a_events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+        assert!(matches!(
+            firm_b_events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+        let mut exch_a = accepted.remove("FIRM_A").unwrap();
+        assert_eq!(exch_a.session_id.sender_comp_id, "EXCH");
+        assert!(matches!(
+            exch_a.events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+
+        let mut order = FixMessage::new("FIX.4.4", "D");
+        order.set(tag::CL_ORD_ID, "A-1");
+        firm_a.send(order).await.unwrap();
+        match exch_a.events.recv().await {
+            Some(EngineEvent::Message(msg)) => assert_eq!(msg.get(tag::CL_ORD_ID), Some("A-1")),
+            other => panic!("expected Message, got {other:?}"),
+        }
+        let mut report = FixMessage::new("FIX.4.4", "8");
+        report.set(tag::CL_ORD_ID, "A-1");
+        exch_a.handle.send(report).await.unwrap();
+        match firm_a_events.recv().await {
+            Some(EngineEvent::Message(msg)) => assert_eq!(msg.msg_type, "8"),
+            other => panic!("expected Message, got {other:?}"),
+        }
+
+        // 接続中の相手方の 2 本目と、未登録の相手方は拒否される。
+        let (_dup, mut dup_events) = connect(addr, "FIRM_A").await;
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::AlreadyConnected(comp_id),
+                ..
+            }) if comp_id == "FIRM_A"
+        ));
+        assert!(matches!(
+            dup_events.recv().await,
+            Some(EngineEvent::Disconnected(_))
+        ));
+        let _unknown = connect(addr, "FIRM_X").await;
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::Rejected(AcceptError::UnknownCompId(_)),
+                ..
+            })
+        ));
+    }
+
+    /// `dir` 内の全ファイルの中身。
+    fn read_dir(dir: &std::path::Path) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let bytes = std::fs::read(&path).unwrap();
+                (path, bytes)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn refused_duplicate_leaves_live_store_untouched() {
+        let dir = std::env::temp_dir().join(format!("alice-fix-engine-dup-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let acceptor = Acceptor::new("EXCH")
+            .counterparty("FIRM_A", CounterpartyConfig::new(30).store_dir(&dir));
+        let mut incoming = Listener::new(acceptor).bind("127.0.0.1:0").await.unwrap();
+        let addr = incoming.local_addr();
+
+        let (firm_a, mut firm_a_events) = connect(addr, "FIRM_A").await;
+        let Some(ListenerEvent::Accepted(mut exch_a)) = incoming.recv().await else {
+            panic!("expected Accepted");
+        };
+        assert!(matches!(
+            firm_a_events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+        assert!(matches!(
+            exch_a.events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+        firm_a.send(FixMessage::new("FIX.4.4", "D")).await.unwrap();
+        assert!(matches!(
+            exch_a.events.recv().await,
+            Some(EngineEvent::Message(_))
+        ));
+        exch_a
+            .handle
+            .send(FixMessage::new("FIX.4.4", "8"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            firm_a_events.recv().await,
+            Some(EngineEvent::Message(_))
+        ));
+        let before = read_dir(&dir);
+        assert!(!before.is_empty());
+
+        // seq 1 の 2 本目は、稼働中セッションのストアとシーケンス番号を
+        // 開く前に断られる。
+        let _dup = connect(addr, "FIRM_A").await;
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::AlreadyConnected(_),
+                ..
+            })
+        ));
+        assert_eq!(read_dir(&dir), before);
+        drop(firm_a);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn tls_with_client_certificates() {
+        use crate::tls::{self, Identity};
+        use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+        use tokio_rustls::rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let issue = |name: &str| {
+            let key = KeyPair::generate().unwrap();
+            let cert = CertificateParams::new(vec![name.to_string()])
+                .unwrap()
+                .signed_by(&key, &ca, &ca_key)
+                .unwrap();
+            Identity {
+                cert_chain: vec![cert.der().clone()],
+                key: PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der())),
+            }
+        };
+        let roots = || tls::root_store([ca.der().clone()]).unwrap();
+
+        let server = tls::server_config(issue("exch.test"), Some(roots())).unwrap();
+        let acceptor = Acceptor::new("EXCH").counterparty("FIRM_A", CounterpartyConfig::new(30));
+        let mut incoming = Listener::new(acceptor)
+            .tls(server)
+            .bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let addr = incoming.local_addr();
+        let initiator = || Initiator::new(FixSession::new("FIRM_A", "EXCH", BeginString::Fix44));
+
+        let client = tls::client_config(roots(), Some(issue("firm-a.test"))).unwrap();
+        let (_firm_a, mut firm_a_events) = initiator()
+            .connect_tls(addr, "exch.test", client)
+            .await
+            .unwrap();
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Accepted(_))
+        ));
+        assert!(matches!(
+            firm_a_events.recv().await,
+            Some(EngineEvent::LoggedOn)
+        ));
+
+        // サーバ名が証明書と合わない接続は Initiator 側で失敗する。
+        let client = tls::client_config(roots(), Some(issue("firm-a.test"))).unwrap();
+        assert!(initiator()
+            .connect_tls(addr, "other.test", client)
+            .await
+            .is_err());
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::Handshake(_),
+                ..
+            })
+        ));
+
+        // クライアント証明書のない接続は Acceptor 側で拒否される。
+        let client = tls::client_config(roots(), None).unwrap();
+        let _ = initiator().connect_tls(addr, "exch.test", client).await;
+        assert!(matches!(
+            incoming.recv().await,
+            Some(ListenerEvent::Refused {
+                error: ConnectionError::Handshake(_),
+                ..
+            })
+        ));
+    }
+}
@@ -1,9 +1,12 @@
 //! Execution Report パーシング
 //!
 //! FIX `ExecutionReport` (MsgType=8) の構造化パース。
+//! `TryFrom<&FixMessage>` / `From<&ExecutionReport> for FixBuilder` で相互変換する。
 
-use crate::message::FixMessage;
+use crate::builder::FixBuilder;
+use crate::message::{FieldError, FixMessage};
 use crate::tag;
+use crate::version::BeginString;
 
 /// 約定種別 (`ExecType`, tag 150)。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +23,10 @@ pub enum ExecType {
     Replaced,
     /// 拒否。
     Rejected,
+    /// 約定 (FIX 4.4 以降。4.2 の部分約定・全量約定に相当)。
+    Trade,
+    /// 注文状態の照会への応答 (`OrderStatusRequest` / `OrderMassStatusRequest`)。
+    OrderStatus,
     /// その他。
     Other(u8),
 }
@@ -35,9 +42,27 @@ impl ExecType {
             "4" => Self::Canceled,
             "5" => Self::Replaced,
             "8" => Self::Rejected,
+            "F" => Self::Trade,
+            "I" => Self::OrderStatus,
             _ => Self::Other(s.as_bytes().first().copied().unwrap_or(0)),
         }
     }
+
+    /// FIX 文字に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> char {
+        match self {
+            Self::New => '0',
+            Self::PartialFill => '1',
+            Self::Fill => '2',
+            Self::Canceled => '4',
+            Self::Replaced => '5',
+            Self::Rejected => '8',
+            Self::Trade => 'F',
+            Self::OrderStatus => 'I',
+            Self::Other(b) => b as char,
+        }
+    }
 }
 
 /// 注文ステータス (`OrdStatus`, tag 39)。
@@ -51,8 +76,16 @@ pub enum OrdStatus {
     Filled,
     /// キャンセル済み。
     Canceled,
+    /// 取消処理中。
+    PendingCancel,
     /// 拒否。
     Rejected,
+    /// 失効。
+    Expired,
+    /// 受付処理中。
+    PendingNew,
+    /// 訂正処理中。
+    PendingReplace,
     /// その他。
     Other(u8),
 }
@@ -66,10 +99,45 @@ impl OrdStatus {
             "1" => Self::PartiallyFilled,
             "2" => Self::Filled,
             "4" => Self::Canceled,
+            "6" => Self::PendingCancel,
             "8" => Self::Rejected,
+            "C" => Self::Expired,
+            "A" => Self::PendingNew,
+            "E" => Self::PendingReplace,
             _ => Self::Other(s.as_bytes().first().copied().unwrap_or(0)),
         }
     }
+
+    /// FIX 文字に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> char {
+        match self {
+            Self::New => '0',
+            Self::PartiallyFilled => '1',
+            Self::Filled => '2',
+            Self::Canceled => '4',
+            Self::PendingCancel => '6',
+            Self::Rejected => '8',
+            Self::Expired => 'C',
+            Self::PendingNew => 'A',
+            Self::PendingReplace => 'E',
+            Self::Other(b) => b as char,
+        }
+    }
+
+    /// 注文がまだ市場に残っている (約定・取消の余地がある) か。
+    /// [`Self::Other`] は判断できないため `false`。
+    #[must_use]
+    pub const fn is_open(self) -> bool {
+        matches!(
+            self,
+            Self::New
+                | Self::PartiallyFilled
+                | Self::PendingCancel
+                | Self::PendingNew
+                | Self::PendingReplace
+        )
+    }
 }
 
 /// 構造化 Execution Report。
@@ -101,6 +169,17 @@ pub struct ExecutionReport {
     pub avg_px: Option<f64>,
     /// テキスト (tag 58)。
     pub text: Option<String>,
+    /// 取引時刻 (tag 60, UNIX ナノ秒)。
+    pub transact_time: Option<u64>,
+    /// 照会 ID (tag 790)。`OrderStatusRequest` への応答で返される。
+    pub ord_status_req_id: Option<String>,
+    /// 一括照会 ID (tag 584)。`OrderMassStatusRequest` への応答で返される
+    /// ([`crate::mass_status`])。
+    pub mass_status_req_id: Option<String>,
+    /// 一括照会の応答件数 (tag 911)。
+    pub tot_num_reports: Option<u32>,
+    /// 一括照会の最後の応答か (tag 912)。
+    pub last_rpt_requested: Option<bool>,
 }
 
 impl ExecutionReport {
@@ -114,32 +193,13 @@ impl ExecutionReport {
             return Err(ExecReportError::WrongMsgType(msg.msg_type.clone()));
         }
 
-        let order_id = msg
-            .get(tag::ORDER_ID)
-            .ok_or(ExecReportError::MissingField(tag::ORDER_ID))?
-            .to_string();
-        let cl_ord_id = msg
-            .get(tag::CL_ORD_ID)
-            .ok_or(ExecReportError::MissingField(tag::CL_ORD_ID))?
-            .to_string();
-        let exec_id = msg
-            .get(tag::EXEC_ID)
-            .ok_or(ExecReportError::MissingField(tag::EXEC_ID))?
-            .to_string();
-        let exec_type_str = msg
-            .get(tag::EXEC_TYPE)
-            .ok_or(ExecReportError::MissingField(tag::EXEC_TYPE))?;
-        let ord_status_str = msg
-            .get(tag::ORD_STATUS)
-            .ok_or(ExecReportError::MissingField(tag::ORD_STATUS))?;
-        let symbol = msg
-            .get(tag::SYMBOL)
-            .ok_or(ExecReportError::MissingField(tag::SYMBOL))?
-            .to_string();
-        let side = msg
-            .get(tag::SIDE)
-            .ok_or(ExecReportError::MissingField(tag::SIDE))?
-            .to_string();
+        let order_id = msg.require(tag::ORDER_ID)?.to_string();
+        let cl_ord_id = msg.require(tag::CL_ORD_ID)?.to_string();
+        let exec_id = msg.require(tag::EXEC_ID)?.to_string();
+        let exec_type_str = msg.require(tag::EXEC_TYPE)?;
+        let ord_status_str = msg.require(tag::ORD_STATUS)?;
+        let symbol = msg.require(tag::SYMBOL)?.to_string();
+        let side = msg.require(tag::SIDE)?.to_string();
 
         let parse_f64 = |t: u32| -> Option<f64> { msg.get(t).and_then(|v| v.parse().ok()) };
 
@@ -157,8 +217,78 @@ impl ExecutionReport {
             cum_qty: parse_f64(tag::CUM_QTY),
             avg_px: parse_f64(tag::AVG_PX),
             text: msg.get(tag::TEXT).map(String::from),
+            transact_time: msg.get_timestamp(tag::TRANSACT_TIME),
+            ord_status_req_id: msg.get(tag::ORD_STATUS_REQ_ID).map(String::from),
+            mass_status_req_id: msg.get(tag::MASS_STATUS_REQ_ID).map(String::from),
+            tot_num_reports: msg.get(tag::TOT_NUM_REPORTS).and_then(|v| v.parse().ok()),
+            last_rpt_requested: msg.get_bool(tag::LAST_RPT_REQUESTED),
         })
     }
+
+    /// 注文状態の照会への応答 (`ExecType` I) か。約定を含まないため、
+    /// 約定の集計や照合には使わない。
+    #[must_use]
+    pub fn is_status_report(&self) -> bool {
+        self.exec_type == ExecType::OrderStatus
+    }
+
+    /// `begin_string` を指定してビルダーに変換 (ヘッダは呼び出し側で追加)。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, "8");
+        b.field(tag::ORDER_ID, &self.order_id)
+            .field(tag::CL_ORD_ID, &self.cl_ord_id)
+            .field(tag::EXEC_ID, &self.exec_id)
+            .field(tag::EXEC_TYPE, &self.exec_type.to_fix().to_string())
+            .field(tag::ORD_STATUS, &self.ord_status.to_fix().to_string())
+            .field(tag::SYMBOL, &self.symbol)
+            .field(tag::SIDE, &self.side);
+        let numeric = [
+            (tag::LAST_PX, self.last_px),
+            (tag::LAST_QTY, self.last_qty),
+            (tag::LEAVES_QTY, self.leaves_qty),
+            (tag::CUM_QTY, self.cum_qty),
+            (tag::AVG_PX, self.avg_px),
+        ];
+        for (t, v) in numeric {
+            if let Some(v) = v {
+                b.field(t, &v.to_string());
+            }
+        }
+        if let Some(text) = &self.text {
+            b.field(tag::TEXT, text);
+        }
+        if let Some(ns) = self.transact_time {
+            b.field_timestamp(tag::TRANSACT_TIME, ns);
+        }
+        if let Some(id) = &self.ord_status_req_id {
+            b.field(tag::ORD_STATUS_REQ_ID, id);
+        }
+        if let Some(id) = &self.mass_status_req_id {
+            b.field(tag::MASS_STATUS_REQ_ID, id);
+        }
+        if let Some(n) = self.tot_num_reports {
+            b.field_u64(tag::TOT_NUM_REPORTS, u64::from(n));
+        }
+        if let Some(last) = self.last_rpt_requested {
+            b.field(tag::LAST_RPT_REQUESTED, if last { "Y" } else { "N" });
+        }
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for ExecutionReport {
+    type Error = ExecReportError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        Self::from_message(msg)
+    }
+}
+
+impl From<&ExecutionReport> for FixBuilder {
+    fn from(report: &ExecutionReport) -> Self {
+        report.to_builder(crate::order::DEFAULT_BEGIN_STRING)
+    }
 }
 
 /// Execution Report エラー。
@@ -168,6 +298,13 @@ pub enum ExecReportError {
     WrongMsgType(String),
     /// 必須フィールドが欠落。
     MissingField(u32),
+    /// フィールド値が解釈できない。
+    InvalidField {
+        /// タグ番号。
+        tag: u32,
+        /// 値。
+        value: String,
+    },
 }
 
 impl core::fmt::Display for ExecReportError {
@@ -175,12 +312,22 @@ impl core::fmt::Display for ExecReportError {
         match self {
             Self::WrongMsgType(t) => write!(f, "Wrong MsgType: expected 8, got {t}"),
             Self::MissingField(tag) => write!(f, "Missing required field: tag {tag}"),
+            Self::InvalidField { tag, value } => write!(f, "Invalid value for tag {tag}: {value}"),
         }
     }
 }
 
 impl std::error::Error for ExecReportError {}
 
+impl From<FieldError> for ExecReportError {
+    fn from(e: FieldError) -> Self {
+        match e {
+            FieldError::MissingTag { tag, .. } => Self::MissingField(tag),
+            FieldError::InvalidValue { tag, value, .. } => Self::InvalidField { tag, value },
+        }
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -251,6 +398,7 @@ mod tests {
         assert_eq!(ExecType::from_fix("2"), ExecType::Fill);
         assert_eq!(ExecType::from_fix("4"), ExecType::Canceled);
         assert_eq!(ExecType::from_fix("8"), ExecType::Rejected);
+        assert_eq!(ExecType::from_fix("F"), ExecType::Trade);
     }
 
     #[test]
@@ -261,6 +409,46 @@ mod tests {
         assert_eq!(OrdStatus::from_fix("4"), OrdStatus::Canceled);
     }
 
+    #[test]
+    fn parse_status_report() {
+        let mut msg = make_exec_report();
+        msg.set(tag::EXEC_TYPE, "I")
+            .set(tag::ORD_STATUS, "1")
+            .set(tag::ORD_STATUS_REQ_ID, "42-7")
+            .set(tag::LEAVES_QTY, "0.5");
+        msg.remove(tag::LAST_PX);
+        msg.remove(tag::LAST_QTY);
+        let report = ExecutionReport::from_message(&msg).unwrap();
+        assert!(report.is_status_report());
+        assert!(report.ord_status.is_open());
+        assert_eq!(report.ord_status_req_id.as_deref(), Some("42-7"));
+        assert_eq!(report.last_qty, None);
+
+        let rebuilt = crate::parser::parse(&FixBuilder::from(&report).build()).unwrap();
+        assert_eq!(rebuilt.get(tag::EXEC_TYPE), Some("I"));
+        assert_eq!(rebuilt.get(tag::ORD_STATUS_REQ_ID), Some("42-7"));
+        assert!(!ExecutionReport::from_message(&make_exec_report())
+            .unwrap()
+            .is_status_report());
+    }
+
+    #[test]
+    fn pending_and_terminal_ord_status() {
+        for (fix, status) in [
+            ("6", OrdStatus::PendingCancel),
+            ("A", OrdStatus::PendingNew),
+            ("E", OrdStatus::PendingReplace),
+            ("C", OrdStatus::Expired),
+        ] {
+            assert_eq!(OrdStatus::from_fix(fix), status);
+            assert_eq!(status.to_fix().to_string(), fix);
+        }
+        assert!(OrdStatus::PendingNew.is_open());
+        assert!(!OrdStatus::Expired.is_open());
+        assert!(!OrdStatus::Filled.is_open());
+        assert!(!OrdStatus::Other(b'Z').is_open());
+    }
+
     #[test]
     fn exec_type_other() {
         let et = ExecType::from_fix("Z");
@@ -329,4 +517,27 @@ mod tests {
         assert_eq!(report.exec_type, ExecType::Canceled);
         assert_eq!(report.ord_status, OrdStatus::Canceled);
     }
+
+    #[test]
+    fn try_from_and_into_builder_roundtrip() {
+        let report = ExecutionReport::try_from(&make_exec_report()).unwrap();
+        let bytes = FixBuilder::from(&report).build();
+        let msg = crate::parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::EXEC_TYPE), Some("2"));
+        assert_eq!(msg.get(tag::LAST_PX), Some("50000.5"));
+        let back = ExecutionReport::try_from(&msg).unwrap();
+        assert_eq!(back.exec_id, report.exec_id);
+        assert_eq!(back.ord_status, OrdStatus::Filled);
+        assert_eq!(back.cum_qty, report.cum_qty);
+    }
+
+    #[test]
+    fn to_fix_inverts_from_fix() {
+        for s in ["0", "1", "2", "4", "5", "8", "C"] {
+            assert_eq!(ExecType::from_fix(s).to_fix().to_string(), s);
+        }
+        for s in ["0", "1", "2", "4", "8", "6"] {
+            assert_eq!(OrdStatus::from_fix(s).to_fix().to_string(), s);
+        }
+    }
 }
@@ -17,7 +17,8 @@ use crate::builder::FixBuilder;
 use crate::convert;
 use crate::message::FixMessage;
 use crate::parser;
-use crate::session::{FixSession, SessionState};
+use crate::session::{FixSession, SeqCheck, SessionState};
+use crate::version::BeginString;
 use alice_ledger::{OrderType, Side, TimeInForce};
 
 // -----------------------------------------------------------------------
@@ -166,6 +167,9 @@ pub unsafe extern "C" fn af_fix_message_msg_type(msg: *const FixMessage) -> *mut
 // -----------------------------------------------------------------------
 
 /// Create a new FIX message builder.
+///
+/// Returns null for an unsupported `begin_string`. `FIXT.1.1` is not
+/// accepted, as it needs an application version.
 #[no_mangle]
 pub unsafe extern "C" fn af_fix_builder_new(
     begin_string: *const c_char,
@@ -174,7 +178,13 @@ pub unsafe extern "C" fn af_fix_builder_new(
     if begin_string.is_null() || msg_type.is_null() {
         return std::ptr::null_mut();
     }
-    let bs = CStr::from_ptr(begin_string).to_str().unwrap_or("");
+    let Ok(bs) = CStr::from_ptr(begin_string)
+        .to_str()
+        .unwrap_or("")
+        .parse::<BeginString>()
+    else {
+        return std::ptr::null_mut();
+    };
     let mt = CStr::from_ptr(msg_type).to_str().unwrap_or("");
     Box::into_raw(Box::new(FixBuilder::new(bs, mt)))
 }
@@ -277,6 +287,9 @@ pub unsafe extern "C" fn af_fix_checksum(bytes: *const u8, len: i32) -> u8 {
 // -----------------------------------------------------------------------
 
 /// Create a new FIX session in Disconnected state.
+///
+/// Returns null for an unsupported `begin_string`, as
+/// [`af_fix_builder_new`] does.
 #[no_mangle]
 pub unsafe extern "C" fn af_fix_session_new(
     sender: *const c_char,
@@ -288,7 +301,13 @@ pub unsafe extern "C" fn af_fix_session_new(
     }
     let s = CStr::from_ptr(sender).to_str().unwrap_or("");
     let t = CStr::from_ptr(target).to_str().unwrap_or("");
-    let bs = CStr::from_ptr(begin_string).to_str().unwrap_or("");
+    let Ok(bs) = CStr::from_ptr(begin_string)
+        .to_str()
+        .unwrap_or("")
+        .parse::<BeginString>()
+    else {
+        return std::ptr::null_mut();
+    };
     Box::into_raw(Box::new(FixSession::new(s, t, bs)))
 }
 
@@ -332,11 +351,7 @@ pub unsafe extern "C" fn af_fix_session_validate_incoming_seq(
     if session.is_null() {
         return 0;
     }
-    if (*session).validate_incoming_seq(seq) {
-        1
-    } else {
-        0
-    }
+    u8::from((*session).validate_incoming_seq(seq) == SeqCheck::Expected)
 }
 
 /// Build a Logon message and transition to LogonSent state.
@@ -632,6 +647,9 @@ mod tests {
     fn test_builder_null_safety() {
         unsafe {
             assert!(af_fix_builder_new(std::ptr::null(), std::ptr::null()).is_null());
+            let bs = CString::new("FIX4.4").unwrap();
+            let mt = CString::new("0").unwrap();
+            assert!(af_fix_builder_new(bs.as_ptr(), mt.as_ptr()).is_null());
             af_fix_builder_free(std::ptr::null_mut());
         }
     }
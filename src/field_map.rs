@@ -0,0 +1,170 @@
+//! Compact Field Storage
+//!
+//! [`FieldMap`] はタグ昇順に整列した `Vec<(u32, String)>` でフィールドを
+//! 保持する。典型的なメッセージは 30 フィールド未満のため、二分探索の方が
+//! `HashMap` のハッシュ計算・バケット確保より速く、メモリも小さい。
+//! 反復は常にタグ昇順。
+
+/// タグ昇順のフィールド列。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieldMap {
+    entries: Vec<(u32, String)>,
+}
+
+impl FieldMap {
+    /// 空のマップを作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// 容量を指定して作成。
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn position(&self, tag: u32) -> Result<usize, usize> {
+        self.entries.binary_search_by_key(&tag, |&(t, _)| t)
+    }
+
+    /// 値を設定し、以前の値を返す。
+    pub fn insert(&mut self, tag: u32, value: String) -> Option<String> {
+        match self.position(tag) {
+            Ok(i) => Some(core::mem::replace(&mut self.entries[i].1, value)),
+            Err(i) => {
+                self.entries.insert(i, (tag, value));
+                None
+            }
+        }
+    }
+
+    /// 値を取得。
+    #[inline]
+    #[must_use]
+    pub fn get(&self, tag: u32) -> Option<&String> {
+        self.position(tag).ok().map(|i| &self.entries[i].1)
+    }
+
+    /// 値を可変参照で取得。
+    pub fn get_mut(&mut self, tag: u32) -> Option<&mut String> {
+        self.position(tag).ok().map(|i| &mut self.entries[i].1)
+    }
+
+    /// 値を削除して返す。
+    pub fn remove(&mut self, tag: u32) -> Option<String> {
+        self.position(tag).ok().map(|i| self.entries.remove(i).1)
+    }
+
+    /// `tag` を含むか。
+    #[inline]
+    #[must_use]
+    pub fn contains_key(&self, tag: u32) -> bool {
+        self.position(tag).is_ok()
+    }
+
+    /// フィールド数。
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 空か。
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 全フィールドを削除。
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// `(tag, 値)` をタグ昇順で列挙。
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.entries.iter().map(|(t, v)| (*t, v.as_str()))
+    }
+
+    /// `(tag, 値の可変参照)` をタグ昇順で列挙。
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut String)> {
+        self.entries.iter_mut().map(|(t, v)| (*t, v))
+    }
+}
+
+impl FromIterator<(u32, String)> for FieldMap {
+    fn from_iter<I: IntoIterator<Item = (u32, String)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (t, v) in iter {
+            map.insert(t, v);
+        }
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FieldMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.entries.iter().map(|(t, v)| (t, v)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FieldMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = std::collections::BTreeMap::<u32, String>::deserialize(deserializer)?;
+        Ok(Self {
+            entries: map.into_iter().collect(),
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_insert_and_overwrite() {
+        let mut m = FieldMap::new();
+        assert_eq!(m.insert(55, "BTCUSD".into()), None);
+        assert_eq!(m.insert(11, "A".into()), None);
+        assert_eq!(m.insert(38, "1".into()), None);
+        assert_eq!(m.insert(11, "B".into()), Some("A".into()));
+        assert_eq!(m.iter().map(|(t, _)| t).collect::<Vec<_>>(), [11, 38, 55]);
+        assert_eq!(m.get(11).map(String::as_str), Some("B"));
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    fn remove_and_lookup() {
+        let mut m: FieldMap = [(1, "x".to_string()), (2, "y".to_string())]
+            .into_iter()
+            .collect();
+        assert!(m.contains_key(2));
+        assert_eq!(m.remove(2), Some("y".into()));
+        assert_eq!(m.remove(2), None);
+        assert!(!m.contains_key(2));
+        m.get_mut(1).unwrap().push('!');
+        assert_eq!(m.get(1).map(String::as_str), Some("x!"));
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn equality_ignores_insertion_order() {
+        let a: FieldMap = [(1, "x".to_string()), (2, "y".to_string())]
+            .into_iter()
+            .collect();
+        let b: FieldMap = [(2, "y".to_string()), (1, "x".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,317 @@
+//! 受信メッセージファイアウォール
+//!
+//! フルパース前に生フレームを走査し、セッション単位のポリシー
+//! (許可 `MsgType`、最大ボディ長、繰り返しグループ最大エントリ数) を適用する。
+//! 違反時は [`PolicyAction`] に応じて Reject / Drop を判定する。
+
+use crate::parser::{parse_body_length, split_field, FieldIter};
+use crate::repeating_group::find_group_def;
+use crate::tag;
+
+/// 違反時の処理方針。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PolicyAction {
+    /// 拒否応答 (Reject 等) を返す。
+    #[default]
+    Reject,
+    /// 応答せず破棄。
+    Drop,
+}
+
+/// ポリシー違反の内容。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// 許可されていない `MsgType`。
+    MsgTypeNotAllowed(String),
+    /// `BodyLength` (tag 9) が上限超過。
+    BodyTooLarge {
+        /// 宣言されたボディ長。
+        len: usize,
+        /// 上限。
+        max: usize,
+    },
+    /// 繰り返しグループのエントリ数が上限超過。
+    TooManyGroupEntries {
+        /// `NoXxx` カウントタグ。
+        count_tag: u32,
+        /// 宣言されたエントリ数。
+        count: usize,
+        /// 上限。
+        max: usize,
+    },
+}
+
+impl core::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MsgTypeNotAllowed(t) => write!(f, "MsgType {t} not allowed"),
+            Self::BodyTooLarge { len, max } => {
+                write!(f, "body length {len} exceeds limit {max}")
+            }
+            Self::TooManyGroupEntries {
+                count_tag,
+                count,
+                max,
+            } => write!(
+                f,
+                "repeating group {count_tag}: {count} entries exceeds limit {max}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// ポリシー判定結果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// 通過 (パーサへ渡してよい)。
+    Accept,
+    /// 拒否応答を返すべき違反。
+    Reject(PolicyViolation),
+    /// 黙って破棄すべき違反。
+    Drop(PolicyViolation),
+}
+
+impl Verdict {
+    /// 通過判定か。
+    #[must_use]
+    pub const fn is_accept(&self) -> bool {
+        matches!(self, Self::Accept)
+    }
+
+    /// 違反内容 (通過時は `None`)。
+    #[must_use]
+    pub const fn violation(&self) -> Option<&PolicyViolation> {
+        match self {
+            Self::Accept => None,
+            Self::Reject(v) | Self::Drop(v) => Some(v),
+        }
+    }
+}
+
+/// セッション単位の受信ポリシー。
+///
+/// デフォルトは全許可。ヘッダ (8/9/35) が読めないフレームは判定せず
+/// [`Verdict::Accept`] を返し、エラー報告はパーサに委ねる。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InboundPolicy {
+    allowed_msg_types: Option<Vec<String>>,
+    max_body_len: Option<usize>,
+    max_group_entries: Option<usize>,
+    action: PolicyAction,
+}
+
+impl InboundPolicy {
+    /// 全許可のポリシーを作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 許可する `MsgType` を設定 (未設定時は全許可)。
+    #[must_use]
+    pub fn allow_msg_types(mut self, msg_types: &[&str]) -> Self {
+        self.allowed_msg_types = Some(msg_types.iter().map(|t| (*t).to_string()).collect());
+        self
+    }
+
+    /// 最大ボディ長 (tag 9 の値) を設定。
+    #[must_use]
+    pub const fn max_body_len(mut self, max: usize) -> Self {
+        self.max_body_len = Some(max);
+        self
+    }
+
+    /// 既知の繰り返しグループ 1 つあたりの最大エントリ数を設定。
+    #[must_use]
+    pub const fn max_group_entries(mut self, max: usize) -> Self {
+        self.max_group_entries = Some(max);
+        self
+    }
+
+    /// 違反時の処理方針を設定。
+    #[must_use]
+    pub const fn on_violation(mut self, action: PolicyAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// `MsgType` が許可されているか。
+    #[must_use]
+    pub fn allows(&self, msg_type: &str) -> bool {
+        self.allowed_msg_types
+            .as_ref()
+            .is_none_or(|types| types.iter().any(|t| t == msg_type))
+    }
+
+    /// 生フレームを検査。
+    #[must_use]
+    pub fn check(&self, raw: &[u8]) -> Verdict {
+        self.first_violation(raw)
+            .map_or(Verdict::Accept, |v| match self.action {
+                PolicyAction::Reject => Verdict::Reject(v),
+                PolicyAction::Drop => Verdict::Drop(v),
+            })
+    }
+
+    fn first_violation(&self, raw: &[u8]) -> Option<PolicyViolation> {
+        let mut iter = FieldIter::new(raw);
+        let mut next = || iter.next().and_then(|f| split_field(f).ok());
+
+        let (t, _) = next()?;
+        if t != tag::BEGIN_STRING {
+            return None;
+        }
+        let (t, v) = next()?;
+        if t != tag::BODY_LENGTH {
+            return None;
+        }
+        let body_len = parse_body_length(v)?;
+        if let Some(max) = self.max_body_len {
+            if body_len > max {
+                return Some(PolicyViolation::BodyTooLarge { len: body_len, max });
+            }
+        }
+        let (t, v) = next()?;
+        if t != tag::MSG_TYPE {
+            return None;
+        }
+        let msg_type = core::str::from_utf8(v).ok()?;
+        if !self.allows(msg_type) {
+            return Some(PolicyViolation::MsgTypeNotAllowed(msg_type.to_string()));
+        }
+
+        let max = self.max_group_entries?;
+        while let Some((t, v)) = next() {
+            if find_group_def(t).is_none() {
+                continue;
+            }
+            // 数値でないカウントはパーサが MalformedField として報告する
+            let Some(count) = parse_body_length(v) else {
+                continue;
+            };
+            if count > max {
+                return Some(PolicyViolation::TooManyGroupEntries {
+                    count_tag: t,
+                    count,
+                    max,
+                });
+            }
+        }
+        None
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::repeating_group::{GroupEntry, RepeatingGroup};
+    use crate::version::BeginString;
+
+    fn heartbeat() -> Vec<u8> {
+        FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::SENDER_COMP_ID, "A")
+            .field(tag::TARGET_COMP_ID, "B")
+            .build()
+    }
+
+    fn md_snapshot(entries: usize) -> Vec<u8> {
+        let mut group = RepeatingGroup::new(tag::NO_MD_ENTRIES, tag::MD_ENTRY_TYPE);
+        for _ in 0..entries {
+            let mut e = GroupEntry::new();
+            e.set(tag::MD_ENTRY_TYPE, "0").set(tag::MD_ENTRY_PX, "100");
+            group.add_entry(e);
+        }
+        FixBuilder::new(BeginString::Fix44, "W")
+            .field(tag::SYMBOL, "BTCUSD")
+            .group(&group)
+            .build()
+    }
+
+    #[test]
+    fn default_accepts_everything() {
+        let p = InboundPolicy::new();
+        assert_eq!(p.check(&heartbeat()), Verdict::Accept);
+        assert_eq!(p.check(&md_snapshot(50)), Verdict::Accept);
+    }
+
+    #[test]
+    fn msg_type_allow_list() {
+        let p = InboundPolicy::new().allow_msg_types(&["0", "A", "5"]);
+        assert!(p.check(&heartbeat()).is_accept());
+        assert_eq!(
+            p.check(&md_snapshot(1)),
+            Verdict::Reject(PolicyViolation::MsgTypeNotAllowed("W".into()))
+        );
+    }
+
+    #[test]
+    fn body_length_limit() {
+        let p = InboundPolicy::new().max_body_len(16);
+        let v = p.check(&md_snapshot(3));
+        assert!(matches!(
+            v.violation(),
+            Some(PolicyViolation::BodyTooLarge { max: 16, .. })
+        ));
+        assert!(InboundPolicy::new()
+            .max_body_len(4096)
+            .check(&md_snapshot(3))
+            .is_accept());
+    }
+
+    #[test]
+    fn group_entry_limit() {
+        let p = InboundPolicy::new().max_group_entries(2);
+        assert!(p.check(&md_snapshot(2)).is_accept());
+        assert_eq!(
+            p.check(&md_snapshot(3)),
+            Verdict::Reject(PolicyViolation::TooManyGroupEntries {
+                count_tag: tag::NO_MD_ENTRIES,
+                count: 3,
+                max: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn drop_action() {
+        let p = InboundPolicy::new()
+            .allow_msg_types(&["0"])
+            .on_violation(PolicyAction::Drop);
+        assert_eq!(
+            p.check(&md_snapshot(1)),
+            Verdict::Drop(PolicyViolation::MsgTypeNotAllowed("W".into()))
+        );
+    }
+
+    #[test]
+    fn body_checked_before_msg_type() {
+        // 巨大フレームは MsgType 判定前に弾く
+        let p = InboundPolicy::new().allow_msg_types(&["0"]).max_body_len(8);
+        assert!(matches!(
+            p.check(&md_snapshot(1)).violation(),
+            Some(PolicyViolation::BodyTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn unreadable_header_passes_to_parser() {
+        let p = InboundPolicy::new().allow_msg_types(&["0"]);
+        assert!(p.check(b"garbage").is_accept());
+        assert!(p.check(b"").is_accept());
+    }
+
+    #[test]
+    fn violation_display() {
+        assert_eq!(
+            PolicyViolation::BodyTooLarge { len: 100, max: 10 }.to_string(),
+            "body length 100 exceeds limit 10"
+        );
+    }
+}
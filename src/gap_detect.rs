@@ -4,6 +4,7 @@
 
 /// ギャップ情報。
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SequenceGap {
     /// ギャップ開始シーケンス番号。
     pub begin: u64,
@@ -0,0 +1,348 @@
+//! Reusable Header Template
+//!
+//! ゲートウェイが組み立てるメッセージのヘッダ (`BeginString`、
+//! `SenderCompID` / `TargetCompID`、任意の [`Routing`]) は全メッセージで
+//! 同一になる。[`HeaderTemplate`] に一度だけ設定し、
+//! [`HeaderTemplate::builder`] で新しい [`FixBuilder`] を作るか、
+//! [`HeaderTemplate::apply`] で既存のビルダーに書き込む。
+//!
+//! `MsgSeqNum` / `SendingTime` はメッセージごとに変わるため対象外。
+//!
+//! ## Third-Party Routing
+//!
+//! ハブ経由で複数の執行ブローカーと取引する場合、`SenderCompID` /
+//! `TargetCompID` はハブとの接続を表し、実際の発信元と宛先は
+//! `OnBehalfOfCompID` (115) / `OnBehalfOfSubID` (116) と
+//! `DeliverToCompID` (128) / `DeliverToSubID` (129) で示す。[`Routing`] は
+//! この 4 タグをまとめたもので、受信メッセージからは
+//! [`Routing::from_message`] で取り出し、[`Routing::reply`] で返信用
+//! (発信元と宛先を入れ替えたもの) に変換する。
+//! [`crate::session::FixSession::set_routing`] で設定すると、セッションが
+//! 送るアプリケーションメッセージに付与される。
+
+use crate::admin::is_admin_msg_type;
+use crate::builder::FixBuilder;
+use crate::message::FixMessage;
+use crate::tag;
+use crate::version::BeginString;
+
+/// サードパーティ経由のルーティング (tag 115 / 116 / 128 / 129)。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Routing {
+    /// `OnBehalfOfCompID` (115)。
+    pub on_behalf_of_comp_id: Option<String>,
+    /// `OnBehalfOfSubID` (116)。
+    pub on_behalf_of_sub_id: Option<String>,
+    /// `DeliverToCompID` (128)。
+    pub deliver_to_comp_id: Option<String>,
+    /// `DeliverToSubID` (129)。
+    pub deliver_to_sub_id: Option<String>,
+}
+
+impl Routing {
+    /// 何も設定していないルーティング。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            on_behalf_of_comp_id: None,
+            on_behalf_of_sub_id: None,
+            deliver_to_comp_id: None,
+            deliver_to_sub_id: None,
+        }
+    }
+
+    /// `OnBehalfOfCompID` (115) を設定。
+    #[must_use]
+    pub fn on_behalf_of(mut self, comp_id: &str) -> Self {
+        self.on_behalf_of_comp_id = Some(comp_id.to_string());
+        self
+    }
+
+    /// `OnBehalfOfSubID` (116) を設定。
+    #[must_use]
+    pub fn on_behalf_of_sub(mut self, sub_id: &str) -> Self {
+        self.on_behalf_of_sub_id = Some(sub_id.to_string());
+        self
+    }
+
+    /// `DeliverToCompID` (128) を設定。
+    #[must_use]
+    pub fn deliver_to(mut self, comp_id: &str) -> Self {
+        self.deliver_to_comp_id = Some(comp_id.to_string());
+        self
+    }
+
+    /// `DeliverToSubID` (129) を設定。
+    #[must_use]
+    pub fn deliver_to_sub(mut self, sub_id: &str) -> Self {
+        self.deliver_to_sub_id = Some(sub_id.to_string());
+        self
+    }
+
+    /// 受信メッセージのルーティングフィールド。
+    #[must_use]
+    pub fn from_message(msg: &FixMessage) -> Self {
+        let get = |t| msg.get(t).map(str::to_string);
+        Self {
+            on_behalf_of_comp_id: get(tag::ON_BEHALF_OF_COMP_ID),
+            on_behalf_of_sub_id: get(tag::ON_BEHALF_OF_SUB_ID),
+            deliver_to_comp_id: get(tag::DELIVER_TO_COMP_ID),
+            deliver_to_sub_id: get(tag::DELIVER_TO_SUB_ID),
+        }
+    }
+
+    /// 返信用のルーティング: 受信側の `OnBehalfOf*` を `DeliverTo*` に、
+    /// `DeliverTo*` を `OnBehalfOf*` にする。
+    #[must_use]
+    pub fn reply(&self) -> Self {
+        Self {
+            on_behalf_of_comp_id: self.deliver_to_comp_id.clone(),
+            on_behalf_of_sub_id: self.deliver_to_sub_id.clone(),
+            deliver_to_comp_id: self.on_behalf_of_comp_id.clone(),
+            deliver_to_sub_id: self.on_behalf_of_sub_id.clone(),
+        }
+    }
+
+    /// どのフィールドも設定されていないか。
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.on_behalf_of_comp_id.is_none()
+            && self.on_behalf_of_sub_id.is_none()
+            && self.deliver_to_comp_id.is_none()
+            && self.deliver_to_sub_id.is_none()
+    }
+
+    /// 設定済みのフィールドを (タグ, 値) で列挙。
+    pub fn fields(&self) -> impl Iterator<Item = (u32, &str)> {
+        [
+            (tag::ON_BEHALF_OF_COMP_ID, &self.on_behalf_of_comp_id),
+            (tag::ON_BEHALF_OF_SUB_ID, &self.on_behalf_of_sub_id),
+            (tag::DELIVER_TO_COMP_ID, &self.deliver_to_comp_id),
+            (tag::DELIVER_TO_SUB_ID, &self.deliver_to_sub_id),
+        ]
+        .into_iter()
+        .filter_map(|(t, v)| v.as_deref().map(|v| (t, v)))
+    }
+
+    /// 設定済みのフィールドを `builder` に追記する。
+    pub fn apply<'a>(&self, builder: &'a mut FixBuilder) -> &'a mut FixBuilder {
+        for (t, v) in self.fields() {
+            builder.field(t, v);
+        }
+        builder
+    }
+
+    /// `msg` がまだ持たないルーティングフィールドだけを書き込む
+    /// (メッセージ側の指定を優先する)。
+    pub fn stamp(&self, msg: &mut FixMessage) {
+        for (t, v) in self.fields() {
+            if !msg.contains(t) {
+                msg.set(t, v);
+            }
+        }
+    }
+}
+
+/// 複数の [`FixBuilder`] に適用するヘッダ設定。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderTemplate {
+    begin_string: BeginString,
+    sender_comp_id: String,
+    target_comp_id: String,
+    routing: Routing,
+}
+
+impl HeaderTemplate {
+    /// `BeginString` と送受信 `CompID` を指定して作成。
+    #[must_use]
+    pub fn new(
+        begin_string: impl Into<BeginString>,
+        sender_comp_id: &str,
+        target_comp_id: &str,
+    ) -> Self {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender_comp_id.to_string(),
+            target_comp_id: target_comp_id.to_string(),
+            routing: Routing::new(),
+        }
+    }
+
+    /// `OnBehalfOfCompID` (115) を設定。
+    #[must_use]
+    pub fn on_behalf_of(mut self, comp_id: &str) -> Self {
+        self.routing = self.routing.on_behalf_of(comp_id);
+        self
+    }
+
+    /// `DeliverToCompID` (128) を設定。
+    #[must_use]
+    pub fn deliver_to(mut self, comp_id: &str) -> Self {
+        self.routing = self.routing.deliver_to(comp_id);
+        self
+    }
+
+    /// [`Routing`] をまとめて設定 (115 / 116 / 128 / 129)。
+    #[must_use]
+    pub fn with_routing(mut self, routing: Routing) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// `BeginString`。
+    #[must_use]
+    pub const fn begin_string(&self) -> BeginString {
+        self.begin_string
+    }
+
+    /// `SenderCompID`。
+    #[must_use]
+    pub fn sender_comp_id(&self) -> &str {
+        &self.sender_comp_id
+    }
+
+    /// `TargetCompID`。
+    #[must_use]
+    pub fn target_comp_id(&self) -> &str {
+        &self.target_comp_id
+    }
+
+    /// `OnBehalfOfCompID`。
+    #[must_use]
+    pub fn on_behalf_of_comp_id(&self) -> Option<&str> {
+        self.routing.on_behalf_of_comp_id.as_deref()
+    }
+
+    /// `DeliverToCompID`。
+    #[must_use]
+    pub fn deliver_to_comp_id(&self) -> Option<&str> {
+        self.routing.deliver_to_comp_id.as_deref()
+    }
+
+    /// サードパーティ経由のルーティング。
+    #[must_use]
+    pub const fn routing(&self) -> &Routing {
+        &self.routing
+    }
+
+    /// ヘッダを書き込んだ `msg_type` のビルダーを作成。
+    #[must_use]
+    pub fn builder(&self, msg_type: &str) -> FixBuilder {
+        let mut b = FixBuilder::new(self.begin_string, msg_type);
+        self.apply(&mut b);
+        b
+    }
+
+    /// `builder` の `BeginString` を置き換え、ヘッダフィールドを追記する。
+    ///
+    /// `FIXT.1.1` のアプリメッセージには `ApplVerID` (1128) も付ける。
+    /// フィールドは呼び出し時点の末尾に追加されるため、本文より先に呼ぶ。
+    pub fn apply<'a>(&self, builder: &'a mut FixBuilder) -> &'a mut FixBuilder {
+        builder.begin_string.clear();
+        builder.begin_string.push_str(self.begin_string.as_str());
+        if let Some(id) = self.begin_string.appl_ver_id() {
+            if !is_admin_msg_type(&builder.msg_type) {
+                builder.field(tag::APPL_VER_ID, id);
+            }
+        }
+        builder
+            .field(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .field(tag::TARGET_COMP_ID, &self.target_comp_id);
+        self.routing.apply(builder)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn builder_carries_header_fields() {
+        let header = HeaderTemplate::new(BeginString::Fix42, "ALICE", "BROKER")
+            .on_behalf_of("DESK1")
+            .deliver_to("VENUE");
+        let bytes = header
+            .builder("D")
+            .field_u64(tag::MSG_SEQ_NUM, 7)
+            .field(tag::CL_ORD_ID, "A1")
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.begin_string, "FIX.4.2");
+        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
+        assert_eq!(msg.get(tag::TARGET_COMP_ID), Some("BROKER"));
+        assert_eq!(msg.get(tag::ON_BEHALF_OF_COMP_ID), Some("DESK1"));
+        assert_eq!(msg.get(tag::DELIVER_TO_COMP_ID), Some("VENUE"));
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(
+            text.contains("35=D\x0149=ALICE\x0156=BROKER\x01115=DESK1\x01128=VENUE\x0134=7\x01")
+        );
+    }
+
+    #[test]
+    fn apply_reuses_template_and_omits_unset_routing() {
+        let header = HeaderTemplate::new(BeginString::Fix44, "ALICE", "BROKER");
+        assert_eq!(header.on_behalf_of_comp_id(), None);
+        let mut a = FixBuilder::new(BeginString::Fix42, "0");
+        header.apply(&mut a);
+        let b = header.builder("0");
+        assert_eq!(a.build(), b.build());
+        let msg = parser::parse(&b.build()).unwrap();
+        assert_eq!(msg.begin_string, "FIX.4.4");
+        assert!(!msg.contains(tag::ON_BEHALF_OF_COMP_ID));
+        assert!(!msg.contains(tag::DELIVER_TO_COMP_ID));
+    }
+
+    #[test]
+    fn routing_sub_ids_and_reply() {
+        let routing = Routing::new()
+            .on_behalf_of("FUND")
+            .on_behalf_of_sub("PM1")
+            .deliver_to("BROKER_B")
+            .deliver_to_sub("DESK9");
+        let bytes = HeaderTemplate::new(BeginString::Fix44, "ALICE", "HUB")
+            .with_routing(routing.clone())
+            .builder("D")
+            .field_u64(tag::MSG_SEQ_NUM, 1)
+            .build();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains("115=FUND\x01128=BROKER_B\x0134=1\x01116=PM1\x01129=DESK9\x01"));
+
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(Routing::from_message(&msg), routing);
+        let reply = routing.reply();
+        assert_eq!(reply.on_behalf_of_comp_id.as_deref(), Some("BROKER_B"));
+        assert_eq!(reply.deliver_to_sub_id.as_deref(), Some("PM1"));
+        assert_eq!(reply.reply(), routing);
+
+        let mut ack = FixMessage::new("FIX.4.4", "8");
+        ack.set(tag::DELIVER_TO_COMP_ID, "OTHER");
+        reply.stamp(&mut ack);
+        assert_eq!(ack.get(tag::DELIVER_TO_COMP_ID), Some("OTHER"));
+        assert_eq!(ack.get(tag::ON_BEHALF_OF_COMP_ID), Some("BROKER_B"));
+        assert!(Routing::new().is_empty());
+        assert!(!reply.is_empty());
+    }
+
+    #[test]
+    fn fixt_template_stamps_appl_ver_id_on_application_messages() {
+        let header = HeaderTemplate::new(
+            BeginString::Fixt11 {
+                appl_ver: crate::version::FixVersion::Fix44,
+            },
+            "ALICE",
+            "BROKER",
+        );
+        let order = parser::parse(&header.builder("D").build()).unwrap();
+        assert_eq!(order.begin_string, "FIXT.1.1");
+        assert_eq!(order.get(tag::APPL_VER_ID), Some("6"));
+        let heartbeat = parser::parse(&header.builder("0").build()).unwrap();
+        assert!(!heartbeat.contains(tag::APPL_VER_ID));
+    }
+}
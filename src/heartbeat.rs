@@ -0,0 +1,287 @@
+//! Heartbeat Scheduling
+//!
+//! Heartbeat 送出時刻を決定的なオフセットとジッタで分散させる。
+//! 1 プロセスで多数のセッションを管理しても、全セッションが同じ
+//! ミリ秒に Heartbeat を送ることを避ける。
+//!
+//! 送出時刻は `anchor + k × interval + jitter(k)` の格子上に置かれる
+//! (`anchor` = 開始時刻 + オフセット)。ジッタは `seed` と `k` から
+//! 決定的に計算されるため、同じ設定なら再現可能。ただし送出から 1 間隔を
+//! 超えて空けることはなく、格子点が遠ければ送出 + 間隔に送る。時刻はナノ秒。
+
+/// 1 秒あたりのナノ秒数。
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Heartbeat 間隔・オフセット・ジッタの設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// 間隔 (ns)。
+    interval: u64,
+    /// 位相オフセット (ns)。
+    offset: u64,
+    /// 最大ジッタ (ns)。
+    jitter: u64,
+}
+
+impl HeartbeatConfig {
+    /// `HeartBtInt` (tag 108, 秒) から作成。オフセット・ジッタは 0。
+    ///
+    /// 0 秒は 1 秒として扱う。
+    #[must_use]
+    pub const fn new(heart_bt_int_secs: u32) -> Self {
+        let secs = if heart_bt_int_secs == 0 {
+            1
+        } else {
+            heart_bt_int_secs as u64
+        };
+        Self {
+            interval: secs * NANOS_PER_SEC,
+            offset: 0,
+            jitter: 0,
+        }
+    }
+
+    /// 位相オフセットを設定 (間隔で剰余を取る)。
+    #[must_use]
+    pub const fn with_offset_ns(mut self, offset_ns: u64) -> Self {
+        self.offset = offset_ns % self.interval;
+        self
+    }
+
+    /// セッションキー (例: `SenderCompID` + `TargetCompID`) から決定的に
+    /// オフセットを導出。
+    #[must_use]
+    pub const fn with_offset_for(self, session_key: &str) -> Self {
+        self.with_offset_ns(splitmix64(fnv1a(session_key.as_bytes())))
+    }
+
+    /// 各周期に加える最大ジッタを設定 (間隔未満に丸める)。
+    #[must_use]
+    pub const fn with_jitter_ns(mut self, jitter_ns: u64) -> Self {
+        self.jitter = if jitter_ns >= self.interval {
+            self.interval - 1
+        } else {
+            jitter_ns
+        };
+        self
+    }
+
+    /// Heartbeat 間隔 (ns)。
+    #[must_use]
+    pub const fn interval_ns(&self) -> u64 {
+        self.interval
+    }
+
+    /// 位相オフセット (ns)。
+    #[must_use]
+    pub const fn offset_ns(&self) -> u64 {
+        self.offset
+    }
+
+    /// 最大ジッタ (ns)。
+    #[must_use]
+    pub const fn jitter_ns(&self) -> u64 {
+        self.jitter
+    }
+}
+
+/// セッション単位の Heartbeat タイマー。
+#[derive(Debug, Clone)]
+pub struct HeartbeatTimer {
+    config: HeartbeatConfig,
+    seed: u64,
+    anchor_ns: u64,
+    next_due_ns: u64,
+    /// `next_due_ns` が格子点ではなく送出 + 間隔に切り詰めたものか。
+    clamped: bool,
+}
+
+impl HeartbeatTimer {
+    /// `start_ns` (Logon 完了時刻など) を起点にタイマーを作成。
+    #[must_use]
+    pub const fn new(config: HeartbeatConfig, seed: u64, start_ns: u64) -> Self {
+        let mut timer = Self {
+            config,
+            seed,
+            anchor_ns: start_ns + config.offset,
+            next_due_ns: 0,
+            clamped: false,
+        };
+        timer.schedule_after(start_ns);
+        timer
+    }
+
+    /// 設定を取得。
+    #[must_use]
+    pub const fn config(&self) -> &HeartbeatConfig {
+        &self.config
+    }
+
+    /// 次の Heartbeat 送出予定時刻。
+    #[must_use]
+    pub const fn next_due_ns(&self) -> u64 {
+        self.next_due_ns
+    }
+
+    /// `now_ns` 時点で Heartbeat を送るべきか。
+    #[must_use]
+    pub const fn is_due(&self, now_ns: u64) -> bool {
+        now_ns >= self.next_due_ns
+    }
+
+    /// メッセージ (Heartbeat を含む) の送出を記録し、次の予定を
+    /// 約 1 間隔後 (ジッタ幅ぶん早まりうる) の格子点に再設定する。
+    /// 予定より前の送出 (業務メッセージ) や切り詰めた予定での送出では、
+    /// 格子点が送出から 1 間隔より後なら送出 + 間隔とする。格子点の予定
+    /// どおりの送出には切り詰めず、周期ごとに新しいジッタを引く。
+    pub const fn on_sent(&mut self, now_ns: u64) {
+        let clamp = now_ns < self.next_due_ns || self.clamped;
+        self.schedule_after(now_ns);
+        let latest = now_ns + self.config.interval;
+        self.clamped = clamp && self.next_due_ns > latest;
+        if self.clamped {
+            self.next_due_ns = latest;
+        }
+    }
+
+    const fn schedule_after(&mut self, sent_ns: u64) {
+        let interval = self.config.interval;
+        // ジッタで遅れて送った場合も次の格子点を飛ばさない
+        let earliest = sent_ns + interval - self.config.jitter;
+        let k = if earliest > self.anchor_ns {
+            (earliest - self.anchor_ns).div_ceil(interval)
+        } else {
+            0
+        };
+        self.next_due_ns = self.anchor_ns + k * interval + self.jitter(k);
+    }
+
+    const fn jitter(&self, cycle: u64) -> u64 {
+        if self.config.jitter == 0 {
+            return 0;
+        }
+        splitmix64(self.seed ^ cycle) % (self.config.jitter + 1)
+    }
+}
+
+/// 64-bit FNV-1a。
+pub(crate) const fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// `SplitMix64` の出力関数。
+pub(crate) const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = NANOS_PER_SEC;
+
+    #[test]
+    fn plain_interval() {
+        let mut t = HeartbeatTimer::new(HeartbeatConfig::new(30), 0, 0);
+        assert_eq!(t.next_due_ns(), 30 * SEC);
+        assert!(!t.is_due(29 * SEC));
+        assert!(t.is_due(30 * SEC));
+        t.on_sent(30 * SEC);
+        assert_eq!(t.next_due_ns(), 60 * SEC);
+    }
+
+    #[test]
+    fn outgoing_traffic_delays_at_most_one_interval() {
+        let mut t = HeartbeatTimer::new(HeartbeatConfig::new(30), 0, 0);
+        // 10 秒時点で業務メッセージを送ると、次の格子点 (60 秒) ではなく 40 秒
+        t.on_sent(10 * SEC);
+        assert_eq!(t.next_due_ns(), 40 * SEC);
+        t.on_sent(40 * SEC);
+        assert_eq!(t.next_due_ns(), 70 * SEC);
+        // 格子点上で送れば格子に戻る
+        t.on_sent(60 * SEC);
+        assert_eq!(t.next_due_ns(), 90 * SEC);
+    }
+
+    #[test]
+    fn offset_shifts_phase() {
+        let cfg = HeartbeatConfig::new(30).with_offset_ns(250_000_000);
+        let mut t = HeartbeatTimer::new(cfg, 0, 0);
+        assert_eq!(t.next_due_ns(), 30 * SEC + 250_000_000);
+        t.on_sent(t.next_due_ns());
+        assert_eq!(t.next_due_ns(), 60 * SEC + 250_000_000);
+    }
+
+    #[test]
+    fn offset_wraps_at_interval() {
+        let cfg = HeartbeatConfig::new(1).with_offset_ns(2 * SEC + 5);
+        assert_eq!(cfg.offset_ns(), 5);
+    }
+
+    #[test]
+    fn session_keys_spread_offsets() {
+        let offsets: Vec<u64> = (0..32)
+            .map(|i| {
+                HeartbeatConfig::new(30)
+                    .with_offset_for(&format!("ALICE->BROKER{i}"))
+                    .offset_ns()
+            })
+            .collect();
+        let ms: std::collections::HashSet<u64> = offsets.iter().map(|o| o / 1_000_000).collect();
+        assert_eq!(ms.len(), offsets.len());
+        // 決定的
+        assert_eq!(
+            HeartbeatConfig::new(30).with_offset_for("A->B"),
+            HeartbeatConfig::new(30).with_offset_for("A->B")
+        );
+    }
+
+    #[test]
+    fn jitter_is_bounded_and_deterministic() {
+        let cfg = HeartbeatConfig::new(30).with_jitter_ns(SEC);
+        let mut a = HeartbeatTimer::new(cfg, 42, 0);
+        let mut b = HeartbeatTimer::new(cfg, 42, 0);
+        let mut jitters = Vec::new();
+        for k in 1..=20 {
+            let due = a.next_due_ns();
+            assert!(due >= k * 30 * SEC && due <= k * 30 * SEC + SEC);
+            assert_eq!(due, b.next_due_ns());
+            jitters.push(due - k * 30 * SEC);
+            a.on_sent(due);
+            b.on_sent(due);
+        }
+        // アイドル中もジッタは周期ごとに変わり、最小値に張り付かない
+        assert!(jitters.windows(2).any(|w| w[1] > w[0]));
+        let intervals: std::collections::HashSet<u64> =
+            jitters.windows(2).map(|w| 30 * SEC + w[1] - w[0]).collect();
+        assert!(intervals.len() > 1);
+        let c = HeartbeatTimer::new(cfg, 43, 0);
+        let d = HeartbeatTimer::new(cfg, 42, 0);
+        assert_ne!(c.next_due_ns(), d.next_due_ns());
+    }
+
+    #[test]
+    fn jitter_clamped_below_interval() {
+        let cfg = HeartbeatConfig::new(1).with_jitter_ns(5 * SEC);
+        assert_eq!(cfg.jitter_ns(), SEC - 1);
+    }
+
+    #[test]
+    fn zero_interval_treated_as_one_second() {
+        assert_eq!(HeartbeatConfig::new(0).interval_ns(), SEC);
+    }
+}
@@ -0,0 +1,246 @@
+//! Instrument component / `NoRelatedSym` (146)
+//!
+//! `MarketDataRequest` (V)、`QuoteRequest` (R)、`SecurityList` (y) の
+//! 複数銘柄グループを型付きで読み書きする。
+
+use crate::builder::FixBuilder;
+use crate::message::FixMessage;
+use crate::repeating_group::{GroupEntry, RepeatingGroup};
+use crate::tag;
+
+/// `NoRelatedSym` を持つメッセージ種別。
+pub mod msg_type {
+    /// Market Data Request。
+    pub const MARKET_DATA_REQUEST: &str = "V";
+    /// Quote Request。
+    pub const QUOTE_REQUEST: &str = "R";
+    /// Security List。
+    pub const SECURITY_LIST: &str = "y";
+}
+
+/// Instrument component (1 銘柄)。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Instrument {
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// 証券 ID (tag 48)。
+    pub security_id: Option<String>,
+    /// 証券 ID 種別 (tag 22)。
+    pub security_id_source: Option<String>,
+    /// 取引所 (tag 207)。
+    pub security_exchange: Option<String>,
+    /// 証券種別 (tag 167)。
+    pub security_type: Option<String>,
+    /// 通貨 (tag 15)。
+    pub currency: Option<String>,
+}
+
+impl Instrument {
+    /// シンボルのみの Instrument を作成。
+    #[must_use]
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// グループエントリから変換。
+    ///
+    /// # Errors
+    ///
+    /// Symbol (tag 55) が欠落している場合。
+    pub fn from_entry(entry: &GroupEntry) -> Result<Self, InstrumentError> {
+        let symbol = entry
+            .get(tag::SYMBOL)
+            .ok_or(InstrumentError::MissingField(tag::SYMBOL))?
+            .to_string();
+        let opt = |t: u32| entry.get(t).map(String::from);
+        Ok(Self {
+            symbol,
+            security_id: opt(tag::SECURITY_ID),
+            security_id_source: opt(tag::SECURITY_ID_SOURCE),
+            security_exchange: opt(tag::SECURITY_EXCHANGE),
+            security_type: opt(tag::SECURITY_TYPE),
+            currency: opt(tag::CURRENCY),
+        })
+    }
+
+    /// グループエントリに変換。
+    #[must_use]
+    pub fn to_entry(&self) -> GroupEntry {
+        let mut entry = GroupEntry::new();
+        entry.set(tag::SYMBOL, &self.symbol);
+        let optional = [
+            (tag::SECURITY_ID, &self.security_id),
+            (tag::SECURITY_ID_SOURCE, &self.security_id_source),
+            (tag::SECURITY_EXCHANGE, &self.security_exchange),
+            (tag::SECURITY_TYPE, &self.security_type),
+            (tag::CURRENCY, &self.currency),
+        ];
+        for (t, v) in optional {
+            if let Some(v) = v {
+                entry.set(t, v);
+            }
+        }
+        entry
+    }
+}
+
+/// `NoRelatedSym` を持つメッセージ種別か。
+#[must_use]
+pub fn has_related_sym(msg_type: &str) -> bool {
+    matches!(
+        msg_type,
+        msg_type::MARKET_DATA_REQUEST | msg_type::QUOTE_REQUEST | msg_type::SECURITY_LIST
+    )
+}
+
+/// メッセージの `NoRelatedSym` グループを銘柄リストとして取得。
+///
+/// # Errors
+///
+/// メッセージ種別が V/R/y 以外の場合、エントリに Symbol が欠落している場合。
+pub fn related_symbols(msg: &FixMessage) -> Result<Vec<Instrument>, InstrumentError> {
+    if !has_related_sym(&msg.msg_type) {
+        return Err(InstrumentError::WrongMsgType(msg.msg_type.clone()));
+    }
+    msg.groups(tag::NO_RELATED_SYM)
+        .iter()
+        .map(Instrument::from_entry)
+        .collect()
+}
+
+/// 銘柄リストから `NoRelatedSym` グループを構築。
+#[must_use]
+pub fn related_sym_group(instruments: &[Instrument]) -> RepeatingGroup {
+    let mut group = RepeatingGroup::new(tag::NO_RELATED_SYM, tag::SYMBOL);
+    for inst in instruments {
+        group.add_entry(inst.to_entry());
+    }
+    group
+}
+
+/// ビルダーに `NoRelatedSym` グループを追加。
+pub fn append_related_symbols(builder: &mut FixBuilder, instruments: &[Instrument]) {
+    builder.group(&related_sym_group(instruments));
+}
+
+/// Instrument エラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstrumentError {
+    /// メッセージタイプが不正。
+    WrongMsgType(String),
+    /// 必須フィールドが欠落。
+    MissingField(u32),
+}
+
+impl core::fmt::Display for InstrumentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongMsgType(t) => write!(f, "MsgType {t} has no NoRelatedSym group"),
+            Self::MissingField(tag) => write!(f, "Missing required field: tag {tag}"),
+        }
+    }
+}
+
+impl std::error::Error for InstrumentError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::version::BeginString;
+
+    fn btc() -> Instrument {
+        Instrument {
+            security_exchange: Some("XALC".into()),
+            currency: Some("USD".into()),
+            ..Instrument::new("BTCUSD")
+        }
+    }
+
+    fn roundtrip(msg_type: &str, instruments: &[Instrument]) -> FixMessage {
+        let mut b = FixBuilder::new(BeginString::Fix44, msg_type);
+        b.field(tag::MD_REQ_ID, "REQ-1");
+        append_related_symbols(&mut b, instruments);
+        parser::parse(&b.build()).unwrap()
+    }
+
+    #[test]
+    fn market_data_request_multiple_symbols() {
+        let msg = roundtrip(
+            msg_type::MARKET_DATA_REQUEST,
+            &[btc(), Instrument::new("ETHUSD")],
+        );
+        let syms = related_symbols(&msg).unwrap();
+        assert_eq!(syms.len(), 2);
+        assert_eq!(syms[0], btc());
+        assert_eq!(syms[1].symbol, "ETHUSD");
+        assert!(syms[1].currency.is_none());
+        // Symbol は flat フィールドに潰れない
+        assert_eq!(msg.get(tag::SYMBOL), None);
+    }
+
+    #[test]
+    fn quote_request_entries_keep_side_and_qty() {
+        let mut entry = Instrument::new("BTCUSD").to_entry();
+        entry.set(tag::SIDE, "1").set(tag::ORDER_QTY, "5");
+        let mut group = RepeatingGroup::new(tag::NO_RELATED_SYM, tag::SYMBOL);
+        group.add_entry(entry);
+        let bytes = FixBuilder::new(BeginString::Fix44, msg_type::QUOTE_REQUEST)
+            .field(tag::QUOTE_REQ_ID, "Q1")
+            .group(&group)
+            .build();
+        let msg = parser::parse(&bytes).unwrap();
+
+        let entries = msg.groups(tag::NO_RELATED_SYM);
+        assert_eq!(entries[0].get(tag::SIDE), Some("1"));
+        assert_eq!(entries[0].get_u64(tag::ORDER_QTY), Some(5));
+        assert_eq!(related_symbols(&msg).unwrap()[0].symbol, "BTCUSD");
+    }
+
+    #[test]
+    fn security_list_roundtrip() {
+        let msg = roundtrip(msg_type::SECURITY_LIST, &[btc()]);
+        assert_eq!(related_symbols(&msg).unwrap(), vec![btc()]);
+    }
+
+    #[test]
+    fn wrong_msg_type() {
+        let msg = FixMessage::new("FIX.4.4", "D");
+        assert_eq!(
+            related_symbols(&msg),
+            Err(InstrumentError::WrongMsgType("D".into()))
+        );
+    }
+
+    #[test]
+    fn entry_missing_symbol() {
+        let mut entry = GroupEntry::new();
+        entry.set(tag::SECURITY_ID, "US0000000001");
+        assert_eq!(
+            Instrument::from_entry(&entry),
+            Err(InstrumentError::MissingField(tag::SYMBOL))
+        );
+    }
+
+    #[test]
+    fn empty_request_has_no_symbols() {
+        let msg = roundtrip(msg_type::MARKET_DATA_REQUEST, &[]);
+        assert!(related_symbols(&msg).unwrap().is_empty());
+        assert_eq!(msg.get_u64(tag::NO_RELATED_SYM), Some(0));
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            InstrumentError::WrongMsgType("D".into()).to_string(),
+            "MsgType D has no NoRelatedSym group"
+        );
+    }
+}
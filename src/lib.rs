@@ -33,10 +33,10 @@
 //! ## Example
 //!
 //! ```rust
-//! use alice_fix::{builder::FixBuilder, parser, tag};
+//! use alice_fix::{builder::FixBuilder, parser, tag, BeginString};
 //!
 //! // Build a simple Heartbeat.
-//! let bytes = FixBuilder::new("FIX.4.4", "0")
+//! let bytes = FixBuilder::new(BeginString::Fix44, "0")
 //!     .field(tag::SENDER_COMP_ID, "ALICE")
 //!     .field(tag::TARGET_COMP_ID, "BROKER")
 //!     .field(tag::MSG_SEQ_NUM, "1")
@@ -48,24 +48,70 @@
 //! assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
 //! ```
 
+pub mod acceptor;
 pub mod admin;
+#[cfg(feature = "alloc-count")]
+pub mod alloc_count;
+pub mod application;
 pub mod builder;
+pub mod clock;
+#[cfg(feature = "codec")]
+pub mod codec;
+pub mod config;
 pub mod convert;
+#[cfg(any(test, feature = "testing"))]
+pub mod corpus;
+pub mod dictionary;
+pub mod drain;
+#[cfg(feature = "engine")]
+pub mod engine;
 pub mod execution_report;
 #[cfg(feature = "ffi")]
 pub mod ffi;
+pub mod field_map;
+pub mod firewall;
 pub mod gap_detect;
+pub mod header;
+pub mod heartbeat;
+pub mod instrument;
+pub mod list;
+pub mod manager;
+pub mod market_data;
+pub mod mass_status;
+pub mod md_subscription;
 pub mod message;
+pub mod order;
 pub mod parser;
+pub mod reconcile;
+pub mod reject_text;
 pub mod repeating_group;
+pub mod sanitize;
+pub mod schedule;
 pub mod session;
+#[cfg(any(test, feature = "testing"))]
+pub mod simulator;
+pub mod stats;
+pub mod store;
 pub mod tag;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod throttle;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod validation;
+pub mod value;
+pub mod version;
 
 // Re-export the most commonly used types at the crate root.
-pub use builder::FixBuilder;
-pub use message::FixMessage;
+pub use builder::{BuildError, FixBuilder};
+pub use message::{FieldError, FixMessage, StandardHeader};
 pub use parser::ParseError;
-pub use session::{FixSession, SessionState};
+pub use session::{
+    Amendment, FixSession, RotationError, SeqCheck, SessionEvent, SessionId, SessionMetrics,
+    SessionMode, SessionRole, SessionSnapshot, SessionState, SubmitError,
+};
+pub use value::FixValue;
+pub use version::BeginString;
 
 /// ALICE-FIX crate version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
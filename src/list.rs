@@ -0,0 +1,604 @@
+//! List Orders
+//!
+//! プログラム売買のバスケット発注 `NewOrderList` (E) と、リスト全体の
+//! 状況を返す `ListStatus` (N)。
+//!
+//! 各注文は `NoOrders` (73) グループのエントリで、`ClOrdID` (11) から
+//! 始まり `ListSeqNo` (67) でリスト内の順番を示す。`TotNoOrders` (68) は
+//! リストの総件数で、分割送信しない限りエントリ数と等しい。`ListStatus`
+//! のエントリは注文ごとの `CumQty` / `LeavesQty` / `CxlQty` / `AvgPx` を
+//! 持つ。個々の約定は通常どおり `ExecutionReport` (8) で届く。
+
+use alice_ledger::{OrderType, Side, TimeInForce};
+
+use crate::builder::FixBuilder;
+use crate::convert::{
+    alice_ord_type_to_fix, alice_side_to_fix, alice_tif_to_fix, fix_ord_type_to_alice,
+    fix_side_to_alice, fix_tif_to_alice,
+};
+use crate::execution_report::OrdStatus;
+use crate::message::FixMessage;
+use crate::order::{OrderMsgError, DEFAULT_BEGIN_STRING};
+use crate::repeating_group::{GroupEntry, RepeatingGroup};
+use crate::tag;
+use crate::value::Decimal;
+use crate::version::BeginString;
+
+/// リスト注文のメッセージ種別。
+pub mod msg_type {
+    /// New Order List。
+    pub const NEW_ORDER_LIST: &str = "E";
+    /// List Status。
+    pub const LIST_STATUS: &str = "N";
+}
+
+/// 入札方式 (`BidType`, tag 394)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidType {
+    /// 非開示 (1)。
+    NonDisclosed,
+    /// 開示 (2)。
+    Disclosed,
+    /// 入札なし (3)。
+    NoBiddingProcess,
+}
+
+impl BidType {
+    /// FIX 値から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        Some(match s {
+            "1" => Self::NonDisclosed,
+            "2" => Self::Disclosed,
+            "3" => Self::NoBiddingProcess,
+            _ => return None,
+        })
+    }
+
+    /// FIX 値に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::NonDisclosed => "1",
+            Self::Disclosed => "2",
+            Self::NoBiddingProcess => "3",
+        }
+    }
+}
+
+/// `ListStatus` の送信理由 (`ListStatusType`, tag 429)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListStatusType {
+    /// 受付 (1)。
+    Ack,
+    /// 照会への応答 (2)。
+    Response,
+    /// 定時 (3)。
+    Timed,
+    /// 執行開始 (4)。
+    ExecStarted,
+    /// 全注文の完了 (5)。
+    AllDone,
+    /// 警告 (6)。
+    Alert,
+}
+
+impl ListStatusType {
+    /// FIX 値から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        Some(match s {
+            "1" => Self::Ack,
+            "2" => Self::Response,
+            "3" => Self::Timed,
+            "4" => Self::ExecStarted,
+            "5" => Self::AllDone,
+            "6" => Self::Alert,
+            _ => return None,
+        })
+    }
+
+    /// FIX 値に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::Ack => "1",
+            Self::Response => "2",
+            Self::Timed => "3",
+            Self::ExecStarted => "4",
+            Self::AllDone => "5",
+            Self::Alert => "6",
+        }
+    }
+}
+
+/// リスト全体の状態 (`ListOrderStatus`, tag 431)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListOrderStatus {
+    /// 入札中 (1)。
+    InBiddingProcess,
+    /// 執行待ち (2)。
+    ReceivedForExecution,
+    /// 執行中 (3)。
+    Executing,
+    /// 取消中 (4)。
+    Cancelling,
+    /// 警告 (5)。
+    Alert,
+    /// 全注文の完了 (6)。
+    AllDone,
+    /// 拒否 (7)。
+    Reject,
+}
+
+impl ListOrderStatus {
+    /// FIX 値から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        Some(match s {
+            "1" => Self::InBiddingProcess,
+            "2" => Self::ReceivedForExecution,
+            "3" => Self::Executing,
+            "4" => Self::Cancelling,
+            "5" => Self::Alert,
+            "6" => Self::AllDone,
+            "7" => Self::Reject,
+            _ => return None,
+        })
+    }
+
+    /// FIX 値に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::InBiddingProcess => "1",
+            Self::ReceivedForExecution => "2",
+            Self::Executing => "3",
+            Self::Cancelling => "4",
+            Self::Alert => "5",
+            Self::AllDone => "6",
+            Self::Reject => "7",
+        }
+    }
+
+    /// これ以上状況が変わらないか (完了または拒否)。
+    #[must_use]
+    pub const fn is_final(self) -> bool {
+        matches!(self, Self::AllDone | Self::Reject)
+    }
+}
+
+/// `NewOrderList` の 1 注文 (`NoOrders` エントリ)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListOrder {
+    /// クライアント注文 ID (tag 11)。
+    pub cl_ord_id: String,
+    /// リスト内の順番 (tag 67, 1 始まり)。
+    pub list_seq_no: u32,
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// サイド (tag 54)。
+    pub side: Side,
+    /// 注文種別 (tag 40)。
+    pub ord_type: OrderType,
+    /// 数量 (tag 38)。
+    pub order_qty: Decimal,
+    /// 指値 (tag 44)。
+    pub price: Option<Decimal>,
+    /// 執行条件 (tag 59)。
+    pub time_in_force: Option<TimeInForce>,
+    /// 口座 (tag 1)。
+    pub account: Option<String>,
+}
+
+impl ListOrder {
+    /// グループエントリから変換。
+    ///
+    /// # Errors
+    ///
+    /// 必須フィールドの欠落、または値を解釈できない場合。
+    pub fn from_entry(entry: &GroupEntry) -> Result<Self, OrderMsgError> {
+        Ok(Self {
+            cl_ord_id: require(entry, tag::CL_ORD_ID)?.to_string(),
+            list_seq_no: convert(entry, tag::LIST_SEQ_NO, |v| v.parse().ok())?,
+            symbol: require(entry, tag::SYMBOL)?.to_string(),
+            side: convert(entry, tag::SIDE, fix_side_to_alice)?,
+            ord_type: convert(entry, tag::ORD_TYPE, fix_ord_type_to_alice)?,
+            order_qty: convert(entry, tag::ORDER_QTY, Decimal::parse)?,
+            price: optional(entry, tag::PRICE, Decimal::parse)?,
+            time_in_force: optional(entry, tag::TIME_IN_FORCE, fix_tif_to_alice)?,
+            account: entry.get(tag::ACCOUNT).map(String::from),
+        })
+    }
+
+    /// グループエントリに変換。
+    #[must_use]
+    pub fn to_entry(&self) -> GroupEntry {
+        let mut entry = GroupEntry::new();
+        entry
+            .set(tag::CL_ORD_ID, &self.cl_ord_id)
+            .set(tag::LIST_SEQ_NO, &self.list_seq_no.to_string())
+            .set(tag::SYMBOL, &self.symbol)
+            .set(tag::SIDE, alice_side_to_fix(self.side))
+            .set(tag::ORD_TYPE, alice_ord_type_to_fix(self.ord_type))
+            .set(tag::ORDER_QTY, &self.order_qty.to_string());
+        if let Some(px) = self.price {
+            entry.set(tag::PRICE, &px.to_string());
+        }
+        if let Some(tif) = self.time_in_force {
+            entry.set(tag::TIME_IN_FORCE, alice_tif_to_fix(tif));
+        }
+        if let Some(account) = &self.account {
+            entry.set(tag::ACCOUNT, account);
+        }
+        entry
+    }
+}
+
+/// New Order List (35=E)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewOrderList {
+    /// リスト ID (tag 66)。
+    pub list_id: String,
+    /// 入札方式 (tag 394)。
+    pub bid_type: BidType,
+    /// 注文 (`ListSeqNo` 順)。
+    pub orders: Vec<ListOrder>,
+}
+
+impl NewOrderList {
+    /// 入札なしの空のリストを作成。
+    #[must_use]
+    pub fn new(list_id: &str) -> Self {
+        Self {
+            list_id: list_id.to_string(),
+            bid_type: BidType::NoBiddingProcess,
+            orders: Vec::new(),
+        }
+    }
+
+    /// 注文を末尾に追加し、`ListSeqNo` を振る。
+    pub fn push(&mut self, mut order: ListOrder) -> &mut Self {
+        order.list_seq_no = u32::try_from(self.orders.len() + 1).unwrap_or(u32::MAX);
+        self.orders.push(order);
+        self
+    }
+
+    /// `begin_string` を指定してビルダーに変換 (ヘッダは呼び出し側で追加)。
+    ///
+    /// `TotNoOrders` はエントリ数。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut group = RepeatingGroup::new(tag::NO_ORDERS, tag::CL_ORD_ID);
+        for order in &self.orders {
+            group.add_entry(order.to_entry());
+        }
+        let mut b = FixBuilder::new(begin_string, msg_type::NEW_ORDER_LIST);
+        b.field(tag::LIST_ID, &self.list_id)
+            .field(tag::BID_TYPE, self.bid_type.to_fix())
+            .field_u64(tag::TOT_NO_ORDERS, self.orders.len() as u64)
+            .group(&group);
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for NewOrderList {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::NEW_ORDER_LIST)?;
+        let bid_type = msg.require(tag::BID_TYPE)?;
+        Ok(Self {
+            list_id: msg.require(tag::LIST_ID)?.to_string(),
+            bid_type: BidType::from_fix(bid_type)
+                .ok_or_else(|| invalid(tag::BID_TYPE, bid_type))?,
+            orders: msg
+                .groups(tag::NO_ORDERS)
+                .iter()
+                .map(ListOrder::from_entry)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&NewOrderList> for FixBuilder {
+    fn from(msg: &NewOrderList) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+/// `ListStatus` の 1 注文 (`NoOrders` エントリ)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListOrderState {
+    /// クライアント注文 ID (tag 11)。
+    pub cl_ord_id: String,
+    /// 約定済み数量 (tag 14)。
+    pub cum_qty: Decimal,
+    /// 注文ステータス (tag 39)。
+    pub ord_status: OrdStatus,
+    /// 残数量 (tag 151)。
+    pub leaves_qty: Decimal,
+    /// 取消数量 (tag 84)。
+    pub cxl_qty: Decimal,
+    /// 平均約定価格 (tag 6)。
+    pub avg_px: Decimal,
+    /// テキスト (tag 58)。
+    pub text: Option<String>,
+}
+
+impl ListOrderState {
+    /// グループエントリから変換。
+    ///
+    /// # Errors
+    ///
+    /// 必須フィールドの欠落、または値を解釈できない場合。
+    pub fn from_entry(entry: &GroupEntry) -> Result<Self, OrderMsgError> {
+        Ok(Self {
+            cl_ord_id: require(entry, tag::CL_ORD_ID)?.to_string(),
+            cum_qty: convert(entry, tag::CUM_QTY, Decimal::parse)?,
+            ord_status: OrdStatus::from_fix(require(entry, tag::ORD_STATUS)?),
+            leaves_qty: convert(entry, tag::LEAVES_QTY, Decimal::parse)?,
+            cxl_qty: convert(entry, tag::CXL_QTY, Decimal::parse)?,
+            avg_px: convert(entry, tag::AVG_PX, Decimal::parse)?,
+            text: entry.get(tag::TEXT).map(String::from),
+        })
+    }
+
+    /// グループエントリに変換。
+    #[must_use]
+    pub fn to_entry(&self) -> GroupEntry {
+        let mut entry = GroupEntry::new();
+        entry
+            .set(tag::CL_ORD_ID, &self.cl_ord_id)
+            .set(tag::CUM_QTY, &self.cum_qty.to_string())
+            .set(tag::ORD_STATUS, &self.ord_status.to_fix().to_string())
+            .set(tag::LEAVES_QTY, &self.leaves_qty.to_string())
+            .set(tag::CXL_QTY, &self.cxl_qty.to_string())
+            .set(tag::AVG_PX, &self.avg_px.to_string());
+        if let Some(text) = &self.text {
+            entry.set(tag::TEXT, text);
+        }
+        entry
+    }
+}
+
+/// List Status (35=N)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListStatus {
+    /// リスト ID (tag 66)。
+    pub list_id: String,
+    /// 送信理由 (tag 429)。
+    pub list_status_type: ListStatusType,
+    /// この応答を構成する `ListStatus` の件数 (tag 82)。
+    pub no_rpts: u32,
+    /// リスト全体の状態 (tag 431)。
+    pub list_order_status: ListOrderStatus,
+    /// `NoRpts` 中の順番 (tag 83, 1 始まり)。
+    pub rpt_seq: u32,
+    /// リストの総注文数 (tag 68)。
+    pub tot_no_orders: u32,
+    /// テキスト (tag 444)。
+    pub list_status_text: Option<String>,
+    /// 時刻 (tag 60, UNIX ナノ秒)。
+    pub transact_time: Option<u64>,
+    /// 注文ごとの状態。
+    pub orders: Vec<ListOrderState>,
+}
+
+impl ListStatus {
+    /// `cl_ord_id` の注文の状態。
+    #[must_use]
+    pub fn order(&self, cl_ord_id: &str) -> Option<&ListOrderState> {
+        self.orders.iter().find(|o| o.cl_ord_id == cl_ord_id)
+    }
+
+    /// `begin_string` を指定してビルダーに変換 (ヘッダは呼び出し側で追加)。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut group = RepeatingGroup::new(tag::NO_ORDERS, tag::CL_ORD_ID);
+        for order in &self.orders {
+            group.add_entry(order.to_entry());
+        }
+        let mut b = FixBuilder::new(begin_string, msg_type::LIST_STATUS);
+        b.field(tag::LIST_ID, &self.list_id)
+            .field(tag::LIST_STATUS_TYPE, self.list_status_type.to_fix())
+            .field_u64(tag::NO_RPTS, u64::from(self.no_rpts))
+            .field(tag::LIST_ORDER_STATUS, self.list_order_status.to_fix())
+            .field_u64(tag::RPT_SEQ, u64::from(self.rpt_seq))
+            .field_opt(tag::LIST_STATUS_TEXT, self.list_status_text.as_deref());
+        if let Some(ns) = self.transact_time {
+            b.field_timestamp(tag::TRANSACT_TIME, ns);
+        }
+        b.field_u64(tag::TOT_NO_ORDERS, u64::from(self.tot_no_orders))
+            .group(&group);
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for ListStatus {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::LIST_STATUS)?;
+        let status_type = msg.require(tag::LIST_STATUS_TYPE)?;
+        let order_status = msg.require(tag::LIST_ORDER_STATUS)?;
+        let count = |t: u32| {
+            let v = msg.require(t)?;
+            v.parse().map_err(|_| invalid(t, v))
+        };
+        Ok(Self {
+            list_id: msg.require(tag::LIST_ID)?.to_string(),
+            list_status_type: ListStatusType::from_fix(status_type)
+                .ok_or_else(|| invalid(tag::LIST_STATUS_TYPE, status_type))?,
+            no_rpts: count(tag::NO_RPTS)?,
+            list_order_status: ListOrderStatus::from_fix(order_status)
+                .ok_or_else(|| invalid(tag::LIST_ORDER_STATUS, order_status))?,
+            rpt_seq: count(tag::RPT_SEQ)?,
+            tot_no_orders: count(tag::TOT_NO_ORDERS)?,
+            list_status_text: msg.get(tag::LIST_STATUS_TEXT).map(String::from),
+            transact_time: msg.get_timestamp(tag::TRANSACT_TIME),
+            orders: msg
+                .groups(tag::NO_ORDERS)
+                .iter()
+                .map(ListOrderState::from_entry)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&ListStatus> for FixBuilder {
+    fn from(msg: &ListStatus) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+fn expect_msg_type(msg: &FixMessage, expected: &'static str) -> Result<(), OrderMsgError> {
+    if msg.msg_type == expected {
+        Ok(())
+    } else {
+        Err(OrderMsgError::WrongMsgType {
+            expected,
+            actual: msg.msg_type.clone(),
+        })
+    }
+}
+
+fn invalid(t: u32, value: &str) -> OrderMsgError {
+    OrderMsgError::InvalidField {
+        tag: t,
+        value: value.to_string(),
+    }
+}
+
+fn require(entry: &GroupEntry, t: u32) -> Result<&str, OrderMsgError> {
+    entry.get(t).ok_or(OrderMsgError::MissingField(t))
+}
+
+/// エントリの必須フィールドを `f` で変換。
+fn convert<T>(
+    entry: &GroupEntry,
+    t: u32,
+    f: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, OrderMsgError> {
+    let v = require(entry, t)?;
+    f(v).ok_or_else(|| invalid(t, v))
+}
+
+/// エントリの任意フィールドを `f` で変換。存在して変換できない場合はエラー。
+fn optional<T>(
+    entry: &GroupEntry,
+    t: u32,
+    f: impl FnOnce(&str) -> Option<T>,
+) -> Result<Option<T>, OrderMsgError> {
+    entry
+        .get(t)
+        .map(|v| f(v).ok_or_else(|| invalid(t, v)))
+        .transpose()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn order(cl_ord_id: &str, symbol: &str, side: Side, qty: &str, px: &str) -> ListOrder {
+        ListOrder {
+            cl_ord_id: cl_ord_id.to_string(),
+            list_seq_no: 0,
+            symbol: symbol.to_string(),
+            side,
+            ord_type: OrderType::Limit,
+            order_qty: Decimal::parse(qty).unwrap(),
+            price: Decimal::parse(px),
+            time_in_force: Some(TimeInForce::GTC),
+            account: None,
+        }
+    }
+
+    #[test]
+    fn new_order_list_roundtrip() {
+        let mut list = NewOrderList::new("L1");
+        list.push(order("C1", "BTCUSD", Side::Bid, "2", "48000"))
+            .push(ListOrder {
+                account: Some("ACC1".into()),
+                ..order("C2", "ETHUSD", Side::Ask, "10", "2500.5")
+            });
+        assert_eq!(list.orders[1].list_seq_no, 2);
+
+        let builder = FixBuilder::from(&list);
+        assert_eq!(builder.missing_required().len(), 4); // ヘッダのみ欠落
+        let msg = parser::parse(&builder.build()).unwrap();
+        assert_eq!(msg.msg_type, "E");
+        assert_eq!(msg.get(tag::BID_TYPE), Some("3"));
+        assert_eq!(msg.get_u64(tag::TOT_NO_ORDERS), Some(2));
+        // エントリのフィールドはグループ側にだけある。
+        assert_eq!(msg.get(tag::CL_ORD_ID), None);
+        assert_eq!(
+            msg.groups(tag::NO_ORDERS)[1].get(tag::PRICE),
+            Some("2500.5")
+        );
+        assert_eq!(NewOrderList::try_from(&msg).unwrap(), list);
+
+        let mut bad = msg;
+        bad.set(tag::BID_TYPE, "9");
+        assert_eq!(
+            NewOrderList::try_from(&bad),
+            Err(OrderMsgError::InvalidField {
+                tag: tag::BID_TYPE,
+                value: "9".into()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_list_status() {
+        let mut b = FixBuilder::new(BeginString::Fix44, msg_type::LIST_STATUS);
+        for (t, v) in [
+            (tag::LIST_ID, "L1"),
+            (tag::LIST_STATUS_TYPE, "4"),
+            (tag::NO_RPTS, "1"),
+            (tag::LIST_ORDER_STATUS, "3"),
+            (tag::RPT_SEQ, "1"),
+            (tag::TOT_NO_ORDERS, "2"),
+            (tag::NO_ORDERS, "2"),
+            (tag::CL_ORD_ID, "C1"),
+            (tag::CUM_QTY, "2"),
+            (tag::ORD_STATUS, "2"),
+            (tag::LEAVES_QTY, "0"),
+            (tag::CXL_QTY, "0"),
+            (tag::AVG_PX, "48000"),
+            (tag::CL_ORD_ID, "C2"),
+            (tag::CUM_QTY, "4"),
+            (tag::ORD_STATUS, "1"),
+            (tag::LEAVES_QTY, "6"),
+            (tag::CXL_QTY, "0"),
+            (tag::AVG_PX, "2500.5"),
+        ] {
+            b.field(t, v);
+        }
+        let msg = parser::parse(&b.build()).unwrap();
+        let status = ListStatus::try_from(&msg).unwrap();
+        assert_eq!(status.list_status_type, ListStatusType::ExecStarted);
+        assert_eq!(status.list_order_status, ListOrderStatus::Executing);
+        assert!(!status.list_order_status.is_final());
+        assert_eq!(status.orders.len(), 2);
+        assert_eq!(status.order("C1").unwrap().ord_status, OrdStatus::Filled);
+        let c2 = status.order("C2").unwrap();
+        assert_eq!(c2.ord_status, OrdStatus::PartiallyFilled);
+        assert_eq!(c2.leaves_qty, Decimal::parse("6").unwrap());
+
+        let again = parser::parse(&FixBuilder::from(&status).build()).unwrap();
+        assert_eq!(ListStatus::try_from(&again).unwrap(), status);
+
+        let mut missing = msg;
+        missing.remove(tag::RPT_SEQ);
+        assert_eq!(
+            ListStatus::try_from(&missing),
+            Err(OrderMsgError::MissingField(tag::RPT_SEQ))
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Multi-Session Manager
+//!
+//! 1 プロセスで複数の相手方と接続するための [`FixSession`] の集合。
+//! セッションは [`SessionId`] をキーに保持し、受信メッセージはヘッダ
+//! (`BeginString`、`SenderCompID` / `TargetCompID` を自側から見て逆向き)
+//! で振り分ける ([`SessionManager::route`])。
+//!
+//! 修飾子 ([`SessionId::qualifier`]、設定では
+//! [`crate::config::SessionConfig::qualifier`]) を付ければ、同じ相手方への
+//! `CompID` が同一のセッション (発注用とドロップコピー用など) を並べて
+//! 登録できる。修飾子はワイヤ上に現れないため、ヘッダだけでの振り分けは
+//! [`RouteError::Ambiguous`] になる。その場合は接続ごとに受け持つ
+//! [`SessionId`] を覚えておき、[`SessionManager::on_message_for`] で
+//! 宛先を指定して処理する。
+
+use std::collections::BTreeMap;
+
+use crate::message::FixMessage;
+use crate::session::{FixSession, SessionAction, SessionId};
+use crate::tag;
+
+/// 受信メッセージを振り分けられなかった理由。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// ヘッダに必須タグが無い。
+    MissingHeader(u32),
+    /// ヘッダに一致するセッションが無い。
+    UnknownSession {
+        /// `BeginString` (tag 8)。
+        begin_string: String,
+        /// 受信した `SenderCompID` (tag 49)。
+        sender_comp_id: String,
+        /// 受信した `TargetCompID` (tag 56)。
+        target_comp_id: String,
+    },
+    /// 修飾子違いのセッションが複数一致した。
+    Ambiguous(Vec<SessionId>),
+}
+
+impl core::fmt::Display for RouteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingHeader(tag) => write!(f, "header tag {tag} missing"),
+            Self::UnknownSession {
+                begin_string,
+                sender_comp_id,
+                target_comp_id,
+            } => write!(
+                f,
+                "no session for {begin_string} {sender_comp_id}->{target_comp_id}"
+            ),
+            Self::Ambiguous(ids) => write!(f, "{} sessions match the header", ids.len()),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+/// 複数セッションの管理。
+#[derive(Default)]
+pub struct SessionManager {
+    sessions: BTreeMap<SessionId, FixSession>,
+}
+
+impl core::fmt::Debug for SessionManager {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionManager")
+            .field("sessions", &self.sessions.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl SessionManager {
+    /// 空のマネージャを作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// セッションを追加 (同じ [`SessionId`] の既存は置き換えて返す)。
+    pub fn insert(&mut self, session: FixSession) -> Option<FixSession> {
+        self.sessions.insert(session.session_id(), session)
+    }
+
+    /// セッションを取り除く。
+    pub fn remove(&mut self, id: &SessionId) -> Option<FixSession> {
+        self.sessions.remove(id)
+    }
+
+    /// `id` のセッション。
+    #[must_use]
+    pub fn get(&self, id: &SessionId) -> Option<&FixSession> {
+        self.sessions.get(id)
+    }
+
+    /// `id` のセッション。
+    pub fn get_mut(&mut self, id: &SessionId) -> Option<&mut FixSession> {
+        self.sessions.get_mut(id)
+    }
+
+    /// セッション数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// セッションが無いか。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// 登録済みの [`SessionId`] (昇順)。
+    pub fn ids(&self) -> impl Iterator<Item = &SessionId> {
+        self.sessions.keys()
+    }
+
+    /// セッションを [`SessionId`] の昇順で走査。
+    pub fn iter(&self) -> impl Iterator<Item = (&SessionId, &FixSession)> {
+        self.sessions.iter()
+    }
+
+    /// セッションを [`SessionId`] の昇順で走査。
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&SessionId, &mut FixSession)> {
+        self.sessions.iter_mut()
+    }
+
+    /// 受信メッセージのヘッダから宛先セッションを決める。
+    ///
+    /// # Errors
+    ///
+    /// `SenderCompID` / `TargetCompID` の欠落、一致するセッションが無い、
+    /// または複数ある場合。
+    pub fn route(&self, msg: &FixMessage) -> Result<&SessionId, RouteError> {
+        let sender = msg
+            .get(tag::SENDER_COMP_ID)
+            .ok_or(RouteError::MissingHeader(tag::SENDER_COMP_ID))?;
+        let target = msg
+            .get(tag::TARGET_COMP_ID)
+            .ok_or(RouteError::MissingHeader(tag::TARGET_COMP_ID))?;
+        // 修飾子なしの ID が同じヘッダを持つ範囲の先頭。
+        let first = SessionId::new(&msg.begin_string, target, sender);
+        let matches: Vec<&SessionId> = self
+            .sessions
+            .range(first..)
+            .map(|(id, _)| id)
+            .take_while(|id| id.matches_inbound(msg))
+            .collect();
+        match matches.as_slice() {
+            [id] => Ok(id),
+            [] => Err(RouteError::UnknownSession {
+                begin_string: msg.begin_string.clone(),
+                sender_comp_id: sender.to_string(),
+                target_comp_id: target.to_string(),
+            }),
+            _ => Err(RouteError::Ambiguous(
+                matches.into_iter().cloned().collect(),
+            )),
+        }
+    }
+
+    /// `id` のセッションの [`FixSession::on_message`] を呼ぶ。
+    ///
+    /// 修飾子でしか区別できないセッションを接続ごとに処理するためのもの。
+    /// ヘッダが `id` 宛てでなければ処理しない。
+    ///
+    /// # Errors
+    ///
+    /// `id` が未登録、またはヘッダが `id` と一致しない場合
+    /// ([`RouteError::UnknownSession`])。
+    pub fn on_message_for(
+        &mut self,
+        id: &SessionId,
+        msg: &FixMessage,
+    ) -> Result<Vec<SessionAction>, RouteError> {
+        match self.sessions.get_mut(id) {
+            Some(session) if id.matches_inbound(msg) => Ok(session.on_message(msg)),
+            _ => Err(RouteError::UnknownSession {
+                begin_string: msg.begin_string.clone(),
+                sender_comp_id: msg.get(tag::SENDER_COMP_ID).unwrap_or_default().to_string(),
+                target_comp_id: msg.get(tag::TARGET_COMP_ID).unwrap_or_default().to_string(),
+            }),
+        }
+    }
+
+    /// 宛先セッションの [`FixSession::on_message`] を呼ぶ。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::route`] と同じ。
+    pub fn on_message(
+        &mut self,
+        msg: &FixMessage,
+    ) -> Result<(SessionId, Vec<SessionAction>), RouteError> {
+        let id = self.route(msg)?.clone();
+        let actions = self
+            .sessions
+            .get_mut(&id)
+            .map(|session| session.on_message(msg))
+            .unwrap_or_default();
+        Ok((id, actions))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{SessionRole, SessionState};
+    use crate::version::BeginString;
+
+    fn inbound(sender: &str, target: &str) -> FixMessage {
+        let mut msg = FixMessage::new("FIX.4.4", "A");
+        msg.set(tag::SENDER_COMP_ID, sender)
+            .set(tag::TARGET_COMP_ID, target)
+            .set(tag::MSG_SEQ_NUM, "1")
+            .set(tag::HEART_BT_INT, "30");
+        msg
+    }
+
+    fn acceptor(sender: &str, target: &str) -> FixSession {
+        let mut session = FixSession::new(sender, target, BeginString::Fix44);
+        session.set_role(SessionRole::Acceptor);
+        session
+    }
+
+    #[test]
+    fn routes_by_header() {
+        let mut manager = SessionManager::new();
+        for firm in ["FIRM_A", "FIRM_B", "FIRM_C"] {
+            assert!(manager.insert(acceptor("EXCH", firm)).is_none());
+        }
+        assert!(manager.insert(acceptor("EXCH", "FIRM_A")).is_some());
+        assert_eq!(manager.len(), 3);
+
+        let (id, actions) = manager.on_message(&inbound("FIRM_B", "EXCH")).unwrap();
+        assert_eq!(id, SessionId::new("FIX.4.4", "EXCH", "FIRM_B"));
+        assert!(matches!(actions[0], SessionAction::Send(_)));
+        let active: Vec<_> = manager
+            .iter()
+            .filter(|(_, s)| *s.state() == SessionState::Active)
+            .map(|(id, _)| id.target_comp_id.as_str())
+            .collect();
+        assert_eq!(active, ["FIRM_B"]);
+
+        assert!(matches!(
+            manager.route(&inbound("FIRM_X", "EXCH")),
+            Err(RouteError::UnknownSession { .. })
+        ));
+        let mut missing = inbound("FIRM_A", "EXCH");
+        missing.remove(tag::TARGET_COMP_ID);
+        assert_eq!(
+            manager.route(&missing),
+            Err(RouteError::MissingHeader(tag::TARGET_COMP_ID))
+        );
+    }
+
+    #[test]
+    fn qualified_sessions_need_direct_lookup() {
+        let mut manager = SessionManager::new();
+        let mut primary = acceptor("EXCH", "FIRM_A");
+        primary.set_qualifier(Some("primary"));
+        let mut backup = acceptor("EXCH", "FIRM_A");
+        backup.set_qualifier(Some("backup"));
+        let primary_id = primary.session_id();
+        manager.insert(primary);
+        manager.insert(backup);
+        manager.insert(acceptor("EXCH", "FIRM_B"));
+
+        assert!(matches!(
+            manager.route(&inbound("FIRM_A", "EXCH")),
+            Err(RouteError::Ambiguous(ids)) if ids.len() == 2
+        ));
+        assert!(manager.get(&primary_id).is_some());
+        assert_eq!(
+            manager.remove(&primary_id).unwrap().session_id(),
+            primary_id
+        );
+        assert!(manager.route(&inbound("FIRM_A", "EXCH")).is_ok());
+    }
+
+    #[test]
+    fn qualified_sessions_addressed_per_connection() {
+        use crate::config::SessionConfig;
+
+        let text = r#"
+            [default]
+            begin_string = "FIX.4.4"
+            sender_comp_id = "EXCH"
+            target_comp_id = "FIRM_A"
+            role = "acceptor"
+
+            [[session]]
+            qualifier = "orders"
+
+            [[session]]
+            qualifier = "dropcopy"
+            mode = "drop_copy"
+        "#;
+        let mut manager = SessionManager::new();
+        let mut ids = Vec::new();
+        for config in SessionConfig::from_toml(text).unwrap() {
+            ids.push(config.session_id());
+            assert!(manager.insert(config.session()).is_none());
+        }
+        assert_eq!(manager.len(), 2);
+        assert_ne!(ids[0].file_stem(), ids[1].file_stem());
+
+        let actions = manager
+            .on_message_for(&ids[1], &inbound("FIRM_A", "EXCH"))
+            .unwrap();
+        assert!(matches!(actions[0], SessionAction::Send(_)));
+        assert_eq!(*manager.get(&ids[1]).unwrap().state(), SessionState::Active);
+        assert_eq!(
+            *manager.get(&ids[0]).unwrap().state(),
+            SessionState::Disconnected
+        );
+        assert!(matches!(
+            manager.on_message_for(&ids[0], &inbound("FIRM_B", "EXCH")),
+            Err(RouteError::UnknownSession { .. })
+        ));
+    }
+}
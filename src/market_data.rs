@@ -0,0 +1,646 @@
+//! Market Data Entries & Order Book Assembly
+//!
+//! `MarketDataSnapshotFullRefresh` (W) / `MarketDataIncrementalRefresh` (X) の
+//! `NoMDEntries` (268) グループを型付き [`MDEntry`] に変換し、
+//! [`OrderBookAssembler`] で価格レベル板に組み立てる。
+//! 下流コードはタグ 269/270/271/279 を直接扱わない。
+//!
+//! 全板を配信する取引所でもメモリを使い切らないよう、[`BookLimits`] で
+//! 片側の最大レベル数とメモリ上限を設定できる。上限を超えたレベルは
+//! 最良気配から遠い側から捨て、[`OrderBookAssembler::truncation`] で報告する。
+
+use crate::message::FixMessage;
+use crate::repeating_group::GroupEntry;
+use crate::tag;
+use crate::value::Decimal;
+
+/// マーケットデータメッセージ種別。
+pub mod msg_type {
+    /// Market Data Snapshot/Full Refresh。
+    pub const SNAPSHOT_FULL_REFRESH: &str = "W";
+    /// Market Data Incremental Refresh。
+    pub const INCREMENTAL_REFRESH: &str = "X";
+}
+
+/// エントリ種別 (`MDEntryType`, tag 269)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MDEntryType {
+    /// 買気配。
+    Bid,
+    /// 売気配。
+    Offer,
+    /// 約定。
+    Trade,
+    /// その他。
+    Other(u8),
+}
+
+impl MDEntryType {
+    /// FIX 文字列から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Self {
+        match s {
+            "0" => Self::Bid,
+            "1" => Self::Offer,
+            "2" => Self::Trade,
+            _ => Self::Other(s.as_bytes().first().copied().unwrap_or(0)),
+        }
+    }
+
+    /// FIX 文字に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> char {
+        match self {
+            Self::Bid => '0',
+            Self::Offer => '1',
+            Self::Trade => '2',
+            Self::Other(b) => b as char,
+        }
+    }
+}
+
+/// 更新種別 (`MDUpdateAction`, tag 279)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MDUpdateAction {
+    /// 新規。
+    New,
+    /// 変更。
+    Change,
+    /// 削除。
+    Delete,
+}
+
+impl MDUpdateAction {
+    /// FIX 文字列から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        match s {
+            "0" => Some(Self::New),
+            "1" => Some(Self::Change),
+            "2" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+
+    /// FIX 文字列に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::New => "0",
+            Self::Change => "1",
+            Self::Delete => "2",
+        }
+    }
+}
+
+/// マーケットデータエントリ (`NoMDEntries` の 1 要素)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MDEntry {
+    /// エントリ種別 (tag 269)。
+    pub entry_type: MDEntryType,
+    /// 価格 (tag 270)。
+    pub px: Option<Decimal>,
+    /// 数量 (tag 271)。
+    pub size: Option<Decimal>,
+    /// 板の位置 (tag 290, 1 始まり)。
+    pub position: Option<u32>,
+    /// 更新種別 (tag 279, Incremental のみ)。
+    pub update_action: Option<MDUpdateAction>,
+    /// エントリ ID (tag 278)。
+    pub entry_id: Option<String>,
+    /// シンボル (tag 55, Incremental のエントリ単位)。
+    pub symbol: Option<String>,
+}
+
+impl MDEntry {
+    /// グループエントリから変換。
+    ///
+    /// # Errors
+    ///
+    /// `MDEntryType` が欠落している場合、数値・更新種別が不正な場合。
+    pub fn from_entry(entry: &GroupEntry) -> Result<Self, MarketDataError> {
+        let entry_type = entry
+            .get(tag::MD_ENTRY_TYPE)
+            .map(MDEntryType::from_fix)
+            .ok_or(MarketDataError::MissingField(tag::MD_ENTRY_TYPE))?;
+        let decimal = |t: u32| {
+            entry
+                .get(t)
+                .map(|v| Decimal::parse(v).ok_or(MarketDataError::InvalidField(t)))
+                .transpose()
+        };
+        let position = entry
+            .get(tag::MD_ENTRY_POSITION_NO)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| MarketDataError::InvalidField(tag::MD_ENTRY_POSITION_NO))
+            })
+            .transpose()?;
+        let update_action = entry
+            .get(tag::MD_UPDATE_ACTION)
+            .map(|v| {
+                MDUpdateAction::from_fix(v)
+                    .ok_or(MarketDataError::InvalidField(tag::MD_UPDATE_ACTION))
+            })
+            .transpose()?;
+        Ok(Self {
+            entry_type,
+            px: decimal(tag::MD_ENTRY_PX)?,
+            size: decimal(tag::MD_ENTRY_SIZE)?,
+            position,
+            update_action,
+            entry_id: entry.get(tag::MD_ENTRY_ID).map(String::from),
+            symbol: entry.get(tag::SYMBOL).map(String::from),
+        })
+    }
+
+    /// グループエントリに変換。
+    #[must_use]
+    pub fn to_entry(&self) -> GroupEntry {
+        let mut entry = GroupEntry::new();
+        entry.set(tag::MD_ENTRY_TYPE, &self.entry_type.to_fix().to_string());
+        if let Some(action) = self.update_action {
+            entry.set(tag::MD_UPDATE_ACTION, action.to_fix());
+        }
+        let optional = [
+            (tag::MD_ENTRY_PX, self.px.map(|d| d.to_string())),
+            (tag::MD_ENTRY_SIZE, self.size.map(|d| d.to_string())),
+            (
+                tag::MD_ENTRY_POSITION_NO,
+                self.position.map(|p| p.to_string()),
+            ),
+            (tag::MD_ENTRY_ID, self.entry_id.clone()),
+            (tag::SYMBOL, self.symbol.clone()),
+        ];
+        for (t, v) in optional {
+            if let Some(v) = v {
+                entry.set(t, &v);
+            }
+        }
+        entry
+    }
+}
+
+/// W / X メッセージの `NoMDEntries` を型付きエントリとして取得。
+///
+/// # Errors
+///
+/// メッセージ種別が W/X 以外の場合、エントリが不正な場合。
+pub fn md_entries(msg: &FixMessage) -> Result<Vec<MDEntry>, MarketDataError> {
+    if !matches!(
+        msg.msg_type.as_str(),
+        msg_type::SNAPSHOT_FULL_REFRESH | msg_type::INCREMENTAL_REFRESH
+    ) {
+        return Err(MarketDataError::WrongMsgType(msg.msg_type.clone()));
+    }
+    msg.groups(tag::NO_MD_ENTRIES)
+        .iter()
+        .map(MDEntry::from_entry)
+        .collect()
+}
+
+/// マーケットデータエラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketDataError {
+    /// メッセージタイプが不正。
+    WrongMsgType(String),
+    /// 必須フィールドが欠落。
+    MissingField(u32),
+    /// フィールド値が不正。
+    InvalidField(u32),
+}
+
+impl core::fmt::Display for MarketDataError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongMsgType(t) => write!(f, "MsgType {t} is not market data"),
+            Self::MissingField(tag) => write!(f, "Missing required field: tag {tag}"),
+            Self::InvalidField(tag) => write!(f, "Invalid value for tag {tag}"),
+        }
+    }
+}
+
+impl std::error::Error for MarketDataError {}
+
+/// 板の価格レベル。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookLevel {
+    /// 価格。
+    pub price: Decimal,
+    /// 数量。
+    pub size: Decimal,
+}
+
+/// 板 1 つあたりの上限。既定は無制限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BookLimits {
+    max_depth: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl BookLimits {
+    /// 無制限。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_depth: None,
+            max_bytes: None,
+        }
+    }
+
+    /// 片側の最大レベル数。
+    #[must_use]
+    pub const fn max_depth(mut self, levels: usize) -> Self {
+        self.max_depth = Some(levels);
+        self
+    }
+
+    /// 板 (買い + 売り) のレベルが使うメモリの上限 (バイト)。
+    /// 両側に等分する。
+    #[must_use]
+    pub const fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// 片側に保持する最大レベル数 (`max_depth` と `max_bytes` の小さい方)。
+    /// 無制限なら `None`。
+    #[must_use]
+    pub const fn levels_per_side(&self) -> Option<usize> {
+        let by_bytes = match self.max_bytes {
+            Some(bytes) => Some(bytes / 2 / core::mem::size_of::<BookLevel>()),
+            None => None,
+        };
+        match (self.max_depth, by_bytes) {
+            (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+            (Some(a), None) | (None, Some(a)) => Some(a),
+            (None, None) => None,
+        }
+    }
+}
+
+/// 上限による切り捨ての状況。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BookTruncation {
+    /// 直近のスナップショット以降にレベルを捨てたか。`true` の間、
+    /// 上限より深い気配は欠けている。
+    pub truncated: bool,
+    /// 捨てたレベルの累計。
+    pub evicted_levels: u64,
+}
+
+/// [`MDEntry`] 列から価格レベル板を組み立てる。
+///
+/// 買いは価格降順、売りは価格昇順で保持する。価格を持たないエントリと
+/// 約定 (Trade) などの板以外のエントリは無視する。
+#[derive(Debug, Clone, Default)]
+pub struct OrderBookAssembler {
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+    limits: BookLimits,
+    truncation: BookTruncation,
+}
+
+impl OrderBookAssembler {
+    /// 空の板を作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 上限付きの空の板を作成。
+    #[must_use]
+    pub fn with_limits(limits: BookLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// 設定された上限。
+    #[must_use]
+    pub const fn limits(&self) -> BookLimits {
+        self.limits
+    }
+
+    /// 上限による切り捨ての状況。
+    #[must_use]
+    pub const fn truncation(&self) -> BookTruncation {
+        self.truncation
+    }
+
+    /// スナップショットで板を置き換える。
+    pub fn apply_snapshot(&mut self, entries: &[MDEntry]) {
+        self.bids.clear();
+        self.asks.clear();
+        self.truncation.truncated = false;
+        for e in entries {
+            self.upsert(e);
+        }
+    }
+
+    /// 差分更新を適用。更新種別の無いエントリは New として扱う。
+    pub fn apply_incremental(&mut self, entries: &[MDEntry]) {
+        for e in entries {
+            match e.update_action.unwrap_or(MDUpdateAction::New) {
+                MDUpdateAction::New | MDUpdateAction::Change => self.upsert(e),
+                MDUpdateAction::Delete => self.delete(e),
+            }
+        }
+    }
+
+    /// W / X メッセージを適用。
+    ///
+    /// # Errors
+    ///
+    /// [`md_entries`] のエラー。
+    pub fn apply(&mut self, msg: &FixMessage) -> Result<(), MarketDataError> {
+        let entries = md_entries(msg)?;
+        if msg.msg_type == msg_type::SNAPSHOT_FULL_REFRESH {
+            self.apply_snapshot(&entries);
+        } else {
+            self.apply_incremental(&entries);
+        }
+        Ok(())
+    }
+
+    /// 買い板 (価格降順)。
+    #[must_use]
+    pub fn bids(&self) -> &[BookLevel] {
+        &self.bids
+    }
+
+    /// 売り板 (価格昇順)。
+    #[must_use]
+    pub fn asks(&self) -> &[BookLevel] {
+        &self.asks
+    }
+
+    /// 最良買気配。
+    #[must_use]
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids.first().copied()
+    }
+
+    /// 最良売気配。
+    #[must_use]
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks.first().copied()
+    }
+
+    /// 板を空にする。
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.truncation.truncated = false;
+    }
+
+    const fn side_mut(&mut self, entry_type: MDEntryType) -> Option<(&mut Vec<BookLevel>, bool)> {
+        match entry_type {
+            MDEntryType::Bid => Some((&mut self.bids, true)),
+            MDEntryType::Offer => Some((&mut self.asks, false)),
+            _ => None,
+        }
+    }
+
+    fn upsert(&mut self, e: &MDEntry) {
+        let cap = self.limits.levels_per_side();
+        let (Some(price), Some((levels, descending))) = (e.px, self.side_mut(e.entry_type)) else {
+            return;
+        };
+        let size = e.size.unwrap_or(Decimal::new(0, 0));
+        let pos = levels.binary_search_by(|l| {
+            if descending {
+                price.cmp(&l.price)
+            } else {
+                l.price.cmp(&price)
+            }
+        });
+        let evicted = match pos {
+            Ok(i) => {
+                levels[i].size = size;
+                0
+            }
+            // 上限より深いレベルは挿入せずに捨てる。
+            Err(i) if cap.is_some_and(|cap| i >= cap) => 1,
+            Err(i) => {
+                levels.insert(i, BookLevel { price, size });
+                let excess = cap.map_or(0, |cap| levels.len().saturating_sub(cap));
+                levels.truncate(levels.len() - excess);
+                excess
+            }
+        };
+        if evicted > 0 {
+            self.truncation.truncated = true;
+            self.truncation.evicted_levels += evicted as u64;
+        }
+    }
+
+    fn delete(&mut self, e: &MDEntry) {
+        let (Some(price), Some((levels, _))) = (e.px, self.side_mut(e.entry_type)) else {
+            return;
+        };
+        levels.retain(|l| l.price != price);
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::parser;
+    use crate::repeating_group::RepeatingGroup;
+    use crate::version::BeginString;
+
+    fn entry(t: MDEntryType, px: &str, size: &str) -> MDEntry {
+        MDEntry {
+            entry_type: t,
+            px: Decimal::parse(px),
+            size: Decimal::parse(size),
+            position: None,
+            update_action: None,
+            entry_id: None,
+            symbol: None,
+        }
+    }
+
+    fn action(a: MDUpdateAction, e: MDEntry) -> MDEntry {
+        MDEntry {
+            update_action: Some(a),
+            ..e
+        }
+    }
+
+    fn message(msg_type: &str, entries: &[MDEntry]) -> FixMessage {
+        let mut group = RepeatingGroup::new(tag::NO_MD_ENTRIES, tag::MD_ENTRY_TYPE);
+        for e in entries {
+            group.add_entry(e.to_entry());
+        }
+        let bytes = FixBuilder::new(BeginString::Fix44, msg_type)
+            .field(tag::MD_REQ_ID, "R1")
+            .group(&group)
+            .build();
+        parser::parse(&bytes).unwrap()
+    }
+
+    #[test]
+    fn entries_roundtrip_through_wire() {
+        let mut bid = entry(MDEntryType::Bid, "100.5", "3");
+        bid.position = Some(1);
+        bid.entry_id = Some("E1".into());
+        let trade = entry(MDEntryType::Trade, "100.6", "1");
+        let msg = message(
+            msg_type::SNAPSHOT_FULL_REFRESH,
+            &[bid.clone(), trade.clone()],
+        );
+        assert_eq!(md_entries(&msg).unwrap(), vec![bid, trade]);
+    }
+
+    #[test]
+    fn snapshot_builds_sorted_book() {
+        let mut book = OrderBookAssembler::new();
+        let msg = message(
+            msg_type::SNAPSHOT_FULL_REFRESH,
+            &[
+                entry(MDEntryType::Bid, "99", "1"),
+                entry(MDEntryType::Offer, "101.5", "2"),
+                entry(MDEntryType::Bid, "100", "5"),
+                entry(MDEntryType::Offer, "101", "4"),
+                entry(MDEntryType::Trade, "100.5", "1"),
+            ],
+        );
+        book.apply(&msg).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, Decimal::new(100, 0));
+        assert_eq!(book.best_ask().unwrap().size, Decimal::new(4, 0));
+        assert_eq!(book.bids().len(), 2);
+        assert_eq!(book.asks()[1].price, Decimal::new(1015, 1));
+    }
+
+    #[test]
+    fn incremental_new_change_delete() {
+        let mut book = OrderBookAssembler::new();
+        book.apply_snapshot(&[
+            entry(MDEntryType::Bid, "100", "5"),
+            entry(MDEntryType::Bid, "99", "1"),
+        ]);
+        let msg = message(
+            msg_type::INCREMENTAL_REFRESH,
+            &[
+                action(
+                    MDUpdateAction::Change,
+                    entry(MDEntryType::Bid, "100.0", "7"),
+                ),
+                action(MDUpdateAction::Delete, entry(MDEntryType::Bid, "99", "0")),
+                action(MDUpdateAction::New, entry(MDEntryType::Bid, "100.25", "2")),
+            ],
+        );
+        book.apply(&msg).unwrap();
+        let levels: Vec<(String, String)> = book
+            .bids()
+            .iter()
+            .map(|l| (l.price.to_string(), l.size.to_string()))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                ("100.25".to_string(), "2".to_string()),
+                ("100".to_string(), "7".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_replaces_book() {
+        let mut book = OrderBookAssembler::new();
+        book.apply_snapshot(&[entry(MDEntryType::Offer, "101", "1")]);
+        book.apply_snapshot(&[entry(MDEntryType::Bid, "100", "1")]);
+        assert!(book.asks().is_empty());
+        assert_eq!(book.bids().len(), 1);
+    }
+
+    #[test]
+    fn wrong_msg_type() {
+        let msg = FixMessage::new("FIX.4.4", "D");
+        assert_eq!(
+            md_entries(&msg),
+            Err(MarketDataError::WrongMsgType("D".into()))
+        );
+        assert!(OrderBookAssembler::new().apply(&msg).is_err());
+    }
+
+    #[test]
+    fn invalid_entry_fields() {
+        let mut g = GroupEntry::new();
+        assert_eq!(
+            MDEntry::from_entry(&g),
+            Err(MarketDataError::MissingField(tag::MD_ENTRY_TYPE))
+        );
+        g.set(tag::MD_ENTRY_TYPE, "0").set(tag::MD_ENTRY_PX, "1e5");
+        assert_eq!(
+            MDEntry::from_entry(&g),
+            Err(MarketDataError::InvalidField(tag::MD_ENTRY_PX))
+        );
+        g.set(tag::MD_ENTRY_PX, "1").set(tag::MD_UPDATE_ACTION, "9");
+        assert_eq!(
+            MDEntry::from_entry(&g),
+            Err(MarketDataError::InvalidField(tag::MD_UPDATE_ACTION))
+        );
+    }
+
+    #[test]
+    fn depth_limit_evicts_far_levels() {
+        let mut book = OrderBookAssembler::with_limits(BookLimits::new().max_depth(2));
+        book.apply_snapshot(&[
+            entry(MDEntryType::Bid, "100", "1"),
+            entry(MDEntryType::Bid, "99", "1"),
+            entry(MDEntryType::Bid, "98", "1"),
+            entry(MDEntryType::Offer, "101", "1"),
+        ]);
+        assert_eq!(book.bids().len(), 2);
+        assert_eq!(book.bids()[1].price, Decimal::new(99, 0));
+        assert_eq!(
+            book.truncation(),
+            BookTruncation {
+                truncated: true,
+                evicted_levels: 1
+            }
+        );
+
+        // 最良気配の更新は深い側を押し出す
+        book.apply_incremental(&[action(
+            MDUpdateAction::New,
+            entry(MDEntryType::Bid, "100.5", "2"),
+        )]);
+        let prices: Vec<String> = book.bids().iter().map(|l| l.price.to_string()).collect();
+        assert_eq!(prices, ["100.5", "100"]);
+        assert_eq!(book.truncation().evicted_levels, 2);
+
+        // 収まるスナップショットで truncated は解除、累計は保持
+        book.apply_snapshot(&[entry(MDEntryType::Bid, "100", "1")]);
+        assert!(!book.truncation().truncated);
+        assert_eq!(book.truncation().evicted_levels, 2);
+    }
+
+    #[test]
+    fn byte_limit_caps_levels_per_side() {
+        let level = core::mem::size_of::<BookLevel>();
+        let limits = BookLimits::new().max_bytes(level * 6);
+        assert_eq!(limits.levels_per_side(), Some(3));
+        assert_eq!(limits.max_depth(2).levels_per_side(), Some(2));
+        assert_eq!(BookLimits::new().levels_per_side(), None);
+
+        let mut book = OrderBookAssembler::with_limits(limits);
+        let asks: Vec<MDEntry> = (0..100)
+            .map(|i| entry(MDEntryType::Offer, &format!("{}", 200 - i), "1"))
+            .collect();
+        book.apply_snapshot(&asks);
+        let prices: Vec<String> = book.asks().iter().map(|l| l.price.to_string()).collect();
+        assert_eq!(prices, ["101", "102", "103"]);
+        assert_eq!(book.truncation().evicted_levels, 97);
+        assert!(book.bids().is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+//! Order Mass Status
+//!
+//! 再接続後に未約定注文を洗い出すための `OrderMassStatusRequest` (AF) と、
+//! それに応えて届く一連の `ExecutionReport` (`ExecType` I) の突き合わせ。
+//!
+//! 応答は要求の `MassStatusReqID` (584) を持ち、`TotNumReports` (911) で
+//! 件数を、最後の 1 件は `LastRptRequested` (912) Y で終わりを示す。
+//! [`MassStatusCollector::expect`] で要求 ID を登録し、受信した
+//! `ExecutionReport` を [`MassStatusCollector::record`] に渡すと、終わりが
+//! 来た時点で [`MassStatusProgress::Complete`] として注文の一覧を返す。
+//! 該当注文が無い場合、相手方は `TotNumReports` 0 の 1 件だけを返すため、
+//! その報告は一覧に含めない。
+
+use std::collections::BTreeMap;
+
+use alice_ledger::Side;
+
+use crate::builder::FixBuilder;
+use crate::convert::{alice_side_to_fix, fix_side_to_alice};
+use crate::execution_report::ExecutionReport;
+use crate::message::FixMessage;
+use crate::order::{OrderMsgError, DEFAULT_BEGIN_STRING};
+use crate::tag;
+use crate::version::BeginString;
+
+/// `OrderMassStatusRequest` の `MsgType`。
+pub const ORDER_MASS_STATUS_REQUEST: &str = "AF";
+
+/// 照会範囲 (`MassStatusReqType`, tag 585)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MassStatusReqType {
+    /// 銘柄を指定 (1)。
+    Security,
+    /// 原資産を指定 (2)。
+    UnderlyingSecurity,
+    /// 商品区分を指定 (3)。
+    Product,
+    /// CFI コードを指定 (4)。
+    CfiCode,
+    /// 証券種別を指定 (5)。
+    SecurityType,
+    /// 立会を指定 (6)。
+    TradingSession,
+    /// すべての注文 (7)。
+    AllOrders,
+    /// 当事者を指定 (8)。
+    PartyId,
+}
+
+impl MassStatusReqType {
+    /// FIX 値から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        Some(match s {
+            "1" => Self::Security,
+            "2" => Self::UnderlyingSecurity,
+            "3" => Self::Product,
+            "4" => Self::CfiCode,
+            "5" => Self::SecurityType,
+            "6" => Self::TradingSession,
+            "7" => Self::AllOrders,
+            "8" => Self::PartyId,
+            _ => return None,
+        })
+    }
+
+    /// FIX 値に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::Security => "1",
+            Self::UnderlyingSecurity => "2",
+            Self::Product => "3",
+            Self::CfiCode => "4",
+            Self::SecurityType => "5",
+            Self::TradingSession => "6",
+            Self::AllOrders => "7",
+            Self::PartyId => "8",
+        }
+    }
+}
+
+/// Order Mass Status Request (35=AF)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderMassStatusRequest {
+    /// 要求 ID (tag 584)。応答の `ExecutionReport` に返される。
+    pub mass_status_req_id: String,
+    /// 照会範囲 (tag 585)。
+    pub req_type: MassStatusReqType,
+    /// シンボル (tag 55)。
+    pub symbol: Option<String>,
+    /// サイド (tag 54)。指定すると片側のみ。
+    pub side: Option<Side>,
+}
+
+impl OrderMassStatusRequest {
+    /// すべての注文を照会する要求を作成。
+    #[must_use]
+    pub fn all_orders(mass_status_req_id: &str) -> Self {
+        Self {
+            mass_status_req_id: mass_status_req_id.to_string(),
+            req_type: MassStatusReqType::AllOrders,
+            symbol: None,
+            side: None,
+        }
+    }
+
+    /// `symbol` の注文を照会する要求を作成。
+    #[must_use]
+    pub fn for_security(mass_status_req_id: &str, symbol: &str) -> Self {
+        Self {
+            mass_status_req_id: mass_status_req_id.to_string(),
+            req_type: MassStatusReqType::Security,
+            symbol: Some(symbol.to_string()),
+            side: None,
+        }
+    }
+
+    /// `begin_string` を指定してビルダーに変換 (ヘッダは呼び出し側で追加)。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, ORDER_MASS_STATUS_REQUEST);
+        b.field(tag::MASS_STATUS_REQ_ID, &self.mass_status_req_id)
+            .field(tag::MASS_STATUS_REQ_TYPE, self.req_type.to_fix())
+            .field_opt(tag::SYMBOL, self.symbol.as_deref())
+            .field_opt(tag::SIDE, self.side.map(alice_side_to_fix));
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for OrderMassStatusRequest {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        if msg.msg_type != ORDER_MASS_STATUS_REQUEST {
+            return Err(OrderMsgError::WrongMsgType {
+                expected: ORDER_MASS_STATUS_REQUEST,
+                actual: msg.msg_type.clone(),
+            });
+        }
+        let invalid = |t: u32, v: &str| OrderMsgError::InvalidField {
+            tag: t,
+            value: v.to_string(),
+        };
+        let req_type = msg.require(tag::MASS_STATUS_REQ_TYPE)?;
+        let side = msg
+            .get(tag::SIDE)
+            .map(|v| fix_side_to_alice(v).ok_or_else(|| invalid(tag::SIDE, v)))
+            .transpose()?;
+        Ok(Self {
+            mass_status_req_id: msg.require(tag::MASS_STATUS_REQ_ID)?.to_string(),
+            req_type: MassStatusReqType::from_fix(req_type)
+                .ok_or_else(|| invalid(tag::MASS_STATUS_REQ_TYPE, req_type))?,
+            symbol: msg.get(tag::SYMBOL).map(String::from),
+            side,
+        })
+    }
+}
+
+impl From<&OrderMassStatusRequest> for FixBuilder {
+    fn from(msg: &OrderMassStatusRequest) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+/// [`MassStatusCollector::record`] の結果。
+#[derive(Debug, Clone)]
+pub enum MassStatusProgress {
+    /// 登録済みの要求への応答ではない。
+    Unrelated,
+    /// 応答の途中。
+    Pending {
+        /// 要求 ID。
+        mass_status_req_id: String,
+        /// これまでに受けた注文の件数。
+        received: usize,
+        /// `TotNumReports` (届いていれば)。
+        expected: Option<u32>,
+    },
+    /// 応答が揃った。要求は登録から外れる。
+    Complete {
+        /// 要求 ID。
+        mass_status_req_id: String,
+        /// 受信順の状態報告。該当注文が無ければ空。
+        reports: Vec<ExecutionReport>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Batch {
+    reports: Vec<ExecutionReport>,
+    expected: Option<u32>,
+}
+
+/// 送信済みの `OrderMassStatusRequest` ごとに応答を集める。
+#[derive(Debug, Default)]
+pub struct MassStatusCollector {
+    pending: BTreeMap<String, Batch>,
+}
+
+impl MassStatusCollector {
+    /// 空のコレクタを作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `mass_status_req_id` への応答を待つ。同じ ID の途中経過は捨てる。
+    pub fn expect(&mut self, mass_status_req_id: &str) {
+        self.pending
+            .insert(mass_status_req_id.to_string(), Batch::default());
+    }
+
+    /// 応答を待っている要求 ID (昇順)。
+    pub fn outstanding(&self) -> impl Iterator<Item = &str> {
+        self.pending.keys().map(String::as_str)
+    }
+
+    /// 要求を取り下げ、それまでに受けた報告を返す (再接続で応答が
+    /// 途切れた場合など)。
+    pub fn cancel(&mut self, mass_status_req_id: &str) -> Option<Vec<ExecutionReport>> {
+        self.pending
+            .remove(mass_status_req_id)
+            .map(|batch| batch.reports)
+    }
+
+    /// 受信した `ExecutionReport` を要求ごとに振り分ける。
+    ///
+    /// `LastRptRequested` Y、`TotNumReports` 件に到達、または
+    /// `TotNumReports` 0 (該当なし) で完了する。
+    pub fn record(&mut self, report: &ExecutionReport) -> MassStatusProgress {
+        let Some(id) = report.mass_status_req_id.as_deref() else {
+            return MassStatusProgress::Unrelated;
+        };
+        let Some(batch) = self.pending.get_mut(id) else {
+            return MassStatusProgress::Unrelated;
+        };
+        if report.tot_num_reports.is_some() {
+            batch.expected = report.tot_num_reports;
+        }
+        if batch.expected != Some(0) {
+            batch.reports.push(report.clone());
+        }
+        let done = report.last_rpt_requested == Some(true)
+            || batch
+                .expected
+                .is_some_and(|n| batch.reports.len() >= n as usize);
+        if done {
+            let batch = self.pending.remove(id).unwrap_or_default();
+            return MassStatusProgress::Complete {
+                mass_status_req_id: id.to_string(),
+                reports: batch.reports,
+            };
+        }
+        MassStatusProgress::Pending {
+            mass_status_req_id: id.to_string(),
+            received: batch.reports.len(),
+            expected: batch.expected,
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn status(req_id: &str, cl_ord_id: &str, total: u32, last: bool) -> ExecutionReport {
+        let mut msg = FixMessage::new("FIX.4.4", "8");
+        msg.set(tag::ORDER_ID, "O1")
+            .set(tag::CL_ORD_ID, cl_ord_id)
+            .set(tag::EXEC_ID, "S1")
+            .set(tag::EXEC_TYPE, "I")
+            .set(tag::ORD_STATUS, "0")
+            .set(tag::SYMBOL, "BTCUSD")
+            .set(tag::SIDE, "1")
+            .set(tag::MASS_STATUS_REQ_ID, req_id)
+            .set_value(tag::TOT_NUM_REPORTS, u64::from(total));
+        if last {
+            msg.set(tag::LAST_RPT_REQUESTED, "Y");
+        }
+        ExecutionReport::from_message(&msg).unwrap()
+    }
+
+    #[test]
+    fn request_roundtrip() {
+        let mut req = OrderMassStatusRequest::for_security("MS1", "BTCUSD");
+        req.side = Some(Side::Ask);
+        let builder = FixBuilder::from(&req);
+        let msg = parser::parse(&builder.build()).unwrap();
+        assert_eq!(msg.msg_type, "AF");
+        assert_eq!(msg.get(tag::MASS_STATUS_REQ_TYPE), Some("1"));
+        assert_eq!(msg.get(tag::SIDE), Some("2"));
+        assert_eq!(OrderMassStatusRequest::try_from(&msg).unwrap(), req);
+
+        let all =
+            parser::parse(&FixBuilder::from(&OrderMassStatusRequest::all_orders("MS2")).build())
+                .unwrap();
+        assert_eq!(all.get(tag::MASS_STATUS_REQ_TYPE), Some("7"));
+        assert!(!all.contains(tag::SYMBOL));
+
+        let mut bad = msg;
+        bad.set(tag::MASS_STATUS_REQ_TYPE, "99");
+        assert!(matches!(
+            OrderMassStatusRequest::try_from(&bad),
+            Err(OrderMsgError::InvalidField { tag: 585, .. })
+        ));
+    }
+
+    #[test]
+    fn collects_reports_until_last() {
+        let mut collector = MassStatusCollector::new();
+        collector.expect("MS1");
+        collector.expect("MS2");
+        assert!(matches!(
+            collector.record(&status("OTHER", "C9", 1, true)),
+            MassStatusProgress::Unrelated
+        ));
+        assert!(matches!(
+            collector.record(&status("MS1", "C1", 2, false)),
+            MassStatusProgress::Pending {
+                received: 1,
+                expected: Some(2),
+                ..
+            }
+        ));
+        let MassStatusProgress::Complete { reports, .. } =
+            collector.record(&status("MS1", "C2", 2, true))
+        else {
+            panic!("expected completion");
+        };
+        let ids: Vec<_> = reports.iter().map(|r| r.cl_ord_id.as_str()).collect();
+        assert_eq!(ids, ["C1", "C2"]);
+        assert_eq!(collector.outstanding().collect::<Vec<_>>(), ["MS2"]);
+
+        // No open orders: a single placeholder with TotNumReports 0.
+        let MassStatusProgress::Complete { reports, .. } =
+            collector.record(&status("MS2", "NONE", 0, true))
+        else {
+            panic!("expected completion");
+        };
+        assert!(reports.is_empty());
+        assert_eq!(collector.outstanding().count(), 0);
+
+        collector.expect("MS3");
+        collector.record(&status("MS3", "C1", 3, false));
+        assert_eq!(collector.cancel("MS3").map(|r| r.len()), Some(1));
+        assert!(collector.cancel("MS3").is_none());
+    }
+}
@@ -0,0 +1,422 @@
+//! Market Data Subscription State
+//!
+//! `MarketDataRequest` (V) の購読状態を保持し、再接続時に有効な購読を
+//! 新しい `MDReqID` で自動的に再発行する。再接続後の最初のスナップショット
+//! (W) は古い板と突き合わせ、[`MdEvent::BookResynced`] を発行する。
+//!
+//! 購読ごとに [`OrderBookAssembler`] を持つ。切断中の板は stale とし、
+//! 再同期までの差分更新 (X) は適用しない。各板の上限は
+//! [`MdSubscriptionManager::book_limits`] で設定する。
+
+use crate::builder::FixBuilder;
+use crate::instrument::{append_related_symbols, msg_type, Instrument};
+use crate::market_data::{
+    md_entries, msg_type as md_msg_type, BookLevel, BookLimits, MDEntryType, MarketDataError,
+    OrderBookAssembler,
+};
+use crate::message::FixMessage;
+use crate::repeating_group::{GroupEntry, RepeatingGroup};
+use crate::tag;
+use crate::version::BeginString;
+
+/// 購読内容 (1 銘柄)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdSubscription {
+    /// 対象銘柄。
+    pub instrument: Instrument,
+    /// 要求するエントリ種別 (`NoMDEntryTypes`, tag 267)。
+    pub entry_types: Vec<MDEntryType>,
+    /// 板の深さ (`MarketDepth`, tag 264)。0 = 全板。
+    pub market_depth: u32,
+}
+
+impl MdSubscription {
+    /// 買気配・売気配の全板購読を作成。
+    #[must_use]
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            instrument: Instrument::new(symbol),
+            entry_types: vec![MDEntryType::Bid, MDEntryType::Offer],
+            market_depth: 0,
+        }
+    }
+
+    /// エントリ種別を設定。
+    #[must_use]
+    pub fn with_entry_types(mut self, entry_types: &[MDEntryType]) -> Self {
+        self.entry_types = entry_types.to_vec();
+        self
+    }
+
+    /// 板の深さを設定。
+    #[must_use]
+    pub const fn with_depth(mut self, market_depth: u32) -> Self {
+        self.market_depth = market_depth;
+        self
+    }
+
+    /// 購読開始 (snapshot + updates) の `MarketDataRequest` を構築。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>, md_req_id: &str) -> FixBuilder {
+        self.request(begin_string, md_req_id, "1")
+    }
+
+    fn request(
+        &self,
+        begin_string: impl Into<BeginString>,
+        md_req_id: &str,
+        request_type: &str,
+    ) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, msg_type::MARKET_DATA_REQUEST);
+        b.field(tag::MD_REQ_ID, md_req_id)
+            .field(tag::SUBSCRIPTION_REQUEST_TYPE, request_type)
+            .field_u64(tag::MARKET_DEPTH, u64::from(self.market_depth))
+            .field(tag::MD_UPDATE_TYPE, "1");
+        let mut types = RepeatingGroup::new(tag::NO_MD_ENTRY_TYPES, tag::MD_ENTRY_TYPE);
+        for t in &self.entry_types {
+            let mut e = GroupEntry::new();
+            e.set(tag::MD_ENTRY_TYPE, &t.to_fix().to_string());
+            types.add_entry(e);
+        }
+        b.group(&types);
+        append_related_symbols(&mut b, core::slice::from_ref(&self.instrument));
+        b
+    }
+}
+
+/// 購読マネージャのイベント。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MdEvent {
+    /// 板を更新した。
+    BookUpdated {
+        /// 購読の `MDReqID`。
+        md_req_id: String,
+    },
+    /// 再接続後の最初のスナップショットで板を再同期した。
+    BookResynced {
+        /// 購読の `MDReqID` (再発行後)。
+        md_req_id: String,
+        /// 銘柄。
+        symbol: String,
+        /// 古い板と比べて変化した価格レベル数 (追加・削除・数量変更)。
+        changed_levels: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Active {
+    md_req_id: String,
+    subscription: MdSubscription,
+    book: OrderBookAssembler,
+    stale: bool,
+}
+
+/// 購読状態を保持し、再接続時に再購読する。
+#[derive(Debug, Clone)]
+pub struct MdSubscriptionManager {
+    prefix: String,
+    next_id: u64,
+    active: Vec<Active>,
+    limits: BookLimits,
+}
+
+impl MdSubscriptionManager {
+    /// `MDReqID` の接頭辞を指定して作成 (`{prefix}-{n}`)。
+    #[must_use]
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            next_id: 1,
+            active: Vec::new(),
+            limits: BookLimits::new(),
+        }
+    }
+
+    /// 以後に登録する購読の板 (銘柄ごと) に適用する上限を設定。
+    #[must_use]
+    pub const fn book_limits(mut self, limits: BookLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn fresh_id(&mut self) -> String {
+        let id = format!("{}-{}", self.prefix, self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// 購読を登録し、割り当てた `MDReqID` と送信用リクエストを返す。
+    pub fn subscribe(
+        &mut self,
+        subscription: MdSubscription,
+        begin_string: impl Into<BeginString>,
+    ) -> (String, FixBuilder) {
+        let md_req_id = self.fresh_id();
+        let builder = subscription.to_builder(begin_string, &md_req_id);
+        self.active.push(Active {
+            md_req_id: md_req_id.clone(),
+            subscription,
+            book: OrderBookAssembler::with_limits(self.limits),
+            stale: false,
+        });
+        (md_req_id, builder)
+    }
+
+    /// 購読を解除し、解除リクエスト (`SubscriptionRequestType`=2) を返す。
+    ///
+    /// 未知の `MDReqID` は `None`。
+    pub fn unsubscribe(
+        &mut self,
+        md_req_id: &str,
+        begin_string: impl Into<BeginString>,
+    ) -> Option<FixBuilder> {
+        let pos = self.active.iter().position(|a| a.md_req_id == md_req_id)?;
+        let removed = self.active.remove(pos);
+        Some(removed.subscription.request(begin_string, md_req_id, "2"))
+    }
+
+    /// 切断を記録。全購読の板を stale にする。
+    pub fn on_disconnect(&mut self) {
+        for a in &mut self.active {
+            a.stale = true;
+        }
+    }
+
+    /// 再接続時に全購読を新しい `MDReqID` で再発行する。
+    ///
+    /// 板は stale になり、次のスナップショットで再同期される。
+    pub fn on_reconnect(&mut self, begin_string: impl Into<BeginString>) -> Vec<FixBuilder> {
+        let begin_string = begin_string.into();
+        self.on_disconnect();
+        let mut requests = Vec::with_capacity(self.active.len());
+        for i in 0..self.active.len() {
+            let id = self.fresh_id();
+            let a = &mut self.active[i];
+            a.md_req_id = id;
+            requests.push(a.subscription.to_builder(begin_string, &a.md_req_id));
+        }
+        requests
+    }
+
+    /// W / X メッセージを該当購読の板に適用。
+    ///
+    /// 未知の `MDReqID` は `Ok(None)`。stale な板への差分更新は無視する
+    /// (`Ok(None)`)。
+    ///
+    /// # Errors
+    ///
+    /// `MDReqID` が欠落している場合、[`md_entries`] のエラー。
+    pub fn on_market_data(&mut self, msg: &FixMessage) -> Result<Option<MdEvent>, MarketDataError> {
+        let entries = md_entries(msg)?;
+        let md_req_id = msg
+            .get(tag::MD_REQ_ID)
+            .ok_or(MarketDataError::MissingField(tag::MD_REQ_ID))?;
+        let Some(a) = self.active.iter_mut().find(|a| a.md_req_id == md_req_id) else {
+            return Ok(None);
+        };
+        let snapshot = msg.msg_type == md_msg_type::SNAPSHOT_FULL_REFRESH;
+        if !a.stale {
+            if snapshot {
+                a.book.apply_snapshot(&entries);
+            } else {
+                a.book.apply_incremental(&entries);
+            }
+            return Ok(Some(MdEvent::BookUpdated {
+                md_req_id: a.md_req_id.clone(),
+            }));
+        }
+        if !snapshot {
+            return Ok(None);
+        }
+        let old = a.book.clone();
+        a.book.apply_snapshot(&entries);
+        a.stale = false;
+        let changed_levels =
+            level_diff(old.bids(), a.book.bids()) + level_diff(old.asks(), a.book.asks());
+        Ok(Some(MdEvent::BookResynced {
+            md_req_id: a.md_req_id.clone(),
+            symbol: a.subscription.instrument.symbol.clone(),
+            changed_levels,
+        }))
+    }
+
+    /// 有効な購読 (`MDReqID`, 購読内容) を登録順に列挙。
+    pub fn active(&self) -> impl Iterator<Item = (&str, &MdSubscription)> {
+        self.active
+            .iter()
+            .map(|a| (a.md_req_id.as_str(), &a.subscription))
+    }
+
+    /// 購読の板。
+    #[must_use]
+    pub fn book(&self, md_req_id: &str) -> Option<&OrderBookAssembler> {
+        self.find(md_req_id).map(|a| &a.book)
+    }
+
+    /// 購読の板が再同期待ちか。
+    #[must_use]
+    pub fn is_stale(&self, md_req_id: &str) -> Option<bool> {
+        self.find(md_req_id).map(|a| a.stale)
+    }
+
+    fn find(&self, md_req_id: &str) -> Option<&Active> {
+        self.active.iter().find(|a| a.md_req_id == md_req_id)
+    }
+}
+
+/// 片側の板で変化した価格レベル数。
+fn level_diff(old: &[BookLevel], new: &[BookLevel]) -> usize {
+    let removed = old.iter().filter(|l| !new.contains(l)).count();
+    let added = new
+        .iter()
+        .filter(|l| !old.iter().any(|o| o.price == l.price))
+        .count();
+    removed + added
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::MDEntry;
+    use crate::parser;
+    use crate::value::Decimal;
+
+    fn level(entry_type: MDEntryType, px: i64, size: i64) -> MDEntry {
+        MDEntry {
+            entry_type,
+            px: Some(Decimal::new(px, 0)),
+            size: Some(Decimal::new(size, 0)),
+            position: None,
+            update_action: None,
+            entry_id: None,
+            symbol: None,
+        }
+    }
+
+    fn md(msg_type: &str, md_req_id: &str, entries: &[MDEntry]) -> FixMessage {
+        let mut msg = FixMessage::new("FIX.4.4", msg_type);
+        msg.set(tag::MD_REQ_ID, md_req_id)
+            .set(tag::NO_MD_ENTRIES, &entries.len().to_string());
+        msg.groups.insert(
+            tag::NO_MD_ENTRIES,
+            entries.iter().map(MDEntry::to_entry).collect(),
+        );
+        msg
+    }
+
+    #[test]
+    fn subscribe_builds_market_data_request() {
+        let mut mgr = MdSubscriptionManager::new("MD");
+        let (id, b) = mgr.subscribe(
+            MdSubscription::new("BTCUSD").with_depth(5),
+            BeginString::Fix44,
+        );
+        assert_eq!(id, "MD-1");
+        let msg = parser::parse(&b.build()).unwrap();
+        assert_eq!(msg.msg_type, "V");
+        assert_eq!(msg.get(tag::MD_REQ_ID), Some("MD-1"));
+        assert_eq!(msg.get(tag::SUBSCRIPTION_REQUEST_TYPE), Some("1"));
+        assert_eq!(msg.get(tag::MARKET_DEPTH), Some("5"));
+        assert_eq!(msg.get(tag::NO_MD_ENTRY_TYPES), Some("2"));
+        assert_eq!(
+            msg.groups(tag::NO_RELATED_SYM)[0].get(tag::SYMBOL),
+            Some("BTCUSD")
+        );
+    }
+
+    #[test]
+    fn reconnect_reissues_with_fresh_ids() {
+        let mut mgr = MdSubscriptionManager::new("MD");
+        let (a, _) = mgr.subscribe(MdSubscription::new("BTCUSD"), BeginString::Fix44);
+        let (b, _) = mgr.subscribe(MdSubscription::new("ETHUSD"), BeginString::Fix44);
+        let requests = mgr.on_reconnect(BeginString::Fix44);
+        assert_eq!(requests.len(), 2);
+        let ids: Vec<&str> = mgr.active().map(|(id, _)| id).collect();
+        assert_eq!(ids, ["MD-3", "MD-4"]);
+        assert!(!ids.contains(&a.as_str()) && !ids.contains(&b.as_str()));
+        let first = parser::parse(&requests[0].build()).unwrap();
+        assert_eq!(first.get(tag::MD_REQ_ID), Some("MD-3"));
+        assert_eq!(mgr.is_stale("MD-3"), Some(true));
+    }
+
+    #[test]
+    fn first_snapshot_after_reconnect_resyncs() {
+        let mut mgr = MdSubscriptionManager::new("MD");
+        let (id, _) = mgr.subscribe(MdSubscription::new("BTCUSD"), BeginString::Fix44);
+        let snap = [
+            level(MDEntryType::Bid, 100, 1),
+            level(MDEntryType::Offer, 101, 2),
+        ];
+        assert_eq!(
+            mgr.on_market_data(&md("W", &id, &snap)).unwrap(),
+            Some(MdEvent::BookUpdated { md_req_id: id })
+        );
+
+        mgr.on_reconnect(BeginString::Fix44);
+        let (new_id, _) = mgr
+            .active()
+            .next()
+            .map(|(i, s)| (i.to_string(), s.clone()))
+            .unwrap();
+        // 再同期前の差分更新は適用しない
+        let inc = [level(MDEntryType::Bid, 99, 5)];
+        assert_eq!(mgr.on_market_data(&md("X", &new_id, &inc)).unwrap(), None);
+        assert_eq!(mgr.book(&new_id).unwrap().bids().len(), 1);
+
+        // 買い 100 の数量変更 + 売り 102 追加・101 削除
+        let snap = [
+            level(MDEntryType::Bid, 100, 3),
+            level(MDEntryType::Offer, 102, 2),
+        ];
+        assert_eq!(
+            mgr.on_market_data(&md("W", &new_id, &snap)).unwrap(),
+            Some(MdEvent::BookResynced {
+                md_req_id: new_id.clone(),
+                symbol: "BTCUSD".into(),
+                changed_levels: 3,
+            })
+        );
+        assert_eq!(mgr.is_stale(&new_id), Some(false));
+        assert_eq!(
+            mgr.book(&new_id).unwrap().best_ask().unwrap().price,
+            Decimal::new(102, 0)
+        );
+    }
+
+    #[test]
+    fn unsubscribe_and_unknown_ids() {
+        let mut mgr = MdSubscriptionManager::new("MD");
+        let (id, _) = mgr.subscribe(MdSubscription::new("BTCUSD"), BeginString::Fix44);
+        let msg =
+            parser::parse(&mgr.unsubscribe(&id, BeginString::Fix44).unwrap().build()).unwrap();
+        assert_eq!(msg.get(tag::SUBSCRIPTION_REQUEST_TYPE), Some("2"));
+        assert!(mgr.unsubscribe(&id, BeginString::Fix44).is_none());
+        assert!(mgr.on_reconnect(BeginString::Fix44).is_empty());
+        assert_eq!(mgr.on_market_data(&md("W", &id, &[])).unwrap(), None);
+        assert_eq!(
+            mgr.on_market_data(&FixMessage::new("FIX.4.4", "W")),
+            Err(MarketDataError::MissingField(tag::MD_REQ_ID))
+        );
+    }
+
+    #[test]
+    fn book_limits_apply_per_subscription() {
+        let mut mgr = MdSubscriptionManager::new("MD").book_limits(BookLimits::new().max_depth(1));
+        let (a, _) = mgr.subscribe(MdSubscription::new("BTCUSD"), BeginString::Fix44);
+        let (b, _) = mgr.subscribe(MdSubscription::new("ETHUSD"), BeginString::Fix44);
+        let snap = [
+            level(MDEntryType::Bid, 100, 1),
+            level(MDEntryType::Bid, 99, 1),
+        ];
+        mgr.on_market_data(&md("W", &a, &snap)).unwrap();
+        let book = mgr.book(&a).unwrap();
+        assert_eq!(book.bids().len(), 1);
+        assert!(book.truncation().truncated);
+        assert!(!mgr.book(&b).unwrap().truncation().truncated);
+        assert_eq!(mgr.book(&b).unwrap().limits().levels_per_side(), Some(1));
+    }
+}
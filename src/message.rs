@@ -6,29 +6,195 @@
 //! FIX message representation.
 //!
 //! A [`FixMessage`] holds the parsed contents of a single FIX frame.
-//! Tags are stored in a [`HashMap`] for O(1) lookup on the hot path.
-//! Iteration order is not guaranteed; sort the keys explicitly when
-//! deterministic output is required (e.g., in tests or logging).
+//! Tags are stored in a [`FieldMap`], a tag-sorted vector: typical messages
+//! carry fewer than 30 fields, where a binary search beats hashing and avoids
+//! per-bucket allocation. [`FixMessage::iter`] yields fields in ascending tag
+//! order so that logging and golden-file output is deterministic.
 //!
 //! The structural tags 8 (`BeginString`), 9 (`BodyLength`), and 10 (Checksum)
 //! are not stored in [`FixMessage::fields`]; they are either captured in
 //! dedicated fields or reconstructed at serialisation time by [`crate::builder`].
+//!
+//! Repeating groups recognised by the parser (see
+//! [`crate::repeating_group::STANDARD_GROUPS`]) are stored separately in
+//! [`FixMessage::groups`], keyed by their `NoXxx` count tag. The count tag
+//! itself remains in [`FixMessage::fields`].
+//!
+//! Messages produced by [`crate::parser::parse_with_spans`] additionally
+//! record the byte range of every field in the original frame
+//! ([`FixMessage::spans`]), so tooling can point at the exact bytes of a
+//! rejected tag.
+//!
+//! Values are stored in their wire (string) form, exactly as parsed.
+//! [`FixMessage::set_value`] accepts a typed [`FixValue`], and the typed
+//! getters ([`FixMessage::get_decimal`], [`FixMessage::get_bool`],
+//! [`FixMessage::get_timestamp`]) parse the stored text back into its
+//! natural type for comparison. Space-separated multi-value fields
+//! (`MultipleStringValue` / `MultipleCharValue`, e.g. `ExecInst`) are read
+//! with [`FixMessage::get_multiple`].
+//!
+//! [`FixMessage`] implements [`core::fmt::Display`] as a pipe-delimited
+//! wire image (`8=FIX.4.4|35=D|55=BTCUSD`); the alternate form (`{:#}`)
+//! replaces well-known tag numbers with their field names
+//! (`BeginString=FIX.4.4|MsgType=D|Symbol=BTCUSD`).
+//!
+//! [`FixMessage::to_bytes`] re-serializes a message to wire format,
+//! recomputing `BodyLength` and Checksum, for parse → modify → forward flows.
+//!
+//! The `require*` accessors ([`FixMessage::require`],
+//! [`FixMessage::require_u64`], ...) return a [`FieldError`] naming the
+//! missing or malformed tag and the message type instead of a bare `None`.
+//!
+//! [`FixMessage::merge`] overlays another message's fields onto this one,
+//! and [`FixMessage::apply_defaults`] fills only the fields that are absent,
+//! so a template (static routing or account fields) can be combined with a
+//! per-order message before sending.
+//!
+//! [`FixMessage::sanitized`] returns a copy with credential and account
+//! fields masked (see [`SensitiveTags`]) for logging and support dumps.
+//!
+//! With the `serde` feature enabled, [`FixMessage`] and [`StandardHeader`]
+//! implement `Serialize` / `Deserialize`.
 
 use std::collections::HashMap;
 
+use crate::builder::FixBuilder;
+use crate::field_map::FieldMap;
+use crate::repeating_group::GroupEntry;
+use crate::sanitize::SensitiveTags;
+use crate::tag;
+use crate::value::{
+    parse_bool, parse_i64_bytes, parse_u64_bytes, parse_utc_timestamp_bytes, split_multiple,
+    Decimal, FixValue,
+};
+
+/// Byte range of a single `tag=value` field within the original frame.
+///
+/// `offset` is the index of the first tag digit; `len` covers `tag=value`
+/// and excludes the trailing SOH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldSpan {
+    /// Offset of the field from the start of the frame.
+    pub offset: usize,
+    /// Length of the field in bytes, excluding the SOH delimiter.
+    pub len: usize,
+}
+
 /// A parsed FIX message.
 ///
 /// Structural framing tags (8, 9, 10) are excluded from [`Self::fields`]; they are
 /// handled by the parser and builder layers.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FixMessage {
     /// FIX version string from tag 8 (e.g., "FIX.4.4" or "FIXT.1.1").
     pub begin_string: String,
     /// Message type from tag 35 (e.g., "D" for `NewOrderSingle`, "8" for `ExecutionReport`).
     pub msg_type: String,
-    /// All non-structural tag/value pairs keyed by tag number.
-    /// Uses [`HashMap`] for O(1) lookup on the hot path.
-    pub fields: HashMap<u32, String>,
+    /// All non-structural tag/value pairs, sorted by tag number.
+    pub fields: FieldMap,
+    /// Repeating group entries keyed by count tag (e.g., 453 = `NoPartyIDs`),
+    /// in wire order.
+    pub groups: HashMap<u32, Vec<GroupEntry>>,
+    /// Byte ranges of every field in the original frame (including 8, 9, 35,
+    /// and 10), in wire order. Empty unless parsed with
+    /// [`crate::parser::parse_with_spans`].
+    pub spans: Vec<(u32, FieldSpan)>,
+}
+
+/// Error returned by the `require*` accessors of [`FixMessage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// The tag is absent from the message.
+    MissingTag {
+        /// Tag number.
+        tag: u32,
+        /// `MsgType` of the message.
+        msg_type: String,
+    },
+    /// The tag is present but its value cannot be parsed as the requested type.
+    InvalidValue {
+        /// Tag number.
+        tag: u32,
+        /// `MsgType` of the message.
+        msg_type: String,
+        /// Raw value.
+        value: String,
+    },
+}
+
+impl FieldError {
+    /// Tag number the error refers to.
+    #[must_use]
+    pub const fn tag(&self) -> u32 {
+        match self {
+            Self::MissingTag { tag, .. } | Self::InvalidValue { tag, .. } => *tag,
+        }
+    }
+}
+
+impl core::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let describe = |f: &mut core::fmt::Formatter<'_>, t: u32| match tag::name(t) {
+            Some(name) => write!(f, "tag {t} ({name})"),
+            None => write!(f, "tag {t}"),
+        };
+        match self {
+            Self::MissingTag { tag, msg_type } => {
+                f.write_str("Missing required field: ")?;
+                describe(f, *tag)?;
+                write!(f, " in MsgType {msg_type}")
+            }
+            Self::InvalidValue {
+                tag,
+                msg_type,
+                value,
+            } => {
+                f.write_str("Invalid value for ")?;
+                describe(f, *tag)?;
+                write!(f, " in MsgType {msg_type}: {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Typed view of the standard header fields of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardHeader {
+    /// `BeginString` (tag 8).
+    pub begin_string: String,
+    /// `MsgType` (tag 35).
+    pub msg_type: String,
+    /// `SenderCompID` (tag 49).
+    pub sender_comp_id: String,
+    /// `TargetCompID` (tag 56).
+    pub target_comp_id: String,
+    /// `MsgSeqNum` (tag 34).
+    pub msg_seq_num: u64,
+    /// `SendingTime` (tag 52), if present.
+    pub sending_time: Option<String>,
+}
+
+impl StandardHeader {
+    /// Extract the standard header from a message.
+    ///
+    /// Returns `None` if `SenderCompID`, `TargetCompID`, or a numeric
+    /// `MsgSeqNum` is missing.
+    #[must_use]
+    pub fn from_message(msg: &FixMessage) -> Option<Self> {
+        Some(Self {
+            begin_string: msg.begin_string.clone(),
+            msg_type: msg.msg_type.clone(),
+            sender_comp_id: msg.get(tag::SENDER_COMP_ID)?.to_string(),
+            target_comp_id: msg.get(tag::TARGET_COMP_ID)?.to_string(),
+            msg_seq_num: msg.get_u64(tag::MSG_SEQ_NUM)?,
+            sending_time: msg.get(tag::SENDING_TIME).map(String::from),
+        })
+    }
 }
 
 impl FixMessage {
@@ -39,7 +205,9 @@ impl FixMessage {
         Self {
             begin_string: begin_string.to_string(),
             msg_type: msg_type.to_string(),
-            fields: HashMap::new(),
+            fields: FieldMap::new(),
+            groups: HashMap::new(),
+            spans: Vec::new(),
         }
     }
 
@@ -52,12 +220,82 @@ impl FixMessage {
         self
     }
 
+    /// Set (or overwrite) a field from a typed [`FixValue`], stored in its
+    /// wire form.
+    ///
+    /// Returns `&mut self` for method chaining.
+    #[inline(always)]
+    pub fn set_value(&mut self, tag: u32, value: impl Into<FixValue>) -> &mut Self {
+        let value = value.into();
+        self.fields.insert(tag, value.to_string());
+        self
+    }
+
     /// Retrieve the string value for a tag, or `None` if absent.
     ///
-    /// O(1) average — backed by [`HashMap`].
+    /// O(log n) — binary search over the sorted [`FieldMap`].
     #[inline(always)]
     pub fn get(&self, tag: u32) -> Option<&str> {
-        self.fields.get(&tag).map(String::as_str)
+        self.fields.get(tag).map(String::as_str)
+    }
+
+    /// Retrieve a field by its FIX name (e.g., `"Symbol"`), see [`tag::by_name`].
+    ///
+    /// Returns `None` if the name is unknown or the field is absent.
+    #[must_use]
+    pub fn get_by_name(&self, name: &str) -> Option<&str> {
+        self.get(tag::by_name(name)?)
+    }
+
+    /// Set a field by its FIX name (e.g., `"Account"`), see [`tag::by_name`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tag::UnknownTagName`] if the name is not in the tag table.
+    pub fn set_by_name(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<&mut Self, tag::UnknownTagName> {
+        let t = tag::by_name(name).ok_or_else(|| tag::UnknownTagName(name.to_string()))?;
+        Ok(self.set(t, value))
+    }
+
+    /// Remove a field, returning its value if it was present.
+    #[inline(always)]
+    pub fn remove(&mut self, tag: u32) -> Option<String> {
+        self.fields.remove(tag)
+    }
+
+    /// Return `true` if the message contains a value for `tag`.
+    #[inline(always)]
+    #[must_use]
+    pub fn contains(&self, tag: u32) -> bool {
+        self.fields.contains_key(tag)
+    }
+
+    /// Number of (non-structural, non-group-member) fields in the message.
+    #[inline(always)]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Return `true` if the message holds no fields.
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Remove all fields, repeating groups, and recorded spans.
+    ///
+    /// `begin_string` and `msg_type` are kept.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.fields.clear();
+        self.groups.clear();
+        self.spans.clear();
     }
 
     /// Parse the value of a tag as an `i64`.
@@ -66,7 +304,7 @@ impl FixMessage {
     #[inline(always)]
     #[must_use]
     pub fn get_i64(&self, tag: u32) -> Option<i64> {
-        self.fields.get(&tag)?.parse().ok()
+        parse_i64_bytes(self.get_bytes(tag)?)
     }
 
     /// Parse the value of a tag as a `u64`.
@@ -75,7 +313,277 @@ impl FixMessage {
     #[inline(always)]
     #[must_use]
     pub fn get_u64(&self, tag: u32) -> Option<u64> {
-        self.fields.get(&tag)?.parse().ok()
+        parse_u64_bytes(self.get_bytes(tag)?)
+    }
+
+    /// Raw bytes of a tag's value, or `None` if absent.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_bytes(&self, tag: u32) -> Option<&[u8]> {
+        self.fields.get(tag).map(String::as_bytes)
+    }
+
+    /// Retrieve a field that must be present.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FieldError::MissingTag`] if the tag is absent.
+    pub fn require(&self, tag: u32) -> Result<&str, FieldError> {
+        self.get(tag).ok_or_else(|| FieldError::MissingTag {
+            tag,
+            msg_type: self.msg_type.clone(),
+        })
+    }
+
+    /// Parse a required field's bytes with `parse`.
+    fn require_with<T>(
+        &self,
+        tag: u32,
+        parse: impl FnOnce(&[u8]) -> Option<T>,
+    ) -> Result<T, FieldError> {
+        let value = self.require(tag)?;
+        parse(value.as_bytes()).ok_or_else(|| FieldError::InvalidValue {
+            tag,
+            msg_type: self.msg_type.clone(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Parse a required field as an `i64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FieldError`] if the tag is absent or not an integer.
+    pub fn require_i64(&self, tag: u32) -> Result<i64, FieldError> {
+        self.require_with(tag, parse_i64_bytes)
+    }
+
+    /// Parse a required field as a `u64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FieldError`] if the tag is absent or not an unsigned integer.
+    pub fn require_u64(&self, tag: u32) -> Result<u64, FieldError> {
+        self.require_with(tag, parse_u64_bytes)
+    }
+
+    /// Parse a required field as a [`Decimal`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FieldError`] if the tag is absent or not a FIX decimal.
+    pub fn require_decimal(&self, tag: u32) -> Result<Decimal, FieldError> {
+        self.require_with(tag, Decimal::parse_bytes)
+    }
+
+    /// Parse a required `UTCTimestamp` field as nanoseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FieldError`] if the tag is absent or malformed.
+    pub fn require_timestamp(&self, tag: u32) -> Result<u64, FieldError> {
+        self.require_with(tag, parse_utc_timestamp_bytes)
+    }
+
+    /// Return the standard header fields, or `None` if a required header
+    /// field is missing. See [`StandardHeader::from_message`].
+    #[must_use]
+    pub fn header(&self) -> Option<StandardHeader> {
+        StandardHeader::from_message(self)
+    }
+
+    /// Parse the value of a tag as a [`Decimal`] (e.g., prices and quantities).
+    ///
+    /// Returns `None` if the tag is absent or the value is not a FIX decimal.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_decimal(&self, tag: u32) -> Option<Decimal> {
+        Decimal::parse_bytes(self.get_bytes(tag)?)
+    }
+
+    /// Parse the value of a tag as a FIX Boolean (`Y` / `N`).
+    ///
+    /// Returns `None` if the tag is absent or the value is neither `Y` nor `N`.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_bool(&self, tag: u32) -> Option<bool> {
+        parse_bool(self.get(tag)?)
+    }
+
+    /// Return the value of a tag as a single-character code.
+    ///
+    /// Returns `None` if the tag is absent or the value is not exactly one
+    /// character.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_char(&self, tag: u32) -> Option<char> {
+        let mut chars = self.get(tag)?.chars();
+        chars.next().filter(|_| chars.next().is_none())
+    }
+
+    /// Parse the value of a tag as a `UTCTimestamp`, returning nanoseconds
+    /// since the Unix epoch.
+    ///
+    /// Returns `None` if the tag is absent or the value is malformed.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_timestamp(&self, tag: u32) -> Option<u64> {
+        parse_utc_timestamp_bytes(self.get_bytes(tag)?)
+    }
+
+    /// Iterate over the space-separated values of a `MultipleStringValue` /
+    /// `MultipleCharValue` field (e.g., `ExecInst` = `"6 G"`).
+    ///
+    /// Yields nothing if the tag is absent. Write such fields with
+    /// [`FixValue::Multiple`] via [`Self::set_value`].
+    pub fn get_multiple(&self, tag: u32) -> impl Iterator<Item = &str> {
+        split_multiple(self.get(tag).unwrap_or(""))
+    }
+
+    /// Iterate over `(tag, value)` pairs in ascending tag order.
+    ///
+    /// Repeating group members are not included; use [`Self::groups`].
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.fields.iter()
+    }
+
+    /// Iterate over tag numbers in the same order as [`Self::iter`].
+    pub fn keys(&self) -> impl Iterator<Item = u32> + '_ {
+        self.iter().map(|(t, _)| t)
+    }
+
+    /// Iterate over values in the same order as [`Self::iter`].
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Return the entries of the repeating group introduced by `no_tag`
+    /// (e.g., [`crate::tag::NO_PARTY_IDS`]) in wire order.
+    ///
+    /// Returns an empty slice if the group is absent.
+    #[inline(always)]
+    #[must_use]
+    pub fn groups(&self, no_tag: u32) -> &[GroupEntry] {
+        self.groups.get(&no_tag).map_or(&[], Vec::as_slice)
+    }
+
+    /// Return the byte range of the first occurrence of `tag` in the
+    /// original frame.
+    ///
+    /// Returns `None` if the tag is absent or spans were not recorded.
+    #[must_use]
+    pub fn span(&self, tag: u32) -> Option<FieldSpan> {
+        self.spans.iter().find(|(t, _)| *t == tag).map(|&(_, s)| s)
+    }
+
+    /// Serialize the message back to FIX wire format.
+    ///
+    /// `BodyLength` (9) and Checksum (10) are recomputed, so a parsed message
+    /// can be modified and forwarded as-is. Field order and group layout
+    /// follow [`FixBuilder::from_message`].
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        FixBuilder::from_message(self).build()
+    }
+
+    /// Overlay every field and repeating group of `other` onto this message;
+    /// values from `other` win.
+    ///
+    /// `begin_string` and `msg_type` are kept. [`Self::spans`] is cleared,
+    /// since the fields no longer match the original frame.
+    /// Returns `&mut self` for method chaining.
+    pub fn merge(&mut self, other: &Self) -> &mut Self {
+        for (t, v) in other.fields.iter() {
+            self.fields.insert(t, v.to_string());
+        }
+        for (&t, entries) in &other.groups {
+            self.groups.insert(t, entries.clone());
+        }
+        self.spans.clear();
+        self
+    }
+
+    /// Copy the fields and repeating groups of `defaults` that are absent
+    /// from this message; existing values win.
+    ///
+    /// A group is copied only together with its count tag, so a group the
+    /// message already declares is never mixed with template entries.
+    /// [`Self::spans`] is cleared if anything was added.
+    /// Returns `&mut self` for method chaining.
+    pub fn apply_defaults(&mut self, defaults: &Self) -> &mut Self {
+        let mut changed = false;
+        for (t, v) in defaults.fields.iter() {
+            if self.fields.contains_key(t) {
+                continue;
+            }
+            self.fields.insert(t, v.to_string());
+            if let Some(entries) = defaults.groups.get(&t) {
+                self.groups.insert(t, entries.clone());
+            }
+            changed = true;
+        }
+        if changed {
+            self.spans.clear();
+        }
+        self
+    }
+
+    /// Return a copy with every field listed in `sensitive` replaced by its
+    /// mask, including fields inside repeating groups.
+    ///
+    /// [`Self::spans`] is cleared, since masked values no longer match the
+    /// original frame.
+    #[must_use]
+    pub fn sanitized(&self, sensitive: &SensitiveTags) -> Self {
+        let mut out = self.clone();
+        out.spans.clear();
+        let fields = out.fields.iter_mut().chain(
+            out.groups
+                .values_mut()
+                .flatten()
+                .flat_map(|e| e.fields.iter_mut().map(|(&t, v)| (t, v))),
+        );
+        for (t, v) in fields {
+            if sensitive.contains(t) {
+                sensitive.mask().clone_into(v);
+            }
+        }
+        out
+    }
+}
+
+impl core::fmt::Display for FixMessage {
+    /// Render `BeginString`, `MsgType`, then all fields in [`FixMessage::iter`]
+    /// order, each repeating group's entries following its count tag.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let verbose = f.alternate();
+        write_display_field(f, verbose, tag::BEGIN_STRING, &self.begin_string)?;
+        f.write_str("|")?;
+        write_display_field(f, verbose, tag::MSG_TYPE, &self.msg_type)?;
+        for (t, v) in self.iter() {
+            f.write_str("|")?;
+            write_display_field(f, verbose, t, v)?;
+            for entry in self.groups(t) {
+                for (gt, gv) in entry.iter() {
+                    f.write_str("|")?;
+                    write_display_field(f, verbose, gt, gv)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write `tag=value`, or `Name=value` in verbose mode when the tag is known.
+fn write_display_field(
+    f: &mut core::fmt::Formatter<'_>,
+    verbose: bool,
+    t: u32,
+    value: &str,
+) -> core::fmt::Result {
+    match tag::name(t).filter(|_| verbose) {
+        Some(name) => write!(f, "{name}={value}"),
+        None => write!(f, "{t}={value}"),
     }
 }
 
@@ -86,7 +594,7 @@ impl FixMessage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tag;
+    use crate::version::BeginString;
 
     #[test]
     fn test_new_message() {
@@ -300,10 +808,455 @@ mod tests {
     }
 
     #[test]
-    fn test_hashmap_is_o1_lookup() {
-        // Confirm FixMessage uses HashMap (not BTreeMap) for O(1) field lookup.
-        // This is a compile-time design verification: fields is HashMap<u32, String>.
+    fn test_set_value_typed() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set_value(tag::PRICE, Decimal::new(10_050, 2))
+            .set_value(tag::ORDER_QTY, 7u64)
+            .set_value(43, false);
+        assert_eq!(msg.get(tag::PRICE), Some("100.50"));
+        assert_eq!(msg.get_decimal(tag::PRICE), Some(Decimal::new(1005, 1)));
+        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(7));
+        assert_eq!(msg.get_bool(43), Some(false));
+    }
+
+    #[test]
+    fn test_get_decimal_invalid() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::PRICE, "1.2.3");
+        assert_eq!(msg.get_decimal(tag::PRICE), None);
+        assert_eq!(msg.get_decimal(tag::SYMBOL), None);
+    }
+
+    #[test]
+    fn test_get_char() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SIDE, "2").set(tag::SYMBOL, "7203");
+        assert_eq!(msg.get_char(tag::SIDE), Some('2'));
+        assert_eq!(msg.get_char(tag::SYMBOL), None);
+        assert_eq!(msg.get_char(tag::TEXT), None);
+    }
+
+    #[test]
+    fn test_get_timestamp() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SENDING_TIME, "19700101-00:00:01.250");
+        assert_eq!(msg.get_timestamp(tag::SENDING_TIME), Some(1_250_000_000));
+        msg.set(tag::SENDING_TIME, "garbage");
+        assert_eq!(msg.get_timestamp(tag::SENDING_TIME), None);
+    }
+
+    #[test]
+    fn test_header_extraction() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SENDER_COMP_ID, "ALICE")
+            .set(tag::TARGET_COMP_ID, "BROKER")
+            .set(tag::MSG_SEQ_NUM, "12");
+        let header = msg.header().unwrap();
+        assert_eq!(header.begin_string, "FIX.4.4");
+        assert_eq!(header.msg_type, "D");
+        assert_eq!(header.sender_comp_id, "ALICE");
+        assert_eq!(header.target_comp_id, "BROKER");
+        assert_eq!(header.msg_seq_num, 12);
+        assert_eq!(header.sending_time, None);
+    }
+
+    #[test]
+    fn test_header_missing_seq_num() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SENDER_COMP_ID, "A")
+            .set(tag::TARGET_COMP_ID, "B");
+        assert!(msg.header().is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_roundtrip() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD").set(tag::PRICE, "100.5");
+        let mut party = GroupEntry::new();
+        party.set(tag::PARTY_ID, "P1");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![party]);
+
+        let json = serde_json::to_string(&msg).unwrap();
+        let back: FixMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, msg);
+
+        let header = StandardHeader {
+            begin_string: "FIX.4.4".into(),
+            msg_type: "0".into(),
+            sender_comp_id: "A".into(),
+            target_comp_id: "B".into(),
+            msg_seq_num: 3,
+            sending_time: Some("20260101-00:00:00".into()),
+        };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StandardHeader>(&json).unwrap(),
+            header
+        );
+    }
+
+    #[test]
+    fn test_display_wire_image() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD").set(tag::SIDE, "1");
+        assert_eq!(msg.to_string(), "8=FIX.4.4|35=D|54=1|55=BTCUSD");
+    }
+
+    #[test]
+    fn test_display_verbose_names() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD").set(9999, "custom");
+        assert_eq!(
+            format!("{msg:#}"),
+            "BeginString=FIX.4.4|MsgType=D|Symbol=BTCUSD|9999=custom"
+        );
+    }
+
+    #[test]
+    fn test_display_includes_group_entries() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::NO_PARTY_IDS, "2").set(tag::SYMBOL, "X");
+        let mut a = GroupEntry::new();
+        a.set(tag::PARTY_ID, "A");
+        let mut b = GroupEntry::new();
+        b.set(tag::PARTY_ID, "B").set(tag::PARTY_ROLE, "3");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![a, b]);
+        assert_eq!(
+            msg.to_string(),
+            "8=FIX.4.4|35=D|55=X|453=2|448=A|448=B|452=3"
+        );
+    }
+
+    #[test]
+    fn test_get_and_set_by_name() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set_by_name("Account", "ACC-1")
+            .unwrap()
+            .set_by_name("Symbol", "BTCUSD")
+            .unwrap();
+        assert_eq!(msg.get(tag::ACCOUNT), Some("ACC-1"));
+        assert_eq!(msg.get_by_name("Symbol"), Some("BTCUSD"));
+        assert_eq!(msg.get_by_name("Price"), None);
+        assert_eq!(msg.get_by_name("NoSuchField"), None);
+        assert_eq!(
+            msg.set_by_name("NoSuchField", "x").unwrap_err(),
+            tag::UnknownTagName("NoSuchField".into())
+        );
+    }
+
+    #[test]
+    fn test_remove_returns_value() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::PRICE, "100").set(tag::SYMBOL, "BTCUSD");
+        assert_eq!(msg.remove(tag::PRICE), Some("100".to_string()));
+        assert_eq!(msg.remove(tag::PRICE), None);
+        assert!(!msg.contains(tag::PRICE));
+        assert!(msg.contains(tag::SYMBOL));
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        assert!(msg.is_empty());
+        assert_eq!(msg.len(), 0);
+        msg.set(tag::SYMBOL, "BTCUSD").set(tag::SIDE, "1");
+        assert!(!msg.is_empty());
+        assert_eq!(msg.len(), 2);
+        // Overwriting does not grow the message.
+        msg.set(tag::SIDE, "2");
+        assert_eq!(msg.len(), 2);
+    }
+
+    #[test]
+    fn test_clear_keeps_header_identity() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD");
+        msg.groups
+            .insert(tag::NO_PARTY_IDS, vec![GroupEntry::new()]);
+        msg.clear();
+        assert!(msg.is_empty());
+        assert!(msg.groups(tag::NO_PARTY_IDS).is_empty());
+        assert_eq!(msg.begin_string, "FIX.4.4");
+        assert_eq!(msg.msg_type, "D");
+    }
+
+    #[test]
+    fn test_iter_yields_sorted_pairs() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD")
+            .set(tag::SENDER_COMP_ID, "ALICE")
+            .set(tag::CL_ORD_ID, "ORD-1");
+        let pairs: Vec<(u32, &str)> = msg.iter().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (tag::CL_ORD_ID, "ORD-1"),
+                (tag::SENDER_COMP_ID, "ALICE"),
+                (tag::SYMBOL, "BTCUSD"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keys_and_values_match_iter_order() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::TEXT, "t")
+            .set(tag::PRICE, "10")
+            .set(tag::SIDE, "1");
+        assert_eq!(
+            msg.keys().collect::<Vec<_>>(),
+            vec![tag::PRICE, tag::SIDE, tag::TEXT]
+        );
+        assert_eq!(msg.values().collect::<Vec<_>>(), vec!["10", "1", "t"]);
+    }
+
+    #[test]
+    fn test_iter_empty_message() {
+        let msg = FixMessage::new("FIX.4.4", "0");
+        assert_eq!(msg.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_span_absent_without_recording() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD");
+        assert_eq!(msg.span(tag::SYMBOL), None);
+    }
+
+    #[test]
+    fn test_span_returns_first_occurrence() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.spans
+            .push((tag::PARTY_ID, FieldSpan { offset: 10, len: 6 }));
+        msg.spans
+            .push((tag::PARTY_ID, FieldSpan { offset: 20, len: 6 }));
+        assert_eq!(
+            msg.span(tag::PARTY_ID),
+            Some(FieldSpan { offset: 10, len: 6 })
+        );
+    }
+
+    #[test]
+    fn test_groups_absent_returns_empty_slice() {
         let msg = FixMessage::new("FIX.4.4", "D");
-        let _: &HashMap<u32, String> = &msg.fields;
+        assert!(msg.groups(tag::NO_PARTY_IDS).is_empty());
+    }
+
+    #[test]
+    fn test_groups_returns_entries_in_order() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        let mut first = GroupEntry::new();
+        first.set(tag::PARTY_ID, "P1");
+        let mut second = GroupEntry::new();
+        second.set(tag::PARTY_ID, "P2");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![first, second]);
+
+        let parties = msg.groups(tag::NO_PARTY_IDS);
+        assert_eq!(parties.len(), 2);
+        assert_eq!(parties[0].get(tag::PARTY_ID), Some("P1"));
+        assert_eq!(parties[1].get(tag::PARTY_ID), Some("P2"));
+    }
+
+    #[test]
+    fn test_to_bytes_parse_modify_forward() {
+        let raw = crate::builder::FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field(tag::MSG_SEQ_NUM, "7")
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(tag::PRICE, "100")
+            .build();
+        let mut msg = crate::parser::parse(&raw).unwrap();
+        msg.set(tag::PRICE, "101.25")
+            .set(tag::TARGET_COMP_ID, "VENUE");
+
+        let out = msg.to_bytes();
+        // Header fields lead the body; 9 and 10 are recomputed.
+        assert!(out.starts_with(b"8=FIX.4.4\x019="));
+        assert!(out
+            .windows(30)
+            .any(|w| w.starts_with(b"35=D\x0149=ALICE\x0156=VENUE\x0134=7")));
+        let back = crate::parser::parse(&out).unwrap();
+        assert_eq!(back, msg);
+    }
+
+    #[test]
+    fn test_sanitized_masks_sensitive_fields() {
+        use crate::sanitize::SensitiveTags;
+
+        let mut msg = FixMessage::new("FIX.4.4", "A");
+        msg.set(tag::USERNAME, "alice")
+            .set(tag::PASSWORD, "hunter2")
+            .set(tag::ACCOUNT, "ACC-1")
+            .set(tag::HEART_BT_INT, "30")
+            .set(tag::NO_PARTY_IDS, "1");
+        let mut party = GroupEntry::new();
+        party
+            .set(tag::PARTY_ID, "TRADER-7")
+            .set(tag::PARTY_ROLE, "12");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![party]);
+
+        let clean = msg.sanitized(&SensitiveTags::default().with(tag::PARTY_ID));
+        assert_eq!(clean.get(tag::PASSWORD), Some("***"));
+        assert_eq!(clean.get(tag::USERNAME), Some("***"));
+        assert_eq!(clean.get(tag::ACCOUNT), Some("***"));
+        assert_eq!(clean.get(tag::HEART_BT_INT), Some("30"));
+        assert_eq!(
+            clean.groups(tag::NO_PARTY_IDS)[0].get(tag::PARTY_ID),
+            Some("***")
+        );
+        assert_eq!(
+            clean.groups(tag::NO_PARTY_IDS)[0].get(tag::PARTY_ROLE),
+            Some("12")
+        );
+        assert!(!clean.to_string().contains("hunter2"));
+        // 元のメッセージは変更しない
+        assert_eq!(msg.get(tag::PASSWORD), Some("hunter2"));
+
+        let none = msg.sanitized(&SensitiveTags::none());
+        assert_eq!(none, msg);
+    }
+
+    #[test]
+    fn test_require_accessors() {
+        let mut msg = FixMessage::new("FIX.4.4", "8");
+        msg.set(tag::MSG_SEQ_NUM, "42")
+            .set(tag::LAST_PX, "101.5")
+            .set(tag::ORDER_QTY, "abc");
+        assert_eq!(msg.require(tag::MSG_SEQ_NUM), Ok("42"));
+        assert_eq!(msg.require_u64(tag::MSG_SEQ_NUM), Ok(42));
+        assert_eq!(msg.require_i64(tag::MSG_SEQ_NUM), Ok(42));
+        assert_eq!(msg.require_decimal(tag::LAST_PX), Ok(Decimal::new(1015, 1)));
+
+        let missing = msg.require(tag::ORDER_ID).unwrap_err();
+        assert_eq!(
+            missing,
+            FieldError::MissingTag {
+                tag: tag::ORDER_ID,
+                msg_type: "8".into()
+            }
+        );
+        assert_eq!(
+            missing.to_string(),
+            "Missing required field: tag 37 (OrderID) in MsgType 8"
+        );
+
+        let invalid = msg.require_u64(tag::ORDER_QTY).unwrap_err();
+        assert_eq!(invalid.tag(), tag::ORDER_QTY);
+        assert_eq!(
+            invalid.to_string(),
+            "Invalid value for tag 38 (OrderQty) in MsgType 8: abc"
+        );
+        assert!(msg.require_timestamp(tag::TRANSACT_TIME).is_err());
+    }
+
+    #[test]
+    fn test_multiple_value_fields() {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set_value(tag::EXEC_INST, &["6", "G"][..])
+            .set(tag::TRADING_SESSION_ID, "1 3");
+        assert_eq!(msg.get(tag::EXEC_INST), Some("6 G"));
+        assert_eq!(
+            msg.get_multiple(tag::EXEC_INST).collect::<Vec<_>>(),
+            ["6", "G"]
+        );
+        assert_eq!(msg.get_multiple(tag::TRADING_SESSION_ID).count(), 2);
+        assert_eq!(msg.get_multiple(tag::TEXT).count(), 0);
+    }
+
+    #[test]
+    fn test_merge_overlays_fields() {
+        let mut template = FixMessage::new("FIX.4.4", "D");
+        template
+            .set(tag::ACCOUNT, "ACC-1")
+            .set(tag::HANDL_INST, "1")
+            .set(tag::NO_PARTY_IDS, "1");
+        let mut party = GroupEntry::new();
+        party.set(tag::PARTY_ID, "DESK").set(tag::PARTY_ROLE, "3");
+        template.groups.insert(tag::NO_PARTY_IDS, vec![party]);
+
+        let raw = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::CL_ORD_ID, "ORD-1")
+            .field(tag::ACCOUNT, "ACC-9")
+            .build();
+        let mut order = crate::parser::parse_with_spans(&raw).unwrap();
+        order.merge(&template);
+        assert_eq!(order.get(tag::ACCOUNT), Some("ACC-1"));
+        assert_eq!(order.get(tag::CL_ORD_ID), Some("ORD-1"));
+        assert_eq!(
+            order.groups(tag::NO_PARTY_IDS)[0].get(tag::PARTY_ID),
+            Some("DESK")
+        );
+        assert!(order.spans.is_empty());
+        assert_eq!(order.msg_type, "D");
+    }
+
+    #[test]
+    fn test_apply_defaults_keeps_existing() {
+        let mut template = FixMessage::new("FIX.4.4", "D");
+        template
+            .set(tag::ACCOUNT, "ACC-1")
+            .set(tag::HANDL_INST, "1")
+            .set(tag::NO_PARTY_IDS, "1");
+        let mut desk = GroupEntry::new();
+        desk.set(tag::PARTY_ID, "DESK");
+        template.groups.insert(tag::NO_PARTY_IDS, vec![desk]);
+
+        let mut order = FixMessage::new("FIX.4.4", "D");
+        order.set(tag::ACCOUNT, "ACC-9").set(tag::NO_PARTY_IDS, "1");
+        let mut trader = GroupEntry::new();
+        trader.set(tag::PARTY_ID, "TRADER");
+        order.groups.insert(tag::NO_PARTY_IDS, vec![trader]);
+
+        order.apply_defaults(&template);
+        assert_eq!(order.get(tag::ACCOUNT), Some("ACC-9"));
+        assert_eq!(order.get(tag::HANDL_INST), Some("1"));
+        assert_eq!(order.groups(tag::NO_PARTY_IDS).len(), 1);
+        assert_eq!(
+            order.groups(tag::NO_PARTY_IDS)[0].get(tag::PARTY_ID),
+            Some("TRADER")
+        );
+
+        let mut bare = FixMessage::new("FIX.4.4", "D");
+        bare.apply_defaults(&template);
+        assert_eq!(
+            bare.groups(tag::NO_PARTY_IDS)[0].get(tag::PARTY_ID),
+            Some("DESK")
+        );
+    }
+
+    #[test]
+    fn test_to_bytes_roundtrips_groups() {
+        let mut msg = FixMessage::new("FIX.4.4", "V");
+        msg.set(tag::MD_REQ_ID, "R1").set(tag::NO_RELATED_SYM, "1");
+        let mut a = GroupEntry::new();
+        a.set(tag::SYMBOL, "BTCUSD")
+            .set(tag::SECURITY_ID_SOURCE, "8");
+        let mut b = GroupEntry::new();
+        b.set(tag::SYMBOL, "ETHUSD");
+        msg.groups.insert(tag::NO_RELATED_SYM, vec![a, b]);
+
+        let back = crate::parser::parse(&msg.to_bytes()).unwrap();
+        // The count tag is rewritten from the entries.
+        assert_eq!(back.get_u64(tag::NO_RELATED_SYM), Some(2));
+        let syms = back.groups(tag::NO_RELATED_SYM);
+        assert_eq!(syms.len(), 2);
+        assert_eq!(syms[0].get(tag::SECURITY_ID_SOURCE), Some("8"));
+        assert_eq!(syms[1].get(tag::SYMBOL), Some("ETHUSD"));
+    }
+
+    #[test]
+    fn test_fields_are_compact_and_sorted() {
+        // Fields live in a tag-sorted FieldMap rather than a HashMap.
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SYMBOL, "BTCUSD")
+            .set(tag::CL_ORD_ID, "ORD-1")
+            .set(tag::SIDE, "1");
+        let _: &FieldMap = &msg.fields;
+        assert_eq!(
+            msg.fields.iter().map(|(t, _)| t).collect::<Vec<_>>(),
+            [tag::CL_ORD_ID, tag::SIDE, tag::SYMBOL]
+        );
     }
 }
@@ -0,0 +1,653 @@
+//! 型付き注文メッセージ
+//!
+//! `NewOrderSingle` (D)、`OrderCancelRequest` (F)、`OrderCancelReject` (9)、
+//! `OrderStatusRequest` (H) を
+//! Rust の型で表現し、`TryFrom<&FixMessage>` / `From<&T> for FixBuilder` で
+//! 相互変換する。`ExecutionReport` (8) は [`crate::execution_report`] を参照。
+//!
+//! [`FixBuilder`] への変換はボディフィールドのみを設定する。標準ヘッダ
+//! (49/56/34/52) は呼び出し側で追加する。`From` 変換の `BeginString` は
+//! [`DEFAULT_BEGIN_STRING`]、それ以外は `to_builder` を使う。
+
+use alice_ledger::{OrderType, Side, TimeInForce};
+
+use crate::builder::FixBuilder;
+use crate::convert::{
+    alice_ord_type_to_fix, alice_side_to_fix, alice_tif_to_fix, fix_ord_type_to_alice,
+    fix_side_to_alice, fix_tif_to_alice,
+};
+use crate::execution_report::OrdStatus;
+use crate::message::{FieldError, FixMessage};
+use crate::tag;
+use crate::value::{Decimal, FixValue};
+use crate::version::BeginString;
+
+/// 型付きメッセージの `MsgType`。
+pub mod msg_type {
+    /// New Order Single。
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    /// Order Cancel Request。
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+    /// Order Cancel Reject。
+    pub const ORDER_CANCEL_REJECT: &str = "9";
+    /// Order Status Request。
+    pub const ORDER_STATUS_REQUEST: &str = "H";
+}
+
+/// `From` 変換で使う `BeginString`。
+pub const DEFAULT_BEGIN_STRING: BeginString = BeginString::Fix44;
+
+/// 型付きメッセージ変換エラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderMsgError {
+    /// メッセージタイプが不正。
+    WrongMsgType {
+        /// 期待した `MsgType`。
+        expected: &'static str,
+        /// 実際の `MsgType`。
+        actual: String,
+    },
+    /// 必須フィールドが欠落。
+    MissingField(u32),
+    /// フィールド値が解釈できない。
+    InvalidField {
+        /// タグ番号。
+        tag: u32,
+        /// 値。
+        value: String,
+    },
+}
+
+impl core::fmt::Display for OrderMsgError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongMsgType { expected, actual } => {
+                write!(f, "Wrong MsgType: expected {expected}, got {actual}")
+            }
+            Self::MissingField(tag) => write!(f, "Missing required field: tag {tag}"),
+            Self::InvalidField { tag, value } => write!(f, "Invalid value for tag {tag}: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for OrderMsgError {}
+
+impl From<FieldError> for OrderMsgError {
+    fn from(e: FieldError) -> Self {
+        match e {
+            FieldError::MissingTag { tag, .. } => Self::MissingField(tag),
+            FieldError::InvalidValue { tag, value, .. } => Self::InvalidField { tag, value },
+        }
+    }
+}
+
+/// New Order Single (35=D)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewOrderSingle {
+    /// クライアント注文 ID (tag 11)。
+    pub cl_ord_id: String,
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// サイド (tag 54)。
+    pub side: Side,
+    /// 発注時刻 (tag 60, UNIX ナノ秒)。
+    pub transact_time: u64,
+    /// 注文種別 (tag 40)。
+    pub ord_type: OrderType,
+    /// 数量 (tag 38)。
+    pub order_qty: Decimal,
+    /// 指値 (tag 44)。
+    pub price: Option<Decimal>,
+    /// 執行条件 (tag 59)。
+    pub time_in_force: Option<TimeInForce>,
+    /// テキスト (tag 58)。
+    pub text: Option<String>,
+}
+
+impl NewOrderSingle {
+    /// `begin_string` を指定してビルダーに変換。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, msg_type::NEW_ORDER_SINGLE);
+        b.field(tag::CL_ORD_ID, &self.cl_ord_id)
+            .field(tag::SYMBOL, &self.symbol)
+            .field(tag::SIDE, alice_side_to_fix(self.side))
+            .field_value(tag::TRANSACT_TIME, FixValue::Timestamp(self.transact_time))
+            .field(tag::ORD_TYPE, alice_ord_type_to_fix(self.ord_type))
+            .field_value(tag::ORDER_QTY, self.order_qty);
+        if let Some(px) = self.price {
+            b.field_value(tag::PRICE, px);
+        }
+        if let Some(tif) = self.time_in_force {
+            b.field(tag::TIME_IN_FORCE, alice_tif_to_fix(tif));
+        }
+        if let Some(text) = &self.text {
+            b.field(tag::TEXT, text);
+        }
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for NewOrderSingle {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::NEW_ORDER_SINGLE)?;
+        Ok(Self {
+            cl_ord_id: msg.require(tag::CL_ORD_ID)?.to_string(),
+            symbol: msg.require(tag::SYMBOL)?.to_string(),
+            side: require_side(msg)?,
+            transact_time: msg.require_timestamp(tag::TRANSACT_TIME)?,
+            ord_type: convert(msg, tag::ORD_TYPE, fix_ord_type_to_alice)?,
+            order_qty: msg.require_decimal(tag::ORDER_QTY)?,
+            price: optional_decimal(msg, tag::PRICE)?,
+            time_in_force: optional(msg, tag::TIME_IN_FORCE, fix_tif_to_alice)?,
+            text: msg.get(tag::TEXT).map(String::from),
+        })
+    }
+}
+
+impl From<&NewOrderSingle> for FixBuilder {
+    fn from(msg: &NewOrderSingle) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+/// Order Cancel Request (35=F)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCancelRequest {
+    /// 取消対象の `ClOrdID` (tag 41)。
+    pub orig_cl_ord_id: String,
+    /// 取消要求自体の `ClOrdID` (tag 11)。
+    pub cl_ord_id: String,
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// サイド (tag 54)。
+    pub side: Side,
+    /// 要求時刻 (tag 60, UNIX ナノ秒)。
+    pub transact_time: u64,
+    /// ブローカー注文 ID (tag 37)。
+    pub order_id: Option<String>,
+    /// 数量 (tag 38)。
+    pub order_qty: Option<Decimal>,
+}
+
+impl OrderCancelRequest {
+    /// `begin_string` を指定してビルダーに変換。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, msg_type::ORDER_CANCEL_REQUEST);
+        b.field(tag::ORIG_CL_ORD_ID, &self.orig_cl_ord_id)
+            .field(tag::CL_ORD_ID, &self.cl_ord_id)
+            .field(tag::SYMBOL, &self.symbol)
+            .field(tag::SIDE, alice_side_to_fix(self.side))
+            .field_value(tag::TRANSACT_TIME, FixValue::Timestamp(self.transact_time));
+        if let Some(id) = &self.order_id {
+            b.field(tag::ORDER_ID, id);
+        }
+        if let Some(qty) = self.order_qty {
+            b.field_value(tag::ORDER_QTY, qty);
+        }
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for OrderCancelRequest {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::ORDER_CANCEL_REQUEST)?;
+        Ok(Self {
+            orig_cl_ord_id: msg.require(tag::ORIG_CL_ORD_ID)?.to_string(),
+            cl_ord_id: msg.require(tag::CL_ORD_ID)?.to_string(),
+            symbol: msg.require(tag::SYMBOL)?.to_string(),
+            side: require_side(msg)?,
+            transact_time: msg.require_timestamp(tag::TRANSACT_TIME)?,
+            order_id: msg.get(tag::ORDER_ID).map(String::from),
+            order_qty: optional_decimal(msg, tag::ORDER_QTY)?,
+        })
+    }
+}
+
+impl From<&OrderCancelRequest> for FixBuilder {
+    fn from(msg: &OrderCancelRequest) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+/// 取消拒否の応答対象 (`CxlRejResponseTo`, tag 434)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CxlRejResponseTo {
+    /// Order Cancel Request (F) への応答。
+    CancelRequest,
+    /// Order Cancel/Replace Request (G) への応答。
+    CancelReplaceRequest,
+}
+
+impl CxlRejResponseTo {
+    /// FIX 文字列から変換。
+    #[must_use]
+    pub fn from_fix(s: &str) -> Option<Self> {
+        match s {
+            "1" => Some(Self::CancelRequest),
+            "2" => Some(Self::CancelReplaceRequest),
+            _ => None,
+        }
+    }
+
+    /// FIX 文字列に変換。
+    #[must_use]
+    pub const fn to_fix(self) -> &'static str {
+        match self {
+            Self::CancelRequest => "1",
+            Self::CancelReplaceRequest => "2",
+        }
+    }
+}
+
+/// 取消拒否の理由 (`CxlRejReason`, tag 102)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CxlRejReason {
+    /// 0: 取消に間に合わない (約定済み等)。
+    TooLateToCancel,
+    /// 1: 注文が見つからない。
+    UnknownOrder,
+    /// 2: ブローカー / 取引所の判断。
+    BrokerOption,
+    /// 3: 既に取消中 / 訂正中。
+    AlreadyPending,
+    /// 4: 一括取消を処理できない。
+    UnableToProcessMassCancel,
+    /// 5: `OrigOrdModTime` (586) が一致しない。
+    OrigOrdModTimeMismatch,
+    /// 6: `ClOrdID` の重複。
+    DuplicateClOrdId,
+    /// 7: 価格が現在値を超える。
+    PriceExceedsCurrentPrice,
+    /// 8: 価格が値幅を超える。
+    PriceExceedsCurrentPriceBand,
+    /// 18: 呼値の単位が不正。
+    InvalidPriceIncrement,
+    /// 99: その他。
+    Other,
+}
+
+impl CxlRejReason {
+    /// タグ 102 の値から変換。
+    #[must_use]
+    pub const fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(Self::TooLateToCancel),
+            1 => Some(Self::UnknownOrder),
+            2 => Some(Self::BrokerOption),
+            3 => Some(Self::AlreadyPending),
+            4 => Some(Self::UnableToProcessMassCancel),
+            5 => Some(Self::OrigOrdModTimeMismatch),
+            6 => Some(Self::DuplicateClOrdId),
+            7 => Some(Self::PriceExceedsCurrentPrice),
+            8 => Some(Self::PriceExceedsCurrentPriceBand),
+            18 => Some(Self::InvalidPriceIncrement),
+            99 => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    /// タグ 102 の値。
+    #[must_use]
+    pub const fn code(self) -> u32 {
+        match self {
+            Self::TooLateToCancel => 0,
+            Self::UnknownOrder => 1,
+            Self::BrokerOption => 2,
+            Self::AlreadyPending => 3,
+            Self::UnableToProcessMassCancel => 4,
+            Self::OrigOrdModTimeMismatch => 5,
+            Self::DuplicateClOrdId => 6,
+            Self::PriceExceedsCurrentPrice => 7,
+            Self::PriceExceedsCurrentPriceBand => 8,
+            Self::InvalidPriceIncrement => 18,
+            Self::Other => 99,
+        }
+    }
+}
+
+/// Order Cancel Reject (35=9)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCancelReject {
+    /// ブローカー注文 ID (tag 37)。
+    pub order_id: String,
+    /// 取消要求の `ClOrdID` (tag 11)。
+    pub cl_ord_id: String,
+    /// 取消対象の `ClOrdID` (tag 41)。
+    pub orig_cl_ord_id: String,
+    /// 注文ステータス (tag 39)。
+    pub ord_status: OrdStatus,
+    /// 応答対象 (tag 434)。
+    pub response_to: CxlRejResponseTo,
+    /// 拒否理由 (tag 102)。
+    pub reason: Option<CxlRejReason>,
+    /// テキスト (tag 58)。
+    pub text: Option<String>,
+}
+
+impl OrderCancelReject {
+    /// `begin_string` を指定してビルダーに変換。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, msg_type::ORDER_CANCEL_REJECT);
+        b.field(tag::ORDER_ID, &self.order_id)
+            .field(tag::CL_ORD_ID, &self.cl_ord_id)
+            .field(tag::ORIG_CL_ORD_ID, &self.orig_cl_ord_id)
+            .field(tag::ORD_STATUS, &self.ord_status.to_fix().to_string())
+            .field(tag::CXL_REJ_RESPONSE_TO, self.response_to.to_fix());
+        if let Some(reason) = self.reason {
+            b.field_u64(tag::CXL_REJ_REASON, u64::from(reason.code()));
+        }
+        if let Some(text) = &self.text {
+            b.field(tag::TEXT, text);
+        }
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for OrderCancelReject {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::ORDER_CANCEL_REJECT)?;
+        Ok(Self {
+            order_id: msg.require(tag::ORDER_ID)?.to_string(),
+            cl_ord_id: msg.require(tag::CL_ORD_ID)?.to_string(),
+            orig_cl_ord_id: msg.require(tag::ORIG_CL_ORD_ID)?.to_string(),
+            ord_status: OrdStatus::from_fix(msg.require(tag::ORD_STATUS)?),
+            response_to: convert(msg, tag::CXL_REJ_RESPONSE_TO, CxlRejResponseTo::from_fix)?,
+            reason: optional(msg, tag::CXL_REJ_REASON, |v| {
+                v.parse().ok().and_then(CxlRejReason::from_code)
+            })?,
+            text: msg.get(tag::TEXT).map(String::from),
+        })
+    }
+}
+
+impl From<&OrderCancelReject> for FixBuilder {
+    fn from(msg: &OrderCancelReject) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+/// Order Status Request (35=H)。
+///
+/// 応答は `ExecType` I の `ExecutionReport`
+/// ([`crate::execution_report::ExecutionReport::is_status_report`])。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderStatusRequest {
+    /// 照会対象の `ClOrdID` (tag 11)。
+    pub cl_ord_id: String,
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// サイド (tag 54)。
+    pub side: Side,
+    /// ブローカー注文 ID (tag 37)。
+    pub order_id: Option<String>,
+    /// 照会 ID (tag 790)。応答に返される。
+    pub ord_status_req_id: Option<String>,
+}
+
+impl OrderStatusRequest {
+    /// `begin_string` を指定してビルダーに変換。
+    #[must_use]
+    pub fn to_builder(&self, begin_string: impl Into<BeginString>) -> FixBuilder {
+        let mut b = FixBuilder::new(begin_string, msg_type::ORDER_STATUS_REQUEST);
+        b.field(tag::CL_ORD_ID, &self.cl_ord_id)
+            .field(tag::SYMBOL, &self.symbol)
+            .field(tag::SIDE, alice_side_to_fix(self.side))
+            .field_opt(tag::ORDER_ID, self.order_id.as_deref())
+            .field_opt(tag::ORD_STATUS_REQ_ID, self.ord_status_req_id.as_deref());
+        b
+    }
+}
+
+impl TryFrom<&FixMessage> for OrderStatusRequest {
+    type Error = OrderMsgError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        expect_msg_type(msg, msg_type::ORDER_STATUS_REQUEST)?;
+        Ok(Self {
+            cl_ord_id: msg.require(tag::CL_ORD_ID)?.to_string(),
+            symbol: msg.require(tag::SYMBOL)?.to_string(),
+            side: require_side(msg)?,
+            order_id: msg.get(tag::ORDER_ID).map(String::from),
+            ord_status_req_id: msg.get(tag::ORD_STATUS_REQ_ID).map(String::from),
+        })
+    }
+}
+
+impl From<&OrderStatusRequest> for FixBuilder {
+    fn from(msg: &OrderStatusRequest) -> Self {
+        msg.to_builder(DEFAULT_BEGIN_STRING)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Field helpers
+// ---------------------------------------------------------------------------
+
+fn expect_msg_type(msg: &FixMessage, expected: &'static str) -> Result<(), OrderMsgError> {
+    if msg.msg_type == expected {
+        Ok(())
+    } else {
+        Err(OrderMsgError::WrongMsgType {
+            expected,
+            actual: msg.msg_type.clone(),
+        })
+    }
+}
+
+fn invalid(t: u32, value: &str) -> OrderMsgError {
+    OrderMsgError::InvalidField {
+        tag: t,
+        value: value.to_string(),
+    }
+}
+
+/// 必須フィールドを `f` で変換。
+fn convert<T>(
+    msg: &FixMessage,
+    t: u32,
+    f: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, OrderMsgError> {
+    let v = msg.require(t)?;
+    f(v).ok_or_else(|| invalid(t, v))
+}
+
+/// 任意フィールドを `f` で変換。存在して変換できない場合はエラー。
+fn optional<T>(
+    msg: &FixMessage,
+    t: u32,
+    f: impl FnOnce(&str) -> Option<T>,
+) -> Result<Option<T>, OrderMsgError> {
+    msg.get(t)
+        .map(|v| f(v).ok_or_else(|| invalid(t, v)))
+        .transpose()
+}
+
+fn require_side(msg: &FixMessage) -> Result<Side, OrderMsgError> {
+    convert(msg, tag::SIDE, fix_side_to_alice)
+}
+
+fn optional_decimal(msg: &FixMessage, t: u32) -> Result<Option<Decimal>, OrderMsgError> {
+    optional(msg, t, Decimal::parse)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::version::FixVersion;
+
+    const T0: u64 = 1_767_225_600_123_000_000; // 2026-01-01 00:00:00.123
+
+    fn nos() -> NewOrderSingle {
+        NewOrderSingle {
+            cl_ord_id: "C1".into(),
+            symbol: "BTCUSD".into(),
+            side: Side::Bid,
+            transact_time: T0,
+            ord_type: OrderType::Limit,
+            order_qty: Decimal::new(15, 1),
+            price: Some(Decimal::new(5_000_050, 2)),
+            time_in_force: Some(TimeInForce::IOC),
+            text: None,
+        }
+    }
+
+    fn wire(builder: &FixBuilder) -> FixMessage {
+        parser::parse(&builder.build()).unwrap()
+    }
+
+    #[test]
+    fn new_order_single_roundtrip() {
+        let msg = wire(&FixBuilder::from(&nos()));
+        assert_eq!(msg.msg_type, "D");
+        assert_eq!(msg.get(tag::PRICE), Some("50000.50"));
+        assert_eq!(NewOrderSingle::try_from(&msg).unwrap(), nos());
+    }
+
+    #[test]
+    fn new_order_single_market_without_price() {
+        let order = NewOrderSingle {
+            ord_type: OrderType::Market,
+            price: None,
+            time_in_force: None,
+            ..nos()
+        };
+        let msg = wire(&order.to_builder(FixVersion::Fix50));
+        assert_eq!(msg.begin_string, "FIXT.1.1");
+        assert!(!msg.contains(tag::PRICE));
+        assert_eq!(NewOrderSingle::try_from(&msg).unwrap(), order);
+    }
+
+    #[test]
+    fn new_order_single_errors() {
+        let msg = FixMessage::new("FIX.4.4", "8");
+        assert_eq!(
+            NewOrderSingle::try_from(&msg),
+            Err(OrderMsgError::WrongMsgType {
+                expected: "D",
+                actual: "8".into()
+            })
+        );
+
+        let mut msg = wire(&FixBuilder::from(&nos()));
+        msg.set(tag::SIDE, "X");
+        assert_eq!(
+            NewOrderSingle::try_from(&msg),
+            Err(OrderMsgError::InvalidField {
+                tag: tag::SIDE,
+                value: "X".into()
+            })
+        );
+
+        msg.remove(tag::CL_ORD_ID);
+        assert_eq!(
+            NewOrderSingle::try_from(&msg),
+            Err(OrderMsgError::MissingField(tag::CL_ORD_ID))
+        );
+    }
+
+    #[test]
+    fn cancel_request_roundtrip() {
+        let req = OrderCancelRequest {
+            orig_cl_ord_id: "C1".into(),
+            cl_ord_id: "C2".into(),
+            symbol: "BTCUSD".into(),
+            side: Side::Ask,
+            transact_time: T0,
+            order_id: Some("O9".into()),
+            order_qty: None,
+        };
+        let builder = FixBuilder::from(&req);
+        assert_eq!(builder.missing_required().len(), 4); // ヘッダのみ欠落
+        let msg = wire(&builder);
+        assert_eq!(OrderCancelRequest::try_from(&msg).unwrap(), req);
+    }
+
+    #[test]
+    fn status_request_roundtrip() {
+        let req = OrderStatusRequest {
+            cl_ord_id: "C1".into(),
+            symbol: "BTCUSD".into(),
+            side: Side::Bid,
+            order_id: None,
+            ord_status_req_id: Some("S1".into()),
+        };
+        let builder = FixBuilder::from(&req);
+        assert_eq!(builder.missing_required().len(), 4); // ヘッダのみ欠落
+        let msg = wire(&builder);
+        assert!(!msg.contains(tag::ORDER_ID));
+        assert_eq!(OrderStatusRequest::try_from(&msg).unwrap(), req);
+    }
+
+    #[test]
+    fn cancel_reject_roundtrip() {
+        let rej = OrderCancelReject {
+            order_id: "O9".into(),
+            cl_ord_id: "C2".into(),
+            orig_cl_ord_id: "C1".into(),
+            ord_status: OrdStatus::Filled,
+            response_to: CxlRejResponseTo::CancelRequest,
+            reason: Some(CxlRejReason::TooLateToCancel),
+            text: Some("too late".into()),
+        };
+        let msg = wire(&FixBuilder::from(&rej));
+        assert_eq!(msg.get(tag::CXL_REJ_RESPONSE_TO), Some("1"));
+        assert_eq!(msg.get(tag::CXL_REJ_REASON), Some("0"));
+        assert_eq!(OrderCancelReject::try_from(&msg).unwrap(), rej);
+
+        for code in [1, 6, 18, 99] {
+            assert_eq!(CxlRejReason::from_code(code).unwrap().code(), code);
+        }
+        let mut unknown = msg;
+        unknown.set(tag::CXL_REJ_REASON, "42");
+        assert_eq!(
+            OrderCancelReject::try_from(&unknown),
+            Err(OrderMsgError::InvalidField {
+                tag: tag::CXL_REJ_REASON,
+                value: "42".into()
+            })
+        );
+    }
+
+    #[test]
+    fn cancel_reject_invalid_response_to() {
+        let mut msg = FixMessage::new("FIX.4.4", "9");
+        msg.set(tag::ORDER_ID, "O")
+            .set(tag::CL_ORD_ID, "C")
+            .set(tag::ORIG_CL_ORD_ID, "P")
+            .set(tag::ORD_STATUS, "0")
+            .set(tag::CXL_REJ_RESPONSE_TO, "7");
+        assert_eq!(
+            OrderCancelReject::try_from(&msg),
+            Err(OrderMsgError::InvalidField {
+                tag: tag::CXL_REJ_RESPONSE_TO,
+                value: "7".into()
+            })
+        );
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            OrderMsgError::InvalidField {
+                tag: 54,
+                value: "X".into()
+            }
+            .to_string(),
+            "Invalid value for tag 54: X"
+        );
+    }
+}
@@ -15,6 +15,12 @@
 //! 4. The last field must be tag 10 (Checksum); the checksum is validated.
 //! 5. Tag 35 (`MsgType`) must be present among the body fields.
 //! 6. All other fields are collected into [`FixMessage::fields`].
+//! 7. Fields following a known `NoXxx` count tag (see
+//!    [`crate::repeating_group::STANDARD_GROUPS`]) are collected into
+//!    [`FixMessage::groups`]. The first field after the count tag marks the
+//!    start of every entry; the group ends at the first non-member tag
+//!    once the last entry has started. Before that, tags the definition
+//!    does not list stay in the current entry.
 //!
 //! ## Zero-copy design
 //!
@@ -22,13 +28,25 @@
 //! building an intermediate `Vec`. Each field slice (`&[u8]`) is interpreted
 //! as a UTF-8 string in-place; only the final owned values written into
 //! [`FixMessage`] allocate heap memory.
+//!
+//! ## Provenance
+//!
+//! [`parse_with_spans`] additionally records the byte offset and length of
+//! every field in [`FixMessage::spans`]. Offsets are derived from the field
+//! sub-slices themselves, so recording costs one `Vec` push per field.
 
-use crate::message::FixMessage;
+use crate::field_map::FieldMap;
+use crate::message::{FieldSpan, FixMessage};
+use crate::repeating_group::{find_group_def, GroupDef, GroupEntry};
 use crate::tag;
 
 /// SOH byte — the FIX field delimiter (ASCII 0x01).
 pub const SOH: u8 = 0x01;
 
+/// Default limit on the length of one inbound frame, in bytes, for readers
+/// that split a stream with [`declared_frame_len`].
+pub const DEFAULT_MAX_FRAME_LEN: usize = 1 << 20;
+
 /// Errors that can occur while parsing a FIX message.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseError {
@@ -51,6 +69,15 @@ pub enum ParseError {
     MalformedField(String),
     /// A tag number string cannot be parsed as a `u32`.
     InvalidTag(String),
+    /// A repeating group's declared entry count does not match the entries found.
+    GroupCountMismatch {
+        /// `NoXxx` count tag that introduced the group.
+        count_tag: u32,
+        /// Entry count declared by the count tag.
+        expected: usize,
+        /// Number of entries actually present.
+        actual: usize,
+    },
 }
 
 impl core::fmt::Display for ParseError {
@@ -68,6 +95,14 @@ impl core::fmt::Display for ParseError {
             }
             Self::MalformedField(s) => write!(f, "malformed field: {s}"),
             Self::InvalidTag(s) => write!(f, "invalid tag number: {s}"),
+            Self::GroupCountMismatch {
+                count_tag,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "repeating group {count_tag}: expected {expected} entries, found {actual}"
+            ),
         }
     }
 }
@@ -76,7 +111,7 @@ impl core::fmt::Display for ParseError {
 ///
 /// The FIX checksum is the sum of all byte values, modulo 256.
 #[inline(always)]
-fn compute_checksum(bytes: &[u8]) -> u8 {
+pub(crate) fn compute_checksum(bytes: &[u8]) -> u8 {
     let mut sum: u32 = 0;
     for &b in bytes {
         sum = sum.wrapping_add(b as u32);
@@ -89,7 +124,7 @@ fn compute_checksum(bytes: &[u8]) -> u8 {
 /// Both the tag and value are returned as zero-copy sub-slices of the input.
 /// The caller is responsible for converting the value slice to `&str` / `String`.
 #[inline(always)]
-fn split_field(field: &[u8]) -> Result<(u32, &[u8]), ParseError> {
+pub(crate) fn split_field(field: &[u8]) -> Result<(u32, &[u8]), ParseError> {
     // Find the '=' byte position.
     let eq = field
         .iter()
@@ -129,13 +164,13 @@ fn parse_tag_number(bytes: &[u8]) -> Option<u32> {
 ///
 /// Yields `&[u8]` sub-slices, each corresponding to one `tag=value` field.
 /// Empty sub-slices (e.g., from a trailing SOH) are skipped.
-struct FieldIter<'a> {
+pub(crate) struct FieldIter<'a> {
     remaining: &'a [u8],
 }
 
 impl<'a> FieldIter<'a> {
     #[inline(always)]
-    const fn new(input: &'a [u8]) -> Self {
+    pub(crate) const fn new(input: &'a [u8]) -> Self {
         Self { remaining: input }
     }
 }
@@ -170,6 +205,82 @@ impl<'a> Iterator for FieldIter<'a> {
     }
 }
 
+/// A repeating group being collected while iterating the body fields.
+struct OpenGroup {
+    def: &'static GroupDef,
+    /// Tag that starts each entry; taken from the first field of the group.
+    delimiter: Option<u32>,
+    expected: usize,
+    entries: Vec<GroupEntry>,
+}
+
+impl OpenGroup {
+    #[inline(always)]
+    const fn new(def: &'static GroupDef, expected: usize) -> Self {
+        Self {
+            def,
+            delimiter: None,
+            expected,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Try to add a field to the group.
+    ///
+    /// Returns `false` if the field does not belong to the group, which
+    /// terminates it. A tag the definition does not list is kept in the
+    /// current entry while more entries are due, since counterparties send
+    /// members the table does not enumerate; after the last entry has
+    /// started it ends the group.
+    #[inline(always)]
+    fn push(&mut self, t: u32, value: &str) -> bool {
+        if !self.def.contains(t) {
+            if t == tag::CHECKSUM || self.entries.len() >= self.expected {
+                return false;
+            }
+            return match self.entries.last_mut() {
+                Some(entry) if !entry.fields.contains_key(&t) => {
+                    entry.set(t, value);
+                    true
+                }
+                _ => false,
+            };
+        }
+        let delimiter = *self.delimiter.get_or_insert(t);
+        if t == delimiter {
+            self.entries.push(GroupEntry::new());
+        } else if self
+            .entries
+            .last()
+            .is_none_or(|e| e.fields.contains_key(&t))
+        {
+            // A repeated member tag without a new delimiter ends the group.
+            return false;
+        }
+        if let Some(entry) = self.entries.last_mut() {
+            entry.set(t, value);
+        }
+        true
+    }
+
+    /// Validate the entry count and store the entries into `msg_groups`.
+    #[inline(always)]
+    fn close(
+        self,
+        msg_groups: &mut std::collections::HashMap<u32, Vec<GroupEntry>>,
+    ) -> Result<(), ParseError> {
+        if self.entries.len() != self.expected {
+            return Err(ParseError::GroupCountMismatch {
+                count_tag: self.def.count_tag,
+                expected: self.expected,
+                actual: self.entries.len(),
+            });
+        }
+        msg_groups.insert(self.def.count_tag, self.entries);
+        Ok(())
+    }
+}
+
 /// Parse a raw FIX message byte slice into a [`FixMessage`].
 ///
 /// Validates the `BeginString`, `BodyLength`, and Checksum fields.
@@ -184,6 +295,75 @@ impl<'a> Iterator for FieldIter<'a> {
 /// Returns a [`ParseError`] if the input is malformed, missing required
 /// fields, or fails checksum validation.
 pub fn parse(input: &[u8]) -> Result<FixMessage, ParseError> {
+    parse_impl(input, false)
+}
+
+/// Parse a raw FIX message like [`parse`], additionally recording the byte
+/// range of every field in [`FixMessage::spans`].
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] under the same conditions as [`parse`].
+pub fn parse_with_spans(input: &[u8]) -> Result<FixMessage, ParseError> {
+    parse_impl(input, true)
+}
+
+/// Length of the frame at the start of `buf`, read from its `BeginString`
+/// (8) and `BodyLength` (9) prefix, or `None` while `buf` does not yet hold
+/// the whole frame.
+///
+/// Splits a byte stream (e.g. a TCP connection) into frames. Only the
+/// prefix is inspected; the frame itself is checked by [`parse`].
+///
+/// # Errors
+///
+/// Same as [`declared_frame_len`].
+pub fn frame_len(buf: &[u8]) -> Result<Option<usize>, ParseError> {
+    Ok(declared_frame_len(buf)?.filter(|&len| buf.len() >= len))
+}
+
+/// Length of the frame starting `buf` as declared by its `BodyLength` (9).
+///
+/// Available once the `BeginString` / `BodyLength` prefix has arrived even
+/// if the rest of the frame has not; `None` before that. A length that
+/// does not fit in `usize` saturates, so a size limit rejects it.
+///
+/// # Errors
+///
+/// [`ParseError::MissingBeginString`] when `buf` does not start with tag 8,
+/// [`ParseError::MissingBodyLength`] when tag 9 does not follow it or its
+/// value is not a number.
+pub fn declared_frame_len(buf: &[u8]) -> Result<Option<usize>, ParseError> {
+    if !b"8=".starts_with(&buf[..buf.len().min(2)]) {
+        return Err(ParseError::MissingBeginString);
+    }
+    let Some(end8) = buf.iter().position(|&b| b == SOH) else {
+        return Ok(None);
+    };
+    let rest = &buf[end8 + 1..];
+    if !b"9=".starts_with(&rest[..rest.len().min(2)]) {
+        return Err(ParseError::MissingBodyLength);
+    }
+    let Some(end9) = rest.iter().position(|&b| b == SOH) else {
+        return Ok(None);
+    };
+    let body_len = parse_body_length(&rest[2..end9]).ok_or(ParseError::MissingBodyLength)?;
+    // "8=...\x01" + "9=...\x01" + body + "10=XXX\x01"
+    Ok(Some((end8 + end9 + 2 + 7).saturating_add(body_len)))
+}
+
+/// Offset/length of `field` relative to the start of `input`.
+///
+/// `field` must be a sub-slice of `input` (as yielded by [`FieldIter`]).
+#[inline(always)]
+fn field_span(input: &[u8], field: &[u8]) -> FieldSpan {
+    FieldSpan {
+        offset: field.as_ptr() as usize - input.as_ptr() as usize,
+        len: field.len(),
+    }
+}
+
+fn parse_impl(input: &[u8], record_spans: bool) -> Result<FixMessage, ParseError> {
     if input.is_empty() {
         return Err(ParseError::EmptyInput);
     }
@@ -228,13 +408,33 @@ pub fn parse(input: &[u8]) -> Result<FixMessage, ParseError> {
 
     // --- Collect body fields and validate checksum tag ---
     // We do not know how many fields there are ahead of time, so allocate
-    // a HashMap with a small initial capacity typical of FIX messages.
+    // a FieldMap with a small initial capacity typical of FIX messages.
     let mut msg_type = String::new();
-    let mut fields = std::collections::HashMap::with_capacity(16);
+    let mut fields = FieldMap::with_capacity(16);
+    let mut groups = std::collections::HashMap::new();
+    let mut open_group: Option<OpenGroup> = None;
     let mut saw_checksum = false;
+    let mut spans = Vec::new();
+    if record_spans {
+        spans.reserve(16);
+        spans.push((tag::BEGIN_STRING, field_span(input, field0)));
+        spans.push((tag::BODY_LENGTH, field_span(input, field1)));
+    }
 
     for field_bytes in iter {
         let (t, v_bytes) = split_field(field_bytes)?;
+        if record_spans {
+            spans.push((t, field_span(input, field_bytes)));
+        }
+        if let Some(group) = open_group.as_mut() {
+            let value = core::str::from_utf8(v_bytes).unwrap_or("");
+            if group.push(t, value) {
+                continue;
+            }
+            if let Some(group) = open_group.take() {
+                group.close(&mut groups)?;
+            }
+        }
         match t {
             _ if t == tag::CHECKSUM => {
                 // Validate the checksum value without allocating on the error path.
@@ -257,11 +457,23 @@ pub fn parse(input: &[u8]) -> Result<FixMessage, ParseError> {
             }
             _ => {
                 let value = core::str::from_utf8(v_bytes).unwrap_or("").to_string();
+                if let Some(def) = find_group_def(t) {
+                    let expected = value
+                        .parse::<usize>()
+                        .map_err(|_| ParseError::MalformedField(format!("{t}={value}")))?;
+                    if expected > 0 {
+                        open_group = Some(OpenGroup::new(def, expected));
+                    }
+                }
                 fields.insert(t, value);
             }
         }
     }
 
+    if let Some(group) = open_group {
+        group.close(&mut groups)?;
+    }
+
     if !saw_checksum {
         return Err(ParseError::MissingChecksum);
     }
@@ -272,12 +484,14 @@ pub fn parse(input: &[u8]) -> Result<FixMessage, ParseError> {
         begin_string,
         msg_type,
         fields,
+        groups,
+        spans,
     })
 }
 
 /// Parse a decimal `usize` from ASCII digit bytes (used for `BodyLength`).
 #[inline(always)]
-fn parse_body_length(bytes: &[u8]) -> Option<usize> {
+pub(crate) fn parse_body_length(bytes: &[u8]) -> Option<usize> {
     if bytes.is_empty() {
         return None;
     }
@@ -318,10 +532,11 @@ mod tests {
     use super::*;
     use crate::builder::FixBuilder;
     use crate::tag;
+    use crate::version::{BeginString, FixVersion};
 
     /// Build a minimal valid FIX message byte vector for testing.
     fn make_valid_message() -> Vec<u8> {
-        FixBuilder::new("FIX.4.4", "0")
+        FixBuilder::new(BeginString::Fix44, "0")
             .field(tag::SENDER_COMP_ID, "ALICE")
             .field(tag::TARGET_COMP_ID, "BROKER")
             .field(tag::MSG_SEQ_NUM, "1")
@@ -368,7 +583,7 @@ mod tests {
 
     #[test]
     fn test_parse_all_tags_present() {
-        let bytes = FixBuilder::new("FIX.4.4", "D")
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
             .field(tag::SENDER_COMP_ID, "ME")
             .field(tag::TARGET_COMP_ID, "YOU")
             .field(tag::MSG_SEQ_NUM, "7")
@@ -445,6 +660,19 @@ mod tests {
         assert_eq!(format!("{err}"), "invalid tag number: abc");
     }
 
+    #[test]
+    fn test_parse_error_display_group_count_mismatch() {
+        let err = ParseError::GroupCountMismatch {
+            count_tag: 453,
+            expected: 2,
+            actual: 1,
+        };
+        assert_eq!(
+            format!("{err}"),
+            "repeating group 453: expected 2 entries, found 1"
+        );
+    }
+
     #[test]
     fn test_parse_error_clone_and_eq() {
         let a = ParseError::EmptyInput;
@@ -482,7 +710,7 @@ mod tests {
 
     #[test]
     fn test_parse_fixt11_version() {
-        let bytes = FixBuilder::new("FIXT.1.1", "0")
+        let bytes = FixBuilder::new(FixVersion::Fix50, "0")
             .field(tag::SENDER_COMP_ID, "A")
             .field(tag::TARGET_COMP_ID, "B")
             .field(tag::MSG_SEQ_NUM, "1")
@@ -494,7 +722,7 @@ mod tests {
 
     #[test]
     fn test_parse_preserves_text_field() {
-        let bytes = FixBuilder::new("FIX.4.4", "0")
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
             .field(tag::SENDER_COMP_ID, "S")
             .field(tag::TEXT, "Hello FIX World")
             .build();
@@ -505,7 +733,7 @@ mod tests {
     #[test]
     fn test_parse_checksum_integrity() {
         // Build a valid message, verify the checksum is correct by re-parsing.
-        let bytes = FixBuilder::new("FIX.4.4", "A")
+        let bytes = FixBuilder::new(BeginString::Fix44, "A")
             .field(tag::SENDER_COMP_ID, "ALICE")
             .field(tag::TARGET_COMP_ID, "EXCHANGE")
             .field(tag::MSG_SEQ_NUM, "999")
@@ -518,7 +746,7 @@ mod tests {
     fn test_field_iter_skips_empty_segments() {
         // Build a message with the builder (no consecutive SOH issue),
         // but confirm parsing succeeds as expected.
-        let bytes = FixBuilder::new("FIX.4.4", "0")
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
             .field(tag::SENDER_COMP_ID, "X")
             .build();
         let msg = parse(&bytes).expect("should parse");
@@ -534,6 +762,186 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_party_ids_group() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::CL_ORD_ID, "ORD-1")
+            .field(tag::NO_PARTY_IDS, "2")
+            .field(tag::PARTY_ID, "FIRM-A")
+            .field(tag::PARTY_ID_SOURCE, "D")
+            .field(tag::PARTY_ROLE, "1")
+            .field(tag::PARTY_ID, "TRADER-7")
+            .field(tag::PARTY_ROLE, "11")
+            .field(tag::SYMBOL, "BTCUSD")
+            .build();
+        let msg = parse(&bytes).expect("should parse");
+
+        let parties = msg.groups(tag::NO_PARTY_IDS);
+        assert_eq!(parties.len(), 2);
+        assert_eq!(parties[0].get(tag::PARTY_ID), Some("FIRM-A"));
+        assert_eq!(parties[0].get(tag::PARTY_ID_SOURCE), Some("D"));
+        assert_eq!(parties[0].get_u64(tag::PARTY_ROLE), Some(1));
+        assert_eq!(parties[1].get(tag::PARTY_ID), Some("TRADER-7"));
+        assert_eq!(parties[1].get(tag::PARTY_ID_SOURCE), None);
+        // Count tag stays in the flat fields; members do not.
+        assert_eq!(msg.get_u64(tag::NO_PARTY_IDS), Some(2));
+        assert_eq!(msg.get(tag::PARTY_ID), None);
+        // The first non-member tag ends the group.
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+    }
+
+    #[test]
+    fn test_parse_group_keeps_unlisted_member_tags() {
+        // 276 (QuoteCondition) and 461 (CFICode) are not in the member lists.
+        let bytes = FixBuilder::new(BeginString::Fix44, "W")
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(tag::NO_MD_ENTRIES, "2")
+            .field(tag::MD_ENTRY_TYPE, "0")
+            .field(tag::MD_ENTRY_PX, "100")
+            .field(tag::MD_ENTRY_DATE, "20260101")
+            .field(tag::MD_ENTRY_TIME, "09:00:00")
+            .field(276, "A")
+            .field(tag::MD_ENTRY_TYPE, "1")
+            .field(tag::MD_ENTRY_PX, "101")
+            .field(tag::MD_ENTRY_DATE, "20260101")
+            .field(tag::MD_ENTRY_TIME, "09:00:01")
+            .build();
+        let msg = parse(&bytes).expect("should parse");
+        let entries = msg.groups(tag::NO_MD_ENTRIES);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].get(276), Some("A"));
+        assert_eq!(entries[0].get(tag::MD_ENTRY_TIME), Some("09:00:00"));
+        assert_eq!(entries[1].get(tag::MD_ENTRY_TIME), Some("09:00:01"));
+
+        let bytes = FixBuilder::new(BeginString::Fix44, "V")
+            .field(tag::NO_RELATED_SYM, "2")
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(461, "FXXXXX")
+            .field(tag::SYMBOL, "ETHUSD")
+            .field(461, "FXXXXX")
+            .build();
+        let msg = parse(&bytes).expect("should parse");
+        let entries = msg.groups(tag::NO_RELATED_SYM);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].get(461), Some("FXXXXX"));
+        // After the last entry has started, an unlisted tag ends the group.
+        assert_eq!(entries[1].get(461), None);
+        assert_eq!(msg.get(461), Some("FXXXXX"));
+    }
+
+    #[test]
+    fn test_parse_md_entries_group_starting_with_update_action() {
+        // Incremental refreshes start each entry with MDUpdateAction (279).
+        let bytes = FixBuilder::new(BeginString::Fix44, "X")
+            .field(tag::NO_MD_ENTRIES, "2")
+            .field(tag::MD_UPDATE_ACTION, "0")
+            .field(tag::MD_ENTRY_TYPE, "0")
+            .field(tag::MD_ENTRY_PX, "100")
+            .field(tag::MD_UPDATE_ACTION, "2")
+            .field(tag::MD_ENTRY_TYPE, "1")
+            .field(tag::MD_ENTRY_PX, "101")
+            .build();
+        let msg = parse(&bytes).expect("should parse");
+
+        let entries = msg.groups(tag::NO_MD_ENTRIES);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].get_i64(tag::MD_ENTRY_PX), Some(100));
+        assert_eq!(entries[1].get(tag::MD_UPDATE_ACTION), Some("2"));
+        assert_eq!(entries[1].get(tag::MD_ENTRY_TYPE), Some("1"));
+    }
+
+    #[test]
+    fn test_parse_group_count_mismatch() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::NO_PARTY_IDS, "3")
+            .field(tag::PARTY_ID, "A")
+            .field(tag::PARTY_ID, "B")
+            .build();
+        assert_eq!(
+            parse(&bytes),
+            Err(ParseError::GroupCountMismatch {
+                count_tag: tag::NO_PARTY_IDS,
+                expected: 3,
+                actual: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_group_zero_count() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::NO_PARTY_IDS, "0")
+            .build();
+        let msg = parse(&bytes).expect("should parse");
+        assert!(msg.groups(tag::NO_PARTY_IDS).is_empty());
+    }
+
+    #[test]
+    fn test_parse_group_invalid_count() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::NO_PARTY_IDS, "x")
+            .build();
+        assert!(matches!(parse(&bytes), Err(ParseError::MalformedField(_))));
+    }
+
+    #[test]
+    fn test_parse_without_spans_records_nothing() {
+        let msg = parse(&make_valid_message()).unwrap();
+        assert!(msg.spans.is_empty());
+    }
+
+    #[test]
+    fn test_parse_with_spans_locates_fields() {
+        let bytes = make_valid_message();
+        let msg = parse_with_spans(&bytes).expect("should parse");
+
+        let span = msg.span(tag::SENDER_COMP_ID).unwrap();
+        assert_eq!(&bytes[span.offset..span.offset + span.len], b"49=ALICE");
+
+        let span = msg.span(tag::BEGIN_STRING).unwrap();
+        assert_eq!(span.offset, 0);
+        assert_eq!(&bytes[..span.len], b"8=FIX.4.4");
+
+        // The checksum field is the final 7 bytes minus the SOH.
+        let span = msg.span(tag::CHECKSUM).unwrap();
+        assert_eq!(span.offset, bytes.len() - 7);
+        assert_eq!(span.len, 6);
+
+        // Every field is recorded, in wire order.
+        let tags: Vec<u32> = msg.spans.iter().map(|&(t, _)| t).collect();
+        assert_eq!(
+            tags,
+            vec![
+                tag::BEGIN_STRING,
+                tag::BODY_LENGTH,
+                tag::MSG_TYPE,
+                tag::SENDER_COMP_ID,
+                tag::TARGET_COMP_ID,
+                tag::MSG_SEQ_NUM,
+                tag::SENDING_TIME,
+                tag::CHECKSUM,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_with_spans_includes_group_members() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::NO_PARTY_IDS, "2")
+            .field(tag::PARTY_ID, "A")
+            .field(tag::PARTY_ID, "BB")
+            .build();
+        let msg = parse_with_spans(&bytes).unwrap();
+        let party_spans: Vec<FieldSpan> = msg
+            .spans
+            .iter()
+            .filter(|(t, _)| *t == tag::PARTY_ID)
+            .map(|&(_, s)| s)
+            .collect();
+        assert_eq!(party_spans.len(), 2);
+        assert_eq!(party_spans[1].len, "448=BB".len());
+    }
+
     #[test]
     fn test_compute_checksum_empty() {
         assert_eq!(compute_checksum(&[]), 0);
@@ -618,4 +1026,35 @@ mod tests {
         let result = split_field(b"no_equals_here");
         assert!(matches!(result, Err(ParseError::MalformedField(_))));
     }
+
+    #[test]
+    fn test_frame_len_splits_stream() {
+        let frame = make_valid_message();
+        let mut stream = frame.clone();
+        stream.extend_from_slice(&frame);
+        assert_eq!(frame_len(&stream), Ok(Some(frame.len())));
+        for cut in [0, 1, 5, 12, frame.len() - 1] {
+            assert_eq!(frame_len(&frame[..cut]), Ok(None), "cut at {cut}");
+        }
+        assert!(parse(&stream[..frame.len()]).is_ok());
+        assert_eq!(declared_frame_len(&frame[..20]), Ok(Some(frame.len())));
+    }
+
+    #[test]
+    fn test_frame_len_rejects_garbage() {
+        assert_eq!(frame_len(b"35=0\x01"), Err(ParseError::MissingBeginString));
+        assert_eq!(
+            frame_len(b"8=FIX.4.4\x0135=0\x01"),
+            Err(ParseError::MissingBodyLength)
+        );
+        assert_eq!(
+            frame_len(b"8=FIX.4.4\x019=x\x01"),
+            Err(ParseError::MissingBodyLength)
+        );
+        // An absurd BodyLength saturates instead of reading as incomplete.
+        assert_eq!(
+            declared_frame_len(b"8=FIX.4.4\x019=18446744073709551615\x01"),
+            Ok(Some(usize::MAX))
+        );
+    }
 }
@@ -0,0 +1,415 @@
+//! Drop-Copy Reconciliation
+//!
+//! 取引セッションで受けた `ExecutionReport` と、独立したドロップコピー
+//! セッションで受けた写しを突き合わせる。どちらか一方にしか現れない約定は
+//! 規制上の重要な統制対象 (約定漏れ・身に覚えのない約定) になる。
+//!
+//! [`Reconciler::record`] に両セッションの約定 (`LastQty` > 0) を流し込むと、
+//! 反対側の未照合分と `ExecID` で、一致しなければ [`ExecFingerprint`]
+//! (`OrderID` / `Symbol` / `Side` / `LastQty` / `LastPx`) で照合する。
+//! 照合ウィンドウ内に相手が現れなかった約定は [`Reconciler::poll`] が
+//! [`Unmatched`] として返す。時刻は呼び出し側が供給するナノ秒 (`now_ns`)。
+//!
+//! 照合済みの `ExecID` は受信元ごとに直近 [`DEFAULT_MATCHED_WINDOW`] 件
+//! 覚えておき、`PossDupFlag` 付きの再送を [`ReconcileOutcome::Duplicate`]
+//! として扱う。
+
+use std::collections::VecDeque;
+
+use crate::execution_report::ExecutionReport;
+use crate::session::RecentIds;
+
+/// 重複検出のため受信元ごとに覚えておく照合済み `ExecID` の件数
+/// ([`Reconciler::set_matched_window`] で変更)。
+pub const DEFAULT_MATCHED_WINDOW: usize = 10_000;
+
+/// 約定の受信元。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExecSource {
+    /// 取引セッション。
+    Trading,
+    /// ドロップコピーセッション。
+    DropCopy,
+}
+
+impl ExecSource {
+    /// 照合相手の受信元。
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Trading => Self::DropCopy,
+            Self::DropCopy => Self::Trading,
+        }
+    }
+}
+
+/// `ExecID` が両セッションで異なる場合に使う約定の指紋。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecFingerprint {
+    /// 注文 ID (tag 37)。
+    pub order_id: String,
+    /// シンボル (tag 55)。
+    pub symbol: String,
+    /// サイド (tag 54)。
+    pub side: String,
+    /// 約定数量 (tag 32)。
+    pub last_qty: Option<f64>,
+    /// 約定価格 (tag 31)。
+    pub last_px: Option<f64>,
+}
+
+impl ExecFingerprint {
+    /// `report` の指紋。
+    #[must_use]
+    pub fn of(report: &ExecutionReport) -> Self {
+        Self {
+            order_id: report.order_id.clone(),
+            symbol: report.symbol.clone(),
+            side: report.side.clone(),
+            last_qty: report.last_qty,
+            last_px: report.last_px,
+        }
+    }
+}
+
+/// 照合に使った鍵。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKey {
+    /// `ExecID` が一致。
+    ExecId,
+    /// `ExecID` は異なるが指紋が一致。
+    Fingerprint,
+}
+
+/// [`Reconciler::record`] の結果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconcileOutcome {
+    /// 約定ではない (`LastQty` が無いか 0) ため対象外。
+    Ignored,
+    /// 同じ受信元で同じ `ExecID` が未照合のまま残っているか、最近照合
+    /// 済み (再送)。
+    Duplicate,
+    /// 反対側を待つ。
+    Pending,
+    /// 反対側の未照合分と一致した。
+    Matched {
+        /// 照合に使った鍵。
+        key: MatchKey,
+        /// 先に届いた側からの遅れ (ns)。
+        lag_ns: u64,
+    },
+}
+
+/// 照合ウィンドウ内に相手が現れなかった約定。
+#[derive(Debug, Clone)]
+pub struct Unmatched {
+    /// 受信元。
+    pub source: ExecSource,
+    /// 受信時刻 (ns)。
+    pub received_ns: u64,
+    /// 約定。
+    pub report: ExecutionReport,
+}
+
+/// 照合の累計。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconcileStats {
+    /// `ExecID` で照合した件数。
+    pub matched_exec_id: u64,
+    /// 指紋で照合した件数。
+    pub matched_fingerprint: u64,
+    /// 取引セッションにしか現れなかった件数。
+    pub unmatched_trading: u64,
+    /// ドロップコピーにしか現れなかった件数。
+    pub unmatched_drop_copy: u64,
+    /// 再送として無視した件数。
+    pub duplicates: u64,
+}
+
+/// 未照合の約定。
+#[derive(Debug, Clone)]
+struct Pending {
+    source: ExecSource,
+    received_ns: u64,
+    fingerprint: ExecFingerprint,
+    report: ExecutionReport,
+}
+
+/// 取引セッションとドロップコピーの約定照合エンジン。
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    window_ns: u64,
+    /// 到着順の未照合分。
+    pending: VecDeque<Pending>,
+    /// 取引セッション側で照合済みの `ExecID`。
+    matched_trading: RecentIds,
+    /// ドロップコピー側で照合済みの `ExecID`。
+    matched_drop_copy: RecentIds,
+    stats: ReconcileStats,
+}
+
+impl Reconciler {
+    /// 照合ウィンドウ `window_ns` で作成。
+    #[must_use]
+    pub fn new(window_ns: u64) -> Self {
+        Self {
+            window_ns,
+            pending: VecDeque::new(),
+            matched_trading: RecentIds::new(DEFAULT_MATCHED_WINDOW),
+            matched_drop_copy: RecentIds::new(DEFAULT_MATCHED_WINDOW),
+            stats: ReconcileStats {
+                matched_exec_id: 0,
+                matched_fingerprint: 0,
+                unmatched_trading: 0,
+                unmatched_drop_copy: 0,
+                duplicates: 0,
+            },
+        }
+    }
+
+    /// 照合ウィンドウ (ns)。
+    #[must_use]
+    pub const fn window_ns(&self) -> u64 {
+        self.window_ns
+    }
+
+    /// 受信元ごとに覚えておく照合済み `ExecID` の件数
+    /// (既定は [`DEFAULT_MATCHED_WINDOW`]、0 で再送検出を照合前に限る)。
+    /// 縮めると古いものから忘れる。
+    pub fn set_matched_window(&mut self, window: usize) {
+        self.matched_trading.set_capacity(window);
+        self.matched_drop_copy.set_capacity(window);
+    }
+
+    const fn matched(&mut self, source: ExecSource) -> &mut RecentIds {
+        match source {
+            ExecSource::Trading => &mut self.matched_trading,
+            ExecSource::DropCopy => &mut self.matched_drop_copy,
+        }
+    }
+
+    /// `source` で `now_ns` に受けた `report` を照合。
+    ///
+    /// ウィンドウを過ぎた未照合分は照合相手にしない (次の
+    /// [`Self::poll`] で報告される)。
+    pub fn record(
+        &mut self,
+        source: ExecSource,
+        report: &ExecutionReport,
+        now_ns: u64,
+    ) -> ReconcileOutcome {
+        if !report.last_qty.is_some_and(|q| q > 0.0) {
+            return ReconcileOutcome::Ignored;
+        }
+        if self.matched(source).contains(&report.exec_id)
+            || self
+                .pending
+                .iter()
+                .any(|p| p.source == source && p.report.exec_id == report.exec_id)
+        {
+            self.stats.duplicates += 1;
+            return ReconcileOutcome::Duplicate;
+        }
+
+        let fingerprint = ExecFingerprint::of(report);
+        let window = self.window_ns;
+        let live = |p: &&Pending| {
+            p.source == source.other() && now_ns.saturating_sub(p.received_ns) <= window
+        };
+        let found = self
+            .pending
+            .iter()
+            .position(|p| live(&p) && p.report.exec_id == report.exec_id)
+            .map(|i| (i, MatchKey::ExecId))
+            .or_else(|| {
+                self.pending
+                    .iter()
+                    .position(|p| live(&p) && p.fingerprint == fingerprint)
+                    .map(|i| (i, MatchKey::Fingerprint))
+            });
+
+        let Some((other, key)) = found.and_then(|(i, key)| Some((self.pending.remove(i)?, key)))
+        else {
+            self.pending.push_back(Pending {
+                source,
+                received_ns: now_ns,
+                fingerprint,
+                report: report.clone(),
+            });
+            return ReconcileOutcome::Pending;
+        };
+        match key {
+            MatchKey::ExecId => self.stats.matched_exec_id += 1,
+            MatchKey::Fingerprint => self.stats.matched_fingerprint += 1,
+        }
+        self.matched(source).insert(report.exec_id.clone());
+        self.matched(other.source)
+            .insert(other.report.exec_id.clone());
+        ReconcileOutcome::Matched {
+            key,
+            lag_ns: now_ns.saturating_sub(other.received_ns),
+        }
+    }
+
+    /// ウィンドウを過ぎた未照合分を取り出す (到着順)。
+    pub fn poll(&mut self, now_ns: u64) -> Vec<Unmatched> {
+        let window = self.window_ns;
+        let (expired, live): (VecDeque<_>, VecDeque<_>) = self
+            .pending
+            .drain(..)
+            .partition(|p| now_ns.saturating_sub(p.received_ns) > window);
+        self.pending = live;
+        expired
+            .into_iter()
+            .map(|p| {
+                match p.source {
+                    ExecSource::Trading => self.stats.unmatched_trading += 1,
+                    ExecSource::DropCopy => self.stats.unmatched_drop_copy += 1,
+                }
+                Unmatched {
+                    source: p.source,
+                    received_ns: p.received_ns,
+                    report: p.report,
+                }
+            })
+            .collect()
+    }
+
+    /// 未照合の件数。
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 照合の累計。
+    #[must_use]
+    pub const fn stats(&self) -> ReconcileStats {
+        self.stats
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::FixMessage;
+    use crate::tag;
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn fill(exec_id: &str, qty: &str, px: &str) -> ExecutionReport {
+        let mut msg = FixMessage::new("FIX.4.4", "8");
+        msg.set(tag::ORDER_ID, "O1")
+            .set(tag::CL_ORD_ID, "C1")
+            .set(tag::EXEC_ID, exec_id)
+            .set(tag::EXEC_TYPE, "1")
+            .set(tag::ORD_STATUS, "1")
+            .set(tag::SYMBOL, "7203")
+            .set(tag::SIDE, "1")
+            .set(tag::LAST_QTY, qty)
+            .set(tag::LAST_PX, px);
+        ExecutionReport::from_message(&msg).unwrap()
+    }
+
+    #[test]
+    fn matches_by_exec_id_then_fingerprint() {
+        let mut r = Reconciler::new(5 * SEC);
+        assert_eq!(
+            r.record(ExecSource::Trading, &fill("E1", "100", "2500"), 0),
+            ReconcileOutcome::Pending
+        );
+        assert_eq!(
+            r.record(ExecSource::DropCopy, &fill("E1", "100", "2500"), SEC),
+            ReconcileOutcome::Matched {
+                key: MatchKey::ExecId,
+                lag_ns: SEC
+            }
+        );
+
+        // ドロップコピー側が独自の ExecID を振る場合
+        r.record(ExecSource::DropCopy, &fill("DC-9", "50", "2501"), 2 * SEC);
+        assert_eq!(
+            r.record(ExecSource::Trading, &fill("E2", "50", "2501"), 3 * SEC),
+            ReconcileOutcome::Matched {
+                key: MatchKey::Fingerprint,
+                lag_ns: SEC
+            }
+        );
+        assert_eq!(r.pending(), 0);
+        let stats = r.stats();
+        assert_eq!((stats.matched_exec_id, stats.matched_fingerprint), (1, 1));
+    }
+
+    #[test]
+    fn reports_unmatched_after_window() {
+        let mut r = Reconciler::new(5 * SEC);
+        r.record(ExecSource::Trading, &fill("E1", "100", "2500"), 0);
+        r.record(ExecSource::DropCopy, &fill("E7", "10", "9"), 4 * SEC);
+        assert!(r.poll(5 * SEC).is_empty());
+
+        let out = r.poll(6 * SEC);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].source, ExecSource::Trading);
+        assert_eq!(out[0].report.exec_id, "E1");
+
+        // 期限切れ後に届いた写しは照合しない
+        let late = fill("E7", "10", "9");
+        assert_eq!(
+            r.record(ExecSource::Trading, &late, 10 * SEC),
+            ReconcileOutcome::Pending
+        );
+        let out = r.poll(16 * SEC);
+        assert_eq!(out.len(), 2);
+        let stats = r.stats();
+        assert_eq!((stats.unmatched_trading, stats.unmatched_drop_copy), (2, 1));
+    }
+
+    #[test]
+    fn ignores_non_fills_and_duplicates() {
+        let mut r = Reconciler::new(SEC);
+        let mut ack = fill("E0", "0", "0");
+        ack.last_qty = None;
+        assert_eq!(
+            r.record(ExecSource::Trading, &ack, 0),
+            ReconcileOutcome::Ignored
+        );
+        r.record(ExecSource::Trading, &fill("E1", "1", "1"), 0);
+        assert_eq!(
+            r.record(ExecSource::Trading, &fill("E1", "1", "1"), 1),
+            ReconcileOutcome::Duplicate
+        );
+        assert_eq!(r.pending(), 1);
+        assert_eq!(r.stats().duplicates, 1);
+    }
+
+    #[test]
+    fn resends_of_matched_executions_are_duplicates() {
+        let mut r = Reconciler::new(5 * SEC);
+        r.record(ExecSource::Trading, &fill("E1", "100", "2500"), 0);
+        r.record(ExecSource::DropCopy, &fill("DC-1", "100", "2500"), SEC);
+        // 照合後の PossDup 再送は、どちらの側でも未照合にならない
+        for (source, exec_id) in [(ExecSource::Trading, "E1"), (ExecSource::DropCopy, "DC-1")] {
+            assert_eq!(
+                r.record(source, &fill(exec_id, "100", "2500"), 2 * SEC),
+                ReconcileOutcome::Duplicate
+            );
+        }
+        assert_eq!(r.pending(), 0);
+        assert!(r.poll(20 * SEC).is_empty());
+        assert_eq!(r.stats().duplicates, 2);
+
+        // 覚えておく件数を超えた古い ExecID は忘れる
+        r.set_matched_window(1);
+        r.record(ExecSource::Trading, &fill("E2", "1", "1"), 30 * SEC);
+        r.record(ExecSource::DropCopy, &fill("E2", "1", "1"), 30 * SEC);
+        assert_eq!(
+            r.record(ExecSource::Trading, &fill("E1", "100", "2500"), 31 * SEC),
+            ReconcileOutcome::Pending
+        );
+    }
+}
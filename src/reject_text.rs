@@ -0,0 +1,240 @@
+//! Reject Code Extraction
+//!
+//! 多くの取引所は `Text` (58) に機械可読なコードを埋め込む
+//! (`"[1234] price out of band"`, `"ERR-17: unknown symbol"`)。
+//! [`RejectCodeExtractor`] は取引所ごとに設定した [`CodeRule`] を順に
+//! 試し、コードと残りの説明文を [`RejectText`] として返す。
+//! 正規表現が必要な場合は [`CodeRule::custom`] にクロージャを渡す。
+
+use std::sync::Arc;
+
+use crate::message::FixMessage;
+use crate::tag;
+
+/// 抽出関数 (`Text` → `(コード, 説明文)`)。
+type CustomRule = Arc<dyn Fn(&str) -> Option<(String, String)> + Send + Sync>;
+
+/// コード抽出規則。
+#[derive(Clone)]
+pub enum CodeRule {
+    /// 先頭の `open` … `close` で囲まれた部分 (例: `[1234] ...`)。
+    Bracketed {
+        /// 開き文字。
+        open: char,
+        /// 閉じ文字。
+        close: char,
+    },
+    /// 先頭の固定接頭辞に続くコード (例: 接頭辞 `ERR` で `ERR-17: ...` の
+    /// コードは `17`)。接頭辞直後の `-` `_` `:` 空白は読み飛ばす。
+    Prefix(String),
+    /// 先頭から `delimiter` までをコードとする (例: `1234: ...` で `:`)。
+    Leading {
+        /// 区切り文字。
+        delimiter: char,
+    },
+    /// 任意の抽出関数。
+    Custom(CustomRule),
+}
+
+impl core::fmt::Debug for CodeRule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bracketed { open, close } => f
+                .debug_struct("Bracketed")
+                .field("open", open)
+                .field("close", close)
+                .finish(),
+            Self::Prefix(p) => f.debug_tuple("Prefix").field(p).finish(),
+            Self::Leading { delimiter } => f
+                .debug_struct("Leading")
+                .field("delimiter", delimiter)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl CodeRule {
+    /// `[code] text` 形式。
+    #[must_use]
+    pub const fn brackets() -> Self {
+        Self::Bracketed {
+            open: '[',
+            close: ']',
+        }
+    }
+
+    /// `prefix` に続くコード。
+    #[must_use]
+    pub fn prefix(prefix: &str) -> Self {
+        Self::Prefix(prefix.to_string())
+    }
+
+    /// `code<delimiter> text` 形式。
+    #[must_use]
+    pub const fn leading(delimiter: char) -> Self {
+        Self::Leading { delimiter }
+    }
+
+    /// 任意の抽出関数 (正規表現など)。
+    #[must_use]
+    pub fn custom(f: impl Fn(&str) -> Option<(String, String)> + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(f))
+    }
+
+    /// `text` にこの規則を適用。
+    fn apply(&self, text: &str) -> Option<(String, String)> {
+        let text = text.trim_start();
+        match self {
+            Self::Bracketed { open, close } => {
+                let rest = text.strip_prefix(*open)?;
+                let (code, rest) = rest.split_once(*close)?;
+                non_empty_code(code.trim(), rest)
+            }
+            Self::Prefix(prefix) => {
+                let rest = text.strip_prefix(prefix.as_str())?;
+                let rest = rest.trim_start_matches(['-', '_', ':', ' ']);
+                let end = rest.find(|c: char| !is_code_char(c)).unwrap_or(rest.len());
+                non_empty_code(&rest[..end], &rest[end..])
+            }
+            Self::Leading { delimiter } => {
+                let (code, rest) = text.split_once(*delimiter)?;
+                let code = code.trim();
+                if !code.chars().all(is_code_char) {
+                    return None;
+                }
+                non_empty_code(code, rest)
+            }
+            Self::Custom(f) => f(text),
+        }
+    }
+}
+
+/// コードに使える文字 (英数字と `-` `_` `.`)。
+const fn is_code_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+fn non_empty_code(code: &str, rest: &str) -> Option<(String, String)> {
+    let message = rest.trim_start_matches([':', '-', ' ']).trim_end();
+    (!code.is_empty()).then(|| (code.to_string(), message.to_string()))
+}
+
+/// 構造化した `Text`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectText {
+    /// 抽出したコード (規則に一致しなければ `None`)。
+    pub code: Option<String>,
+    /// コードを除いた説明文 (一致しなければ元の `Text`)。
+    pub message: String,
+}
+
+/// 取引所ごとのコード抽出設定。規則は追加順に試し、最初の一致を使う。
+#[derive(Debug, Clone, Default)]
+pub struct RejectCodeExtractor {
+    rules: Vec<CodeRule>,
+}
+
+impl RejectCodeExtractor {
+    /// 規則なし (コードは常に `None`) で作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 規則を追加。
+    #[must_use]
+    pub fn rule(mut self, rule: CodeRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// `text` からコードを抽出。
+    #[must_use]
+    pub fn extract(&self, text: &str) -> RejectText {
+        self.rules.iter().find_map(|r| r.apply(text)).map_or_else(
+            || RejectText {
+                code: None,
+                message: text.to_string(),
+            },
+            |(code, message)| RejectText {
+                code: Some(code),
+                message,
+            },
+        )
+    }
+}
+
+impl FixMessage {
+    /// `Text` (58) を `extractor` で構造化。`Text` が無ければ `None`。
+    #[must_use]
+    pub fn reject_text(&self, extractor: &RejectCodeExtractor) -> Option<RejectText> {
+        self.get(tag::TEXT).map(|t| extractor.extract(t))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coded(code: &str, message: &str) -> RejectText {
+        RejectText {
+            code: Some(code.into()),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn builtin_rules() {
+        let brackets = RejectCodeExtractor::new().rule(CodeRule::brackets());
+        assert_eq!(
+            brackets.extract("[1234] price out of band"),
+            coded("1234", "price out of band")
+        );
+
+        let prefix = RejectCodeExtractor::new().rule(CodeRule::prefix("ERR"));
+        assert_eq!(
+            prefix.extract("ERR-17: unknown symbol"),
+            coded("17", "unknown symbol")
+        );
+
+        let leading = RejectCodeExtractor::new().rule(CodeRule::leading(':'));
+        assert_eq!(
+            leading.extract("E42: throttle exceeded"),
+            coded("E42", "throttle exceeded")
+        );
+        // 説明文中の ':' はコードとみなさない
+        assert_eq!(leading.extract("price too far: 5%").code, None);
+    }
+
+    #[test]
+    fn first_matching_rule_wins_and_fallback_keeps_text() {
+        let venue = RejectCodeExtractor::new()
+            .rule(CodeRule::brackets())
+            .rule(CodeRule::prefix("RC"));
+        assert_eq!(venue.extract("RC 99 halted"), coded("99", "halted"));
+        assert_eq!(
+            venue.extract("no code here"),
+            RejectText {
+                code: None,
+                message: "no code here".into()
+            }
+        );
+    }
+
+    #[test]
+    fn custom_rule_and_message_accessor() {
+        let venue = RejectCodeExtractor::new().rule(CodeRule::custom(|t| {
+            let (msg, code) = t.rsplit_once(" (code ")?;
+            Some((code.strip_suffix(')')?.to_string(), msg.to_string()))
+        }));
+        let mut msg = FixMessage::new("FIX.4.4", "3");
+        assert_eq!(msg.reject_text(&venue), None);
+        msg.set(tag::TEXT, "invalid qty (code 7)");
+        assert_eq!(msg.reject_text(&venue), Some(coded("7", "invalid qty")));
+    }
+}
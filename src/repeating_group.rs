@@ -4,8 +4,12 @@
 
 use std::collections::HashMap;
 
+use crate::tag;
+use crate::value::{parse_i64_bytes, parse_u64_bytes};
+
 /// Repeating Group エントリ。
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupEntry {
     /// タグ-値ペア。
     pub fields: HashMap<u32, String>,
@@ -31,6 +35,26 @@ impl GroupEntry {
     pub fn get(&self, tag: u32) -> Option<&str> {
         self.fields.get(&tag).map(String::as_str)
     }
+
+    /// (タグ, 値) をタグ昇順で反復。
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        let mut pairs: Vec<(u32, &str)> =
+            self.fields.iter().map(|(&t, v)| (t, v.as_str())).collect();
+        pairs.sort_unstable_by_key(|&(t, _)| t);
+        pairs.into_iter()
+    }
+
+    /// フィールドを `i64` として取得。欠落またはパース失敗時は `None`。
+    #[must_use]
+    pub fn get_i64(&self, tag: u32) -> Option<i64> {
+        parse_i64_bytes(self.fields.get(&tag)?.as_bytes())
+    }
+
+    /// フィールドを `u64` として取得。欠落またはパース失敗時は `None`。
+    #[must_use]
+    pub fn get_u64(&self, tag: u32) -> Option<u64> {
+        parse_u64_bytes(self.fields.get(&tag)?.as_bytes())
+    }
 }
 
 impl Default for GroupEntry {
@@ -101,6 +125,93 @@ impl RepeatingGroup {
     }
 }
 
+/// パーサが認識する Repeating Group 定義。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupDef {
+    /// カウントタグ (`NoXxx`)。
+    pub count_tag: u32,
+    /// 既定の先頭タグ。
+    pub delimiter_tag: u32,
+    /// 先頭タグ以外のメンバタグ。
+    pub member_tags: &'static [u32],
+}
+
+impl GroupDef {
+    /// `tag` がこのグループに属するか。
+    #[must_use]
+    pub fn contains(&self, tag: u32) -> bool {
+        tag == self.delimiter_tag || self.member_tags.contains(&tag)
+    }
+}
+
+/// 標準 Repeating Group 定義テーブル。
+///
+/// ネストされたグループ (例: `NoPartySubIDs`) は展開しない。メンバー表は
+/// 網羅的ではなく、表にないタグも後続のエントリがある間は現在のエントリに
+/// 含めて解析する ([`crate::parser::parse`])。
+pub const STANDARD_GROUPS: &[GroupDef] = &[
+    GroupDef {
+        count_tag: tag::NO_PARTY_IDS,
+        delimiter_tag: tag::PARTY_ID,
+        member_tags: &[tag::PARTY_ID_SOURCE, tag::PARTY_ROLE],
+    },
+    GroupDef {
+        count_tag: tag::NO_MD_ENTRIES,
+        delimiter_tag: tag::MD_ENTRY_TYPE,
+        member_tags: &[
+            tag::MD_UPDATE_ACTION,
+            tag::MD_ENTRY_ID,
+            tag::MD_ENTRY_PX,
+            tag::MD_ENTRY_SIZE,
+            tag::MD_ENTRY_DATE,
+            tag::MD_ENTRY_TIME,
+            tag::MD_ENTRY_POSITION_NO,
+            tag::SYMBOL,
+        ],
+    },
+    GroupDef {
+        count_tag: tag::NO_RELATED_SYM,
+        delimiter_tag: tag::SYMBOL,
+        member_tags: &[
+            tag::SECURITY_ID,
+            tag::SECURITY_ID_SOURCE,
+            tag::SECURITY_EXCHANGE,
+            tag::SECURITY_TYPE,
+            tag::CURRENCY,
+            tag::SIDE,
+            tag::ORDER_QTY,
+        ],
+    },
+    // NewOrderList (E) と ListStatus (N) の両方のメンバーを含む。
+    GroupDef {
+        count_tag: tag::NO_ORDERS,
+        delimiter_tag: tag::CL_ORD_ID,
+        member_tags: &[
+            tag::LIST_SEQ_NO,
+            tag::ACCOUNT,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::TRANSACT_TIME,
+            tag::ORDER_QTY,
+            tag::ORD_TYPE,
+            tag::PRICE,
+            tag::TIME_IN_FORCE,
+            tag::CUM_QTY,
+            tag::ORD_STATUS,
+            tag::LEAVES_QTY,
+            tag::CXL_QTY,
+            tag::AVG_PX,
+            tag::TEXT,
+        ],
+    },
+];
+
+/// カウントタグに対応する標準グループ定義を検索。
+#[must_use]
+pub fn find_group_def(count_tag: u32) -> Option<&'static GroupDef> {
+    STANDARD_GROUPS.iter().find(|d| d.count_tag == count_tag)
+}
+
 /// tag-value ペア列から Repeating Group をパース。
 ///
 /// # Errors
@@ -310,6 +421,33 @@ mod tests {
         assert!(entry.fields.is_empty());
     }
 
+    #[test]
+    fn group_entry_typed_accessors() {
+        let mut entry = GroupEntry::new();
+        entry.set(270, "-125").set(271, "40").set(269, "X");
+        assert_eq!(entry.get_i64(270), Some(-125));
+        assert_eq!(entry.get_u64(271), Some(40));
+        assert_eq!(entry.get_u64(269), None);
+        assert_eq!(entry.get_u64(999), None);
+    }
+
+    #[test]
+    fn group_entry_iter_sorted() {
+        let mut entry = GroupEntry::new();
+        entry.set(452, "1").set(448, "P1").set(447, "D");
+        let pairs: Vec<(u32, &str)> = entry.iter().collect();
+        assert_eq!(pairs, vec![(447, "D"), (448, "P1"), (452, "1")]);
+    }
+
+    #[test]
+    fn find_standard_group_def() {
+        let def = find_group_def(tag::NO_PARTY_IDS).unwrap();
+        assert_eq!(def.delimiter_tag, tag::PARTY_ID);
+        assert!(def.contains(tag::PARTY_ROLE));
+        assert!(!def.contains(tag::SYMBOL));
+        assert!(find_group_def(tag::SYMBOL).is_none());
+    }
+
     #[test]
     fn empty_group_serialize() {
         let group = RepeatingGroup::new(453, 448);
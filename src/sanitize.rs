@@ -0,0 +1,118 @@
+//! Sensitive-Field Sanitizer
+//!
+//! ログ出力・サポート用にメッセージ中の機密タグ (パスワード・口座番号等)
+//! をマスクする。[`crate::message::FixMessage::sanitized`] で使う。
+
+use crate::tag;
+
+/// 既定でマスクするタグ。
+pub const DEFAULT_SENSITIVE_TAGS: &[u32] = &[
+    tag::ACCOUNT,
+    tag::USERNAME,
+    tag::PASSWORD,
+    tag::NEW_PASSWORD,
+];
+
+/// 既定のマスク文字列。
+pub const DEFAULT_MASK: &str = "***";
+
+/// マスク対象タグとマスク文字列の設定。
+///
+/// [`Default`] は [`DEFAULT_SENSITIVE_TAGS`] と [`DEFAULT_MASK`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensitiveTags {
+    tags: Vec<u32>,
+    mask: String,
+}
+
+impl Default for SensitiveTags {
+    fn default() -> Self {
+        Self {
+            tags: DEFAULT_SENSITIVE_TAGS.to_vec(),
+            mask: DEFAULT_MASK.to_string(),
+        }
+    }
+}
+
+impl SensitiveTags {
+    /// マスク対象なしの設定を作成。
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            tags: Vec::new(),
+            mask: DEFAULT_MASK.to_string(),
+        }
+    }
+
+    /// マスク対象にタグを追加。
+    #[must_use]
+    pub fn with(mut self, tag: u32) -> Self {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// マスク対象からタグを除外。
+    #[must_use]
+    pub fn without(mut self, tag: u32) -> Self {
+        self.tags.retain(|&t| t != tag);
+        self
+    }
+
+    /// マスク文字列を設定。
+    #[must_use]
+    pub fn with_mask(mut self, mask: &str) -> Self {
+        self.mask = mask.to_string();
+        self
+    }
+
+    /// `tag` がマスク対象か。
+    #[must_use]
+    pub fn contains(&self, tag: u32) -> bool {
+        self.tags.contains(&tag)
+    }
+
+    /// マスク文字列。
+    #[must_use]
+    pub fn mask(&self) -> &str {
+        &self.mask
+    }
+
+    /// マスク対象タグ。
+    #[must_use]
+    pub fn tags(&self) -> &[u32] {
+        &self.tags
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_covers_credentials_and_account() {
+        let s = SensitiveTags::default();
+        assert!(s.contains(tag::PASSWORD));
+        assert!(s.contains(tag::NEW_PASSWORD));
+        assert!(s.contains(tag::ACCOUNT));
+        assert!(!s.contains(tag::SYMBOL));
+        assert_eq!(s.mask(), "***");
+    }
+
+    #[test]
+    fn builder_edits_set() {
+        let s = SensitiveTags::none()
+            .with(tag::TEXT)
+            .with(tag::TEXT)
+            .with(tag::ACCOUNT)
+            .without(tag::ACCOUNT)
+            .with_mask("<redacted>");
+        assert_eq!(s.tags(), &[tag::TEXT]);
+        assert_eq!(s.mask(), "<redacted>");
+    }
+}
@@ -0,0 +1,248 @@
+//! Session Schedule
+//!
+//! 取引所ごとの接続時間帯。毎日の開始・終了時刻、稼働曜日、UTC
+//! オフセットで時間帯 (ウィンドウ) を定め、[`crate::session::FixSession`]
+//! が Logon 開始 ([`crate::session::FixSession::should_logon`])、終業時の
+//! Logout、日替わりのシーケンス番号リセットの判断に使う。
+//!
+//! 終了時刻が開始時刻以前なら日をまたぐウィンドウ (例: 17:00 → 翌 16:00)、
+//! 等しければ 24 時間。稼働曜日はウィンドウの開始日で判定する。
+//! タイムゾーンは固定の UTC オフセット (分) で、夏時間の切り替えは
+//! 呼び出し側で設定し直す。時刻は Unix エポックからのナノ秒。
+
+/// 1 日の秒数。
+const DAY_SECS: i64 = 86_400;
+
+/// 1 秒あたりのナノ秒数。
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// 曜日。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weekday {
+    /// 月曜。
+    Mon,
+    /// 火曜。
+    Tue,
+    /// 水曜。
+    Wed,
+    /// 木曜。
+    Thu,
+    /// 金曜。
+    Fri,
+    /// 土曜。
+    Sat,
+    /// 日曜。
+    Sun,
+}
+
+impl Weekday {
+    /// 月曜から金曜。
+    pub const WEEKDAYS: [Self; 5] = [Self::Mon, Self::Tue, Self::Wed, Self::Thu, Self::Fri];
+
+    /// エポックからの日数 (1970-01-01 は木曜) の曜日。
+    const fn of_day(day: i64) -> Self {
+        match (day + 3).rem_euclid(7) {
+            0 => Self::Mon,
+            1 => Self::Tue,
+            2 => Self::Wed,
+            3 => Self::Thu,
+            4 => Self::Fri,
+            5 => Self::Sat,
+            _ => Self::Sun,
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// 時刻 (0 時からの秒)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeOfDay(u32);
+
+impl TimeOfDay {
+    /// 時・分・秒から作成 (24 時間で剰余を取る)。
+    #[must_use]
+    pub const fn hms(hour: u32, minute: u32, second: u32) -> Self {
+        Self((hour * 3600 + minute * 60 + second) % DAY_SECS as u32)
+    }
+
+    /// 0 時からの秒。
+    #[must_use]
+    pub const fn seconds(self) -> u32 {
+        self.0
+    }
+}
+
+/// 接続時間帯。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSchedule {
+    start: TimeOfDay,
+    end: TimeOfDay,
+    /// 稼働曜日のビット集合 ([`Weekday::bit`])。
+    days: u8,
+    utc_offset_minutes: i32,
+}
+
+impl SessionSchedule {
+    /// 毎日 `start` から `end` まで (UTC)。
+    #[must_use]
+    pub const fn new(start: TimeOfDay, end: TimeOfDay) -> Self {
+        Self {
+            start,
+            end,
+            days: 0x7f,
+            utc_offset_minutes: 0,
+        }
+    }
+
+    /// ウィンドウを開始する曜日を限定。
+    #[must_use]
+    pub fn days(mut self, days: &[Weekday]) -> Self {
+        self.days = days.iter().fold(0, |bits, d| bits | d.bit());
+        self
+    }
+
+    /// 開始・終了時刻の UTC オフセット (分、例: JST は 540)。
+    #[must_use]
+    pub const fn utc_offset_minutes(mut self, minutes: i32) -> Self {
+        self.utc_offset_minutes = minutes;
+        self
+    }
+
+    /// `now_ns` がウィンドウ内か。
+    #[must_use]
+    pub fn is_active(&self, now_ns: u64) -> bool {
+        self.window(now_ns).is_some()
+    }
+
+    /// `now_ns` を含むウィンドウの `(開始, 終了)` (ns、終了は含まない)。
+    #[must_use]
+    pub fn window(&self, now_ns: u64) -> Option<(u64, u64)> {
+        let local = self.local_secs(now_ns);
+        let day = local.div_euclid(DAY_SECS);
+        [day, day - 1]
+            .into_iter()
+            .filter(|&d| self.runs_on(d))
+            .map(|d| self.window_of(d))
+            .find(|&(start, end)| start <= local && local < end)
+            .map(|(start, end)| (self.utc_ns(start), self.utc_ns(end)))
+    }
+
+    /// `now_ns` より後に始まる最初のウィンドウの開始時刻 (ns)。稼働曜日が
+    /// 無ければ `None`。
+    #[must_use]
+    pub fn next_start(&self, now_ns: u64) -> Option<u64> {
+        let local = self.local_secs(now_ns);
+        let day = local.div_euclid(DAY_SECS);
+        (day..=day + 7)
+            .filter(|&d| self.runs_on(d))
+            .map(|d| self.window_of(d).0)
+            .find(|&start| start > local)
+            .map(|start| self.utc_ns(start))
+    }
+
+    const fn runs_on(&self, day: i64) -> bool {
+        self.days & Weekday::of_day(day).bit() != 0
+    }
+
+    /// `day` に始まるウィンドウ (ローカル秒)。
+    const fn window_of(&self, day: i64) -> (i64, i64) {
+        let start = day * DAY_SECS + self.start.0 as i64;
+        let mut end = day * DAY_SECS + self.end.0 as i64;
+        if end <= start {
+            end += DAY_SECS;
+        }
+        (start, end)
+    }
+
+    const fn local_secs(&self, now_ns: u64) -> i64 {
+        (now_ns / NANOS_PER_SEC as u64) as i64 + self.utc_offset_minutes as i64 * 60
+    }
+
+    const fn utc_ns(&self, local: i64) -> u64 {
+        let secs = local - self.utc_offset_minutes as i64 * 60;
+        if secs < 0 {
+            0
+        } else {
+            secs as u64 * NANOS_PER_SEC as u64
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = NANOS_PER_SEC as u64;
+    const HOUR: u64 = 3600 * SEC;
+    /// 2026-01-05 (月) 00:00 UTC。
+    const MONDAY: u64 = 1_767_571_200 * SEC;
+
+    #[test]
+    fn weekday_of_epoch_days() {
+        assert_eq!(Weekday::of_day(0), Weekday::Thu);
+        assert_eq!(
+            Weekday::of_day((MONDAY / SEC) as i64 / DAY_SECS),
+            Weekday::Mon
+        );
+        assert_eq!(Weekday::of_day(-1), Weekday::Wed);
+    }
+
+    #[test]
+    fn daily_window_with_offset() {
+        // JST 9:00-15:00 = UTC 0:00-6:00
+        let s = SessionSchedule::new(TimeOfDay::hms(9, 0, 0), TimeOfDay::hms(15, 0, 0))
+            .days(&Weekday::WEEKDAYS)
+            .utc_offset_minutes(540);
+        assert_eq!(s.window(MONDAY + HOUR), Some((MONDAY, MONDAY + 6 * HOUR)));
+        assert!(!s.is_active(MONDAY + 6 * HOUR));
+        assert_eq!(s.next_start(MONDAY + 6 * HOUR), Some(MONDAY + 24 * HOUR));
+        // 金曜の終了後は月曜まで無い
+        let friday_close = MONDAY + 4 * 24 * HOUR + 6 * HOUR;
+        assert_eq!(s.next_start(friday_close), Some(MONDAY + 7 * 24 * HOUR));
+        assert!(!s.is_active(MONDAY + 5 * 24 * HOUR + HOUR));
+    }
+
+    #[test]
+    fn overnight_window() {
+        // 日曜 17:00 から金曜 17:00 までの各日 17:00 → 翌 16:00
+        let s = SessionSchedule::new(TimeOfDay::hms(17, 0, 0), TimeOfDay::hms(16, 0, 0)).days(&[
+            Weekday::Sun,
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+        ]);
+        let sunday = MONDAY - 24 * HOUR;
+        // 月曜 10:00 は日曜 17:00 開始のウィンドウ内
+        assert_eq!(
+            s.window(MONDAY + 10 * HOUR),
+            Some((sunday + 17 * HOUR, MONDAY + 16 * HOUR))
+        );
+        assert!(!s.is_active(MONDAY + 16 * HOUR + SEC));
+        assert!(s.is_active(MONDAY + 17 * HOUR));
+        // 金曜 17:00 は稼働日外
+        assert!(!s.is_active(MONDAY + 4 * 24 * HOUR + 18 * HOUR));
+    }
+
+    #[test]
+    fn no_days_never_active() {
+        let s = SessionSchedule::new(TimeOfDay::hms(0, 0, 0), TimeOfDay::hms(0, 0, 0)).days(&[]);
+        assert!(!s.is_active(MONDAY));
+        assert_eq!(s.next_start(MONDAY), None);
+        let all_day = SessionSchedule::new(TimeOfDay::hms(0, 0, 0), TimeOfDay::hms(24, 0, 0));
+        assert_eq!(
+            all_day.window(MONDAY + HOUR),
+            Some((MONDAY, MONDAY + 24 * HOUR))
+        );
+    }
+}
@@ -8,22 +8,212 @@
 //! [`FixSession`] manages the state and sequence numbers for a single FIX
 //! session. It provides helpers to build standard administrative messages
 //! (Logon, Logout, Heartbeat) and to construct a `NewOrderSingle` (35=D)
-//! from an ALICE-Ledger [`Order`].
+//! or an `OrderCancelRequest` (35=F) / `OrderCancelReplaceRequest` (35=G) /
+//! `OrderStatusRequest` (35=H) for it from an ALICE-Ledger [`Order`].
 //!
 //! ## Session States
 //!
 //! ```text
 //! Disconnected → (send Logon) → LogonSent → (receive Logon) → Active
+//! Disconnected → (receive Logon, answer Logon) → Active
 //! Active → (send Logout) → LogoutSent → (receive Logout) → Disconnected
+//! Active → (receive Logout, answer Logout) → Disconnected
 //! ```
+//!
+//! The first path is the initiator's, the second the acceptor's
+//! ([`SessionRole`], [`FixSession::set_role`]).
+//!
+//! When the transport drops without a Logout exchange,
+//! [`FixSession::on_disconnect`] returns the session to `Disconnected`
+//! with its sequence numbers intact: the next Logon continues the
+//! sequence, and each side requests a resend of whatever it missed.
+//!
+//! ## Message Processing
+//!
+//! [`FixSession::on_message`] runs the administrative protocol for one parsed
+//! inbound message and returns the [`SessionAction`]s the caller must carry
+//! out: frames to send, application messages to deliver, outgoing ranges to
+//! replay, or a disconnect. Inbound messages are classified by `MsgType`:
+//! application messages (including every `ExecutionReport`) always come out
+//! as [`SessionAction::Deliver`], while administrative ones (35=0/1/2/3/4/5/A)
+//! are handled by the session and, for Rejects, surfaced separately as
+//! [`SessionAction::DeliverAdmin`]. It answers `TestRequest` (35=1) with a Heartbeat,
+//! completes or accepts Logon, answers Logout, applies `SequenceReset`
+//! (35=4), and requests a resend when `MsgSeqNum` jumps ahead, holding
+//! later messages ([`FixSession::queued`]) until the gap is filled.
+//! An acceptor accepts a Logon received while disconnected only if its
+//! `BeginString` and `CompID`s match the session, it carries a positive
+//! `HeartBtInt`, and, when [`FixSession::require_credentials`] is set, the
+//! expected `Username`/`Password`; an initiator accepts the response to its
+//! Logon only if the identity matches and `HeartBtInt` equals the one it
+//! sent. Otherwise the Logon is answered with Logout. With
+//! [`FixSession::set_max_latency`], a message whose `SendingTime` is too far
+//! from the session clock (and is not a retransmission) is rejected with
+//! `SessionRejectReason` 10 instead of being processed.
+//! [`FixSession::replay`] answers the counterparty's `ResendRequest` from a
+//! [`MessageStore`], gap-filling over administrative messages;
+//! [`FixSession::resolve_resends`] turns the `Resend` actions of
+//! `on_message` into those frames. With the frames we send recorded by
+//! [`FixSession::store_sent`], [`FixSession::on_message_with_store`] does
+//! both in one call, so a `ResendRequest` comes back as the complete
+//! response: `PossDupFlag` copies of the application messages and
+//! `GapFill`s over the rest.
+//!
+//! ## Heartbeats
+//!
+//! Logon carries `HeartBtInt` (108): an acceptor adopts the value in the
+//! counterparty's Logon, and an initiator requires the response to echo
+//! its own. While active, every outgoing message
+//! pushes the heartbeat deadline back, and [`FixSession::on_timer`] emits a
+//! Heartbeat once the interval passes in silence
+//! ([`FixSession::time_until_next_heartbeat`] says when to call it).
+//! When nothing arrives for 1.5 × `HeartBtInt` it sends a `TestRequest`
+//! and disconnects if the Heartbeat echoing its `TestReqID` (112) does not
+//! follow within another interval.
+//!
+//! ## Drop Copy
+//!
+//! A [`SessionMode::DropCopy`] session ([`FixSession::set_mode`]) is
+//! receive-only: [`FixSession::submit`] and [`FixSession::build_new_order`]
+//! fail with [`SubmitError::ReceiveOnly`], and retransmitted application
+//! messages below the expected `MsgSeqNum` are delivered rather than
+//! dropped, so every `ExecutionReport` reaches reconciliation.
+//!
+//! ## Third-Party Routing
+//!
+//! Through a hub, [`FixSession::set_routing`] sets the `OnBehalfOf*` /
+//! `DeliverTo*` fields (115 / 116 / 128 / 129) stamped on every
+//! application message the session frames; administrative messages carry
+//! only our own `CompID`s. A field already set on a submitted message is
+//! kept, so a reply can target a different broker with
+//! [`Routing::reply`] of the inbound message's [`Routing::from_message`].
+//!
+//! ## Events
+//!
+//! A handler installed with [`FixSession::set_event_handler`] receives a
+//! [`SessionEvent`] for each state change, missed heartbeat, inbound gap,
+//! counterparty `ResendRequest`, Reject we send, and completed logout, so
+//! alerting can hook in without parsing logs.
+//!
+//! With [`FixSession::set_order_ack_timeout_ms`], each `NewOrderSingle` we
+//! send awaits an `ExecutionReport` carrying its `ClOrdID`; one still
+//! unanswered at the deadline is reported by [`FixSession::on_timer`] as
+//! [`SessionEvent::OrderAckTimeout`], the trading layer's cue to treat the
+//! order's state as unknown.
+//!
+//! ## Schedule
+//!
+//! A [`SessionSchedule`] ([`FixSession::set_schedule`]) limits the session to
+//! a daily window: [`FixSession::should_logon`] says when to connect,
+//! [`FixSession::on_timer`] logs out at the end of the window and rolls the
+//! sequence numbers over to 1 when the next window opens.
+//!
+//! ## Outbound Queue
+//!
+//! [`FixSession::submit`] stamps an application message with the session
+//! header, a fresh `MsgSeqNum` and `SendingTime` while active. Messages
+//! submitted before logon completes are held and returned, stamped in the
+//! order submitted, right after the Logon exchange in the actions of
+//! [`FixSession::on_message`].
+//!
+//! Submitted messages spend the session's [`Throttle`] budget. Out of
+//! budget, they are held and released by [`FixSession::on_timer`] in order,
+//! or refused with [`SubmitError::Throttled`] when the throttle's policy is
+//! [`ThrottlePolicy::Reject`].
+//!
+//! ## Inbound Policy
+//!
+//! [`FixSession::screen_inbound`] applies a per-session
+//! [`InboundPolicy`] to raw frames before they reach the parser.
+//!
+//! ## Drain Metrics
+//!
+//! Each session owns a [`DrainMonitor`] ([`FixSession::drain_mut`]) that
+//! tracks outbound queue depth, age, and time-to-flush and raises a
+//! [`crate::drain::SlowConsumer`] event when configured thresholds are
+//! exceeded.
+//!
+//! ## Throttle
+//!
+//! Each session owns an outbound [`Throttle`] ([`FixSession::throttle_mut`])
+//! with global and per-`MsgType` budgets. Cancels (35=F/q) are exempt by
+//! default so risk-reducing messages are never delayed.
+//!
+//! ## Clock
+//!
+//! Every message built by the session carries `SendingTime` (52), stamped
+//! from the session's [`Clock`] ([`FixSession::set_clock`]; the system clock
+//! by default) at the precision set with
+//! [`FixSession::set_timestamp_precision`] (milliseconds by default);
+//! resent frames get a fresh one. Inbound receive times, the `SendingTime`
+//! latency check, and [`FixSession::tick`] (timers at the clock's current
+//! time) read the same clock, so a shared [`crate::clock::ManualClock`]
+//! makes timing tests deterministic.
+//!
+//! ## Identity Rotation
+//!
+//! Venue-mandated `CompID` renames follow a fixed procedure: send Logout,
+//! call [`FixSession::rotate_identity`] once logged out (which resets both
+//! sequence numbers to 1), then open the new identity's stores with
+//! [`crate::store::FileStore::for_session`] and
+//! [`crate::store::FileSeqNumStore::for_session`]. Stores are keyed by
+//! [`SessionId`], so the new identity starts from empty files and the old
+//! identity's messages and sequence numbers stay on disk untouched.
+//!
+//! ## Snapshots
+//!
+//! [`FixSession::snapshot`] captures identity, sequence numbers, and state as
+//! a plain [`SessionSnapshot`] value (serializable with the `serde` feature);
+//! [`FixSession::from_snapshot`] restores a session from it. For restarts,
+//! [`FixSession::save_seq_nums`] persists just the sequence numbers to a
+//! [`SeqNumStore`] and [`FixSession::restore`] resumes them.
 
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io;
+use std::sync::Arc;
+
+use crate::admin::{is_admin_msg_type, Reject, SessionRejectReason};
 use crate::builder::FixBuilder;
+use crate::clock::{Clock, SystemClock};
 use crate::convert::{alice_ord_type_to_fix, alice_side_to_fix, alice_tif_to_fix};
+use crate::drain::DrainMonitor;
+use crate::execution_report::ExecutionReport;
+use crate::firewall::{InboundPolicy, Verdict};
+use crate::header::Routing;
+use crate::heartbeat::{HeartbeatConfig, HeartbeatTimer};
+use crate::list::{msg_type as list_msg_type, NewOrderList};
+use crate::mass_status::{OrderMassStatusRequest, ORDER_MASS_STATUS_REQUEST};
+use crate::message::FixMessage;
+use crate::parser::{self, ParseError, SOH};
+use crate::schedule::SessionSchedule;
+use crate::store::{MessageStore, SeqNumStore, SeqNums};
 use crate::tag;
-use alice_ledger::Order;
+use crate::throttle::{Throttle, ThrottleDecision, ThrottlePolicy};
+use crate::value::{format_utc_timestamp_with, parse_utc_timestamp, TimestampPrecision};
+use crate::version::BeginString;
+use alice_ledger::{Order, TimeInForce};
+
+/// `HeartBtInt` (seconds) sent in Logon unless changed with
+/// [`FixSession::set_heart_bt_int`].
+pub const DEFAULT_HEART_BT_INT: u32 = 30;
+
+/// Seconds to wait for the counterparty's Logout after sending ours, unless
+/// changed with [`FixSession::set_logout_timeout`].
+pub const DEFAULT_LOGOUT_TIMEOUT_SECS: u32 = 2;
+
+/// Number of recent `ClOrdID`s remembered for duplicate detection, unless
+/// changed with [`FixSession::set_cl_ord_id_window`].
+pub const DEFAULT_CL_ORD_ID_WINDOW: usize = 10_000;
+
+/// `MsgType`s whose `ClOrdID` (11) must be new: `NewOrderSingle`,
+/// `OrderCancelRequest`, `OrderCancelReplaceRequest`.
+const CL_ORD_ID_MSG_TYPES: &[&str] = &["D", "F", "G"];
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
 
 /// Operational state of a FIX session.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SessionState {
     /// No connection established; no messages exchanged.
     Disconnected,
@@ -35,33 +225,727 @@ pub enum SessionState {
     LogoutSent,
 }
 
+/// Outcome of checking an inbound `MsgSeqNum` against the expected one
+/// ([`FixSession::validate_incoming_seq`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqCheck {
+    /// The expected number; the expected counter has advanced.
+    Expected,
+    /// Below the expected number: a duplicate when `PossDupFlag` (43) is Y,
+    /// otherwise fatal (Logout with Text, then disconnect).
+    TooLow {
+        /// Expected `MsgSeqNum`.
+        expected: u64,
+        /// Received `MsgSeqNum`.
+        received: u64,
+    },
+    /// Above the expected number: messages were missed, so request a resend
+    /// and queue this one.
+    TooHigh {
+        /// Expected `MsgSeqNum`.
+        expected: u64,
+        /// Received `MsgSeqNum`.
+        received: u64,
+    },
+}
+
+/// Which side of the connection a session plays.
+///
+/// The initiator connects and sends Logon first; the acceptor waits for
+/// the counterparty's Logon and answers it. When the counterparty starts a
+/// logout, the acceptor closes the connection after answering, while the
+/// initiator answers and leaves the close to the acceptor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionRole {
+    /// Connects and sends the first Logon.
+    #[default]
+    Initiator,
+    /// Accepts connections and answers the counterparty's Logon.
+    Acceptor,
+}
+
+/// What a session is used for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionMode {
+    /// Orders may be sent.
+    #[default]
+    Trading,
+    /// Receive-only drop-copy feed: the session never sends application
+    /// messages, and retransmitted application messages (`PossDupFlag` Y)
+    /// below the expected `MsgSeqNum` are delivered instead of dropped, so
+    /// reconciliation sees every `ExecutionReport`.
+    DropCopy,
+}
+
+/// Credentials and extra fields carried in our Logon.
+///
+/// `Debug` output masks the password.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct LogonConfig {
+    username: Option<String>,
+    password: Option<String>,
+    encrypt_method: u32,
+    reset_seq_num: bool,
+    extra: Vec<(u32, String)>,
+}
+
+impl LogonConfig {
+    /// Create a configuration with no credentials and `EncryptMethod` 0.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `Username` (tag 553).
+    #[must_use]
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Set `Password` (tag 554).
+    #[must_use]
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Set `EncryptMethod` (tag 98); 0 (none) by default.
+    #[must_use]
+    pub const fn encrypt_method(mut self, method: u32) -> Self {
+        self.encrypt_method = method;
+        self
+    }
+
+    /// Send `ResetSeqNumFlag` (141) Y: both sequence numbers restart at 1
+    /// when the Logon is built.
+    #[must_use]
+    pub const fn reset_seq_num(mut self, reset: bool) -> Self {
+        self.reset_seq_num = reset;
+        self
+    }
+
+    /// Append a venue-specific field, sent after the standard Logon fields
+    /// in the order added.
+    #[must_use]
+    pub fn extra_field(mut self, tag: u32, value: &str) -> Self {
+        self.extra.push((tag, value.to_string()));
+        self
+    }
+}
+
+impl core::fmt::Debug for LogonConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LogonConfig")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("encrypt_method", &self.encrypt_method)
+            .field("reset_seq_num", &self.reset_seq_num)
+            .field("extra", &self.extra)
+            .finish()
+    }
+}
+
+/// Work requested by [`FixSession::on_message`], in the order it should be
+/// performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionAction {
+    /// Write this frame to the counterparty.
+    Send(Vec<u8>),
+    /// Hand this application message to the business logic. Administrative
+    /// messages never take this path.
+    Deliver(FixMessage),
+    /// Hand this administrative message (a session-level Reject, or an
+    /// administrative `MsgType` the session does not act on) to the
+    /// administrative path, e.g. for logging.
+    DeliverAdmin(FixMessage),
+    /// Replay our outgoing messages `begin..=end` from the store; `end` 0
+    /// means through the latest message sent.
+    Resend {
+        /// `BeginSeqNo` (tag 7).
+        begin: u64,
+        /// `EndSeqNo` (tag 16).
+        end: u64,
+    },
+    /// Close the transport.
+    Disconnect,
+    /// Close the transport without waiting any longer: our Logout went
+    /// unanswered for the logout timeout ([`FixSession::set_logout_timeout`]).
+    ForceDisconnect,
+}
+
+/// Notable session occurrence, reported to the handler installed with
+/// [`FixSession::set_event_handler`] for operational alerting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The session moved between states.
+    StateChanged {
+        /// State before the change.
+        from: SessionState,
+        /// State after the change.
+        to: SessionState,
+    },
+    /// Nothing arrived for 1.5 × `HeartBtInt`; a `TestRequest` was sent.
+    HeartbeatMissed {
+        /// `TestReqID` (tag 112) of the `TestRequest`.
+        test_req_id: String,
+    },
+    /// Inbound messages are missing; a `ResendRequest` was sent for them.
+    GapDetected {
+        /// First missing `MsgSeqNum`.
+        begin: u64,
+        /// Last missing `MsgSeqNum`.
+        end: u64,
+    },
+    /// The counterparty sent a `ResendRequest`.
+    ResendRequested {
+        /// `BeginSeqNo` (tag 7).
+        begin: u64,
+        /// `EndSeqNo` (tag 16); 0 means through the latest message.
+        end: u64,
+    },
+    /// We rejected an inbound message with a session-level Reject (35=3).
+    Rejected {
+        /// `SessionRejectReason` (tag 373) sent.
+        reason: SessionRejectReason,
+        /// `Text` (tag 58) sent.
+        text: String,
+    },
+    /// An order message reused a recent `ClOrdID` (11): refused by
+    /// [`FixSession::submit`], sent anyway by [`FixSession::build_new_order`].
+    DuplicateClOrdId {
+        /// The reused `ClOrdID`.
+        cl_ord_id: String,
+    },
+    /// A `NewOrderSingle` got no `ExecutionReport` within the order
+    /// acknowledgement timeout ([`FixSession::set_order_ack_timeout_ms`]).
+    /// The order is no longer tracked.
+    OrderAckTimeout {
+        /// `ClOrdID` (tag 11) of the order.
+        cl_ord_id: String,
+        /// Clock reading (ns) when the order was sent.
+        sent_ns: u64,
+    },
+    /// The session ended with a Logout: ours, the counterparty's, or an
+    /// unanswered one.
+    LoggedOut {
+        /// Our Logout text, or the counterparty's when it logged out.
+        reason: Option<String>,
+    },
+}
+
+/// Handler installed with [`FixSession::set_event_handler`].
+type EventHandler = Box<dyn FnMut(&SessionEvent) + Send>;
+
+/// Point-in-time copy of a session's identity, sequence numbers, and state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSnapshot {
+    /// `SenderCompID` (tag 49) used on outgoing messages.
+    pub sender_comp_id: String,
+    /// `TargetCompID` (tag 56) used on outgoing messages.
+    pub target_comp_id: String,
+    /// `BeginString` (tag 8), with the application version for `FIXT.1.1`.
+    pub begin_string: BeginString,
+    /// Next sequence number to assign to an outgoing message.
+    pub outgoing_seq: u64,
+    /// Next sequence number expected from the counterparty.
+    pub incoming_seq: u64,
+    /// Session state at the time of the snapshot.
+    pub state: SessionState,
+}
+
+/// Message counters and sequence state of a session, from
+/// [`FixSession::stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionMetrics {
+    /// Messages sent, by `MsgType`: the frames the session numbers itself
+    /// (`build_*`, [`FixSession::submit`], replies). Retransmissions and
+    /// frames numbered with [`FixSession::next_outgoing_seq`] are not
+    /// counted.
+    pub sent: BTreeMap<String, u64>,
+    /// Messages received, by `MsgType`.
+    pub received: BTreeMap<String, u64>,
+    /// Next sequence number to assign to an outgoing message.
+    pub outgoing_seq: u64,
+    /// Next sequence number expected from the counterparty.
+    pub incoming_seq: u64,
+    /// Clock reading (ns) when the last message was sent.
+    pub last_sent_ns: Option<u64>,
+    /// Clock reading (ns) when the last message was received.
+    pub last_received_ns: Option<u64>,
+    /// Inbound sequence gaps detected (`ResendRequest`s we sent).
+    pub gaps: u64,
+    /// Counterparty `ResendRequest`s answered.
+    pub resends: u64,
+}
+
+/// Identity of a FIX session: `BeginString` plus the `CompID` pair, and an
+/// optional local qualifier.
+///
+/// The qualifier never goes on the wire; it tells apart sessions that share
+/// the same `BeginString` and `CompID`s (e.g. two connections to one venue).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionId {
+    /// `BeginString` (tag 8).
+    pub begin_string: String,
+    /// `SenderCompID` (tag 49) used on outgoing messages.
+    pub sender_comp_id: String,
+    /// `TargetCompID` (tag 56) used on outgoing messages.
+    pub target_comp_id: String,
+    /// Local qualifier, if any.
+    pub qualifier: Option<String>,
+}
+
+impl SessionId {
+    /// Create a session identity without a qualifier.
+    #[must_use]
+    pub fn new(begin_string: &str, sender: &str, target: &str) -> Self {
+        Self {
+            begin_string: begin_string.to_string(),
+            sender_comp_id: sender.to_string(),
+            target_comp_id: target.to_string(),
+            qualifier: None,
+        }
+    }
+
+    /// Set the local qualifier.
+    #[must_use]
+    pub fn with_qualifier(mut self, qualifier: &str) -> Self {
+        self.qualifier = Some(qualifier.to_string());
+        self
+    }
+
+    /// Whether an inbound message's header (`BeginString`, and its
+    /// `SenderCompID`/`TargetCompID` as our target/sender) addresses this
+    /// session.
+    #[must_use]
+    pub fn matches_inbound(&self, msg: &FixMessage) -> bool {
+        msg.begin_string == self.begin_string
+            && msg.get(tag::SENDER_COMP_ID) == Some(self.target_comp_id.as_str())
+            && msg.get(tag::TARGET_COMP_ID) == Some(self.sender_comp_id.as_str())
+    }
+
+    /// File-system safe key, e.g. `FIX.4.4-ALICE-BROKER`, with `-<qualifier>`
+    /// appended when there is one.
+    ///
+    /// Characters other than ASCII alphanumerics, `.` and `_` are written as
+    /// `%XX`, so distinct identities never share a key.
+    #[must_use]
+    pub fn file_stem(&self) -> String {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        let mut out = String::new();
+        for (i, part) in [
+            &self.begin_string,
+            &self.sender_comp_id,
+            &self.target_comp_id,
+        ]
+        .into_iter()
+        .chain(&self.qualifier)
+        .enumerate()
+        {
+            if i > 0 {
+                out.push('-');
+            }
+            for b in part.bytes() {
+                if b.is_ascii_alphanumeric() || b == b'.' || b == b'_' {
+                    out.push(char::from(b));
+                } else {
+                    out.push('%');
+                    out.push(char::from(HEX[usize::from(b >> 4)]));
+                    out.push(char::from(HEX[usize::from(b & 0x0f)]));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl core::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}:{}->{}",
+            self.begin_string, self.sender_comp_id, self.target_comp_id
+        )?;
+        if let Some(qualifier) = &self.qualifier {
+            write!(f, ":{qualifier}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reason [`FixSession::submit`] refused to send a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitError {
+    /// The outbound [`Throttle`] is out of budget and its policy is
+    /// [`ThrottlePolicy::Reject`]; the message is handed back unsent.
+    Throttled {
+        /// The refused message.
+        message: Box<FixMessage>,
+        /// When the budget allows the next message (ns).
+        until_ns: u64,
+    },
+    /// The message reuses a `ClOrdID` (11) sent recently on this session.
+    DuplicateClOrdId(String),
+    /// The session is a receive-only [`SessionMode::DropCopy`] feed.
+    ReceiveOnly,
+}
+
+impl core::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Throttled { message, until_ns } => write!(
+                f,
+                "outbound rate limit reached for MsgType {}; retry at {until_ns} ns",
+                message.msg_type
+            ),
+            Self::DuplicateClOrdId(id) => write!(f, "duplicate ClOrdID {id}"),
+            Self::ReceiveOnly => write!(f, "drop-copy session cannot send application messages"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// Changes requested by [`FixSession::build_cancel_replace`]; a `None`
+/// field keeps the original order's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Amendment {
+    /// New limit price (tag 44).
+    pub price: Option<i64>,
+    /// New total quantity (tag 38), including any quantity already filled.
+    pub quantity: Option<u64>,
+    /// New time in force (tag 59).
+    pub time_in_force: Option<TimeInForce>,
+}
+
+impl Amendment {
+    /// Change the price.
+    #[must_use]
+    pub const fn price(mut self, price: i64) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// Change the total quantity.
+    #[must_use]
+    pub const fn quantity(mut self, quantity: u64) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    /// Change the time in force.
+    #[must_use]
+    pub const fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+}
+
+/// Reason [`FixSession::rotate_identity`] refused to change identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationError {
+    /// The session is still logged on (or logging on); send Logout first.
+    NotLoggedOut(SessionState),
+    /// The new `CompID`s equal the current ones.
+    SameIdentity,
+}
+
+impl core::fmt::Display for RotationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotLoggedOut(state) => {
+                write!(
+                    f,
+                    "cannot rotate identity in state {state:?}; log out first"
+                )
+            }
+            Self::SameIdentity => write!(f, "new CompIDs equal the current identity"),
+        }
+    }
+}
+
+impl std::error::Error for RotationError {}
+
 /// FIX session context tracking sequence numbers and administrative state.
 pub struct FixSession {
     sender_comp_id: String,
     target_comp_id: String,
-    begin_string: String,
+    begin_string: BeginString,
     /// Next sequence number to assign to an outgoing message.
     outgoing_seq: u64,
     /// Next sequence number expected from the counterparty.
     incoming_seq: u64,
     state: SessionState,
+    inbound_policy: InboundPolicy,
+    drain: DrainMonitor,
+    throttle: Throttle,
+    clock: Arc<dyn Clock>,
+    /// Clock reading when the last inbound message was processed.
+    last_received_ns: Option<u64>,
+    /// Highest `MsgSeqNum` requested for resend or queued while a gap is
+    /// open.
+    resend_until: Option<u64>,
+    /// Messages received beyond a gap, keyed by `MsgSeqNum`; `None` holds
+    /// the place of one already acted on (a `ResendRequest`).
+    queued: BTreeMap<u64, Option<FixMessage>>,
+    /// Credentials and extra fields for our Logon.
+    logon: LogonConfig,
+    /// `HeartBtInt` (tag 108) in seconds: ours until Logon, then negotiated.
+    heart_bt_int: u32,
+    /// Heartbeat deadline; running only while [`SessionState::Active`].
+    heartbeat: Option<HeartbeatTimer>,
+    /// Outstanding `TestRequest`: `TestReqID` and send time (ns).
+    test_request: Option<(String, u64)>,
+    /// Source of generated `TestReqID`s.
+    test_req_counter: u64,
+    /// Connection window, if the venue has one.
+    schedule: Option<SessionSchedule>,
+    /// Start (ns) of the schedule window last seen by [`Self::on_timer`].
+    schedule_window: Option<u64>,
+    /// `Username` and `Password` an inbound Logon must carry.
+    required_credentials: Option<(String, String)>,
+    role: SessionRole,
+    mode: SessionMode,
+    /// [`SessionId::qualifier`].
+    qualifier: Option<String>,
+    /// Third-party routing stamped on application messages.
+    routing: Routing,
+    /// Application messages submitted before logon completed.
+    pending_outbound: VecDeque<FixMessage>,
+    /// Application messages held by the throttle, oldest first.
+    throttled: VecDeque<FixMessage>,
+    /// Fractional-second digits of the `SendingTime`s we stamp.
+    timestamp_precision: TimestampPrecision,
+    /// Recently sent `ClOrdID`s.
+    cl_ord_ids: RecentIds,
+    /// Seconds to wait for the counterparty's Logout.
+    logout_timeout_secs: u32,
+    /// When our Logout was sent (ns), while awaiting the answer.
+    logout_sent_ns: Option<u64>,
+    /// Largest accepted difference between `SendingTime` and our clock
+    /// (seconds); `None` disables the check.
+    max_latency_secs: Option<u32>,
+    /// How long a `NewOrderSingle` may wait for its first
+    /// `ExecutionReport` (ms); `None` disables tracking.
+    order_ack_timeout_ms: Option<u32>,
+    /// `ClOrdID` and send time (ns) of orders awaiting their first
+    /// `ExecutionReport`, oldest first.
+    awaiting_ack: VecDeque<(String, u64)>,
+    /// Message counters for [`Self::stats`]; the sequence numbers and
+    /// `last_received_ns` are filled in when read.
+    metrics: SessionMetrics,
+    /// Receives [`SessionEvent`]s, if installed.
+    event_handler: Option<EventHandler>,
 }
 
 impl FixSession {
     /// Create a new session in the [`SessionState::Disconnected`] state.
     ///
-    /// Sequence numbers start at 1 per FIX specification.
+    /// Sequence numbers start at 1 per FIX specification. For
+    /// [`BeginString::Fixt11`], Logons carry its `DefaultApplVerID` (1137)
+    /// and application messages its `ApplVerID` (1128).
     #[inline(always)]
     #[must_use]
-    pub fn new(sender: &str, target: &str, begin_string: &str) -> Self {
+    pub fn new(sender: &str, target: &str, begin_string: impl Into<BeginString>) -> Self {
         Self {
             sender_comp_id: sender.to_string(),
             target_comp_id: target.to_string(),
-            begin_string: begin_string.to_string(),
+            begin_string: begin_string.into(),
             outgoing_seq: 1,
             incoming_seq: 1,
             state: SessionState::Disconnected,
+            inbound_policy: InboundPolicy::default(),
+            drain: DrainMonitor::default(),
+            throttle: Throttle::default(),
+            clock: Arc::new(SystemClock),
+            last_received_ns: None,
+            resend_until: None,
+            queued: BTreeMap::new(),
+            logon: LogonConfig::default(),
+            heart_bt_int: DEFAULT_HEART_BT_INT,
+            heartbeat: None,
+            test_request: None,
+            test_req_counter: 0,
+            schedule: None,
+            schedule_window: None,
+            required_credentials: None,
+            role: SessionRole::Initiator,
+            mode: SessionMode::Trading,
+            qualifier: None,
+            routing: Routing::new(),
+            pending_outbound: VecDeque::new(),
+            throttled: VecDeque::new(),
+            timestamp_precision: TimestampPrecision::Millis,
+            cl_ord_ids: RecentIds::new(DEFAULT_CL_ORD_ID_WINDOW),
+            logout_timeout_secs: DEFAULT_LOGOUT_TIMEOUT_SECS,
+            logout_sent_ns: None,
+            max_latency_secs: None,
+            order_ack_timeout_ms: None,
+            awaiting_ack: VecDeque::new(),
+            metrics: SessionMetrics::default(),
+            event_handler: None,
+        }
+    }
+
+    /// Restore a session from a [`SessionSnapshot`].
+    #[must_use]
+    pub fn from_snapshot(snapshot: SessionSnapshot) -> Self {
+        Self {
+            sender_comp_id: snapshot.sender_comp_id,
+            target_comp_id: snapshot.target_comp_id,
+            begin_string: snapshot.begin_string,
+            outgoing_seq: snapshot.outgoing_seq,
+            incoming_seq: snapshot.incoming_seq,
+            state: snapshot.state,
+            inbound_policy: InboundPolicy::default(),
+            drain: DrainMonitor::default(),
+            throttle: Throttle::default(),
+            clock: Arc::new(SystemClock),
+            last_received_ns: None,
+            resend_until: None,
+            queued: BTreeMap::new(),
+            logon: LogonConfig::default(),
+            heart_bt_int: DEFAULT_HEART_BT_INT,
+            heartbeat: None,
+            test_request: None,
+            test_req_counter: 0,
+            schedule: None,
+            schedule_window: None,
+            required_credentials: None,
+            role: SessionRole::Initiator,
+            mode: SessionMode::Trading,
+            qualifier: None,
+            routing: Routing::new(),
+            pending_outbound: VecDeque::new(),
+            throttled: VecDeque::new(),
+            timestamp_precision: TimestampPrecision::Millis,
+            cl_ord_ids: RecentIds::new(DEFAULT_CL_ORD_ID_WINDOW),
+            logout_timeout_secs: DEFAULT_LOGOUT_TIMEOUT_SECS,
+            logout_sent_ns: None,
+            max_latency_secs: None,
+            order_ack_timeout_ms: None,
+            awaiting_ack: VecDeque::new(),
+            metrics: SessionMetrics::default(),
+            event_handler: None,
+        }
+    }
+
+    /// Capture the session's identity, sequence numbers, and state.
+    #[must_use]
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            sender_comp_id: self.sender_comp_id.clone(),
+            target_comp_id: self.target_comp_id.clone(),
+            begin_string: self.begin_string,
+            outgoing_seq: self.outgoing_seq,
+            incoming_seq: self.incoming_seq,
+            state: self.state,
+        }
+    }
+
+    /// Next outgoing and expected incoming sequence numbers.
+    #[must_use]
+    pub const fn seq_nums(&self) -> SeqNums {
+        SeqNums {
+            outgoing: self.outgoing_seq,
+            incoming: self.incoming_seq,
+        }
+    }
+
+    /// Write the current sequence numbers to `store`.
+    ///
+    /// Call after each message sent or processed (or at least before
+    /// shutdown) so a restart resumes the sequence instead of restarting at 1.
+    ///
+    /// # Errors
+    ///
+    /// Store write failures.
+    pub fn save_seq_nums(&self, store: &mut (impl SeqNumStore + ?Sized)) -> io::Result<()> {
+        store.save(self.seq_nums())
+    }
+
+    /// Resume the sequence numbers saved in `store`, before Logon.
+    ///
+    /// Returns `false` and leaves the session unchanged when nothing has
+    /// been saved (a first start).
+    ///
+    /// # Errors
+    ///
+    /// Store read failures or a corrupt store.
+    pub fn restore(&mut self, store: &mut (impl SeqNumStore + ?Sized)) -> io::Result<bool> {
+        let Some(seqs) = store.load()? else {
+            return Ok(false);
+        };
+        self.outgoing_seq = seqs.outgoing;
+        self.incoming_seq = seqs.incoming;
+        self.resend_until = None;
+        self.queued.clear();
+        Ok(true)
+    }
+
+    /// Return the session's [`SessionId`].
+    #[must_use]
+    pub fn session_id(&self) -> SessionId {
+        SessionId {
+            qualifier: self.qualifier.clone(),
+            ..SessionId::new(
+                self.begin_string.as_str(),
+                &self.sender_comp_id,
+                &self.target_comp_id,
+            )
+        }
+    }
+
+    /// Set the local qualifier reported in [`Self::session_id`].
+    pub fn set_qualifier(&mut self, qualifier: Option<&str>) {
+        self.qualifier = qualifier.map(str::to_string);
+    }
+
+    /// Switch to new `CompID`s with a fresh sequence space.
+    ///
+    /// Allowed only once Logout has been sent ([`SessionState::LogoutSent`])
+    /// or the session is [`SessionState::Disconnected`]. Both sequence
+    /// numbers restart at 1 and the session ends up `Disconnected`; the
+    /// inbound policy, drain monitor, and throttle are kept. Returns the
+    /// previous [`SessionId`] so its store can be located.
+    ///
+    /// # Errors
+    ///
+    /// [`RotationError::NotLoggedOut`] while logon is pending or active,
+    /// [`RotationError::SameIdentity`] if nothing would change.
+    pub fn rotate_identity(
+        &mut self,
+        sender: &str,
+        target: &str,
+    ) -> Result<SessionId, RotationError> {
+        if matches!(self.state, SessionState::LogonSent | SessionState::Active) {
+            return Err(RotationError::NotLoggedOut(self.state));
+        }
+        if sender == self.sender_comp_id && target == self.target_comp_id {
+            return Err(RotationError::SameIdentity);
         }
+        let previous = self.session_id();
+        self.sender_comp_id = sender.to_string();
+        self.target_comp_id = target.to_string();
+        self.outgoing_seq = 1;
+        self.incoming_seq = 1;
+        self.disconnected();
+        Ok(previous)
+    }
+
+    /// The transport closed: move to [`SessionState::Disconnected`] and drop
+    /// the heartbeat, logout and gap-recovery state, including messages
+    /// held for a gap ([`Self::queued`]). Sequence numbers are kept, so the
+    /// next [`Self::build_logon`] resumes them.
+    pub fn on_disconnect(&mut self) {
+        self.queued.clear();
+        self.disconnected();
     }
 
     /// Return the current session state.
@@ -71,6 +955,158 @@ impl FixSession {
         &self.state
     }
 
+    /// Install the inbound [`InboundPolicy`] applied by [`FixSession::screen_inbound`].
+    pub fn set_inbound_policy(&mut self, policy: InboundPolicy) {
+        self.inbound_policy = policy;
+    }
+
+    /// Return the session's inbound policy.
+    #[must_use]
+    pub const fn inbound_policy(&self) -> &InboundPolicy {
+        &self.inbound_policy
+    }
+
+    /// Screen a raw inbound frame against the session's policy before parsing.
+    ///
+    /// Only frames yielding [`Verdict::Accept`] should be handed to the parser.
+    #[must_use]
+    pub fn screen_inbound(&self, raw: &[u8]) -> Verdict {
+        self.inbound_policy.check(raw)
+    }
+
+    /// Return the session's outbound [`DrainMonitor`].
+    #[must_use]
+    pub const fn drain(&self) -> &DrainMonitor {
+        &self.drain
+    }
+
+    /// Return the session's outbound [`DrainMonitor`] for recording
+    /// enqueue/flush events.
+    pub const fn drain_mut(&mut self) -> &mut DrainMonitor {
+        &mut self.drain
+    }
+
+    /// Return the session's outbound [`Throttle`].
+    #[must_use]
+    pub const fn throttle(&self) -> &Throttle {
+        &self.throttle
+    }
+
+    /// Return the session's outbound [`Throttle`] for checking and
+    /// consuming send budget.
+    pub const fn throttle_mut(&mut self) -> &mut Throttle {
+        &mut self.throttle
+    }
+
+    /// Replace the session's outbound [`Throttle`].
+    pub fn set_throttle(&mut self, throttle: Throttle) {
+        self.throttle = throttle;
+    }
+
+    /// Replace the credentials and extra fields sent in [`Self::build_logon`].
+    pub fn set_logon_config(&mut self, config: LogonConfig) {
+        self.logon = config;
+    }
+
+    /// Credentials and extra fields sent in Logon.
+    #[must_use]
+    pub const fn logon_config(&self) -> &LogonConfig {
+        &self.logon
+    }
+
+    /// Require `Username` (553) and `Password` (554) in the counterparty's
+    /// Logon when accepting; a mismatch is answered with Logout.
+    pub fn require_credentials(&mut self, username: &str, password: &str) {
+        self.required_credentials = Some((username.to_string(), password.to_string()));
+    }
+
+    /// Set which side of the connection the session plays
+    /// ([`SessionRole::Initiator`] by default).
+    pub const fn set_role(&mut self, role: SessionRole) {
+        self.role = role;
+    }
+
+    /// Which side of the connection the session plays.
+    #[must_use]
+    pub const fn role(&self) -> SessionRole {
+        self.role
+    }
+
+    /// Set what the session is used for ([`SessionMode::Trading`] by
+    /// default). Venue-specific drop-copy Logon flags go in
+    /// [`LogonConfig::extra_field`].
+    pub const fn set_mode(&mut self, mode: SessionMode) {
+        self.mode = mode;
+    }
+
+    /// What the session is used for.
+    #[must_use]
+    pub const fn mode(&self) -> SessionMode {
+        self.mode
+    }
+
+    /// Set the `OnBehalfOf*` / `DeliverTo*` fields stamped on outgoing
+    /// application messages ([`Routing::new`], i.e. none, by default).
+    pub fn set_routing(&mut self, routing: Routing) {
+        self.routing = routing;
+    }
+
+    /// Third-party routing stamped on outgoing application messages.
+    #[must_use]
+    pub const fn routing(&self) -> &Routing {
+        &self.routing
+    }
+
+    /// Install `handler` to receive every [`SessionEvent`], replacing any
+    /// previous one.
+    ///
+    /// It is called synchronously from the session method that caused the
+    /// event, before that method returns its actions.
+    pub fn set_event_handler(&mut self, handler: impl FnMut(&SessionEvent) + Send + 'static) {
+        self.event_handler = Some(Box::new(handler));
+    }
+
+    /// Remove the event handler.
+    pub fn clear_event_handler(&mut self) {
+        self.event_handler = None;
+    }
+
+    /// Replace the [`Clock`] used to stamp `SendingTime`, time inbound
+    /// messages, check `SendingTime` latency, and drive [`Self::tick`].
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The session's [`Clock`].
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Remember the last `window` `ClOrdID`s for duplicate detection
+    /// ([`DEFAULT_CL_ORD_ID_WINDOW`] by default; 0 disables the check).
+    /// Shrinking the window forgets the oldest ids.
+    pub fn set_cl_ord_id_window(&mut self, window: usize) {
+        self.cl_ord_ids.set_capacity(window);
+    }
+
+    /// Forget the remembered `ClOrdID`s, e.g. at the start of a trading day.
+    pub fn clear_cl_ord_ids(&mut self) {
+        self.cl_ord_ids.clear();
+    }
+
+    /// Set the precision of the `SendingTime` (52) stamped on every message
+    /// the session builds, submits, or resends (milliseconds by default).
+    pub const fn set_timestamp_precision(&mut self, precision: TimestampPrecision) {
+        self.timestamp_precision = precision;
+    }
+
+    /// Precision of the `SendingTime`s the session stamps.
+    #[must_use]
+    pub const fn timestamp_precision(&self) -> TimestampPrecision {
+        self.timestamp_precision
+    }
+
     /// Increment the outgoing sequence number and return the value assigned
     /// to the next message.
     ///
@@ -82,335 +1118,3778 @@ impl FixSession {
         seq
     }
 
-    /// Validate that an incoming message has the expected sequence number.
+    /// Check an incoming message's sequence number against the expected one.
     ///
-    /// Returns `true` and advances the expected counter when the sequence
-    /// matches; returns `false` without updating state when it does not.
+    /// Advances the expected counter on [`SeqCheck::Expected`]; otherwise
+    /// leaves state unchanged. [`Self::on_message`] acts on the outcome.
     #[inline(always)]
-    pub const fn validate_incoming_seq(&mut self, seq: u64) -> bool {
-        if seq == self.incoming_seq {
+    pub const fn validate_incoming_seq(&mut self, seq: u64) -> SeqCheck {
+        let expected = self.incoming_seq;
+        if seq == expected {
             self.incoming_seq += 1;
-            true
+            SeqCheck::Expected
+        } else if seq < expected {
+            SeqCheck::TooLow {
+                expected,
+                received: seq,
+            }
         } else {
-            false
+            SeqCheck::TooHigh {
+                expected,
+                received: seq,
+            }
+        }
+    }
+
+    /// Stamp an application message with the session header, the next
+    /// `MsgSeqNum` and a fresh `SendingTime`, and return the frame.
+    ///
+    /// Before the session is [`SessionState::Active`] the message is held
+    /// instead (returning `Ok(None)`) and sent, with the sequence number and
+    /// time of that moment, once logon completes.
+    ///
+    /// While active the message spends [`Throttle`] budget
+    /// ([`Self::set_throttle`]). Out of budget, it is held (`Ok(None)`) and
+    /// sent by [`Self::on_timer`] once the budget recovers, or refused under
+    /// [`ThrottlePolicy::Reject`]. Exempt types (cancels by default) skip
+    /// messages held by the throttle.
+    ///
+    /// A `NewOrderSingle`, `OrderCancelRequest` or
+    /// `OrderCancelReplaceRequest` whose `ClOrdID` was accepted recently
+    /// ([`Self::set_cl_ord_id_window`]) is refused, even before logon.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::Throttled`] when the throttle refuses the message,
+    /// [`SubmitError::DuplicateClOrdId`] for a reused `ClOrdID`,
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn submit(&mut self, msg: FixMessage) -> Result<Option<Vec<u8>>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let cl_ord_id = CL_ORD_ID_MSG_TYPES
+            .contains(&msg.msg_type.as_str())
+            .then(|| msg.get(tag::CL_ORD_ID).map(str::to_string))
+            .flatten();
+        if let Some(id) = cl_ord_id.as_deref() {
+            if self.cl_ord_ids.contains(id) {
+                self.emit(&SessionEvent::DuplicateClOrdId {
+                    cl_ord_id: id.to_string(),
+                });
+                return Err(SubmitError::DuplicateClOrdId(id.to_string()));
+            }
+        }
+        let result = self.submit_unchecked(msg);
+        if let (Some(id), Ok(_)) = (cl_ord_id, &result) {
+            self.cl_ord_ids.insert(id);
         }
+        result
+    }
+
+    /// Number of application messages held by the throttle.
+    #[must_use]
+    pub fn throttled(&self) -> usize {
+        self.throttled.len()
+    }
+
+    /// Drop the messages held by the throttle and return them, oldest first.
+    pub fn take_throttled(&mut self) -> Vec<FixMessage> {
+        self.throttled.drain(..).collect()
+    }
+
+    /// Application messages held by [`Self::submit`] until logon, oldest
+    /// first.
+    #[must_use]
+    pub fn pending_outbound(&self) -> impl ExactSizeIterator<Item = &FixMessage> {
+        self.pending_outbound.iter()
     }
 
-    /// Build a Logon message (`MsgType` "A") and transition to
-    /// [`SessionState::LogonSent`].
-    pub fn build_logon(&mut self) -> Vec<u8> {
-        let seq = self.next_outgoing_seq();
-        self.state = SessionState::LogonSent;
-        self.build_admin("A", seq)
-    }
+    /// Drop the held application messages and return them.
+    pub fn take_pending_outbound(&mut self) -> Vec<FixMessage> {
+        self.pending_outbound.drain(..).collect()
+    }
+
+    /// Build a Logon message (`MsgType` "A") and transition to
+    /// [`SessionState::LogonSent`].
+    ///
+    /// The Logon carries `EncryptMethod` (98), our `HeartBtInt` (108,
+    /// [`Self::set_heart_bt_int`]), and the `Username` (553), `Password`
+    /// (554), and extra fields of the [`LogonConfig`]
+    /// ([`Self::set_logon_config`]). With [`LogonConfig::reset_seq_num`]
+    /// both sequence numbers restart at 1 (the Logon itself is 1) and the
+    /// Logon carries `ResetSeqNumFlag` (141) Y.
+    pub fn build_logon(&mut self) -> Vec<u8> {
+        self.logon_frame(self.logon.reset_seq_num)
+    }
+
+    /// [`Self::build_logon`], restarting the sequence numbers and setting
+    /// `ResetSeqNumFlag` when `reset`.
+    fn logon_frame(&mut self, reset: bool) -> Vec<u8> {
+        if reset {
+            self.restart_sequences();
+        }
+        let seq = self.send_seq("A");
+        self.set_state(SessionState::LogonSent);
+        let mut b = self.admin_builder("A", seq);
+        b.field_u64(tag::ENCRYPT_METHOD, u64::from(self.logon.encrypt_method))
+            .field_u64(tag::HEART_BT_INT, u64::from(self.heart_bt_int));
+        if reset {
+            b.field(tag::RESET_SEQ_NUM_FLAG, "Y");
+        }
+        if let Some(id) = self.begin_string.appl_ver_id() {
+            b.field(tag::DEFAULT_APPL_VER_ID, id);
+        }
+        if let Some(username) = &self.logon.username {
+            b.field(tag::USERNAME, username);
+        }
+        if let Some(password) = &self.logon.password {
+            b.field(tag::PASSWORD, password);
+        }
+        for (tag, value) in &self.logon.extra {
+            b.field(*tag, value);
+        }
+        b.build()
+    }
+
+    /// Build a Logout message (`MsgType` "5") and transition to
+    /// [`SessionState::LogoutSent`].
+    ///
+    /// [`Self::on_timer`] forces a disconnect if the counterparty's Logout
+    /// does not follow within the logout timeout.
+    pub fn build_logout(&mut self) -> Vec<u8> {
+        self.logout(None, self.clock.now_ns())
+    }
+
+    /// [`Self::build_logout`] with a `Text` (58) reason.
+    pub fn build_logout_with_text(&mut self, text: &str) -> Vec<u8> {
+        self.logout(Some(text), self.clock.now_ns())
+    }
+
+    /// Hard reset for operational recovery: restart both sequence numbers
+    /// at 1 and drop gap-recovery state.
+    ///
+    /// With `store`, the messages of the old sequence space are deleted
+    /// first, since they can no longer be resent. With `send_reset`, returns
+    /// a Logon with `ResetSeqNumFlag` (141) Y as message 1 and enters
+    /// [`SessionState::LogonSent`]: the counterparty restarts both of its
+    /// sequence numbers too and confirms with its own reset Logon, which
+    /// brings the session back to [`SessionState::Active`]. Without it, the
+    /// counterparty must restart its sequences by agreement out of band.
+    ///
+    /// # Errors
+    ///
+    /// Store write failures; the sequence numbers are then left unchanged.
+    pub fn reset_sequences(
+        &mut self,
+        store: Option<&mut dyn MessageStore>,
+        send_reset: bool,
+    ) -> io::Result<Option<Vec<u8>>> {
+        if let Some(store) = store {
+            store.reset_sequence()?;
+        }
+        self.restart_sequences();
+        Ok(send_reset.then(|| self.logon_frame(true)))
+    }
+
+    /// Set how long to wait for the counterparty's Logout after sending ours
+    /// ([`DEFAULT_LOGOUT_TIMEOUT_SECS`] by default).
+    pub const fn set_logout_timeout(&mut self, secs: u32) {
+        self.logout_timeout_secs = secs;
+    }
+
+    /// Seconds to wait for the counterparty's Logout.
+    #[must_use]
+    pub const fn logout_timeout(&self) -> u32 {
+        self.logout_timeout_secs
+    }
+
+    /// Reject inbound messages whose `SendingTime` (52) differs from the
+    /// session clock by more than `secs` (`None`, the default, disables the
+    /// check). Retransmissions (`PossDupFlag` Y) are exempt.
+    pub const fn set_max_latency(&mut self, secs: Option<u32>) {
+        self.max_latency_secs = secs;
+    }
+
+    /// Largest accepted `SendingTime` difference in seconds, if checked.
+    #[must_use]
+    pub const fn max_latency(&self) -> Option<u32> {
+        self.max_latency_secs
+    }
+
+    /// Report a `NewOrderSingle` still without an `ExecutionReport`
+    /// `millis` after it was sent as [`SessionEvent::OrderAckTimeout`]
+    /// (`None`, the default, disables the tracking and forgets the orders
+    /// awaiting one).
+    pub fn set_order_ack_timeout_ms(&mut self, millis: Option<u32>) {
+        self.order_ack_timeout_ms = millis;
+        if millis.is_none() {
+            self.awaiting_ack.clear();
+        }
+    }
+
+    /// Order acknowledgement timeout in milliseconds, if tracked.
+    #[must_use]
+    pub const fn order_ack_timeout_ms(&self) -> Option<u32> {
+        self.order_ack_timeout_ms
+    }
+
+    /// `ClOrdID`s of sent `NewOrderSingle`s still awaiting their first
+    /// `ExecutionReport`, oldest first.
+    pub fn unacknowledged_orders(&self) -> impl Iterator<Item = &str> {
+        self.awaiting_ack.iter().map(|(id, _)| id.as_str())
+    }
+
+    /// Build a Heartbeat message (`MsgType` "0") without changing session state.
+    pub fn build_heartbeat(&mut self) -> Vec<u8> {
+        let seq = self.send_seq("0");
+        self.build_admin("0", seq)
+    }
+
+    /// Build a `NewOrderSingle` (`MsgType` "D") from an ALICE-Ledger [`Order`].
+    ///
+    /// The `symbol` parameter provides the instrument identifier (tag 55),
+    /// since [`Order`] does not carry a symbol string. `TransactTime` (60)
+    /// is the session clock, and a GTD order carries its expiry as
+    /// `ExpireTime` (126). An order id reused as
+    /// `ClOrdID` within the duplicate window is still built, but reported as
+    /// [`SessionEvent::DuplicateClOrdId`]; [`Self::submit`] refuses instead.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_new_order(&mut self, order: &Order, symbol: &str) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq("D");
+        let price_str = order.price.to_string();
+        let qty_str = order.quantity.to_string();
+        let cl_ord_id = order.id.0.to_string();
+        self.note_cl_ord_id(&cl_ord_id);
+        self.await_ack(&cl_ord_id);
+
+        let mut b = self.app_builder("D", seq);
+        b.field(tag::CL_ORD_ID, &cl_ord_id)
+            .field(tag::SYMBOL, symbol)
+            .field(tag::SIDE, alice_side_to_fix(order.side))
+            .field(tag::ORD_TYPE, alice_ord_type_to_fix(order.order_type))
+            .field(tag::PRICE, &price_str)
+            .field(tag::ORDER_QTY, &qty_str)
+            .field(tag::TIME_IN_FORCE, alice_tif_to_fix(order.time_in_force));
+        if let TimeInForce::GTD { expiry_ns } = order.time_in_force {
+            b.field_timestamp(tag::EXPIRE_TIME, expiry_ns);
+        }
+        b.field_timestamp(tag::TRANSACT_TIME, self.clock.now_ns());
+        Ok(b.build())
+    }
+
+    /// Build an `OrderCancelRequest` (`MsgType` "F") for an ALICE-Ledger
+    /// [`Order`] whose working `ClOrdID` is `orig_cl_ord_id` (tag 41).
+    ///
+    /// The request gets its own `ClOrdID`, `<order id>-<MsgSeqNum>`, which
+    /// is the `orig_cl_ord_id` of any later request on the order. `Side`,
+    /// `Symbol`, `OrderQty` and `TransactTime` (the session clock) are
+    /// filled in from `order` and `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_cancel_request(
+        &mut self,
+        order: &Order,
+        orig_cl_ord_id: &str,
+        symbol: &str,
+    ) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq("F");
+        let cl_ord_id = format!("{}-{seq}", order.id.0);
+        self.note_cl_ord_id(&cl_ord_id);
+
+        let mut b = self.app_builder("F", seq);
+        b.field(tag::ORIG_CL_ORD_ID, orig_cl_ord_id)
+            .field(tag::CL_ORD_ID, &cl_ord_id)
+            .field(tag::SYMBOL, symbol)
+            .field(tag::SIDE, alice_side_to_fix(order.side))
+            .field_u64(tag::ORDER_QTY, order.quantity)
+            .field_timestamp(tag::TRANSACT_TIME, self.clock.now_ns());
+        Ok(b.build())
+    }
+
+    /// Build an `OrderCancelReplaceRequest` (`MsgType` "G") applying
+    /// `amendment` to an ALICE-Ledger [`Order`] whose working `ClOrdID` is
+    /// `orig_cl_ord_id` (tag 41).
+    ///
+    /// As with [`Self::build_cancel_request`], the request's `ClOrdID` is
+    /// `<order id>-<MsgSeqNum>`; once the replace is accepted it is the
+    /// `orig_cl_ord_id` of the next amendment. The unchanged order terms
+    /// (`Side`, `OrdType`, and any price, quantity or time in force not
+    /// amended) are echoed from `order`, with `TransactTime` from the
+    /// session clock and `ExpireTime` for GTD.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_cancel_replace(
+        &mut self,
+        order: &Order,
+        orig_cl_ord_id: &str,
+        symbol: &str,
+        amendment: Amendment,
+    ) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq("G");
+        let cl_ord_id = format!("{}-{seq}", order.id.0);
+        self.note_cl_ord_id(&cl_ord_id);
+        let price = amendment.price.unwrap_or(order.price);
+        let quantity = amendment.quantity.unwrap_or(order.quantity);
+        let tif = amendment.time_in_force.unwrap_or(order.time_in_force);
+
+        let mut b = self.app_builder("G", seq);
+        b.field(tag::ORIG_CL_ORD_ID, orig_cl_ord_id)
+            .field(tag::CL_ORD_ID, &cl_ord_id)
+            .field(tag::SYMBOL, symbol)
+            .field(tag::SIDE, alice_side_to_fix(order.side))
+            .field(tag::ORD_TYPE, alice_ord_type_to_fix(order.order_type))
+            .field_value(tag::PRICE, price)
+            .field_u64(tag::ORDER_QTY, quantity)
+            .field(tag::TIME_IN_FORCE, alice_tif_to_fix(tif));
+        if let TimeInForce::GTD { expiry_ns } = tif {
+            b.field_timestamp(tag::EXPIRE_TIME, expiry_ns);
+        }
+        b.field_timestamp(tag::TRANSACT_TIME, self.clock.now_ns());
+        Ok(b.build())
+    }
+
+    /// Build an `OrderStatusRequest` (`MsgType` "H") for an ALICE-Ledger
+    /// [`Order`] working under `cl_ord_id`, e.g. to poll open orders after
+    /// a reconnect.
+    ///
+    /// `OrdStatusReqID` (790) is `<order id>-<MsgSeqNum>`; the answer is an
+    /// `ExecutionReport` with `ExecType` I
+    /// ([`crate::execution_report::ExecutionReport::is_status_report`])
+    /// echoing it. The `ClOrdID` is not checked for duplicates, as it names
+    /// an existing order.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_order_status_request(
+        &mut self,
+        order: &Order,
+        cl_ord_id: &str,
+        symbol: &str,
+    ) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq("H");
+        let mut b = self.app_builder("H", seq);
+        b.field(tag::CL_ORD_ID, cl_ord_id)
+            .field(tag::SYMBOL, symbol)
+            .field(tag::SIDE, alice_side_to_fix(order.side))
+            .field(tag::ORD_STATUS_REQ_ID, &format!("{}-{seq}", order.id.0));
+        Ok(b.build())
+    }
+
+    /// Frame an [`ExecutionReport`] (`MsgType` "8") for the counterparty,
+    /// as an acceptor playing the exchange; see
+    /// [`crate::convert::fill_execution_report`] for reports of ledger fills.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_execution_report(
+        &mut self,
+        report: &ExecutionReport,
+    ) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq("8");
+        let mut b = report.to_builder(self.begin_string);
+        self.write_app_header(&mut b, seq);
+        Ok(b.build())
+    }
+
+    /// Frame an `OrderMassStatusRequest` (`MsgType` "AF") for open-order
+    /// discovery after a reconnect. Register its `MassStatusReqID` with a
+    /// [`crate::mass_status::MassStatusCollector`] to gather the answering
+    /// `ExecutionReport`s.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_mass_status_request(
+        &mut self,
+        request: &OrderMassStatusRequest,
+    ) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        let seq = self.send_seq(ORDER_MASS_STATUS_REQUEST);
+        let mut b = request.to_builder(self.begin_string);
+        self.write_app_header(&mut b, seq);
+        Ok(b.build())
+    }
+
+    /// Frame a `NewOrderList` (`MsgType` "E") for a basket of orders.
+    ///
+    /// Each entry's `ClOrdID` is remembered like [`Self::build_new_order`]'s:
+    /// a reused one raises [`SessionEvent::DuplicateClOrdId`] and the list
+    /// is sent anyway. Progress arrives as
+    /// [`crate::list::ListStatus`] (35=N) and per-order `ExecutionReport`s.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::ReceiveOnly`] on a drop-copy session.
+    pub fn build_new_order_list(&mut self, list: &NewOrderList) -> Result<Vec<u8>, SubmitError> {
+        if self.mode == SessionMode::DropCopy {
+            return Err(SubmitError::ReceiveOnly);
+        }
+        for order in &list.orders {
+            self.note_cl_ord_id(&order.cl_ord_id);
+        }
+        let seq = self.send_seq(list_msg_type::NEW_ORDER_LIST);
+        let mut b = list.to_builder(self.begin_string);
+        self.write_app_header(&mut b, seq);
+        Ok(b.build())
+    }
+
+    /// Message counts by type, sequence numbers, last send and receive
+    /// times, and gap / resend counts.
+    #[must_use]
+    pub fn stats(&self) -> SessionMetrics {
+        SessionMetrics {
+            outgoing_seq: self.outgoing_seq,
+            incoming_seq: self.incoming_seq,
+            last_received_ns: self.last_received_ns,
+            ..self.metrics.clone()
+        }
+    }
+
+    /// Clock reading (ns) when [`Self::on_message`] last processed an
+    /// inbound message, for heartbeat timeout checks.
+    #[must_use]
+    pub const fn last_received_ns(&self) -> Option<u64> {
+        self.last_received_ns
+    }
+
+    /// Set the `HeartBtInt` (seconds) to request in Logon.
+    ///
+    /// A `HeartBtInt` in the counterparty's Logon replaces it.
+    pub const fn set_heart_bt_int(&mut self, secs: u32) {
+        self.heart_bt_int = secs;
+    }
+
+    /// `HeartBtInt` in seconds: the value we request until Logon completes,
+    /// then the negotiated one.
+    #[must_use]
+    pub const fn heart_bt_int(&self) -> u32 {
+        self.heart_bt_int
+    }
+
+    /// Nanoseconds until a Heartbeat is due, measured on the session's
+    /// [`Clock`]; zero if already due, `None` unless the session is active.
+    #[must_use]
+    pub fn time_until_next_heartbeat(&self) -> Option<u64> {
+        self.heartbeat
+            .as_ref()
+            .map(|timer| timer.next_due_ns().saturating_sub(self.clock.now_ns()))
+    }
+
+    /// Number of inbound messages held until a sequence gap is filled.
+    #[must_use]
+    pub fn queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Restrict the session to a connection window (`None`: always open).
+    pub const fn set_schedule(&mut self, schedule: Option<SessionSchedule>) {
+        self.schedule = schedule;
+    }
+
+    /// Connection window, if any.
+    #[must_use]
+    pub const fn schedule(&self) -> Option<&SessionSchedule> {
+        self.schedule.as_ref()
+    }
+
+    /// Whether an initiator should connect and send Logon at `now_ns`:
+    /// disconnected and inside the schedule window. Always `false` for an
+    /// acceptor.
+    #[must_use]
+    pub fn should_logon(&self, now_ns: u64) -> bool {
+        self.role == SessionRole::Initiator
+            && self.state == SessionState::Disconnected
+            && self.schedule.as_ref().is_none_or(|s| s.is_active(now_ns))
+    }
+
+    /// `TestReqID` of our outstanding `TestRequest`, if any.
+    #[must_use]
+    pub fn pending_test_req_id(&self) -> Option<&str> {
+        self.test_request.as_ref().map(|(id, _)| id.as_str())
+    }
+
+    /// Drive timers at `now_ns` (on the session's [`Clock`] timeline).
+    ///
+    /// - After 1.5 × `HeartBtInt` with nothing received, sends a
+    ///   `TestRequest` with a fresh `TestReqID`; the counterparty's
+    ///   Heartbeat echoing it clears the request.
+    /// - If that Heartbeat has not arrived one interval later, sends Logout
+    ///   and [`SessionAction::Disconnect`].
+    /// - Sends a Heartbeat when `HeartBtInt` has passed without outgoing
+    ///   traffic.
+    /// - With a [`SessionSchedule`], sends Logout once the window closes and,
+    ///   while disconnected, restarts both sequence numbers at 1 when a new
+    ///   window opens (the first window observed keeps them).
+    /// - Once our Logout has gone unanswered for the logout timeout, moves to
+    ///   [`SessionState::Disconnected`] with [`SessionAction::ForceDisconnect`].
+    /// - While active, sends messages held by the throttle, oldest first,
+    ///   as far as the budget allows.
+    /// - Reports each `NewOrderSingle` unacknowledged for the order
+    ///   acknowledgement timeout as [`SessionEvent::OrderAckTimeout`], in any
+    ///   state.
+    pub fn on_timer(&mut self, now_ns: u64) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        self.expire_acks(now_ns);
+        if let Some(sent_ns) = self.logout_sent_ns {
+            if now_ns.saturating_sub(sent_ns) >= u64::from(self.logout_timeout_secs) * NANOS_PER_SEC
+            {
+                self.disconnected();
+                self.emit(&SessionEvent::LoggedOut {
+                    reason: Some("Logout not answered".to_string()),
+                });
+                actions.push(SessionAction::ForceDisconnect);
+                return actions;
+            }
+        }
+        if let Some(schedule) = self.schedule {
+            match schedule.window(now_ns) {
+                None if matches!(self.state, SessionState::LogonSent | SessionState::Active) => {
+                    let logout = self.logout(Some("end of session window"), now_ns);
+                    actions.push(SessionAction::Send(logout));
+                    return actions;
+                }
+                Some((start, _)) if self.schedule_window != Some(start) => {
+                    if self.schedule_window.is_some() && self.state == SessionState::Disconnected {
+                        self.restart_sequences();
+                    }
+                    self.schedule_window = Some(start);
+                }
+                _ => {}
+            }
+        }
+        if self.state == SessionState::Active {
+            while let Some(held) = self.throttled.front() {
+                if !self.throttle.check(&held.msg_type, now_ns).is_send() {
+                    break;
+                }
+                if let Some(held) = self.throttled.pop_front() {
+                    let frame = self.stamp_outbound(held);
+                    actions.push(SessionAction::Send(frame));
+                }
+            }
+        }
+        let Some(interval) = self.heartbeat.as_ref().map(|t| t.config().interval_ns()) else {
+            return actions;
+        };
+        if let Some((_, sent_ns)) = self.test_request {
+            if now_ns.saturating_sub(sent_ns) >= interval {
+                self.logout_and_disconnect("TestRequest not answered", &mut actions);
+                return actions;
+            }
+        } else if now_ns.saturating_sub(self.last_received_ns.unwrap_or(0))
+            >= interval + interval / 2
+        {
+            self.test_req_counter += 1;
+            let id = format!("TEST{}", self.test_req_counter);
+            let seq = self.timer_seq(now_ns, "1");
+            let mut b = self.admin_builder("1", seq);
+            b.field(tag::TEST_REQ_ID, &id);
+            actions.push(SessionAction::Send(b.build()));
+            self.emit(&SessionEvent::HeartbeatMissed {
+                test_req_id: id.clone(),
+            });
+            self.test_request = Some((id, now_ns));
+        }
+        if self.heartbeat.as_ref().is_some_and(|t| t.is_due(now_ns)) {
+            let seq = self.timer_seq(now_ns, "0");
+            actions.push(SessionAction::Send(self.build_admin("0", seq)));
+        }
+        actions
+    }
+
+    /// [`Self::on_timer`] at the session clock's current time.
+    pub fn tick(&mut self) -> Vec<SessionAction> {
+        self.on_timer(self.clock.now_ns())
+    }
+
+    /// Rewrite a stored frame for retransmission: `PossDupFlag` (43) Y,
+    /// `OrigSendingTime` (122) set to the original `SendingTime` (kept if
+    /// the frame was already a resend), a fresh `SendingTime`, and
+    /// recomputed `BodyLength` / `CheckSum`. Every other byte is kept as
+    /// stored, so repeating groups, data fields (which may contain SOH) and
+    /// the `Signature` (89) go out exactly as first sent.
+    ///
+    /// # Errors
+    ///
+    /// The stored frame is not a complete frame: the envelope, a field, or
+    /// the length in front of a data field does not hold.
+    pub fn poss_dup_frame(&self, raw: &[u8]) -> Result<Vec<u8>, ParseError> {
+        rewrite_poss_dup(raw, &self.now_timestamp()).map(|(_, frame)| frame)
+    }
+
+    /// Record `frame`, which we sent, in `store` under its `MsgSeqNum`,
+    /// stamped with the session clock, for later [`Self::replay`].
+    ///
+    /// Call for every frame written except replayed ones, which keep the
+    /// original's sequence number.
+    ///
+    /// # Errors
+    ///
+    /// `frame` does not parse or lacks `MsgSeqNum`
+    /// ([`io::ErrorKind::InvalidData`]), or the store write fails.
+    pub fn store_sent(
+        &self,
+        store: &mut (impl MessageStore + ?Sized),
+        frame: &[u8],
+    ) -> io::Result<()> {
+        let seq = parser::parse(frame)
+            .ok()
+            .and_then(|msg| msg.get_u64(tag::MSG_SEQ_NUM))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "frame without MsgSeqNum"))?;
+        store.put(seq, self.clock.now_ns(), frame)
+    }
+
+    /// [`Self::on_message`] backed by `store`: frames the session sends in
+    /// response are recorded with [`Self::store_sent`], and each
+    /// [`SessionAction::Resend`] is answered from `store` as by
+    /// [`Self::resolve_resends`].
+    ///
+    /// # Errors
+    ///
+    /// Store read or write failures.
+    pub fn on_message_with_store(
+        &mut self,
+        msg: &FixMessage,
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Vec<SessionAction>> {
+        let actions = self.on_message(msg);
+        for action in &actions {
+            if let SessionAction::Send(frame) = action {
+                self.store_sent(store, frame)?;
+            }
+        }
+        self.resolve_resends(actions, store)
+    }
+
+    /// Replace each [`SessionAction::Resend`] in `actions` with the frames
+    /// from [`Self::replay`], so the result only needs to be written out.
+    ///
+    /// # Errors
+    ///
+    /// Store read failures.
+    pub fn resolve_resends(
+        &self,
+        actions: Vec<SessionAction>,
+        store: &mut (impl MessageStore + ?Sized),
+    ) -> io::Result<Vec<SessionAction>> {
+        let mut out = Vec::with_capacity(actions.len());
+        for action in actions {
+            match action {
+                SessionAction::Resend { begin, end } => out.extend(
+                    self.replay(store, begin, end)?
+                        .into_iter()
+                        .map(SessionAction::Send),
+                ),
+                other => out.push(other),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Frames answering a counterparty `ResendRequest` for `begin..=end`
+    /// (`end` 0 means through the last message sent), read from `store`.
+    ///
+    /// Application messages and Rejects are resent as by
+    /// [`Self::poss_dup_frame`]. Other administrative messages, and sequence numbers
+    /// the store does not hold, are not retransmitted: each run of them is
+    /// replaced by one `SequenceReset`-`GapFill` (35=4, 123=Y) whose
+    /// `NewSeqNo` points past the run. Sequence numbers are not consumed.
+    ///
+    /// # Errors
+    ///
+    /// Store read failures, and a stored frame that cannot be rewritten
+    /// ([`io::ErrorKind::InvalidData`]); it is never replaced by a
+    /// `GapFill` in its place.
+    pub fn replay(
+        &self,
+        store: &mut (impl MessageStore + ?Sized),
+        begin: u64,
+        end: u64,
+    ) -> io::Result<Vec<Vec<u8>>> {
+        let last = self.outgoing_seq - 1;
+        let end = if end == 0 { last } else { end.min(last) };
+        let now = self.now_timestamp();
+        let mut frames = Vec::new();
+        let mut gap_from = None;
+        for seq in begin.max(1)..=end {
+            let resend = match store.get(seq)? {
+                Some(raw) => Some(rewrite_poss_dup(&raw, &now).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("stored frame {seq} cannot be resent: {e}"),
+                    )
+                })?),
+                None => None,
+            };
+            let Some((_, frame)) = resend.filter(|(msg_type, _)| {
+                !matches!(msg_type.as_str(), "0" | "1" | "2" | "4" | "5" | "A")
+            }) else {
+                gap_from.get_or_insert(seq);
+                continue;
+            };
+            if let Some(from) = gap_from.take() {
+                frames.push(self.build_gap_fill(from, seq));
+            }
+            frames.push(frame);
+        }
+        if let Some(from) = gap_from {
+            frames.push(self.build_gap_fill(from, end + 1));
+        }
+        Ok(frames)
+    }
+
+    /// Process one inbound message and return the actions to perform.
+    ///
+    /// - A missing or too-low `MsgSeqNum` (without `PossDupFlag`) and any
+    ///   message other than Logon while [`SessionState::Disconnected`] are
+    ///   answered with Logout and [`SessionAction::Disconnect`].
+    /// - A `MsgSeqNum` above the expected one sends a `ResendRequest` for
+    ///   the missing range (once per range) and queues the message; queued
+    ///   messages are processed in order as the gap is filled. Logon,
+    ///   Logout and `ResendRequest` are acted on immediately, so two
+    ///   sessions that both have a gap do not wait on each other's replay.
+    /// - `TestRequest` is answered with a Heartbeat echoing `TestReqID`;
+    ///   `ResendRequest` becomes [`SessionAction::Resend`]; `SequenceReset`
+    ///   moves the expected sequence number forward.
+    /// - Logon completes a pending logon; an acceptor answers the
+    ///   counterparty's Logon with Logon (echoing `HeartBtInt`), an
+    ///   initiator rejects a Logon it did not ask for. A Logon with
+    ///   `ResetSeqNumFlag` Y while active restarts both sequence numbers
+    ///   and is confirmed with our own reset Logon.
+    /// - Logout is answered with Logout unless we sent ours first. The side
+    ///   that started the logout disconnects, as does an acceptor answering
+    ///   one.
+    /// - Application messages become [`SessionAction::Deliver`]; Rejects
+    ///   become [`SessionAction::DeliverAdmin`].
+    pub fn on_message(&mut self, msg: &FixMessage) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        self.last_received_ns = Some(self.clock.now_ns());
+        *self
+            .metrics
+            .received
+            .entry(msg.msg_type.clone())
+            .or_default() += 1;
+
+        let Some(seq) = msg.get_u64(tag::MSG_SEQ_NUM) else {
+            self.logout_and_disconnect("MsgSeqNum missing", &mut actions);
+            return actions;
+        };
+        if self.state == SessionState::Disconnected && msg.msg_type != "A" {
+            self.logout_and_disconnect("first message must be Logon", &mut actions);
+            return actions;
+        }
+        if msg.msg_type == "A"
+            && matches!(
+                self.state,
+                SessionState::Disconnected | SessionState::LogonSent
+            )
+        {
+            let checked = match (self.state, self.role) {
+                (SessionState::Disconnected, SessionRole::Initiator) => {
+                    Err("Logon received before ours".to_string())
+                }
+                _ => self.validate_logon(msg),
+            };
+            if let Err(text) = checked {
+                self.logout_and_disconnect(&text, &mut actions);
+                return actions;
+            }
+        }
+        // A Logon with ResetSeqNumFlag starts a new sequence space; an
+        // accepted reset, at logon or in session, also restarts ours
+        // (answered in `on_admin`).
+        if msg.msg_type == "A" && msg.get_bool(tag::RESET_SEQ_NUM_FLAG) == Some(true) {
+            if matches!(
+                self.state,
+                SessionState::Disconnected | SessionState::Active
+            ) {
+                self.restart_sequences();
+            }
+            self.incoming_seq = seq;
+        }
+        // SequenceReset-Reset ignores MsgSeqNum.
+        if msg.msg_type == "4" && msg.get_bool(tag::GAP_FILL_FLAG) != Some(true) {
+            self.apply_new_seq_no(msg, &mut actions);
+            self.drain_queued(&mut actions);
+            return actions;
+        }
+
+        match self.validate_incoming_seq(seq) {
+            SeqCheck::TooLow { expected, received } => {
+                if msg.get_bool(tag::POSS_DUP_FLAG) != Some(true) {
+                    let text =
+                        format!("MsgSeqNum too low, expecting {expected} but received {received}");
+                    self.logout_and_disconnect(&text, &mut actions);
+                } else if self.mode == SessionMode::DropCopy && !is_admin_msg_type(&msg.msg_type) {
+                    actions.push(SessionAction::Deliver(msg.clone()));
+                }
+                return actions;
+            }
+            SeqCheck::TooHigh { .. } => {
+                if msg.msg_type == "A" || msg.msg_type == "5" {
+                    self.on_admin(msg, &mut actions);
+                }
+                if self.state == SessionState::Disconnected {
+                    return actions;
+                }
+                // Answer a ResendRequest before asking for our own gap: the
+                // counterparty may be holding our missing messages behind
+                // a gap of its own.
+                let answered = msg.msg_type == "2";
+                if answered {
+                    self.on_admin(msg, &mut actions);
+                }
+                // Request only what is neither requested nor queued yet.
+                let from = self
+                    .resend_until
+                    .map_or(self.incoming_seq, |end| end + 1)
+                    .max(self.incoming_seq);
+                if seq > from {
+                    self.metrics.gaps += 1;
+                    let out_seq = self.send_seq("2");
+                    let mut b = self.admin_builder("2", out_seq);
+                    b.field_u64(tag::BEGIN_SEQ_NO, from)
+                        .field_u64(tag::END_SEQ_NO, seq - 1);
+                    actions.push(SessionAction::Send(b.build()));
+                    self.emit(&SessionEvent::GapDetected {
+                        begin: from,
+                        end: seq - 1,
+                    });
+                }
+                self.resend_until = Some(self.resend_until.map_or(seq, |end| end.max(seq)));
+                self.queued.insert(seq, (!answered).then(|| msg.clone()));
+                return actions;
+            }
+            SeqCheck::Expected => {}
+        }
+        if let Some(text) = self.sending_time_problem(msg) {
+            self.reject(
+                msg,
+                SessionRejectReason::SendingTimeAccuracy,
+                tag::SENDING_TIME,
+                text,
+                &mut actions,
+            );
+        } else {
+            self.on_admin(msg, &mut actions);
+        }
+        self.drain_queued(&mut actions);
+        actions
+    }
+
+    // -----------------------------------------------------------------------
+    // Private helpers
+    // -----------------------------------------------------------------------
+
+    /// Construct a minimal administrative message with standard header fields.
+    fn build_admin(&self, msg_type: &str, seq: u64) -> Vec<u8> {
+        self.admin_builder(msg_type, seq).build()
+    }
+
+    /// `SequenceReset`-`GapFill` sent as `seq`, skipping to `new_seq_no`.
+    fn build_gap_fill(&self, seq: u64, new_seq_no: u64) -> Vec<u8> {
+        let mut b = self.admin_builder("4", seq);
+        b.field(tag::POSS_DUP_FLAG, "Y")
+            .field(tag::GAP_FILL_FLAG, "Y")
+            .field_u64(tag::NEW_SEQ_NO, new_seq_no);
+        b.build()
+    }
+
+    /// Builder for an administrative message with the standard header set.
+    fn admin_builder(&self, msg_type: &str, seq: u64) -> FixBuilder {
+        let mut b = FixBuilder::new(self.begin_string, msg_type);
+        self.write_header(&mut b, seq);
+        b
+    }
+
+    /// Builder for an application message with the standard header set.
+    fn app_builder(&self, msg_type: &str, seq: u64) -> FixBuilder {
+        let mut b = FixBuilder::new(self.begin_string, msg_type);
+        self.write_app_header(&mut b, seq);
+        b
+    }
+
+    /// [`Self::write_header`] plus `ApplVerID` (1128) for `FIXT.1.1` and our
+    /// [`Routing`], for application messages.
+    fn write_app_header(&self, b: &mut FixBuilder, seq: u64) {
+        if let Some(id) = self.begin_string.appl_ver_id() {
+            b.field(tag::APPL_VER_ID, id);
+        }
+        self.write_header(b, seq);
+        self.routing.apply(b);
+    }
+
+    /// Add the standard header (`SendingTime` stamped at build) to `b`.
+    fn write_header(&self, b: &mut FixBuilder, seq: u64) {
+        b.clock(Arc::clone(&self.clock))
+            .timestamp_precision(self.timestamp_precision)
+            .auto_sending_time(true)
+            .field(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .field(tag::TARGET_COMP_ID, &self.target_comp_id)
+            .field_u64(tag::MSG_SEQ_NUM, seq);
+    }
+
+    /// [`Self::submit`] without the `ClOrdID` check.
+    fn submit_unchecked(&mut self, msg: FixMessage) -> Result<Option<Vec<u8>>, SubmitError> {
+        if self.state != SessionState::Active {
+            self.pending_outbound.push_back(msg);
+            return Ok(None);
+        }
+        let queue = self.throttle.limit_policy() == ThrottlePolicy::Queue;
+        if queue && !self.throttled.is_empty() && !self.throttle.is_exempt(&msg.msg_type) {
+            self.throttled.push_back(msg);
+            return Ok(None);
+        }
+        match self.throttle.check(&msg.msg_type, self.clock.now_ns()) {
+            ThrottleDecision::Send => Ok(Some(self.stamp_outbound(msg))),
+            ThrottleDecision::Wait { .. } if queue => {
+                self.throttled.push_back(msg);
+                Ok(None)
+            }
+            ThrottleDecision::Wait { until_ns } => Err(SubmitError::Throttled {
+                message: Box::new(msg),
+                until_ns,
+            }),
+        }
+    }
+
+    /// The session clock's current time as a `UTCTimestamp` at the
+    /// session's precision.
+    fn now_timestamp(&self) -> String {
+        format_utc_timestamp_with(self.clock.now_ns(), self.timestamp_precision)
+    }
+
+    /// Logout sent at `now_ns`, optionally with `Text`; starts the logout
+    /// timeout.
+    fn logout(&mut self, text: Option<&str>, now_ns: u64) -> Vec<u8> {
+        let seq = self.send_seq("5");
+        self.set_state(SessionState::LogoutSent);
+        self.logout_sent_ns = Some(now_ns);
+        let mut b = self.admin_builder("5", seq);
+        if let Some(text) = text {
+            b.field(tag::TEXT, text);
+        }
+        b.build()
+    }
+
+    /// Frame an application message with our header, the next `MsgSeqNum`,
+    /// and the current `SendingTime`.
+    fn stamp_outbound(&mut self, mut msg: FixMessage) -> Vec<u8> {
+        let seq = self.send_seq(&msg.msg_type);
+        msg.begin_string = self.begin_string.to_string();
+        if let Some(id) = self.begin_string.appl_ver_id() {
+            if !msg.contains(tag::APPL_VER_ID) {
+                msg.set(tag::APPL_VER_ID, id);
+            }
+        }
+        msg.set(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .set(tag::TARGET_COMP_ID, &self.target_comp_id)
+            .set_value(tag::MSG_SEQ_NUM, seq);
+        self.routing.stamp(&mut msg);
+        msg.remove(tag::SENDING_TIME);
+        if msg.msg_type == "D" {
+            if let Some(id) = msg.get(tag::CL_ORD_ID).map(str::to_string) {
+                self.await_ack(&id);
+            }
+        }
+        let mut b = FixBuilder::from_message(&msg);
+        b.clock(Arc::clone(&self.clock))
+            .timestamp_precision(self.timestamp_precision)
+            .auto_sending_time(true);
+        b.build()
+    }
+
+    /// Act on an in-sequence message (or a Logon/Logout beyond a gap).
+    fn on_admin(&mut self, msg: &FixMessage, actions: &mut Vec<SessionAction>) {
+        match msg.msg_type.as_str() {
+            "0" => {
+                if self
+                    .test_request
+                    .as_ref()
+                    .is_some_and(|(id, _)| msg.get(tag::TEST_REQ_ID) == Some(id.as_str()))
+                {
+                    self.test_request = None;
+                }
+            }
+            "1" => {
+                let seq = self.send_seq("0");
+                let mut b = self.admin_builder("0", seq);
+                if let Some(id) = msg.get(tag::TEST_REQ_ID) {
+                    b.field(tag::TEST_REQ_ID, id);
+                }
+                actions.push(SessionAction::Send(b.build()));
+            }
+            "2" => {
+                self.metrics.resends += 1;
+                let begin = msg.get_u64(tag::BEGIN_SEQ_NO).unwrap_or(1);
+                let end = msg.get_u64(tag::END_SEQ_NO).unwrap_or(0);
+                self.emit(&SessionEvent::ResendRequested { begin, end });
+                actions.push(SessionAction::Resend { begin, end });
+            }
+            "4" => self.apply_new_seq_no(msg, actions),
+            "A" => {
+                let reset = msg.get_bool(tag::RESET_SEQ_NUM_FLAG) == Some(true);
+                if self.state == SessionState::Active && reset {
+                    let frame = self.logon_ack(true);
+                    actions.push(SessionAction::Send(frame));
+                }
+                if !matches!(
+                    self.state,
+                    SessionState::Disconnected | SessionState::LogonSent
+                ) {
+                    return;
+                }
+                if let Some(secs) = msg
+                    .get_u64(tag::HEART_BT_INT)
+                    .and_then(|s| u32::try_from(s).ok())
+                {
+                    self.heart_bt_int = secs;
+                }
+                if self.state == SessionState::Disconnected {
+                    let frame = self.logon_ack(reset);
+                    actions.push(SessionAction::Send(frame));
+                }
+                self.set_state(SessionState::Active);
+                self.heartbeat = Some(HeartbeatTimer::new(
+                    HeartbeatConfig::new(self.heart_bt_int),
+                    0,
+                    self.clock.now_ns(),
+                ));
+                let now_ns = self.clock.now_ns();
+                while let Some(held) = self.pending_outbound.pop_front() {
+                    if self.throttled.is_empty()
+                        && self.throttle.check(&held.msg_type, now_ns).is_send()
+                    {
+                        let frame = self.stamp_outbound(held);
+                        actions.push(SessionAction::Send(frame));
+                    } else {
+                        self.throttled.push_back(held);
+                    }
+                }
+            }
+            "5" => {
+                // Whoever started the logout closes the connection; when the
+                // counterparty started it, the acceptor closes after
+                // answering.
+                let answered = self.state != SessionState::LogoutSent;
+                if answered {
+                    actions.push(SessionAction::Send(self.build_logout()));
+                }
+                self.disconnected();
+                self.emit(&SessionEvent::LoggedOut {
+                    reason: msg.get(tag::TEXT).map(str::to_string),
+                });
+                if !answered || self.role == SessionRole::Acceptor {
+                    actions.push(SessionAction::Disconnect);
+                }
+            }
+            msg_type if is_admin_msg_type(msg_type) => {
+                actions.push(SessionAction::DeliverAdmin(msg.clone()));
+            }
+            msg_type => {
+                if msg_type == "8" {
+                    if let Some(id) = msg.get(tag::CL_ORD_ID) {
+                        self.awaiting_ack.retain(|(awaited, _)| awaited != id);
+                    }
+                }
+                actions.push(SessionAction::Deliver(msg.clone()));
+            }
+        }
+    }
+
+    /// Check an inbound Logon against the session's identity and
+    /// `HeartBtInt`; `Err` carries the Logout text.
+    ///
+    /// A Logon we accept must carry the required credentials; the response
+    /// to our Logon must agree on our `HeartBtInt`.
+    fn validate_logon(&self, msg: &FixMessage) -> Result<(), String> {
+        if msg.begin_string != self.begin_string.as_str() {
+            return Err(format!(
+                "BeginString {} does not match {}",
+                msg.begin_string, self.begin_string
+            ));
+        }
+        let sender = msg.get(tag::SENDER_COMP_ID).unwrap_or_default();
+        if sender != self.target_comp_id {
+            return Err(format!("unknown SenderCompID {sender}"));
+        }
+        let target = msg.get(tag::TARGET_COMP_ID).unwrap_or_default();
+        if target != self.sender_comp_id {
+            return Err(format!("TargetCompID {target} is not served here"));
+        }
+        let heart_bt_int = msg.get_u64(tag::HEART_BT_INT).unwrap_or(0);
+        if heart_bt_int == 0 {
+            return Err("HeartBtInt missing or invalid".to_string());
+        }
+        if self.state == SessionState::LogonSent {
+            if heart_bt_int != u64::from(self.heart_bt_int) {
+                return Err(format!(
+                    "HeartBtInt {heart_bt_int} does not match {}",
+                    self.heart_bt_int
+                ));
+            }
+            return Ok(());
+        }
+        if let Some((username, password)) = &self.required_credentials {
+            if msg.get(tag::USERNAME) != Some(username.as_str())
+                || msg.get(tag::PASSWORD) != Some(password.as_str())
+            {
+                return Err("invalid credentials".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the expected inbound sequence number to `NewSeqNo` (tag 36).
+    ///
+    /// A missing `NewSeqNo`, or one below the expected sequence number, is
+    /// rejected (35=3) and leaves the sequence unchanged.
+    fn apply_new_seq_no(&mut self, msg: &FixMessage, actions: &mut Vec<SessionAction>) {
+        let (reason, text) = match msg.get_u64(tag::NEW_SEQ_NO) {
+            Some(new_seq) if new_seq >= self.incoming_seq => {
+                self.incoming_seq = new_seq;
+                return;
+            }
+            Some(new_seq) => (
+                SessionRejectReason::ValueOutOfRange,
+                format!(
+                    "NewSeqNo {new_seq} is below the expected {}",
+                    self.incoming_seq
+                ),
+            ),
+            None => (
+                SessionRejectReason::RequiredTagMissing,
+                "NewSeqNo missing".to_string(),
+            ),
+        };
+        self.reject(msg, reason, tag::NEW_SEQ_NO, text, actions);
+    }
+
+    /// Send a session-level Reject (35=3) of `msg` for `ref_tag`.
+    fn reject(
+        &mut self,
+        msg: &FixMessage,
+        reason: SessionRejectReason,
+        ref_tag: u32,
+        text: String,
+        actions: &mut Vec<SessionAction>,
+    ) {
+        let reject = Reject {
+            ref_seq_num: msg.get_u64(tag::MSG_SEQ_NUM).unwrap_or(0),
+            ref_tag_id: Some(ref_tag),
+            ref_msg_type: Some(msg.msg_type.clone()),
+            reason,
+            text: Some(text.clone()),
+        };
+        self.emit(&SessionEvent::Rejected { reason, text });
+        let seq = self.send_seq("3");
+        let now = self.now_timestamp();
+        actions.push(SessionAction::Send(reject.build(
+            self.begin_string,
+            &self.sender_comp_id,
+            &self.target_comp_id,
+            seq,
+            &now,
+        )));
+    }
+
+    /// With a maximum latency set, the Reject text for a message (other than
+    /// a retransmission) whose `SendingTime` is too far from our clock.
+    fn sending_time_problem(&self, msg: &FixMessage) -> Option<String> {
+        let max_ns = u64::from(self.max_latency_secs?) * NANOS_PER_SEC;
+        if msg.get_bool(tag::POSS_DUP_FLAG) == Some(true) {
+            return None;
+        }
+        let Some(sent_ns) = msg.get(tag::SENDING_TIME).and_then(parse_utc_timestamp) else {
+            return Some("SendingTime missing or invalid".to_string());
+        };
+        let skew_ns = self.clock.now_ns().abs_diff(sent_ns);
+        (skew_ns > max_ns).then(|| {
+            format!(
+                "SendingTime accuracy problem: {} ms from local time",
+                skew_ns / 1_000_000
+            )
+        })
+    }
+
+    /// Process queued messages that are now in sequence and discard those
+    /// the expected sequence number has moved past.
+    fn drain_queued(&mut self, actions: &mut Vec<SessionAction>) {
+        while let Some(entry) = self.queued.first_entry() {
+            match entry.key().cmp(&self.incoming_seq) {
+                core::cmp::Ordering::Less => {
+                    entry.remove();
+                }
+                core::cmp::Ordering::Equal => {
+                    let msg = entry.remove();
+                    self.incoming_seq += 1;
+                    if let Some(msg) = msg {
+                        self.on_admin(&msg, actions);
+                    }
+                }
+                core::cmp::Ordering::Greater => break,
+            }
+        }
+        if self.resend_until.is_some_and(|end| self.incoming_seq > end) {
+            self.resend_until = None;
+        }
+    }
+
+    /// Send Logout with `text` and disconnect.
+    fn logout_and_disconnect(&mut self, text: &str, actions: &mut Vec<SessionAction>) {
+        let seq = self.send_seq("5");
+        let mut b = self.admin_builder("5", seq);
+        b.field(tag::TEXT, text);
+        actions.push(SessionAction::Send(b.build()));
+        actions.push(SessionAction::Disconnect);
+        self.disconnected();
+        self.emit(&SessionEvent::LoggedOut {
+            reason: Some(text.to_string()),
+        });
+    }
+
+    /// Assign the next outgoing sequence number to a `msg_type` message,
+    /// count it, and push back the heartbeat deadline, since any outgoing
+    /// message counts as traffic.
+    fn send_seq(&mut self, msg_type: &str) -> u64 {
+        let now_ns = self.clock.now_ns();
+        self.timer_seq(now_ns, msg_type)
+    }
+
+    /// Like [`Self::send_seq`] for messages sent from [`Self::on_timer`].
+    fn timer_seq(&mut self, now_ns: u64, msg_type: &str) -> u64 {
+        if let Some(timer) = &mut self.heartbeat {
+            timer.on_sent(now_ns);
+        }
+        *self.metrics.sent.entry(msg_type.to_string()).or_default() += 1;
+        self.metrics.last_sent_ns = Some(now_ns);
+        self.next_outgoing_seq()
+    }
+
+    /// Our Logon answering the counterparty's, echoing `ResetSeqNumFlag`
+    /// when `reset`.
+    fn logon_ack(&mut self, reset: bool) -> Vec<u8> {
+        let seq = self.send_seq("A");
+        let mut b = self.admin_builder("A", seq);
+        b.field(tag::ENCRYPT_METHOD, "0")
+            .field_u64(tag::HEART_BT_INT, u64::from(self.heart_bt_int));
+        if reset {
+            b.field(tag::RESET_SEQ_NUM_FLAG, "Y");
+        }
+        if let Some(id) = self.begin_string.appl_ver_id() {
+            b.field(tag::DEFAULT_APPL_VER_ID, id);
+        }
+        b.build()
+    }
+
+    /// Restart both sequence numbers at 1, dropping gap-recovery state.
+    fn restart_sequences(&mut self) {
+        self.outgoing_seq = 1;
+        self.incoming_seq = 1;
+        self.resend_until = None;
+        self.queued.clear();
+    }
+
+    /// Remember `cl_ord_id`, reporting [`SessionEvent::DuplicateClOrdId`]
+    /// if it was already known.
+    fn note_cl_ord_id(&mut self, cl_ord_id: &str) {
+        if self.cl_ord_ids.contains(cl_ord_id) {
+            self.emit(&SessionEvent::DuplicateClOrdId {
+                cl_ord_id: cl_ord_id.to_string(),
+            });
+        } else {
+            self.cl_ord_ids.insert(cl_ord_id.to_string());
+        }
+    }
+
+    /// Start the acknowledgement timeout of a `NewOrderSingle` sent now.
+    fn await_ack(&mut self, cl_ord_id: &str) {
+        if self.order_ack_timeout_ms.is_some() {
+            let now_ns = self.clock.now_ns();
+            self.awaiting_ack.push_back((cl_ord_id.to_string(), now_ns));
+        }
+    }
+
+    /// Stop tracking orders unacknowledged at `now_ns`, reporting each as
+    /// [`SessionEvent::OrderAckTimeout`].
+    fn expire_acks(&mut self, now_ns: u64) {
+        let Some(timeout_ms) = self.order_ack_timeout_ms else {
+            return;
+        };
+        let timeout_ns = u64::from(timeout_ms) * 1_000_000;
+        while let Some((_, sent_ns)) = self.awaiting_ack.front() {
+            if now_ns.saturating_sub(*sent_ns) < timeout_ns {
+                break;
+            }
+            if let Some((cl_ord_id, sent_ns)) = self.awaiting_ack.pop_front() {
+                self.emit(&SessionEvent::OrderAckTimeout { cl_ord_id, sent_ns });
+            }
+        }
+    }
+
+    /// Change state, reporting [`SessionEvent::StateChanged`] if it differs.
+    fn set_state(&mut self, to: SessionState) {
+        let from = self.state;
+        self.state = to;
+        if from != to {
+            self.emit(&SessionEvent::StateChanged { from, to });
+        }
+    }
+
+    /// Pass `event` to the event handler, if any.
+    fn emit(&mut self, event: &SessionEvent) {
+        if let Some(handler) = &mut self.event_handler {
+            handler(event);
+        }
+    }
+
+    /// Enter [`SessionState::Disconnected`], dropping per-connection state.
+    fn disconnected(&mut self) {
+        self.set_state(SessionState::Disconnected);
+        self.logout_sent_ns = None;
+        self.resend_until = None;
+        self.heartbeat = None;
+        self.test_request = None;
+    }
+}
+
+/// Length and data tags of the FIX data fields. A data field's value is
+/// delimited by the length in front of it, not by SOH.
+const DATA_FIELDS: &[(u32, u32)] = &[
+    (90, 91),
+    (tag::SIGNATURE_LENGTH, tag::SIGNATURE),
+    (tag::RAW_DATA_LENGTH, tag::RAW_DATA),
+    (tag::XML_DATA_LEN, tag::XML_DATA),
+    (348, 349),
+    (350, 351),
+    (352, 353),
+    (354, 355),
+    (356, 357),
+    (358, 359),
+    (360, 361),
+    (362, 363),
+    (364, 365),
+    (445, 446),
+    (618, 619),
+    (621, 622),
+];
+
+/// Mark the stored frame `raw` as a retransmission sent at `now` (a
+/// `UTCTimestamp`) without re-encoding it: only 43, 52 and 122 change and
+/// `BodyLength` / `CheckSum` are recomputed. Returns the frame's `MsgType`
+/// with the rewritten bytes.
+fn rewrite_poss_dup(raw: &[u8], now: &str) -> Result<(String, Vec<u8>), ParseError> {
+    let len = parser::declared_frame_len(raw)?.ok_or(ParseError::MissingBodyLength)?;
+    if len != raw.len() || len < 7 || !raw[len - 7..].starts_with(b"10=") || raw[len - 1] != SOH {
+        return Err(ParseError::MissingChecksum);
+    }
+    // declared_frame_len() has seen both prefix fields.
+    let end8 = raw.iter().position(|&b| b == SOH).unwrap_or_default();
+    let end9 = end8
+        + 1
+        + raw[end8 + 1..]
+            .iter()
+            .position(|&b| b == SOH)
+            .unwrap_or_default();
+    let body = raw
+        .get(end9 + 1..len - 7)
+        .ok_or(ParseError::MissingChecksum)?;
+
+    // (tag, whole field, value)
+    let mut fields: Vec<(u32, &[u8], &[u8])> = Vec::new();
+    let mut data = None;
+    let mut pos = 0;
+    while pos < body.len() {
+        let malformed =
+            || ParseError::MalformedField(String::from_utf8_lossy(&body[pos..]).into_owned());
+        let eq = pos
+            + body[pos..]
+                .iter()
+                .position(|&b| b == b'=')
+                .ok_or_else(malformed)?;
+        let (field_tag, _) = parser::split_field(&body[pos..=eq])?;
+        let end = match data.take() {
+            Some((data_tag, n)) if data_tag == field_tag => eq + 1 + n,
+            _ => body[eq + 1..]
+                .iter()
+                .position(|&b| b == SOH)
+                .map_or(body.len(), |i| eq + 1 + i),
+        };
+        if body.get(end) != Some(&SOH) {
+            return Err(malformed());
+        }
+        let value = &body[eq + 1..end];
+        data = DATA_FIELDS
+            .iter()
+            .find(|(len_tag, _)| *len_tag == field_tag)
+            .and_then(|&(_, data_tag)| Some((data_tag, parser::parse_body_length(value)?)));
+        fields.push((field_tag, &body[pos..end], value));
+        pos = end + 1;
+    }
+
+    let value_of = |t: u32| fields.iter().find(|f| f.0 == t).map(|f| f.2);
+    let msg_type = value_of(tag::MSG_TYPE)
+        .map(|v| String::from_utf8_lossy(v).into_owned())
+        .ok_or_else(|| ParseError::MalformedField("missing MsgType (tag 35)".into()))?;
+    let orig = value_of(tag::SENDING_TIME);
+    let keep_orig = value_of(tag::ORIG_SENDING_TIME).is_some();
+    let stamp_after = if orig.is_some() {
+        tag::SENDING_TIME
+    } else {
+        tag::MSG_SEQ_NUM
+    };
+
+    let mut out = Vec::with_capacity(body.len() + 64);
+    for &(field_tag, field, _) in &fields {
+        if field_tag == tag::POSS_DUP_FLAG {
+            continue;
+        }
+        if field_tag != tag::SENDING_TIME {
+            out.extend_from_slice(field);
+            out.push(SOH);
+        }
+        if field_tag == stamp_after {
+            out.extend_from_slice(format!("43=Y\x0152={now}\x01").as_bytes());
+            if let (Some(orig), false) = (orig, keep_orig) {
+                out.extend_from_slice(b"122=");
+                out.extend_from_slice(orig);
+                out.push(SOH);
+            }
+        }
+    }
+
+    let mut frame = Vec::with_capacity(out.len() + end8 + 24);
+    frame.extend_from_slice(&raw[..=end8]);
+    frame.extend_from_slice(format!("9={}\x01", out.len()).as_bytes());
+    frame.extend_from_slice(&out);
+    let checksum = parser::compute_checksum(&frame);
+    frame.extend_from_slice(format!("10={checksum:03}\x01").as_bytes());
+    Ok((msg_type, frame))
+}
+
+/// Bounded set of recently used ids; the oldest is forgotten first.
+#[derive(Debug, Clone)]
+pub(crate) struct RecentIds {
+    capacity: usize,
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl RecentIds {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            ids: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub(crate) fn insert(&mut self, id: String) {
+        if self.capacity == 0 || !self.ids.insert(id.clone()) {
+            return;
+        }
+        self.order.push_back(id);
+        self.trim();
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    fn clear(&mut self) {
+        self.order.clear();
+        self.ids.clear();
+    }
+
+    fn trim(&mut self) {
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.ids.remove(&old);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::tag;
+    use crate::version::FixVersion;
+    use alice_ledger::{Order, OrderId, OrderType, Side, TimeInForce};
+
+    fn make_session() -> FixSession {
+        FixSession::new("ALICE", "BROKER", BeginString::Fix44)
+    }
+
+    fn make_acceptor() -> FixSession {
+        let mut session = make_session();
+        session.set_role(SessionRole::Acceptor);
+        session
+    }
+
+    fn make_limit_order(id: u64, side: Side, price: i64, qty: u64) -> Order {
+        Order {
+            id: OrderId(id),
+            side,
+            order_type: OrderType::Limit,
+            price,
+            quantity: qty,
+            filled_quantity: 0,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_session_creation() {
+        let session = make_session();
+        assert_eq!(*session.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn test_outgoing_seq_increments() {
+        let mut session = make_session();
+        assert_eq!(session.next_outgoing_seq(), 1);
+        assert_eq!(session.next_outgoing_seq(), 2);
+        assert_eq!(session.next_outgoing_seq(), 3);
+    }
+
+    #[test]
+    fn test_incoming_seq_validation() {
+        let mut session = make_session();
+        // Sequence 1 is expected first.
+        assert_eq!(session.validate_incoming_seq(1), SeqCheck::Expected);
+        // Now sequence 2 is expected.
+        assert_eq!(session.validate_incoming_seq(2), SeqCheck::Expected);
+        // Sequence 1 again is too low.
+        assert_eq!(
+            session.validate_incoming_seq(1),
+            SeqCheck::TooLow {
+                expected: 3,
+                received: 1
+            }
+        );
+        // Sequence 4 is a gap.
+        assert_eq!(
+            session.validate_incoming_seq(4),
+            SeqCheck::TooHigh {
+                expected: 3,
+                received: 4
+            }
+        );
+        // Sequence 3 is the correct next.
+        assert_eq!(session.validate_incoming_seq(3), SeqCheck::Expected);
+    }
+
+    #[test]
+    fn test_build_logon_message() {
+        let mut session = make_session();
+        let bytes = session.build_logon();
+        let msg = parser::parse(&bytes).expect("logon should parse");
+
+        assert_eq!(msg.msg_type, "A");
+        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
+        assert_eq!(msg.get(tag::TARGET_COMP_ID), Some("BROKER"));
+        assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(*session.state(), SessionState::LogonSent);
+    }
+
+    #[test]
+    fn test_build_logout_message() {
+        let mut session = make_session();
+        let bytes = session.build_logout();
+        let msg = parser::parse(&bytes).expect("logout should parse");
+        assert_eq!(msg.msg_type, "5");
+        assert_eq!(*session.state(), SessionState::LogoutSent);
+    }
+
+    #[test]
+    fn test_build_heartbeat_message() {
+        let mut session = make_session();
+        let bytes = session.build_heartbeat();
+        let msg = parser::parse(&bytes).expect("heartbeat should parse");
+        assert_eq!(msg.msg_type, "0");
+        // State should remain Disconnected (heartbeat does not change state).
+        assert_eq!(*session.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn test_build_new_order() {
+        let mut session = make_session();
+        let order = make_limit_order(42, Side::Bid, 50_000, 10);
+        let bytes = session.build_new_order(&order, "BTCUSD").unwrap();
+        let msg = parser::parse(&bytes).expect("new order should parse");
+
+        assert_eq!(msg.msg_type, "D");
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+        assert_eq!(msg.get(tag::SIDE), Some("1")); // Bid = "1"
+        assert_eq!(msg.get(tag::ORD_TYPE), Some("2")); // Limit = "2"
+        assert_eq!(msg.get_i64(tag::PRICE), Some(50_000));
+        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(10));
+        assert_eq!(msg.get(tag::CL_ORD_ID), Some("42"));
+    }
+
+    #[test]
+    fn test_build_cancel_request() {
+        use crate::clock::ManualClock;
+
+        let clock = Arc::new(ManualClock::new(1_767_225_600_123_000_000));
+        let mut session = make_session();
+        session.set_clock(clock);
+        let order = make_limit_order(42, Side::Ask, 50_000, 10);
+        session.build_new_order(&order, "BTCUSD").unwrap();
+        let bytes = session
+            .build_cancel_request(&order, "42", "BTCUSD")
+            .unwrap();
+        let msg = parser::parse(&bytes).unwrap();
+
+        assert_eq!(msg.msg_type, "F");
+        assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(msg.get(tag::ORIG_CL_ORD_ID), Some("42"));
+        assert_eq!(msg.get(tag::CL_ORD_ID), Some("42-2"));
+        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
+        assert_eq!(msg.get(tag::SIDE), Some("2"));
+        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(10));
+        assert_eq!(msg.get(tag::TRANSACT_TIME), Some("20260101-00:00:00.123"));
+        assert!(!msg.contains(tag::PRICE));
+        let typed = crate::order::OrderCancelRequest::try_from(&msg).unwrap();
+        assert_eq!(typed.transact_time, 1_767_225_600_123_000_000);
+
+        session.set_mode(SessionMode::DropCopy);
+        assert_eq!(
+            session.build_cancel_request(&order, "42-2", "BTCUSD"),
+            Err(SubmitError::ReceiveOnly)
+        );
+    }
+
+    #[test]
+    fn test_build_cancel_replace_chains_cl_ord_ids() {
+        let mut session = make_session();
+        let order = make_limit_order(42, Side::Bid, 50_000, 10);
+        session.build_new_order(&order, "BTCUSD").unwrap();
+
+        let first = session
+            .build_cancel_replace(&order, "42", "BTCUSD", Amendment::default().price(49_500))
+            .unwrap();
+        let first = parser::parse(&first).unwrap();
+        assert_eq!(first.msg_type, "G");
+        assert_eq!(first.get(tag::ORIG_CL_ORD_ID), Some("42"));
+        assert_eq!(first.get(tag::CL_ORD_ID), Some("42-2"));
+        assert_eq!(first.get(tag::SIDE), Some("1"));
+        assert_eq!(first.get(tag::ORD_TYPE), Some("2"));
+        assert_eq!(first.get_i64(tag::PRICE), Some(49_500));
+        assert_eq!(first.get_u64(tag::ORDER_QTY), Some(10));
+        assert_eq!(first.get(tag::TIME_IN_FORCE), Some("1"));
+        assert!(first.contains(tag::TRANSACT_TIME));
+
+        let amendment = Amendment::default()
+            .quantity(4)
+            .time_in_force(TimeInForce::IOC);
+        let second = session
+            .build_cancel_replace(&order, "42-2", "BTCUSD", amendment)
+            .unwrap();
+        let second = parser::parse(&second).unwrap();
+        assert_eq!(second.get(tag::ORIG_CL_ORD_ID), Some("42-2"));
+        assert_eq!(second.get(tag::CL_ORD_ID), Some("42-3"));
+        assert_eq!(second.get_i64(tag::PRICE), Some(50_000));
+        assert_eq!(second.get_u64(tag::ORDER_QTY), Some(4));
+        assert_eq!(second.get(tag::TIME_IN_FORCE), Some("3"));
+
+        session.set_mode(SessionMode::DropCopy);
+        assert_eq!(
+            session.build_cancel_replace(&order, "42-3", "BTCUSD", amendment),
+            Err(SubmitError::ReceiveOnly)
+        );
+    }
+
+    #[test]
+    fn test_build_order_status_request() {
+        use crate::execution_report::{ExecutionReport, OrdStatus};
+
+        let mut session = make_session();
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        let order = make_limit_order(42, Side::Bid, 50_000, 10);
+        let request = session
+            .build_order_status_request(&order, "42-5", "BTCUSD")
+            .unwrap();
+        let request = parser::parse(&request).unwrap();
+        assert_eq!(request.msg_type, "H");
+        assert_eq!(request.get(tag::CL_ORD_ID), Some("42-5"));
+        assert_eq!(request.get(tag::SIDE), Some("1"));
+        assert_eq!(request.get(tag::ORD_STATUS_REQ_ID), Some("42-2"));
+        let typed = crate::order::OrderStatusRequest::try_from(&request).unwrap();
+        assert_eq!(typed.symbol, "BTCUSD");
+        // A status query never trips the duplicate ClOrdID check.
+        assert!(session
+            .build_order_status_request(&order, "42-5", "BTCUSD")
+            .is_ok());
+
+        let mut status = inbound("8", 2);
+        status
+            .set(tag::ORDER_ID, "O1")
+            .set(tag::CL_ORD_ID, "42-5")
+            .set(tag::EXEC_ID, "S-1")
+            .set(tag::EXEC_TYPE, "I")
+            .set(tag::ORD_STATUS, "1")
+            .set(tag::SYMBOL, "BTCUSD")
+            .set(tag::SIDE, "1")
+            .set(tag::ORD_STATUS_REQ_ID, "42-2");
+        let actions = session.on_message(&status);
+        let SessionAction::Deliver(delivered) = &actions[0] else {
+            panic!("expected Deliver");
+        };
+        let report = ExecutionReport::try_from(delivered).unwrap();
+        assert!(report.is_status_report());
+        assert_eq!(report.ord_status, OrdStatus::PartiallyFilled);
+        assert_eq!(report.ord_status_req_id.as_deref(), Some("42-2"));
+    }
+
+    #[test]
+    fn test_mass_status_request_and_reports() {
+        use crate::execution_report::ExecutionReport;
+        use crate::mass_status::{MassStatusCollector, MassStatusProgress};
+
+        let mut session = make_session();
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        let request = OrderMassStatusRequest::all_orders("MS1");
+        let frame = session.build_mass_status_request(&request).unwrap();
+        let frame = parser::parse(&frame).unwrap();
+        assert_eq!(frame.msg_type, "AF");
+        assert_eq!(frame.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert!(frame.contains(tag::SENDING_TIME));
+        assert_eq!(OrderMassStatusRequest::try_from(&frame).unwrap(), request);
+
+        let mut collector = MassStatusCollector::new();
+        collector.expect(&request.mass_status_req_id);
+        let mut done = None;
+        for (seq, (cl_ord_id, last)) in (2..).zip([("C1", false), ("C2", true)]) {
+            let mut report = inbound("8", seq);
+            report
+                .set(tag::ORDER_ID, "O1")
+                .set(tag::CL_ORD_ID, cl_ord_id)
+                .set(tag::EXEC_ID, "S1")
+                .set(tag::EXEC_TYPE, "I")
+                .set(tag::ORD_STATUS, "0")
+                .set(tag::SYMBOL, "BTCUSD")
+                .set(tag::SIDE, "1")
+                .set(tag::MASS_STATUS_REQ_ID, "MS1")
+                .set(tag::TOT_NUM_REPORTS, "2")
+                .set(tag::LAST_RPT_REQUESTED, if last { "Y" } else { "N" });
+            for action in session.on_message(&report) {
+                if let SessionAction::Deliver(msg) = action {
+                    let report = ExecutionReport::try_from(&msg).unwrap();
+                    if let MassStatusProgress::Complete { reports, .. } = collector.record(&report)
+                    {
+                        done = Some(reports);
+                    }
+                }
+            }
+        }
+        assert_eq!(done.map(|r| r.len()), Some(2));
+
+        session.set_mode(SessionMode::DropCopy);
+        assert_eq!(
+            session.build_mass_status_request(&request),
+            Err(SubmitError::ReceiveOnly)
+        );
+    }
+
+    #[test]
+    fn test_new_order_list_and_status() {
+        use crate::list::{ListOrder, ListOrderStatus, ListStatus};
+        use crate::value::Decimal;
+
+        let mut session = make_session();
+        let duplicates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&duplicates);
+        session.set_event_handler(move |e| {
+            if let SessionEvent::DuplicateClOrdId { cl_ord_id } = e {
+                sink.lock().unwrap().push(cl_ord_id.clone());
+            }
+        });
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+
+        let entry = |cl_ord_id: &str, side| ListOrder {
+            cl_ord_id: cl_ord_id.to_string(),
+            list_seq_no: 0,
+            symbol: "BTCUSD".to_string(),
+            side,
+            ord_type: OrderType::Market,
+            order_qty: Decimal::parse("1").unwrap(),
+            price: None,
+            time_in_force: None,
+            account: None,
+        };
+        let mut list = NewOrderList::new("L1");
+        list.push(entry("L1-1", Side::Bid))
+            .push(entry("L1-2", Side::Ask));
+        let frame = parser::parse(&session.build_new_order_list(&list).unwrap()).unwrap();
+        assert_eq!(frame.msg_type, "E");
+        assert_eq!(frame.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(NewOrderList::try_from(&frame).unwrap(), list);
+        assert!(duplicates.lock().unwrap().is_empty());
+
+        // Resending the same basket flags every ClOrdID but still frames it.
+        assert!(session.build_new_order_list(&list).is_ok());
+        assert_eq!(*duplicates.lock().unwrap(), ["L1-1", "L1-2"]);
+
+        let mut status = inbound("N", 2);
+        status
+            .set(tag::LIST_ID, "L1")
+            .set(tag::LIST_STATUS_TYPE, "1")
+            .set(tag::NO_RPTS, "1")
+            .set(tag::LIST_ORDER_STATUS, "7")
+            .set(tag::RPT_SEQ, "1")
+            .set(tag::TOT_NO_ORDERS, "2")
+            .set(tag::LIST_STATUS_TEXT, "basket refused");
+        let actions = session.on_message(&status);
+        let SessionAction::Deliver(delivered) = &actions[0] else {
+            panic!("expected delivery");
+        };
+        let status = ListStatus::try_from(delivered).unwrap();
+        assert_eq!(status.list_order_status, ListOrderStatus::Reject);
+        assert!(status.list_order_status.is_final());
+        assert!(status.orders.is_empty());
+
+        session.set_mode(SessionMode::DropCopy);
+        assert_eq!(
+            session.build_new_order_list(&list),
+            Err(SubmitError::ReceiveOnly)
+        );
+    }
+
+    #[test]
+    fn test_acceptor_reports_ledger_fill() {
+        use crate::convert::{fill_execution_report, FillContext};
+        use alice_ledger::Fill;
+
+        let mut exchange = make_acceptor();
+        exchange.on_message(&inbound("A", 1));
+        let mut order = make_limit_order(9, Side::Bid, 100, 5);
+        order.filled_quantity = 5;
+        let fill = Fill {
+            maker_id: OrderId(3),
+            taker_id: OrderId(9),
+            price: 100,
+            quantity: 5,
+            timestamp_ns: 0,
+        };
+        let ctx = FillContext {
+            order: &order,
+            cl_ord_id: "9",
+            symbol: "X",
+            exec_id: "E9",
+            avg_px: 100.0,
+        };
+        let frame = exchange
+            .build_execution_report(&fill_execution_report(&fill, &ctx))
+            .unwrap();
+        let msg = parser::parse(&frame).unwrap();
+        assert_eq!(msg.msg_type, "8");
+        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
+        assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(msg.get(tag::ORD_STATUS), Some("2"));
+        assert_eq!(msg.get_u64(tag::CUM_QTY), Some(5));
+
+        // The initiator side parses it back into the same report.
+        let report = ExecutionReport::try_from(&msg).unwrap();
+        assert_eq!(report.exec_id, "E9");
+        assert_eq!(report.transact_time, Some(0));
+    }
+
+    #[test]
+    fn test_built_messages_validate_against_fix44() {
+        use crate::convert::{fill_execution_report, FillContext};
+        use crate::dictionary::Dictionary;
+        use crate::list::ListOrder;
+        use crate::value::Decimal;
+        use alice_ledger::Fill;
+
+        fn check(dict: &Dictionary, frame: &[u8]) {
+            let text = std::str::from_utf8(frame).unwrap();
+            let mut msg_type = "";
+            let mut fields = Vec::new();
+            for field in text.split('\x01').filter(|f| !f.is_empty()) {
+                let (t, v) = field.split_once('=').unwrap();
+                match t.parse().unwrap() {
+                    tag::BEGIN_STRING | tag::BODY_LENGTH | tag::CHECKSUM => {}
+                    tag::MSG_TYPE => msg_type = v,
+                    t => fields.push((t, v)),
+                }
+            }
+            assert_eq!(dict.validate_fields(msg_type, &fields), Ok(()), "{text}");
+        }
+
+        let dict = Dictionary::fix44();
+        let mut session = make_session();
+        check(&dict, &session.build_logon());
+        session.on_message(&counterparty_logon());
+        check(&dict, &session.build_heartbeat());
+        check(&dict, &session.build_gap_fill(2, 3));
+
+        let mut order = make_limit_order(7, Side::Bid, 100, 1);
+        check(&dict, &session.build_new_order(&order, "BTCUSD").unwrap());
+        order.time_in_force = TimeInForce::GTD {
+            expiry_ns: 1_767_225_600_000_000_000,
+        };
+        let gtd = session.build_new_order(&order, "BTCUSD").unwrap();
+        assert_eq!(
+            parser::parse(&gtd).unwrap().get(tag::EXPIRE_TIME),
+            Some("20260101-00:00:00.000")
+        );
+        check(&dict, &gtd);
+        check(
+            &dict,
+            &session.build_cancel_request(&order, "7", "BTCUSD").unwrap(),
+        );
+        let amendment = Amendment::default().price(99);
+        check(
+            &dict,
+            &session
+                .build_cancel_replace(&order, "7", "BTCUSD", amendment)
+                .unwrap(),
+        );
+        check(
+            &dict,
+            &session
+                .build_order_status_request(&order, "7", "BTCUSD")
+                .unwrap(),
+        );
+        check(
+            &dict,
+            &session
+                .build_mass_status_request(&OrderMassStatusRequest::all_orders("MS1"))
+                .unwrap(),
+        );
+        let mut list = NewOrderList::new("L1");
+        list.push(ListOrder {
+            cl_ord_id: "L1-1".to_string(),
+            list_seq_no: 1,
+            symbol: "BTCUSD".to_string(),
+            side: Side::Bid,
+            ord_type: OrderType::Limit,
+            order_qty: Decimal::parse("1").unwrap(),
+            price: Decimal::parse("100"),
+            time_in_force: Some(TimeInForce::GTC),
+            account: Some("ACC".to_string()),
+        });
+        check(&dict, &session.build_new_order_list(&list).unwrap());
+        check(&dict, &session.build_logout_with_text("done"));
+
+        let mut exchange = make_acceptor();
+        exchange.on_message(&inbound("A", 1));
+        let fill = Fill {
+            maker_id: OrderId(3),
+            taker_id: OrderId(7),
+            price: 100,
+            quantity: 1,
+            timestamp_ns: 0,
+        };
+        let ctx = FillContext {
+            order: &order,
+            cl_ord_id: "7",
+            symbol: "BTCUSD",
+            exec_id: "E1",
+            avg_px: 100.0,
+        };
+        let report = fill_execution_report(&fill, &ctx);
+        check(&dict, &exchange.build_execution_report(&report).unwrap());
+    }
+
+    #[test]
+    fn test_fixt_session_stamps_appl_ver_id() {
+        let mut session = FixSession::new(
+            "ALICE",
+            "BROKER",
+            BeginString::Fixt11 {
+                appl_ver: FixVersion::Fix44,
+            },
+        );
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.begin_string, "FIXT.1.1");
+        assert_eq!(logon.get(tag::DEFAULT_APPL_VER_ID), Some("6"));
+        assert!(!logon.contains(tag::APPL_VER_ID));
+
+        let mut reply = counterparty_logon();
+        reply.begin_string = "FIXT.1.1".into();
+        reply.set(tag::DEFAULT_APPL_VER_ID, "6");
+        session.on_message(&reply);
+        assert_eq!(*session.state(), SessionState::Active);
+
+        let heartbeat = parser::parse(&session.build_heartbeat()).unwrap();
+        assert!(!heartbeat.contains(tag::APPL_VER_ID));
+        let order = make_limit_order(7, Side::Bid, 100, 1);
+        let frame = session.build_new_order(&order, "BTCUSD").unwrap();
+        assert!(std::str::from_utf8(&frame)
+            .unwrap()
+            .contains("\x0135=D\x011128=6\x0149=ALICE\x01"));
+        let submitted = session
+            .submit(FixMessage::new("FIX.4.4", "D"))
+            .unwrap()
+            .unwrap();
+        let submitted = parser::parse(&submitted).unwrap();
+        assert_eq!(submitted.begin_string, "FIXT.1.1");
+        assert_eq!(submitted.get(tag::APPL_VER_ID), Some("6"));
+    }
+
+    #[test]
+    fn test_seq_advances_across_messages() {
+        let mut session = make_session();
+        let b1 = session.build_logon();
+        let b2 = session.build_heartbeat();
+        let b3 = session.build_heartbeat();
+
+        let m1 = parser::parse(&b1).unwrap();
+        let m2 = parser::parse(&b2).unwrap();
+        let m3 = parser::parse(&b3).unwrap();
+
+        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(m3.get_u64(tag::MSG_SEQ_NUM), Some(3));
+    }
+
+    // -----------------------------------------------------------------------
+    // Additional session tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_logon_changes_state_to_logon_sent() {
+        let mut session = make_session();
+        assert_eq!(*session.state(), SessionState::Disconnected);
+        let _ = session.build_logon();
+        assert_eq!(*session.state(), SessionState::LogonSent);
+    }
+
+    #[test]
+    fn test_logout_changes_state_to_logout_sent() {
+        let mut session = make_session();
+        let _ = session.build_logout();
+        assert_eq!(*session.state(), SessionState::LogoutSent);
+    }
+
+    #[test]
+    fn test_heartbeat_does_not_change_state() {
+        let mut session = make_session();
+        let _ = session.build_logon();
+        assert_eq!(*session.state(), SessionState::LogonSent);
+        let _ = session.build_heartbeat();
+        // State should remain LogonSent.
+        assert_eq!(*session.state(), SessionState::LogonSent);
+    }
+
+    #[test]
+    fn test_multiple_logons_advance_seq() {
+        let mut session = make_session();
+        let b1 = session.build_logon();
+        let b2 = session.build_logon();
+        let m1 = parser::parse(&b1).unwrap();
+        let m2 = parser::parse(&b2).unwrap();
+        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
+    }
+
+    #[test]
+    fn test_incoming_seq_starts_at_one() {
+        let mut session = make_session();
+        assert!(matches!(
+            session.validate_incoming_seq(0),
+            SeqCheck::TooLow { .. }
+        ));
+        assert_eq!(session.validate_incoming_seq(1), SeqCheck::Expected);
+    }
+
+    #[test]
+    fn test_incoming_seq_gap_rejection() {
+        let mut session = make_session();
+        assert_eq!(session.validate_incoming_seq(1), SeqCheck::Expected);
+        // Skip 2, send 3 -> should fail.
+        assert!(matches!(
+            session.validate_incoming_seq(3),
+            SeqCheck::TooHigh { .. }
+        ));
+        // Sequence 2 is still expected.
+        assert_eq!(session.validate_incoming_seq(2), SeqCheck::Expected);
+    }
+
+    #[test]
+    fn test_build_new_order_ask_side() {
+        let mut session = make_session();
+        let order = Order {
+            id: OrderId(100),
+            side: Side::Ask,
+            order_type: OrderType::Limit,
+            price: 60_000,
+            quantity: 25,
+            filled_quantity: 0,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::IOC,
+        };
+        let bytes = session.build_new_order(&order, "ETHUSD").unwrap();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.msg_type, "D");
+        assert_eq!(msg.get(tag::SIDE), Some("2")); // Ask = "2"
+        assert_eq!(msg.get(tag::SYMBOL), Some("ETHUSD"));
+        assert_eq!(msg.get(tag::TIME_IN_FORCE), Some("3")); // IOC = "3"
+        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(25));
+    }
+
+    #[test]
+    fn test_build_new_order_market_type() {
+        let mut session = make_session();
+        let order = Order {
+            id: OrderId(200),
+            side: Side::Bid,
+            order_type: OrderType::Market,
+            price: 0,
+            quantity: 50,
+            filled_quantity: 0,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::FOK,
+        };
+        let bytes = session.build_new_order(&order, "BTCUSD").unwrap();
+        let msg = parser::parse(&bytes).unwrap();
+        assert_eq!(msg.get(tag::ORD_TYPE), Some("1")); // Market = "1"
+        assert_eq!(msg.get(tag::TIME_IN_FORCE), Some("4")); // FOK = "4"
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut session = make_session();
+        let _ = session.build_logon();
+        assert_eq!(session.validate_incoming_seq(1), SeqCheck::Expected);
+
+        let snap = session.snapshot();
+        assert_eq!(snap.outgoing_seq, 2);
+        assert_eq!(snap.incoming_seq, 2);
+        assert_eq!(snap.state, SessionState::LogonSent);
+
+        let mut restored = FixSession::from_snapshot(snap.clone());
+        assert_eq!(restored.snapshot(), snap);
+        assert_eq!(restored.next_outgoing_seq(), 2);
+        assert_eq!(restored.validate_incoming_seq(2), SeqCheck::Expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde_json() {
+        let snap = make_session().snapshot();
+        let json = serde_json::to_string(&snap).unwrap();
+        assert!(json.contains("\"state\":\"Disconnected\""));
+        let back: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, snap);
+    }
+
+    #[test]
+    fn test_session_state_debug() {
+        let state = SessionState::Active;
+        assert_eq!(format!("{state:?}"), "Active");
+    }
+
+    #[test]
+    fn test_session_state_clone() {
+        let s1 = SessionState::LogonSent;
+        let s2 = s1;
+        assert_eq!(s1, s2);
+    }
+
+    #[test]
+    fn test_full_session_lifecycle_seq_numbers() {
+        let mut session = make_session();
+        // Logon = seq 1
+        let b1 = session.build_logon();
+        assert_eq!(*session.state(), SessionState::LogonSent);
+        // Heartbeat = seq 2
+        let b2 = session.build_heartbeat();
+        // New order = seq 3
+        let order = make_limit_order(1, Side::Bid, 100, 10);
+        let b3 = session.build_new_order(&order, "SYM").unwrap();
+        // Logout = seq 4
+        let b4 = session.build_logout();
+        assert_eq!(*session.state(), SessionState::LogoutSent);
+
+        let m1 = parser::parse(&b1).unwrap();
+        let m2 = parser::parse(&b2).unwrap();
+        let m3 = parser::parse(&b3).unwrap();
+        let m4 = parser::parse(&b4).unwrap();
+
+        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        assert_eq!(m3.get_u64(tag::MSG_SEQ_NUM), Some(3));
+        assert_eq!(m4.get_u64(tag::MSG_SEQ_NUM), Some(4));
+    }
+
+    #[test]
+    fn test_screen_inbound_uses_session_policy() {
+        use crate::firewall::{PolicyViolation, Verdict};
+
+        let mut session = make_session();
+        let order = make_limit_order(1, Side::Bid, 100, 10);
+        let raw = session.build_new_order(&order, "SYM").unwrap();
+        assert_eq!(session.screen_inbound(&raw), Verdict::Accept);
+
+        session.set_inbound_policy(InboundPolicy::new().allow_msg_types(&["0", "A", "5"]));
+        assert_eq!(
+            session.screen_inbound(&raw),
+            Verdict::Reject(PolicyViolation::MsgTypeNotAllowed("D".into()))
+        );
+        let heartbeat = session.build_heartbeat();
+        assert!(session.screen_inbound(&heartbeat).is_accept());
+    }
+
+    #[test]
+    fn test_drain_monitor_per_session() {
+        use crate::drain::{DrainThresholds, SlowConsumer};
+
+        let mut session = make_session();
+        session
+            .drain_mut()
+            .set_thresholds(DrainThresholds::new().max_depth(1));
+        let hb = session.build_heartbeat();
+        assert_eq!(session.drain_mut().on_enqueue(0, hb.len()), None);
+        assert_eq!(
+            session.drain_mut().on_enqueue(1, hb.len()),
+            Some(SlowConsumer::QueueDepth { depth: 2, max: 1 })
+        );
+        assert!(session.drain().is_slow());
+    }
+
+    #[test]
+    fn test_throttle_exempts_cancels() {
+        use crate::throttle::RateLimit;
+
+        let mut session = make_session();
+        session.set_throttle(Throttle::new().global_limit(RateLimit::new(1, 1), 0));
+        assert!(session.throttle_mut().check("D", 0).is_send());
+        assert!(!session.throttle_mut().check("D", 0).is_send());
+        assert!(session.throttle_mut().check("F", 0).is_send());
+        assert!(session.throttle().is_exempt("q"));
+    }
+
+    #[test]
+    fn test_session_id_display_and_file_stem() {
+        let id = make_session().session_id();
+        assert_eq!(id.to_string(), "FIX.4.4:ALICE->BROKER");
+        assert_eq!(id.file_stem(), "FIX.4.4-ALICE-BROKER");
+        // '-' inside a CompID is escaped so keys stay unambiguous.
+        assert_ne!(
+            SessionId::new("FIX.4.4", "A-B", "C").file_stem(),
+            SessionId::new("FIX.4.4", "A", "B-C").file_stem()
+        );
+        assert_eq!(
+            SessionId::new("FIX.4.4", "A/B", "C").file_stem(),
+            "FIX.4.4-A%2FB-C"
+        );
+
+        let mut session = make_session();
+        session.set_qualifier(Some("backup"));
+        let id = session.session_id();
+        assert_eq!(id.to_string(), "FIX.4.4:ALICE->BROKER:backup");
+        assert_eq!(id.file_stem(), "FIX.4.4-ALICE-BROKER-backup");
+        assert_ne!(id, make_session().session_id());
+    }
+
+    #[test]
+    fn test_rotate_identity_requires_logout() {
+        let mut session = make_session();
+        session.build_logon();
+        assert_eq!(
+            session.rotate_identity("ALICE2", "BROKER"),
+            Err(RotationError::NotLoggedOut(SessionState::LogonSent))
+        );
+        session.build_logout();
+        assert_eq!(
+            session.rotate_identity("ALICE", "BROKER"),
+            Err(RotationError::SameIdentity)
+        );
+
+        let old = session.rotate_identity("ALICE2", "BROKER").unwrap();
+        assert_eq!(old, SessionId::new("FIX.4.4", "ALICE", "BROKER"));
+        assert_eq!(session.session_id().sender_comp_id, "ALICE2");
+        assert_eq!(*session.state(), SessionState::Disconnected);
+        let snap = session.snapshot();
+        assert_eq!((snap.outgoing_seq, snap.incoming_seq), (1, 1));
+
+        let logon = session.build_logon();
+        let msg = parser::parse(&logon).unwrap();
+        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE2"));
+        assert_eq!(msg.get(tag::MSG_SEQ_NUM), Some("1"));
+    }
+
+    #[test]
+    fn test_messages_carry_sending_time_from_clock() {
+        use crate::clock::FixedClock;
+
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(1_767_225_600_000_000_000)));
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get(tag::SENDING_TIME), Some("20260101-00:00:00.000"));
+        let order = make_limit_order(1, Side::Bid, 100, 1);
+        let order = parser::parse(&session.build_new_order(&order, "BTCUSD").unwrap()).unwrap();
+        assert!(order.contains(tag::SENDING_TIME));
+    }
+
+    fn inbound(msg_type: &str, seq: u64) -> FixMessage {
+        let mut msg = FixMessage::new("FIX.4.4", msg_type);
+        msg.set(tag::SENDER_COMP_ID, "BROKER")
+            .set(tag::TARGET_COMP_ID, "ALICE")
+            .set_value(tag::MSG_SEQ_NUM, seq);
+        msg
+    }
+
+    /// Logon from the counterparty that passes acceptor validation.
+    fn counterparty_logon() -> FixMessage {
+        let mut msg = inbound("A", 1);
+        msg.set(tag::HEART_BT_INT, "30");
+        msg
+    }
+
+    fn sent(action: &SessionAction) -> FixMessage {
+        match action {
+            SessionAction::Send(frame) => parser::parse(frame).unwrap(),
+            other => panic!("expected Send, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_on_message_logon_heartbeat_and_delivery() {
+        use crate::clock::FixedClock;
+
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(42)));
+        session.build_logon();
+        assert!(session.on_message(&counterparty_logon()).is_empty());
+        assert_eq!(*session.state(), SessionState::Active);
+        assert_eq!(session.last_received_ns(), Some(42));
+
+        let mut test_req = inbound("1", 2);
+        test_req.set(tag::TEST_REQ_ID, "PING");
+        let actions = session.on_message(&test_req);
+        let hb = sent(&actions[0]);
+        assert_eq!(hb.msg_type, "0");
+        assert_eq!(hb.get(tag::TEST_REQ_ID), Some("PING"));
+        assert_eq!(hb.get_u64(tag::MSG_SEQ_NUM), Some(2));
+
+        let order = inbound("8", 3);
+        assert_eq!(
+            session.on_message(&order),
+            vec![SessionAction::Deliver(order.clone())]
+        );
+        assert!(session.on_message(&inbound("0", 4)).is_empty());
+        assert_eq!(session.snapshot().incoming_seq, 5);
+    }
+
+    #[test]
+    fn test_on_disconnect_resumes_sequence_on_next_logon() {
+        let mut session = make_session();
+        session.build_logon();
+        assert!(session.on_message(&counterparty_logon()).is_empty());
+        session.build_heartbeat();
+        assert!(session.on_message(&inbound("0", 2)).is_empty());
+
+        session.on_disconnect();
+        assert_eq!(*session.state(), SessionState::Disconnected);
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get_u64(tag::MSG_SEQ_NUM), Some(3));
+        assert!(!logon.contains(tag::RESET_SEQ_NUM_FLAG));
+
+        // The counterparty sent 3 and 4 while the transport was down.
+        let mut relogon = counterparty_logon();
+        relogon.set_value(tag::MSG_SEQ_NUM, 5_u64);
+        let actions = session.on_message(&relogon);
+        assert_eq!(*session.state(), SessionState::Active);
+        let resend = sent(&actions[0]);
+        assert_eq!(resend.msg_type, "2");
+        assert_eq!(resend.get_u64(tag::BEGIN_SEQ_NO), Some(3));
+        assert_eq!(resend.get_u64(tag::END_SEQ_NO), Some(4));
+    }
+
+    #[test]
+    fn test_on_message_accepts_counterparty_logon_and_logout() {
+        let mut session = make_acceptor();
+        let mut logon = inbound("A", 1);
+        logon.set(tag::HEART_BT_INT, "15");
+        let reply = sent(&session.on_message(&logon)[0]);
+        assert_eq!(reply.msg_type, "A");
+        assert_eq!(reply.get(tag::HEART_BT_INT), Some("15"));
+        assert_eq!(*session.state(), SessionState::Active);
+
+        let actions = session.on_message(&inbound("5", 2));
+        assert_eq!(sent(&actions[0]).msg_type, "5");
+        assert_eq!(actions[1], SessionAction::Disconnect);
+        assert_eq!(*session.state(), SessionState::Disconnected);
+
+        // Logout we initiated: only disconnect.
+        let mut session = make_acceptor();
+        session.on_message(&counterparty_logon());
+        session.build_logout();
+        assert_eq!(
+            session.on_message(&inbound("5", 2)),
+            vec![SessionAction::Disconnect]
+        );
+    }
+
+    #[test]
+    fn test_pre_logon_outbound_queue_flushed_on_logon() {
+        use crate::clock::FixedClock;
+
+        let mut session = make_session();
+        let order = |id: &str| {
+            let mut msg = FixMessage::new("FIX.4.4", "D");
+            msg.set(tag::CL_ORD_ID, id)
+                .set(tag::SENDING_TIME, "20200101-00:00:00.000");
+            msg
+        };
+        assert_eq!(session.submit(order("1")), Ok(None));
+        session.build_logon();
+        assert_eq!(session.submit(order("2")), Ok(None));
+        assert_eq!(session.pending_outbound().len(), 2);
+
+        session.set_clock(Arc::new(FixedClock(1_767_225_600_000_000_000)));
+        let actions = session.on_message(&counterparty_logon());
+        let flushed: Vec<_> = actions.iter().map(sent).collect();
+        assert_eq!(flushed.len(), 2);
+        for (msg, (id, seq)) in flushed.iter().zip([("1", 2), ("2", 3)]) {
+            assert_eq!(msg.get(tag::CL_ORD_ID), Some(id));
+            assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(seq));
+            assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
+            assert_eq!(msg.get(tag::SENDING_TIME), Some("20260101-00:00:00.000"));
+        }
+        assert_eq!(session.pending_outbound().len(), 0);
+
+        let live = parser::parse(&session.submit(order("3")).unwrap().unwrap()).unwrap();
+        assert_eq!(live.get_u64(tag::MSG_SEQ_NUM), Some(4));
+
+        session.build_logout();
+        assert_eq!(session.submit(order("4")), Ok(None));
+        assert_eq!(session.take_pending_outbound().len(), 1);
+        assert_eq!(session.pending_outbound().len(), 0);
+    }
+
+    #[test]
+    fn test_logout_text_and_timeout() {
+        use crate::clock::FixedClock;
+
+        const SEC: u64 = 1_000_000_000;
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(10 * SEC)));
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        assert_eq!(session.logout_timeout(), DEFAULT_LOGOUT_TIMEOUT_SECS);
+        session.set_logout_timeout(5);
+
+        let logout = parser::parse(&session.build_logout_with_text("maintenance")).unwrap();
+        assert_eq!(logout.msg_type, "5");
+        assert_eq!(logout.get(tag::TEXT), Some("maintenance"));
+        assert_eq!(*session.state(), SessionState::LogoutSent);
+
+        assert!(!session
+            .on_timer(14 * SEC)
+            .contains(&SessionAction::ForceDisconnect));
+        assert_eq!(
+            session.on_timer(15 * SEC),
+            vec![SessionAction::ForceDisconnect]
+        );
+        assert_eq!(*session.state(), SessionState::Disconnected);
+        assert!(session.on_timer(30 * SEC).is_empty());
+
+        // An answered Logout stops the timer.
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(0)));
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        assert_eq!(
+            parser::parse(&session.build_logout())
+                .unwrap()
+                .get(tag::TEXT),
+            None
+        );
+        session.on_message(&inbound("5", 2));
+        assert!(session.on_timer(60 * SEC).is_empty());
+    }
+
+    #[test]
+    fn test_sending_time_accuracy_rejected() {
+        use crate::clock::FixedClock;
+
+        const SEC: u64 = 1_000_000_000;
+        const NOW: u64 = 1_767_225_600 * SEC;
+        let stamp = |ns: u64| format_utc_timestamp_with(ns, TimestampPrecision::default());
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(NOW)));
+        assert_eq!(session.max_latency(), None);
+        session.set_max_latency(Some(120));
+        session.build_logon();
+
+        let mut logon = counterparty_logon();
+        logon.set(tag::SENDING_TIME, &stamp(NOW - 5 * SEC));
+        assert!(session.on_message(&logon).is_empty());
+        assert_eq!(*session.state(), SessionState::Active);
+
+        let mut stale = inbound("D", 2);
+        stale.set(tag::SENDING_TIME, &stamp(NOW - 200 * SEC));
+        let actions = session.on_message(&stale);
+        assert_eq!(actions.len(), 1);
+        let reject = sent(&actions[0]);
+        assert_eq!(reject.msg_type, "3");
+        assert_eq!(reject.get_u64(tag::REF_SEQ_NUM), Some(2));
+        assert_eq!(reject.get_u64(tag::REF_TAG_ID), Some(52));
+        assert_eq!(reject.get_u64(tag::SESSION_REJECT_REASON), Some(10));
+        assert_eq!(session.snapshot().incoming_seq, 3);
+
+        // Retransmissions keep their original SendingTime.
+        let mut resent = inbound("D", 3);
+        resent
+            .set(tag::SENDING_TIME, &stamp(NOW - 200 * SEC))
+            .set(tag::POSS_DUP_FLAG, "Y");
+        assert_eq!(
+            session.on_message(&resent),
+            vec![SessionAction::Deliver(resent.clone())]
+        );
+
+        let actions = session.on_message(&inbound("D", 4));
+        assert_eq!(
+            sent(&actions[0]).get(tag::TEXT),
+            Some("SendingTime missing or invalid")
+        );
+    }
+
+    #[test]
+    fn test_hard_sequence_reset() {
+        use crate::store::MemoryStore;
+
+        let mut session = make_session();
+        let mut store = MemoryStore::new();
+        let logon = session.build_logon();
+        store.put(1, 0, &logon).unwrap();
+        session.on_message(&counterparty_logon());
+        store.put(2, 0, &session.build_heartbeat()).unwrap();
+        session.on_message(&inbound("0", 5));
+        assert_eq!(session.queued(), 1);
+
+        let reset = session
+            .reset_sequences(Some(&mut store), true)
+            .unwrap()
+            .unwrap();
+        let reset = parser::parse(&reset).unwrap();
+        assert_eq!(reset.msg_type, "A");
+        assert_eq!(reset.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(reset.get_bool(tag::RESET_SEQ_NUM_FLAG), Some(true));
+        assert_eq!(
+            session.seq_nums(),
+            SeqNums {
+                outgoing: 2,
+                incoming: 1
+            }
+        );
+        assert_eq!(session.queued(), 0);
+        assert!(store.entries().is_empty());
+        assert_eq!(*session.state(), SessionState::LogonSent);
+
+        assert_eq!(session.reset_sequences(None, false).unwrap(), None);
+        assert_eq!(
+            session.seq_nums(),
+            SeqNums {
+                outgoing: 1,
+                incoming: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_in_session_reset_resynchronises_counterparty() {
+        fn deliver(to: &mut FixSession, frame: &[u8]) -> Vec<Vec<u8>> {
+            to.on_message(&parser::parse(frame).unwrap())
+                .into_iter()
+                .filter_map(|action| match action {
+                    SessionAction::Send(frame) => Some(frame),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        let mut alice = make_session();
+        let mut broker = FixSession::new("BROKER", "ALICE", BeginString::Fix44);
+        broker.set_role(SessionRole::Acceptor);
+        let logon = alice.build_logon();
+        let ack = deliver(&mut broker, &logon);
+        assert!(deliver(&mut alice, &ack[0]).is_empty());
+        for _ in 0..3 {
+            let hb = alice.build_heartbeat();
+            deliver(&mut broker, &hb);
+        }
+        assert_eq!(broker.seq_nums().incoming, 5);
+
+        let reset = alice.reset_sequences(None, true).unwrap().unwrap();
+        let confirm = deliver(&mut broker, &reset);
+        assert_eq!(confirm.len(), 1);
+        let confirm_msg = parser::parse(&confirm[0]).unwrap();
+        assert_eq!(confirm_msg.msg_type, "A");
+        assert_eq!(confirm_msg.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(confirm_msg.get_bool(tag::RESET_SEQ_NUM_FLAG), Some(true));
+        assert!(deliver(&mut alice, &confirm[0]).is_empty());
+
+        let both = SeqNums {
+            outgoing: 2,
+            incoming: 2,
+        };
+        assert_eq!(alice.seq_nums(), both);
+        assert_eq!(broker.seq_nums(), both);
+        assert_eq!(*alice.state(), SessionState::Active);
+        assert_eq!(*broker.state(), SessionState::Active);
+
+        // Traffic continues in the new sequence space without gaps.
+        let hb = alice.build_heartbeat();
+        assert!(deliver(&mut broker, &hb).is_empty());
+        let hb = broker.build_heartbeat();
+        assert!(deliver(&mut alice, &hb).is_empty());
+        assert_eq!(broker.queued() + alice.queued(), 0);
+    }
+
+    #[test]
+    fn test_session_role_transitions() {
+        let mut initiator = make_session();
+        assert_eq!(initiator.role(), SessionRole::Initiator);
+        assert!(initiator.should_logon(0));
+
+        // An initiator only accepts the reply to its own Logon.
+        let actions = initiator.on_message(&counterparty_logon());
+        assert_eq!(
+            sent(&actions[0]).get(tag::TEXT),
+            Some("Logon received before ours")
+        );
+        assert_eq!(actions[1], SessionAction::Disconnect);
+
+        // Answering the acceptor's Logout leaves the close to the acceptor.
+        let mut initiator = make_session();
+        initiator.build_logon();
+        assert!(initiator.on_message(&counterparty_logon()).is_empty());
+        let actions = initiator.on_message(&inbound("5", 2));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(sent(&actions[0]).msg_type, "5");
+        assert_eq!(*initiator.state(), SessionState::Disconnected);
+
+        let acceptor = make_acceptor();
+        assert!(!acceptor.should_logon(0));
+    }
+
+    #[test]
+    fn test_on_message_validates_accepted_logon() {
+        let rejected = |session: &mut FixSession, logon: &FixMessage| {
+            let actions = session.on_message(logon);
+            assert_eq!(actions.len(), 2);
+            assert_eq!(actions[1], SessionAction::Disconnect);
+            assert_eq!(*session.state(), SessionState::Disconnected);
+            sent(&actions[0]).get(tag::TEXT).unwrap().to_string()
+        };
+
+        let mut session = make_acceptor();
+        let mut logon = counterparty_logon();
+        logon.set(tag::SENDER_COMP_ID, "OTHER");
+        assert_eq!(rejected(&mut session, &logon), "unknown SenderCompID OTHER");
+
+        let mut logon = counterparty_logon();
+        logon.set(tag::TARGET_COMP_ID, "EXCH");
+        assert_eq!(
+            rejected(&mut session, &logon),
+            "TargetCompID EXCH is not served here"
+        );
+
+        let mut logon = counterparty_logon();
+        logon.begin_string = "FIX.4.2".into();
+        assert_eq!(
+            rejected(&mut session, &logon),
+            "BeginString FIX.4.2 does not match FIX.4.4"
+        );
+
+        let mut logon = counterparty_logon();
+        logon.set(tag::HEART_BT_INT, "0");
+        assert_eq!(
+            rejected(&mut session, &logon),
+            "HeartBtInt missing or invalid"
+        );
+
+        session.require_credentials("trader", "secret");
+        let mut logon = counterparty_logon();
+        logon
+            .set(tag::USERNAME, "trader")
+            .set(tag::PASSWORD, "wrong");
+        assert_eq!(rejected(&mut session, &logon), "invalid credentials");
+
+        logon.set(tag::PASSWORD, "secret");
+        let actions = session.on_message(&logon);
+        assert_eq!(sent(&actions[0]).msg_type, "A");
+        assert_eq!(*session.state(), SessionState::Active);
+    }
+
+    #[test]
+    fn test_on_message_validates_logon_response() {
+        let response_text = |reply: &FixMessage| {
+            let mut session = make_session();
+            session.build_logon();
+            let actions = session.on_message(reply);
+            assert_eq!(actions.len(), 2);
+            assert_eq!(actions[1], SessionAction::Disconnect);
+            assert_eq!(*session.state(), SessionState::Disconnected);
+            sent(&actions[0]).get(tag::TEXT).unwrap().to_string()
+        };
+
+        let mut reply = counterparty_logon();
+        reply.set(tag::HEART_BT_INT, "60");
+        assert_eq!(response_text(&reply), "HeartBtInt 60 does not match 30");
+
+        let mut reply = counterparty_logon();
+        reply.set(tag::SENDER_COMP_ID, "OTHER");
+        assert_eq!(response_text(&reply), "unknown SenderCompID OTHER");
+
+        let mut reply = counterparty_logon();
+        reply.begin_string = "FIX.4.2".into();
+        assert_eq!(
+            response_text(&reply),
+            "BeginString FIX.4.2 does not match FIX.4.4"
+        );
+
+        let mut reply = counterparty_logon();
+        reply.remove(tag::HEART_BT_INT);
+        assert_eq!(response_text(&reply), "HeartBtInt missing or invalid");
+    }
+
+    #[test]
+    fn test_on_message_gap_requests_resend_and_queues() {
+        let mut session = make_acceptor();
+        session.on_message(&counterparty_logon());
+
+        let actions = session.on_message(&inbound("8", 5));
+        assert_eq!(actions.len(), 1);
+        let req = sent(&actions[0]);
+        assert_eq!(req.msg_type, "2");
+        assert_eq!(req.get_u64(tag::BEGIN_SEQ_NO), Some(2));
+        assert_eq!(req.get_u64(tag::END_SEQ_NO), Some(4));
+        assert!(session.on_message(&inbound("8", 6)).is_empty());
+        let req = sent(&session.on_message(&inbound("8", 8))[0]);
+        assert_eq!(req.get_u64(tag::BEGIN_SEQ_NO), Some(7));
+        assert_eq!(req.get_u64(tag::END_SEQ_NO), Some(7));
+        assert_eq!(session.queued(), 3);
+
+        let mut replay = inbound("8", 2);
+        replay.set(tag::POSS_DUP_FLAG, "Y");
+        assert_eq!(
+            session.on_message(&replay),
+            vec![SessionAction::Deliver(replay.clone())]
+        );
+
+        // Filling 3..=4 releases the queued 5 and 6 in order.
+        let mut fill = inbound("4", 3);
+        fill.set(tag::GAP_FILL_FLAG, "Y").set(tag::NEW_SEQ_NO, "5");
+        assert_eq!(
+            session.on_message(&fill),
+            vec![
+                SessionAction::Deliver(inbound("8", 5)),
+                SessionAction::Deliver(inbound("8", 6)),
+            ]
+        );
+
+        let mut resend = inbound("2", 7);
+        resend.set(tag::BEGIN_SEQ_NO, "3").set(tag::END_SEQ_NO, "0");
+        assert_eq!(
+            session.on_message(&resend),
+            vec![
+                SessionAction::Resend { begin: 3, end: 0 },
+                SessionAction::Deliver(inbound("8", 8)),
+            ]
+        );
+        assert_eq!(session.queued(), 0);
+        assert_eq!(session.snapshot().incoming_seq, 9);
+    }
+
+    #[test]
+    fn test_mutual_gap_resend_requests_answered_at_once() {
+        use crate::store::MemoryStore;
+
+        /// Feed `frames` to `session` and return the frames it sends back.
+        fn deliver(
+            session: &mut FixSession,
+            store: &mut MemoryStore,
+            frames: Vec<Vec<u8>>,
+        ) -> Vec<Vec<u8>> {
+            let mut out = Vec::new();
+            for frame in frames {
+                let msg = parser::parse(&frame).unwrap();
+                for action in session.on_message_with_store(&msg, store).unwrap() {
+                    if let SessionAction::Send(frame) = action {
+                        out.push(frame);
+                    }
+                }
+            }
+            out
+        }
+        fn pump(
+            (alice, alice_store): (&mut FixSession, &mut MemoryStore),
+            (broker, broker_store): (&mut FixSession, &mut MemoryStore),
+            mut to_alice: Vec<Vec<u8>>,
+            mut to_broker: Vec<Vec<u8>>,
+        ) {
+            while !(to_alice.is_empty() && to_broker.is_empty()) {
+                let replies = deliver(broker, broker_store, core::mem::take(&mut to_broker));
+                to_broker = deliver(alice, alice_store, core::mem::take(&mut to_alice));
+                to_alice = replies;
+            }
+        }
+
+        let mut alice = make_session();
+        let mut broker = FixSession::new("BROKER", "ALICE", BeginString::Fix44);
+        broker.set_role(SessionRole::Acceptor);
+        let (mut alice_store, mut broker_store) = (MemoryStore::new(), MemoryStore::new());
+        let logon = alice.build_logon();
+        alice.store_sent(&mut alice_store, &logon).unwrap();
+        pump(
+            (&mut alice, &mut alice_store),
+            (&mut broker, &mut broker_store),
+            Vec::new(),
+            vec![logon],
+        );
+        assert_eq!(*alice.state(), SessionState::Active);
+
+        // Each side loses two heartbeats, then sends a third.
+        let send = |session: &mut FixSession, store: &mut MemoryStore| {
+            for _ in 0..2 {
+                let lost = session.build_heartbeat();
+                session.store_sent(store, &lost).unwrap();
+            }
+            let hb = session.build_heartbeat();
+            session.store_sent(store, &hb).unwrap();
+            vec![hb]
+        };
+        let to_broker = send(&mut alice, &mut alice_store);
+        let to_alice = send(&mut broker, &mut broker_store);
+        pump(
+            (&mut alice, &mut alice_store),
+            (&mut broker, &mut broker_store),
+            to_alice,
+            to_broker,
+        );
+
+        for (session, peer) in [(&alice, &broker), (&broker, &alice)] {
+            assert_eq!(session.queued(), 0);
+            assert_eq!(
+                session.snapshot().incoming_seq,
+                peer.snapshot().outgoing_seq
+            );
+        }
+    }
+
+    #[test]
+    fn test_on_message_protocol_violations_disconnect() {
+        let mut session = make_session();
+        let actions = session.on_message(&inbound("D", 1));
+        assert_eq!(sent(&actions[0]).msg_type, "5");
+        assert_eq!(actions[1], SessionAction::Disconnect);
+
+        let mut session = make_acceptor();
+        session.on_message(&counterparty_logon());
+        session.on_message(&inbound("0", 2));
+        let mut dup = inbound("0", 2);
+        dup.set(tag::POSS_DUP_FLAG, "Y");
+        assert!(session.on_message(&dup).is_empty());
+        let actions = session.on_message(&inbound("0", 1));
+        let logout = sent(&actions[0]);
+        assert_eq!(
+            logout.get(tag::TEXT),
+            Some("MsgSeqNum too low, expecting 3 but received 1")
+        );
+        assert_eq!(*session.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn test_heartbeat_interval_agreed_and_scheduled() {
+        use crate::clock::Clock;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        const SEC: u64 = 1_000_000_000;
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        let clock: Arc<dyn Clock> = Arc::new(|| NOW.load(Ordering::Relaxed));
+
+        let mut session = make_session();
+        session.set_clock(clock);
+        session.set_heart_bt_int(20);
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get(tag::HEART_BT_INT), Some("20"));
+        assert_eq!(session.time_until_next_heartbeat(), None);
+        assert!(session.on_timer(100 * SEC).is_empty());
+
+        let mut reply = counterparty_logon();
+        reply.set(tag::HEART_BT_INT, "20");
+        session.on_message(&reply);
+        assert_eq!(session.heart_bt_int(), 20);
+        assert_eq!(session.time_until_next_heartbeat(), Some(20 * SEC));
+
+        // Outgoing traffic at 15s pushes the deadline to 35s, not to the
+        // next grid point at 40s.
+        NOW.store(15 * SEC, Ordering::Relaxed);
+        session.on_message(&inbound("0", 2));
+        session.build_heartbeat();
+        assert!(session.on_timer(34 * SEC).is_empty());
+        let actions = session.on_timer(35 * SEC);
+        let hb = sent(&actions[0]);
+        assert_eq!(hb.msg_type, "0");
+        assert_eq!(hb.get_u64(tag::MSG_SEQ_NUM), Some(3));
+        assert!(session.on_timer(36 * SEC).is_empty());
 
-    /// Build a Logout message (`MsgType` "5") and transition to
-    /// [`SessionState::LogoutSent`].
-    pub fn build_logout(&mut self) -> Vec<u8> {
-        let seq = self.next_outgoing_seq();
-        self.state = SessionState::LogoutSent;
-        self.build_admin("5", seq)
+        session.on_message(&inbound("5", 3));
+        assert_eq!(session.time_until_next_heartbeat(), None);
     }
 
-    /// Build a Heartbeat message (`MsgType` "0") without changing session state.
-    pub fn build_heartbeat(&mut self) -> Vec<u8> {
-        let seq = self.next_outgoing_seq();
-        self.build_admin("0", seq)
-    }
+    #[test]
+    fn test_test_request_sent_on_silence_and_correlated() {
+        use crate::clock::FixedClock;
 
-    /// Build a `NewOrderSingle` (`MsgType` "D") from an ALICE-Ledger [`Order`].
-    ///
-    /// The `symbol` parameter provides the instrument identifier (tag 55),
-    /// since [`Order`] does not carry a symbol string.
-    pub fn build_new_order(&mut self, order: &Order, symbol: &str) -> Vec<u8> {
-        let seq = self.next_outgoing_seq();
-        let price_str = order.price.to_string();
-        let qty_str = order.quantity.to_string();
-        let cl_ord_id = order.id.0.to_string();
+        const SEC: u64 = 1_000_000_000;
+        let mut session = make_session();
+        session.set_clock(Arc::new(FixedClock(0)));
+        session.set_heart_bt_int(10);
+        session.build_logon();
+        let mut reply = counterparty_logon();
+        reply.set(tag::HEART_BT_INT, "10");
+        session.on_message(&reply);
 
-        FixBuilder::new(&self.begin_string, "D")
-            .field(tag::SENDER_COMP_ID, &self.sender_comp_id)
-            .field(tag::TARGET_COMP_ID, &self.target_comp_id)
-            .field_u64(tag::MSG_SEQ_NUM, seq)
-            .field(tag::CL_ORD_ID, &cl_ord_id)
-            .field(tag::SYMBOL, symbol)
-            .field(tag::SIDE, alice_side_to_fix(order.side))
-            .field(tag::ORD_TYPE, alice_ord_type_to_fix(order.order_type))
-            .field(tag::PRICE, &price_str)
-            .field(tag::ORDER_QTY, &qty_str)
-            .field(tag::TIME_IN_FORCE, alice_tif_to_fix(order.time_in_force))
-            .build()
-    }
+        // 10s: our heartbeat is due, but 1.5 × interval has not passed.
+        let actions = session.on_timer(10 * SEC);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(sent(&actions[0]).msg_type, "0");
 
-    // -----------------------------------------------------------------------
-    // Private helpers
-    // -----------------------------------------------------------------------
+        let actions = session.on_timer(15 * SEC);
+        let req = sent(&actions[0]);
+        assert_eq!(req.msg_type, "1");
+        assert_eq!(req.get(tag::TEST_REQ_ID), Some("TEST1"));
+        assert_eq!(session.pending_test_req_id(), Some("TEST1"));
+        assert!(session.on_timer(16 * SEC).is_empty());
 
-    /// Construct a minimal administrative message with standard header fields.
-    fn build_admin(&self, msg_type: &str, seq: u64) -> Vec<u8> {
-        FixBuilder::new(&self.begin_string, msg_type)
-            .field(tag::SENDER_COMP_ID, &self.sender_comp_id)
-            .field(tag::TARGET_COMP_ID, &self.target_comp_id)
-            .field_u64(tag::MSG_SEQ_NUM, seq)
-            .build()
+        // A Heartbeat without the matching TestReqID does not clear it.
+        let mut hb = inbound("0", 2);
+        hb.set(tag::TEST_REQ_ID, "OTHER");
+        session.on_message(&hb);
+        assert_eq!(session.pending_test_req_id(), Some("TEST1"));
+        let mut hb = inbound("0", 3);
+        hb.set(tag::TEST_REQ_ID, "TEST1");
+        session.on_message(&hb);
+        assert_eq!(session.pending_test_req_id(), None);
+
+        // Still silent (the fixed clock stamped every receipt at 0): a new
+        // TestRequest, and logout one interval later if it goes unanswered.
+        let actions = session.on_timer(15 * SEC);
+        assert_eq!(sent(&actions[0]).get(tag::TEST_REQ_ID), Some("TEST2"));
+        assert!(session.on_timer(24 * SEC).is_empty());
+        let actions = session.on_timer(25 * SEC);
+        let logout = sent(&actions[0]);
+        assert_eq!(logout.get(tag::TEXT), Some("TestRequest not answered"));
+        assert_eq!(actions[1], SessionAction::Disconnect);
+        assert_eq!(*session.state(), SessionState::Disconnected);
     }
-}
 
-// ---------------------------------------------------------------------------
-// Tests
-// ---------------------------------------------------------------------------
+    #[test]
+    fn test_sequence_reset_inbound() {
+        let mut session = make_acceptor();
+        session.on_message(&counterparty_logon());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::parser;
-    use crate::tag;
-    use alice_ledger::{Order, OrderId, OrderType, Side, TimeInForce};
+        // Reset mode ignores MsgSeqNum.
+        let mut reset = inbound("4", 99);
+        reset.set(tag::NEW_SEQ_NO, "10");
+        assert!(session.on_message(&reset).is_empty());
+        assert_eq!(session.snapshot().incoming_seq, 10);
 
-    fn make_session() -> FixSession {
-        FixSession::new("ALICE", "BROKER", "FIX.4.4")
+        let mut back = inbound("4", 1);
+        back.set(tag::NEW_SEQ_NO, "5");
+        let reject = sent(&session.on_message(&back)[0]);
+        assert_eq!(reject.msg_type, "3");
+        assert_eq!(reject.get_u64(tag::REF_TAG_ID), Some(36));
+        assert_eq!(reject.get_u64(tag::SESSION_REJECT_REASON), Some(5));
+        assert_eq!(session.snapshot().incoming_seq, 10);
+
+        let mut fill = inbound("4", 10);
+        fill.set(tag::GAP_FILL_FLAG, "Y").set(tag::NEW_SEQ_NO, "12");
+        assert!(session.on_message(&fill).is_empty());
+        assert_eq!(session.snapshot().incoming_seq, 12);
     }
 
-    fn make_limit_order(id: u64, side: Side, price: i64, qty: u64) -> Order {
-        Order {
-            id: OrderId(id),
-            side,
-            order_type: OrderType::Limit,
-            price,
-            quantity: qty,
-            filled_quantity: 0,
-            timestamp_ns: 0,
-            time_in_force: TimeInForce::GTC,
+    #[test]
+    fn test_replay_gap_fills_admin_messages() {
+        use crate::store::{MemoryStore, MessageStore};
+
+        let mut session = make_session();
+        let mut store = MemoryStore::new();
+        let order = make_limit_order(7, Side::Bid, 100, 1);
+        let frames = [
+            session.build_logon(),
+            session.build_new_order(&order, "BTCUSD").unwrap(),
+            session.build_heartbeat(),
+            session.build_heartbeat(),
+            session.build_new_order(&order, "ETHUSD").unwrap(),
+            session.build_heartbeat(),
+        ];
+        for (seq, frame) in (1..).zip(&frames) {
+            store.put(seq, 0, frame).unwrap();
         }
+
+        let out: Vec<FixMessage> = session
+            .replay(&mut store, 1, 0)
+            .unwrap()
+            .iter()
+            .map(|f| parser::parse(f).unwrap())
+            .collect();
+        let summary: Vec<(&str, Option<u64>, Option<u64>)> = out
+            .iter()
+            .map(|m| {
+                (
+                    m.msg_type.as_str(),
+                    m.get_u64(tag::MSG_SEQ_NUM),
+                    m.get_u64(tag::NEW_SEQ_NO),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("4", Some(1), Some(2)),
+                ("D", Some(2), None),
+                ("4", Some(3), Some(5)),
+                ("D", Some(5), None),
+                ("4", Some(6), Some(7)),
+            ]
+        );
+        assert!(out
+            .iter()
+            .all(|m| m.get_bool(tag::POSS_DUP_FLAG) == Some(true)));
+        assert_eq!(out[0].get_bool(tag::GAP_FILL_FLAG), Some(true));
+        assert!(out[1].contains(tag::ORIG_SENDING_TIME));
+        assert_eq!(out[1].get(tag::SYMBOL), Some("BTCUSD"));
+        assert_eq!(session.snapshot().outgoing_seq, 7);
     }
 
     #[test]
-    fn test_session_creation() {
-        let session = make_session();
-        assert_eq!(*session.state(), SessionState::Disconnected);
+    fn test_resend_request_answered_from_store() {
+        use crate::store::{MemoryStore, MessageStore};
+
+        let mut session = make_session();
+        let mut store = MemoryStore::new();
+        let logon = session.build_logon();
+        session.store_sent(&mut store, &logon).unwrap();
+        let actions = session
+            .on_message_with_store(&counterparty_logon(), &mut store)
+            .unwrap();
+        assert!(actions.is_empty());
+        for id in ["A1", "A2"] {
+            let mut order = FixMessage::new("FIX.4.4", "D");
+            order.set(tag::CL_ORD_ID, id);
+            let frame = session.submit(order).unwrap().unwrap();
+            session.store_sent(&mut store, &frame).unwrap();
+        }
+        let heartbeat = session.build_heartbeat();
+        session.store_sent(&mut store, &heartbeat).unwrap();
+        assert_eq!(store.last_seq(), 4);
+        assert!(session.store_sent(&mut store, b"garbage").is_err());
+
+        let mut request = inbound("2", 2);
+        request
+            .set_value(tag::BEGIN_SEQ_NO, 1_u64)
+            .set_value(tag::END_SEQ_NO, 0_u64);
+        let out: Vec<FixMessage> = session
+            .on_message_with_store(&request, &mut store)
+            .unwrap()
+            .iter()
+            .map(sent)
+            .collect();
+        let summary: Vec<(&str, Option<&str>, Option<u64>)> = out
+            .iter()
+            .map(|m| {
+                (
+                    m.msg_type.as_str(),
+                    m.get(tag::CL_ORD_ID),
+                    m.get_u64(tag::MSG_SEQ_NUM),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("4", None, Some(1)),
+                ("D", Some("A1"), Some(2)),
+                ("D", Some("A2"), Some(3)),
+                ("4", None, Some(4)),
+            ]
+        );
+        assert_eq!(out[3].get_u64(tag::NEW_SEQ_NO), Some(5));
+        assert!(out
+            .iter()
+            .all(|m| m.get_bool(tag::POSS_DUP_FLAG) == Some(true)));
+        // Replayed frames keep their sequence numbers and are not re-stored.
+        assert_eq!(session.seq_nums().outgoing, 5);
+        assert_eq!(store.last_seq(), 4);
     }
 
     #[test]
-    fn test_outgoing_seq_increments() {
+    fn test_logon_carries_credentials_and_extra_fields() {
         let mut session = make_session();
-        assert_eq!(session.next_outgoing_seq(), 1);
-        assert_eq!(session.next_outgoing_seq(), 2);
-        assert_eq!(session.next_outgoing_seq(), 3);
+        let bare = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(bare.get(tag::ENCRYPT_METHOD), Some("0"));
+        assert!(!bare.contains(tag::USERNAME));
+
+        let config = LogonConfig::new()
+            .username("alice")
+            .password("s3cret")
+            .extra_field(1137, "9")
+            .extra_field(1400, "1");
+        assert!(!format!("{config:?}").contains("s3cret"));
+        let mut session = make_session();
+        session.set_logon_config(config);
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get(tag::USERNAME), Some("alice"));
+        assert_eq!(logon.get(tag::PASSWORD), Some("s3cret"));
+        assert_eq!(logon.get(tag::HEART_BT_INT), Some("30"));
+        assert_eq!(logon.get(1137), Some("9"));
+        assert_eq!(logon.get(1400), Some("1"));
+        assert!(!logon.contains(tag::RESET_SEQ_NUM_FLAG));
     }
 
     #[test]
-    fn test_incoming_seq_validation() {
+    fn test_reset_seq_num_on_logon() {
+        // Outbound: both counters restart and the Logon is 1 with 141=Y.
         let mut session = make_session();
-        // Sequence 1 is expected first.
-        assert!(session.validate_incoming_seq(1));
-        // Now sequence 2 is expected.
-        assert!(session.validate_incoming_seq(2));
-        // Sequence 1 again is out of order.
-        assert!(!session.validate_incoming_seq(1));
-        // Sequence 4 is a gap.
-        assert!(!session.validate_incoming_seq(4));
-        // Sequence 3 is the correct next.
-        assert!(session.validate_incoming_seq(3));
+        session.set_logon_config(LogonConfig::new().reset_seq_num(true));
+        session.next_outgoing_seq();
+        session.validate_incoming_seq(1);
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(logon.get_bool(tag::RESET_SEQ_NUM_FLAG), Some(true));
+        assert_eq!(session.snapshot().incoming_seq, 1);
+        let mut reply = counterparty_logon();
+        reply.set(tag::RESET_SEQ_NUM_FLAG, "Y");
+        assert!(session.on_message(&reply).is_empty());
+        assert_eq!(*session.state(), SessionState::Active);
+
+        // Inbound: the counterparty's reset is honored and echoed.
+        let mut session = FixSession::from_snapshot(SessionSnapshot {
+            outgoing_seq: 40,
+            incoming_seq: 57,
+            ..make_session().snapshot()
+        });
+        session.set_role(SessionRole::Acceptor);
+        let mut logon = counterparty_logon();
+        logon.set(tag::RESET_SEQ_NUM_FLAG, "Y");
+        let actions = session.on_message(&logon);
+        assert_eq!(actions.len(), 1);
+        let ack = sent(&actions[0]);
+        assert_eq!(ack.get_u64(tag::MSG_SEQ_NUM), Some(1));
+        assert_eq!(ack.get_bool(tag::RESET_SEQ_NUM_FLAG), Some(true));
+        let snap = session.snapshot();
+        assert_eq!((snap.outgoing_seq, snap.incoming_seq), (2, 2));
     }
 
     #[test]
-    fn test_build_logon_message() {
+    fn test_seq_nums_survive_restart() {
+        use crate::store::MemorySeqNumStore;
+
+        let mut store = MemorySeqNumStore::default();
+        let mut fresh = make_session();
+        assert!(!fresh.restore(&mut store).unwrap());
+        assert_eq!(fresh.seq_nums().outgoing, 1);
+
         let mut session = make_session();
-        let bytes = session.build_logon();
-        let msg = parser::parse(&bytes).expect("logon should parse");
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        session.build_heartbeat();
+        session.save_seq_nums(&mut store).unwrap();
 
-        assert_eq!(msg.msg_type, "A");
-        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("ALICE"));
-        assert_eq!(msg.get(tag::TARGET_COMP_ID), Some("BROKER"));
-        assert_eq!(msg.get_u64(tag::MSG_SEQ_NUM), Some(1));
-        assert_eq!(*session.state(), SessionState::LogonSent);
+        let mut restarted = make_session();
+        assert!(restarted.restore(&mut store).unwrap());
+        assert_eq!(
+            restarted.seq_nums(),
+            SeqNums {
+                outgoing: 3,
+                incoming: 2
+            }
+        );
+        let logon = parser::parse(&restarted.build_logon()).unwrap();
+        assert_eq!(logon.get_u64(tag::MSG_SEQ_NUM), Some(3));
     }
 
     #[test]
-    fn test_build_logout_message() {
+    fn test_resend_pipeline_stamps_poss_dup() {
+        use crate::clock::FixedClock;
+        use crate::store::{MemoryStore, MessageStore};
+
         let mut session = make_session();
-        let bytes = session.build_logout();
-        let msg = parser::parse(&bytes).expect("logout should parse");
-        assert_eq!(msg.msg_type, "5");
-        assert_eq!(*session.state(), SessionState::LogoutSent);
+        session.set_clock(Arc::new(FixedClock(1_767_225_600_000_000_000)));
+        let mut store = MemoryStore::new();
+        let logon = session.build_logon();
+        store.put(1, 0, &logon).unwrap();
+        let order = make_limit_order(7, Side::Bid, 100, 1);
+        let original = session.build_new_order(&order, "BTCUSD").unwrap();
+        store.put(2, 0, &original).unwrap();
+        session.on_message(&counterparty_logon());
+
+        session.set_clock(Arc::new(FixedClock(1_767_225_660_000_000_000)));
+        let mut request = inbound("2", 2);
+        request
+            .set(tag::BEGIN_SEQ_NO, "2")
+            .set(tag::END_SEQ_NO, "0");
+        let actions = session.on_message(&request);
+        let actions = session.resolve_resends(actions, &mut store).unwrap();
+        assert_eq!(actions.len(), 1);
+        let SessionAction::Send(frame) = &actions[0] else {
+            panic!("expected Send");
+        };
+        // parse() verifies the recomputed BodyLength and CheckSum.
+        let resent = parser::parse(frame).unwrap();
+        assert_eq!(resent.get_bool(tag::POSS_DUP_FLAG), Some(true));
+        assert_eq!(
+            resent.get(tag::ORIG_SENDING_TIME),
+            Some("20260101-00:00:00.000")
+        );
+        assert_eq!(resent.get(tag::SENDING_TIME), Some("20260101-00:01:00.000"));
+        assert_eq!(resent.get_u64(tag::MSG_SEQ_NUM), Some(2));
+
+        // Resending a resend keeps the first OrigSendingTime.
+        session.set_clock(Arc::new(FixedClock(1_767_225_720_000_000_000)));
+        let again = parser::parse(&session.poss_dup_frame(frame).unwrap()).unwrap();
+        assert_eq!(
+            again.get(tag::ORIG_SENDING_TIME),
+            Some("20260101-00:00:00.000")
+        );
+        assert_eq!(again.get(tag::SENDING_TIME), Some("20260101-00:02:00.000"));
     }
 
     #[test]
-    fn test_build_heartbeat_message() {
+    fn test_resend_keeps_stored_bytes() {
+        use crate::clock::FixedClock;
+        use crate::store::{MemoryStore, MessageStore};
+
         let mut session = make_session();
-        let bytes = session.build_heartbeat();
-        let msg = parser::parse(&bytes).expect("heartbeat should parse");
-        assert_eq!(msg.msg_type, "0");
-        // State should remain Disconnected (heartbeat does not change state).
-        assert_eq!(*session.state(), SessionState::Disconnected);
+        session.set_clock(Arc::new(FixedClock(1_767_225_600_000_000_000)));
+        session.build_logon();
+        session.build_heartbeat();
+        let mut builder = FixBuilder::new(BeginString::Fix44, "D");
+        builder
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, 2)
+            .field(tag::SENDING_TIME, "20260101-00:00:00.000")
+            .field(tag::CL_ORD_ID, "A1")
+            .field_data(tag::RAW_DATA_LENGTH, tag::RAW_DATA, b"a\x0110=000\x01b")
+            .field(9001, "2")
+            .field(9002, "X")
+            .field(9003, "1")
+            .field(9002, "Y")
+            .field(9003, "2")
+            .field_data(tag::SIGNATURE_LENGTH, tag::SIGNATURE, b"\x01sig\x01");
+        let original = builder.build();
+        let mut store = MemoryStore::new();
+        store.put(2, 0, &original).unwrap();
+
+        session.set_clock(Arc::new(FixedClock(1_767_225_660_000_000_000)));
+        let frames = session.replay(&mut store, 2, 2).unwrap();
+        assert_eq!(frames.len(), 1);
+        let text = String::from_utf8_lossy(&frames[0]).replace('\x01', "|");
+        let body = "35=D|49=ALICE|56=BROKER|34=2|43=Y|52=20260101-00:01:00.000|\
+                    122=20260101-00:00:00.000|11=A1|95=10|96=a|10=000|b|\
+                    9001=2|9002=X|9003=1|9002=Y|9003=2|93=5|89=|sig||";
+        assert!(text.starts_with(&format!("8=FIX.4.4|9={}|", body.len())));
+        assert!(text.contains(body), "{text}");
+        let checksum = parser::compute_checksum(&frames[0][..frames[0].len() - 7]);
+        assert!(text.ends_with(&format!("10={checksum:03}|")));
     }
 
     #[test]
-    fn test_build_new_order() {
+    fn test_replay_refuses_unreadable_stored_frame() {
+        use crate::store::{MemoryStore, MessageStore};
+
         let mut session = make_session();
-        let order = make_limit_order(42, Side::Bid, 50_000, 10);
-        let bytes = session.build_new_order(&order, "BTCUSD");
-        let msg = parser::parse(&bytes).expect("new order should parse");
+        let mut store = MemoryStore::new();
+        let order = make_limit_order(7, Side::Bid, 100, 1);
+        let logon = session.build_logon();
+        let mut frame = session.build_new_order(&order, "BTCUSD").unwrap();
+        store.put(1, 0, &logon).unwrap();
+        frame.truncate(frame.len() - 3);
+        store.put(2, 0, &frame).unwrap();
 
-        assert_eq!(msg.msg_type, "D");
-        assert_eq!(msg.get(tag::SYMBOL), Some("BTCUSD"));
-        assert_eq!(msg.get(tag::SIDE), Some("1")); // Bid = "1"
-        assert_eq!(msg.get(tag::ORD_TYPE), Some("2")); // Limit = "2"
-        assert_eq!(msg.get_i64(tag::PRICE), Some(50_000));
-        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(10));
-        assert_eq!(msg.get(tag::CL_ORD_ID), Some("42"));
+        let err = session.replay(&mut store, 1, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("stored frame 2"));
+        assert!(session.poss_dup_frame(&frame).is_err());
     }
 
     #[test]
-    fn test_seq_advances_across_messages() {
+    fn test_schedule_drives_logon_logout_and_rollover() {
+        use crate::schedule::TimeOfDay;
+
+        const HOUR: u64 = 3_600_000_000_000;
+        let monday = 1_767_571_200_000_000_000;
         let mut session = make_session();
-        let b1 = session.build_logon();
-        let b2 = session.build_heartbeat();
-        let b3 = session.build_heartbeat();
+        session.set_schedule(Some(SessionSchedule::new(
+            TimeOfDay::hms(8, 0, 0),
+            TimeOfDay::hms(16, 0, 0),
+        )));
+        assert!(!session.should_logon(monday + 7 * HOUR));
+        assert!(session.should_logon(monday + 8 * HOUR));
 
-        let m1 = parser::parse(&b1).unwrap();
-        let m2 = parser::parse(&b2).unwrap();
-        let m3 = parser::parse(&b3).unwrap();
+        session.on_timer(monday + 8 * HOUR);
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        assert!(!session.should_logon(monday + 9 * HOUR));
 
-        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
-        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
-        assert_eq!(m3.get_u64(tag::MSG_SEQ_NUM), Some(3));
-    }
+        let actions = session.on_timer(monday + 16 * HOUR);
+        assert_eq!(sent(&actions[0]).msg_type, "5");
+        assert_eq!(*session.state(), SessionState::LogoutSent);
+        session.on_message(&inbound("5", 2));
+        assert_eq!(session.seq_nums().outgoing, 3);
 
-    // -----------------------------------------------------------------------
-    // Additional session tests
-    // -----------------------------------------------------------------------
+        // Next morning: a new window resets the sequence numbers.
+        session.on_timer(monday + 31 * HOUR);
+        assert_eq!(session.seq_nums().outgoing, 3);
+        session.on_timer(monday + 32 * HOUR);
+        assert_eq!(
+            session.seq_nums(),
+            SeqNums {
+                outgoing: 1,
+                incoming: 1
+            }
+        );
+    }
 
     #[test]
-    fn test_logon_changes_state_to_logon_sent() {
+    fn test_session_stats() {
+        use crate::clock::FixedClock;
+
+        fn counts(m: &BTreeMap<String, u64>) -> Vec<(&str, u64)> {
+            m.iter().map(|(k, v)| (k.as_str(), *v)).collect()
+        }
+
         let mut session = make_session();
-        assert_eq!(*session.state(), SessionState::Disconnected);
-        let _ = session.build_logon();
-        assert_eq!(*session.state(), SessionState::LogonSent);
+        session.set_clock(Arc::new(FixedClock(7)));
+        assert_eq!(
+            session.stats(),
+            SessionMetrics {
+                outgoing_seq: 1,
+                incoming_seq: 1,
+                ..SessionMetrics::default()
+            }
+        );
+
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        session
+            .build_new_order(&make_limit_order(1, Side::Bid, 100, 5), "X")
+            .unwrap();
+        // 2 is missing: one gap, one ResendRequest.
+        session.on_message(&inbound("0", 3));
+        session.on_message(&inbound("0", 4));
+        let mut resend = inbound("2", 2);
+        resend.set(tag::BEGIN_SEQ_NO, "1").set(tag::END_SEQ_NO, "0");
+        session.on_message(&resend);
+
+        let stats = session.stats();
+        assert_eq!(counts(&stats.sent), [("2", 1), ("A", 1), ("D", 1)]);
+        assert_eq!(counts(&stats.received), [("0", 2), ("2", 1), ("A", 1)]);
+        assert_eq!((stats.outgoing_seq, stats.incoming_seq), (4, 5));
+        assert_eq!(stats.last_sent_ns, Some(7));
+        assert_eq!(stats.last_received_ns, Some(7));
+        assert_eq!((stats.gaps, stats.resends), (1, 1));
     }
 
     #[test]
-    fn test_logout_changes_state_to_logout_sent() {
+    fn test_session_events() {
+        use crate::clock::FixedClock;
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
         let mut session = make_session();
-        let _ = session.build_logout();
-        assert_eq!(*session.state(), SessionState::LogoutSent);
+        session.set_clock(Arc::new(FixedClock(0)));
+        session.set_event_handler(move |e| sink.lock().unwrap().push(e.clone()));
+
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        session.on_message(&inbound("0", 4));
+        let mut resend = inbound("2", 2);
+        resend.set(tag::BEGIN_SEQ_NO, "1").set(tag::END_SEQ_NO, "0");
+        session.on_message(&resend);
+        let mut reset = inbound("4", 3);
+        reset.set(tag::GAP_FILL_FLAG, "Y").set(tag::NEW_SEQ_NO, "1");
+        session.on_message(&reset);
+        session.on_timer(45 * NANOS_PER_SEC);
+        let mut logout = inbound("5", 5);
+        logout.set(tag::TEXT, "bye");
+        session.on_message(&logout);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                SessionEvent::StateChanged {
+                    from: SessionState::Disconnected,
+                    to: SessionState::LogonSent,
+                },
+                SessionEvent::StateChanged {
+                    from: SessionState::LogonSent,
+                    to: SessionState::Active,
+                },
+                SessionEvent::GapDetected { begin: 2, end: 3 },
+                SessionEvent::ResendRequested { begin: 1, end: 0 },
+                SessionEvent::Rejected {
+                    reason: SessionRejectReason::ValueOutOfRange,
+                    text: "NewSeqNo 1 is below the expected 4".to_string(),
+                },
+                SessionEvent::HeartbeatMissed {
+                    test_req_id: "TEST1".to_string(),
+                },
+                SessionEvent::StateChanged {
+                    from: SessionState::Active,
+                    to: SessionState::LogoutSent,
+                },
+                SessionEvent::StateChanged {
+                    from: SessionState::LogoutSent,
+                    to: SessionState::Disconnected,
+                },
+                SessionEvent::LoggedOut {
+                    reason: Some("bye".to_string()),
+                },
+            ]
+        );
     }
 
     #[test]
-    fn test_heartbeat_does_not_change_state() {
+    fn test_tick_follows_manual_clock() {
+        use crate::clock::ManualClock;
+
+        let clock = Arc::new(ManualClock::new(0));
         let mut session = make_session();
-        let _ = session.build_logon();
-        assert_eq!(*session.state(), SessionState::LogonSent);
-        let _ = session.build_heartbeat();
-        // State should remain LogonSent.
-        assert_eq!(*session.state(), SessionState::LogonSent);
+        session.set_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        assert!(session.tick().is_empty());
+
+        clock.advance(30 * NANOS_PER_SEC);
+        assert_eq!(session.time_until_next_heartbeat(), Some(0));
+        let hb = sent(&session.tick()[0]);
+        assert_eq!(hb.msg_type, "0");
+        assert_eq!(hb.get(tag::SENDING_TIME), Some("19700101-00:00:30.000"));
+
+        clock.advance(15 * NANOS_PER_SEC);
+        let test_req = sent(&session.tick()[0]);
+        assert_eq!(test_req.msg_type, "1");
+        clock.advance(30 * NANOS_PER_SEC);
+        assert_eq!(session.tick().last(), Some(&SessionAction::Disconnect));
     }
 
     #[test]
-    fn test_multiple_logons_advance_seq() {
+    fn test_admin_and_application_paths() {
         let mut session = make_session();
-        let b1 = session.build_logon();
-        let b2 = session.build_logon();
-        let m1 = parser::parse(&b1).unwrap();
-        let m2 = parser::parse(&b2).unwrap();
-        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
-        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+
+        assert!(session.on_message(&inbound("0", 2)).is_empty());
+        let mut reject = inbound("3", 3);
+        reject.set(tag::REF_SEQ_NUM, "2");
+        assert_eq!(
+            session.on_message(&reject),
+            vec![SessionAction::DeliverAdmin(reject.clone())]
+        );
+        let exec = inbound("8", 4);
+        assert_eq!(
+            session.on_message(&exec),
+            vec![SessionAction::Deliver(exec.clone())]
+        );
     }
 
     #[test]
-    fn test_incoming_seq_starts_at_one() {
+    fn test_submit_throttle_queue_and_reject() {
+        use crate::clock::ManualClock;
+        use crate::throttle::RateLimit;
+
+        let order = |msg_type: &str, id: &str| {
+            let mut msg = FixMessage::new("FIX.4.4", msg_type);
+            msg.set(tag::CL_ORD_ID, id);
+            msg
+        };
+        let clock = Arc::new(ManualClock::new(0));
         let mut session = make_session();
-        assert!(!session.validate_incoming_seq(0));
-        assert!(session.validate_incoming_seq(1));
+        session.set_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        session.set_throttle(Throttle::new().global_limit(RateLimit::new(1, 1), 0));
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+
+        assert!(session.submit(order("D", "1")).unwrap().is_some());
+        assert_eq!(session.submit(order("D", "2")), Ok(None));
+        assert_eq!(session.submit(order("D", "3")), Ok(None));
+        // Cancels are exempt and overtake held orders.
+        assert!(session.submit(order("F", "4")).unwrap().is_some());
+        assert_eq!(session.throttled(), 2);
+
+        clock.advance(NANOS_PER_SEC);
+        let released = session.tick();
+        assert_eq!(released.len(), 1);
+        assert_eq!(sent(&released[0]).get(tag::CL_ORD_ID), Some("2"));
+        assert_eq!(session.throttled(), 1);
+
+        session.set_throttle(
+            Throttle::new()
+                .global_limit(RateLimit::new(0, 1), NANOS_PER_SEC)
+                .on_limit(ThrottlePolicy::Reject),
+        );
+        let err = session.submit(order("D", "5")).unwrap_err();
+        assert_eq!(
+            err,
+            SubmitError::Throttled {
+                message: Box::new(order("D", "5")),
+                until_ns: 2 * NANOS_PER_SEC,
+            }
+        );
+        assert_eq!(session.take_throttled().len(), 1);
     }
 
     #[test]
-    fn test_incoming_seq_gap_rejection() {
+    fn test_sending_time_precision() {
+        use crate::clock::FixedClock;
+
         let mut session = make_session();
-        assert!(session.validate_incoming_seq(1));
-        // Skip 2, send 3 -> should fail.
-        assert!(!session.validate_incoming_seq(3));
-        // Sequence 2 is still expected.
-        assert!(session.validate_incoming_seq(2));
+        session.set_clock(Arc::new(FixedClock(1_767_225_600_123_456_789)));
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get(tag::SENDING_TIME), Some("20260101-00:00:00.123"));
+
+        session.set_timestamp_precision(TimestampPrecision::Micros);
+        session.on_message(&counterparty_logon());
+        let hb = parser::parse(&session.build_heartbeat()).unwrap();
+        assert_eq!(hb.get(tag::SENDING_TIME), Some("20260101-00:00:00.123456"));
+        let order = make_limit_order(1, Side::Bid, 100, 5);
+        let nos = parser::parse(&session.build_new_order(&order, "X").unwrap()).unwrap();
+        assert_eq!(nos.get(tag::SENDING_TIME), Some("20260101-00:00:00.123456"));
+        let app = session
+            .submit(FixMessage::new("FIX.4.4", "D"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            parser::parse(&app).unwrap().get(tag::SENDING_TIME),
+            Some("20260101-00:00:00.123456")
+        );
+
+        session.set_timestamp_precision(TimestampPrecision::Seconds);
+        let resent = parser::parse(&session.poss_dup_frame(&app).unwrap()).unwrap();
+        assert_eq!(resent.get(tag::SENDING_TIME), Some("20260101-00:00:00"));
+        assert_eq!(
+            resent.get(tag::ORIG_SENDING_TIME),
+            Some("20260101-00:00:00.123456")
+        );
     }
 
     #[test]
-    fn test_build_new_order_ask_side() {
+    fn test_order_ack_timeout() {
+        use crate::clock::ManualClock;
+        use std::sync::Mutex;
+
+        let clock = Arc::new(ManualClock::new(1_000_000_000));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
         let mut session = make_session();
-        let order = Order {
-            id: OrderId(100),
-            side: Side::Ask,
-            order_type: OrderType::Limit,
-            price: 60_000,
-            quantity: 25,
-            filled_quantity: 0,
-            timestamp_ns: 0,
-            time_in_force: TimeInForce::IOC,
-        };
-        let bytes = session.build_new_order(&order, "ETHUSD");
-        let msg = parser::parse(&bytes).unwrap();
-        assert_eq!(msg.msg_type, "D");
-        assert_eq!(msg.get(tag::SIDE), Some("2")); // Ask = "2"
-        assert_eq!(msg.get(tag::SYMBOL), Some("ETHUSD"));
-        assert_eq!(msg.get(tag::TIME_IN_FORCE), Some("3")); // IOC = "3"
-        assert_eq!(msg.get_u64(tag::ORDER_QTY), Some(25));
+        session.set_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        session.set_event_handler(move |e| {
+            if let SessionEvent::OrderAckTimeout { cl_ord_id, sent_ns } = e {
+                sink.lock().unwrap().push((cl_ord_id.clone(), *sent_ns));
+            }
+        });
+
+        // Not tracked until a timeout is set.
+        session
+            .build_new_order(&make_limit_order(1, Side::Bid, 100, 5), "X")
+            .unwrap();
+        assert_eq!(session.unacknowledged_orders().count(), 0);
+
+        session.set_order_ack_timeout_ms(Some(500));
+        assert_eq!(session.order_ack_timeout_ms(), Some(500));
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        session
+            .build_new_order(&make_limit_order(2, Side::Bid, 100, 5), "X")
+            .unwrap();
+        clock.advance(100_000_000);
+        let mut order = FixMessage::new("FIX.4.4", "D");
+        order.set(tag::CL_ORD_ID, "3");
+        assert!(session.submit(order).unwrap().is_some());
+        assert_eq!(
+            session.unacknowledged_orders().collect::<Vec<_>>(),
+            ["2", "3"]
+        );
+
+        // The first ExecutionReport for an order acknowledges it.
+        let mut ack = inbound("8", 2);
+        ack.set(tag::CL_ORD_ID, "2").set(tag::EXEC_TYPE, "0");
+        assert!(matches!(
+            session.on_message(&ack)[..],
+            [SessionAction::Deliver(_)]
+        ));
+        assert_eq!(session.unacknowledged_orders().collect::<Vec<_>>(), ["3"]);
+
+        session.on_timer(clock.advance(499_000_000));
+        assert!(events.lock().unwrap().is_empty());
+        session.on_timer(clock.advance(1_000_000));
+        assert_eq!(*events.lock().unwrap(), [("3".to_string(), 1_100_000_000)]);
+        assert_eq!(session.unacknowledged_orders().count(), 0);
+
+        // Reported once only.
+        session.on_timer(clock.advance(NANOS_PER_SEC));
+        assert_eq!(events.lock().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_build_new_order_market_type() {
-        let mut session = make_session();
-        let order = Order {
-            id: OrderId(200),
-            side: Side::Bid,
-            order_type: OrderType::Market,
-            price: 0,
-            quantity: 50,
-            filled_quantity: 0,
-            timestamp_ns: 0,
-            time_in_force: TimeInForce::FOK,
+    fn test_duplicate_cl_ord_id() {
+        use std::sync::Mutex;
+
+        let order = |msg_type: &str, id: &str| {
+            let mut msg = FixMessage::new("FIX.4.4", msg_type);
+            msg.set(tag::CL_ORD_ID, id);
+            msg
         };
-        let bytes = session.build_new_order(&order, "BTCUSD");
-        let msg = parser::parse(&bytes).unwrap();
-        assert_eq!(msg.get(tag::ORD_TYPE), Some("1")); // Market = "1"
-        assert_eq!(msg.get(tag::TIME_IN_FORCE), Some("4")); // FOK = "4"
-    }
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let mut session = make_session();
+        session.set_event_handler(move |e| {
+            if let SessionEvent::DuplicateClOrdId { cl_ord_id } = e {
+                sink.lock().unwrap().push(cl_ord_id.clone());
+            }
+        });
 
-    #[test]
-    fn test_session_state_debug() {
-        let state = SessionState::Active;
-        assert_eq!(format!("{state:?}"), "Active");
+        // Held before logon, but remembered already.
+        assert_eq!(session.submit(order("D", "A1")), Ok(None));
+        assert_eq!(
+            session.submit(order("F", "A1")),
+            Err(SubmitError::DuplicateClOrdId("A1".to_string()))
+        );
+        session.build_logon();
+        session.on_message(&counterparty_logon());
+        assert!(session.submit(order("F", "A2")).unwrap().is_some());
+        // Types other than D/F/G may repeat a ClOrdID.
+        assert!(session.submit(order("H", "A2")).unwrap().is_some());
+
+        let new_order = make_limit_order(7, Side::Bid, 100, 5);
+        session.build_new_order(&new_order, "X").unwrap();
+        session.build_new_order(&new_order, "X").unwrap();
+        assert_eq!(
+            session.submit(order("G", "7")),
+            Err(SubmitError::DuplicateClOrdId("7".to_string()))
+        );
+        assert_eq!(*events.lock().unwrap(), ["A1", "7", "7"]);
+
+        session.set_cl_ord_id_window(1);
+        assert!(session.submit(order("D", "A1")).unwrap().is_some());
+        session.clear_cl_ord_ids();
+        assert!(session.submit(order("D", "A1")).unwrap().is_some());
     }
 
     #[test]
-    fn test_session_state_clone() {
-        let s1 = SessionState::LogonSent;
-        let s2 = s1;
-        assert_eq!(s1, s2);
+    fn test_drop_copy_is_receive_only() {
+        let mut session = make_session();
+        session.set_mode(SessionMode::DropCopy);
+        session.set_logon_config(LogonConfig::new().extra_field(9001, "Y"));
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert_eq!(logon.get(9001), Some("Y"));
+        session.on_message(&counterparty_logon());
+
+        let order = make_limit_order(1, Side::Bid, 100, 5);
+        assert_eq!(
+            session.build_new_order(&order, "X"),
+            Err(SubmitError::ReceiveOnly)
+        );
+        assert_eq!(
+            session.submit(FixMessage::new("FIX.4.4", "D")),
+            Err(SubmitError::ReceiveOnly)
+        );
+        assert_eq!(session.seq_nums().outgoing, 2);
+
+        let exec = inbound("8", 2);
+        assert_eq!(
+            session.on_message(&exec),
+            vec![SessionAction::Deliver(exec.clone())]
+        );
+        let mut dup = exec;
+        dup.set(tag::POSS_DUP_FLAG, "Y");
+        assert_eq!(
+            session.on_message(&dup),
+            vec![SessionAction::Deliver(dup.clone())]
+        );
+        let mut dup_hb = inbound("0", 2);
+        dup_hb.set(tag::POSS_DUP_FLAG, "Y");
+        assert!(session.on_message(&dup_hb).is_empty());
+
+        session.set_mode(SessionMode::Trading);
+        assert!(session.on_message(&dup).is_empty());
     }
 
     #[test]
-    fn test_full_session_lifecycle_seq_numbers() {
+    fn test_third_party_routing() {
         let mut session = make_session();
-        // Logon = seq 1
-        let b1 = session.build_logon();
-        assert_eq!(*session.state(), SessionState::LogonSent);
-        // Heartbeat = seq 2
-        let b2 = session.build_heartbeat();
-        // New order = seq 3
-        let order = make_limit_order(1, Side::Bid, 100, 10);
-        let b3 = session.build_new_order(&order, "SYM");
-        // Logout = seq 4
-        let b4 = session.build_logout();
-        assert_eq!(*session.state(), SessionState::LogoutSent);
+        session.set_routing(
+            Routing::new()
+                .on_behalf_of("FUND")
+                .on_behalf_of_sub("PM1")
+                .deliver_to("BROKER_B"),
+        );
+        let logon = parser::parse(&session.build_logon()).unwrap();
+        assert!(Routing::from_message(&logon).is_empty());
+        session.on_message(&counterparty_logon());
 
-        let m1 = parser::parse(&b1).unwrap();
-        let m2 = parser::parse(&b2).unwrap();
-        let m3 = parser::parse(&b3).unwrap();
-        let m4 = parser::parse(&b4).unwrap();
+        let order = make_limit_order(1, Side::Bid, 100, 5);
+        let frame = parser::parse(&session.build_new_order(&order, "X").unwrap()).unwrap();
+        assert_eq!(Routing::from_message(&frame), *session.routing());
 
-        assert_eq!(m1.get_u64(tag::MSG_SEQ_NUM), Some(1));
-        assert_eq!(m2.get_u64(tag::MSG_SEQ_NUM), Some(2));
-        assert_eq!(m3.get_u64(tag::MSG_SEQ_NUM), Some(3));
-        assert_eq!(m4.get_u64(tag::MSG_SEQ_NUM), Some(4));
+        // An execution report from broker C, answered through the hub.
+        let mut exec = inbound("8", 2);
+        exec.set(tag::ON_BEHALF_OF_COMP_ID, "BROKER_C")
+            .set(tag::DELIVER_TO_COMP_ID, "FUND")
+            .set(tag::DELIVER_TO_SUB_ID, "PM1");
+        session.on_message(&exec);
+        let mut reply = FixMessage::new("FIX.4.4", "F");
+        Routing::from_message(&exec).reply().stamp(&mut reply);
+        let sent = parser::parse(&session.submit(reply).unwrap().unwrap()).unwrap();
+        assert_eq!(sent.get(tag::DELIVER_TO_COMP_ID), Some("BROKER_C"));
+        assert_eq!(sent.get(tag::ON_BEHALF_OF_COMP_ID), Some("FUND"));
+        assert_eq!(sent.get(tag::ON_BEHALF_OF_SUB_ID), Some("PM1"));
+
+        let heartbeat = parser::parse(&session.build_heartbeat()).unwrap();
+        assert!(!heartbeat.contains(tag::ON_BEHALF_OF_COMP_ID));
     }
 }
@@ -0,0 +1,246 @@
+//! Mock Exchange with Scripted Sequence Faults
+//!
+//! 回復ロジック (ギャップ検出、`ResendRequest`、`PossDup` 処理) を
+//! 試験するための模擬取引所 (feature `testing`)。[`MockExchange::send`] は
+//! 通常 `MsgSeqNum` を 1 ずつ振ってフレームを返すが、事前に
+//! [`MockExchange::command`] で積んだ [`SeqCommand`] を次の送信時に順に
+//! 適用し、番号の欠落・重複・順序入れ替え・`PossDupFlag` 付き再送を
+//! 意図的に起こす。
+//!
+//! 返り値はその送信で相手に届けるフレーム列 (0 個以上、配送順)。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+use crate::builder::FixBuilder;
+use crate::clock::{Clock, SystemClock};
+use crate::header::HeaderTemplate;
+use crate::message::FixMessage;
+use crate::parser;
+use crate::tag;
+use crate::value::{format_utc_timestamp_with, TimestampPrecision};
+
+/// 次の送信に適用する異常動作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqCommand {
+    /// `MsgSeqNum` を `n` 個飛ばす (相手側でギャップになる)。
+    Skip(u64),
+    /// 同じフレームを 2 回届ける (`PossDupFlag` なし)。
+    Duplicate,
+    /// フレームを保留し、次の送信フレームの後に届ける。
+    Reorder,
+    /// 送信済みの `seq` を `PossDupFlag=Y` / `OrigSendingTime` 付きで
+    /// 再送してから本来のフレームを届ける。
+    PossDupResend(u64),
+}
+
+/// シーケンス異常を注入できる模擬取引所。
+pub struct MockExchange {
+    header: HeaderTemplate,
+    clock: Arc<dyn Clock>,
+    next_seq: u64,
+    commands: VecDeque<SeqCommand>,
+    /// 送信済みメッセージ (`PossDup` 再送用)。
+    sent: BTreeMap<u64, FixMessage>,
+    /// [`SeqCommand::Reorder`] で保留中のフレーム。
+    held: Option<Vec<u8>>,
+}
+
+impl core::fmt::Debug for MockExchange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MockExchange")
+            .field("header", &self.header)
+            .field("next_seq", &self.next_seq)
+            .field("commands", &self.commands)
+            .field("sent", &self.sent.len())
+            .field("held", &self.held.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MockExchange {
+    /// `header` で送信する取引所を作成 (`MsgSeqNum` は 1 から)。
+    #[must_use]
+    pub fn new(header: HeaderTemplate) -> Self {
+        Self {
+            header,
+            clock: Arc::new(SystemClock),
+            next_seq: 1,
+            commands: VecDeque::new(),
+            sent: BTreeMap::new(),
+            held: None,
+        }
+    }
+
+    /// `SendingTime` の時刻源を設定。
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 次に振る `MsgSeqNum`。
+    #[must_use]
+    pub const fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// 異常動作を積む。次の [`Self::send`] で積んだ順に適用する。
+    pub fn command(&mut self, command: SeqCommand) -> &mut Self {
+        self.commands.push_back(command);
+        self
+    }
+
+    /// 未適用のコマンド数。
+    #[must_use]
+    pub fn pending_commands(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// `msg_type` のメッセージを送信し、相手に届けるフレームを返す。
+    ///
+    /// `body` で本文フィールドを設定する。ヘッダ (`CompID`、`MsgSeqNum`、
+    /// `SendingTime`) は取引所が付ける。
+    pub fn send(&mut self, msg_type: &str, body: impl FnOnce(&mut FixBuilder)) -> Vec<Vec<u8>> {
+        let mut duplicate = false;
+        let mut reorder = false;
+        let mut resends = Vec::new();
+        while let Some(command) = self.commands.pop_front() {
+            match command {
+                SeqCommand::Skip(n) => self.next_seq += n,
+                SeqCommand::Duplicate => duplicate = true,
+                SeqCommand::Reorder => reorder = true,
+                SeqCommand::PossDupResend(seq) => resends.extend(self.poss_dup(seq)),
+            }
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let mut builder = self.header.builder(msg_type);
+        builder
+            .field_u64(tag::MSG_SEQ_NUM, seq)
+            .clock(Arc::clone(&self.clock))
+            .auto_sending_time(true);
+        body(&mut builder);
+        let frame = builder.build();
+        if let Ok(msg) = parser::parse(&frame) {
+            self.sent.insert(seq, msg);
+        }
+
+        let mut out = resends;
+        if reorder {
+            out.extend(self.held.replace(frame));
+            return out;
+        }
+        if duplicate {
+            out.push(frame.clone());
+        }
+        out.push(frame);
+        out.extend(self.held.take());
+        out
+    }
+
+    /// 保留中のフレームを (あれば) 取り出す。
+    pub const fn flush_held(&mut self) -> Option<Vec<u8>> {
+        self.held.take()
+    }
+
+    /// 送信済みの `seq` を `PossDupFlag=Y` で再構築。未送信なら `None`。
+    #[must_use]
+    pub fn poss_dup(&self, seq: u64) -> Option<Vec<u8>> {
+        let mut msg = self.sent.get(&seq)?.clone();
+        let orig = msg.get(tag::SENDING_TIME).unwrap_or_default().to_string();
+        let now = format_utc_timestamp_with(self.clock.now_ns(), TimestampPrecision::default());
+        msg.set(tag::POSS_DUP_FLAG, "Y")
+            .set(tag::ORIG_SENDING_TIME, &orig)
+            .set(tag::SENDING_TIME, &now);
+        Some(msg.to_bytes())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::gap_detect::{SequenceGap, SequenceTracker};
+    use crate::version::BeginString;
+
+    fn exchange() -> MockExchange {
+        MockExchange::new(HeaderTemplate::new(BeginString::Fix44, "EXCH", "ALICE"))
+            .clock(Arc::new(FixedClock(1_767_225_600_000_000_000)))
+    }
+
+    fn seqs(frames: &[Vec<u8>]) -> Vec<u64> {
+        frames
+            .iter()
+            .map(|f| parser::parse(f).unwrap().get_u64(tag::MSG_SEQ_NUM).unwrap())
+            .collect()
+    }
+
+    fn heartbeat(ex: &mut MockExchange) -> Vec<Vec<u8>> {
+        ex.send("0", |_| {})
+    }
+
+    #[test]
+    fn normal_sends_are_sequential() {
+        let mut ex = exchange();
+        let frames: Vec<_> = (0..3).flat_map(|_| heartbeat(&mut ex)).collect();
+        assert_eq!(seqs(&frames), [1, 2, 3]);
+        let msg = parser::parse(&frames[0]).unwrap();
+        assert_eq!(msg.get(tag::SENDER_COMP_ID), Some("EXCH"));
+        assert_eq!(msg.get(tag::SENDING_TIME), Some("20260101-00:00:00.000"));
+    }
+
+    #[test]
+    fn skip_creates_gap_seen_by_tracker() {
+        let mut ex = exchange();
+        let mut tracker = SequenceTracker::new(1);
+        heartbeat(&mut ex);
+        ex.command(SeqCommand::Skip(2));
+        let frames = heartbeat(&mut ex);
+        assert_eq!(seqs(&frames), [4]);
+        assert_eq!(tracker.process(1), None);
+        assert_eq!(tracker.process(4), Some(SequenceGap { begin: 2, end: 3 }));
+    }
+
+    #[test]
+    fn duplicate_and_reorder() {
+        let mut ex = exchange();
+        ex.command(SeqCommand::Duplicate);
+        assert_eq!(seqs(&heartbeat(&mut ex)), [1, 1]);
+
+        ex.command(SeqCommand::Reorder);
+        assert!(heartbeat(&mut ex).is_empty());
+        assert_eq!(seqs(&heartbeat(&mut ex)), [3, 2]);
+
+        ex.command(SeqCommand::Reorder);
+        assert!(heartbeat(&mut ex).is_empty());
+        assert_eq!(seqs(&[ex.flush_held().unwrap()]), [4]);
+    }
+
+    #[test]
+    fn poss_dup_resend_precedes_next_frame() {
+        let mut ex = exchange();
+        ex.send("8", |b| {
+            b.field(tag::CL_ORD_ID, "A1");
+        });
+        ex.command(SeqCommand::PossDupResend(1))
+            .command(SeqCommand::PossDupResend(9));
+        assert_eq!(ex.pending_commands(), 2);
+        let frames = heartbeat(&mut ex);
+        assert_eq!(seqs(&frames), [1, 2]);
+        let resent = parser::parse(&frames[0]).unwrap();
+        assert_eq!(resent.msg_type, "8");
+        assert_eq!(resent.get(tag::POSS_DUP_FLAG), Some("Y"));
+        assert_eq!(resent.get(tag::CL_ORD_ID), Some("A1"));
+        assert_eq!(
+            resent.get(tag::ORIG_SENDING_TIME),
+            resent.get(tag::SENDING_TIME)
+        );
+        assert_eq!(ex.pending_commands(), 0);
+    }
+}
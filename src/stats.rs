@@ -0,0 +1,289 @@
+//! Periodic Stats Reporter
+//!
+//! セッション・パーサのメトリクスを一定間隔で JSON に書き出す。
+//! Prometheus を動かさない小規模構成向けの簡易な可観測性。
+//!
+//! [`StatsReporter::poll`] を任意のループ (タイマースレッド、イベント
+//! ループのティック) から呼ぶと、間隔が経過したときだけスナップショットを
+//! 作成して [`StatsSink`] (ファイルまたはコールバック) に渡す。
+//! ファイルは一時ファイル経由で置き換えるため、読み手が書きかけの JSON を
+//! 見ることはない。時刻は呼び出し側が供給するナノ秒 (`now_ns`)。
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::PathBuf;
+
+use crate::drain::DrainMetrics;
+use crate::parser::ParseError;
+use crate::session::{FixSession, SessionState};
+
+/// パーサの累計メトリクス。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParserStats {
+    /// パースに成功したフレーム数。
+    pub parsed: u64,
+    /// パースに失敗したフレーム数。
+    pub errors: u64,
+    /// 処理したバイト数 (成功・失敗とも)。
+    pub bytes: u64,
+}
+
+impl ParserStats {
+    /// 1 フレームのパース結果を記録。
+    pub const fn record<T>(&mut self, frame_len: usize, result: &Result<T, ParseError>) {
+        self.bytes += frame_len as u64;
+        if result.is_ok() {
+            self.parsed += 1;
+        } else {
+            self.errors += 1;
+        }
+    }
+}
+
+/// 1 セッションのメトリクス。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionStats {
+    /// `SessionId` の表示形式 (`FIX.4.4:ALICE->BROKER`)。
+    pub session_id: String,
+    /// セッション状態。
+    pub state: SessionState,
+    /// 次の送信 seq。
+    pub outgoing_seq: u64,
+    /// 次の受信期待 seq。
+    pub incoming_seq: u64,
+    /// 送信キューのメトリクス。
+    pub drain: DrainMetrics,
+}
+
+impl SessionStats {
+    /// `session` の現在値を取得。
+    #[must_use]
+    pub fn of(session: &FixSession, now_ns: u64) -> Self {
+        let snap = session.snapshot();
+        Self {
+            session_id: session.session_id().to_string(),
+            state: snap.state,
+            outgoing_seq: snap.outgoing_seq,
+            incoming_seq: snap.incoming_seq,
+            drain: session.drain().metrics(now_ns),
+        }
+    }
+}
+
+/// ある時点のメトリクス全体。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatsSnapshot {
+    /// 取得時刻 (ns)。
+    pub taken_ns: u64,
+    /// セッションごとのメトリクス。
+    pub sessions: Vec<SessionStats>,
+    /// パーサのメトリクス。
+    pub parser: ParserStats,
+}
+
+impl StatsSnapshot {
+    /// JSON 文字列に変換 (1 行、キー順固定)。
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(out, "{{\"taken_ns\":{},\"sessions\":[", self.taken_ns);
+        for (i, s) in self.sessions.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"session_id\":");
+            push_json_str(&mut out, &s.session_id);
+            let d = &s.drain;
+            let _ = write!(
+                out,
+                ",\"state\":\"{:?}\",\"outgoing_seq\":{},\"incoming_seq\":{},\
+                 \"drain\":{{\"depth\":{},\"queued_bytes\":{},\"oldest_age_ns\":{},\
+                 \"last_flush_ns\":{},\"max_flush_ns\":{},\"flushed_total\":{}}}}}",
+                s.state,
+                s.outgoing_seq,
+                s.incoming_seq,
+                d.depth,
+                d.queued_bytes,
+                d.oldest_age_ns,
+                d.last_flush_ns,
+                d.max_flush_ns,
+                d.flushed_total
+            );
+        }
+        let p = &self.parser;
+        let _ = write!(
+            out,
+            "],\"parser\":{{\"parsed\":{},\"errors\":{},\"bytes\":{}}}}}",
+            p.parsed, p.errors, p.bytes
+        );
+        out
+    }
+}
+
+/// JSON 文字列リテラルとして追記。
+fn push_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// スナップショットの出力先。
+pub enum StatsSink {
+    /// JSON をファイルに書き出す (一時ファイル → rename)。
+    File(PathBuf),
+    /// スナップショットをコールバックに渡す。
+    Callback(Box<dyn FnMut(&StatsSnapshot) + Send>),
+}
+
+impl core::fmt::Debug for StatsSink {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+/// 一定間隔でスナップショットを出力するレポーター。
+#[derive(Debug)]
+pub struct StatsReporter {
+    interval_ns: u64,
+    next_due_ns: u64,
+    sink: StatsSink,
+}
+
+impl StatsReporter {
+    /// `interval_ns` ごとに `sink` へ出力するレポーターを作成。
+    /// 最初の [`Self::poll`] で即座に出力する。
+    #[must_use]
+    pub const fn new(interval_ns: u64, sink: StatsSink) -> Self {
+        Self {
+            interval_ns,
+            next_due_ns: 0,
+            sink,
+        }
+    }
+
+    /// `now_ns` に出力すべきか。
+    #[must_use]
+    pub const fn is_due(&self, now_ns: u64) -> bool {
+        now_ns >= self.next_due_ns
+    }
+
+    /// 間隔が経過していれば `snapshot` でスナップショットを作成して出力する。
+    ///
+    /// 出力した場合は `true`。次回は `now_ns + interval_ns` 以降。
+    ///
+    /// # Errors
+    ///
+    /// ファイルへの書き込みに失敗した場合 (次回の予定は更新済み)。
+    pub fn poll(
+        &mut self,
+        now_ns: u64,
+        snapshot: impl FnOnce() -> StatsSnapshot,
+    ) -> io::Result<bool> {
+        if !self.is_due(now_ns) {
+            return Ok(false);
+        }
+        self.next_due_ns = now_ns.saturating_add(self.interval_ns);
+        let snap = snapshot();
+        match &mut self.sink {
+            StatsSink::File(path) => {
+                let tmp = path.with_extension("tmp");
+                std::fs::write(&tmp, snap.to_json())?;
+                std::fs::rename(&tmp, path)?;
+            }
+            StatsSink::Callback(f) => f(&snap),
+        }
+        Ok(true)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::version::BeginString;
+    use std::sync::{Arc, Mutex};
+
+    fn snapshot(now_ns: u64) -> StatsSnapshot {
+        let mut session = FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+        let hb = session.build_heartbeat();
+        session.drain_mut().on_enqueue(now_ns, hb.len());
+        let mut parser_stats = ParserStats::default();
+        parser_stats.record(hb.len(), &parser::parse(&hb));
+        parser_stats.record(3, &parser::parse(b"bad"));
+        StatsSnapshot {
+            taken_ns: now_ns,
+            sessions: vec![SessionStats::of(&session, now_ns)],
+            parser: parser_stats,
+        }
+    }
+
+    #[test]
+    fn json_layout() {
+        let json = snapshot(5).to_json();
+        assert!(json.starts_with(
+            "{\"taken_ns\":5,\"sessions\":[{\"session_id\":\"FIX.4.4:ALICE->BROKER\""
+        ));
+        assert!(json.contains("\"state\":\"Disconnected\",\"outgoing_seq\":2,\"incoming_seq\":1"));
+        assert!(json.contains("\"drain\":{\"depth\":1,"));
+        assert!(json.contains("\"parser\":{\"parsed\":1,\"errors\":1,\"bytes\":"));
+        assert!(json.ends_with("}}"));
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["parser"]["errors"], 1);
+        assert_eq!(parsed["sessions"][0]["drain"]["depth"], 1);
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        let mut out = String::new();
+        push_json_str(&mut out, "a\"b\\c\u{1}");
+        assert_eq!(out, "\"a\\\"b\\\\c\\u0001\"");
+    }
+
+    #[test]
+    fn callback_fires_once_per_interval() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = Arc::clone(&seen);
+        let mut reporter = StatsReporter::new(
+            100,
+            StatsSink::Callback(Box::new(move |s| {
+                sink_seen.lock().unwrap().push(s.taken_ns);
+            })),
+        );
+        for now in [0, 50, 99, 100, 150, 250] {
+            reporter.poll(now, || snapshot(now)).unwrap();
+        }
+        assert_eq!(*seen.lock().unwrap(), [0, 100, 250]);
+    }
+
+    #[test]
+    fn file_sink_replaces_json() {
+        let path =
+            std::env::temp_dir().join(format!("alice-fix-stats-{}.json", std::process::id()));
+        let mut reporter = StatsReporter::new(10, StatsSink::File(path.clone()));
+        assert!(reporter.poll(0, || snapshot(0)).unwrap());
+        assert!(!reporter.poll(5, || unreachable!()).unwrap());
+        assert!(reporter.poll(10, || snapshot(10)).unwrap());
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.starts_with("{\"taken_ns\":10,"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,999 @@
+//! Outbound Message Store
+//!
+//! 送信済みメッセージを `MsgSeqNum` 単位で保存し、`ResendRequest` に
+//! 応答できるようにする。[`MemoryStore`] と追記専用の [`FileStore`] を提供する。
+//!
+//! 保存時の圧縮は [`Compressor`] で差し替え可能。既定は無圧縮
+//! ([`NoCompression`])。feature `lz4` / `zstd` で [`Lz4`] / [`Zstd`] が
+//! 使える。FIX テキストは圧縮率が高く、長期保存の容量を大きく削減できる。
+//!
+//! 保存期間は [`RetentionPolicy`] (経過時間・件数・seq 下限) で設定し、
+//! [`MessageStore::prune`] で削除する。シーケンスリセット後は
+//! [`MessageStore::reset_sequence`] で旧シーケンス空間を破棄する。
+//! 時刻は呼び出し側が供給するナノ秒 (`now_ns`)。
+//!
+//! [`FileStore`] は [`SessionId`] をキーにディレクトリ内へ配置できる
+//! ([`FileStore::for_session`])。`CompID` 変更時は新 ID で開けば空の
+//! ストアから始まり、旧 ID のストアはそのまま残る。
+//!
+//! 次に使う送受信シーケンス番号は [`SeqNumStore`] ([`MemorySeqNumStore`] /
+//! [`FileSeqNumStore`]) に保存し、再起動時に
+//! [`crate::session::FixSession::restore`] で復元する。
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::session::SessionId;
+
+/// 保存ペイロードの圧縮方式。
+pub trait Compressor: core::fmt::Debug + Send + Sync {
+    /// 方式 ID。[`FileStore`] のレコードに記録し、読み出し時に照合する。
+    fn id(&self) -> u8;
+
+    /// 圧縮。
+    ///
+    /// # Errors
+    ///
+    /// 圧縮できなかった場合。保存 ([`MessageStore::put`]) はこのエラーで失敗する。
+    fn compress(&self, raw: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// 伸張。
+    ///
+    /// # Errors
+    ///
+    /// ペイロードが壊れている場合 ([`io::ErrorKind::InvalidData`])。
+    fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// 無圧縮 (ID 0)。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCompression;
+
+impl Compressor for NoCompression {
+    fn id(&self) -> u8 {
+        0
+    }
+
+    fn compress(&self, raw: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(raw.to_vec())
+    }
+
+    fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(payload.to_vec())
+    }
+}
+
+/// LZ4 ブロック圧縮 (ID 1, feature `lz4`)。
+#[cfg(feature = "lz4")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lz4;
+
+#[cfg(feature = "lz4")]
+impl Compressor for Lz4 {
+    fn id(&self) -> u8 {
+        1
+    }
+
+    fn compress(&self, raw: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(lz4_flex::compress_prepend_size(raw))
+    }
+
+    fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        lz4_flex::decompress_size_prepended(payload)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+/// zstd 圧縮 (ID 2, feature `zstd`)。
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Copy)]
+pub struct Zstd {
+    /// 圧縮レベル (1–22)。
+    pub level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl Default for Zstd {
+    fn default() -> Self {
+        Self { level: 3 }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Compressor for Zstd {
+    fn id(&self) -> u8 {
+        2
+    }
+
+    fn compress(&self, raw: &[u8]) -> io::Result<Vec<u8>> {
+        // 範囲外のレベルなどは空ペイロードにせずエラーにする
+        zstd::bulk::compress(raw, self.level)
+    }
+
+    fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        zstd::stream::decode_all(payload)
+    }
+}
+
+/// 保存期間の方針。未設定の項目は判定しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// 保存時刻からの最大経過時間 (ns)。
+    pub max_age_ns: Option<u64>,
+    /// 最大保存件数 (超過分は古い seq から削除)。
+    pub max_count: Option<usize>,
+    /// seq の下限 (これ未満を削除)。
+    pub min_seq: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// 無期限の方針を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_age_ns: None,
+            max_count: None,
+            min_seq: None,
+        }
+    }
+
+    /// 最大経過時間を設定。
+    #[must_use]
+    pub const fn max_age_ns(mut self, max: u64) -> Self {
+        self.max_age_ns = Some(max);
+        self
+    }
+
+    /// 最大保存件数を設定。
+    #[must_use]
+    pub const fn max_count(mut self, max: usize) -> Self {
+        self.max_count = Some(max);
+        self
+    }
+
+    /// seq の下限を設定。
+    #[must_use]
+    pub const fn min_seq(mut self, seq: u64) -> Self {
+        self.min_seq = Some(seq);
+        self
+    }
+
+    /// `entries` (seq 昇順の `(seq, 保存時刻)`) のうち削除対象の seq。
+    #[must_use]
+    pub fn expired(&self, entries: &[(u64, u64)], now_ns: u64) -> Vec<u64> {
+        let (mut keep, mut drop): (Vec<_>, Vec<_>) = entries.iter().partition(|&&(seq, stored)| {
+            self.max_age_ns
+                .is_none_or(|max| now_ns.saturating_sub(stored) <= max)
+                && self.min_seq.is_none_or(|min| seq >= min)
+        });
+        if let Some(max) = self.max_count {
+            let excess = keep.len().saturating_sub(max);
+            drop.extend(keep.drain(..excess));
+        }
+        let mut seqs: Vec<u64> = drop.into_iter().map(|&(seq, _)| seq).collect();
+        seqs.sort_unstable();
+        seqs
+    }
+}
+
+/// 送信済みメッセージのストア。
+pub trait MessageStore {
+    /// `seq` のメッセージを保存時刻 `stored_ns` とともに保存
+    /// (既存は上書き)。
+    ///
+    /// # Errors
+    ///
+    /// 書き込みに失敗した場合。
+    fn put(&mut self, seq: u64, stored_ns: u64, raw: &[u8]) -> io::Result<()>;
+
+    /// `seq` のメッセージを取得。
+    ///
+    /// # Errors
+    ///
+    /// 読み出し・伸張に失敗した場合。
+    fn get(&mut self, seq: u64) -> io::Result<Option<Vec<u8>>>;
+
+    /// 保存済みの最大 `seq` (空なら 0)。
+    fn last_seq(&self) -> u64;
+
+    /// `begin..=end` の保存済みメッセージを昇順で取得。
+    ///
+    /// `end` = 0 は `ResendRequest` と同じく「最後まで」。
+    ///
+    /// # Errors
+    ///
+    /// 読み出し・伸張に失敗した場合。
+    fn range(&mut self, begin: u64, end: u64) -> io::Result<Vec<(u64, Vec<u8>)>> {
+        let end = if end == 0 { self.last_seq() } else { end };
+        let mut out = Vec::new();
+        for seq in begin..=end {
+            if let Some(raw) = self.get(seq)? {
+                out.push((seq, raw));
+            }
+        }
+        Ok(out)
+    }
+
+    /// 保存済みの `(seq, 保存時刻)` を seq 昇順で列挙。
+    fn entries(&self) -> Vec<(u64, u64)>;
+
+    /// `seqs` のメッセージを削除。
+    ///
+    /// # Errors
+    ///
+    /// 書き込みに失敗した場合。
+    fn remove(&mut self, seqs: &[u64]) -> io::Result<()>;
+
+    /// `policy` に従って削除し、削除件数を返す。
+    ///
+    /// # Errors
+    ///
+    /// 書き込みに失敗した場合。
+    fn prune(&mut self, policy: &RetentionPolicy, now_ns: u64) -> io::Result<usize> {
+        let expired = policy.expired(&self.entries(), now_ns);
+        if !expired.is_empty() {
+            self.remove(&expired)?;
+        }
+        Ok(expired.len())
+    }
+
+    /// シーケンスリセット後に旧シーケンス空間のメッセージを全て削除し、
+    /// 削除件数を返す (リセット前のメッセージは再送できないため)。
+    ///
+    /// # Errors
+    ///
+    /// 書き込みに失敗した場合。
+    fn reset_sequence(&mut self) -> io::Result<usize> {
+        let seqs: Vec<u64> = self.entries().into_iter().map(|(seq, _)| seq).collect();
+        if !seqs.is_empty() {
+            self.remove(&seqs)?;
+        }
+        Ok(seqs.len())
+    }
+}
+
+/// メモリ上のストア。
+#[derive(Debug)]
+pub struct MemoryStore {
+    compressor: Box<dyn Compressor>,
+    /// seq → (保存時刻, ペイロード)。
+    entries: BTreeMap<u64, (u64, Vec<u8>)>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    /// 無圧縮のストアを作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_compressor(Box::new(NoCompression))
+    }
+
+    /// 圧縮方式を指定して作成。
+    #[must_use]
+    pub fn with_compressor(compressor: Box<dyn Compressor>) -> Self {
+        Self {
+            compressor,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// 保存件数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 空か。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 保存ペイロード (圧縮後) の合計バイト数。
+    #[must_use]
+    pub fn stored_bytes(&self) -> usize {
+        self.entries.values().map(|(_, p)| p.len()).sum()
+    }
+}
+
+impl MessageStore for MemoryStore {
+    fn put(&mut self, seq: u64, stored_ns: u64, raw: &[u8]) -> io::Result<()> {
+        let payload = self.compressor.compress(raw)?;
+        self.entries.insert(seq, (stored_ns, payload));
+        Ok(())
+    }
+
+    fn get(&mut self, seq: u64) -> io::Result<Option<Vec<u8>>> {
+        self.entries
+            .get(&seq)
+            .map(|(_, p)| self.compressor.decompress(p))
+            .transpose()
+    }
+
+    fn last_seq(&self) -> u64 {
+        self.entries.keys().next_back().copied().unwrap_or(0)
+    }
+
+    fn entries(&self) -> Vec<(u64, u64)> {
+        self.entries
+            .iter()
+            .map(|(&seq, &(t, _))| (seq, t))
+            .collect()
+    }
+
+    fn remove(&mut self, seqs: &[u64]) -> io::Result<()> {
+        for seq in seqs {
+            self.entries.remove(seq);
+        }
+        Ok(())
+    }
+}
+
+/// レコードヘッダ長: seq (u64 LE) + 保存時刻 (u64 LE) + 方式 ID (u8)
+/// + ペイロード長 (u32 LE)。
+const RECORD_HEADER: usize = 21;
+
+/// [`FileStore`] の索引エントリ。
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    offset: u64,
+    len: u32,
+    stored_ns: u64,
+}
+
+/// 追記専用ファイルのストア。
+///
+/// レコードは `seq | 保存時刻 | 方式 ID | 長さ | ペイロード` の順に追記し、
+/// 開く際に走査して索引を再構築する。同じ `seq` は後のレコードが優先。
+/// 削除 ([`MessageStore::remove`]) は残りのレコードでファイルを書き直す。
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    file: File,
+    compressor: Box<dyn Compressor>,
+    index: BTreeMap<u64, Slot>,
+    end: u64,
+}
+
+impl FileStore {
+    /// 無圧縮でファイルを開く (無ければ作成)。
+    ///
+    /// # Errors
+    ///
+    /// ファイルを開けない場合、既存レコードが壊れている場合。
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_with(path, Box::new(NoCompression))
+    }
+
+    /// 圧縮方式を指定してファイルを開く (無ければ作成)。
+    ///
+    /// 書き込み途中で止まった末尾の不完全なレコードは切り詰めて捨てる
+    /// (そのメッセージは保存されなかったものとして扱う)。
+    ///
+    /// # Errors
+    ///
+    /// ファイルを開けない場合、既存レコードが別の方式で書かれている場合
+    /// ([`io::ErrorKind::InvalidData`])。
+    pub fn open_with(path: impl AsRef<Path>, compressor: Box<dyn Compressor>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let mut index = BTreeMap::new();
+        let mut pos = 0usize;
+        while pos < data.len() {
+            let Some(header) = data.get(pos..pos + RECORD_HEADER) else {
+                break;
+            };
+            let seq = u64::from_le_bytes(header[..8].try_into().unwrap_or_default());
+            let stored_ns = u64::from_le_bytes(header[8..16].try_into().unwrap_or_default());
+            if header[16] != compressor.id() {
+                return Err(invalid("record written with a different compressor"));
+            }
+            let len = u32::from_le_bytes(header[17..].try_into().unwrap_or_default());
+            let start = pos + RECORD_HEADER;
+            if start + len as usize > data.len() {
+                break;
+            }
+            index.insert(
+                seq,
+                Slot {
+                    offset: start as u64,
+                    len,
+                    stored_ns,
+                },
+            );
+            pos = start + len as usize;
+        }
+        if pos < data.len() {
+            file.set_len(pos as u64)?;
+        }
+        Ok(Self {
+            path,
+            file,
+            compressor,
+            index,
+            end: pos as u64,
+        })
+    }
+
+    /// `dir` 内の `session` 用ストアのパス (`<file_stem>.store`)。
+    #[must_use]
+    pub fn session_path(dir: impl AsRef<Path>, session: &SessionId) -> PathBuf {
+        dir.as_ref().join(format!("{}.store", session.file_stem()))
+    }
+
+    /// `dir` 内の `session` 用ストアを開く (無ければ作成)。
+    ///
+    /// # Errors
+    ///
+    /// [`FileStore::open_with`] と同じ。
+    pub fn for_session(
+        dir: impl AsRef<Path>,
+        session: &SessionId,
+        compressor: Box<dyn Compressor>,
+    ) -> io::Result<Self> {
+        Self::open_with(Self::session_path(dir, session), compressor)
+    }
+
+    /// ファイルパス。
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 保存件数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// 空か。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// ファイルサイズ (バイト)。
+    #[must_use]
+    pub const fn file_bytes(&self) -> u64 {
+        self.end
+    }
+
+    fn read_payload(&mut self, slot: Slot) -> io::Result<Vec<u8>> {
+        let mut payload = vec![0; slot.len as usize];
+        self.file.seek(SeekFrom::Start(slot.offset))?;
+        self.file.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+
+    fn record(&self, seq: u64, stored_ns: u64, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let len = u32::try_from(payload.len()).map_err(|_| invalid("message too large"))?;
+        let mut record = Vec::with_capacity(RECORD_HEADER + payload.len());
+        record.extend_from_slice(&seq.to_le_bytes());
+        record.extend_from_slice(&stored_ns.to_le_bytes());
+        record.push(self.compressor.id());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(payload);
+        Ok(record)
+    }
+
+    /// 索引にあるレコードだけでファイルを書き直す (一時ファイル → rename)。
+    fn rewrite(&mut self) -> io::Result<()> {
+        let mut data = Vec::new();
+        let mut index = BTreeMap::new();
+        let slots: Vec<(u64, Slot)> = self.index.iter().map(|(&s, &slot)| (s, slot)).collect();
+        for (seq, slot) in slots {
+            let payload = self.read_payload(slot)?;
+            let record = self.record(seq, slot.stored_ns, &payload)?;
+            index.insert(
+                seq,
+                Slot {
+                    offset: (data.len() + RECORD_HEADER) as u64,
+                    ..slot
+                },
+            );
+            data.extend_from_slice(&record);
+        }
+        let tmp = self.path.with_extension("compact");
+        std::fs::write(&tmp, &data)?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        self.index = index;
+        self.end = data.len() as u64;
+        Ok(())
+    }
+}
+
+impl MessageStore for FileStore {
+    fn put(&mut self, seq: u64, stored_ns: u64, raw: &[u8]) -> io::Result<()> {
+        let payload = self.compressor.compress(raw)?;
+        let record = self.record(seq, stored_ns, &payload)?;
+        self.file.write_all(&record)?;
+        self.index.insert(
+            seq,
+            Slot {
+                offset: self.end + RECORD_HEADER as u64,
+                len: (record.len() - RECORD_HEADER) as u32,
+                stored_ns,
+            },
+        );
+        self.end += record.len() as u64;
+        Ok(())
+    }
+
+    fn get(&mut self, seq: u64) -> io::Result<Option<Vec<u8>>> {
+        let Some(&slot) = self.index.get(&seq) else {
+            return Ok(None);
+        };
+        let payload = self.read_payload(slot)?;
+        self.compressor.decompress(&payload).map(Some)
+    }
+
+    fn last_seq(&self) -> u64 {
+        self.index.keys().next_back().copied().unwrap_or(0)
+    }
+
+    fn entries(&self) -> Vec<(u64, u64)> {
+        self.index
+            .iter()
+            .map(|(&seq, slot)| (seq, slot.stored_ns))
+            .collect()
+    }
+
+    fn remove(&mut self, seqs: &[u64]) -> io::Result<()> {
+        let before = self.index.len();
+        for seq in seqs {
+            self.index.remove(seq);
+        }
+        if self.index.len() == before {
+            return Ok(());
+        }
+        self.rewrite()
+    }
+}
+
+/// 次に使う送受信シーケンス番号。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeqNums {
+    /// 次の送信 `MsgSeqNum`。
+    pub outgoing: u64,
+    /// 次に期待する受信 `MsgSeqNum`。
+    pub incoming: u64,
+}
+
+/// シーケンス番号の保存先。再起動後に
+/// [`crate::session::FixSession::restore`] で復元する。
+pub trait SeqNumStore {
+    /// 現在値を保存 (既存は上書き)。
+    ///
+    /// # Errors
+    ///
+    /// 書き込みに失敗した場合。
+    fn save(&mut self, seqs: SeqNums) -> io::Result<()>;
+
+    /// 保存済みの値。未保存なら `None`。
+    ///
+    /// # Errors
+    ///
+    /// 読み出しに失敗した場合、内容が壊れている場合
+    /// ([`io::ErrorKind::InvalidData`])。
+    fn load(&mut self) -> io::Result<Option<SeqNums>>;
+}
+
+/// メモリ上のシーケンス番号ストア。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemorySeqNumStore {
+    seqs: Option<SeqNums>,
+}
+
+impl SeqNumStore for MemorySeqNumStore {
+    fn save(&mut self, seqs: SeqNums) -> io::Result<()> {
+        self.seqs = Some(seqs);
+        Ok(())
+    }
+
+    fn load(&mut self) -> io::Result<Option<SeqNums>> {
+        Ok(self.seqs)
+    }
+}
+
+/// ファイルのシーケンス番号ストア。
+///
+/// `<送信> <受信>\n` のテキスト 1 行を一時ファイル → rename で置き換える
+/// ため、書き込み途中で落ちても直前の値が残る。
+#[derive(Debug, Clone)]
+pub struct FileSeqNumStore {
+    path: PathBuf,
+}
+
+impl FileSeqNumStore {
+    /// `path` のストア (ファイルは最初の保存で作成)。
+    #[must_use]
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// `dir` 内の `session` 用ストア (`<file_stem>.seqnums`)。
+    #[must_use]
+    pub fn for_session(dir: impl AsRef<Path>, session: &SessionId) -> Self {
+        Self::open(
+            dir.as_ref()
+                .join(format!("{}.seqnums", session.file_stem())),
+        )
+    }
+
+    /// ファイルパス。
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl SeqNumStore for FileSeqNumStore {
+    fn save(&mut self, seqs: SeqNums) -> io::Result<()> {
+        let tmp = self.path.with_extension("seqnums.tmp");
+        std::fs::write(&tmp, format!("{} {}\n", seqs.outgoing, seqs.incoming))?;
+        std::fs::rename(&tmp, &self.path)
+    }
+
+    fn load(&mut self) -> io::Result<Option<SeqNums>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut parts = text.split_whitespace().map(str::parse::<u64>);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(outgoing)), Some(Ok(incoming)), None) if outgoing > 0 && incoming > 0 => {
+                Ok(Some(SeqNums { outgoing, incoming }))
+            }
+            _ => Err(invalid("malformed sequence number file")),
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::tag;
+    use crate::version::BeginString;
+
+    fn order(seq: u64) -> Vec<u8> {
+        FixBuilder::new(BeginString::Fix44, "D")
+            .field(tag::SENDER_COMP_ID, "ALICE")
+            .field(tag::TARGET_COMP_ID, "BROKER")
+            .field_u64(tag::MSG_SEQ_NUM, seq)
+            .field(tag::SYMBOL, "BTCUSD")
+            .field(tag::TEXT, &" ".repeat(512))
+            .build()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("alice-fix-store-{}-{name}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// テスト用のランレングス圧縮 (ID 200)。
+    #[derive(Debug)]
+    struct Rle;
+
+    impl Compressor for Rle {
+        fn id(&self) -> u8 {
+            200
+        }
+
+        fn compress(&self, raw: &[u8]) -> io::Result<Vec<u8>> {
+            let mut out = Vec::new();
+            for chunk in raw.chunk_by(|a, b| a == b) {
+                for part in chunk.chunks(255) {
+                    out.push(part.len() as u8);
+                    out.push(part[0]);
+                }
+            }
+            Ok(out)
+        }
+
+        fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+            if !payload.len().is_multiple_of(2) {
+                return Err(invalid("odd RLE payload"));
+            }
+            Ok(payload
+                .chunks(2)
+                .flat_map(|p| std::iter::repeat_n(p[1], p[0] as usize))
+                .collect())
+        }
+    }
+
+    /// テスト用の常に失敗する圧縮 (ID 201)。
+    #[derive(Debug)]
+    struct Refuse;
+
+    impl Compressor for Refuse {
+        fn id(&self) -> u8 {
+            201
+        }
+
+        fn compress(&self, _raw: &[u8]) -> io::Result<Vec<u8>> {
+            Err(io::Error::other("compression failed"))
+        }
+
+        fn decompress(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+            Ok(payload.to_vec())
+        }
+    }
+
+    #[test]
+    fn memory_store_range() {
+        let mut store = MemoryStore::new();
+        for seq in [1, 2, 4] {
+            store.put(seq, 0, &order(seq)).unwrap();
+        }
+        assert_eq!(store.last_seq(), 4);
+        assert_eq!(store.get(2).unwrap(), Some(order(2)));
+        assert_eq!(store.get(3).unwrap(), None);
+        let seqs: Vec<u64> = store
+            .range(2, 0)
+            .unwrap()
+            .into_iter()
+            .map(|(s, _)| s)
+            .collect();
+        assert_eq!(seqs, [2, 4]);
+    }
+
+    #[test]
+    fn pluggable_compressor_shrinks_payload() {
+        let mut plain = MemoryStore::new();
+        let mut packed = MemoryStore::with_compressor(Box::new(Rle));
+        plain.put(1, 0, &order(1)).unwrap();
+        packed.put(1, 0, &order(1)).unwrap();
+        assert!(packed.stored_bytes() < plain.stored_bytes());
+        assert_eq!(packed.get(1).unwrap(), Some(order(1)));
+    }
+
+    #[test]
+    fn compression_failure_fails_put() {
+        let mut memory = MemoryStore::with_compressor(Box::new(Refuse));
+        assert!(memory.put(1, 0, &order(1)).is_err());
+        assert_eq!(memory.get(1).unwrap(), None);
+
+        let path = temp_path("refuse");
+        let mut file = FileStore::open_with(&path, Box::new(Refuse)).unwrap();
+        assert!(file.put(1, 0, &order(1)).is_err());
+        assert!(file.is_empty());
+        assert_eq!(file.file_bytes(), 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_store_reopens_and_overwrites() {
+        let path = temp_path("reopen");
+        {
+            let mut store = FileStore::open_with(&path, Box::new(Rle)).unwrap();
+            store.put(1, 0, &order(1)).unwrap();
+            store.put(2, 0, b"stale").unwrap();
+            store.put(2, 0, &order(2)).unwrap();
+            assert_eq!(store.get(2).unwrap(), Some(order(2)));
+        }
+        let mut store = FileStore::open_with(&path, Box::new(Rle)).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.last_seq(), 2);
+        assert_eq!(store.get(1).unwrap(), Some(order(1)));
+        assert_eq!(store.get(2).unwrap(), Some(order(2)));
+        store.put(3, 0, &order(3)).unwrap();
+        assert_eq!(store.range(1, 3).unwrap().len(), 3);
+
+        // 別の方式で書かれたファイルは開けない
+        let err = FileStore::open(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_store_drops_torn_last_record() {
+        let path = temp_path("truncated");
+        let intact = {
+            let mut store = FileStore::open(&path).unwrap();
+            store.put(1, 0, &order(1)).unwrap();
+            let intact = store.file_bytes();
+            store.put(2, 0, &order(2)).unwrap();
+            intact
+        };
+        // 2 件目の書き込み途中 (ペイロード・ヘッダの途中) で止まった場合
+        for torn in [intact + RECORD_HEADER as u64 + 3, intact + 5] {
+            OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_len(torn)
+                .unwrap();
+            let mut store = FileStore::open(&path).unwrap();
+            assert_eq!(store.len(), 1);
+            assert_eq!(store.get(1).unwrap(), Some(order(1)));
+            assert_eq!(std::fs::metadata(&path).unwrap().len(), intact);
+            store.put(2, 0, &order(2)).unwrap();
+            drop(store);
+            let mut store = FileStore::open(&path).unwrap();
+            assert_eq!(store.get(2).unwrap(), Some(order(2)));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn retention_policy_selection() {
+        let entries: Vec<(u64, u64)> = (1..=6).map(|seq| (seq, seq * 10)).collect();
+        assert!(RetentionPolicy::new().expired(&entries, 1_000).is_empty());
+        // 保存時刻 30 以前 (経過 > 30) を削除
+        assert_eq!(
+            RetentionPolicy::new().max_age_ns(30).expired(&entries, 60),
+            [1, 2]
+        );
+        assert_eq!(
+            RetentionPolicy::new().min_seq(5).expired(&entries, 0),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            RetentionPolicy::new().max_count(2).expired(&entries, 0),
+            [1, 2, 3, 4]
+        );
+        // 条件の組み合わせ: seq >= 2 を残した上で件数を 3 に制限
+        assert_eq!(
+            RetentionPolicy::new()
+                .min_seq(2)
+                .max_count(3)
+                .expired(&entries, 0),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn memory_store_prune_and_reset() {
+        let mut store = MemoryStore::new();
+        for seq in 1..=5 {
+            store.put(seq, seq * 100, &order(seq)).unwrap();
+        }
+        let policy = RetentionPolicy::new().max_age_ns(250);
+        assert_eq!(store.prune(&policy, 500).unwrap(), 2);
+        assert_eq!(store.entries(), [(3, 300), (4, 400), (5, 500)]);
+        assert_eq!(store.prune(&policy, 500).unwrap(), 0);
+        assert_eq!(store.reset_sequence().unwrap(), 3);
+        assert!(store.is_empty());
+        assert_eq!(store.last_seq(), 0);
+    }
+
+    #[test]
+    fn file_store_prune_reclaims_space() {
+        let path = temp_path("prune");
+        let mut store = FileStore::open(&path).unwrap();
+        for seq in 1..=10 {
+            store.put(seq, seq, &order(seq)).unwrap();
+        }
+        let full = store.file_bytes();
+        assert_eq!(
+            store
+                .prune(&RetentionPolicy::new().max_count(3), 10)
+                .unwrap(),
+            7
+        );
+        assert!(store.file_bytes() < full / 3 + 1);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), store.file_bytes());
+        assert_eq!(store.get(10).unwrap(), Some(order(10)));
+        assert_eq!(store.get(7).unwrap(), None);
+        store.put(11, 11, &order(11)).unwrap();
+        drop(store);
+
+        let mut store = FileStore::open(&path).unwrap();
+        assert_eq!(store.entries(), [(8, 8), (9, 9), (10, 10), (11, 11)]);
+        assert_eq!(store.get(11).unwrap(), Some(order(11)));
+        assert_eq!(store.reset_sequence().unwrap(), 4);
+        assert_eq!(store.file_bytes(), 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rotated_identity_gets_its_own_store() {
+        let dir = std::env::temp_dir().join(format!("alice-fix-rotate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = SessionId::new("FIX.4.4", "ALICE", "BROKER");
+        let new = SessionId::new("FIX.4.4", "ALICE2", "BROKER");
+
+        let mut store = FileStore::for_session(&dir, &old, Box::new(NoCompression)).unwrap();
+        store.put(1, 1, &order(1)).unwrap();
+        drop(store);
+        FileSeqNumStore::for_session(&dir, &old)
+            .save(SeqNums {
+                outgoing: 2,
+                incoming: 5,
+            })
+            .unwrap();
+
+        // 新しい識別子は空のストアと未保存のシーケンス番号から始まる
+        let mut fresh = FileStore::for_session(&dir, &new, Box::new(NoCompression)).unwrap();
+        assert!(fresh.is_empty());
+        assert_eq!(
+            FileSeqNumStore::for_session(&dir, &new).load().unwrap(),
+            None
+        );
+        fresh.put(1, 2, &order(1)).unwrap();
+        drop(fresh);
+
+        // 旧識別子のファイルはそのまま残る
+        let mut archived = FileStore::for_session(&dir, &old, Box::new(NoCompression)).unwrap();
+        assert_eq!(archived.entries(), [(1, 1)]);
+        assert_eq!(archived.get(1).unwrap(), Some(order(1)));
+        assert_eq!(
+            FileSeqNumStore::for_session(&dir, &old).load().unwrap(),
+            Some(SeqNums {
+                outgoing: 2,
+                incoming: 5
+            })
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_roundtrip() {
+        let mut store = MemoryStore::with_compressor(Box::new(Lz4));
+        store.put(1, 0, &order(1)).unwrap();
+        assert_eq!(store.get(1).unwrap(), Some(order(1)));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_roundtrip() {
+        let mut store = MemoryStore::with_compressor(Box::new(Zstd::default()));
+        store.put(1, 0, &order(1)).unwrap();
+        assert_eq!(store.get(1).unwrap(), Some(order(1)));
+    }
+
+    #[test]
+    fn seq_num_store_roundtrip() {
+        let path = temp_path("seqnums");
+        let mut store = FileSeqNumStore::open(&path);
+        assert_eq!(store.load().unwrap(), None);
+        let seqs = SeqNums {
+            outgoing: 42,
+            incoming: 17,
+        };
+        store.save(seqs).unwrap();
+        assert_eq!(FileSeqNumStore::open(&path).load().unwrap(), Some(seqs));
+
+        std::fs::write(&path, "42\n").unwrap();
+        assert_eq!(store.load().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut mem = MemorySeqNumStore::default();
+        mem.save(seqs).unwrap();
+        assert_eq!(mem.load().unwrap(), Some(seqs));
+    }
+}
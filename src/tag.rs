@@ -28,15 +28,115 @@ pub const SENDER_COMP_ID: u32 = 49;
 /// Tag 56 — `TargetCompID`: assigned value identifying the receiving firm.
 pub const TARGET_COMP_ID: u32 = 56;
 
+/// Tag 115 — `OnBehalfOfCompID`: originating firm when the sender acts on its behalf.
+pub const ON_BEHALF_OF_COMP_ID: u32 = 115;
+
+/// Tag 128 — `DeliverToCompID`: final recipient when the message is routed through a third party.
+pub const DELIVER_TO_COMP_ID: u32 = 128;
+
+/// Tag 116 — `OnBehalfOfSubID`: sub-identifier (desk, trader) of the originating firm.
+pub const ON_BEHALF_OF_SUB_ID: u32 = 116;
+
+/// Tag 129 — `DeliverToSubID`: sub-identifier of the final recipient.
+pub const DELIVER_TO_SUB_ID: u32 = 129;
+
 /// Tag 34 — `MsgSeqNum`: integer message sequence number.
 pub const MSG_SEQ_NUM: u32 = 34;
 
+/// Tag 50 — `SenderSubID`: sub-identifier of the sending firm (desk, trader).
+pub const SENDER_SUB_ID: u32 = 50;
+
+/// Tag 57 — `TargetSubID`: sub-identifier of the receiving firm.
+pub const TARGET_SUB_ID: u32 = 57;
+
 /// Tag 52 — `SendingTime`: UTC timestamp when the message was transmitted.
 pub const SENDING_TIME: u32 = 52;
 
+/// Tag 43 — `PossDupFlag`: `Y` when the message may be a retransmission.
+pub const POSS_DUP_FLAG: u32 = 43;
+
+/// Tag 97 — `PossResend`: `Y` when the message may duplicate one sent under another sequence number.
+pub const POSS_RESEND: u32 = 97;
+
+/// Tag 122 — `OrigSendingTime`: original `SendingTime` of a message being retransmitted.
+pub const ORIG_SENDING_TIME: u32 = 122;
+
 /// Tag 10 — `CheckSum`: three-digit modulo-256 checksum of the message bytes.
 pub const CHECKSUM: u32 = 10;
 
+/// Tag 93 — `SignatureLength`: byte length of the following `Signature` (tag 89).
+pub const SIGNATURE_LENGTH: u32 = 93;
+
+/// Tag 89 — `Signature`: electronic signature carried in the standard trailer.
+pub const SIGNATURE: u32 = 89;
+
+/// Tag 95 — `RawDataLength`: byte length of the following `RawData` (tag 96).
+pub const RAW_DATA_LENGTH: u32 = 95;
+
+/// Tag 96 — `RawData`: unformatted binary data (may contain SOH).
+pub const RAW_DATA: u32 = 96;
+
+/// Tag 212 — `XmlDataLen`: byte length of the following `XmlData` (tag 213).
+pub const XML_DATA_LEN: u32 = 212;
+
+/// Tag 213 — `XmlData`: embedded XML document.
+pub const XML_DATA: u32 = 213;
+
+/// Tag 1128 — `ApplVerID`: application version of a FIXT.1.1 message.
+pub const APPL_VER_ID: u32 = 1128;
+
+// ---------------------------------------------------------------------------
+// Session-level (administrative) fields
+// ---------------------------------------------------------------------------
+
+/// Tag 98 — `EncryptMethod`: "0" = None / Other.
+pub const ENCRYPT_METHOD: u32 = 98;
+
+/// Tag 1137 — `DefaultApplVerID`: default application version announced in a FIXT.1.1 Logon.
+pub const DEFAULT_APPL_VER_ID: u32 = 1137;
+
+/// Tag 108 — `HeartBtInt`: heartbeat interval in seconds.
+pub const HEART_BT_INT: u32 = 108;
+
+/// Tag 141 — `ResetSeqNumFlag`: both sides reset sequence numbers to 1 on this Logon.
+pub const RESET_SEQ_NUM_FLAG: u32 = 141;
+
+/// Tag 112 — `TestReqID`: identifier echoed in the Heartbeat answering a `TestRequest`.
+pub const TEST_REQ_ID: u32 = 112;
+
+/// Tag 553 — `Username`: user name sent in Logon.
+pub const USERNAME: u32 = 553;
+
+/// Tag 554 — `Password`: password sent in Logon.
+pub const PASSWORD: u32 = 554;
+
+/// Tag 925 — `NewPassword`: replacement password sent in Logon.
+pub const NEW_PASSWORD: u32 = 925;
+
+/// Tag 7 — `BeginSeqNo`: first sequence number of a `ResendRequest` range.
+pub const BEGIN_SEQ_NO: u32 = 7;
+
+/// Tag 16 — `EndSeqNo`: last sequence number of a `ResendRequest` range (0 = infinity).
+pub const END_SEQ_NO: u32 = 16;
+
+/// Tag 36 — `NewSeqNo`: next sequence number after a `SequenceReset`.
+pub const NEW_SEQ_NO: u32 = 36;
+
+/// Tag 123 — `GapFillFlag`: the `SequenceReset` replaces administrative messages in a gap.
+pub const GAP_FILL_FLAG: u32 = 123;
+
+/// Tag 45 — `RefSeqNum`: sequence number of the message being rejected.
+pub const REF_SEQ_NUM: u32 = 45;
+
+/// Tag 371 — `RefTagID`: tag number referenced by a `Reject`.
+pub const REF_TAG_ID: u32 = 371;
+
+/// Tag 372 — `RefMsgType`: `MsgType` of the message being rejected.
+pub const REF_MSG_TYPE: u32 = 372;
+
+/// Tag 373 — `SessionRejectReason`: code for the reason of a session-level `Reject`.
+pub const SESSION_REJECT_REASON: u32 = 373;
+
 // ---------------------------------------------------------------------------
 // Order identification
 // ---------------------------------------------------------------------------
@@ -44,12 +144,39 @@ pub const CHECKSUM: u32 = 10;
 /// Tag 11 — `ClOrdID`: unique identifier for an order assigned by the client.
 pub const CL_ORD_ID: u32 = 11;
 
+/// Tag 1 — `Account`: account mnemonic the order is booked to.
+pub const ACCOUNT: u32 = 1;
+
 /// Tag 37 — `OrderID`: unique identifier for an order assigned by the broker.
 pub const ORDER_ID: u32 = 37;
 
 /// Tag 17 — `ExecID`: unique identifier for an execution report.
 pub const EXEC_ID: u32 = 17;
 
+/// Tag 41 — `OrigClOrdID`: `ClOrdID` of the order being cancelled or replaced.
+pub const ORIG_CL_ORD_ID: u32 = 41;
+
+/// Tag 434 — `CxlRejResponseTo`: request type an `OrderCancelReject` answers.
+pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+
+/// Tag 102 — `CxlRejReason`: code explaining an `OrderCancelReject`.
+pub const CXL_REJ_REASON: u32 = 102;
+
+/// Tag 790 — `OrdStatusReqID`: identifier of an `OrderStatusRequest`, echoed in the status report.
+pub const ORD_STATUS_REQ_ID: u32 = 790;
+
+/// Tag 584 — `MassStatusReqID`: identifier of an `OrderMassStatusRequest`, echoed in each report.
+pub const MASS_STATUS_REQ_ID: u32 = 584;
+
+/// Tag 585 — `MassStatusReqType`: scope of an `OrderMassStatusRequest` (7 = all orders).
+pub const MASS_STATUS_REQ_TYPE: u32 = 585;
+
+/// Tag 911 — `TotNumReports`: number of reports answering a mass status request.
+pub const TOT_NUM_REPORTS: u32 = 911;
+
+/// Tag 912 — `LastRptRequested`: `Y` on the last report answering a request.
+pub const LAST_RPT_REQUESTED: u32 = 912;
+
 // ---------------------------------------------------------------------------
 // Instrument
 // ---------------------------------------------------------------------------
@@ -57,6 +184,24 @@ pub const EXEC_ID: u32 = 17;
 /// Tag 55 — Symbol: ticker symbol for the traded instrument.
 pub const SYMBOL: u32 = 55;
 
+/// Tag 48 — `SecurityID`: security identifier (e.g., ISIN, CUSIP).
+pub const SECURITY_ID: u32 = 48;
+
+/// Tag 22 — `SecurityIDSource`: identifies the class of `SecurityID`.
+pub const SECURITY_ID_SOURCE: u32 = 22;
+
+/// Tag 207 — `SecurityExchange`: market on which the security trades.
+pub const SECURITY_EXCHANGE: u32 = 207;
+
+/// Tag 167 — `SecurityType`: instrument classification (e.g., "CS", "FUT").
+pub const SECURITY_TYPE: u32 = 167;
+
+/// Tag 15 — Currency: currency of the instrument's price.
+pub const CURRENCY: u32 = 15;
+
+/// Tag 146 — `NoRelatedSym`: number of instruments in the related symbol group.
+pub const NO_RELATED_SYM: u32 = 146;
+
 // ---------------------------------------------------------------------------
 // Order attributes
 // ---------------------------------------------------------------------------
@@ -77,6 +222,27 @@ pub const ORDER_QTY: u32 = 38;
 /// "0" = Day, "1" = GTC, "3" = IOC, "4" = FOK.
 pub const TIME_IN_FORCE: u32 = 59;
 
+/// Tag 99 — `StopPx`: trigger price for stop and stop-limit orders.
+pub const STOP_PX: u32 = 99;
+
+/// Tag 126 — `ExpireTime`: UTC time a GTD order (`TimeInForce` "6") expires.
+pub const EXPIRE_TIME: u32 = 126;
+
+/// Tag 21 — `HandlInst`: broker handling instruction (required in FIX 4.2 orders).
+pub const HANDL_INST: u32 = 21;
+
+/// Tag 18 — `ExecInst`: space-separated execution instructions (`MultipleCharValue`).
+pub const EXEC_INST: u32 = 18;
+
+/// Tag 336 — `TradingSessionID`: trading session identifier.
+pub const TRADING_SESSION_ID: u32 = 336;
+
+/// Tag 528 — `OrderCapacity`: capacity of the order firm (FIX 4.4+, replaces `Rule80A`).
+pub const ORDER_CAPACITY: u32 = 528;
+
+/// Tag 47 — `Rule80A`: FIX 4.2 order capacity indicator (superseded by `OrderCapacity`).
+pub const RULE_80A: u32 = 47;
+
 // ---------------------------------------------------------------------------
 // Execution report fields
 // ---------------------------------------------------------------------------
@@ -87,6 +253,9 @@ pub const EXEC_TYPE: u32 = 150;
 /// Tag 39 — `OrdStatus`: current status of an order.
 pub const ORD_STATUS: u32 = 39;
 
+/// Tag 20 — `ExecTransType`: FIX 4.2 execution transaction type (removed in FIX 4.4).
+pub const EXEC_TRANS_TYPE: u32 = 20;
+
 /// Tag 31 — `LastPx`: price of the most recent fill.
 pub const LAST_PX: u32 = 31;
 
@@ -105,9 +274,306 @@ pub const AVG_PX: u32 = 6;
 /// Tag 60 — `TransactTime`: UTC timestamp of the transaction.
 pub const TRANSACT_TIME: u32 = 60;
 
+// ---------------------------------------------------------------------------
+// List orders
+// ---------------------------------------------------------------------------
+
+/// Tag 66 — `ListID`: identifier of a list (basket) order.
+pub const LIST_ID: u32 = 66;
+
+/// Tag 67 — `ListSeqNo`: position of an order within its list.
+pub const LIST_SEQ_NO: u32 = 67;
+
+/// Tag 68 — `TotNoOrders`: total number of orders in the list.
+pub const TOT_NO_ORDERS: u32 = 68;
+
+/// Tag 73 — `NoOrders`: number of order entries in the repeating group.
+pub const NO_ORDERS: u32 = 73;
+
+/// Tag 82 — `NoRpts`: number of `ListStatus` messages answering a status request.
+pub const NO_RPTS: u32 = 82;
+
+/// Tag 83 — `RptSeq`: sequence of this `ListStatus` within `NoRpts`.
+pub const RPT_SEQ: u32 = 83;
+
+/// Tag 84 — `CxlQty`: quantity cancelled for an order.
+pub const CXL_QTY: u32 = 84;
+
+/// Tag 394 — `BidType`: bidding process of a list order (3 = no bidding).
+pub const BID_TYPE: u32 = 394;
+
+/// Tag 429 — `ListStatusType`: reason a `ListStatus` was sent.
+pub const LIST_STATUS_TYPE: u32 = 429;
+
+/// Tag 431 — `ListOrderStatus`: status of the list as a whole.
+pub const LIST_ORDER_STATUS: u32 = 431;
+
+/// Tag 444 — `ListStatusText`: free-form text about the list's status.
+pub const LIST_STATUS_TEXT: u32 = 444;
+
 // ---------------------------------------------------------------------------
 // Miscellaneous
 // ---------------------------------------------------------------------------
 
 /// Tag 58 — Text: free-form text field for human-readable annotations.
 pub const TEXT: u32 = 58;
+
+// ---------------------------------------------------------------------------
+// Parties component (repeating group)
+// ---------------------------------------------------------------------------
+
+/// Tag 453 — `NoPartyIDs`: number of entries in the Parties repeating group.
+pub const NO_PARTY_IDS: u32 = 453;
+
+/// Tag 448 — `PartyID`: party identifier (first field of each Parties entry).
+pub const PARTY_ID: u32 = 448;
+
+/// Tag 447 — `PartyIDSource`: identifies the class or source of `PartyID`.
+pub const PARTY_ID_SOURCE: u32 = 447;
+
+/// Tag 452 — `PartyRole`: role of the party (e.g., 1 = Executing Firm).
+pub const PARTY_ROLE: u32 = 452;
+
+// ---------------------------------------------------------------------------
+// Market data entries (repeating group)
+// ---------------------------------------------------------------------------
+
+/// Tag 268 — `NoMDEntries`: number of entries in the market data group.
+pub const NO_MD_ENTRIES: u32 = 268;
+
+/// Tag 269 — `MDEntryType`: "0" = Bid, "1" = Offer, "2" = Trade.
+pub const MD_ENTRY_TYPE: u32 = 269;
+
+/// Tag 270 — `MDEntryPx`: price of the market data entry.
+pub const MD_ENTRY_PX: u32 = 270;
+
+/// Tag 271 — `MDEntrySize`: quantity of the market data entry.
+pub const MD_ENTRY_SIZE: u32 = 271;
+
+/// Tag 272 — `MDEntryDate`: date of the market data entry.
+pub const MD_ENTRY_DATE: u32 = 272;
+
+/// Tag 273 — `MDEntryTime`: time of the market data entry.
+pub const MD_ENTRY_TIME: u32 = 273;
+
+/// Tag 278 — `MDEntryID`: unique identifier of the market data entry.
+pub const MD_ENTRY_ID: u32 = 278;
+
+/// Tag 279 — `MDUpdateAction`: "0" = New, "1" = Change, "2" = Delete.
+pub const MD_UPDATE_ACTION: u32 = 279;
+
+/// Tag 290 — `MDEntryPositionNo`: display position of the entry in the book.
+pub const MD_ENTRY_POSITION_NO: u32 = 290;
+
+/// Tag 263 — `SubscriptionRequestType`: "0" = snapshot, "1" = snapshot + updates, "2" = unsubscribe.
+pub const SUBSCRIPTION_REQUEST_TYPE: u32 = 263;
+
+/// Tag 264 — `MarketDepth`: book depth requested ("0" = full book).
+pub const MARKET_DEPTH: u32 = 264;
+
+/// Tag 265 — `MDUpdateType`: "0" = full refresh, "1" = incremental refresh.
+pub const MD_UPDATE_TYPE: u32 = 265;
+
+/// Tag 267 — `NoMDEntryTypes`: number of requested `MDEntryType` entries.
+pub const NO_MD_ENTRY_TYPES: u32 = 267;
+
+// ---------------------------------------------------------------------------
+// Request identifiers
+// ---------------------------------------------------------------------------
+
+/// Tag 262 — `MDReqID`: unique identifier of a market data request.
+pub const MD_REQ_ID: u32 = 262;
+
+/// Tag 131 — `QuoteReqID`: unique identifier of a quote request.
+pub const QUOTE_REQ_ID: u32 = 131;
+
+/// Tag 320 — `SecurityReqID`: unique identifier of a security definition request.
+pub const SECURITY_REQ_ID: u32 = 320;
+
+/// Tag 322 — `SecurityResponseID`: unique identifier of a security list response.
+pub const SECURITY_RESPONSE_ID: u32 = 322;
+
+// ---------------------------------------------------------------------------
+// Tag names
+// ---------------------------------------------------------------------------
+
+/// FIX field names for every constant in this module, sorted by tag number.
+const NAMES: &[(u32, &str)] = &[
+    (ACCOUNT, "Account"),
+    (AVG_PX, "AvgPx"),
+    (BEGIN_SEQ_NO, "BeginSeqNo"),
+    (BEGIN_STRING, "BeginString"),
+    (BODY_LENGTH, "BodyLength"),
+    (CHECKSUM, "CheckSum"),
+    (CL_ORD_ID, "ClOrdID"),
+    (CUM_QTY, "CumQty"),
+    (CURRENCY, "Currency"),
+    (END_SEQ_NO, "EndSeqNo"),
+    (EXEC_ID, "ExecID"),
+    (EXEC_INST, "ExecInst"),
+    (EXEC_TRANS_TYPE, "ExecTransType"),
+    (HANDL_INST, "HandlInst"),
+    (SECURITY_ID_SOURCE, "SecurityIDSource"),
+    (LAST_PX, "LastPx"),
+    (LAST_QTY, "LastQty"),
+    (MSG_SEQ_NUM, "MsgSeqNum"),
+    (MSG_TYPE, "MsgType"),
+    (NEW_SEQ_NO, "NewSeqNo"),
+    (ORDER_ID, "OrderID"),
+    (ORDER_QTY, "OrderQty"),
+    (ORD_STATUS, "OrdStatus"),
+    (ORD_TYPE, "OrdType"),
+    (ORIG_CL_ORD_ID, "OrigClOrdID"),
+    (POSS_DUP_FLAG, "PossDupFlag"),
+    (PRICE, "Price"),
+    (REF_SEQ_NUM, "RefSeqNum"),
+    (RULE_80A, "Rule80A"),
+    (SECURITY_ID, "SecurityID"),
+    (SENDER_COMP_ID, "SenderCompID"),
+    (SENDER_SUB_ID, "SenderSubID"),
+    (SENDING_TIME, "SendingTime"),
+    (SIDE, "Side"),
+    (SYMBOL, "Symbol"),
+    (TARGET_COMP_ID, "TargetCompID"),
+    (TARGET_SUB_ID, "TargetSubID"),
+    (TEXT, "Text"),
+    (TIME_IN_FORCE, "TimeInForce"),
+    (TRANSACT_TIME, "TransactTime"),
+    (LIST_ID, "ListID"),
+    (LIST_SEQ_NO, "ListSeqNo"),
+    (TOT_NO_ORDERS, "TotNoOrders"),
+    (NO_ORDERS, "NoOrders"),
+    (NO_RPTS, "NoRpts"),
+    (RPT_SEQ, "RptSeq"),
+    (CXL_QTY, "CxlQty"),
+    (SIGNATURE, "Signature"),
+    (SIGNATURE_LENGTH, "SignatureLength"),
+    (RAW_DATA_LENGTH, "RawDataLength"),
+    (RAW_DATA, "RawData"),
+    (POSS_RESEND, "PossResend"),
+    (ENCRYPT_METHOD, "EncryptMethod"),
+    (STOP_PX, "StopPx"),
+    (CXL_REJ_REASON, "CxlRejReason"),
+    (HEART_BT_INT, "HeartBtInt"),
+    (TEST_REQ_ID, "TestReqID"),
+    (ON_BEHALF_OF_COMP_ID, "OnBehalfOfCompID"),
+    (ON_BEHALF_OF_SUB_ID, "OnBehalfOfSubID"),
+    (ORIG_SENDING_TIME, "OrigSendingTime"),
+    (GAP_FILL_FLAG, "GapFillFlag"),
+    (EXPIRE_TIME, "ExpireTime"),
+    (DELIVER_TO_COMP_ID, "DeliverToCompID"),
+    (DELIVER_TO_SUB_ID, "DeliverToSubID"),
+    (QUOTE_REQ_ID, "QuoteReqID"),
+    (RESET_SEQ_NUM_FLAG, "ResetSeqNumFlag"),
+    (NO_RELATED_SYM, "NoRelatedSym"),
+    (EXEC_TYPE, "ExecType"),
+    (LEAVES_QTY, "LeavesQty"),
+    (SECURITY_TYPE, "SecurityType"),
+    (SECURITY_EXCHANGE, "SecurityExchange"),
+    (XML_DATA_LEN, "XmlDataLen"),
+    (XML_DATA, "XmlData"),
+    (MD_REQ_ID, "MDReqID"),
+    (SUBSCRIPTION_REQUEST_TYPE, "SubscriptionRequestType"),
+    (MARKET_DEPTH, "MarketDepth"),
+    (MD_UPDATE_TYPE, "MDUpdateType"),
+    (NO_MD_ENTRY_TYPES, "NoMDEntryTypes"),
+    (NO_MD_ENTRIES, "NoMDEntries"),
+    (MD_ENTRY_TYPE, "MDEntryType"),
+    (MD_ENTRY_PX, "MDEntryPx"),
+    (MD_ENTRY_SIZE, "MDEntrySize"),
+    (MD_ENTRY_DATE, "MDEntryDate"),
+    (MD_ENTRY_TIME, "MDEntryTime"),
+    (MD_ENTRY_ID, "MDEntryID"),
+    (MD_UPDATE_ACTION, "MDUpdateAction"),
+    (MD_ENTRY_POSITION_NO, "MDEntryPositionNo"),
+    (SECURITY_REQ_ID, "SecurityReqID"),
+    (SECURITY_RESPONSE_ID, "SecurityResponseID"),
+    (TRADING_SESSION_ID, "TradingSessionID"),
+    (REF_TAG_ID, "RefTagID"),
+    (REF_MSG_TYPE, "RefMsgType"),
+    (SESSION_REJECT_REASON, "SessionRejectReason"),
+    (BID_TYPE, "BidType"),
+    (LIST_STATUS_TYPE, "ListStatusType"),
+    (LIST_ORDER_STATUS, "ListOrderStatus"),
+    (CXL_REJ_RESPONSE_TO, "CxlRejResponseTo"),
+    (LIST_STATUS_TEXT, "ListStatusText"),
+    (PARTY_ID_SOURCE, "PartyIDSource"),
+    (PARTY_ID, "PartyID"),
+    (PARTY_ROLE, "PartyRole"),
+    (NO_PARTY_IDS, "NoPartyIDs"),
+    (ORDER_CAPACITY, "OrderCapacity"),
+    (USERNAME, "Username"),
+    (PASSWORD, "Password"),
+    (MASS_STATUS_REQ_ID, "MassStatusReqID"),
+    (MASS_STATUS_REQ_TYPE, "MassStatusReqType"),
+    (ORD_STATUS_REQ_ID, "OrdStatusReqID"),
+    (TOT_NUM_REPORTS, "TotNumReports"),
+    (LAST_RPT_REQUESTED, "LastRptRequested"),
+    (NEW_PASSWORD, "NewPassword"),
+    (APPL_VER_ID, "ApplVerID"),
+    (DEFAULT_APPL_VER_ID, "DefaultApplVerID"),
+];
+
+/// Return the FIX field name of a well-known tag (e.g., 55 → `"Symbol"`).
+///
+/// Returns `None` for tags not defined in this module.
+#[must_use]
+pub fn name(tag: u32) -> Option<&'static str> {
+    NAMES
+        .binary_search_by_key(&tag, |&(t, _)| t)
+        .ok()
+        .map(|i| NAMES[i].1)
+}
+
+/// Return the tag number for a FIX field name (e.g., `"Symbol"` → 55).
+///
+/// Matching is exact and case-sensitive; returns `None` for names not
+/// defined in this module.
+#[must_use]
+pub fn by_name(name: &str) -> Option<u32> {
+    NAMES.iter().find(|&&(_, n)| n == name).map(|&(t, _)| t)
+}
+
+/// Error returned when a field name is not in the tag table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTagName(pub String);
+
+impl core::fmt::Display for UnknownTagName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unknown FIX field name: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTagName {}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_sorted_and_unique() {
+        assert!(NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_name_lookup() {
+        assert_eq!(name(SYMBOL), Some("Symbol"));
+        assert_eq!(name(BEGIN_STRING), Some("BeginString"));
+        assert_eq!(name(NO_PARTY_IDS), Some("NoPartyIDs"));
+        assert_eq!(name(9999), None);
+    }
+
+    #[test]
+    fn test_by_name_inverts_name() {
+        for &(t, n) in NAMES {
+            assert_eq!(by_name(n), Some(t));
+        }
+        assert_eq!(by_name("Account"), Some(ACCOUNT));
+        assert_eq!(by_name("symbol"), None);
+        assert_eq!(by_name(""), None);
+    }
+}
@@ -0,0 +1,325 @@
+//! Round-trip Test Helpers
+//!
+//! 下流クレートの統合テスト向けに、ビルド → パース → 比較の検査を
+//! 公開する (feature `testing`)。失敗時はフレームを `|` 区切りで表示して
+//! panic する。
+//!
+//! [`assert_parses_like`] は受信フレームのパース結果を [`Expected`] と
+//! 突き合わせる。取引所ごとの実例フレームは [`crate::corpus`] にある。
+
+use crate::message::FixMessage;
+use crate::parser::{self, SOH};
+use crate::repeating_group::GroupEntry;
+use crate::tag;
+
+/// SOH を `|` に置き換えた表示用文字列。
+#[must_use]
+pub fn printable(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).replace(char::from(SOH), "|")
+}
+
+/// フレームが厳密な FIX 形式であることを検査し、パース結果を返す。
+///
+/// [`parser::parse`] の検証 (8/9 の位置、`BodyLength`、Checksum) に加え、
+/// 以下を検査する。
+///
+/// - 3 番目のフィールドが空でない `MsgType` (35)
+/// - すべての値が空でない UTF-8
+/// - 末尾が SOH
+///
+/// # Panics
+///
+/// いずれかの検査に失敗した場合。
+#[must_use]
+pub fn assert_frame_valid(bytes: &[u8]) -> FixMessage {
+    let shown = printable(bytes);
+    let msg = parser::parse(bytes).unwrap_or_else(|e| panic!("invalid FIX frame ({e}): {shown}"));
+    assert!(
+        bytes.last() == Some(&SOH),
+        "FIX frame does not end with SOH: {shown}"
+    );
+    for (i, field) in bytes[..bytes.len() - 1].split(|&b| b == SOH).enumerate() {
+        let text = core::str::from_utf8(field)
+            .unwrap_or_else(|_| panic!("field {i} is not UTF-8: {shown}"));
+        let (t, value) = text
+            .split_once('=')
+            .unwrap_or_else(|| panic!("field {i} has no '=': {shown}"));
+        assert!(!value.is_empty(), "tag {t} has an empty value: {shown}");
+        if i == 2 {
+            assert!(
+                t == tag::MSG_TYPE.to_string(),
+                "third field must be MsgType (35), found {t}: {shown}"
+            );
+        }
+    }
+    msg
+}
+
+/// `msg` をシリアライズ → パースし、元のメッセージと一致することを検査。
+///
+/// [`FixMessage::spans`] は比較しない。シリアライズしたバイト列を返す。
+///
+/// # Panics
+///
+/// フレームが不正な場合 ([`assert_frame_valid`])、または往復後の
+/// メッセージが一致しない場合。
+#[must_use]
+pub fn assert_roundtrip(msg: &FixMessage) -> Vec<u8> {
+    let bytes = msg.to_bytes();
+    let mut parsed = assert_frame_valid(&bytes);
+    parsed.spans.clone_from(&msg.spans);
+    assert!(
+        parsed == *msg,
+        "FIX round-trip mismatch\n  original: {msg}\n    parsed: {parsed}"
+    );
+    bytes
+}
+
+/// 期待するフィールド列 `(タグ, 値)`。
+pub type ExpectedFields<'a> = &'a [(u32, &'a str)];
+
+/// [`assert_parses_like`] が期待するパース結果。
+///
+/// `fields` は [`FixMessage::fields`] (8 / 9 / 35 / 10 とグループ内を除く、
+/// `NoXxx` カウントタグを含む) の全フィールドで、順不同。`groups` は
+/// カウントタグごとのエントリを受信順に並べる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expected<'a> {
+    /// `BeginString` (8)。
+    pub begin_string: &'a str,
+    /// `MsgType` (35)。
+    pub msg_type: &'a str,
+    /// グループ外のフィールド。
+    pub fields: ExpectedFields<'a>,
+    /// `(カウントタグ, エントリごとのフィールド)`。
+    pub groups: &'a [(u32, &'a [ExpectedFields<'a>])],
+}
+
+/// `frame` をパースし、結果が `expected` と完全に一致することを検査。
+///
+/// パース結果を返す。
+///
+/// # Panics
+///
+/// パースに失敗した場合、または不一致 (値の違い、欠落、余分なフィールド・
+/// グループ・エントリ) があった場合。不一致はすべて列挙して表示する。
+#[must_use]
+pub fn assert_parses_like(frame: &[u8], expected: &Expected<'_>) -> FixMessage {
+    let shown = printable(frame);
+    let msg = parser::parse(frame).unwrap_or_else(|e| panic!("invalid FIX frame ({e}): {shown}"));
+
+    let mut diffs = Vec::new();
+    if msg.begin_string != expected.begin_string {
+        diffs.push(format!(
+            "BeginString: expected {:?}, got {:?}",
+            expected.begin_string, msg.begin_string
+        ));
+    }
+    if msg.msg_type != expected.msg_type {
+        diffs.push(format!(
+            "MsgType: expected {:?}, got {:?}",
+            expected.msg_type, msg.msg_type
+        ));
+    }
+    diff_fields(
+        "field",
+        msg.iter(),
+        expected.fields,
+        |t| msg.get(t),
+        &mut diffs,
+    );
+
+    for &(count_tag, entries) in expected.groups {
+        let actual = msg.groups.get(&count_tag).map_or(&[][..], Vec::as_slice);
+        if actual.len() != entries.len() {
+            diffs.push(format!(
+                "group {count_tag}: expected {} entries, got {}",
+                entries.len(),
+                actual.len()
+            ));
+        }
+        for (i, (entry, fields)) in actual.iter().zip(entries.iter()).enumerate() {
+            diff_fields(
+                &format!("group {count_tag}[{i}]"),
+                entry_iter(entry),
+                fields,
+                |t| entry.get(t),
+                &mut diffs,
+            );
+        }
+    }
+    let mut extra: Vec<u32> = msg
+        .groups
+        .keys()
+        .filter(|t| !expected.groups.iter().any(|(c, _)| c == *t))
+        .copied()
+        .collect();
+    extra.sort_unstable();
+    for t in extra {
+        diffs.push(format!("group {t}: unexpected"));
+    }
+
+    assert!(
+        diffs.is_empty(),
+        "FIX frame does not parse as expected:\n  - {}\n  frame: {shown}",
+        diffs.join("\n  - ")
+    );
+    msg
+}
+
+/// エントリのフィールドをタグ昇順で列挙 (差分の表示順を固定する)。
+fn entry_iter(entry: &GroupEntry) -> impl Iterator<Item = (u32, &str)> {
+    let mut fields: Vec<_> = entry.iter().collect();
+    fields.sort_unstable_by_key(|&(t, _)| t);
+    fields.into_iter()
+}
+
+/// `actual` と `expected` の差分を `diffs` に追記。
+fn diff_fields<'m>(
+    scope: &str,
+    actual: impl Iterator<Item = (u32, &'m str)>,
+    expected: &[(u32, &str)],
+    get: impl Fn(u32) -> Option<&'m str>,
+    diffs: &mut Vec<String>,
+) {
+    for &(t, want) in expected {
+        match get(t) {
+            Some(got) if got == want => {}
+            Some(got) => diffs.push(format!("{scope} {t}: expected {want:?}, got {got:?}")),
+            None => diffs.push(format!("{scope} {t}: missing (expected {want:?})")),
+        }
+    }
+    for (t, got) in actual {
+        if !expected.iter().any(|&(e, _)| e == t) {
+            diffs.push(format!("{scope} {t}: unexpected {got:?}"));
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::repeating_group::GroupEntry;
+    use crate::version::BeginString;
+
+    fn order() -> FixMessage {
+        let mut msg = FixMessage::new("FIX.4.4", "D");
+        msg.set(tag::SENDER_COMP_ID, "ALICE")
+            .set(tag::TARGET_COMP_ID, "BROKER")
+            .set(tag::MSG_SEQ_NUM, "1")
+            .set(tag::SYMBOL, "BTCUSD");
+        msg
+    }
+
+    #[test]
+    fn roundtrip_plain_and_grouped() {
+        let bytes = assert_roundtrip(&order());
+        assert!(bytes.starts_with(b"8=FIX.4.4\x01"));
+
+        let mut msg = order();
+        msg.set(tag::NO_PARTY_IDS, "1");
+        let mut party = GroupEntry::new();
+        party.set(tag::PARTY_ID, "P1").set(tag::PARTY_ROLE, "3");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![party]);
+        let _ = assert_roundtrip(&msg);
+    }
+
+    #[test]
+    fn roundtrip_ignores_spans() {
+        let raw = order().to_bytes();
+        let msg = parser::parse_with_spans(&raw).unwrap();
+        assert_eq!(assert_roundtrip(&msg), raw);
+    }
+
+    #[test]
+    #[should_panic(expected = "round-trip mismatch")]
+    fn roundtrip_detects_stale_count_tag() {
+        // 件数タグはエントリ数で書き直されるため一致しない
+        let mut msg = order();
+        msg.set(tag::NO_PARTY_IDS, "2");
+        let mut party = GroupEntry::new();
+        party.set(tag::PARTY_ID, "P1");
+        msg.groups.insert(tag::NO_PARTY_IDS, vec![party]);
+        let _ = assert_roundtrip(&msg);
+    }
+
+    #[test]
+    fn builder_output_is_valid() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::SENDER_COMP_ID, "A")
+            .build();
+        assert_eq!(assert_frame_valid(&bytes).msg_type, "0");
+    }
+
+    #[test]
+    #[should_panic(expected = "empty value")]
+    fn empty_value_rejected() {
+        let bytes = FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::TEXT, "")
+            .build();
+        let _ = assert_frame_valid(&bytes);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid FIX frame")]
+    fn bad_checksum_rejected() {
+        let mut bytes = FixBuilder::new(BeginString::Fix44, "0").build();
+        let n = bytes.len();
+        bytes[n - 2] = if bytes[n - 2] == b'0' { b'1' } else { b'0' };
+        let _ = assert_frame_valid(&bytes);
+    }
+
+    #[test]
+    fn printable_replaces_soh() {
+        assert_eq!(printable(b"8=FIX.4.4\x0135=0\x01"), "8=FIX.4.4|35=0|");
+    }
+
+    fn heartbeat() -> Vec<u8> {
+        FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::SENDER_COMP_ID, "A")
+            .field(tag::TARGET_COMP_ID, "B")
+            .field(tag::TEST_REQ_ID, "T1")
+            .build()
+    }
+
+    #[test]
+    fn parses_like_expected() {
+        let msg = assert_parses_like(
+            &heartbeat(),
+            &Expected {
+                begin_string: "FIX.4.4",
+                msg_type: "0",
+                fields: &[(112, "T1"), (49, "A"), (56, "B")],
+                groups: &[],
+            },
+        );
+        assert_eq!(msg.get(tag::TEST_REQ_ID), Some("T1"));
+    }
+
+    #[test]
+    fn parses_like_lists_every_difference() {
+        let result = std::panic::catch_unwind(|| {
+            let _ = assert_parses_like(
+                &heartbeat(),
+                &Expected {
+                    begin_string: "FIX.4.2",
+                    msg_type: "0",
+                    fields: &[(49, "A"), (56, "C"), (58, "x")],
+                    groups: &[(453, &[])],
+                },
+            );
+        });
+        let err = result.unwrap_err();
+        let text = err.downcast_ref::<String>().unwrap();
+        assert!(text.contains("BeginString: expected \"FIX.4.2\", got \"FIX.4.4\""));
+        assert!(text.contains("field 56: expected \"C\", got \"B\""));
+        assert!(text.contains("field 58: missing"));
+        assert!(text.contains("field 112: unexpected \"T1\""));
+        assert!(!text.contains("group 453"));
+    }
+}
@@ -0,0 +1,297 @@
+//! Outbound Order Throttle
+//!
+//! 送信メッセージのレート制限 (トークンバケット)。全体の予算と
+//! `MsgType` 別の予算を設定できる。リスクを減らすメッセージ
+//! (既定: `OrderCancelRequest` 35=F, `OrderMassCancelRequest` 35=q) は
+//! 免除され、新規注文の流量を絞るスロットルで遅延しない。
+//! 時刻は呼び出し側が供給するナノ秒 (`now_ns`)。
+//!
+//! [`crate::session::FixSession::submit`] はセッションのスロットルを通して
+//! アプリケーションメッセージを送る。予算切れのメッセージは
+//! [`ThrottlePolicy`] に従い、保留して後で送るか呼び出し側に返す。
+
+use std::collections::HashMap;
+
+/// 1 秒あたりのナノ秒数。
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// 既定で免除する `MsgType` (取消・一括取消)。
+pub const DEFAULT_EXEMPT: &[&str] = &["F", "q"];
+
+/// レート上限 (バースト数と 1 秒あたりの補充数)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// バケット容量 (連続送信できる最大数)。
+    pub burst: u32,
+    /// 1 秒あたりの補充数。
+    pub per_sec: u32,
+}
+
+impl RateLimit {
+    /// 1 秒あたり `per_sec` 件、バースト `burst` 件の上限を作成。
+    #[must_use]
+    pub const fn new(burst: u32, per_sec: u32) -> Self {
+        Self { burst, per_sec }
+    }
+}
+
+/// スロットル判定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleDecision {
+    /// 送信可 (予算を消費済み)。
+    Send,
+    /// `until_ns` まで待機が必要 (予算は消費しない)。
+    Wait {
+        /// 送信可能になる時刻 (ns)。
+        until_ns: u64,
+    },
+}
+
+impl ThrottleDecision {
+    /// 送信可か。
+    #[must_use]
+    pub const fn is_send(&self) -> bool {
+        matches!(self, Self::Send)
+    }
+}
+
+/// 予算切れのメッセージの扱い。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThrottlePolicy {
+    /// 保留し、予算が回復したら受け付けた順に送る。
+    #[default]
+    Queue,
+    /// 送らずに呼び出し側へ返す。
+    Reject,
+}
+
+/// トークンバケット。トークンは 1 件 = `NANOS_PER_SEC` 単位で保持する。
+#[derive(Debug, Clone)]
+struct Bucket {
+    limit: RateLimit,
+    units: u64,
+    last_ns: u64,
+}
+
+impl Bucket {
+    const fn new(limit: RateLimit, now_ns: u64) -> Self {
+        Self {
+            limit,
+            units: limit.burst as u64 * NANOS_PER_SEC,
+            last_ns: now_ns,
+        }
+    }
+
+    const fn refill(&mut self, now_ns: u64) {
+        if now_ns > self.last_ns {
+            let cap = self.limit.burst as u64 * NANOS_PER_SEC;
+            let gained = (now_ns - self.last_ns).saturating_mul(self.limit.per_sec as u64);
+            let total = self.units.saturating_add(gained);
+            self.units = if total > cap { cap } else { total };
+            self.last_ns = now_ns;
+        }
+    }
+
+    /// 1 件送信可能になる時刻 (refill 済みが前提)。
+    const fn ready_at(&self, now_ns: u64) -> Option<u64> {
+        if self.units >= NANOS_PER_SEC {
+            return Some(now_ns);
+        }
+        if self.limit.per_sec == 0 {
+            return None;
+        }
+        let missing = NANOS_PER_SEC - self.units;
+        Some(now_ns + missing.div_ceil(self.limit.per_sec as u64))
+    }
+}
+
+/// 送信スロットル。
+///
+/// 既定では上限なし (すべて [`ThrottleDecision::Send`])、免除対象は
+/// [`DEFAULT_EXEMPT`]。
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    global: Option<Bucket>,
+    per_type: HashMap<String, Bucket>,
+    exempt: Vec<String>,
+    policy: ThrottlePolicy,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Throttle {
+    /// 上限なしのスロットルを作成。
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            global: None,
+            per_type: HashMap::new(),
+            exempt: DEFAULT_EXEMPT.iter().map(|&s| s.to_string()).collect(),
+            policy: ThrottlePolicy::Queue,
+        }
+    }
+
+    /// 免除対象以外の全メッセージに共通の上限を設定。
+    #[must_use]
+    pub const fn global_limit(mut self, limit: RateLimit, now_ns: u64) -> Self {
+        self.global = Some(Bucket::new(limit, now_ns));
+        self
+    }
+
+    /// `msg_type` 別の上限を設定 (全体の上限と併用)。
+    #[must_use]
+    pub fn type_limit(mut self, msg_type: &str, limit: RateLimit, now_ns: u64) -> Self {
+        self.per_type
+            .insert(msg_type.to_string(), Bucket::new(limit, now_ns));
+        self
+    }
+
+    /// 免除対象の `MsgType` を置き換え。
+    #[must_use]
+    pub fn exempt(mut self, msg_types: &[&str]) -> Self {
+        self.exempt = msg_types.iter().map(|&s| s.to_string()).collect();
+        self
+    }
+
+    /// 予算切れのメッセージの扱いを設定 (既定 [`ThrottlePolicy::Queue`])。
+    #[must_use]
+    pub const fn on_limit(mut self, policy: ThrottlePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 予算切れのメッセージの扱い。
+    #[must_use]
+    pub const fn limit_policy(&self) -> ThrottlePolicy {
+        self.policy
+    }
+
+    /// `msg_type` がスロットル免除か。
+    #[must_use]
+    pub fn is_exempt(&self, msg_type: &str) -> bool {
+        self.exempt.iter().any(|t| t == msg_type)
+    }
+
+    /// `msg_type` を `now_ns` に送信してよいか判定。
+    ///
+    /// 免除対象は常に [`ThrottleDecision::Send`] で予算を消費しない。
+    /// それ以外は全体・型別の両方の予算がある場合のみ消費して送信可。
+    /// 補充が 0 で予算が尽きた場合は `until_ns` = `u64::MAX`。
+    pub fn check(&mut self, msg_type: &str, now_ns: u64) -> ThrottleDecision {
+        if self.is_exempt(msg_type) {
+            return ThrottleDecision::Send;
+        }
+        let mut ready = now_ns;
+        for bucket in self
+            .global
+            .iter_mut()
+            .chain(self.per_type.get_mut(msg_type))
+        {
+            bucket.refill(now_ns);
+            ready = ready.max(bucket.ready_at(now_ns).unwrap_or(u64::MAX));
+        }
+        if ready > now_ns {
+            return ThrottleDecision::Wait { until_ns: ready };
+        }
+        for bucket in self
+            .global
+            .iter_mut()
+            .chain(self.per_type.get_mut(msg_type))
+        {
+            bucket.units -= NANOS_PER_SEC;
+        }
+        ThrottleDecision::Send
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut t = Throttle::new();
+        for i in 0..1000 {
+            assert!(t.check("D", i).is_send());
+        }
+    }
+
+    #[test]
+    fn global_burst_then_wait() {
+        let mut t = Throttle::new().global_limit(RateLimit::new(2, 10), 0);
+        assert!(t.check("D", 0).is_send());
+        assert!(t.check("G", 0).is_send());
+        // 10 件/秒 → 100 ms で 1 件補充
+        assert_eq!(
+            t.check("D", 0),
+            ThrottleDecision::Wait { until_ns: 100 * MS }
+        );
+        assert!(t.check("D", 100 * MS).is_send());
+    }
+
+    #[test]
+    fn cancels_bypass_exhausted_budget() {
+        let mut t = Throttle::new()
+            .global_limit(RateLimit::new(1, 1), 0)
+            .type_limit("F", RateLimit::new(0, 0), 0);
+        assert!(t.check("D", 0).is_send());
+        assert!(!t.check("D", 0).is_send());
+        for _ in 0..10 {
+            assert!(t.check("F", 0).is_send());
+            assert!(t.check("q", 0).is_send());
+        }
+        // 免除メッセージは予算を消費しない
+        assert!(t.check("D", NANOS_PER_SEC).is_send());
+    }
+
+    #[test]
+    fn per_type_budget() {
+        let mut t = Throttle::new().type_limit("D", RateLimit::new(1, 1), 0);
+        assert!(t.check("D", 0).is_send());
+        assert_eq!(
+            t.check("D", 0),
+            ThrottleDecision::Wait {
+                until_ns: NANOS_PER_SEC
+            }
+        );
+        // 型別の上限は他の型に影響しない
+        assert!(t.check("G", 0).is_send());
+    }
+
+    #[test]
+    fn rejected_check_consumes_nothing() {
+        let mut t = Throttle::new()
+            .global_limit(RateLimit::new(5, 1), 0)
+            .type_limit("D", RateLimit::new(1, 1), 0);
+        assert!(t.check("D", 0).is_send());
+        assert!(!t.check("D", 0).is_send());
+        // 全体予算は D の待機で消費されていない (残り 4)
+        for _ in 0..4 {
+            assert!(t.check("G", 0).is_send());
+        }
+        assert!(!t.check("G", 0).is_send());
+    }
+
+    #[test]
+    fn custom_exemptions_and_zero_refill() {
+        let mut t = Throttle::new()
+            .global_limit(RateLimit::new(0, 0), 0)
+            .exempt(&["F"]);
+        assert!(t.is_exempt("F"));
+        assert!(!t.is_exempt("q"));
+        assert_eq!(
+            t.check("q", 0),
+            ThrottleDecision::Wait { until_ns: u64::MAX }
+        );
+    }
+}
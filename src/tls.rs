@@ -0,0 +1,150 @@
+//! TLS (FIXS)
+//!
+//! [`crate::engine`] の接続を rustls で TLS 化するための設定。`tls`
+//! フィーチャで有効。
+//!
+//! - [`client_config`]: Initiator 側。サーバ証明書を `roots` で検証し、
+//!   [`Identity`] を渡せばクライアント証明書を提示する。SNI とサーバ名の
+//!   照合には [`crate::engine::Initiator::connect_tls`] の `server_name` を使う。
+//! - [`server_config`]: Acceptor 側 ([`crate::engine::Listener::tls`])。
+//!   `client_roots` を渡すとクライアント証明書を必須にして検証する。
+//! - [`load_certs`] / [`load_private_key`] / [`Identity::from_pem_files`]:
+//!   PEM ファイルの読み込み。
+//!
+//! 暗号実装は rustls の `ring` プロバイダを明示して使うため、プロセス既定の
+//! `CryptoProvider` が未設定・複数有効でも panic しない。
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+pub use tokio_rustls::rustls;
+
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+
+/// 提示する証明書チェーンと秘密鍵。
+#[derive(Debug)]
+pub struct Identity {
+    /// 証明書チェーン (自身の証明書が先頭)。
+    pub cert_chain: Vec<CertificateDer<'static>>,
+    /// 秘密鍵。
+    pub key: PrivateKeyDer<'static>,
+}
+
+impl Identity {
+    /// PEM の証明書チェーンと秘密鍵のファイルから読み込む。
+    ///
+    /// # Errors
+    ///
+    /// ファイルを読めない、PEM として不正、証明書・鍵が含まれない場合。
+    pub fn from_pem_files(cert_chain: impl AsRef<Path>, key: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            cert_chain: load_certs(cert_chain)?,
+            key: load_private_key(key)?,
+        })
+    }
+}
+
+/// PEM ファイルの証明書をすべて読み込む。
+///
+/// # Errors
+///
+/// ファイルを読めない、PEM として不正、証明書が 1 つもない場合。
+pub fn load_certs(path: impl AsRef<Path>) -> io::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path.as_ref())
+        .map_err(|e| invalid_pem(&e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid_pem(&e))?;
+    if certs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no certificate in {}", path.as_ref().display()),
+        ));
+    }
+    Ok(certs)
+}
+
+/// PEM ファイルの最初の秘密鍵 (PKCS#8・PKCS#1・SEC1) を読み込む。
+///
+/// # Errors
+///
+/// ファイルを読めない、PEM として不正、秘密鍵がない場合。
+pub fn load_private_key(path: impl AsRef<Path>) -> io::Result<PrivateKeyDer<'static>> {
+    PrivateKeyDer::from_pem_file(path.as_ref()).map_err(|e| invalid_pem(&e))
+}
+
+/// 証明書を信頼する CA として集めたルートストア。
+///
+/// # Errors
+///
+/// 証明書を解析できない場合。
+pub fn root_store(
+    certs: impl IntoIterator<Item = CertificateDer<'static>>,
+) -> Result<RootCertStore, rustls::Error> {
+    let mut roots = RootCertStore::empty();
+    for cert in certs {
+        roots.add(cert)?;
+    }
+    Ok(roots)
+}
+
+/// Initiator 側の設定: サーバ証明書を `roots` で検証し、`identity` があれば
+/// クライアント証明書として提示する。
+///
+/// # Errors
+///
+/// `identity` の鍵が証明書と合わない・未対応の場合。
+pub fn client_config(
+    roots: RootCertStore,
+    identity: Option<Identity>,
+) -> Result<Arc<ClientConfig>, rustls::Error> {
+    let builder = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots);
+    let config = match identity {
+        Some(identity) => builder.with_client_auth_cert(identity.cert_chain, identity.key)?,
+        None => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(config))
+}
+
+/// Acceptor 側の設定: `identity` を提示し、`client_roots` があれば
+/// クライアント証明書を必須としてそれで検証する。
+///
+/// # Errors
+///
+/// `identity` の鍵が証明書と合わない・未対応の場合、`client_roots` が空の
+/// 場合。
+pub fn server_config(
+    identity: Identity,
+    client_roots: Option<RootCertStore>,
+) -> Result<Arc<ServerConfig>, rustls::Error> {
+    let provider = provider();
+    let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()?;
+    let builder = match client_roots {
+        Some(roots) => {
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| rustls::Error::General(e.to_string()))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(
+        builder.with_single_cert(identity.cert_chain, identity.key)?,
+    ))
+}
+
+/// `ring` の既定プロバイダ (プロセス既定の `CryptoProvider` には依存しない)。
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn invalid_pem(err: &rustls::pki_types::pem::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
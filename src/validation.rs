@@ -0,0 +1,256 @@
+//! Session-Level Message Validation
+//!
+//! 受信メッセージをセッション層で検証する。失敗は [`ValidationError`] で
+//! 返し、[`ValidationError::reason`] が `SessionRejectReason` (tag 373) に
+//! 対応する。[`crate::admin::Reject::from_validation`] でそのまま
+//! Reject (35=3) に変換できる。
+//!
+//! [`validate`] は標準ヘッダと `MsgType` ごとの必須タグ
+//! ([`crate::builder::required_body_tags`])、空値、`MsgSeqNum` の形式を
+//! 検査する。
+
+use crate::admin::SessionRejectReason;
+use crate::builder::{required_body_tags, BuildError, REQUIRED_HEADER_TAGS};
+use crate::message::{FieldError, FixMessage};
+use crate::tag;
+
+/// 検証エラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// 必須タグが無い。
+    RequiredTagMissing {
+        /// タグ番号。
+        tag: u32,
+    },
+    /// この `MsgType` では定義されていないタグ。
+    TagNotDefinedForMsgType {
+        /// タグ番号。
+        tag: u32,
+    },
+    /// タグに値が無い (`tag=` のみ)。
+    TagWithoutValue {
+        /// タグ番号。
+        tag: u32,
+    },
+    /// 値が範囲外。
+    ValueOutOfRange {
+        /// タグ番号。
+        tag: u32,
+        /// 受信した値。
+        value: String,
+    },
+    /// 値の形式が不正。
+    IncorrectDataFormat {
+        /// タグ番号。
+        tag: u32,
+        /// 受信した値。
+        value: String,
+    },
+    /// 繰り返しグループの `NumInGroup` が実際の件数と一致しない。
+    IncorrectNumInGroupCount {
+        /// `NoXxx` カウントタグ。
+        tag: u32,
+    },
+    /// 繰り返しグループのメンバが先頭タグより前にある。
+    GroupFieldsOutOfOrder {
+        /// `NoXxx` カウントタグ。
+        tag: u32,
+    },
+    /// `SenderCompID` / `TargetCompID` がセッションと一致しない。
+    CompIdProblem {
+        /// 一致しなかったタグ (49 または 56)。
+        tag: u32,
+    },
+    /// `SendingTime` が許容範囲外。
+    SendingTimeAccuracy,
+    /// 未知または非対応の `MsgType`。
+    InvalidMsgType,
+}
+
+impl ValidationError {
+    /// 対応する `SessionRejectReason`。
+    #[must_use]
+    pub const fn reason(&self) -> SessionRejectReason {
+        match self {
+            Self::RequiredTagMissing { .. } => SessionRejectReason::RequiredTagMissing,
+            Self::TagNotDefinedForMsgType { .. } => SessionRejectReason::TagNotDefinedForMsgType,
+            Self::TagWithoutValue { .. } => SessionRejectReason::TagWithoutValue,
+            Self::ValueOutOfRange { .. } => SessionRejectReason::ValueOutOfRange,
+            Self::IncorrectDataFormat { .. } => SessionRejectReason::IncorrectDataFormat,
+            Self::IncorrectNumInGroupCount { .. } => SessionRejectReason::IncorrectNumInGroupCount,
+            Self::GroupFieldsOutOfOrder { .. } => SessionRejectReason::GroupFieldsOutOfOrder,
+            Self::CompIdProblem { .. } => SessionRejectReason::CompIdProblem,
+            Self::SendingTimeAccuracy => SessionRejectReason::SendingTimeAccuracy,
+            Self::InvalidMsgType => SessionRejectReason::InvalidMsgType,
+        }
+    }
+
+    /// 問題のタグ (`RefTagID`)。`SendingTime` / `MsgType` の問題はそれぞれのタグ。
+    #[must_use]
+    pub const fn tag(&self) -> Option<u32> {
+        match self {
+            Self::RequiredTagMissing { tag }
+            | Self::TagNotDefinedForMsgType { tag }
+            | Self::TagWithoutValue { tag }
+            | Self::ValueOutOfRange { tag, .. }
+            | Self::IncorrectDataFormat { tag, .. }
+            | Self::IncorrectNumInGroupCount { tag }
+            | Self::GroupFieldsOutOfOrder { tag }
+            | Self::CompIdProblem { tag } => Some(*tag),
+            Self::SendingTimeAccuracy => Some(tag::SENDING_TIME),
+            Self::InvalidMsgType => Some(tag::MSG_TYPE),
+        }
+    }
+}
+
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::RequiredTagMissing { tag } => write!(f, "required tag missing: {tag}"),
+            Self::TagNotDefinedForMsgType { tag } => {
+                write!(f, "tag not defined for this message type: {tag}")
+            }
+            Self::TagWithoutValue { tag } => write!(f, "tag specified without a value: {tag}"),
+            Self::ValueOutOfRange { tag, value } => {
+                write!(
+                    f,
+                    "value is incorrect (out of range) for tag {tag}: {value}"
+                )
+            }
+            Self::IncorrectDataFormat { tag, value } => {
+                write!(f, "incorrect data format for tag {tag}: {value}")
+            }
+            Self::IncorrectNumInGroupCount { tag } => {
+                write!(f, "incorrect NumInGroup count for repeating group {tag}")
+            }
+            Self::GroupFieldsOutOfOrder { tag } => {
+                write!(f, "repeating group fields out of order: {tag}")
+            }
+            Self::CompIdProblem { tag } => write!(f, "CompID problem: tag {tag}"),
+            Self::SendingTimeAccuracy => write!(f, "SendingTime accuracy problem"),
+            Self::InvalidMsgType => write!(f, "invalid MsgType"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<FieldError> for ValidationError {
+    fn from(err: FieldError) -> Self {
+        match err {
+            FieldError::MissingTag { tag, .. } => Self::RequiredTagMissing { tag },
+            FieldError::InvalidValue { tag, value, .. } => Self::IncorrectDataFormat { tag, value },
+        }
+    }
+}
+
+impl From<BuildError> for ValidationError {
+    fn from(err: BuildError) -> Self {
+        match err {
+            BuildError::MissingRequiredField { tag, .. } => Self::RequiredTagMissing { tag },
+        }
+    }
+}
+
+/// 標準ヘッダ・`MsgType` 別必須タグ・空値・`MsgSeqNum` の形式を検査。
+///
+/// 最初に見つかった問題を返す。
+///
+/// # Errors
+///
+/// 必須タグ欠落 ([`ValidationError::RequiredTagMissing`])、空値
+/// ([`ValidationError::TagWithoutValue`])、`MsgSeqNum` が正の整数でない
+/// ([`ValidationError::IncorrectDataFormat`])。
+pub fn validate(msg: &FixMessage) -> Result<(), ValidationError> {
+    if msg.msg_type.is_empty() {
+        return Err(ValidationError::InvalidMsgType);
+    }
+    for &t in REQUIRED_HEADER_TAGS
+        .iter()
+        .chain(required_body_tags(&msg.msg_type))
+    {
+        if !msg.contains(t) {
+            return Err(ValidationError::RequiredTagMissing { tag: t });
+        }
+    }
+    if let Some((t, _)) = msg.iter().find(|(_, v)| v.is_empty()) {
+        return Err(ValidationError::TagWithoutValue { tag: t });
+    }
+    msg.require_u64(tag::MSG_SEQ_NUM)
+        .ok()
+        .filter(|&seq| seq > 0)
+        .map(|_| ())
+        .ok_or_else(|| ValidationError::IncorrectDataFormat {
+            tag: tag::MSG_SEQ_NUM,
+            value: msg.get(tag::MSG_SEQ_NUM).unwrap_or_default().to_string(),
+        })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FixBuilder;
+    use crate::parser;
+    use crate::version::BeginString;
+
+    fn heartbeat(seq: &str) -> FixMessage {
+        let raw = FixBuilder::new(BeginString::Fix44, "0")
+            .field(tag::SENDER_COMP_ID, "BROKER")
+            .field(tag::TARGET_COMP_ID, "ALICE")
+            .field(tag::MSG_SEQ_NUM, seq)
+            .field(tag::SENDING_TIME, "20260101-00:00:00")
+            .build();
+        parser::parse(&raw).unwrap()
+    }
+
+    #[test]
+    fn valid_heartbeat_passes() {
+        assert_eq!(validate(&heartbeat("7")), Ok(()));
+    }
+
+    #[test]
+    fn detects_missing_and_malformed_tags() {
+        let mut msg = heartbeat("7");
+        msg.msg_type = "D".into();
+        assert_eq!(
+            validate(&msg),
+            Err(ValidationError::RequiredTagMissing {
+                tag: tag::CL_ORD_ID
+            })
+        );
+        assert_eq!(
+            validate(&heartbeat("x")),
+            Err(ValidationError::IncorrectDataFormat {
+                tag: tag::MSG_SEQ_NUM,
+                value: "x".into()
+            })
+        );
+        let mut msg = heartbeat("7");
+        msg.set(tag::TEXT, "");
+        assert_eq!(
+            validate(&msg),
+            Err(ValidationError::TagWithoutValue { tag: tag::TEXT })
+        );
+    }
+
+    #[test]
+    fn reason_codes_and_conversions() {
+        let err = ValidationError::from(FieldError::MissingTag {
+            tag: tag::SYMBOL,
+            msg_type: "D".into(),
+        });
+        assert_eq!(err.reason().code(), 1);
+        assert_eq!(err.tag(), Some(tag::SYMBOL));
+        assert_eq!(ValidationError::InvalidMsgType.reason().code(), 11);
+        assert_eq!(
+            ValidationError::IncorrectNumInGroupCount { tag: 453 }
+                .reason()
+                .code(),
+            16
+        );
+    }
+}
@@ -0,0 +1,733 @@
+//! Typed FIX field values
+//!
+//! [`FixValue`] はフィールド値を自然な型のまま保持し、シリアライズ時にのみ
+//! FIX 文字列表現 (Decimal は指数表記なし、Bool は Y/N、Timestamp は
+//! `UTCTimestamp`) へ変換する。
+//!
+//! `MultipleStringValue` / `MultipleCharValue` (例: `ExecInst` 18) は
+//! [`FixValue::Multiple`] で保持し、空白区切りで連結する。
+//! 読み取りは [`split_multiple`]。
+//!
+//! 数値のパース ([`parse_u64_bytes`], [`parse_i64_bytes`],
+//! [`Decimal::parse_bytes`], [`parse_utc_timestamp_bytes`]) はバイト列から
+//! 直接行い、UTF-8 検証や部分文字列の `str::parse` を経由しない。
+
+use core::fmt;
+
+/// 1 秒あたりのナノ秒数。
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+/// 1 日あたりの秒数。
+const SECS_PER_DAY: u64 = 86_400;
+
+/// 固定小数点 10 進数 (`mantissa × 10^-scale`)。
+///
+/// 比較・順序は数値として行う (`1.50 == 1.5`)。表示は `scale` 桁を保持する。
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decimal {
+    mantissa: i64,
+    scale: u32,
+}
+
+impl Decimal {
+    /// 仮数部とスケールから作成。
+    #[must_use]
+    pub const fn new(mantissa: i64, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// 仮数部。
+    #[must_use]
+    pub const fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    /// 小数点以下の桁数。
+    #[must_use]
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// FIX 10 進文字列 (例: `"-12.340"`) をパース。
+    ///
+    /// 指数表記、空文字列、`i64` に収まらない値は `None`。
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::parse_bytes(s.as_bytes())
+    }
+
+    /// [`Self::parse`] のバイト列版。
+    #[must_use]
+    pub fn parse_bytes(b: &[u8]) -> Option<Self> {
+        let (negative, digits) = match b.first()? {
+            b'-' => (true, &b[1..]),
+            b'+' => (false, &b[1..]),
+            _ => (false, b),
+        };
+        let (int_part, frac_part) = digits
+            .iter()
+            .position(|&c| c == b'.')
+            .map_or((digits, &[][..]), |i| (&digits[..i], &digits[i + 1..]));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        let mut mantissa: i64 = 0;
+        for &c in int_part.iter().chain(frac_part) {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            mantissa = mantissa.checked_mul(10)?.checked_add(i64::from(c - b'0'))?;
+        }
+        let scale = u32::try_from(frac_part.len()).ok()?;
+        Some(Self::new(
+            if negative { -mantissa } else { mantissa },
+            scale,
+        ))
+    }
+
+    /// `f64` を小数点以下 `scale` 桁に丸めて作成 (最近接、浮動小数点の
+    /// 正確な 2 進値に基づく)。
+    ///
+    /// 非有限値、`i64` に収まらない値は `None`。
+    #[must_use]
+    pub fn from_f64(value: f64, scale: u32) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        Self::parse(&format!("{value:.prec$}", prec = scale as usize))
+    }
+
+    /// スケールを `scale` 桁に揃える。桁を減らす場合は四捨五入
+    /// (0.5 は 0 から遠い方へ)。
+    ///
+    /// 桁を増やして `i64` からあふれる場合は `None`。
+    #[must_use]
+    pub fn rescale(&self, scale: u32) -> Option<Self> {
+        if scale >= self.scale {
+            let factor = 10i64.checked_pow(scale - self.scale)?;
+            return self
+                .mantissa
+                .checked_mul(factor)
+                .map(|m| Self::new(m, scale));
+        }
+        let Some(div) = 10i64.checked_pow(self.scale - scale) else {
+            return Some(Self::new(0, scale));
+        };
+        let (q, r) = (self.mantissa / div, self.mantissa % div);
+        let round = if r.unsigned_abs() * 2 >= div.unsigned_abs() {
+            self.mantissa.signum()
+        } else {
+            0
+        };
+        Some(Self::new(q + round, scale))
+    }
+
+    /// 末尾のゼロを除去した値 (`1.500` → `1.5`)。
+    #[must_use]
+    pub const fn trimmed(&self) -> Self {
+        let (m, s) = self.normalized();
+        Self::new(m, s)
+    }
+
+    /// 末尾のゼロを除去した (仮数部, スケール)。
+    const fn normalized(&self) -> (i64, u32) {
+        let (mut m, mut s) = (self.mantissa, self.scale);
+        while s > 0 && m % 10 == 0 {
+            m /= 10;
+            s -= 1;
+        }
+        (m, s)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let (am, as_) = self.normalized();
+        let (bm, bs) = other.normalized();
+        let scale = as_.max(bs);
+        let lift = |m: i64, s: u32| {
+            if m == 0 {
+                return Some(0);
+            }
+            10i128
+                .checked_pow(scale - s)
+                .and_then(|p| i128::from(m).checked_mul(p))
+        };
+        match (lift(am, as_), lift(bm, bs)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // 桁あふれした側は絶対値が他方より大きい
+            (None, _) => 0.cmp(&am).reverse(),
+            (_, None) => 0.cmp(&bm),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let abs = self.mantissa.unsigned_abs();
+        if self.scale == 0 {
+            return write!(f, "{sign}{abs}");
+        }
+        let width = self.scale as usize;
+        // スケールが u64 の桁数を超える場合は整数部が常に 0。
+        let (int_part, frac_part) = 10u64
+            .checked_pow(self.scale)
+            .map_or((0, abs), |div| (abs / div, abs % div));
+        write!(f, "{sign}{int_part}.{frac_part:0width$}")
+    }
+}
+
+/// 型付き FIX フィールド値。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FixValue {
+    /// 文字列。
+    Str(String),
+    /// 符号付き整数。
+    Int(i64),
+    /// 符号なし整数 (`SeqNum`、数量など `i64` を超え得る値)。
+    UInt(u64),
+    /// 10 進数。
+    Decimal(Decimal),
+    /// 真偽値 (`Y` / `N`)。
+    Bool(bool),
+    /// UTC タイムスタンプ (Unix エポックからのナノ秒)。
+    Timestamp(u64),
+    /// 生バイト列 (SOH を含み得る)。
+    Data(Vec<u8>),
+    /// 空白区切りの複数値 (`MultipleStringValue` / `MultipleCharValue`)。
+    Multiple(Vec<String>),
+}
+
+impl FixValue {
+    /// ワイヤ表現を `buf` に追記。
+    pub fn write_to(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Str(s) => buf.extend_from_slice(s.as_bytes()),
+            Self::Data(d) => buf.extend_from_slice(d),
+            Self::Bool(b) => buf.push(if *b { b'Y' } else { b'N' }),
+            other => buf.extend_from_slice(other.to_string().as_bytes()),
+        }
+    }
+
+    /// `Str` の場合のみ文字列スライスを返す。
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FixValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => f.write_str(s),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::UInt(v) => write!(f, "{v}"),
+            Self::Decimal(d) => write!(f, "{d}"),
+            Self::Bool(b) => f.write_str(if *b { "Y" } else { "N" }),
+            Self::Timestamp(ns) => f.write_str(&format_utc_timestamp(*ns)),
+            Self::Data(d) => f.write_str(&String::from_utf8_lossy(d)),
+            Self::Multiple(values) => {
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    f.write_str(v)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<&str> for FixValue {
+    fn from(v: &str) -> Self {
+        Self::Str(v.to_string())
+    }
+}
+
+impl From<String> for FixValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl From<i64> for FixValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<u64> for FixValue {
+    fn from(v: u64) -> Self {
+        Self::UInt(v)
+    }
+}
+
+impl From<bool> for FixValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<Decimal> for FixValue {
+    fn from(v: Decimal) -> Self {
+        Self::Decimal(v)
+    }
+}
+
+impl From<Vec<u8>> for FixValue {
+    fn from(v: Vec<u8>) -> Self {
+        Self::Data(v)
+    }
+}
+
+impl From<&[&str]> for FixValue {
+    fn from(v: &[&str]) -> Self {
+        Self::Multiple(v.iter().map(|&s| s.to_string()).collect())
+    }
+}
+
+impl From<Vec<String>> for FixValue {
+    fn from(v: Vec<String>) -> Self {
+        Self::Multiple(v)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Multiple values
+// ---------------------------------------------------------------------------
+
+/// `MultipleStringValue` / `MultipleCharValue` を空白で分割。
+///
+/// 連続・前後の空白は無視する。
+pub fn split_multiple(s: &str) -> impl Iterator<Item = &str> {
+    s.split(' ').filter(|v| !v.is_empty())
+}
+
+// ---------------------------------------------------------------------------
+// Integers
+// ---------------------------------------------------------------------------
+
+/// 10 進数字のみの列を `u64` に変換 (空・非数字・桁あふれは `None`)。
+const fn parse_digits(b: &[u8]) -> Option<u64> {
+    if b.is_empty() {
+        return None;
+    }
+    let mut n: u64 = 0;
+    let mut i = 0;
+    while i < b.len() {
+        let c = b[i];
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        n = match n.checked_mul(10) {
+            Some(v) => match v.checked_add((c - b'0') as u64) {
+                Some(v) => v,
+                None => return None,
+            },
+            None => return None,
+        };
+        i += 1;
+    }
+    Some(n)
+}
+
+/// 符号なし整数をバイト列から直接パース。
+///
+/// 受理範囲は `str::parse::<u64>` と同じ (先頭の `+` は可)。
+#[must_use]
+pub const fn parse_u64_bytes(b: &[u8]) -> Option<u64> {
+    match b {
+        [b'+', rest @ ..] => parse_digits(rest),
+        _ => parse_digits(b),
+    }
+}
+
+/// 符号付き整数をバイト列から直接パース。
+///
+/// 受理範囲は `str::parse::<i64>` と同じ (`i64::MIN` を含む)。
+#[must_use]
+pub const fn parse_i64_bytes(b: &[u8]) -> Option<i64> {
+    let (negative, digits) = match b {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        _ => (false, b),
+    };
+    let Some(n) = parse_digits(digits) else {
+        return None;
+    };
+    if negative {
+        if n > i64::MIN.unsigned_abs() {
+            None
+        } else {
+            Some(0i64.wrapping_sub_unsigned(n))
+        }
+    } else if n > i64::MAX as u64 {
+        None
+    } else {
+        Some(n as i64)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Booleans
+// ---------------------------------------------------------------------------
+
+/// FIX Boolean (`Y` / `N`) をパース。
+#[must_use]
+pub fn parse_bool(s: &str) -> Option<bool> {
+    match s {
+        "Y" => Some(true),
+        "N" => Some(false),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// UTCTimestamp
+// ---------------------------------------------------------------------------
+
+/// `UTCTimestamp` の小数秒の桁数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPrecision {
+    /// 秒 (`HH:MM:SS`)。
+    Seconds,
+    /// ミリ秒 (`.sss`)。
+    #[default]
+    Millis,
+    /// マイクロ秒 (`.ssssss`)。
+    Micros,
+    /// ナノ秒 (`.sssssssss`)。
+    Nanos,
+}
+
+impl TimestampPrecision {
+    /// 小数秒の桁数。
+    #[must_use]
+    pub const fn digits(self) -> u32 {
+        match self {
+            Self::Seconds => 0,
+            Self::Millis => 3,
+            Self::Micros => 6,
+            Self::Nanos => 9,
+        }
+    }
+}
+
+/// Unix エポックからのナノ秒を `YYYYMMDD-HH:MM:SS.sss` 形式に変換。
+#[must_use]
+pub fn format_utc_timestamp(nanos: u64) -> String {
+    format_utc_timestamp_with(nanos, TimestampPrecision::Millis)
+}
+
+/// Unix エポックからのナノ秒を指定精度の `UTCTimestamp` に変換 (切り捨て)。
+#[must_use]
+pub fn format_utc_timestamp_with(nanos: u64, precision: TimestampPrecision) -> String {
+    use fmt::Write as _;
+
+    let secs = nanos / NANOS_PER_SEC;
+    let (y, m, d) = civil_from_days((secs / SECS_PER_DAY) as i64);
+    let sod = secs % SECS_PER_DAY;
+    let mut out = format!(
+        "{y:04}{m:02}{d:02}-{:02}:{:02}:{:02}",
+        sod / 3600,
+        (sod % 3600) / 60,
+        sod % 60
+    );
+    let digits = precision.digits();
+    if digits > 0 {
+        let frac = (nanos % NANOS_PER_SEC) / 10u64.pow(9 - digits);
+        let _ = write!(out, ".{frac:0width$}", width = digits as usize);
+    }
+    out
+}
+
+/// `YYYYMMDD-HH:MM:SS[.f{1,9}]` 形式を Unix エポックからのナノ秒に変換。
+///
+/// 形式不正またはエポック以前の日時は `None`。
+#[must_use]
+pub fn parse_utc_timestamp(s: &str) -> Option<u64> {
+    parse_utc_timestamp_bytes(s.as_bytes())
+}
+
+/// [`parse_utc_timestamp`] のバイト列版。
+#[must_use]
+pub fn parse_utc_timestamp_bytes(b: &[u8]) -> Option<u64> {
+    if b.len() < 17 || b[8] != b'-' || b[11] != b':' || b[14] != b':' {
+        return None;
+    }
+    let num = |r: core::ops::Range<usize>| parse_digits(&b[r]);
+    let (y, m, d) = (num(0..4)?, num(4..6)?, num(6..8)?);
+    let (hh, mm, ss) = (num(9..11)?, num(12..14)?, num(15..17)?);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) || hh > 23 || mm > 59 || ss > 60 {
+        return None;
+    }
+    let frac_nanos = match &b[17..] {
+        [] => 0,
+        [b'.', digits @ ..] if (1..=9).contains(&digits.len()) => {
+            parse_digits(digits)? * 10u64.pow(9 - digits.len() as u32)
+        }
+        _ => return None,
+    };
+    let days = u64::try_from(days_from_civil(y as i64, m, d)).ok()?;
+    let secs = days * SECS_PER_DAY + hh * 3600 + mm * 60 + ss;
+    secs.checked_mul(NANOS_PER_SEC)?.checked_add(frac_nanos)
+}
+
+/// エポックからの日数 → (年, 月, 日)。
+const fn civil_from_days(z: i64) -> (i64, u64, u64) {
+    let z = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let y = yoe + era * 400 + (m <= 2) as i64;
+    (y, m, d)
+}
+
+/// (年, 月, 日) → エポックからの日数。
+const fn days_from_civil(y: i64, m: u64, d: u64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-01-01T00:00:00Z のエポック秒。
+    const JAN_1_2026: u64 = 1_767_225_600;
+
+    #[test]
+    fn decimal_display() {
+        assert_eq!(Decimal::new(12_345, 2).to_string(), "123.45");
+        assert_eq!(Decimal::new(-5, 3).to_string(), "-0.005");
+        assert_eq!(Decimal::new(100, 0).to_string(), "100");
+        assert_eq!(Decimal::new(1500, 3).to_string(), "1.500");
+    }
+
+    #[test]
+    fn decimal_parse() {
+        assert_eq!(Decimal::parse("123.45"), Some(Decimal::new(12_345, 2)));
+        assert_eq!(Decimal::parse("-0.5"), Some(Decimal::new(-5, 1)));
+        assert_eq!(Decimal::parse("42"), Some(Decimal::new(42, 0)));
+        assert_eq!(Decimal::parse(".5"), Some(Decimal::new(5, 1)));
+        assert_eq!(Decimal::parse("1e5"), None);
+        assert_eq!(Decimal::parse(""), None);
+        assert_eq!(Decimal::parse("."), None);
+    }
+
+    #[test]
+    fn decimal_numeric_equality() {
+        assert_eq!(Decimal::new(150, 2), Decimal::new(15, 1));
+        assert_ne!(Decimal::new(150, 2), Decimal::new(15, 2));
+        assert_eq!(Decimal::new(0, 4), Decimal::new(0, 0));
+    }
+
+    #[test]
+    fn decimal_numeric_ordering() {
+        assert!(Decimal::new(15, 1) < Decimal::new(151, 2));
+        assert!(Decimal::new(-1, 0) < Decimal::new(0, 3));
+        assert_eq!(
+            Decimal::new(150, 2).cmp(&Decimal::new(15, 1)),
+            core::cmp::Ordering::Equal
+        );
+        // スケール差で i128 に収まらない場合も符号と桁で決まる
+        assert!(Decimal::new(1, 40) < Decimal::new(1, 0));
+        assert!(Decimal::new(-1, 40) > Decimal::new(-1, 0));
+        assert!(Decimal::new(i64::MAX, 0) > Decimal::new(1, 30));
+    }
+
+    #[test]
+    fn value_rendering() {
+        let render = |v: FixValue| {
+            let mut buf = Vec::new();
+            v.write_to(&mut buf);
+            String::from_utf8(buf).unwrap()
+        };
+        assert_eq!(render(FixValue::from("abc")), "abc");
+        assert_eq!(render(FixValue::from(-7i64)), "-7");
+        assert_eq!(render(FixValue::from(u64::MAX)), u64::MAX.to_string());
+        assert_eq!(render(FixValue::from(true)), "Y");
+        assert_eq!(render(FixValue::from(false)), "N");
+        assert_eq!(render(FixValue::Decimal(Decimal::new(5, 2))), "0.05");
+        assert_eq!(
+            render(FixValue::Timestamp(JAN_1_2026 * NANOS_PER_SEC)),
+            "20260101-00:00:00.000"
+        );
+        assert_eq!(render(FixValue::from(vec![b'a', 0x01, b'b'])), "a\x01b");
+        assert_eq!(render(FixValue::from(&["6", "G"][..])), "6 G");
+        assert_eq!(render(FixValue::Multiple(Vec::new())), "");
+    }
+
+    #[test]
+    fn multiple_split() {
+        assert_eq!(
+            split_multiple("6 G  E ").collect::<Vec<_>>(),
+            ["6", "G", "E"]
+        );
+        assert_eq!(split_multiple("").count(), 0);
+    }
+
+    #[test]
+    fn value_as_str() {
+        assert_eq!(FixValue::from("x").as_str(), Some("x"));
+        assert_eq!(FixValue::Int(1).as_str(), None);
+    }
+
+    #[test]
+    fn parse_bool_values() {
+        assert_eq!(parse_bool("Y"), Some(true));
+        assert_eq!(parse_bool("N"), Some(false));
+        assert_eq!(parse_bool("y"), None);
+    }
+
+    #[test]
+    fn timestamp_format() {
+        let ns = (JAN_1_2026 + 12 * 3600 + 34 * 60 + 56) * NANOS_PER_SEC + 789_000_000;
+        assert_eq!(format_utc_timestamp(ns), "20260101-12:34:56.789");
+        assert_eq!(format_utc_timestamp(0), "19700101-00:00:00.000");
+    }
+
+    #[test]
+    fn decimal_rescale_and_from_f64() {
+        let d = Decimal::new(12_345, 3); // 12.345
+        assert_eq!(d.rescale(2).unwrap().to_string(), "12.35");
+        assert_eq!(d.rescale(5).unwrap().to_string(), "12.34500");
+        assert_eq!(
+            Decimal::new(-125, 2).rescale(1).unwrap().to_string(),
+            "-1.3"
+        );
+        assert_eq!(Decimal::new(-124, 2).rescale(0).unwrap().to_string(), "-1");
+        assert_eq!(Decimal::new(5, 25).rescale(0), Some(Decimal::new(0, 0)));
+        assert_eq!(Decimal::new(i64::MAX, 0).rescale(1), None);
+        assert_eq!(Decimal::new(1500, 3).trimmed().to_string(), "1.5");
+
+        assert_eq!(Decimal::from_f64(0.1 + 0.2, 2).unwrap().to_string(), "0.30");
+        assert_eq!(Decimal::from_f64(50_000.0, 0).unwrap().to_string(), "50000");
+        assert_eq!(Decimal::from_f64(-0.0001, 2).unwrap().to_string(), "0.00");
+        assert_eq!(Decimal::from_f64(f64::NAN, 2), None);
+        assert_eq!(Decimal::from_f64(1e30, 0), None);
+    }
+
+    #[test]
+    fn timestamp_precisions() {
+        let ns = JAN_1_2026 * NANOS_PER_SEC + 123_456_789;
+        let with = |p| format_utc_timestamp_with(ns, p);
+        assert_eq!(with(TimestampPrecision::Seconds), "20260101-00:00:00");
+        assert_eq!(with(TimestampPrecision::Millis), "20260101-00:00:00.123");
+        assert_eq!(with(TimestampPrecision::Micros), "20260101-00:00:00.123456");
+        assert_eq!(
+            with(TimestampPrecision::Nanos),
+            "20260101-00:00:00.123456789"
+        );
+        assert_eq!(
+            parse_utc_timestamp(&with(TimestampPrecision::Nanos)),
+            Some(ns)
+        );
+    }
+
+    #[test]
+    fn timestamp_parse() {
+        assert_eq!(
+            parse_utc_timestamp("20260101-00:00:00"),
+            Some(JAN_1_2026 * NANOS_PER_SEC)
+        );
+        assert_eq!(
+            parse_utc_timestamp("20260101-00:00:00.5"),
+            Some(JAN_1_2026 * NANOS_PER_SEC + 500_000_000)
+        );
+        assert_eq!(
+            parse_utc_timestamp("20260101-00:00:00.000000001"),
+            Some(JAN_1_2026 * NANOS_PER_SEC + 1)
+        );
+        assert_eq!(parse_utc_timestamp("2026-01-01"), None);
+        assert_eq!(parse_utc_timestamp("20261301-00:00:00"), None);
+        assert_eq!(parse_utc_timestamp("20260101-00:00:00."), None);
+        assert_eq!(parse_utc_timestamp("20260101-00:00:00.0000000001"), None);
+    }
+
+    #[test]
+    fn timestamp_roundtrip_leap_day() {
+        let ns = parse_utc_timestamp("20240229-23:59:59.999").unwrap();
+        assert_eq!(format_utc_timestamp(ns), "20240229-23:59:59.999");
+    }
+
+    #[test]
+    fn integer_bytes_match_str_parse() {
+        for s in [
+            "0",
+            "42",
+            "+7",
+            "-7",
+            "-0",
+            "",
+            "+",
+            "-",
+            "1.5",
+            " 1",
+            "9223372036854775807",
+            "9223372036854775808",
+            "-9223372036854775808",
+            "-9223372036854775809",
+            "18446744073709551615",
+            "18446744073709551616",
+        ] {
+            assert_eq!(parse_u64_bytes(s.as_bytes()), s.parse().ok(), "{s:?}");
+            assert_eq!(parse_i64_bytes(s.as_bytes()), s.parse().ok(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn bytes_parsers_match_str_parsers() {
+        for s in ["12.340", "-0.5", ".5", "5.", "1e3", "", "-"] {
+            assert_eq!(
+                Decimal::parse_bytes(s.as_bytes()),
+                Decimal::parse(s),
+                "{s:?}"
+            );
+        }
+        assert_eq!(
+            parse_utc_timestamp_bytes(b"20260101-00:00:00.123456"),
+            Some(JAN_1_2026 * NANOS_PER_SEC + 123_456_000)
+        );
+        for bad in [
+            &b"20260101-00:00:00."[..],
+            b"20260101-00:00:00.1234567890",
+            b"2026010a-00:00:00",
+        ] {
+            assert_eq!(parse_utc_timestamp_bytes(bad), None);
+        }
+    }
+}
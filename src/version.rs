@@ -0,0 +1,511 @@
+//! FIX Version Conversion
+//!
+//! FIX 4.2 / 4.4 / 5.0 (FIXT.1.1) 間で主要メッセージを変換する。
+//! 変換は 4.4 を中継点として 1 段ずつ行う (4.2 ⇄ 4.4 ⇄ 5.0)。
+//!
+//! | 段階 | 変換内容 |
+//! |------|----------|
+//! | 4.2 → 4.4 | `ExecTransType` (20) 削除、`ExecType` 1/2 → F、`Rule80A` (47) → `OrderCapacity` (528、単一注文の A/I/P/W のみ) |
+//! | 4.4 → 4.2 | `ExecType` F → `OrdStatus` 由来の 1/2、`ExecTransType`=0 追加、`HandlInst` 補完、528 → 47 |
+//! | 4.4 → 5.0 | `BeginString` FIXT.1.1、アプリメッセージに `ApplVerID`、Logon に `DefaultApplVerID` |
+//! | 5.0 → 4.4 | `BeginString` FIX.4.4、`ApplVerID` / `DefaultApplVerID` 削除 |
+//!
+//! [`BeginString`] は `BeginString` (tag 8) の型付き表現。
+//! [`crate::builder::FixBuilder::new`] / [`crate::session::FixSession::new`]
+//! はこれを受け取るため、`"FIX4.4"` のような綴り誤りはコンパイル時に防げる。
+//! `FIXT.1.1` はアプリケーション層のバージョン (`ApplVerID`) を伴う。
+
+use crate::message::FixMessage;
+use crate::tag;
+
+/// 変換対象の FIX バージョン。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FixVersion {
+    /// FIX 4.2。
+    Fix42,
+    /// FIX 4.4。
+    Fix44,
+    /// FIX 5.0 (FIXT.1.1 トランスポート)。
+    Fix50,
+}
+
+impl FixVersion {
+    /// `BeginString` (tag 8)。
+    #[must_use]
+    pub const fn begin_string(self) -> &'static str {
+        match self {
+            Self::Fix42 => "FIX.4.2",
+            Self::Fix44 => "FIX.4.4",
+            Self::Fix50 => "FIXT.1.1",
+        }
+    }
+
+    /// `ApplVerID` (tag 1128) の値。
+    #[must_use]
+    pub const fn appl_ver_id(self) -> &'static str {
+        match self {
+            Self::Fix42 => "4",
+            Self::Fix44 => "6",
+            Self::Fix50 => "7",
+        }
+    }
+
+    /// `ApplVerID` (tag 1128) / `DefaultApplVerID` (tag 1137) の値から判定。
+    #[must_use]
+    pub fn from_appl_ver_id(id: &str) -> Option<Self> {
+        match id {
+            "4" => Some(Self::Fix42),
+            "6" => Some(Self::Fix44),
+            "7" => Some(Self::Fix50),
+            _ => None,
+        }
+    }
+
+    /// メッセージのバージョンを判定。
+    ///
+    /// FIXT.1.1 は `ApplVerID` があればその値、無ければ FIX 5.0 とみなす。
+    #[must_use]
+    pub fn of(msg: &FixMessage) -> Option<Self> {
+        match msg.begin_string.as_str() {
+            "FIX.4.2" => Some(Self::Fix42),
+            "FIX.4.4" => Some(Self::Fix44),
+            "FIXT.1.1" => match msg.get(tag::APPL_VER_ID) {
+                None | Some("7") => Some(Self::Fix50),
+                Some(_) => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// `BeginString` (tag 8)。
+///
+/// `FIXT.1.1` はアプリケーション層のバージョンを併せ持つ。
+/// [`crate::session::FixSession`] はこれを Logon の `DefaultApplVerID`
+/// (tag 1137) とアプリメッセージの `ApplVerID` (tag 1128) に載せる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BeginString {
+    /// `FIX.4.2`。
+    Fix42,
+    /// `FIX.4.4`。
+    Fix44,
+    /// `FIXT.1.1` トランスポート。
+    Fixt11 {
+        /// アプリケーション層のバージョン (`ApplVerID` / `DefaultApplVerID`)。
+        appl_ver: FixVersion,
+    },
+}
+
+impl BeginString {
+    /// ワイヤ表現。
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fix42 => "FIX.4.2",
+            Self::Fix44 => "FIX.4.4",
+            Self::Fixt11 { .. } => "FIXT.1.1",
+        }
+    }
+
+    /// アプリケーション層のバージョン。
+    #[must_use]
+    pub const fn version(self) -> FixVersion {
+        match self {
+            Self::Fix42 => FixVersion::Fix42,
+            Self::Fix44 => FixVersion::Fix44,
+            Self::Fixt11 { appl_ver } => appl_ver,
+        }
+    }
+
+    /// FIXT.1.1 の場合の `ApplVerID` (tag 1128) の値。
+    #[must_use]
+    pub const fn appl_ver_id(self) -> Option<&'static str> {
+        match self {
+            Self::Fixt11 { appl_ver } => Some(appl_ver.appl_ver_id()),
+            Self::Fix42 | Self::Fix44 => None,
+        }
+    }
+
+    /// ワイヤ表現と `DefaultApplVerID` (tag 1137) の値から組み立てる。
+    ///
+    /// `FIXT.1.1` は `appl_ver_id` が必須。FIX 4.x では無視する。
+    ///
+    /// # Errors
+    ///
+    /// 未対応の `BeginString` / `ApplVerID` の場合
+    /// [`VersionError::UnsupportedVersion`]、`FIXT.1.1` で `appl_ver_id`
+    /// が無い場合 [`VersionError::MissingField`]。
+    pub fn from_parts(begin_string: &str, appl_ver_id: Option<&str>) -> Result<Self, VersionError> {
+        match begin_string {
+            "FIX.4.2" => Ok(Self::Fix42),
+            "FIX.4.4" => Ok(Self::Fix44),
+            "FIXT.1.1" => {
+                let id = appl_ver_id.ok_or(VersionError::MissingField(tag::DEFAULT_APPL_VER_ID))?;
+                let appl_ver = FixVersion::from_appl_ver_id(id)
+                    .ok_or_else(|| VersionError::UnsupportedVersion(format!("ApplVerID {id}")))?;
+                Ok(Self::Fixt11 { appl_ver })
+            }
+            _ => Err(VersionError::UnsupportedVersion(begin_string.to_string())),
+        }
+    }
+}
+
+impl From<FixVersion> for BeginString {
+    /// FIX 5.0 は `FIXT.1.1` 上で運ぶ。
+    fn from(version: FixVersion) -> Self {
+        match version {
+            FixVersion::Fix42 => Self::Fix42,
+            FixVersion::Fix44 => Self::Fix44,
+            FixVersion::Fix50 => Self::Fixt11 {
+                appl_ver: FixVersion::Fix50,
+            },
+        }
+    }
+}
+
+impl core::str::FromStr for BeginString {
+    type Err = VersionError;
+
+    /// `FIXT.1.1` はアプリケーション層が決まらないため
+    /// [`VersionError::MissingField`] になる。[`BeginString::from_parts`]
+    /// で `DefaultApplVerID` と併せて渡す。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_parts(s, None)
+    }
+}
+
+impl core::fmt::Display for BeginString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for BeginString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// バージョン変換エラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionError {
+    /// 変換元のバージョンが未対応。
+    UnsupportedVersion(String),
+    /// 変換に必要なフィールドが欠落。
+    MissingField(u32),
+}
+
+impl core::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported FIX version: {v}"),
+            Self::MissingField(tag) => write!(f, "Missing required field: tag {tag}"),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+/// メッセージを `to` のバージョン表現に変換。
+///
+/// # Errors
+///
+/// 変換元のバージョンが判定できない場合、4.2 へのダウングレードで
+/// `ExecType`=F の `OrdStatus` が欠落している場合。
+pub fn convert(msg: &FixMessage, to: FixVersion) -> Result<FixMessage, VersionError> {
+    let mut from = FixVersion::of(msg)
+        .ok_or_else(|| VersionError::UnsupportedVersion(msg.begin_string.clone()))?;
+    let mut out = msg.clone();
+    while from != to {
+        let next = match from {
+            FixVersion::Fix44 if to > from => FixVersion::Fix50,
+            FixVersion::Fix44 => FixVersion::Fix42,
+            FixVersion::Fix42 | FixVersion::Fix50 => FixVersion::Fix44,
+        };
+        step(&mut out, from, next)?;
+        out.begin_string = next.begin_string().to_string();
+        from = next;
+    }
+    Ok(out)
+}
+
+fn step(msg: &mut FixMessage, from: FixVersion, to: FixVersion) -> Result<(), VersionError> {
+    match (from, to) {
+        (FixVersion::Fix42, FixVersion::Fix44) => {
+            upgrade_42_to_44(msg);
+            Ok(())
+        }
+        (FixVersion::Fix44, FixVersion::Fix42) => downgrade_44_to_42(msg),
+        (FixVersion::Fix44, FixVersion::Fix50) => {
+            if is_admin(&msg.msg_type) {
+                if msg.msg_type == "A" {
+                    msg.set(tag::DEFAULT_APPL_VER_ID, FixVersion::Fix50.appl_ver_id());
+                }
+            } else {
+                msg.set(tag::APPL_VER_ID, FixVersion::Fix50.appl_ver_id());
+            }
+            Ok(())
+        }
+        _ => {
+            msg.remove(tag::APPL_VER_ID);
+            msg.remove(tag::DEFAULT_APPL_VER_ID);
+            Ok(())
+        }
+    }
+}
+
+fn upgrade_42_to_44(msg: &mut FixMessage) {
+    msg.remove(tag::EXEC_TRANS_TYPE);
+    if msg.msg_type == "8" && matches!(msg.get(tag::EXEC_TYPE), Some("1" | "2")) {
+        msg.set(tag::EXEC_TYPE, "F");
+    }
+    if let Some(rule) = msg.remove(tag::RULE_80A) {
+        if let Some(cap) = rule_80a_to_capacity(&rule) {
+            msg.set(tag::ORDER_CAPACITY, cap);
+        }
+    }
+}
+
+fn downgrade_44_to_42(msg: &mut FixMessage) -> Result<(), VersionError> {
+    if msg.msg_type == "8" {
+        if msg.get(tag::EXEC_TYPE) == Some("F") {
+            let exec_type = match msg.get(tag::ORD_STATUS) {
+                Some("2") => "2",
+                Some(_) => "1",
+                None => return Err(VersionError::MissingField(tag::ORD_STATUS)),
+            };
+            msg.set(tag::EXEC_TYPE, exec_type);
+        }
+        if !msg.contains(tag::EXEC_TRANS_TYPE) {
+            msg.set(tag::EXEC_TRANS_TYPE, "0");
+        }
+    }
+    if msg.msg_type == "D" && !msg.contains(tag::HANDL_INST) {
+        // 自動執行 (ブローカー介入なし)
+        msg.set(tag::HANDL_INST, "1");
+    }
+    if let Some(cap) = msg.remove(tag::ORDER_CAPACITY) {
+        if let Some(rule) = capacity_to_rule_80a(&cap) {
+            msg.set(tag::RULE_80A, rule);
+        }
+    }
+    Ok(())
+}
+
+/// セッション管理メッセージか (`ApplVerID` を付けない)。
+fn is_admin(msg_type: &str) -> bool {
+    matches!(msg_type, "0" | "1" | "2" | "3" | "4" | "5" | "A")
+}
+
+/// FIX 4.3 の `Rule80A` → `OrderCapacity` 対応表のうち単一注文の区分だけ。
+/// プログラム売買・空売り免除・マーケットメイカー区分は `OrderRestrictions`
+/// や `Side` と組で表されるため写さない (`R` も非会員マーケットメイカーの
+/// 意味で、`OrderCapacity` の `R` = Riskless Principal とは別物)。
+fn rule_80a_to_capacity(rule: &str) -> Option<&'static str> {
+    match rule {
+        "A" => Some("A"),
+        "I" => Some("I"),
+        "P" => Some("P"),
+        "W" => Some("W"),
+        _ => None,
+    }
+}
+
+/// [`rule_80a_to_capacity`] の逆。Riskless Principal (`R`) に当たる
+/// `Rule80A` はないので写さない。
+fn capacity_to_rule_80a(cap: &str) -> Option<&'static str> {
+    match cap {
+        "A" => Some("A"),
+        "G" | "P" => Some("P"),
+        "I" => Some("I"),
+        "W" => Some("W"),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec_report(begin_string: &str) -> FixMessage {
+        let mut msg = FixMessage::new(begin_string, "8");
+        msg.set(tag::ORDER_ID, "O1")
+            .set(tag::EXEC_ID, "E1")
+            .set(tag::EXEC_TYPE, "F")
+            .set(tag::ORD_STATUS, "1")
+            .set(tag::SYMBOL, "BTCUSD")
+            .set(tag::SIDE, "1");
+        msg
+    }
+
+    #[test]
+    fn version_detection() {
+        assert_eq!(
+            FixVersion::of(&FixMessage::new("FIX.4.2", "D")),
+            Some(FixVersion::Fix42)
+        );
+        assert_eq!(
+            FixVersion::of(&FixMessage::new("FIXT.1.1", "D")),
+            Some(FixVersion::Fix50)
+        );
+        let mut sp2 = FixMessage::new("FIXT.1.1", "D");
+        sp2.set(tag::APPL_VER_ID, "9");
+        assert_eq!(FixVersion::of(&sp2), None);
+        assert_eq!(FixVersion::of(&FixMessage::new("FIX.4.0", "D")), None);
+    }
+
+    #[test]
+    fn exec_report_50_to_44() {
+        let mut msg = exec_report("FIXT.1.1");
+        msg.set(tag::APPL_VER_ID, "7");
+        let out = convert(&msg, FixVersion::Fix44).unwrap();
+        assert_eq!(out.begin_string, "FIX.4.4");
+        assert!(!out.contains(tag::APPL_VER_ID));
+        assert_eq!(out.get(tag::EXEC_TYPE), Some("F"));
+    }
+
+    #[test]
+    fn exec_report_50_to_42_derives_exec_type() {
+        let out = convert(&exec_report("FIXT.1.1"), FixVersion::Fix42).unwrap();
+        assert_eq!(out.begin_string, "FIX.4.2");
+        assert_eq!(out.get(tag::EXEC_TYPE), Some("1"));
+        assert_eq!(out.get(tag::EXEC_TRANS_TYPE), Some("0"));
+
+        let mut filled = exec_report("FIX.4.4");
+        filled.set(tag::ORD_STATUS, "2");
+        let out = convert(&filled, FixVersion::Fix42).unwrap();
+        assert_eq!(out.get(tag::EXEC_TYPE), Some("2"));
+    }
+
+    #[test]
+    fn exec_report_42_to_50() {
+        let mut msg = exec_report("FIX.4.2");
+        msg.set(tag::EXEC_TYPE, "2").set(tag::EXEC_TRANS_TYPE, "0");
+        let out = convert(&msg, FixVersion::Fix50).unwrap();
+        assert_eq!(out.begin_string, "FIXT.1.1");
+        assert_eq!(out.get(tag::APPL_VER_ID), Some("7"));
+        assert_eq!(out.get(tag::EXEC_TYPE), Some("F"));
+        assert!(!out.contains(tag::EXEC_TRANS_TYPE));
+    }
+
+    #[test]
+    fn downgrade_requires_ord_status() {
+        let mut msg = exec_report("FIX.4.4");
+        msg.remove(tag::ORD_STATUS);
+        assert_eq!(
+            convert(&msg, FixVersion::Fix42),
+            Err(VersionError::MissingField(tag::ORD_STATUS))
+        );
+    }
+
+    #[test]
+    fn order_capacity_and_handl_inst() {
+        let mut nos = FixMessage::new("FIX.4.4", "D");
+        nos.set(tag::ORDER_CAPACITY, "G");
+        let old = convert(&nos, FixVersion::Fix42).unwrap();
+        assert_eq!(old.get(tag::RULE_80A), Some("P"));
+        assert_eq!(old.get(tag::HANDL_INST), Some("1"));
+        assert!(!old.contains(tag::ORDER_CAPACITY));
+
+        let back = convert(&old, FixVersion::Fix44).unwrap();
+        assert_eq!(back.get(tag::ORDER_CAPACITY), Some("P"));
+        assert!(!back.contains(tag::RULE_80A));
+    }
+
+    #[test]
+    fn capacity_codes_without_a_counterpart_are_dropped() {
+        // OrderCapacity R (Riskless Principal) と Rule80A R (非会員
+        // マーケットメイカー) は別物
+        let mut nos = FixMessage::new("FIX.4.4", "D");
+        nos.set(tag::ORDER_CAPACITY, "R");
+        let old = convert(&nos, FixVersion::Fix42).unwrap();
+        assert!(!old.contains(tag::RULE_80A));
+        assert!(!old.contains(tag::ORDER_CAPACITY));
+
+        for rule in ["R", "C", "E"] {
+            let mut nos = FixMessage::new("FIX.4.2", "D");
+            nos.set(tag::RULE_80A, rule);
+            let new = convert(&nos, FixVersion::Fix44).unwrap();
+            assert!(!new.contains(tag::ORDER_CAPACITY), "{rule}");
+            assert!(!new.contains(tag::RULE_80A), "{rule}");
+        }
+    }
+
+    #[test]
+    fn logon_gets_default_appl_ver_id() {
+        let logon = FixMessage::new("FIX.4.4", "A");
+        let out = convert(&logon, FixVersion::Fix50).unwrap();
+        assert_eq!(out.get(tag::DEFAULT_APPL_VER_ID), Some("7"));
+        assert!(!out.contains(tag::APPL_VER_ID));
+        let back = convert(&out, FixVersion::Fix44).unwrap();
+        assert!(back.is_empty());
+    }
+
+    #[test]
+    fn same_version_is_identity() {
+        let msg = exec_report("FIX.4.4");
+        assert_eq!(convert(&msg, FixVersion::Fix44).unwrap(), msg);
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert_eq!(
+            convert(&FixMessage::new("FIX.4.0", "D"), FixVersion::Fix44),
+            Err(VersionError::UnsupportedVersion("FIX.4.0".into()))
+        );
+    }
+
+    #[test]
+    fn begin_string_round_trip() {
+        for bs in [BeginString::Fix42, BeginString::Fix44] {
+            assert_eq!(bs.as_str().parse::<BeginString>(), Ok(bs));
+            assert_eq!(bs.to_string(), bs.as_str());
+        }
+        assert_eq!(
+            "FIXT.1.1".parse::<BeginString>(),
+            Err(VersionError::MissingField(tag::DEFAULT_APPL_VER_ID))
+        );
+        for appl_ver in [FixVersion::Fix42, FixVersion::Fix44, FixVersion::Fix50] {
+            let bs = BeginString::Fixt11 { appl_ver };
+            assert_eq!(
+                BeginString::from_parts(bs.as_str(), bs.appl_ver_id()),
+                Ok(bs)
+            );
+        }
+        assert_eq!(
+            BeginString::from_parts("FIXT.1.1", Some("9")),
+            Err(VersionError::UnsupportedVersion("ApplVerID 9".into()))
+        );
+        assert_eq!(
+            BeginString::from_parts("FIX.4.4", Some("7")),
+            Ok(BeginString::Fix44)
+        );
+        assert_eq!(
+            "FIX4.4".parse::<BeginString>(),
+            Err(VersionError::UnsupportedVersion("FIX4.4".into()))
+        );
+        let fixt = BeginString::Fixt11 {
+            appl_ver: FixVersion::Fix44,
+        };
+        assert_eq!(fixt.version(), FixVersion::Fix44);
+        assert_eq!(fixt.appl_ver_id(), Some("6"));
+        assert_eq!(BeginString::Fix44.appl_ver_id(), None);
+        assert_eq!(
+            BeginString::from(FixVersion::Fix50).as_str(),
+            FixVersion::Fix50.begin_string()
+        );
+
+        let msg =
+            crate::parser::parse(&crate::builder::FixBuilder::new(BeginString::Fix42, "0").build())
+                .unwrap();
+        assert_eq!(msg.begin_string, "FIX.4.2");
+        let session = crate::session::FixSession::new("ALICE", "BROKER", BeginString::Fix44);
+        assert_eq!(session.session_id().begin_string, "FIX.4.4");
+    }
+}